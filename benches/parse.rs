@@ -0,0 +1,79 @@
+//! Decoding cost per report for a simple gamepad: the general descriptor parser versus the
+//! fixed-offset fast path. One iteration decodes 1000 reports, i.e. one second of input from
+//! a 1 kHz pad.
+//!
+//! Run with `cargo bench --bench parse`. The Windows HIDP path needs a real device and is not
+//! covered, so these numbers say nothing about the fast path versus `HidP_*` decoding.
+
+use std::time::Instant;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use stickup::descriptor::DescriptorParser;
+use stickup::device::{DeviceFingerprint, ParseCtx, ReportParser};
+use stickup::fastpath::FixedLayout;
+use stickup::metadata::DeviceMeta;
+
+/// X/Y (8 bit), a 4-bit hat with padding, 8 buttons (as `tests/fixtures/gamepad.jsonl`).
+const GAMEPAD: &[u8] = &[
+    0x05, 0x01, 0x09, 0x05, 0xA1, 0x01, 0x09, 0x30, 0x09, 0x31, 0x15, 0x00, 0x26, 0xFF, 0x00, 0x75,
+    0x08, 0x95, 0x02, 0x81, 0x02, 0x09, 0x39, 0x15, 0x00, 0x25, 0x07, 0x75, 0x04, 0x95, 0x01, 0x81,
+    0x42, 0x75, 0x04, 0x95, 0x01, 0x81, 0x03, 0x05, 0x09, 0x19, 0x01, 0x29, 0x08, 0x15, 0x00, 0x25,
+    0x01, 0x75, 0x01, 0x95, 0x08, 0x81, 0x02, 0xC0,
+];
+
+/// One second of a 1 kHz pad: the stick sweeping, the hat turning, buttons toggling.
+fn reports() -> Vec<[u8; 4]> {
+    (0..1000u32)
+        .map(|i| {
+            let x = (i % 256) as u8;
+            let y = (255 - i % 256) as u8;
+            let hat = ((i / 50) % 9) as u8;
+            let buttons = (i / 20) as u8;
+            [x, y, hat, buttons]
+        })
+        .collect()
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let reports = reports();
+    let meta = DeviceMeta::default();
+    let fingerprint = DeviceFingerprint {
+        vendor_id: 0x1234,
+        product_id: 0x0001,
+        serial_number: None,
+        path: None,
+    };
+    let ctx = ParseCtx {
+        report_id: 0,
+        now: Instant::now(),
+        meta: &meta,
+        fingerprint: &fingerprint,
+    };
+    let mut group = c.benchmark_group("gamepad_1000_reports");
+
+    let mut parser = DescriptorParser::from_descriptor(GAMEPAD).expect("descriptor");
+    let mut out = Vec::new();
+    group.bench_function("descriptor_parser", |b| {
+        b.iter(|| {
+            for r in &reports {
+                out.clear();
+                parser.parse(&ctx, black_box(r), &mut out);
+            }
+        })
+    });
+
+    let mut layout = FixedLayout::from_descriptor(parser.descriptor()).expect("simple layout");
+    group.bench_function("fixed_layout", |b| {
+        b.iter(|| {
+            for r in &reports {
+                out.clear();
+                layout.decode(black_box(r), &mut out);
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse);
+criterion_main!(benches);
@@ -0,0 +1,57 @@
+//! Lint a binding profile against the connected devices.
+//!
+//! ```text
+//! cargo run --example lint_profile -- my_profile.toml [--offline]
+//! ```
+//!
+//! `--offline` skips device discovery, so only the structural checks run. Exits with status
+//! 1 if any error-level issue is found.
+
+use std::collections::HashMap;
+
+use stickup::validate::Severity;
+use stickup::{BindingProfile, Manager};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let Some(path) = args.next() else {
+        eprintln!("usage: lint_profile <profile.toml|profile.json> [--offline]");
+        std::process::exit(2);
+    };
+    let offline = args.any(|a| a == "--offline");
+
+    let profile = BindingProfile::load(&path).unwrap_or_else(|e| {
+        eprintln!("{path}: {e}");
+        std::process::exit(2);
+    });
+
+    let mut devices = HashMap::new();
+    if !offline {
+        let mgr = Manager::discover().expect("discover devices");
+        for d in mgr.devices() {
+            if let Some(channels) = mgr.channels(&d.id) {
+                devices.insert(d.id.clone(), channels.to_vec());
+            }
+        }
+    }
+
+    let issues = profile.lint(&devices);
+    for issue in &issues {
+        let level = match issue.severity() {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        println!("{level}: {issue}");
+    }
+    let errors = issues
+        .iter()
+        .filter(|i| i.severity() == Severity::Error)
+        .count();
+    println!(
+        "{path}: {errors} error(s), {} warning(s)",
+        issues.len() - errors
+    );
+    if errors > 0 {
+        std::process::exit(1);
+    }
+}
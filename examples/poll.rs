@@ -68,6 +68,7 @@ fn main() {
                         released.insert((i, button));
                         pressed.remove(&(i, button));
                     }
+                    _ => {}
                 }
             }
         }
@@ -0,0 +1,373 @@
+//! Gyro aiming: angular rate → mouse-style deltas or a virtual stick.
+//!
+//! [`GyroAim`] converts [`InputKind::MotionChanged`](crate::event::InputKind::MotionChanged)
+//! gyro samples into aim output the application applies itself (StickUp does not inject OS
+//! input):
+//! - [`GyroAim::update`] returns yaw/pitch deltas in output units (e.g. mouse counts or game
+//!   degrees) and [`GyroAim::take_counts`] hands them out as whole counts, carrying the
+//!   remainder so slow motion is not lost;
+//! - [`GyroAim::stick`] maps the rate onto a `[-1, 1]` stick vector.
+//!
+//! **Ratcheting**: like lifting a mouse, a designated button suspends gyro output so the
+//! player can re-centre the controller ([`RatchetMode`]). Feed its state with
+//! [`set_ratchet_button`](GyroAim::set_ratchet_button) every tick.
+//!
+//! **Space**: [`GyroSpace::Local`] reads yaw from the controller's own axis;
+//! [`GyroSpace::World`] uses the gravity direction from a [`MotionFusion`] so turning
+//! left/right means the same regardless of how the controller is tilted.
+//!
+//! Gyro axis conventions differ between controllers; use [`GyroAxes`] to pick the yaw/pitch
+//! axes and signs (the defaults match DualSense: `x` = pitch, `y` = yaw).
+//!
+//! **Flick stick**: [`FlickStick`] turns a thumbstick into relative yaw: pushing the stick
+//! snaps the view to the stick's direction, rotating it while held turns the view with it.
+//! Add its output to the gyro's with [`GyroAim::add`] so both share one
+//! [`take_counts`](GyroAim::take_counts) stream.
+
+use crate::motion::MotionFusion;
+
+/// Which gyro axis drives yaw/pitch, and their signs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GyroAxes {
+    /// Gyro component index (`0..3`) for yaw.
+    pub yaw: usize,
+    /// Gyro component index (`0..3`) for pitch.
+    pub pitch: usize,
+    /// Flip yaw.
+    pub invert_yaw: bool,
+    /// Flip pitch.
+    pub invert_pitch: bool,
+}
+
+impl Default for GyroAxes {
+    fn default() -> Self {
+        Self {
+            yaw: 1,
+            pitch: 0,
+            invert_yaw: false,
+            invert_pitch: false,
+        }
+    }
+}
+
+/// Reference frame for yaw.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GyroSpace {
+    /// Controller-local yaw axis.
+    #[default]
+    Local,
+    /// Rotation around gravity (needs a [`MotionFusion`]; see [`GyroAim::update_world`]).
+    World,
+}
+
+/// How the ratchet button suspends output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RatchetMode {
+    /// No ratchet button.
+    #[default]
+    Off,
+    /// Gyro output is suspended while the button is held.
+    HoldToPause,
+    /// Gyro output is only enabled while the button is held.
+    HoldToEnable,
+    /// Each press toggles output on/off.
+    Toggle,
+}
+
+/// Gyro aim translator (see module docs).
+#[derive(Clone, Debug)]
+pub struct GyroAim {
+    /// Output units per degree of rotation, `[yaw, pitch]`.
+    pub sensitivity: [f32; 2],
+    /// Rates below this (°/s) are ignored to hide sensor noise.
+    pub deadband_dps: f32,
+    /// Axis selection.
+    pub axes: GyroAxes,
+    /// Yaw reference frame.
+    pub space: GyroSpace,
+    /// Ratchet behaviour.
+    pub ratchet: RatchetMode,
+    /// Rate (°/s) that maps to full stick deflection in [`stick`](Self::stick).
+    pub stick_full_rate_dps: f32,
+    button_held: bool,
+    toggled_off: bool,
+    remainder: [f32; 2],
+    pending: [f32; 2],
+}
+
+impl Default for GyroAim {
+    fn default() -> Self {
+        Self {
+            sensitivity: [1.0, 1.0],
+            deadband_dps: 0.5,
+            axes: GyroAxes::default(),
+            space: GyroSpace::Local,
+            ratchet: RatchetMode::Off,
+            stick_full_rate_dps: 360.0,
+            button_held: false,
+            toggled_off: false,
+            remainder: [0.0; 2],
+            pending: [0.0; 2],
+        }
+    }
+}
+
+impl GyroAim {
+    /// Translator with `sensitivity` output units per degree on both axes.
+    pub fn new(sensitivity: f32) -> Self {
+        Self {
+            sensitivity: [sensitivity, sensitivity],
+            ..Self::default()
+        }
+    }
+
+    /// Builder: ratchet behaviour.
+    pub fn ratchet(mut self, mode: RatchetMode) -> Self {
+        self.ratchet = mode;
+        self
+    }
+
+    /// Builder: axis selection.
+    pub fn axes(mut self, axes: GyroAxes) -> Self {
+        self.axes = axes;
+        self
+    }
+
+    /// Builder: yaw reference frame.
+    pub fn space(mut self, space: GyroSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    /// Feed the ratchet button state (call every tick; ignored with [`RatchetMode::Off`]).
+    pub fn set_ratchet_button(&mut self, held: bool) {
+        if self.ratchet == RatchetMode::Toggle && held && !self.button_held {
+            self.toggled_off = !self.toggled_off;
+        }
+        self.button_held = held;
+    }
+
+    /// Is gyro output currently enabled?
+    pub fn is_active(&self) -> bool {
+        match self.ratchet {
+            RatchetMode::Off => true,
+            RatchetMode::HoldToPause => !self.button_held,
+            RatchetMode::HoldToEnable => self.button_held,
+            RatchetMode::Toggle => !self.toggled_off,
+        }
+    }
+
+    /// `[yaw, pitch]` rates (°/s) from a gyro sample in the local frame.
+    pub fn rates(&self, gyro: [f32; 3]) -> [f32; 2] {
+        let sign = |inv: bool| if inv { -1.0 } else { 1.0 };
+        let yaw = gyro[self.axes.yaw.min(2)] * sign(self.axes.invert_yaw);
+        let pitch = gyro[self.axes.pitch.min(2)] * sign(self.axes.invert_pitch);
+        [self.deadband(yaw), self.deadband(pitch)]
+    }
+
+    fn deadband(&self, rate: f32) -> f32 {
+        if rate.abs() < self.deadband_dps {
+            0.0
+        } else {
+            rate
+        }
+    }
+
+    /// Aim delta `[yaw, pitch]` in output units for a sample covering `dt` seconds
+    /// (zero while ratcheted). Deltas also accumulate for [`take_counts`](Self::take_counts).
+    ///
+    /// With [`GyroSpace::World`], prefer [`update_world`](Self::update_world).
+    pub fn update(&mut self, gyro: [f32; 3], dt: f32) -> [f32; 2] {
+        let rates = self.rates(gyro);
+        self.advance(rates, dt)
+    }
+
+    /// Like [`update`](Self::update), taking yaw as rotation around gravity as estimated by
+    /// `fusion` when [`space`](Self::space) is [`GyroSpace::World`].
+    pub fn update_world(&mut self, fusion: &MotionFusion, gyro: [f32; 3], dt: f32) -> [f32; 2] {
+        let mut rates = self.rates(gyro);
+        if self.space == GyroSpace::World {
+            // World "up" expressed in the sensor frame.
+            let up = fusion.raw_orientation().conjugate().rotate([0.0, 0.0, 1.0]);
+            let yaw = gyro[0] * up[0] + gyro[1] * up[1] + gyro[2] * up[2];
+            let yaw = if self.axes.invert_yaw { -yaw } else { yaw };
+            rates[0] = self.deadband(yaw);
+        }
+        self.advance(rates, dt)
+    }
+
+    fn advance(&mut self, rates: [f32; 2], dt: f32) -> [f32; 2] {
+        if !(self.is_active() && dt > 0.0 && dt.is_finite()) {
+            return [0.0; 2];
+        }
+        let d = [
+            rates[0] * dt * self.sensitivity[0],
+            rates[1] * dt * self.sensitivity[1],
+        ];
+        self.pending[0] += d[0];
+        self.pending[1] += d[1];
+        d
+    }
+
+    /// Add an externally computed delta `[yaw, pitch]` in output units (e.g. from
+    /// [`FlickStick`]) to the pending counts. Not affected by ratcheting.
+    pub fn add(&mut self, delta: [f32; 2]) {
+        self.pending[0] += delta[0];
+        self.pending[1] += delta[1];
+    }
+
+    /// Whole output counts accumulated since the last call (e.g. mouse counts), keeping
+    /// the fractional remainder for next time.
+    pub fn take_counts(&mut self) -> (i32, i32) {
+        let total = [
+            self.pending[0] + self.remainder[0],
+            self.pending[1] + self.remainder[1],
+        ];
+        let whole = [total[0].trunc(), total[1].trunc()];
+        self.remainder = [total[0] - whole[0], total[1] - whole[1]];
+        self.pending = [0.0; 2];
+        (whole[0] as i32, whole[1] as i32)
+    }
+
+    /// Virtual stick `[x, y]` for a gyro sample: rate / [`stick_full_rate_dps`](Self::stick_full_rate_dps),
+    /// clamped to `[-1, 1]` (`[0, 0]` while ratcheted). Pitch up maps to `-y` like thumbsticks.
+    pub fn stick(&self, gyro: [f32; 3]) -> [f32; 2] {
+        if !self.is_active() || self.stick_full_rate_dps <= 0.0 {
+            return [0.0; 2];
+        }
+        let [yaw, pitch] = self.rates(gyro);
+        [
+            (yaw / self.stick_full_rate_dps).clamp(-1.0, 1.0),
+            (-pitch / self.stick_full_rate_dps).clamp(-1.0, 1.0),
+        ]
+    }
+}
+
+/// Wrap an angle in degrees to `(-180, 180]`.
+fn wrap_degrees(a: f32) -> f32 {
+    let a = a.rem_euclid(360.0);
+    if a > 180.0 {
+        a - 360.0
+    } else {
+        a
+    }
+}
+
+/// Flick-stick translator: stick vector → relative yaw (see module docs).
+///
+/// Stick angles are measured clockwise from up (`[0, -1]`), so pushing right flicks +90°.
+#[derive(Clone, Debug)]
+pub struct FlickStick {
+    /// Output units per degree of yaw.
+    pub sensitivity: f32,
+    /// Stick magnitude that starts a flick.
+    pub threshold: f32,
+    /// Magnitude below which the stick counts as released (hysteresis, `<= threshold`).
+    pub release_threshold: f32,
+    /// Seconds a flick is spread over (`0` = applied in a single update).
+    pub flick_time: f32,
+    /// Rotation steps smaller than this (degrees) are ignored to hide stick jitter.
+    pub rotation_deadband: f32,
+    engaged: bool,
+    last_angle: f32,
+    flick_total: f32,
+    flick_elapsed: f32,
+}
+
+impl Default for FlickStick {
+    fn default() -> Self {
+        Self {
+            sensitivity: 1.0,
+            threshold: 0.9,
+            release_threshold: 0.8,
+            flick_time: 0.1,
+            rotation_deadband: 0.0,
+            engaged: false,
+            last_angle: 0.0,
+            flick_total: 0.0,
+            flick_elapsed: 0.0,
+        }
+    }
+}
+
+impl FlickStick {
+    /// Flick stick with `sensitivity` output units per degree.
+    pub fn new(sensitivity: f32) -> Self {
+        Self {
+            sensitivity,
+            ..Self::default()
+        }
+    }
+
+    /// Builder: flick and release thresholds.
+    pub fn thresholds(mut self, threshold: f32, release: f32) -> Self {
+        self.threshold = threshold;
+        self.release_threshold = release.min(threshold);
+        self
+    }
+
+    /// Builder: seconds a flick is spread over.
+    pub fn flick_time(mut self, seconds: f32) -> Self {
+        self.flick_time = seconds.max(0.0);
+        self
+    }
+
+    /// Is the stick currently past the threshold (flicked / rotating)?
+    pub fn is_engaged(&self) -> bool {
+        self.engaged
+    }
+
+    /// Forget the current flick/rotation.
+    pub fn reset(&mut self) {
+        self.engaged = false;
+        self.flick_total = 0.0;
+        self.flick_elapsed = 0.0;
+    }
+
+    /// Yaw delta in output units for stick `[x, y]` over `dt` seconds.
+    ///
+    /// Crossing the threshold starts a flick towards the stick angle; while held, changes
+    /// of the angle are passed through 1:1. A flick still in progress keeps playing out
+    /// after release.
+    pub fn update(&mut self, stick: [f32; 2], dt: f32) -> f32 {
+        let dt = if dt.is_finite() { dt.max(0.0) } else { 0.0 };
+        let [x, y] = stick;
+        let magnitude = (x * x + y * y).sqrt();
+        let angle = x.atan2(-y).to_degrees();
+        let mut yaw = 0.0;
+
+        if self.engaged {
+            if magnitude < self.release_threshold {
+                self.engaged = false;
+            } else {
+                let step = wrap_degrees(angle - self.last_angle);
+                if step.abs() >= self.rotation_deadband {
+                    yaw += step;
+                    self.last_angle = angle;
+                }
+            }
+        } else if magnitude >= self.threshold {
+            self.engaged = true;
+            self.last_angle = angle;
+            // A new flick replaces what is left of the previous one.
+            self.flick_total = angle;
+            self.flick_elapsed = 0.0;
+            if self.flick_time <= 0.0 {
+                yaw += angle;
+                self.flick_total = 0.0;
+            }
+        }
+
+        if self.flick_total != 0.0 {
+            let before = (self.flick_elapsed / self.flick_time).min(1.0);
+            self.flick_elapsed += dt;
+            let after = (self.flick_elapsed / self.flick_time).min(1.0);
+            yaw += self.flick_total * (after - before);
+            if after >= 1.0 {
+                self.flick_total = 0.0;
+            }
+        }
+
+        yaw * self.sensitivity
+    }
+}
@@ -0,0 +1,359 @@
+//! Arcade cabinet conventions: coin/service/test/start inputs, credits, and coin lockout.
+//!
+//! Cabinet builders wire coin mechs and panel buttons to keyboard encoders or I/O boards; the
+//! wiring differs per cabinet but the roles do not. An [`ArcadeConfig`] maps configured
+//! controls (as in binding profiles: device id + [`ControlPath`]) to [`ArcadeRole`]s, and an
+//! [`ArcadeCabinet`] turns snapshots into [`ArcadeEvent`]s with credit counting:
+//! - each coin pulse adds to the coin count; every
+//!   [`coins_per_credit`](ArcadeConfig::coins_per_credit) coins make a credit;
+//! - the service button adds a credit without a coin (the usual "service coin");
+//! - a start button consumes a credit (or always succeeds with
+//!   [`free_play`](ArcadeConfig::free_play));
+//! - at [`max_credits`](ArcadeConfig::max_credits) the coin lockout engages so the mech
+//!   rejects further coins, and releases once credits are spent.
+//!
+//! The lockout coil is driven through raw output reports
+//! ([`OutputCommand::RawReport`]) configured in [`LockoutOutput`], sent by
+//! [`ArcadeCabinet::sync_lockout`].
+//!
+//! ```no_run
+//! use stickup::arcade::{ArcadeCabinet, ArcadeConfig};
+//!
+//! # fn main() -> stickup::Result<()> {
+//! let mut mgr = stickup::Manager::discover()?;
+//! let mut cab = ArcadeCabinet::new(ArcadeConfig::load("cabinet.toml")?);
+//! loop {
+//!     mgr.poll_events();
+//!     for ev in cab.update(&mgr.snapshot()) {
+//!         println!("{ev:?} (credits: {})", cab.credits());
+//!     }
+//!     cab.sync_lockout(&mut mgr)?;
+//! #   break;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Config file (TOML):
+//! ```toml
+//! coins_per_credit = 2
+//! max_credits = 9
+//!
+//! [[inputs]]
+//! role = { kind = "coin", slot = 0 }
+//! device_id = "rawkbd:ipac"
+//! control = { control_id = "key_0006", control_type = "Button" }
+//!
+//! [[inputs]]
+//! role = { kind = "start", player = 0 }
+//! device_id = "rawkbd:ipac"
+//! control = { control_id = "key_0002", control_type = "Button" }
+//!
+//! [lockout]
+//! device_id = "d209:0410:io"
+//! engage = [3, 1]
+//! release = [3, 0]
+//! ```
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::binding::{control_held, ControlPath};
+use crate::manager::Manager;
+use crate::output::OutputCommand;
+use crate::snapshot::Snapshot;
+use crate::{Error, Result};
+
+/// Standard cabinet input roles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ArcadeRole {
+    /// Coin mech switch (`slot` 0-based, for multi-slot doors).
+    Coin { slot: u8 },
+    /// Service button (adds a credit without a coin).
+    Service,
+    /// Test/operator menu button.
+    Test,
+    /// Player start (`player` 0-based).
+    Start { player: u8 },
+    /// Tilt switch.
+    Tilt,
+}
+
+/// One control mapped to a role.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ArcadeInput {
+    pub role: ArcadeRole,
+    /// Device the control is on.
+    pub device_id: String,
+    /// The control (buttons, or axes past `0.5`).
+    pub control: ControlPath,
+}
+
+/// Output reports that drive a coin lockout coil.
+///
+/// Reports are written verbatim, including the leading report ID byte.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockoutOutput {
+    /// Device with the lockout output (I/O board, LED controller).
+    pub device_id: String,
+    /// Report that engages the lockout (mech rejects coins).
+    pub engage: Vec<u8>,
+    /// Report that releases the lockout (mech accepts coins).
+    pub release: Vec<u8>,
+}
+
+fn default_coins_per_credit() -> u32 {
+    1
+}
+fn default_max_credits() -> u32 {
+    9
+}
+
+/// Cabinet configuration (see module docs).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ArcadeConfig {
+    /// Role mappings.
+    #[serde(default)]
+    pub inputs: Vec<ArcadeInput>,
+    /// Coins needed for one credit.
+    #[serde(default = "default_coins_per_credit")]
+    pub coins_per_credit: u32,
+    /// Credit limit; coins are rejected (lockout engaged) while it is reached.
+    #[serde(default = "default_max_credits")]
+    pub max_credits: u32,
+    /// Starts never need credits.
+    #[serde(default)]
+    pub free_play: bool,
+    /// Optional coin lockout output.
+    #[serde(default)]
+    pub lockout: Option<LockoutOutput>,
+}
+
+impl Default for ArcadeConfig {
+    fn default() -> Self {
+        Self {
+            inputs: Vec::new(),
+            coins_per_credit: default_coins_per_credit(),
+            max_credits: default_max_credits(),
+            free_play: false,
+            lockout: None,
+        }
+    }
+}
+
+impl ArcadeConfig {
+    /// Parse a TOML config.
+    pub fn from_toml_str(s: &str) -> Result<Self> {
+        toml::from_str(s).map_err(|e| Error::Other(format!("invalid arcade config: {e}")))
+    }
+
+    /// Load a TOML config file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Other(format!("read {}: {e}", path.display())))?;
+        Self::from_toml_str(&text)
+    }
+
+    /// Builder: map a control to a role.
+    pub fn input(
+        mut self,
+        role: ArcadeRole,
+        device_id: impl Into<String>,
+        control: ControlPath,
+    ) -> Self {
+        self.inputs.push(ArcadeInput {
+            role,
+            device_id: device_id.into(),
+            control,
+        });
+        self
+    }
+}
+
+/// Something that happened on the cabinet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArcadeEvent {
+    /// A coin was registered in `slot`.
+    CoinInserted {
+        slot: u8,
+    },
+    /// Credits increased (from coins or the service button).
+    CreditAdded {
+        credits: u32,
+    },
+    /// A coin arrived while the credit limit was reached (lockout failed or is not wired);
+    /// it was not counted.
+    CoinRejected {
+        slot: u8,
+    },
+    /// Player start accepted (a credit was consumed unless on free play).
+    Started {
+        player: u8,
+    },
+    /// Player start pressed without credits.
+    StartDenied {
+        player: u8,
+    },
+    ServicePressed,
+    TestPressed,
+    Tilt,
+    /// The coin lockout should change state (see [`ArcadeCabinet::sync_lockout`]).
+    LockoutChanged {
+        engaged: bool,
+    },
+}
+
+/// Credit counter and role edge detection (see module docs).
+#[derive(Clone, Debug)]
+pub struct ArcadeCabinet {
+    config: ArcadeConfig,
+    held: Vec<bool>,
+    coins: u32,
+    credits: u32,
+    lockout: bool,
+    /// Lockout state last written to the device (`None` = never written).
+    lockout_sent: Option<bool>,
+}
+
+impl ArcadeCabinet {
+    /// Cabinet with no credits.
+    pub fn new(config: ArcadeConfig) -> Self {
+        Self {
+            held: vec![false; config.inputs.len()],
+            config,
+            coins: 0,
+            credits: 0,
+            lockout: false,
+            lockout_sent: None,
+        }
+    }
+
+    /// The configuration.
+    pub fn config(&self) -> &ArcadeConfig {
+        &self.config
+    }
+
+    /// Available credits.
+    pub fn credits(&self) -> u32 {
+        self.credits
+    }
+
+    /// Coins counted towards the next credit.
+    pub fn pending_coins(&self) -> u32 {
+        self.coins
+    }
+
+    /// Whether the coin lockout is (logically) engaged.
+    pub fn lockout_engaged(&self) -> bool {
+        self.lockout
+    }
+
+    /// Add credits (operator menu, remote top-up); capped at the credit limit.
+    pub fn add_credits(&mut self, n: u32) -> Vec<ArcadeEvent> {
+        let mut out = Vec::new();
+        self.grant(n, &mut out);
+        out
+    }
+
+    /// Consume one credit without a start button (for games that start themselves).
+    /// Returns `false` if there are no credits; always `true` on free play.
+    pub fn consume_credit(&mut self) -> bool {
+        if self.config.free_play {
+            return true;
+        }
+        if self.credits == 0 {
+            return false;
+        }
+        self.credits -= 1;
+        true
+    }
+
+    /// Process one frame: edge-detect the configured controls and update credits.
+    pub fn update(&mut self, snap: &Snapshot) -> Vec<ArcadeEvent> {
+        let mut out = Vec::new();
+        for i in 0..self.config.inputs.len() {
+            let input = &self.config.inputs[i];
+            let down = snap
+                .get(&input.device_id)
+                .is_some_and(|st| control_held(st, &input.control, None));
+            let pressed = down && !self.held[i];
+            self.held[i] = down;
+            if pressed {
+                let role = input.role;
+                self.on_press(role, &mut out);
+            }
+        }
+        self.update_lockout(&mut out);
+        out
+    }
+
+    fn on_press(&mut self, role: ArcadeRole, out: &mut Vec<ArcadeEvent>) {
+        match role {
+            ArcadeRole::Coin { slot } => {
+                if self.credits >= self.config.max_credits {
+                    out.push(ArcadeEvent::CoinRejected { slot });
+                    return;
+                }
+                out.push(ArcadeEvent::CoinInserted { slot });
+                self.coins += 1;
+                let per = self.config.coins_per_credit.max(1);
+                if self.coins >= per {
+                    self.coins -= per;
+                    self.grant(1, out);
+                }
+            }
+            ArcadeRole::Service => {
+                out.push(ArcadeEvent::ServicePressed);
+                self.grant(1, out);
+            }
+            ArcadeRole::Test => out.push(ArcadeEvent::TestPressed),
+            ArcadeRole::Tilt => out.push(ArcadeEvent::Tilt),
+            ArcadeRole::Start { player } => {
+                if self.consume_credit() {
+                    out.push(ArcadeEvent::Started { player });
+                } else {
+                    out.push(ArcadeEvent::StartDenied { player });
+                }
+            }
+        }
+    }
+
+    fn grant(&mut self, n: u32, out: &mut Vec<ArcadeEvent>) {
+        let credits = self.credits.saturating_add(n).min(self.config.max_credits);
+        if credits > self.credits {
+            self.credits = credits;
+            out.push(ArcadeEvent::CreditAdded { credits });
+        }
+        self.update_lockout(out);
+    }
+
+    fn update_lockout(&mut self, out: &mut Vec<ArcadeEvent>) {
+        let engaged = !self.config.free_play && self.credits >= self.config.max_credits;
+        if engaged != self.lockout {
+            self.lockout = engaged;
+            out.push(ArcadeEvent::LockoutChanged { engaged });
+        }
+    }
+
+    /// Write the lockout output report if its state changed since the last write (or was
+    /// never written). No-op without a configured [`LockoutOutput`].
+    pub fn sync_lockout(&mut self, mgr: &mut Manager) -> Result<()> {
+        let Some(out) = &self.config.lockout else {
+            return Ok(());
+        };
+        if self.lockout_sent == Some(self.lockout) {
+            return Ok(());
+        }
+        let report = if self.lockout {
+            &out.engage
+        } else {
+            &out.release
+        };
+        mgr.send_output(&out.device_id, &OutputCommand::RawReport(report.clone()))?;
+        self.lockout_sent = Some(self.lockout);
+        Ok(())
+    }
+}
@@ -0,0 +1,458 @@
+//! Sony DualSense / DualSense Edge report parser.
+//!
+//! The generic HIDP path only sees the DualSense's simple gamepad fields. This parser decodes
+//! the full input report instead:
+//! - USB report `0x01` (64 bytes) and Bluetooth report `0x31` (78 bytes)
+//! - Bluetooth "simple" report `0x01` (sent until the extended mode is enabled)
+//! - sticks/triggers, buttons, D-pad, touchpad points, IMU, and battery
+//!
+//! It also encodes the output report (`0x02` over USB, `0x31` over Bluetooth with CRC) for
+//! rumble, light bar, player LEDs, and adaptive triggers.
+//!
+//! The parser is byte-level and platform-agnostic; backends choose it by VID/PID
+//! (see [`DualSenseParser::matches`]).
+//!
+//! ## Channel layout
+//! - Axes: `0 LX`, `1 LY`, `2 RX`, `3 RY`, `4 L2`, `5 R2` (sticks `[-1, 1]`, up = `-1`;
+//!   triggers `-1` released → `+1` pressed, as for XInput)
+//! - Buttons: `Square, Cross, Circle, Triangle, L1, R1, L2, R2, Create, Options, L3, R3, PS,
+//!   Touchpad, Mute` (+ `FnL, FnR, BackL, BackR` on the Edge)
+//! - Hat `0`: D-pad
+//! - Touch surface `0` (two fingers), motion sensor `0`
+
+use std::cell::{Cell, RefCell};
+
+use crate::device::{BatteryLevel, BatteryStatus, ParseCtx, ReportParser};
+use crate::event::{ChannelDesc, ChannelKind, ChannelUnit, InputKind};
+use crate::output::{OutputCommand, Trigger, TriggerEffect};
+
+/// Sony vendor ID.
+pub const SONY_VID: u16 = 0x054c;
+/// DualSense product ID.
+pub const DUALSENSE_PID: u16 = 0x0ce6;
+/// DualSense Edge product ID.
+pub const DUALSENSE_EDGE_PID: u16 = 0x0df2;
+
+const USB_INPUT_REPORT: u8 = 0x01;
+const BT_INPUT_REPORT: u8 = 0x31;
+const USB_OUTPUT_REPORT: u8 = 0x02;
+const BT_OUTPUT_REPORT: u8 = 0x31;
+const USB_OUTPUT_LEN: usize = 48;
+const BT_OUTPUT_LEN: usize = 78;
+
+/// Reading the calibration feature report switches Bluetooth pads into the extended
+/// (`0x31`) input mode.
+const FEATURE_CALIBRATION: u8 = 0x05;
+
+// Touchpad resolution (points are reported in this pixel space).
+const TOUCH_WIDTH: f32 = 1920.0;
+const TOUCH_HEIGHT: f32 = 1080.0;
+
+// Nominal (uncalibrated) IMU scales: ±2000 dps and ±4 g over 16 bits.
+const GYRO_LSB_PER_DPS: f32 = 16.384;
+const ACCEL_LSB_PER_G: f32 = 8192.0;
+
+const BUTTON_NAMES: [&str; 15] = [
+    "Square", "Cross", "Circle", "Triangle", "L1", "R1", "L2", "R2", "Create", "Options", "L3",
+    "R3", "PS", "Touchpad", "Mute",
+];
+const EDGE_BUTTON_NAMES: [&str; 4] = ["FnL", "FnR", "BackL", "BackR"];
+const AXIS_NAMES: [&str; 6] = ["LX", "LY", "RX", "RY", "L2", "R2"];
+
+/// One decoded input frame, independent of transport.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Frame {
+    axes: [u8; 6],
+    /// Button bits in channel order.
+    buttons: u32,
+    hat: i16,
+    /// `(active, x, y)` per touch point, when the report carries them.
+    touch: Option<[(bool, u16, u16); 2]>,
+    /// Raw `[gyro xyz, accel xyz]`, when the report carries them.
+    motion: Option<[i16; 6]>,
+    /// `(percent, charging)`, when the report carries it.
+    battery: Option<(u8, bool)>,
+}
+
+/// Output state; the DualSense output report always carries every field.
+#[derive(Clone, Debug)]
+struct OutputState {
+    rumble: (u8, u8),
+    lightbar: Option<(u8, u8, u8)>,
+    player_leds: Option<u8>,
+    triggers: [Option<TriggerEffect>; 2],
+}
+
+/// Report parser for DualSense and DualSense Edge controllers.
+pub struct DualSenseParser {
+    bluetooth: bool,
+    edge: bool,
+    last: Option<Frame>,
+    /// Last in-band battery reading (`(percent, charging)`), kept across simple reports.
+    battery: Option<(u8, bool)>,
+    output: RefCell<OutputState>,
+    output_seq: Cell<u8>,
+}
+
+impl DualSenseParser {
+    /// Create a parser for the given transport and model.
+    pub fn new(bluetooth: bool, edge: bool) -> Self {
+        Self {
+            bluetooth,
+            edge,
+            last: None,
+            battery: None,
+            output: RefCell::new(OutputState {
+                rumble: (0, 0),
+                lightbar: None,
+                player_leds: None,
+                triggers: [None, None],
+            }),
+            output_seq: Cell::new(0),
+        }
+    }
+
+    /// `true` if `(vid, pid)` is a DualSense or DualSense Edge.
+    pub fn matches(vid: u16, pid: u16) -> bool {
+        vid == SONY_VID && matches!(pid, DUALSENSE_PID | DUALSENSE_EDGE_PID)
+    }
+
+    fn button_count(&self) -> usize {
+        let extra = if self.edge {
+            EDGE_BUTTON_NAMES.len()
+        } else {
+            0
+        };
+        BUTTON_NAMES.len() + extra
+    }
+
+    /// Decode the full (USB `0x01` / BT `0x31`) report body.
+    ///
+    /// `body` starts at the left stick X byte.
+    fn decode_full(body: &[u8]) -> Option<Frame> {
+        if body.len() < 53 {
+            return None;
+        }
+        let axes = [body[0], body[1], body[2], body[3], body[4], body[5]];
+        let (buttons, hat) = decode_buttons(body[7], body[8], body[9]);
+
+        let le16 = |i: usize| i16::from_le_bytes([body[i], body[i + 1]]);
+        let motion = [le16(15), le16(17), le16(19), le16(21), le16(23), le16(25)];
+
+        let point = |o: usize| {
+            let contact = body[o];
+            let x = u16::from(body[o + 1]) | (u16::from(body[o + 2] & 0x0f) << 8);
+            let y = u16::from(body[o + 2] >> 4) | (u16::from(body[o + 3]) << 4);
+            (contact & 0x80 == 0, x, y)
+        };
+        let touch = [point(32), point(36)];
+
+        let status = body[52];
+        let level = (status & 0x0f).min(10);
+        let charging_state = status >> 4;
+        let percent = match charging_state {
+            0x2 => 100, // full
+            _ => (level * 10 + 5).min(100),
+        };
+        let battery = (percent, charging_state == 0x1);
+
+        Some(Frame {
+            axes,
+            buttons,
+            hat,
+            touch: Some(touch),
+            motion: Some(motion),
+            battery: Some(battery),
+        })
+    }
+
+    /// Decode the Bluetooth "simple" `0x01` report (no touch/IMU/battery).
+    fn decode_simple(body: &[u8]) -> Option<Frame> {
+        if body.len() < 9 {
+            return None;
+        }
+        let (buttons, hat) = decode_buttons(body[4], body[5], body[6]);
+        Some(Frame {
+            axes: [body[0], body[1], body[2], body[3], body[7], body[8]],
+            buttons,
+            hat,
+            ..Frame::default()
+        })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let state = self.output.borrow();
+        let (mut report, common_at) = if self.bluetooth {
+            let mut r = vec![0u8; BT_OUTPUT_LEN];
+            r[0] = BT_OUTPUT_REPORT;
+            let seq = self.output_seq.get();
+            self.output_seq.set((seq + 1) & 0x0f);
+            r[1] = seq << 4;
+            r[2] = 0x10; // tag
+            (r, 3)
+        } else {
+            let mut r = vec![0u8; USB_OUTPUT_LEN];
+            r[0] = USB_OUTPUT_REPORT;
+            (r, 1)
+        };
+
+        let c = &mut report[common_at..common_at + 47];
+        // valid_flag0: compatible vibration + haptics select
+        c[0] |= 0x01 | 0x02;
+        c[2] = state.rumble.1; // right (high-frequency) motor
+        c[3] = state.rumble.0; // left (low-frequency) motor
+
+        // Right trigger block at 10, left at 21 (mode + 10 parameter bytes each).
+        for (trigger, flag, at) in [(Trigger::Right, 0x04, 10), (Trigger::Left, 0x08, 21)] {
+            if let Some(effect) = state.triggers[trigger as usize] {
+                c[0] |= flag;
+                c[at..at + 11].copy_from_slice(&encode_trigger(effect));
+            }
+        }
+
+        if let Some(mask) = state.player_leds {
+            c[1] |= 0x10; // player indicator control enable
+            c[43] = mask & 0x1f;
+        }
+        if let Some((r, g, b)) = state.lightbar {
+            c[1] |= 0x04; // light bar control enable
+            c[38] |= 0x02; // valid_flag2: light bar setup
+            c[41] = 0x02; // light bar setup: fade out the boot animation
+            c[44] = r;
+            c[45] = g;
+            c[46] = b;
+        }
+
+        if self.bluetooth {
+            let crc = crc32(&[&[0xa2], &report[..BT_OUTPUT_LEN - 4]]);
+            report[BT_OUTPUT_LEN - 4..].copy_from_slice(&crc.to_le_bytes());
+        }
+        report
+    }
+}
+
+impl ReportParser for DualSenseParser {
+    fn input_report_len(&self) -> Option<usize> {
+        Some(if self.bluetooth { 78 } else { 64 })
+    }
+
+    fn describe(&self) -> Vec<ChannelDesc> {
+        let desc = |kind, idx: usize, name: &str, logical_min, logical_max| ChannelDesc {
+            kind,
+            idx: idx as u16,
+            name: Some(name.to_string()),
+            logical_min,
+            logical_max,
+            usage_page: None,
+            usage: None,
+            unit: ChannelUnit::for_kind(kind),
+            collection: None,
+            collection_name: None,
+        };
+        let mut out = Vec::new();
+        for (i, name) in AXIS_NAMES.iter().enumerate() {
+            let mut d = desc(ChannelKind::Axis, i, name, -1, 1);
+            if i >= 4 {
+                // L2 / R2 triggers
+                d.unit = Some(ChannelUnit::Percent);
+            }
+            out.push(d);
+        }
+        let names = BUTTON_NAMES.iter().chain(EDGE_BUTTON_NAMES.iter());
+        for (i, name) in names.take(self.button_count()).enumerate() {
+            out.push(desc(ChannelKind::Button, i, name, 0, 1));
+        }
+        out.push(desc(ChannelKind::Hat, 0, "DPad", -1, 7));
+        out
+    }
+
+    fn parse(&mut self, ctx: &ParseCtx, payload: &[u8], out: &mut Vec<InputKind>) {
+        let frame = match ctx.report_id {
+            // BT extended reports carry one extra byte (sequence/tag) before the body.
+            BT_INPUT_REPORT => payload.get(1..).and_then(Self::decode_full),
+            USB_INPUT_REPORT if payload.len() >= 53 => Self::decode_full(payload),
+            USB_INPUT_REPORT => Self::decode_simple(payload),
+            _ => None,
+        };
+        let Some(mut frame) = frame else {
+            return;
+        };
+        if !self.edge {
+            frame.buttons &= (1 << BUTTON_NAMES.len()) - 1;
+        }
+        let prev = self.last.take().unwrap_or(Frame {
+            axes: [128, 128, 128, 128, 0, 0],
+            hat: -1,
+            ..Frame::default()
+        });
+
+        for (i, (&now, &was)) in frame.axes.iter().zip(prev.axes.iter()).enumerate() {
+            if now != was {
+                let value = if i < 4 {
+                    ((now as f32 - 127.5) / 127.5).clamp(-1.0, 1.0)
+                } else {
+                    now as f32 / 255.0 * 2.0 - 1.0
+                };
+                out.push(InputKind::AxisMoved {
+                    axis: i as u16,
+                    value,
+                });
+            }
+        }
+
+        let changed = frame.buttons ^ prev.buttons;
+        for i in 0..self.button_count() {
+            if changed & (1 << i) != 0 {
+                let button = i as u16;
+                out.push(if frame.buttons & (1 << i) != 0 {
+                    InputKind::ButtonPressed { button }
+                } else {
+                    InputKind::ButtonReleased { button }
+                });
+            }
+        }
+
+        if frame.hat != prev.hat {
+            out.push(InputKind::HatChanged {
+                hat: 0,
+                value: frame.hat,
+            });
+        }
+
+        if let Some(points) = frame.touch {
+            let before = prev.touch.unwrap_or_default();
+            for (finger, (&(active, x, y), &was)) in points.iter().zip(before.iter()).enumerate() {
+                // Inactive points keep their last coordinates; only report the lift.
+                if (active, x, y) != was && (active || was.0) {
+                    out.push(InputKind::TouchMoved {
+                        surface: 0,
+                        finger: finger as u8,
+                        active,
+                        x: (x as f32 / (TOUCH_WIDTH - 1.0)).clamp(0.0, 1.0),
+                        y: (y as f32 / (TOUCH_HEIGHT - 1.0)).clamp(0.0, 1.0),
+                    });
+                }
+            }
+        }
+
+        if let Some(raw) = frame.motion {
+            if prev.motion != Some(raw) {
+                let gyro = [raw[0], raw[1], raw[2]].map(|v| v as f32 / GYRO_LSB_PER_DPS);
+                let accel = [raw[3], raw[4], raw[5]].map(|v| v as f32 / ACCEL_LSB_PER_G);
+                out.push(InputKind::MotionChanged {
+                    sensor: 0,
+                    gyro,
+                    accel,
+                });
+            }
+        }
+
+        if let Some((percent, charging)) = frame.battery {
+            if prev.battery != frame.battery {
+                out.push(InputKind::BatteryChanged { percent, charging });
+            }
+            self.battery = frame.battery;
+        }
+
+        self.last = Some(frame);
+    }
+
+    /// Last in-band battery reading; over USB the pad counts as wired.
+    fn battery(&self) -> Option<BatteryStatus> {
+        let (percent, charging) = self.battery?;
+        Some(BatteryStatus {
+            level: BatteryLevel::from_percent(percent),
+            percent: Some(percent),
+            charging,
+            wired: !self.bluetooth,
+        })
+    }
+
+    fn feature_reports_on_open(&self) -> Vec<u8> {
+        if self.bluetooth {
+            vec![FEATURE_CALIBRATION]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Rumble goes through the regular output report (compatible vibration mode).
+    fn encode_rumble(&self, low: f32, high: f32) -> Option<Vec<u8>> {
+        let scale = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        self.output.borrow_mut().rumble = (scale(low), scale(high));
+        Some(self.encode())
+    }
+
+    fn encode_output(&self, cmd: &OutputCommand) -> Option<Vec<u8>> {
+        {
+            let mut state = self.output.borrow_mut();
+            match *cmd {
+                OutputCommand::Lightbar { r, g, b } => state.lightbar = Some((r, g, b)),
+                OutputCommand::PlayerLeds(mask) => state.player_leds = Some(mask),
+                OutputCommand::TriggerEffect { trigger, effect } => {
+                    state.triggers[trigger as usize] = Some(effect)
+                }
+                OutputCommand::RawReport(ref report) => return Some(report.clone()),
+            }
+        }
+        Some(self.encode())
+    }
+}
+
+/// Decode the three button bytes into `(channel bits, hat)`.
+fn decode_buttons(b0: u8, b1: u8, b2: u8) -> (u32, i16) {
+    let dpad = b0 & 0x0f;
+    let hat = if dpad < 8 { dpad as i16 } else { -1 };
+    // b0 high nibble: Square, Cross, Circle, Triangle
+    let mut bits = u32::from(b0 >> 4);
+    // b1: L1, R1, L2, R2, Create, Options, L3, R3
+    bits |= u32::from(b1) << 4;
+    // b2: PS, Touchpad, Mute, (reserved), FnL, FnR, BackL, BackR (Edge)
+    bits |= u32::from(b2 & 0x07) << 12;
+    bits |= u32::from(b2 >> 4) << 15;
+    (bits, hat)
+}
+
+/// 11-byte trigger block: mode followed by 10 parameter bytes.
+fn encode_trigger(effect: TriggerEffect) -> [u8; 11] {
+    let pos = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let mut block = [0u8; 11];
+    match effect {
+        TriggerEffect::Off => block[0] = 0x05,
+        TriggerEffect::Resistance { start, force } => {
+            block[0] = 0x01;
+            block[1] = pos(start);
+            block[2] = pos(force);
+        }
+        TriggerEffect::Section { start, end, force } => {
+            block[0] = 0x02;
+            block[1] = pos(start);
+            block[2] = pos(end.max(start));
+            block[3] = pos(force);
+        }
+        TriggerEffect::Vibration {
+            start,
+            amplitude,
+            frequency_hz,
+        } => {
+            block[0] = 0x06;
+            block[1] = frequency_hz;
+            block[2] = pos(amplitude);
+            block[3] = pos(start);
+        }
+    }
+    block
+}
+
+/// CRC-32 (IEEE, reflected) over concatenated chunks, as required by BT output reports.
+fn crc32(chunks: &[&[u8]]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &b in chunks.iter().flat_map(|c| c.iter()) {
+        crc ^= u32::from(b);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
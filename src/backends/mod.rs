@@ -10,6 +10,10 @@
 //! StickUp reads input devices; it does not create virtual devices (vJoy/uinput).
 
 use crate::device::Device;
+use crate::quirks::QuirkDb;
+use crate::Result;
+
+pub mod dualsense;
 
 #[cfg(all(feature = "hid", target_os = "windows"))]
 #[cfg_attr(docsrs, doc(cfg(all(feature = "hid", target_os = "windows"))))]
@@ -19,15 +23,34 @@ pub mod windows;
 ///
 /// Currently this returns HID/XInput devices on Windows when `hid` is enabled.
 pub fn probe_devices() -> Vec<Box<dyn Device>> {
+    probe_devices_with_quirks(&QuirkDb::default())
+}
+
+/// Unified discovery with a [`QuirkDb`] applied where backends support it.
+///
+/// Backends that fail to initialize contribute no devices; use
+/// [`try_probe_devices_with_quirks`] to see why.
+pub fn probe_devices_with_quirks(quirks: &QuirkDb) -> Vec<Box<dyn Device>> {
+    try_probe_devices_with_quirks(quirks).unwrap_or_default()
+}
+
+/// Like [`probe_devices_with_quirks`], but fails with
+/// [`Error::Discovery`](crate::Error::Discovery) if an enabled
+/// backend cannot be initialized.
+#[cfg_attr(
+    not(all(feature = "hid", target_os = "windows")),
+    allow(unused_variables, unused_mut)
+)]
+pub fn try_probe_devices_with_quirks(quirks: &QuirkDb) -> Result<Vec<Box<dyn Device>>> {
     let mut out: Vec<Box<dyn Device>> = Vec::new();
 
     #[cfg(all(feature = "hid", target_os = "windows"))]
     {
-        use crate::backends::windows::probe_devices as win_probe;
-        if let Ok(api) = hidapi::HidApi::new() {
-            out.extend(win_probe(&api));
-        }
+        use crate::backends::windows::probe_devices_with_quirks as win_probe;
+        let api = hidapi::HidApi::new()
+            .map_err(|e| crate::Error::Discovery(format!("hidapi init: {e}")))?;
+        out.extend(win_probe(&api, quirks));
     }
 
-    out
+    Ok(out)
 }
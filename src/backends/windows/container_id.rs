@@ -0,0 +1,110 @@
+#![cfg(target_os = "windows")]
+
+//! PnP container ID lookup.
+//!
+//! Windows groups all device nodes of one physical product (every HID interface, the XInput
+//! endpoint, audio, ...) under a shared *container ID*. Discovery uses it to recognize HID
+//! interfaces that belong to a pad already surfaced through XInput.
+
+use std::ffi::OsStr;
+use std::os::windows::ffi::OsStrExt;
+
+use windows_sys::core::GUID;
+use windows_sys::Win32::Devices::DeviceAndDriverInstallation::{
+    CM_Get_DevNode_PropertyW, CM_Get_Device_Interface_PropertyW, CM_Locate_DevNodeW,
+    CM_LOCATE_DEVNODE_NORMAL, CR_SUCCESS,
+};
+use windows_sys::Win32::Devices::Properties::{
+    DEVPKEY_Device_ContainerId, DEVPKEY_Device_InstanceId, DEVPROPTYPE, DEVPROP_TYPE_GUID,
+    DEVPROP_TYPE_STRING,
+};
+
+/// Container reported for devices without a physical container (root-enumerated, virtual).
+///
+/// Many unrelated virtual devices share it, so it must never be used for correlation.
+const NO_CONTAINER: &str = "{00000000-0000-0000-ffff-ffffffffffff}";
+
+/// Container ID (`{xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx}`, lowercase) of the device behind a
+/// HID interface path, or `None` if unknown or not a physical container.
+pub(super) fn container_id(interface_path: &str) -> Option<String> {
+    let instance_id = interface_instance_id(interface_path)?;
+
+    let mut devinst = 0u32;
+    let cr =
+        unsafe { CM_Locate_DevNodeW(&mut devinst, instance_id.as_ptr(), CM_LOCATE_DEVNODE_NORMAL) };
+    if cr != CR_SUCCESS {
+        return None;
+    }
+
+    let mut guid = GUID {
+        data1: 0,
+        data2: 0,
+        data3: 0,
+        data4: [0; 8],
+    };
+    let mut ty: DEVPROPTYPE = 0;
+    let mut size = std::mem::size_of::<GUID>() as u32;
+    let cr = unsafe {
+        CM_Get_DevNode_PropertyW(
+            devinst,
+            &DEVPKEY_Device_ContainerId,
+            &mut ty,
+            (&mut guid as *mut GUID).cast::<u8>(),
+            &mut size,
+            0,
+        )
+    };
+    if cr != CR_SUCCESS || ty != DEVPROP_TYPE_GUID {
+        return None;
+    }
+
+    let id = format_guid(&guid);
+    (id != NO_CONTAINER).then_some(id)
+}
+
+/// Device instance ID (NUL-terminated UTF-16) owning a device interface path.
+fn interface_instance_id(interface_path: &str) -> Option<Vec<u16>> {
+    let wide: Vec<u16> = OsStr::new(interface_path)
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+
+    let mut buf = vec![0u16; 512];
+    let mut ty: DEVPROPTYPE = 0;
+    let mut size = (buf.len() * 2) as u32;
+    let cr = unsafe {
+        CM_Get_Device_Interface_PropertyW(
+            wide.as_ptr(),
+            &DEVPKEY_Device_InstanceId,
+            &mut ty,
+            buf.as_mut_ptr().cast::<u8>(),
+            &mut size,
+            0,
+        )
+    };
+    if cr != CR_SUCCESS || ty != DEVPROP_TYPE_STRING {
+        return None;
+    }
+    buf.truncate((size as usize / 2).max(1));
+    if buf.last() != Some(&0) {
+        buf.push(0);
+    }
+    Some(buf)
+}
+
+fn format_guid(g: &GUID) -> String {
+    format!(
+        "{{{:08x}-{:04x}-{:04x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}}}",
+        g.data1,
+        g.data2,
+        g.data3,
+        g.data4[0],
+        g.data4[1],
+        g.data4[2],
+        g.data4[3],
+        g.data4[4],
+        g.data4[5],
+        g.data4[6],
+        g.data4[7]
+    )
+}
@@ -5,6 +5,9 @@
 //! [`HidInputDevice`] wraps a `hidapi::HidDevice` and a [`ReportParser`] implementation.
 //! It is responsible for:
 //! - opening the HID handle in non-blocking mode
+//! - reading input reports: through overlapped reads on the HIDP parser's handle when
+//!   available (reports are timestamped on arrival, see [`overlapped`](super::overlapped)),
+//!   otherwise with non-blocking `hidapi` reads
 //! - draining a bounded number of reports per poll
 //! - splitting `[report_id][payload...]` vs `[payload...]` depending on parser expectations
 //! - translating raw report bytes into [`InputKind`] deltas via the parser
@@ -14,10 +17,18 @@
 //! - apply deadzones/curves/smoothing (that is binding/UI policy)
 //! - create any kind of virtual device output
 
-use crate::device::{Device, DeviceFingerprint, ParseCtx, ReportParser};
+use super::overlapped::{Completion, OverlappedReader};
+use crate::device::{
+    BatteryStatus, Device, DeviceFingerprint, ParseCtx, ReportIdDiagnostics, ReportParser,
+};
 use crate::event::{ChannelDesc, InputKind};
+use crate::ffb::{EffectHandle, EffectOp, FfbEffect};
 use crate::metadata::DeviceMeta;
+use crate::output::OutputCommand;
+use crate::report_capture::{CaptureHeader, ReportCaptureWriter};
+use crate::{Error, Result};
 use hidapi::{DeviceInfo, HidApi};
+use std::path::Path;
 use std::time::Instant;
 
 /// Safety valve: maximum number of HID reports drained per `poll()` call.
@@ -26,6 +37,10 @@ use std::time::Instant;
 /// producing data faster than the host is polling.
 const MAX_REPORTS_PER_TICK: usize = 32;
 
+/// Upper bound on stale reports discarded when falling back to `hidapi` reads (the HID
+/// class driver buffers at most 512 input reports per handle).
+const STALE_DRAIN_LIMIT: usize = 512;
+
 /// Concrete HID-backed device implementing [`Device`](crate::device::Device).
 ///
 /// The device’s stable ID is derived from its [`DeviceFingerprint`]. See `device.rs`
@@ -35,29 +50,49 @@ pub struct HidInputDevice {
     fingerprint_str: String,
     name: String,
     raw: hidapi::HidDevice,
-    buf: Vec<u8>,                         // exactly input_report_len
+    buf: Vec<u8>, // exactly input_report_len
+    /// Overlapped reads on the parser's handle; declared before `parser` so it is dropped
+    /// (and its pending read cancelled) before the handle closes.
+    overlapped: Option<OverlappedReader>,
     parser: Box<dyn ReportParser + Send>, // non-optional
     meta: DeviceMeta,
+    /// `ParseCtx::now` of the first report read by the last poll.
+    report_time: Option<Instant>,
+    /// Read error hit after some reports were parsed; returned by the next `try_poll`.
+    pending_error: Option<Error>,
+    /// Raw report capture, when enabled (see `capture_reports`).
+    capture: Option<ReportCaptureWriter>,
 }
 
 impl HidInputDevice {
     /// Attempt to open and wrap a HID device entry.
     ///
-    /// Returns `None` if the OS handle cannot be opened. The parser is required and non-optional.
+    /// Returns [`Error::HidOpen`] if the OS handle cannot be opened. The parser is required and
+    /// non-optional.
     pub fn new(
         info: &DeviceInfo,
         api: &HidApi,
         parser: impl ReportParser + Send + 'static,
         fingerprint: DeviceFingerprint,
         meta: DeviceMeta,
-    ) -> Option<Self> {
-        let device = info.open_device(api).ok()?;
+    ) -> Result<Self> {
+        let device = info.open_device(api).map_err(|e| Error::HidOpen {
+            path: info.path().to_string_lossy().to_string(),
+            message: e.to_string(),
+        })?;
         // StickUp polls devices in a host-controlled loop, so we use non-blocking reads.
         // If set_blocking_mode fails, we continue anyway; `hidapi` will still error or
         // return data depending on backend behavior.
         let _ = device.set_blocking_mode(false);
 
         let boxed: Box<dyn ReportParser + Send> = Box::new(parser);
+
+        // Some parsers need a feature read to switch the device into the mode they decode.
+        for id in boxed.feature_reports_on_open() {
+            let mut feature = vec![0u8; 64];
+            feature[0] = id;
+            let _ = device.get_feature_report(&mut feature);
+        }
         // Allocate to the exact size (including ID byte) if known; otherwise a safe default.
         let buf_len = boxed.input_report_len().unwrap_or(64);
         let buf = vec![0u8; buf_len];
@@ -78,17 +113,119 @@ impl HidInputDevice {
             fp = fingerprint.to_string(),
         );
 
-        Some(Self {
+        Ok(Self {
             fingerprint_str: fingerprint.to_string(),
             fingerprint,
             name,
             raw: device,
             buf,
+            overlapped: None,
             parser: boxed,
             meta,
+            report_time: None,
+            pending_error: None,
+            capture: None,
         })
     }
 
+    /// Flush captured reports, dropping the capture if the file can no longer be written.
+    fn flush_capture(&mut self) {
+        if let Some(capture) = &mut self.capture {
+            if capture.flush().is_err() {
+                self.capture = None;
+            }
+        }
+    }
+
+    /// Read input reports through overlapped I/O on `handle` instead of `hidapi` (see
+    /// [`overlapped`](super::overlapped)). Keeps the `hidapi` path if that cannot be set up.
+    ///
+    /// # Safety
+    /// `handle` must be the overlapped handle owned by this device's parser
+    /// ([`WinHidpParser::raw_handle`](super::hidp_parser::WinHidpParser::raw_handle)).
+    pub(crate) unsafe fn with_overlapped_reads(
+        mut self,
+        handle: windows_sys::Win32::Foundation::HANDLE,
+    ) -> Self {
+        match unsafe { OverlappedReader::start(handle, self.buf.len()) } {
+            Ok(reader) => self.overlapped = Some(reader),
+            Err(_code) => {
+                #[cfg(feature = "debug-log")]
+                eprintln!(
+                    "[HID/OPEN] dev={} overlapped reads unavailable (error {_code}), using hidapi",
+                    self.fingerprint_str
+                );
+            }
+        }
+        self
+    }
+
+    /// Next raw report and its arrival time; `Ok(None)` when none is pending.
+    ///
+    /// Overlapped reads always deliver `[report_id][payload...]`; for parsers that take no
+    /// ID prefix the leading `0x00` is dropped here, as `hidapi` does, so both paths hand
+    /// `try_poll` the same layout.
+    ///
+    /// A failed overlapped read stops that reader for good, so the device falls back to
+    /// `hidapi` reads; if the device is really gone, those report the error. Reports that
+    /// queued up on the `hidapi` handle while the overlapped reader was active are stale
+    /// and discarded first.
+    fn read_report(&mut self) -> Result<Option<(Instant, Vec<u8>)>> {
+        if let Some(reader) = &self.overlapped {
+            match reader.try_next() {
+                None => return Ok(None),
+                Some(Completion::Report(at, mut data)) => {
+                    if !self.parser.expects_report_id_prefix() && !data.is_empty() {
+                        data.remove(0);
+                    }
+                    return Ok(Some((at, data)));
+                }
+                Some(Completion::Failed(_code)) => {
+                    #[cfg(feature = "debug-log")]
+                    eprintln!(
+                        "[HID/READ] dev={} overlapped read failed (error {_code}), using hidapi",
+                        self.fingerprint_str
+                    );
+                    self.overlapped = None;
+                    self.drain_hidapi();
+                }
+            }
+        }
+        match self.raw.read(&mut self.buf) {
+            Ok(0) => Ok(None), // no data this tick (non-blocking)
+            Ok(n) => Ok(Some((Instant::now(), self.buf[..n].to_vec()))),
+            Err(e) => Err(Error::Read {
+                device: self.fingerprint_str.clone(),
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    /// Discard whatever the `hidapi` handle buffered so far (bounded by the driver's input
+    /// buffer). Errors are left for the next read to report.
+    fn drain_hidapi(&mut self) {
+        for _ in 0..STALE_DRAIN_LIMIT {
+            match self.raw.read(&mut self.buf) {
+                Ok(n) if n > 0 => {}
+                _ => break,
+            }
+        }
+    }
+
+    /// [`Error::Write`] for a failed `what` (output report, feature report, ...).
+    fn write_error(&self, what: &str, e: hidapi::HidError) -> Error {
+        Error::Write {
+            device: self.fingerprint_str.clone(),
+            message: format!("{what}: {e}"),
+        }
+    }
+
+    /// Override the display name (e.g. from a quirk file).
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
     /// Windows-style split: treat the first byte as a report ID.
     ///
     /// Many HID stacks deliver input reports as `[report_id][payload...]` even when the
@@ -108,11 +245,23 @@ impl HidInputDevice {
 }
 
 impl Device for HidInputDevice {
+    /// Drain reports via [`try_poll`](Device::try_poll); read failures yield no events.
+    fn poll(&mut self) -> Vec<InputKind> {
+        self.try_poll().unwrap_or_default()
+    }
+
     /// Drain up to [`MAX_REPORTS_PER_TICK`] reports and return the resulting input deltas.
     ///
-    /// This method does not timestamp events itself beyond the [`ParseCtx::now`] field passed
-    /// to parsers; higher-level timing wrappers live in `Manager`.
-    fn poll(&mut self) -> Vec<InputKind> {
+    /// The [`ParseCtx::now`] of the first report is kept for [`report_time`](Device::report_time);
+    /// higher-level timing wrappers live in `Manager`.
+    ///
+    /// Returns [`Error::Read`] if the OS read fails. Events parsed earlier in the same call
+    /// are returned first and the error is reported by the next call.
+    fn try_poll(&mut self) -> Result<Vec<InputKind>> {
+        self.report_time = None;
+        if let Some(e) = self.pending_error.take() {
+            return Err(e);
+        }
         let mut events = Vec::new();
         let mut drained = 0;
 
@@ -121,43 +270,76 @@ impl Device for HidInputDevice {
                 break;
             }
 
-            match self.raw.read(&mut self.buf) {
-                Ok(0) => break, // no data this tick (non-blocking)
-                Ok(n) => {
-                    drained += 1;
-
-                    let slice = &self.buf[..n];
-
-                    // --- DEBUG: log every raw read ---
-                    // eprintln!(
-                    //     "[HID/READ] dev={} n={} bytes: {:02x?}",
-                    //     self.fingerprint_str, n, slice,
-                    // );
-
-                    // If the parser expects an ID prefix, treat the first byte as ReportID.
-                    // Otherwise, treat the entire slice as payload and use ReportID = 0.
-                    let (report_id, payload) = if self.parser.expects_report_id_prefix() {
-                        Self::split_report_windows(slice)
-                    } else {
-                        (0, slice)
-                    };
-
-                    let ctx = ParseCtx {
-                        report_id,
-                        now: Instant::now(),
-                        meta: &self.meta,
-                        fingerprint: &self.fingerprint,
-                    };
-                    self.parser.parse(&ctx, payload, &mut events);
+            let (now, data) = match self.read_report() {
+                Ok(Some(report)) => report,
+                Ok(None) => break,
+                Err(e) if events.is_empty() => {
+                    self.flush_capture();
+                    return Err(e);
                 }
                 Err(e) => {
-                    eprintln!(
-                        "[HID/ERROR] dev={} read failed: {:?}",
-                        self.fingerprint_str, e
-                    );
+                    self.pending_error = Some(e);
                     break;
                 }
+            };
+            drained += 1;
+
+            // If the parser expects an ID prefix, treat the first byte as ReportID.
+            // Otherwise, treat the entire slice as payload and use ReportID = 0.
+            let (report_id, payload) = if self.parser.expects_report_id_prefix() {
+                Self::split_report_windows(&data)
+            } else {
+                (0, &data[..])
+            };
+
+            self.report_time.get_or_insert(now);
+            if let Some(capture) = &mut self.capture {
+                if capture.write_report(now, report_id, payload).is_err() {
+                    // Disk full or similar: stop capturing rather than fail input.
+                    self.capture = None;
+                }
             }
+            let ctx = ParseCtx {
+                report_id,
+                now,
+                meta: &self.meta,
+                fingerprint: &self.fingerprint,
+            };
+            self.parser.parse(&ctx, payload, &mut events);
+        }
+        self.flush_capture();
+        Ok(events)
+    }
+
+    /// Fetch each input report the parser declares with `HidD_GetInputReport` and parse it.
+    ///
+    /// Devices that reject `Get_Report` (some firmware only answers on the interrupt pipe)
+    /// simply contribute nothing and start from neutral.
+    fn initial_state(&mut self) -> Vec<InputKind> {
+        let mut events = Vec::new();
+        for report_id in self.parser.input_report_ids() {
+            // The buffer always carries the report ID byte here, `0` for unnumbered reports.
+            let mut report = vec![0u8; self.buf.len().max(2)];
+            report[0] = report_id;
+            let n = match self.raw.get_input_report(&mut report) {
+                Ok(n) if n > 1 => n.min(report.len()),
+                Ok(_) => continue,
+                Err(_e) => {
+                    #[cfg(feature = "debug-log")]
+                    eprintln!(
+                        "[HID/PRIME] dev={} rid={report_id} get_input_report failed: {_e:?}",
+                        self.fingerprint_str
+                    );
+                    continue;
+                }
+            };
+            let ctx = ParseCtx {
+                report_id,
+                now: Instant::now(),
+                meta: &self.meta,
+                fingerprint: &self.fingerprint,
+            };
+            self.parser.parse(&ctx, &report[1..n], &mut events);
         }
         events
     }
@@ -174,4 +356,108 @@ impl Device for HidInputDevice {
     fn describe(&self) -> Vec<ChannelDesc> {
         self.parser.describe()
     }
+    fn report_id_diagnostics(&self) -> Option<ReportIdDiagnostics> {
+        self.parser.report_id_diagnostics()
+    }
+    fn battery(&self) -> Option<BatteryStatus> {
+        self.parser.battery()
+    }
+    fn report_time(&self) -> Option<Instant> {
+        self.report_time
+    }
+    /// Capture raw reports and the report descriptor (see [`report_capture`](crate::report_capture)).
+    fn capture_reports(&mut self, path: Option<&Path>) -> Result<()> {
+        self.capture = None;
+        let Some(path) = path else {
+            return Ok(());
+        };
+        let mut descriptor = vec![0u8; 4096];
+        let descriptor = match self.raw.get_report_descriptor(&mut descriptor) {
+            Ok(n) => Some(descriptor[..n].iter().map(|b| format!("{b:02x}")).collect()),
+            Err(_) => None,
+        };
+        let header = CaptureHeader {
+            meta: self.meta.clone(),
+            descriptor,
+            report_id_prefix: self.parser.expects_report_id_prefix(),
+        };
+        self.capture = Some(ReportCaptureWriter::create(path, &header)?);
+        Ok(())
+    }
+    /// Write the parser-encoded rumble output report (see [`ReportParser::encode_rumble`]).
+    fn set_rumble(&mut self, low: f32, high: f32) -> Result<()> {
+        let report = self
+            .parser
+            .encode_rumble(low.clamp(0.0, 1.0), high.clamp(0.0, 1.0))
+            .ok_or(Error::Unsupported("rumble"))?;
+        self.raw
+            .write(&report)
+            .map_err(|e| self.write_error("rumble", e))?;
+        Ok(())
+    }
+    /// Write the parser-encoded output report for `cmd` (see [`ReportParser::encode_output`]).
+    ///
+    /// [`OutputCommand::RawReport`] is written as-is.
+    fn send_output(&mut self, cmd: &OutputCommand) -> Result<()> {
+        let report = match cmd {
+            OutputCommand::RawReport(report) => report.clone(),
+            _ => self
+                .parser
+                .encode_output(cmd)
+                .ok_or(Error::Unsupported("output command"))?,
+        };
+        self.raw
+            .write(&report)
+            .map_err(|e| self.write_error("output report", e))?;
+        Ok(())
+    }
+    /// Run the PID upload transaction: Create New Effect → Block Load → effect parameters.
+    fn upload_effect(&mut self, effect: &FfbEffect) -> Result<EffectHandle> {
+        let enc = self
+            .parser
+            .ffb()
+            .ok_or(Error::Unsupported("force feedback"))?;
+
+        let create = enc
+            .create_effect_report(&effect.kind)
+            .ok_or(Error::Unsupported("this force feedback effect type"))?;
+        self.raw
+            .send_feature_report(&create)
+            .map_err(|e| self.write_error("ffb create effect", e))?;
+
+        let mut load = enc
+            .block_load_buffer()
+            .ok_or(Error::Unsupported("force feedback"))?;
+        let n = self
+            .raw
+            .get_feature_report(&mut load)
+            .map_err(|e| self.write_error("ffb block load", e))?;
+        let handle = enc
+            .parse_block_load(&load[..n.min(load.len())])
+            .ok_or_else(|| Error::Write {
+                device: self.fingerprint_str.clone(),
+                message: "device refused effect allocation".into(),
+            })?;
+
+        let reports = enc
+            .effect_reports(handle, effect)
+            .ok_or(Error::Unsupported("this force feedback effect type"))?;
+        for r in reports {
+            self.raw
+                .write(&r)
+                .map_err(|e| self.write_error("ffb set effect", e))?;
+        }
+        Ok(handle)
+    }
+    fn play_effect(&mut self, handle: EffectHandle, op: EffectOp) -> Result<()> {
+        let report = self
+            .parser
+            .ffb()
+            .and_then(|enc| enc.operation_report(handle, op))
+            .ok_or(Error::Unsupported("force feedback"))?;
+        self.raw
+            .write(&report)
+            .map_err(|e| self.write_error("ffb operation", e))?;
+        Ok(())
+    }
 }
@@ -4,9 +4,12 @@
 //!
 //! - Enumerate HID devices via `hidapi`
 //! - Filter out obvious non-game controls (mouse/keyboard) and XInput HID-compat endpoints
+//! - Drop HID interfaces whose PnP container ID matches an XInput pad, so the same physical
+//!   controller is never listed twice
 //! - Construct a HIDP-driven parser (`WinHidpParser`) to decode reports consistently
+//!   (DualSense pads use the dedicated [`DualSenseParser`] instead)
 //! - Wrap successfully parsed devices as [`HidInputDevice`]
-//! - Add XInput controllers (0..4) as [`XInputDevice`] entries
+//! - Add connected XInput slots (0..4) as [`XInputDevice`] entries
 //!
 //! ## `probe_devices` vs `probe_devices_with_debug`
 //! - [`probe_devices`] returns only successfully wrapped devices.
@@ -18,23 +21,29 @@
 
 #![cfg(target_os = "windows")]
 
+use crate::backends::dualsense::{DualSenseParser, DUALSENSE_EDGE_PID};
+use crate::backends::windows::container_id::container_id;
 use crate::backends::windows::hid_device::HidInputDevice;
 use crate::backends::windows::hidp_parser::WinHidpParser;
-use crate::backends::windows::xinput_devices::XInputDevice;
+use crate::backends::windows::xinput_devices::{slot_connected, XInputDevice, SLOT_COUNT};
 use crate::device::{Device, DeviceFingerprint};
 use crate::event::{ChannelDesc, ChannelKind};
 use crate::metadata::DeviceMeta;
+use crate::quirks::QuirkDb;
+use crate::Error;
 use hidapi::{DeviceInfo, HidApi};
+use std::collections::HashSet;
 
 /// High-level reason why we were unable to construct a HIDP parser.
 ///
-/// At the moment this is coarse-grained (`Unknown`), but it gives us a clear
-/// "parser failed vs filter failed vs wrap failed" split. We can refine this
-/// later by plumbing more detailed error codes out of `WinHidpParser::new`.
+/// Gives a clear "parser failed vs filter failed vs wrap failed" split, with the
+/// underlying [`Error`] (open failure, HIDP status code) when known.
 #[derive(Debug, Clone)]
 pub enum ParserFailKind {
-    /// Construction of `WinHidpParser` returned `None` for an unspecified reason.
+    /// Construction of `WinHidpParser` failed for an unspecified reason.
     Unknown,
+    /// Construction of `WinHidpParser` failed with this error.
+    Failed(Error),
 }
 
 /// Where along the discovery pipeline a device was rejected or failed.
@@ -43,12 +52,15 @@ pub enum DropStage {
     /// Rejected by `accept_device` (usage_page/usage/XInput filter).
     FilterRejected,
 
+    /// Shares its container ID with an XInput pad (already listed as an XInput slot).
+    XInputDuplicate,
+
     /// HIDP descriptor-driven parser failed to construct.
     ParserFailed(ParserFailKind),
 
     /// We were unable to open or wrap the device as a `Device`.
     ///
-    /// This typically corresponds to `HidInputDevice::new` returning `None`.
+    /// Carries the message of the error returned by `HidInputDevice::new`.
     DeviceWrapFailed(String),
 }
 
@@ -140,9 +152,7 @@ fn accept_device(info: &DeviceInfo) -> bool {
     }
 
     // ⬇️ Generic “this is an XInput HID compat endpoint, skip it”
-    let path = info.path().to_string_lossy();
-    if is_generic_desktop && u == 0x05 && path.contains("IG_") {
-        // Generic Desktop Gamepad, Interface Group = XInput-style HID.
+    if is_xinput_endpoint(info) {
         // We already have it via XInput APIs, so don't double-count.
         return false;
     }
@@ -155,6 +165,33 @@ fn accept_device(info: &DeviceInfo) -> bool {
     true
 }
 
+/// Is this the HID-compat endpoint of an XInput pad?
+///
+/// Generic Desktop Gamepad with an `IG_` (interface group) path segment.
+fn is_xinput_endpoint(info: &DeviceInfo) -> bool {
+    info.usage_page() == 0x01
+        && info.usage() == 0x05
+        && info.path().to_string_lossy().contains("IG_")
+}
+
+/// Container IDs of physical pads that expose an XInput endpoint.
+///
+/// Other HID interfaces in these containers (vendor pages, a second gamepad collection)
+/// belong to a controller that is already surfaced through XInput.
+fn xinput_containers(api: &HidApi) -> HashSet<String> {
+    api.device_list()
+        .filter(|info| is_xinput_endpoint(info))
+        .filter_map(|info| container_id(&info.path().to_string_lossy()))
+        .collect()
+}
+
+/// `true` if `meta` belongs to a pad already listed via XInput.
+fn is_xinput_duplicate(meta: &DeviceMeta, xinput: &HashSet<String>) -> bool {
+    meta.container_id
+        .as_ref()
+        .is_some_and(|c| xinput.contains(c))
+}
+
 /// Debug-aware variant of `probe_devices` that returns both the discovered
 /// devices and a per-HID-entry debug record describing how each device fared
 /// in the discovery pipeline.
@@ -168,6 +205,7 @@ fn accept_device(info: &DeviceInfo) -> bool {
 pub fn probe_devices_with_debug(api: &HidApi) -> (Vec<Box<dyn Device>>, Vec<DeviceDebugInfo>) {
     let mut devices: Vec<Box<dyn Device>> = Vec::new();
     let mut debug: Vec<DeviceDebugInfo> = Vec::new();
+    let xinput = xinput_containers(api);
 
     // 1) HID devices
     for info in api.device_list() {
@@ -200,79 +238,133 @@ pub fn probe_devices_with_debug(api: &HidApi) -> (Vec<Box<dyn Device>>, Vec<Devi
         }
         dbg.accepted_by_filter = true;
 
+        let meta = meta(info);
+        if is_xinput_duplicate(&meta, &xinput) {
+            dbg.drop_stage = Some(DropStage::XInputDuplicate);
+            debug.push(dbg);
+            continue;
+        }
+
+        // Devices with a dedicated parser bypass HIDP.
+        if let Some(parser) = dualsense_parser(info) {
+            match HidInputDevice::new(info, api, parser, fingerprint(info), meta) {
+                Ok(dev) => {
+                    dbg.caps = Some(caps_summary(&dev.describe()));
+                    devices.push(Box::new(dev) as Box<dyn Device>);
+                }
+                Err(e) => {
+                    dbg.drop_stage = Some(DropStage::DeviceWrapFailed(e.to_string()));
+                }
+            }
+            debug.push(dbg);
+            continue;
+        }
+
         // Attempt to build the HIDP parser.
         let parser = match WinHidpParser::new(info) {
-            Some(p) => p,
-            None => {
-                dbg.drop_stage = Some(DropStage::ParserFailed(ParserFailKind::Unknown));
+            Ok(p) => p,
+            Err(e) => {
+                dbg.drop_stage = Some(DropStage::ParserFailed(ParserFailKind::Failed(e)));
                 debug.push(dbg);
                 continue;
             }
         };
 
-        // Attempt to wrap as a HidInputDevice.
-        match HidInputDevice::new(info, api, parser, fingerprint(info), meta(info)) {
-            Some(dev) => {
+        // Attempt to wrap as a HidInputDevice, reading reports through the parser's
+        // overlapped handle.
+        let read_handle = parser.raw_handle();
+        let wrapped = HidInputDevice::new(info, api, parser, fingerprint(info), meta)
+            // SAFETY: the handle belongs to the parser the device now owns.
+            .map(|dev| unsafe { dev.with_overlapped_reads(read_handle) });
+        match wrapped {
+            Ok(dev) => {
                 // Derive a coarse caps summary from Device::describe().
-                let descs: Vec<ChannelDesc> = dev.describe();
-                let mut axes = 0usize;
-                let mut buttons = 0usize;
-                let mut hats = 0usize;
-                for ch in &descs {
-                    match ch.kind {
-                        ChannelKind::Axis => axes += 1,
-                        ChannelKind::Button => buttons += 1,
-                        ChannelKind::Hat => hats += 1,
-                    }
-                }
-
-                dbg.caps = Some(CapsSummary {
-                    axes,
-                    buttons,
-                    hats,
-                    report_ids: Vec::new(), // not derived here (HIDP-internal)
-                    only_rid0: false,       // not derived here
-                });
+                dbg.caps = Some(caps_summary(&dev.describe()));
                 dbg.drop_stage = None;
                 devices.push(Box::new(dev) as Box<dyn Device>);
                 debug.push(dbg);
             }
-            None => {
-                dbg.drop_stage = Some(DropStage::DeviceWrapFailed(
-                    "HidInputDevice::new returned None".into(),
-                ));
+            Err(e) => {
+                dbg.drop_stage = Some(DropStage::DeviceWrapFailed(e.to_string()));
                 debug.push(dbg);
             }
         }
     }
 
     // 2) XInput devices (same behavior as probe_devices; no debug records yet).
-    for index in 0..4 {
-        let fp = DeviceFingerprint {
-            vendor_id: 0x045e,
-            product_id: 0x0000,
-            serial_number: Some(format!("xinput:{index}")),
-            path: Some(format!("xinput:{index}")),
-        };
+    for index in connected_xinput_slots() {
+        devices.push(Box::new(xinput_device(index)) as Box<dyn Device>);
+    }
 
-        let meta = DeviceMeta {
-            bus: Some("xinput".into()),
-            vid: Some(0x045e),
-            pid: Some(0x0000),
-            product_string: Some(format!("XInput Controller {}", index)),
-            serial_number: Some(format!("xinput:{index}")),
-            usage_page: None,
-            usage: None,
-            interface_number: None,
-            container_id: None,
-            path: Some(format!("xinput:{index}")),
-        };
+    (devices, debug)
+}
+
+/// XInput slots (`0..4`) that currently have a controller attached.
+fn connected_xinput_slots() -> impl Iterator<Item = u32> {
+    (0..SLOT_COUNT).filter(|&index| slot_connected(index))
+}
 
-        let dev = XInputDevice::new(index, fp, meta);
-        devices.push(Box::new(dev) as Box<dyn Device>);
+/// Synthetic fingerprint for XInput slot `index` (`xinput:<slot>`).
+///
+/// Serial/path are unique per slot so the usual fingerprint-based id stays stable.
+pub fn xinput_fingerprint(index: u32) -> DeviceFingerprint {
+    DeviceFingerprint {
+        vendor_id: 0x045e,  // Microsoft (arbitrary but reasonable)
+        product_id: 0x0000, // "generic XInput"
+        serial_number: Some(format!("xinput:{index}")),
+        path: Some(format!("xinput:{index}")),
     }
+}
 
-    (devices, debug)
+/// Wrap XInput slot `index` as a device.
+///
+/// Note: this is *not* virtual-device generation. It is a wrapper over the Windows
+/// XInput API surfaced through the same `Device` trait.
+pub fn xinput_device(index: u32) -> XInputDevice {
+    let meta = DeviceMeta {
+        bus: Some("xinput".into()),
+        vid: Some(0x045e),
+        pid: Some(0x0000),
+        product_string: Some(format!("XInput Controller {}", index)),
+        serial_number: Some(format!("xinput:{index}")),
+        usage_page: None,
+        usage: None,
+        interface_number: None,
+        container_id: None,
+        path: Some(format!("xinput:{index}")),
+    };
+    XInputDevice::new(index, xinput_fingerprint(index), meta)
+}
+
+/// Coarse channel counts derived from `Device::describe()`.
+fn caps_summary(descs: &[ChannelDesc]) -> CapsSummary {
+    let mut axes = 0usize;
+    let mut buttons = 0usize;
+    let mut hats = 0usize;
+    for ch in descs {
+        match ch.kind {
+            ChannelKind::Axis => axes += 1,
+            ChannelKind::Button => buttons += 1,
+            ChannelKind::Hat => hats += 1,
+        }
+    }
+    CapsSummary {
+        axes,
+        buttons,
+        hats,
+        report_ids: Vec::new(), // not derived here (HIDP-internal)
+        only_rid0: false,       // not derived here
+    }
+}
+
+/// Dedicated parser for DualSense / DualSense Edge pads (touchpad, IMU, outputs).
+fn dualsense_parser(info: &DeviceInfo) -> Option<DualSenseParser> {
+    if !DualSenseParser::matches(info.vendor_id(), info.product_id()) {
+        return None;
+    }
+    let bluetooth = matches!(info.bus_type(), hidapi::BusType::Bluetooth);
+    let edge = info.product_id() == DUALSENSE_EDGE_PID;
+    Some(DualSenseParser::new(bluetooth, edge))
 }
 
 fn fingerprint(info: &DeviceInfo) -> DeviceFingerprint {
@@ -305,7 +397,7 @@ fn meta(info: &DeviceInfo) -> DeviceMeta {
         usage_page: Some(info.usage_page()),
         usage: Some(info.usage()),
         interface_number,
-        container_id: None,
+        container_id: container_id(&info.path().to_string_lossy()),
         path: Some(info.path().to_string_lossy().to_string()),
     }
 }
@@ -314,60 +406,55 @@ fn meta(info: &DeviceInfo) -> DeviceMeta {
 ///
 /// - HID devices are enumerated via `hidapi`, filtered with [`accept_device`], and require
 ///   successful construction of a HIDP parser (`WinHidpParser`) to be included.
-/// - XInput controllers are added as up to 4 synthetic device entries (`xinput:0..3`).
+/// - XInput controllers are added for each connected slot (`xinput:0..3`); empty slots are
+///   skipped and picked up later by the manager's slot watch or a rescan.
 ///
 /// This function returns only successfully wrapped devices (no debug records).
 pub fn probe_devices(api: &HidApi) -> Vec<Box<dyn Device>> {
+    probe_devices_with_quirks(api, &QuirkDb::default())
+}
+
+/// [`probe_devices`] with a [`QuirkDb`] applied to HIDP-parsed devices (vendor usage names,
+/// axis/button semantics, device display names).
+pub fn probe_devices_with_quirks(api: &HidApi, quirks: &QuirkDb) -> Vec<Box<dyn Device>> {
     let mut out: Vec<Box<dyn Device>> = Vec::new();
+    let xinput = xinput_containers(api);
 
     // 1) HID devices (what you already had)
     for info in api.device_list() {
         if !accept_device(info) {
             continue;
         }
+        let meta = meta(info);
+        if is_xinput_duplicate(&meta, &xinput) {
+            continue;
+        }
+
+        if let Some(parser) = dualsense_parser(info) {
+            if let Ok(dev) = HidInputDevice::new(info, api, parser, fingerprint(info), meta) {
+                out.push(Box::new(dev));
+            }
+            continue;
+        }
 
         // HIDP parser is mandatory. If it fails, skip the device.
-        if let Some(parser) = WinHidpParser::new(info) {
-            if let Some(dev) = HidInputDevice::new(info, api, parser, fingerprint(info), meta(info))
-            {
+        let dq = quirks.for_device(info.vendor_id(), info.product_id());
+        if let Ok(parser) = WinHidpParser::with_quirks(info, dq) {
+            let read_handle = parser.raw_handle();
+            if let Ok(mut dev) = HidInputDevice::new(info, api, parser, fingerprint(info), meta) {
+                // SAFETY: the handle belongs to the parser the device now owns.
+                dev = unsafe { dev.with_overlapped_reads(read_handle) };
+                if let Some(name) = dq.and_then(|q| q.name.clone()) {
+                    dev = dev.with_name(name);
+                }
                 out.push(Box::new(dev));
             }
         }
     }
 
-    // 2) XInput devices (up to 4 slots)
-    {
-        for index in 0..4 {
-            // Build a synthetic fingerprint & meta for this virtual device.
-            // Main thing: serial/path are unique per slot so your existing
-            // device_uid() logic has something to chew on.
-            //
-            // Note: this is *not* virtual-device generation. It is a wrapper over the Windows
-            // XInput API surfaced through the same `Device` trait.
-
-            let fp = DeviceFingerprint {
-                vendor_id: 0x045e,  // Microsoft (arbitrary but reasonable)
-                product_id: 0x0000, // "generic XInput" - you can pick any
-                serial_number: Some(format!("xinput:{index}")),
-                path: Some(format!("xinput:{index}")),
-            };
-
-            let meta = DeviceMeta {
-                bus: Some("xinput".into()),
-                vid: Some(0x045e),
-                pid: Some(0x0000),
-                product_string: Some(format!("XInput Controller {}", index)),
-                serial_number: Some(format!("xinput:{index}")),
-                usage_page: None,
-                usage: None,
-                interface_number: None,
-                container_id: None,
-                path: Some(format!("xinput:{index}")),
-            };
-
-            let dev = XInputDevice::new(index, fp, meta);
-            out.push(Box::new(dev) as Box<dyn Device>);
-        }
+    // 2) XInput devices (connected slots only)
+    for index in connected_xinput_slots() {
+        out.push(Box::new(xinput_device(index)) as Box<dyn Device>);
     }
 
     out
@@ -0,0 +1,580 @@
+//! HID PID (Physical Interface Device) report layout and encoding (Windows/HIDP).
+//!
+//! Discovers the PID output/feature reports from a device's preparsed data and builds the
+//! reports needed to allocate, configure, and run force feedback effects. The generic effect
+//! model lives in [`crate::ffb`]; [`WinHidpParser`](super::hidp_parser::WinHidpParser) owns a
+//! [`PidLayout`] and exposes it through [`FfbEncoder`](crate::ffb::FfbEncoder).
+//!
+//! Only the reports StickUp needs are recognized:
+//! - Create New Effect (feature) → PID Block Load (feature)
+//! - Set Effect, Set Constant Force, Set Condition (output)
+//! - Effect Operation (output)
+
+use windows_sys::Win32::Devices::HumanInterfaceDevice::*;
+
+use crate::ffb::{EffectHandle, EffectOp, FfbEffect, FfbEffectKind};
+
+const PID_PAGE: u16 = 0x0F;
+const GENERIC_DESKTOP_PAGE: u16 = 0x01;
+const ORDINAL_PAGE: u16 = 0x0A;
+
+// Effect reports
+const USAGE_EFFECT_BLOCK_INDEX: u16 = 0x22;
+const USAGE_PARAMETER_BLOCK_OFFSET: u16 = 0x23;
+const USAGE_ET_CONSTANT_FORCE: u16 = 0x26;
+const USAGE_ET_SPRING: u16 = 0x40;
+const USAGE_ET_DAMPER: u16 = 0x41;
+const USAGE_DURATION: u16 = 0x50;
+const USAGE_GAIN: u16 = 0x52;
+const USAGE_AXES_ENABLE: u16 = 0x55;
+const USAGE_DIRECTION_ENABLE: u16 = 0x56;
+const USAGE_DIRECTION: u16 = 0x57;
+const USAGE_CP_OFFSET: u16 = 0x60;
+const USAGE_POSITIVE_COEFFICIENT: u16 = 0x61;
+const USAGE_NEGATIVE_COEFFICIENT: u16 = 0x62;
+const USAGE_POSITIVE_SATURATION: u16 = 0x63;
+const USAGE_NEGATIVE_SATURATION: u16 = 0x64;
+const USAGE_DEAD_BAND: u16 = 0x65;
+const USAGE_MAGNITUDE: u16 = 0x70;
+const USAGE_START_DELAY: u16 = 0xA7;
+
+// Operation / allocation
+const USAGE_OP_EFFECT_START: u16 = 0x79;
+const USAGE_OP_EFFECT_START_SOLO: u16 = 0x7A;
+const USAGE_OP_EFFECT_STOP: u16 = 0x7B;
+const USAGE_LOOP_COUNT: u16 = 0x7C;
+const USAGE_BLOCK_LOAD_SUCCESS: u16 = 0x8C;
+
+/// Duration value meaning "infinite" in the PID spec.
+const PID_INFINITE: i32 = 0xFFFF;
+
+/// One PID value or selector discovered from output/feature caps.
+#[derive(Clone, Copy, Debug)]
+struct PidField {
+    report_id: u8,
+    usage_page: u16,
+    usage: u16,
+    /// Usage of the enclosing collection (e.g. `Direction` for ordinal instances).
+    link_usage: u16,
+    link_collection: u16,
+    logical_min: i32,
+    logical_max: i32,
+    bit_size: u16,
+    /// `true` for button caps (selectors / enables), `false` for values.
+    is_selector: bool,
+}
+
+/// Report IDs of the PID reports in use, plus their field tables.
+#[derive(Clone, Debug)]
+pub(crate) struct PidLayout {
+    output_len: u16,
+    feature_len: u16,
+    output: Vec<PidField>,
+    feature: Vec<PidField>,
+
+    set_effect: u8,
+    operation: u8,
+    create_effect: u8,
+    block_load: u8,
+    constant_force: Option<u8>,
+    condition: Option<u8>,
+    /// Number of axes the device exposes in `Axes Enable` (conditions are sent per axis).
+    axes: u8,
+}
+
+impl PidLayout {
+    /// Discover the PID layout from preparsed data.
+    ///
+    /// Returns `None` unless the device declares at least Set Effect, Effect Operation,
+    /// Create New Effect, and Block Load reports.
+    pub(crate) fn discover(ppd: PHIDP_PREPARSED_DATA, caps: &HIDP_CAPS) -> Option<Self> {
+        let output = collect_fields(ppd, HidP_Output);
+        let feature = collect_fields(ppd, HidP_Feature);
+        if output.is_empty() || feature.is_empty() {
+            return None;
+        }
+
+        let set_effect = report_with(&output, &[USAGE_EFFECT_BLOCK_INDEX, USAGE_DURATION])?;
+        let operation = report_with(&output, &[USAGE_EFFECT_BLOCK_INDEX, USAGE_LOOP_COUNT])?;
+        let create_effect = report_with(&feature, &[USAGE_ET_CONSTANT_FORCE])
+            .or_else(|| report_with(&feature, &[USAGE_ET_SPRING]))?;
+        let block_load = report_with(
+            &feature,
+            &[USAGE_EFFECT_BLOCK_INDEX, USAGE_BLOCK_LOAD_SUCCESS],
+        )?;
+        let constant_force = report_with(&output, &[USAGE_EFFECT_BLOCK_INDEX, USAGE_MAGNITUDE]);
+        let condition = report_with(
+            &output,
+            &[USAGE_EFFECT_BLOCK_INDEX, USAGE_POSITIVE_COEFFICIENT],
+        );
+        let axes = output
+            .iter()
+            .filter(|f| {
+                f.report_id == set_effect
+                    && f.is_selector
+                    && f.usage_page == GENERIC_DESKTOP_PAGE
+                    && f.link_usage == USAGE_AXES_ENABLE
+            })
+            .count()
+            .clamp(1, 2) as u8;
+
+        Some(Self {
+            output_len: caps.OutputReportByteLength,
+            feature_len: caps.FeatureReportByteLength,
+            output,
+            feature,
+            set_effect,
+            operation,
+            create_effect,
+            block_load,
+            constant_force,
+            condition,
+            axes,
+        })
+    }
+
+    /// Feature report asking the device to allocate a block for `kind`.
+    pub(crate) fn create_effect_report(
+        &self,
+        ppd: PHIDP_PREPARSED_DATA,
+        kind: &FfbEffectKind,
+    ) -> Option<Vec<u8>> {
+        let mut r = self.builder(ppd, HidP_Feature, self.create_effect)?;
+        r.select(effect_type_usage(kind)).then_some(())?;
+        Some(r.finish())
+    }
+
+    /// Zeroed Block Load feature buffer with the report ID set.
+    pub(crate) fn block_load_buffer(&self) -> Option<Vec<u8>> {
+        let len = self.feature_len as usize;
+        if len < 2 {
+            return None;
+        }
+        let mut buf = vec![0u8; len];
+        buf[0] = self.block_load;
+        Some(buf)
+    }
+
+    /// Decode a Block Load feature report; `None` unless the status is "success".
+    pub(crate) fn parse_block_load(
+        &self,
+        ppd: PHIDP_PREPARSED_DATA,
+        report: &[u8],
+    ) -> Option<EffectHandle> {
+        if report.first() != Some(&self.block_load) {
+            return None;
+        }
+        let mut buf = report.to_vec();
+        buf.resize(self.feature_len as usize, 0);
+        let status = self
+            .feature
+            .iter()
+            .find(|f| f.report_id == self.block_load && f.usage == USAGE_BLOCK_LOAD_SUCCESS)?;
+        let mut usages = [0u16; 8];
+        let mut n = usages.len() as u32;
+        let ok = unsafe {
+            HidP_GetUsages(
+                HidP_Feature,
+                PID_PAGE,
+                status.link_collection,
+                usages.as_mut_ptr(),
+                &mut n,
+                ppd,
+                buf.as_mut_ptr(),
+                buf.len() as u32,
+            )
+        };
+        if ok != HIDP_STATUS_SUCCESS || !usages[..n as usize].contains(&USAGE_BLOCK_LOAD_SUCCESS) {
+            return None;
+        }
+
+        let index = self.field(&self.feature, self.block_load, USAGE_EFFECT_BLOCK_INDEX)?;
+        let mut value = 0u32;
+        let ok = unsafe {
+            HidP_GetUsageValue(
+                HidP_Feature,
+                PID_PAGE,
+                index.link_collection,
+                USAGE_EFFECT_BLOCK_INDEX,
+                &mut value,
+                ppd,
+                buf.as_mut_ptr(),
+                buf.len() as u32,
+            )
+        };
+        (ok == HIDP_STATUS_SUCCESS && value != 0).then_some(EffectHandle(value as u8))
+    }
+
+    /// Type-specific parameter reports followed by the Set Effect report.
+    pub(crate) fn effect_reports(
+        &self,
+        ppd: PHIDP_PREPARSED_DATA,
+        handle: EffectHandle,
+        effect: &FfbEffect,
+    ) -> Option<Vec<Vec<u8>>> {
+        let mut out = Vec::new();
+
+        match effect.kind {
+            FfbEffectKind::ConstantForce { magnitude } => {
+                let mut r = self.builder(ppd, HidP_Output, self.constant_force?)?;
+                r.value(USAGE_EFFECT_BLOCK_INDEX, handle.0 as i32);
+                r.signed(USAGE_MAGNITUDE, magnitude);
+                out.push(r.finish());
+            }
+            FfbEffectKind::Spring {
+                coefficient,
+                offset,
+                deadband,
+            } => out.extend(self.condition_reports(ppd, handle, coefficient, offset, deadband)?),
+            FfbEffectKind::Damper { coefficient } => {
+                out.extend(self.condition_reports(ppd, handle, coefficient, 0.0, 0.0)?)
+            }
+        }
+
+        let mut r = self.builder(ppd, HidP_Output, self.set_effect)?;
+        r.value(USAGE_EFFECT_BLOCK_INDEX, handle.0 as i32);
+        r.select(effect_type_usage(&effect.kind));
+        match effect.duration {
+            Some(d) => r.value(
+                USAGE_DURATION,
+                d.as_millis().min(PID_INFINITE as u128 - 1) as i32,
+            ),
+            None => r.infinite(USAGE_DURATION),
+        };
+        r.value(
+            USAGE_START_DELAY,
+            effect.start_delay.as_millis().min(PID_INFINITE as u128 - 1) as i32,
+        );
+        r.unsigned(USAGE_GAIN, effect.gain);
+        r.enable_axes(self.axes);
+        r.select(USAGE_DIRECTION_ENABLE);
+        r.direction(effect.direction_deg);
+        out.push(r.finish());
+        Some(out)
+    }
+
+    /// Effect Operation output report.
+    pub(crate) fn operation_report(
+        &self,
+        ppd: PHIDP_PREPARSED_DATA,
+        handle: EffectHandle,
+        op: EffectOp,
+    ) -> Option<Vec<u8>> {
+        let (selector, loops) = match op {
+            EffectOp::Start { loop_count } => (USAGE_OP_EFFECT_START, loop_count.max(1)),
+            EffectOp::StartSolo { loop_count } => (USAGE_OP_EFFECT_START_SOLO, loop_count.max(1)),
+            EffectOp::Stop => (USAGE_OP_EFFECT_STOP, 0),
+        };
+        let mut r = self.builder(ppd, HidP_Output, self.operation)?;
+        r.value(USAGE_EFFECT_BLOCK_INDEX, handle.0 as i32);
+        r.select(selector).then_some(())?;
+        r.value(USAGE_LOOP_COUNT, loops as i32);
+        Some(r.finish())
+    }
+
+    fn condition_reports(
+        &self,
+        ppd: PHIDP_PREPARSED_DATA,
+        handle: EffectHandle,
+        coefficient: f32,
+        offset: f32,
+        deadband: f32,
+    ) -> Option<Vec<Vec<u8>>> {
+        let rid = self.condition?;
+        let mut out = Vec::with_capacity(self.axes as usize);
+        for axis in 0..self.axes {
+            let mut r = self.builder(ppd, HidP_Output, rid)?;
+            r.value(USAGE_EFFECT_BLOCK_INDEX, handle.0 as i32);
+            r.value(USAGE_PARAMETER_BLOCK_OFFSET, axis as i32);
+            r.signed(USAGE_CP_OFFSET, offset);
+            r.signed(USAGE_POSITIVE_COEFFICIENT, coefficient);
+            r.signed(USAGE_NEGATIVE_COEFFICIENT, coefficient);
+            r.unsigned(USAGE_POSITIVE_SATURATION, 1.0);
+            r.unsigned(USAGE_NEGATIVE_SATURATION, 1.0);
+            r.unsigned(USAGE_DEAD_BAND, deadband);
+            out.push(r.finish());
+        }
+        Some(out)
+    }
+
+    fn builder(
+        &self,
+        ppd: PHIDP_PREPARSED_DATA,
+        report_type: HIDP_REPORT_TYPE,
+        report_id: u8,
+    ) -> Option<ReportBuilder<'_>> {
+        let (len, fields) = if report_type == HidP_Feature {
+            (self.feature_len, &self.feature)
+        } else {
+            (self.output_len, &self.output)
+        };
+        if len < 2 {
+            return None;
+        }
+        let mut buf = vec![0u8; len as usize];
+        buf[0] = report_id;
+        Some(ReportBuilder {
+            ppd,
+            report_type,
+            report_id,
+            fields,
+            buf,
+        })
+    }
+
+    fn field<'a>(&self, fields: &'a [PidField], report_id: u8, usage: u16) -> Option<&'a PidField> {
+        fields
+            .iter()
+            .find(|f| f.report_id == report_id && f.usage_page == PID_PAGE && f.usage == usage)
+    }
+}
+
+/// Fills one PID report through `HidP_SetUsageValue` / `HidP_SetUsages`.
+///
+/// Missing optional fields are skipped silently; devices only declare what they support.
+struct ReportBuilder<'a> {
+    ppd: PHIDP_PREPARSED_DATA,
+    report_type: HIDP_REPORT_TYPE,
+    report_id: u8,
+    fields: &'a [PidField],
+    buf: Vec<u8>,
+}
+
+impl ReportBuilder<'_> {
+    fn find(&self, page: u16, usage: u16, link_usage: Option<u16>) -> Option<PidField> {
+        self.fields
+            .iter()
+            .find(|f| {
+                f.report_id == self.report_id
+                    && f.usage_page == page
+                    && f.usage == usage
+                    && link_usage.map_or(true, |lu| f.link_usage == lu)
+            })
+            .copied()
+    }
+
+    /// Write a raw logical value (clamped to the field's logical range).
+    fn value(&mut self, usage: u16, raw: i32) -> bool {
+        match self.find(PID_PAGE, usage, None) {
+            Some(f) if !f.is_selector => {
+                let (lo, hi) = (
+                    f.logical_min.min(f.logical_max),
+                    f.logical_min.max(f.logical_max),
+                );
+                self.set_value(&f, raw.clamp(lo, hi))
+            }
+            _ => false,
+        }
+    }
+
+    /// Write [`PID_INFINITE`] unclamped: it lies outside the logical range devices declare
+    /// for durations (typically `0..=32767`), and clamping would turn it into a finite time.
+    fn infinite(&mut self, usage: u16) -> bool {
+        match self.find(PID_PAGE, usage, None) {
+            Some(f) if !f.is_selector => self.set_value(&f, PID_INFINITE),
+            _ => false,
+        }
+    }
+
+    /// Write a normalized `[-1, 1]` value.
+    fn signed(&mut self, usage: u16, v: f32) -> bool {
+        match self.find(PID_PAGE, usage, None) {
+            Some(f) if !f.is_selector => {
+                let v = v.clamp(-1.0, 1.0);
+                let raw = if f.logical_min < 0 {
+                    (v * f.logical_max as f32).round() as i32
+                } else {
+                    let span = (f.logical_max - f.logical_min) as f32;
+                    f.logical_min + ((v + 1.0) * 0.5 * span).round() as i32
+                };
+                self.set_value(&f, raw)
+            }
+            _ => false,
+        }
+    }
+
+    /// Write a normalized `[0, 1]` value.
+    fn unsigned(&mut self, usage: u16, v: f32) -> bool {
+        match self.find(PID_PAGE, usage, None) {
+            Some(f) if !f.is_selector => {
+                let lo = f.logical_min.max(0);
+                let span = (f.logical_max - lo) as f32;
+                let raw = lo + (v.clamp(0.0, 1.0) * span).round() as i32;
+                self.set_value(&f, raw)
+            }
+            _ => false,
+        }
+    }
+
+    /// Polar direction: first `Ordinal` instance inside the `Direction` collection.
+    fn direction(&mut self, degrees: f32) -> bool {
+        match self.find(ORDINAL_PAGE, 1, Some(USAGE_DIRECTION)) {
+            Some(f) => {
+                let span = (f.logical_max - f.logical_min + 1) as f32;
+                let raw = f.logical_min + (degrees.rem_euclid(360.0) / 360.0 * span) as i32;
+                self.set_value(&f, raw.min(f.logical_max))
+            }
+            None => false,
+        }
+    }
+
+    /// Enable the first `count` Generic Desktop axes in `Axes Enable`.
+    fn enable_axes(&mut self, count: u8) {
+        let mut axes: Vec<PidField> = self
+            .fields
+            .iter()
+            .filter(|f| {
+                f.report_id == self.report_id
+                    && f.is_selector
+                    && f.usage_page == GENERIC_DESKTOP_PAGE
+                    && f.link_usage == USAGE_AXES_ENABLE
+            })
+            .copied()
+            .collect();
+        axes.sort_by_key(|f| f.usage);
+        for f in axes.into_iter().take(count as usize) {
+            self.set_selector(&f);
+        }
+    }
+
+    /// Set a PID-page selector (effect type, operation, enable flag) in this report.
+    fn select(&mut self, usage: u16) -> bool {
+        match self.find(PID_PAGE, usage, None) {
+            Some(f) if f.is_selector => self.set_selector(&f),
+            _ => false,
+        }
+    }
+
+    fn set_selector(&mut self, f: &PidField) -> bool {
+        let mut usage = f.usage;
+        let mut n: u32 = 1;
+        let status = unsafe {
+            HidP_SetUsages(
+                self.report_type,
+                f.usage_page,
+                f.link_collection,
+                &mut usage,
+                &mut n,
+                self.ppd,
+                self.buf.as_mut_ptr(),
+                self.buf.len() as u32,
+            )
+        };
+        status == HIDP_STATUS_SUCCESS
+    }
+
+    fn set_value(&mut self, f: &PidField, raw: i32) -> bool {
+        // HIDP expects the raw bit pattern; mask negative values to the field width.
+        let bits = f.bit_size.clamp(1, 32) as u32;
+        let mask = if bits == 32 {
+            u32::MAX
+        } else {
+            (1u32 << bits) - 1
+        };
+        let status = unsafe {
+            HidP_SetUsageValue(
+                self.report_type,
+                f.usage_page,
+                f.link_collection,
+                f.usage,
+                (raw as u32) & mask,
+                self.ppd,
+                self.buf.as_mut_ptr(),
+                self.buf.len() as u32,
+            )
+        };
+        if status != HIDP_STATUS_SUCCESS {
+            #[cfg(feature = "debug-log")]
+            eprintln!(
+                "[HIDP/PID] SetUsageValue rid={} u=0x{:02x} failed: status=0x{:08x}",
+                self.report_id, f.usage, status as u32
+            );
+            return false;
+        }
+        true
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+fn effect_type_usage(kind: &FfbEffectKind) -> u16 {
+    match kind {
+        FfbEffectKind::ConstantForce { .. } => USAGE_ET_CONSTANT_FORCE,
+        FfbEffectKind::Spring { .. } => USAGE_ET_SPRING,
+        FfbEffectKind::Damper { .. } => USAGE_ET_DAMPER,
+    }
+}
+
+/// First report ID whose fields include every usage in `usages` (PID page).
+fn report_with(fields: &[PidField], usages: &[u16]) -> Option<u8> {
+    let mut ids: Vec<u8> = fields.iter().map(|f| f.report_id).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    ids.into_iter().find(|&rid| {
+        usages.iter().all(|&u| {
+            fields
+                .iter()
+                .any(|f| f.report_id == rid && f.usage_page == PID_PAGE && f.usage == u)
+        })
+    })
+}
+
+/// Flatten button and value caps of `report_type` into [`PidField`]s.
+fn collect_fields(ppd: PHIDP_PREPARSED_DATA, report_type: HIDP_REPORT_TYPE) -> Vec<PidField> {
+    let mut out = Vec::new();
+    let button_caps = super::hidp_parser::enumerate_button_caps(ppd, report_type);
+    for c in button_caps.unwrap_or_default() {
+        let (lo, hi) = unsafe {
+            if c.IsRange != 0 {
+                (c.Anonymous.Range.UsageMin, c.Anonymous.Range.UsageMax)
+            } else {
+                (c.Anonymous.NotRange.Usage, c.Anonymous.NotRange.Usage)
+            }
+        };
+        for usage in lo..=hi {
+            out.push(PidField {
+                report_id: c.ReportID,
+                usage_page: c.UsagePage,
+                usage,
+                link_usage: c.LinkUsage,
+                link_collection: c.LinkCollection,
+                logical_min: 0,
+                logical_max: 1,
+                bit_size: 1,
+                is_selector: true,
+            });
+        }
+    }
+    let value_caps = super::hidp_parser::enumerate_value_caps(ppd, report_type);
+    for c in value_caps.unwrap_or_default() {
+        let (lo, hi) = unsafe {
+            if c.IsRange != 0 {
+                (c.Anonymous.Range.UsageMin, c.Anonymous.Range.UsageMax)
+            } else {
+                (c.Anonymous.NotRange.Usage, c.Anonymous.NotRange.Usage)
+            }
+        };
+        let mut logical_max = c.LogicalMax;
+        if c.LogicalMin >= 0 && logical_max < c.LogicalMin && (1..32).contains(&c.BitSize) {
+            // Unsigned maximum written as a short signed item; see `normalize_values` in
+            // the HIDP parser.
+            logical_max = (logical_max as u32 & ((1u32 << c.BitSize) - 1)) as i32;
+        }
+        for usage in lo..=hi {
+            out.push(PidField {
+                report_id: c.ReportID,
+                usage_page: c.UsagePage,
+                usage,
+                link_usage: c.LinkUsage,
+                link_collection: c.LinkCollection,
+                logical_min: c.LogicalMin,
+                logical_max,
+                bit_size: c.BitSize,
+                is_selector: false,
+            });
+        }
+    }
+    out
+}
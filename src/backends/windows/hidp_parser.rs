@@ -7,6 +7,10 @@
 //! - Axes (any bit widths, signed/unsigned) via `HidP_GetUsageValue`
 //! - Hat Switch (usage 0x39): standardizes to **slot** values (-1/0..7) by default
 //!
+//! Single-report devices whose fields are all plain bit ranges (most gamepads) skip the
+//! per-report HIDP calls: their bit offsets are probed once at construction and reports are
+//! decoded with a [`FixedLayout`] (see [`fastpath`](crate::fastpath)).
+//!
 //! It prefers correctness for HOTAS/HOSAS/pedals without per-brand code.
 //!
 //! ## Hat policy
@@ -35,7 +39,7 @@
 //! use stickup::device::{ReportParser, ParseCtx};
 //!
 //! let info: hidapi::DeviceInfo = /* from HidApi::device_list() */;
-//! if let Some(mut p) = WinHidpParser::new(&info) {
+//! if let Ok(mut p) = WinHidpParser::new(&info) {
 //!     // ctx.report_id should be the runtime report ID for `payload`.
 //!     let ctx = ParseCtx { report_id: 1, now: std::time::Instant::now(), meta: /* ... */, fingerprint: /* ... */ };
 //!     let mut out = Vec::new();
@@ -51,16 +55,25 @@ use std::os::windows::ffi::OsStrExt;
 
 use hidapi::DeviceInfo;
 
-use crate::device::{ParseCtx, ReportParser};
+use super::hid_pid::PidLayout;
+use crate::descriptor::{
+    classify_hat, collection_usage_name, hat_value_to_slot, normalize_axis_value, sign_extend,
+    usage_name,
+};
+use crate::device::{BatteryLevel, BatteryStatus, ParseCtx, ReportIdDiagnostics, ReportParser};
+use crate::ffb::{EffectHandle, EffectOp, FfbEffect, FfbEffectKind, FfbEncoder};
 
-use crate::event::{ChannelDesc, ChannelKind, InputKind};
+use crate::event::{ChannelDesc, ChannelKind, ChannelUnit, InputKind};
+use crate::fastpath::{FixedButton, FixedLayout, FixedValue, HatEncoding};
+use crate::quirks::{DeviceQuirks, MatrixLayout, UsageKind};
+use crate::Error;
 
 use windows_sys::Win32::Devices::HumanInterfaceDevice::*;
 use windows_sys::Win32::Foundation::{
     CloseHandle, GetLastError, GENERIC_READ, GENERIC_WRITE, HANDLE, INVALID_HANDLE_VALUE, NTSTATUS,
 };
 use windows_sys::Win32::Storage::FileSystem::{
-    CreateFileW, FILE_ATTRIBUTE_NORMAL, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
+    CreateFileW, FILE_FLAG_OVERLAPPED, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
 };
 
 const USE_LINK_COLLECTION_FOR_VALUES: bool = true;
@@ -76,6 +89,74 @@ struct ButtonField {
     link_collection: u16,
     // The concrete usage codes this cap covers (expanded from range where needed).
     usages: Vec<u16>,
+    // `usage_tables` entries for (report_id, usage_page, link_collection) and for the
+    // RID-0 fallback key; assigned once button indices are known.
+    table: Option<usize>,
+    fallback_table: Option<usize>,
+}
+
+/// Sentinel for usages without a button in a [`UsageTable`].
+const NO_BUTTON: u16 = u16::MAX;
+
+/// Dense usage → button index lookup for one (report_id, usage_page, link_collection).
+///
+/// Replaces a hash lookup per pressed button per report with an array index.
+#[derive(Clone, Debug)]
+struct UsageTable {
+    report_id: u8,
+    usage_page: u16,
+    link_collection: u16,
+    usage_min: u16,
+    // Indexed by `usage - usage_min`; `NO_BUTTON` for gaps.
+    index: Vec<u16>,
+}
+
+impl UsageTable {
+    #[inline]
+    fn get(&self, usage: u16) -> Option<u16> {
+        let i = usize::from(usage.checked_sub(self.usage_min)?);
+        self.index.get(i).copied().filter(|&b| b != NO_BUTTON)
+    }
+}
+
+/// Table for (report_id, usage_page, link_collection), if any button uses that key.
+fn find_table(tables: &[UsageTable], rid: u8, page: u16, lc: u16) -> Option<usize> {
+    tables
+        .iter()
+        .position(|t| t.report_id == rid && t.usage_page == page && t.link_collection == lc)
+}
+
+/// Build one dense table per button key group from the assigned button order.
+fn usage_tables(buttons_by_index: &[(u8, u16, u16, u16)]) -> Vec<UsageTable> {
+    let mut tables: Vec<UsageTable> = Vec::new();
+    for &(rid, page, usage, lc) in buttons_by_index {
+        match find_table(&tables, rid, page, lc) {
+            Some(t) => {
+                let t = &mut tables[t];
+                t.usage_min = t.usage_min.min(usage);
+            }
+            None => tables.push(UsageTable {
+                report_id: rid,
+                usage_page: page,
+                link_collection: lc,
+                usage_min: usage,
+                index: Vec::new(),
+            }),
+        }
+    }
+    for (btn, &(rid, page, usage, lc)) in buttons_by_index.iter().enumerate() {
+        let Some(t) = find_table(&tables, rid, page, lc) else {
+            continue;
+        };
+        let t = &mut tables[t];
+        let i = usize::from(usage - t.usage_min);
+        if t.index.len() <= i {
+            t.index.resize(i + 1, NO_BUTTON);
+        }
+        // Later duplicates win, as with the previous map-based lookup.
+        t.index[i] = btn as u16;
+    }
+    tables
 }
 
 /// One normalized value (axis/hat) field.
@@ -87,10 +168,120 @@ struct ValueField {
     link_collection: u16,
     logical_min: i32,
     logical_max: i32,
-    is_hat: bool,            // usage == 0x39 on Generic Desktop
-    hat_is_degrees: bool,    // true if descriptor suggests degrees rather than 0..7
-    axis_index: Option<u16>, // assigned index for AxisMoved
-    hat_index: Option<u16>,  // assigned index for HatChanged
+    is_hat: bool,                 // usage == 0x39 on Generic Desktop
+    hat_is_degrees: bool,         // true if descriptor suggests degrees rather than 0..7
+    axis_index: Option<u16>,      // assigned index for AxisMoved
+    hat_index: Option<u16>,       // assigned index for HatChanged
+    string_index: u16,            // descriptor string (0 = none)
+    name: Option<String>,         // quirk-provided display name
+    unit: Option<ChannelUnit>,    // quirk-provided unit (overrides usage knowledge)
+    as_button: bool,              // quirk: expose as on/off button (non-zero = pressed)
+    button_index: Option<u16>,    // assigned index for value-backed buttons
+    matrix: Option<MatrixLayout>, // quirk: packed button matrix layout
+    bit_size: u16,                // bits per report element
+    report_count: u16,            // elements (> 1 for value arrays)
+    signed: bool,                 // sign-extend raw values (logical_min < 0, or quirk)
+    relative: bool,               // reports deltas (RelativeMoved), not positions
+}
+
+/// PID-page usages used by rumble-style output reports (Xbox BT, many wireless pads).
+const PID_PAGE: u16 = 0x0F;
+const PID_USAGE_MAGNITUDE: u16 = 0x70;
+const PID_USAGE_DURATION: u16 = 0x50;
+const PID_USAGE_DC_ENABLE_ACTUATORS: u16 = 0x97;
+
+/// Output-report layout for simple rumble (discovered from output value caps).
+///
+/// Recognizes the common "Set Effect" style report that carries a `Magnitude` array on the
+/// PID page (e.g. Xbox controllers over Bluetooth: `[LT, RT, low, high]`).
+#[derive(Clone, Debug)]
+struct RumbleLayout {
+    report_id: u8,
+    link_collection: u16,
+    /// Number of magnitude slots (`ReportCount` of the Magnitude cap).
+    slots: u16,
+    magnitude_max: i32,
+    /// `(link_collection, logical_max)` of `DC Enable Actuators`, if present.
+    enable: Option<(u16, i32)>,
+    /// `(link_collection, logical_max)` of `Duration`, if present.
+    duration: Option<(u16, i32)>,
+}
+
+/// Battery charge usages: Generic Device Controls `Battery Strength` and Battery System
+/// `Remaining Capacity`.
+const BATTERY_USAGES: [(u16, u16); 2] = [(0x06, 0x20), (0x85, 0x66)];
+/// Battery System `Charging` flag.
+const BATTERY_PAGE: u16 = 0x85;
+const BATTERY_USAGE_CHARGING: u16 = 0x44;
+
+/// Location of a battery-strength value in input or feature reports.
+#[derive(Clone, Copy, Debug)]
+struct BatteryField {
+    report_type: HIDP_REPORT_TYPE,
+    report_id: u8,
+    usage_page: u16,
+    usage: u16,
+    link_collection: u16,
+    logical_min: i32,
+    logical_max: i32,
+}
+
+impl BatteryField {
+    fn find(report_type: HIDP_REPORT_TYPE, values: &[ValueField]) -> Option<Self> {
+        let v = values
+            .iter()
+            .find(|v| BATTERY_USAGES.contains(&(v.usage_page, v.usage)))?;
+        Some(Self {
+            report_type,
+            report_id: v.report_id,
+            usage_page: v.usage_page,
+            usage: v.usage,
+            link_collection: v.link_collection,
+            logical_min: v.logical_min,
+            logical_max: v.logical_max,
+        })
+    }
+
+    /// Decode `(percent, charging)` from a full report (ID byte included).
+    fn read(&self, ppd: PHIDP_PREPARSED_DATA, report: &mut [u8]) -> Option<(u8, bool)> {
+        let len = report.len() as u32;
+        let mut value: u32 = 0;
+        let status = unsafe {
+            HidP_GetUsageValue(
+                self.report_type,
+                self.usage_page,
+                self.link_collection,
+                self.usage,
+                &mut value,
+                ppd,
+                report.as_mut_ptr(),
+                len,
+            )
+        };
+        if status != STATUS_SUCCESS {
+            return None;
+        }
+        let span = (self.logical_max - self.logical_min).max(1) as f32;
+        let percent = ((value as i32 - self.logical_min) as f32 / span * 100.0)
+            .round()
+            .clamp(0.0, 100.0) as u8;
+
+        // `Charging` is optional; link collection 0 searches the whole report.
+        let mut charging: u32 = 0;
+        let status = unsafe {
+            HidP_GetUsageValue(
+                self.report_type,
+                BATTERY_PAGE,
+                0,
+                BATTERY_USAGE_CHARGING,
+                &mut charging,
+                ppd,
+                report.as_mut_ptr(),
+                len,
+            )
+        };
+        Some((percent, status == STATUS_SUCCESS && charging != 0))
+    }
 }
 
 /// Descriptor-driven HIDP parser (Windows).
@@ -103,23 +294,48 @@ pub struct WinHidpParser {
     buttons: Vec<ButtonField>,
     values: Vec<ValueField>,
 
-    // Stable index maps (usage → button/axis index)
-    // (report_id, usage_page, usage, link_collection) → idx
-    button_index_by_usage: HashMap<(u8, u16, u16, u16), u16>,
+    // Stable index maps (usage → button/axis index), one dense table per
+    // (report_id, usage_page, link_collection); see `ButtonField::table`
+    usage_tables: Vec<UsageTable>,
     // Deterministic enumeration of buttons by assigned index:
     // each entry is (report_id, usage_page, usage, link_collection)
     buttons_by_index: Vec<(u8, u16, u16, u16)>,
-    axis_fields_by_index: Vec<usize>, // axis_index → values[] idx
-    hat_fields_by_index: Vec<usize>,  // hat_index → values[] idx
+    axis_fields_by_index: Vec<usize>,   // axis_index → values[] idx
+    value_buttons_by_index: Vec<usize>, // quirk value-buttons → values[] idx
+    hat_fields_by_index: Vec<usize>,    // hat_index → values[] idx
+    hat_names: Vec<String>,             // hat_index → display name
+    collection_names: HashMap<u16, String>, // link collection → display name
 
     // Last-frame state for edge/coalesce
-    last_pressed_buttons: HashSet<u16>, // button indices currently pressed
+    last_pressed_buttons: Vec<bool>, // button index → currently pressed
+    pressed_scratch: Vec<bool>,      // reused per report for the new pressed set
+    last_value_buttons: HashSet<u16>, // value-backed button indices currently pressed
     last_axis_value: HashMap<u16, f32>, // axis_index → last value
-    last_hat_value: HashMap<u16, i16>,  // hat_index → last slot value
+    last_hat_value: HashMap<u16, i16>, // hat_index → last slot value
     axis_epsilon: f32,
 
     // gamepad support
-    only_rid0: bool, // true if descriptor uses only report ID 0
+    only_rid0: bool,          // true if descriptor uses only report ID 0
+    report_ids: BTreeSet<u8>, // report IDs declared by the input caps
+
+    // Report-ID mismatch counters (see `ReportIdDiagnostics`)
+    diagnostics: ReportIdDiagnostics,
+
+    // Output (rumble) support
+    output_report_len: u16,
+    rumble: Option<RumbleLayout>,
+
+    // Force feedback (HID PID), if the descriptor declares the effect reports
+    pid: Option<PidLayout>,
+
+    // Battery strength: from input reports when present, else queried via feature report
+    battery_input: Option<BatteryField>,
+    battery_feature: Option<BatteryField>,
+    feature_report_len: u16,
+    last_battery: Option<(u8, bool)>,
+
+    // Fixed-offset decoding for simple single-report devices (see `fixed_layout`)
+    fast: Option<FixedLayout>,
 }
 
 impl Drop for WinHidpParser {
@@ -138,26 +354,43 @@ impl Drop for WinHidpParser {
 }
 
 impl WinHidpParser {
+    /// The OS handle opened for this device (overlapped), valid while the parser lives.
+    pub(crate) fn raw_handle(&self) -> HANDLE {
+        self.handle
+    }
+
     /// Construct from a `hidapi::DeviceInfo`.
     ///
-    /// Returns `None` if the device cannot be opened, its preparsed data cannot
-    /// be obtained, or global caps cannot be read. Callers should fall back to a
-    /// generic parser in that case.
+    /// Returns an error if the device cannot be opened ([`Error::HidOpen`]), its preparsed
+    /// data or global caps cannot be read ([`Error::Hidp`]), or it declares no input
+    /// buttons/values at all. Callers should fall back to a generic parser in that case.
     ///
     /// ### Behavior
     /// - Opens the OS device handle using the HID path from `DeviceInfo`.
     /// - Calls `HidD_GetPreparsedData` and `HidP_GetCaps` to cache descriptor info.
     /// - Enumerates button/value caps and assigns stable indices.
-    pub fn new(info: &DeviceInfo) -> Option<Self> {
+    pub fn new(info: &DeviceInfo) -> crate::Result<Self> {
+        Self::with_quirks(info, None)
+    }
+
+    /// Like [`new`](Self::new), applying per-device [`DeviceQuirks`] (vendor usage names and
+    /// axis/button semantics).
+    pub fn with_quirks(info: &DeviceInfo, quirks: Option<&DeviceQuirks>) -> crate::Result<Self> {
         let path = info.path().to_string_lossy().to_string();
-        let handle = open_device_handle(&path).ok()?;
+        let handle = open_device_handle(&path).map_err(|code| Error::HidOpen {
+            path: path.clone(),
+            message: format!("CreateFileW failed (error {code})"),
+        })?;
 
         // Get Preparsed Data
         let mut ppd: PHIDP_PREPARSED_DATA = 0;
         let ok = unsafe { HidD_GetPreparsedData(handle, &mut ppd) };
         if ok == 0 || ppd == 0 {
             unsafe { CloseHandle(handle) };
-            return None;
+            return Err(Error::Hidp {
+                op: "HidD_GetPreparsedData",
+                status: 0,
+            });
         }
 
         // Caps (global)
@@ -168,7 +401,10 @@ impl WinHidpParser {
                 HidD_FreePreparsedData(ppd);
                 CloseHandle(handle);
             }
-            return None;
+            return Err(Error::Hidp {
+                op: "HidP_GetCaps",
+                status,
+            });
         }
         let caps = unsafe { caps.assume_init() };
 
@@ -205,7 +441,9 @@ impl WinHidpParser {
                 HidD_FreePreparsedData(ppd);
                 CloseHandle(handle);
             }
-            return None;
+            return Err(Error::Other(format!(
+                "{path}: descriptor declares no input buttons or values"
+            )));
         }
 
         // ---- gamepad support: report IDs ----
@@ -230,10 +468,21 @@ impl WinHidpParser {
             only_rid0
         );
 
+        // Output caps are optional; they only feed the rumble encoder.
+        let out_val_caps = enumerate_value_caps(ppd, HidP_Output).unwrap_or_default();
+        let rumble = rumble_layout(&out_val_caps);
+        let pid = PidLayout::discover(ppd, &caps);
+
         // Normalize caps → fields
-        let buttons = normalize_buttons(&btn_caps);
+        let mut buttons = normalize_buttons(&btn_caps);
         let mut values = normalize_values(&val_caps);
 
+        // Battery strength is device status, not a control: keep it out of the axes.
+        let battery_input = BatteryField::find(HidP_Input, &values);
+        values.retain(|v| !BATTERY_USAGES.contains(&(v.usage_page, v.usage)));
+        let battery_feature = enumerate_value_caps(ppd, HidP_Feature)
+            .and_then(|c| BatteryField::find(HidP_Feature, &normalize_values(&c)));
+
         // Device quirk: VKB T-Rudder tends to work only with LinkCollection = 0.
         if info.vendor_id() == 0x231d && info.product_id() == 0x011f {
             for v in &mut values {
@@ -243,17 +492,45 @@ impl WinHidpParser {
             eprintln!("[HIDP/QUIRK] Forced LinkCollection=0 for VKB T-Rudder (231d:011f)");
         }
 
+        // User quirks: names and axis/button semantics for (vendor) usages.
+        if let Some(q) = quirks {
+            for v in &mut values {
+                if let Some(uq) = q.usage(v.usage_page, v.usage) {
+                    v.name = uq.name.clone();
+                    v.unit = uq.unit;
+                    (v.logical_min, v.logical_max, v.signed) =
+                        uq.logical_range(v.logical_min, v.logical_max);
+                    if v.is_hat {
+                        v.hat_is_degrees =
+                            classify_hat(v.usage_page, v.usage, v.logical_min, v.logical_max).1;
+                    }
+                    match uq.kind {
+                        UsageKind::Button => {
+                            v.as_button = true;
+                            v.is_hat = false;
+                        }
+                        UsageKind::ButtonMatrix if uq.matrix.is_some() => {
+                            v.as_button = true;
+                            v.is_hat = false;
+                            v.matrix = uq.matrix.clone();
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
         // Assign stable indices for axes/hats/buttons
         let mut axis_fields_by_index = Vec::new();
         let mut hat_fields_by_index = Vec::new();
-        let mut button_index_by_usage: HashMap<(u8, u16, u16, u16), u16> = HashMap::new();
         let mut buttons_by_index: Vec<(u8, u16, u16, u16)> = Vec::new();
+        let mut value_buttons_by_index: Vec<usize> = Vec::new();
 
         // Axis indices
         {
             let mut next_axis: u16 = 0;
             for (i, vf) in values.iter_mut().enumerate() {
-                if vf.is_hat {
+                if vf.is_hat || vf.as_button {
                     continue;
                 }
                 vf.axis_index = Some(next_axis);
@@ -261,28 +538,58 @@ impl WinHidpParser {
                 next_axis += 1;
             }
         }
-        // Hat indices
+        // Hat indices: ordered by (link collection, report ID, usage) so devices with
+        // hats in several collections (e.g. a grip and a base) get stable numbering
+        // regardless of cap enumeration order.
         {
-            let mut next_hat: u16 = 0;
-            for (i, vf) in values.iter_mut().enumerate() {
-                if vf.is_hat {
-                    vf.hat_index = Some(next_hat);
-                    hat_fields_by_index.push(i);
-                    next_hat += 1;
-                }
+            let mut hats: Vec<usize> = (0..values.len()).filter(|&i| values[i].is_hat).collect();
+            hats.sort_by_key(|&i| {
+                let v = &values[i];
+                (v.link_collection, v.report_id, v.usage, i)
+            });
+            for (next_hat, i) in hats.into_iter().enumerate() {
+                values[i].hat_index = Some(next_hat as u16);
+                hat_fields_by_index.push(i);
             }
         }
+        let nodes = link_collection_nodes(ppd, &caps);
+        let collection_names = collection_names(
+            &nodes,
+            values
+                .iter()
+                .map(|v| v.link_collection)
+                .chain(buttons.iter().map(|b| b.link_collection)),
+        );
+        let hat_names = hat_names(handle, &values, &hat_fields_by_index, &collection_names);
         // Button indices are assigned in a stable, deterministic order
         {
             let mut next_btn: u16 = 0;
             for bf in &buttons {
                 for &u in &bf.usages {
                     let key = (bf.report_id, bf.usage_page, u, bf.link_collection);
-                    button_index_by_usage.insert(key, next_btn);
                     buttons_by_index.push(key);
                     next_btn += 1;
                 }
             }
+            // Value-backed buttons (quirks) follow the descriptor buttons.
+            // A matrix takes one index per configured bit.
+            for (i, vf) in values.iter_mut().enumerate() {
+                if vf.as_button {
+                    vf.button_index = Some(next_btn);
+                    value_buttons_by_index.push(i);
+                    next_btn += vf.matrix.as_ref().map_or(1, |m| m.bits);
+                }
+            }
+        }
+        let usage_tables = usage_tables(&buttons_by_index);
+        for bf in &mut buttons {
+            bf.table = find_table(
+                &usage_tables,
+                bf.report_id,
+                bf.usage_page,
+                bf.link_collection,
+            );
+            bf.fallback_table = find_table(&usage_tables, 0, bf.usage_page, bf.link_collection);
         }
 
         // DEBUG: summarize discovered report IDs for visibility during dev builds
@@ -320,23 +627,56 @@ impl WinHidpParser {
         let lsb = 2.0f32 / (max_span.max(1) as f32); // [-1..1] range → 1 LSB
         let axis_epsilon = lsb * 2.0; // ~2 LSBs to suppress jitter
 
-        Some(Self {
+        let fast = match report_ids.iter().copied().collect::<Vec<_>>()[..] {
+            [rid] if battery_input.is_none() => fixed_layout(
+                ppd,
+                rid,
+                caps.InputReportByteLength,
+                &values,
+                &buttons_by_index,
+            ),
+            _ => None,
+        };
+        #[cfg(feature = "debug-log")]
+        eprintln!(
+            "[HIDP/FAST] vid=0x{:04x} pid=0x{:04x} fixed-offset decoding: {}",
+            info.vendor_id(),
+            info.product_id(),
+            fast.is_some()
+        );
+
+        Ok(Self {
             handle,
             ppd,
             input_report_max_len: caps.InputReportByteLength,
             buttons,
             values,
-            button_index_by_usage,
+            usage_tables,
+            last_pressed_buttons: vec![false; buttons_by_index.len()],
+            pressed_scratch: Vec::new(),
             buttons_by_index,
             axis_fields_by_index,
+            value_buttons_by_index,
             hat_fields_by_index,
-            last_pressed_buttons: HashSet::new(),
+            hat_names,
+            collection_names,
+            last_value_buttons: HashSet::new(),
             last_axis_value: HashMap::new(),
             last_hat_value: HashMap::new(),
             axis_epsilon,
 
             // gamepad support
             only_rid0,
+            report_ids,
+            diagnostics: ReportIdDiagnostics::default(),
+            output_report_len: caps.OutputReportByteLength,
+            rumble,
+            pid,
+            battery_input,
+            battery_feature,
+            feature_report_len: caps.FeatureReportByteLength,
+            last_battery: None,
+            fast,
         })
     }
 }
@@ -359,6 +699,133 @@ impl ReportParser for WinHidpParser {
         !self.only_rid0
     }
 
+    fn input_report_ids(&self) -> Vec<u8> {
+        self.report_ids.iter().copied().collect()
+    }
+
+    fn report_id_diagnostics(&self) -> Option<ReportIdDiagnostics> {
+        Some(self.diagnostics.clone())
+    }
+
+    /// Last battery value seen in an input report, or a fresh feature-report query.
+    fn battery(&self) -> Option<BatteryStatus> {
+        let (percent, charging) = match self.last_battery {
+            Some(b) => b,
+            None => {
+                let field = self.battery_feature?;
+                let mut report = vec![0u8; self.feature_report_len.max(2) as usize];
+                report[0] = field.report_id;
+                let ok = unsafe {
+                    HidD_GetFeature(self.handle, report.as_mut_ptr().cast(), report.len() as u32)
+                };
+                if ok == 0 {
+                    return None;
+                }
+                field.read(self.ppd, &mut report)?
+            }
+        };
+        Some(BatteryStatus {
+            level: BatteryLevel::from_percent(percent),
+            percent: Some(percent),
+            charging,
+            wired: false,
+        })
+    }
+
+    fn ffb(&self) -> Option<&dyn FfbEncoder> {
+        self.pid.as_ref().map(|_| self as &dyn FfbEncoder)
+    }
+
+    /// Build a rumble output report from the discovered PID `Magnitude` layout.
+    ///
+    /// Slot mapping: two slots are `[low, high]`; four or more follow the Xbox layout
+    /// `[left trigger, right trigger, low, high]` (trigger motors are left at 0);
+    /// a single slot receives `max(low, high)`.
+    fn encode_rumble(&self, low: f32, high: f32) -> Option<Vec<u8>> {
+        let layout = self.rumble.as_ref()?;
+        let len = self.output_report_len as usize;
+        if len < 2 {
+            return None;
+        }
+        let mut report = vec![0u8; len];
+        report[0] = layout.report_id;
+
+        let scale = |v: f32| (v.clamp(0.0, 1.0) * layout.magnitude_max as f32).round() as u8;
+        let mut magnitudes = vec![0u8; layout.slots as usize];
+        match layout.slots {
+            1 => magnitudes[0] = scale(low.max(high)),
+            2 | 3 => {
+                magnitudes[0] = scale(low);
+                magnitudes[1] = scale(high);
+            }
+            _ => {
+                magnitudes[2] = scale(low);
+                magnitudes[3] = scale(high);
+            }
+        }
+
+        let report_len = report.len() as u32;
+        unsafe {
+            if let Some((lc, max)) = layout.enable {
+                HidP_SetUsageValue(
+                    HidP_Output,
+                    PID_PAGE,
+                    lc,
+                    PID_USAGE_DC_ENABLE_ACTUATORS,
+                    max.max(0) as u32,
+                    self.ppd,
+                    report.as_mut_ptr(),
+                    report_len,
+                );
+            }
+            if let Some((lc, max)) = layout.duration {
+                HidP_SetUsageValue(
+                    HidP_Output,
+                    PID_PAGE,
+                    lc,
+                    PID_USAGE_DURATION,
+                    max.max(0) as u32,
+                    self.ppd,
+                    report.as_mut_ptr(),
+                    report_len,
+                );
+            }
+            let status = if layout.slots == 1 {
+                HidP_SetUsageValue(
+                    HidP_Output,
+                    PID_PAGE,
+                    layout.link_collection,
+                    PID_USAGE_MAGNITUDE,
+                    magnitudes[0] as u32,
+                    self.ppd,
+                    report.as_mut_ptr(),
+                    report_len,
+                )
+            } else {
+                HidP_SetUsageValueArray(
+                    HidP_Output,
+                    PID_PAGE,
+                    layout.link_collection,
+                    PID_USAGE_MAGNITUDE,
+                    magnitudes.as_ptr(),
+                    magnitudes.len() as u16,
+                    self.ppd,
+                    report.as_mut_ptr(),
+                    report_len,
+                )
+            };
+            if status != STATUS_SUCCESS {
+                #[cfg(feature = "debug-log")]
+                eprintln!(
+                    "[HIDP/RUMBLE] SetUsageValue(Magnitude) failed: status=0x{:08x}",
+                    status as u32
+                );
+                return None;
+            }
+        }
+        Some(report)
+    }
+
     /// Return a descriptor list for axes, hats, and buttons derived from HID caps.
     fn describe(&self) -> Vec<ChannelDesc> {
         let mut out = Vec::new();
@@ -369,11 +836,20 @@ impl ReportParser for WinHidpParser {
             out.push(ChannelDesc {
                 kind: ChannelKind::Axis,
                 idx: v.axis_index.unwrap_or(0),
-                name: usage_name(v.usage_page, v.usage),
+                name: v.name.clone().or_else(|| usage_name(v.usage_page, v.usage)),
                 logical_min: v.logical_min,
                 logical_max: v.logical_max,
                 usage_page: Some(v.usage_page),
                 usage: Some(v.usage),
+                unit: v.unit.or_else(|| {
+                    if v.relative {
+                        Some(ChannelUnit::Counts)
+                    } else {
+                        ChannelUnit::from_usage(v.usage_page, v.usage)
+                    }
+                }),
+                collection: Some(v.link_collection),
+                collection_name: self.collection_names.get(&v.link_collection).cloned(),
             });
         }
         // Hats
@@ -382,15 +858,21 @@ impl ReportParser for WinHidpParser {
             out.push(ChannelDesc {
                 kind: ChannelKind::Hat,
                 idx: v.hat_index.unwrap_or(0),
-                name: Some("Hat".to_string()),
+                name: self
+                    .hat_names
+                    .get(v.hat_index.unwrap_or(0) as usize)
+                    .cloned(),
                 logical_min: 0,
                 logical_max: 7,
                 usage_page: Some(v.usage_page),
                 usage: Some(v.usage),
+                unit: Some(ChannelUnit::HatDirection),
+                collection: Some(v.link_collection),
+                collection_name: self.collection_names.get(&v.link_collection).cloned(),
             });
         }
         // Buttons — deterministic order (by assigned index)
-        for (idx, &(_rid, up, u, _lc)) in self.buttons_by_index.iter().enumerate() {
+        for (idx, &(_rid, up, u, lc)) in self.buttons_by_index.iter().enumerate() {
             let idx = idx as u16;
             out.push(ChannelDesc {
                 kind: ChannelKind::Button,
@@ -400,8 +882,36 @@ impl ReportParser for WinHidpParser {
                 logical_max: 1,
                 usage_page: Some(up),
                 usage: Some(u),
+                unit: Some(ChannelUnit::Boolean),
+                collection: Some(lc),
+                collection_name: self.collection_names.get(&lc).cloned(),
             });
         }
+        for &i in &self.value_buttons_by_index {
+            let v = &self.values[i];
+            let base = v.button_index.unwrap_or(0);
+            let name = v.name.clone().or_else(|| usage_name(v.usage_page, v.usage));
+            let names: Vec<Option<String>> = match &v.matrix {
+                Some(m) => (0..m.bits)
+                    .map(|b| Some(m.button_name(name.as_deref().unwrap_or("Matrix"), b)))
+                    .collect(),
+                None => vec![name],
+            };
+            for (offset, name) in names.into_iter().enumerate() {
+                out.push(ChannelDesc {
+                    kind: ChannelKind::Button,
+                    idx: base + offset as u16,
+                    name,
+                    logical_min: 0,
+                    logical_max: 1,
+                    usage_page: Some(v.usage_page),
+                    usage: Some(v.usage),
+                    unit: Some(ChannelUnit::Boolean),
+                    collection: Some(v.link_collection),
+                    collection_name: self.collection_names.get(&v.link_collection).cloned(),
+                });
+            }
+        }
         out
     }
 
@@ -417,11 +927,6 @@ impl ReportParser for WinHidpParser {
     /// - Emits edge events for buttons and coalesced deltas for axes.
     /// - Normalizes hats to **slot** values `-1 | 0..7`.
     fn parse(&mut self, ctx: &ParseCtx, payload: &[u8], out: &mut Vec<InputKind>) {
-        // Build a HIDP-compatible report buffer sized to InputReportByteLength:
-        // [effective_report_id][body... padded ...]
-        let max = self.input_report_max_len as usize;
-        let mut report = vec![0u8; max.max(1)];
-
         // If the descriptor says "only report ID 0" but ctx.report_id != 0,
         // then that first byte is almost certainly *data*, not an ID.
         // In that case interpret (ctx.report_id + payload) as the report body
@@ -436,13 +941,45 @@ impl ReportParser for WinHidpParser {
             (ctx.report_id, payload)
         };
 
+        // Record mismatches instead of silently leaning on the fallbacks above/below.
+        if self.only_rid0 && ctx.report_id != 0 {
+            self.diagnostics.rid0_reinterpreted += 1;
+        } else if !self.report_ids.contains(&effective_rid) {
+            let hits = self
+                .diagnostics
+                .unknown_report_ids
+                .entry(effective_rid)
+                .or_insert(0);
+            *hits += 1;
+            #[cfg(feature = "debug-log")]
+            if *hits == 1 {
+                eprintln!(
+                    "[HIDP/RID] report id {} not declared by descriptor (declared={:?})",
+                    effective_rid, self.report_ids
+                );
+            }
+        }
+
+        if let Some(fast) = &mut self.fast {
+            if fast.report_id() == effective_rid {
+                fast.decode(body, out);
+                return;
+            }
+        }
+
+        // Build a HIDP-compatible report buffer sized to InputReportByteLength:
+        // [effective_report_id][body... padded ...]
+        let max = self.input_report_max_len as usize;
+        let mut report = vec![0u8; max.max(1)];
         report[0] = effective_rid;
         let copy_len = body.len().min(report.len().saturating_sub(1));
         report[1..1 + copy_len].copy_from_slice(&body[..copy_len]);
         let report_len_full = report.len() as u32;
 
         // ----- BUTTONS -----
-        let mut pressed_now: BTreeSet<u16> = BTreeSet::new();
+        let mut pressed_now = std::mem::take(&mut self.pressed_scratch);
+        pressed_now.clear();
+        pressed_now.resize(self.last_pressed_buttons.len(), false);
 
         for bf in &self.buttons {
             // IMPORTANT: filter on *effective* report ID, not raw ctx.report_id
@@ -475,32 +1012,47 @@ impl ReportParser for WinHidpParser {
                 continue;
             }
 
-            for i in 0..(usage_len as usize) {
-                let usage = usage_buf[i];
-                // Prefer exact RID; fall back to RID=0 for stacks whose caps report 0.
-                let key_exact = (effective_rid, bf.usage_page, usage, bf.link_collection);
-                let key_fallback = (0, bf.usage_page, usage, bf.link_collection);
-                if let Some(&btn_idx) = self
-                    .button_index_by_usage
-                    .get(&key_exact)
-                    .or_else(|| self.button_index_by_usage.get(&key_fallback))
-                {
-                    pressed_now.insert(btn_idx);
+            // Prefer exact RID; fall back to RID=0 for stacks whose caps report 0.
+            let exact = if bf.report_id == effective_rid {
+                bf.table
+            } else {
+                // RID-0 cap on a numbered report: rare, resolve per report.
+                find_table(
+                    &self.usage_tables,
+                    effective_rid,
+                    bf.usage_page,
+                    bf.link_collection,
+                )
+            };
+            let exact = exact.map(|t| &self.usage_tables[t]);
+            let fallback = bf.fallback_table.map(|t| &self.usage_tables[t]);
+            for &usage in &usage_buf[..usage_len as usize] {
+                if let Some(btn_idx) = exact.and_then(|t| t.get(usage)) {
+                    pressed_now[usize::from(btn_idx)] = true;
+                } else if let Some(btn_idx) = fallback.and_then(|t| t.get(usage)) {
+                    self.diagnostics.fallback_lookups += 1;
+                    pressed_now[usize::from(btn_idx)] = true;
                 }
             }
         }
 
-        for &idx in pressed_now.iter() {
-            if !self.last_pressed_buttons.contains(&idx) {
-                out.push(InputKind::ButtonPressed { button: idx });
+        let edges = || {
+            pressed_now
+                .iter()
+                .zip(&self.last_pressed_buttons)
+                .enumerate()
+        };
+        for (idx, (&now, &was)) in edges() {
+            if now && !was {
+                out.push(InputKind::ButtonPressed { button: idx as u16 });
             }
         }
-        for &idx in self.last_pressed_buttons.iter() {
-            if !pressed_now.contains(&idx) {
-                out.push(InputKind::ButtonReleased { button: idx });
+        for (idx, (&now, &was)) in edges() {
+            if !now && was {
+                out.push(InputKind::ButtonReleased { button: idx as u16 });
             }
         }
-        self.last_pressed_buttons = pressed_now.into_iter().collect();
+        self.pressed_scratch = std::mem::replace(&mut self.last_pressed_buttons, pressed_now);
 
         // ----- VALUES (axes + hats) -----
         for vf in self.values.iter_mut() {
@@ -508,6 +1060,18 @@ impl ReportParser for WinHidpParser {
                 continue;
             }
 
+            if let Some(m) = &vf.matrix {
+                decode_matrix(
+                    vf,
+                    m,
+                    self.ppd,
+                    &mut report,
+                    &mut self.last_value_buttons,
+                    out,
+                );
+                continue;
+            }
+
             let mut value: u32 = 0;
             let lc = if USE_LINK_COLLECTION_FOR_VALUES {
                 vf.link_collection
@@ -563,15 +1127,27 @@ impl ReportParser for WinHidpParser {
                 continue;
             }
 
-            // rest of axis / hat logic unchanged...
+            // HidP_GetUsageValue returns the raw bits; signed fields need sign extension.
+            let raw = if vf.signed {
+                sign_extend(value, u32::from(vf.bit_size))
+            } else {
+                value as i32
+            };
 
-            if vf.is_hat {
-                let slot = hat_value_to_slot(
-                    value as i32,
-                    vf.logical_min,
-                    vf.logical_max,
-                    vf.hat_is_degrees,
-                );
+            if let Some(bidx) = vf.button_index {
+                let pressed = value != 0;
+                if pressed != self.last_value_buttons.contains(&bidx) {
+                    if pressed {
+                        self.last_value_buttons.insert(bidx);
+                        out.push(InputKind::ButtonPressed { button: bidx });
+                    } else {
+                        self.last_value_buttons.remove(&bidx);
+                        out.push(InputKind::ButtonReleased { button: bidx });
+                    }
+                }
+            } else if vf.is_hat {
+                let slot =
+                    hat_value_to_slot(raw, vf.logical_min, vf.logical_max, vf.hat_is_degrees);
                 if let Some(hidx) = vf.hat_index {
                     let last = self.last_hat_value.get(&hidx).copied().unwrap_or(i16::MIN);
                     if last != slot {
@@ -582,8 +1158,15 @@ impl ReportParser for WinHidpParser {
                         });
                     }
                 }
+            } else if vf.relative {
+                if let Some(aidx) = vf.axis_index.filter(|_| raw != 0) {
+                    out.push(InputKind::RelativeMoved {
+                        axis: aidx,
+                        delta: raw as f32,
+                    });
+                }
             } else {
-                let v = normalize_axis_value(value as i32, vf.logical_min, vf.logical_max);
+                let v = normalize_axis_value(raw, vf.logical_min, vf.logical_max);
                 if let Some(aidx) = vf.axis_index {
                     let last = self.last_axis_value.get(&aidx).copied().unwrap_or(f32::NAN);
                     if !last.is_finite() || (v - last).abs() > self.axis_epsilon {
@@ -596,6 +1179,232 @@ impl ReportParser for WinHidpParser {
                 }
             }
         }
+
+        // ----- BATTERY -----
+        if let Some(field) = self.battery_input {
+            if field.report_id == 0 || field.report_id == effective_rid {
+                if let Some(b) = field.read(self.ppd, &mut report) {
+                    if self.last_battery != Some(b) {
+                        self.last_battery = Some(b);
+                        out.push(InputKind::BatteryChanged {
+                            percent: b.0,
+                            charging: b.1,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Fixed-offset layout of report `report_id`, if every field is a plain bit range.
+///
+/// HIDP caps carry no bit positions, so each field is written into an empty report with
+/// `HidP_SetUsageValue` / `HidP_SetUsages` and located by the bits that changed. Any field
+/// that doesn't show up as exactly its own contiguous bits (arrays, value arrays, quirk
+/// buttons) keeps the device on the HIDP path. Signed fields do too: HIDP returns their raw
+/// bit pattern, and both paths must decode a device the same way.
+fn fixed_layout(
+    ppd: PHIDP_PREPARSED_DATA,
+    report_id: u8,
+    report_len: u16,
+    values: &[ValueField],
+    buttons_by_index: &[(u8, u16, u16, u16)],
+) -> Option<FixedLayout> {
+    let report_len = usize::from(report_len);
+    if report_len < 2 {
+        return None;
+    }
+    // `(bit offset, bit count)` of the single run of bits `set` writes into the payload.
+    let probe = |set: &dyn Fn(&mut [u8]) -> NTSTATUS| -> Option<(u32, u32)> {
+        let mut report = vec![0u8; report_len];
+        report[0] = report_id;
+        if set(&mut report) != STATUS_SUCCESS {
+            return None;
+        }
+        let payload = &report[1..];
+        let ones: Vec<u32> = (0..payload.len() as u32 * 8)
+            .filter(|&b| (payload[(b / 8) as usize] >> (b % 8)) & 1 != 0)
+            .collect();
+        let (&first, &last) = (ones.first()?, ones.last()?);
+        (last - first + 1 == ones.len() as u32).then_some((first, ones.len() as u32))
+    };
+    let mut used = vec![false; (report_len - 1) * 8];
+    let mut claim = |offset: u32, bits: u32| {
+        let range = &mut used[offset as usize..(offset + bits) as usize];
+        if range.iter().any(|&b| b) {
+            return false;
+        }
+        range.fill(true);
+        true
+    };
+
+    let mut fixed_values = Vec::with_capacity(values.len());
+    for v in values {
+        let bits = u32::from(v.bit_size);
+        if v.as_button
+            || v.report_count > 1
+            || !(2..=32).contains(&bits)
+            || v.logical_min < 0
+            || v.signed
+            || v.relative
+        {
+            return None;
+        }
+        let all_ones = if bits == 32 {
+            u32::MAX
+        } else {
+            (1u32 << bits) - 1
+        };
+        let (bit_offset, found) = probe(&|r: &mut [u8]| unsafe {
+            HidP_SetUsageValue(
+                HidP_Input,
+                v.usage_page,
+                v.link_collection,
+                v.usage,
+                all_ones,
+                ppd,
+                r.as_mut_ptr(),
+                r.len() as u32,
+            )
+        })?;
+        if found != bits || !claim(bit_offset, bits) {
+            return None;
+        }
+        let (index, hat) = match (v.axis_index, v.hat_index) {
+            (Some(axis), _) => (axis, None),
+            (None, Some(hat)) if v.hat_is_degrees => (hat, Some(HatEncoding::Degrees)),
+            (None, Some(hat)) => (hat, Some(HatEncoding::Slots)),
+            (None, None) => return None,
+        };
+        fixed_values.push(FixedValue {
+            index,
+            bit_offset,
+            bit_size: bits,
+            logical_min: v.logical_min,
+            logical_max: v.logical_max,
+            hat,
+        });
+    }
+
+    let mut fixed_buttons = Vec::with_capacity(buttons_by_index.len());
+    for (index, &(_, page, usage, lc)) in buttons_by_index.iter().enumerate() {
+        let (bit_offset, found) = probe(&|r: &mut [u8]| unsafe {
+            let mut usage = usage;
+            let mut n: u32 = 1;
+            HidP_SetUsages(
+                HidP_Input,
+                page,
+                lc,
+                &mut usage,
+                &mut n,
+                ppd,
+                r.as_mut_ptr(),
+                r.len() as u32,
+            )
+        })?;
+        if found != 1 || !claim(bit_offset, 1) {
+            return None;
+        }
+        fixed_buttons.push(FixedButton {
+            index: index as u16,
+            bit_offset,
+        });
+    }
+
+    Some(FixedLayout::new(report_id, fixed_values, fixed_buttons))
+}
+
+/// Expand a quirk-defined button matrix into button edges.
+///
+/// Arrays (`ReportCount > 1`) are read with `HidP_GetUsageValueArray`; single values with
+/// `HidP_GetUsageValue`.
+fn decode_matrix(
+    vf: &ValueField,
+    m: &MatrixLayout,
+    ppd: PHIDP_PREPARSED_DATA,
+    report: &mut [u8],
+    last: &mut HashSet<u16>,
+    out: &mut Vec<InputKind>,
+) {
+    let Some(base) = vf.button_index else {
+        return;
+    };
+    let report_len = report.len() as u32;
+    let raw: Vec<u8> = if vf.report_count > 1 {
+        let len = (vf.bit_size as usize * vf.report_count as usize).div_ceil(8);
+        let mut buf = vec![0u8; len];
+        let status = unsafe {
+            HidP_GetUsageValueArray(
+                HidP_Input,
+                vf.usage_page,
+                vf.link_collection,
+                vf.usage,
+                buf.as_mut_ptr(),
+                buf.len() as u16,
+                ppd,
+                report.as_mut_ptr(),
+                report_len,
+            )
+        };
+        if status != STATUS_SUCCESS {
+            return;
+        }
+        buf
+    } else {
+        let mut value: u32 = 0;
+        let status = unsafe {
+            HidP_GetUsageValue(
+                HidP_Input,
+                vf.usage_page,
+                vf.link_collection,
+                vf.usage,
+                &mut value,
+                ppd,
+                report.as_mut_ptr(),
+                report_len,
+            )
+        };
+        if status != STATUS_SUCCESS {
+            return;
+        }
+        value.to_le_bytes().to_vec()
+    };
+
+    for i in 0..m.bits {
+        let button = base + i;
+        let pressed = m.pressed(&raw, i);
+        if pressed != last.contains(&button) {
+            if pressed {
+                last.insert(button);
+                out.push(InputKind::ButtonPressed { button });
+            } else {
+                last.remove(&button);
+                out.push(InputKind::ButtonReleased { button });
+            }
+        }
+    }
+}
+
+impl FfbEncoder for WinHidpParser {
+    fn create_effect_report(&self, kind: &FfbEffectKind) -> Option<Vec<u8>> {
+        self.pid.as_ref()?.create_effect_report(self.ppd, kind)
+    }
+
+    fn block_load_buffer(&self) -> Option<Vec<u8>> {
+        self.pid.as_ref()?.block_load_buffer()
+    }
+
+    fn parse_block_load(&self, report: &[u8]) -> Option<EffectHandle> {
+        self.pid.as_ref()?.parse_block_load(self.ppd, report)
+    }
+
+    fn effect_reports(&self, handle: EffectHandle, effect: &FfbEffect) -> Option<Vec<Vec<u8>>> {
+        self.pid.as_ref()?.effect_reports(self.ppd, handle, effect)
+    }
+
+    fn operation_report(&self, handle: EffectHandle, op: EffectOp) -> Option<Vec<u8>> {
+        self.pid.as_ref()?.operation_report(self.ppd, handle, op)
     }
 }
 
@@ -605,7 +1414,7 @@ impl ReportParser for WinHidpParser {
 ///
 /// Returns a vector sized to the number of caps returned by HIDP, or `None`
 /// if the API reports failure.
-fn enumerate_button_caps(
+pub(super) fn enumerate_button_caps(
     ppd: PHIDP_PREPARSED_DATA,
     report_type: HIDP_REPORT_TYPE,
 ) -> Option<Vec<HIDP_BUTTON_CAPS>> {
@@ -653,7 +1462,7 @@ fn enumerate_button_caps(
 ///
 /// Returns a vector sized to the number of caps returned by HIDP, or `None`
 /// if the API reports failure.
-fn enumerate_value_caps(
+pub(super) fn enumerate_value_caps(
     ppd: PHIDP_PREPARSED_DATA,
     report_type: HIDP_REPORT_TYPE,
 ) -> Option<Vec<HIDP_VALUE_CAPS>> {
@@ -696,6 +1505,41 @@ fn enumerate_value_caps(
     }
 }
 
+/// Find a rumble-capable `Magnitude` field among output value caps.
+///
+/// Only byte-sized magnitudes are supported (that is what `HidP_SetUsageValueArray`
+/// expects and what real pads use).
+fn rumble_layout(caps: &[HIDP_VALUE_CAPS]) -> Option<RumbleLayout> {
+    let usage_of = |c: &HIDP_VALUE_CAPS| unsafe {
+        if c.IsRange != 0 {
+            c.Anonymous.Range.UsageMin
+        } else {
+            c.Anonymous.NotRange.Usage
+        }
+    };
+    let magnitude = caps
+        .iter()
+        .find(|c| c.UsagePage == PID_PAGE && usage_of(c) == PID_USAGE_MAGNITUDE)?;
+    if magnitude.ReportCount > 1 && magnitude.BitSize != 8 {
+        return None;
+    }
+    let sibling = |usage: u16| {
+        caps.iter()
+            .find(|c| {
+                c.UsagePage == PID_PAGE && c.ReportID == magnitude.ReportID && usage_of(c) == usage
+            })
+            .map(|c| (c.LinkCollection, c.LogicalMax))
+    };
+    Some(RumbleLayout {
+        report_id: magnitude.ReportID,
+        link_collection: magnitude.LinkCollection,
+        slots: magnitude.ReportCount.max(1),
+        magnitude_max: magnitude.LogicalMax.clamp(1, 255),
+        enable: sibling(PID_USAGE_DC_ENABLE_ACTUATORS),
+        duration: sibling(PID_USAGE_DURATION),
+    })
+}
+
 /// Expand button caps (including usage ranges) into concrete button fields.
 fn normalize_buttons(caps: &[HIDP_BUTTON_CAPS]) -> Vec<ButtonField> {
     let mut out = Vec::new();
@@ -728,6 +1572,8 @@ fn normalize_buttons(caps: &[HIDP_BUTTON_CAPS]) -> Vec<ButtonField> {
             usage_page: up,
             link_collection: lc,
             usages,
+            table: None,
+            fallback_table: None,
         });
     }
     out
@@ -740,11 +1586,16 @@ fn normalize_values(caps: &[HIDP_VALUE_CAPS]) -> Vec<ValueField> {
         let rid = c.ReportID;
         let up = c.UsagePage;
         let logical_min = c.LogicalMin as i32;
-        let logical_max = c.LogicalMax as i32;
+        let mut logical_max = c.LogicalMax as i32;
+        if logical_min >= 0 && logical_max < logical_min && (1..32).contains(&c.BitSize) {
+            // Common descriptor bug: an unsigned maximum written as a short signed item
+            // (e.g. `26 FF FF` for 65535), which HIDP reports sign-extended (-1).
+            logical_max = (logical_max as u32 & ((1u32 << c.BitSize) - 1)) as i32;
+        }
 
         // Only reject true garbage (usage_page==0).
         // Usage==0 is valid on Simulation Controls and vendor collections.
-        let push_field = |u: u16, out: &mut Vec<ValueField>| {
+        let push_field = |u: u16, string_index: u16, out: &mut Vec<ValueField>| {
             if up == 0 {
                 return;
             }
@@ -760,6 +1611,16 @@ fn normalize_values(caps: &[HIDP_VALUE_CAPS]) -> Vec<ValueField> {
                 hat_is_degrees,
                 axis_index: None,
                 hat_index: None,
+                string_index,
+                name: None,
+                unit: None,
+                as_button: false,
+                button_index: None,
+                matrix: None,
+                bit_size: c.BitSize,
+                report_count: c.ReportCount,
+                signed: logical_min < 0,
+                relative: c.IsAbsolute == 0,
             });
         };
 
@@ -770,123 +1631,117 @@ fn normalize_values(caps: &[HIDP_VALUE_CAPS]) -> Vec<ValueField> {
                 let u_min = r.UsageMin;
                 let u_max = r.UsageMax;
                 for u in u_min..=u_max {
-                    push_field(u, &mut out);
+                    push_field(u, r.StringMin, &mut out);
                 }
             } else {
                 let nr = c.Anonymous.NotRange;
-                push_field(nr.Usage, &mut out);
+                push_field(nr.Usage, nr.StringIndex, &mut out);
             }
         }
     }
     out
 }
 
-/// Determine if a (usage_page, usage) is a Hat and whether it encodes degrees.
+/// Display names for hats, indexed by hat index.
 ///
-/// Returns `(is_hat, is_degrees)`. When `is_hat` is true:
-/// - `is_degrees == false` means logical slots (e.g., `0..7` or `1..8`).
-/// - `is_degrees == true` means an angular domain (e.g., `0..359`).
-fn classify_hat(usage_page: u16, usage: u16, logical_min: i32, logical_max: i32) -> (bool, bool) {
-    // Generic Desktop page, usage 0x39 = Hat Switch
-    if usage_page == 0x01 && usage == 0x39 {
-        // Use full logical range, not just max:
-        // Treat as "slots" if the device describes exactly 8 positions (0..7 or 1..8).
-        let is_slots =
-            (logical_min == 0 && logical_max == 7) || (logical_min == 1 && logical_max == 8);
-        // Otherwise assume degrees (0..359, 0..100, etc.)
-        let is_degrees = !is_slots;
-        (true, is_degrees)
-    } else {
-        (false, false)
-    }
+/// Hats are named `Hat N` (1-based). When the descriptor attaches a string to the hat,
+/// or the device spreads hats across several link collections, the collection is appended:
+/// `Hat 1 (Castle)`, `Hat 2 (Joystick)`, `Hat 3 (Collection 3)`.
+fn hat_names(
+    handle: HANDLE,
+    values: &[ValueField],
+    hats: &[usize],
+    collection_names: &HashMap<u16, String>,
+) -> Vec<String> {
+    let collections: BTreeSet<u16> = hats.iter().map(|&i| values[i].link_collection).collect();
+
+    hats.iter()
+        .enumerate()
+        .map(|(n, &i)| {
+            let v = &values[i];
+            let label = indexed_string(handle, v.string_index).or_else(|| {
+                (collections.len() > 1)
+                    .then(|| collection_names.get(&v.link_collection).cloned())
+                    .flatten()
+            });
+            match label {
+                Some(l) => format!("Hat {} ({l})", n + 1),
+                None => format!("Hat {}", n + 1),
+            }
+        })
+        .collect()
 }
 
-// --------------------- decoding helpers ---------------------
-
-/// Normalize an integer axis value from `[lo..hi]` into `[-1.0, 1.0]` with clamping.
-fn normalize_axis_value(v: i32, lo: i32, hi: i32) -> f32 {
-    let lo = lo as f64;
-    let hi = hi as f64;
-    if (hi - lo).abs() < 1e-9 {
-        return 0.0;
-    }
-    let t = (v as f64 - lo) / (hi - lo); // 0..1
-    let n = t * 2.0 - 1.0; // -1..1
-    n.clamp(-1.0, 1.0) as f32
+/// Derive a display name for every link collection referenced by `used`.
+///
+/// Known collection usages get their HID name (`Joystick`, `Throttle`, …); anything else
+/// (vendor collections, plain logical groupings) falls back to `Collection N`.
+fn collection_names(
+    nodes: &[HIDP_LINK_COLLECTION_NODE],
+    used: impl IntoIterator<Item = u16>,
+) -> HashMap<u16, String> {
+    used.into_iter()
+        .map(|lc| {
+            let known = nodes
+                .get(lc as usize)
+                .and_then(|n| collection_usage_name(n.LinkUsagePage, n.LinkUsage));
+            let name = known.map_or_else(|| format!("Collection {lc}"), str::to_string);
+            (lc, name)
+        })
+        .collect()
 }
 
-/// Convert a raw hat value into a standardized slot:
-/// - Returns `-1` for neutral.
-/// - Returns `0..7` for directions (Up=0, clockwise).
-/// - If `is_degrees`, maps degrees using 45° sectors; otherwise expects slots.
-fn hat_value_to_slot(raw: i32, lo: i32, hi: i32, is_degrees: bool) -> i16 {
-    // Unify common neutral encodings (outside logical range or special sentinels).
-    if raw < lo || raw > hi || matches!(raw, -1 | 8 | 15 | 255 | 0xFFFF) {
-        return -1;
-    }
-    if !is_degrees {
-        // slots already
-        if raw >= 0 && raw <= 7 {
-            return raw as i16;
-        }
-        // unknown → neutral
-        return -1;
-    }
-    // degrees → slot
-    let deg = raw as f32;
-    let mut slot = ((deg + 22.5) / 45.0).floor() as i32 % 8;
-    if slot < 0 {
-        slot += 8;
-    }
-    slot as i16
+/// Read the link collection tree (index = `LinkCollection` number).
+fn link_collection_nodes(
+    ppd: PHIDP_PREPARSED_DATA,
+    caps: &HIDP_CAPS,
+) -> Vec<HIDP_LINK_COLLECTION_NODE> {
+    let mut len = caps.NumberLinkCollectionNodes as u32;
+    if len == 0 {
+        return Vec::new();
+    }
+    unsafe {
+        let mut nodes: Vec<HIDP_LINK_COLLECTION_NODE> = vec![core::mem::zeroed(); len as usize];
+        let status = HidP_GetLinkCollectionNodes(nodes.as_mut_ptr(), &mut len, ppd);
+        if status != STATUS_SUCCESS {
+            return Vec::new();
+        }
+        nodes.truncate(len as usize);
+        nodes
+    }
 }
 
-/// Provide friendly names for common Generic Desktop usages (X/Y/Z/Rx/Ry/Rz/etc.).
-fn usage_name(usage_page: u16, usage: u16) -> Option<String> {
-    // Generic Desktop
-    if usage_page == 0x01 {
-        let s = match usage {
-            0x30 => "X",
-            0x31 => "Y",
-            0x32 => "Z",
-            0x33 => "Rx",
-            0x34 => "Ry",
-            0x35 => "Rz",
-            0x36 => "Slider",
-            0x37 => "Dial",
-            0x38 => "Wheel",
-            0x39 => "Hat",
-            _ => return Some(format!("GD_{usage:#04x}")),
-        };
-        return Some(s.to_string());
-    }
-
-    // Simulation Controls
-    if usage_page == 0x02 {
-        let s = match usage {
-            0x00 => "SimAxis",
-            0xB0 => "Accelerator",
-            0xB1 => "Brake",
-            0xB2 => "Clutch",
-            0xBB => "Throttle",
-            _ => "Sim",
-        };
-        return Some(s.to_string());
+/// Fetch a descriptor string by index (`0` means "no string").
+fn indexed_string(handle: HANDLE, index: u16) -> Option<String> {
+    if index == 0 || handle.is_null() {
+        return None;
     }
-
-    // Vendor-defined
-    if (usage_page & 0xFF00) == 0xFF00 {
-        return Some("VendorAxis".into());
+    // HID string descriptors are capped at 126 UTF-16 code units.
+    let mut buf = [0u16; 128];
+    let ok = unsafe {
+        HidD_GetIndexedString(
+            handle,
+            index as u32,
+            buf.as_mut_ptr().cast(),
+            (buf.len() * 2) as u32,
+        )
+    };
+    if ok == 0 {
+        return None;
     }
-
-    // Fallback
-    Some(format!("UP_{usage_page:04x}_U_{usage:04x}"))
+    let end = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    let s = String::from_utf16_lossy(&buf[..end]).trim().to_string();
+    (!s.is_empty()).then_some(s)
 }
 
 // --------------------- OS handle helpers ---------------------
 
 /// Open a Windows file handle for a HID interface path.
 ///
+/// The handle is opened for overlapped I/O so [`HidInputDevice`](super::hid_device::HidInputDevice)
+/// can read input reports through it (see [`overlapped`](super::overlapped)); the `HidD_*`
+/// calls made on it are unaffected.
+///
 /// ### Errors
 /// Returns `Err(GetLastError())` on failure.
 ///
@@ -909,8 +1764,8 @@ fn open_device_handle(path: &str) -> Result<HANDLE, u32> {
             FILE_SHARE_READ | FILE_SHARE_WRITE, // dwShareMode
             null(),                             // lpSecurityAttributes: *const SECURITY_ATTRIBUTES
             OPEN_EXISTING,                      // dwCreationDisposition
-            FILE_ATTRIBUTE_NORMAL,              // dwFlagsAndAttributes
-            null_mut(),                         // hTemplateFile: HANDLE
+            FILE_FLAG_OVERLAPPED, // dwFlagsAndAttributes (input reads, see `overlapped`)
+            null_mut(),           // hTemplateFile: HANDLE
         )
     };
 
@@ -0,0 +1,116 @@
+#![cfg(target_os = "windows")]
+
+//! Window-less HID hotplug notifications.
+//!
+//! `WM_DEVICECHANGE` needs a window on an interactive desktop, which services (session 0)
+//! and console daemons don't have. This watcher registers a Configuration Manager
+//! notification (`CM_Register_Notification`) for HID interface arrival/removal instead; the
+//! callback runs on a system thread pool and only raises a flag, which the
+//! [`Manager`](crate::manager::Manager) checks on its next poll
+//! (see [`Manager::watch_hotplug`](crate::manager::Manager::watch_hotplug)).
+//!
+//! XInput pads expose a HID interface too, so their arrival is reported as well.
+
+use core::ffi::c_void;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use windows_sys::core::GUID;
+use windows_sys::Win32::Devices::DeviceAndDriverInstallation::{
+    CM_Register_Notification, CM_Unregister_Notification, CM_NOTIFY_ACTION, CM_NOTIFY_EVENT_DATA,
+    CM_NOTIFY_FILTER, CR_SUCCESS, HCMNOTIFICATION,
+};
+
+use crate::{Error, Result};
+
+/// `GUID_DEVINTERFACE_HID` ({4D1E55B2-F16F-11CF-88CB-001111000030}).
+const GUID_DEVINTERFACE_HID: GUID = GUID::from_u128(0x4d1e55b2_f16f_11cf_88cb_001111000030);
+
+// Local constants (avoid relying on module exports that vary by windows-sys version)
+const CM_NOTIFY_FILTER_TYPE_DEVICEINTERFACE: i32 = 0;
+const CM_NOTIFY_ACTION_DEVICEINTERFACEARRIVAL: i32 = 0;
+const CM_NOTIFY_ACTION_DEVICEINTERFACEREMOVAL: i32 = 1;
+const ERROR_SUCCESS: u32 = 0;
+
+/// Registered HID interface notification; unregistered on drop.
+pub struct HotplugWatcher {
+    handle: HCMNOTIFICATION,
+    /// Set by the callback; boxed so its address stays valid for the registration.
+    changed: Box<AtomicBool>,
+}
+
+impl std::fmt::Debug for HotplugWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HotplugWatcher")
+            .field("changed", &self.changed.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl HotplugWatcher {
+    /// Register for HID interface arrival/removal notifications.
+    pub fn start() -> Result<Self> {
+        let changed = Box::new(AtomicBool::new(false));
+        let mut filter: CM_NOTIFY_FILTER = unsafe { core::mem::zeroed() };
+        filter.cbSize = core::mem::size_of::<CM_NOTIFY_FILTER>() as u32;
+        filter.FilterType = CM_NOTIFY_FILTER_TYPE_DEVICEINTERFACE as _;
+        filter.u.DeviceInterface.ClassGuid = GUID_DEVINTERFACE_HID;
+
+        let mut handle: HCMNOTIFICATION = core::ptr::null_mut();
+        let cr = unsafe {
+            CM_Register_Notification(
+                &filter,
+                &*changed as *const AtomicBool as *const c_void,
+                Some(on_notify),
+                &mut handle,
+            )
+        };
+        if cr != CR_SUCCESS {
+            return Err(Error::Other(format!(
+                "CM_Register_Notification failed (CONFIGRET {cr})"
+            )));
+        }
+        Ok(Self { handle, changed })
+    }
+
+    /// Whether any HID interface arrived or left since the last call.
+    pub fn take_changed(&self) -> bool {
+        self.changed.swap(false, Ordering::AcqRel)
+    }
+}
+
+impl Drop for HotplugWatcher {
+    fn drop(&mut self) {
+        // Waits for in-flight callbacks, so `changed` outlives every use of the context.
+        unsafe { CM_Unregister_Notification(self.handle) };
+    }
+}
+
+unsafe extern "system" fn on_notify(
+    _notify: HCMNOTIFICATION,
+    context: *const c_void,
+    action: CM_NOTIFY_ACTION,
+    _data: *const CM_NOTIFY_EVENT_DATA,
+    _size: u32,
+) -> u32 {
+    let action = action as i32;
+    if action == CM_NOTIFY_ACTION_DEVICEINTERFACEARRIVAL
+        || action == CM_NOTIFY_ACTION_DEVICEINTERFACEREMOVAL
+    {
+        let changed = &*(context as *const AtomicBool);
+        changed.store(true, Ordering::Release);
+    }
+    ERROR_SUCCESS
+}
+
+/// Is this process running in session 0 (services)?
+///
+/// Raw Input is delivered per interactive desktop, so session 0 never receives keyboard/mouse
+/// packets.
+pub(crate) fn in_service_session() -> bool {
+    use windows_sys::Win32::System::RemoteDesktop::ProcessIdToSessionId;
+    use windows_sys::Win32::System::Threading::GetCurrentProcessId;
+
+    let mut session = u32::MAX;
+    let ok = unsafe { ProcessIdToSessionId(GetCurrentProcessId(), &mut session) };
+    ok != 0 && session == 0
+}
@@ -4,9 +4,13 @@
 //!
 //! This module contains the Windows-specific implementations used by StickUp:
 //! - **HID** discovery and device polling via `hidapi`
+//! - overlapped (**IOCP**) input report reads
 //! - **HIDP** report parsing (for consistent axis/button/hat events)
+//! - **HID PID** force feedback report encoding
 //! - **XInput** controller support
 //! - **Raw Input** helpers for keyboard/mouse ingestion (WM_INPUT parsing)
+//! - a managed **Raw Input pump** (hidden message-only window on a background thread)
+//! - window-less **hotplug** notifications (usable from services)
 //!
 //! Most users should not interact with these modules directly. Prefer the high-level
 //! [`Manager`](crate::manager::Manager) API:
@@ -17,11 +21,18 @@
 //! The Raw Input parser is exposed to support host applications that own the Win32
 //! message loop and want to forward WM_INPUT packets into StickUp.
 
+mod container_id;
 pub mod hid_device;
 pub mod hid_discovery;
+mod hid_pid;
 pub mod hidp_parser;
+pub mod hotplug;
+mod overlapped;
+pub mod raw_devices;
 pub mod raw_input;
+pub mod raw_input_pump;
 pub mod xinput_devices;
 
 pub use hid_discovery::probe_devices;
 pub use hid_discovery::probe_devices_with_debug;
+pub use hid_discovery::probe_devices_with_quirks;
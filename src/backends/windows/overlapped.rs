@@ -0,0 +1,251 @@
+#![cfg(target_os = "windows")]
+
+//! Overlapped HID input reads completed on an I/O completion port.
+//!
+//! Non-blocking `hidapi` reads only notice a report on the next poll, so a report's timestamp
+//! (and everything derived from it) lags by up to a poll interval. Here every device keeps one
+//! overlapped `ReadFile` pending on the handle its [`WinHidpParser`] already holds; a single
+//! process-wide thread waits on the completion port, stamps each report the moment it
+//! completes, queues it for the device, and re-arms the read. [`HidInputDevice`] drains the
+//! queue on poll, so timestamps reflect arrival rather than polling.
+//!
+//! Each device's queue holds at most [`REPORT_QUEUE_LEN`] reports; if the host stops polling,
+//! the oldest are discarded (reports carry full state, so the newest matter most).
+//!
+//! A failed read is reported once as [`Completion::Failed`] and not re-armed (a device that
+//! was unplugged would fail again at once); the device then reads through `hidapi` instead.
+//!
+//! hid.dll issues its own overlapped IOCTLs on the same handle (`HidD_GetFeature`,
+//! string queries); their completions also reach the port and are ignored.
+//!
+//! [`WinHidpParser`]: super::hidp_parser::WinHidpParser
+//! [`HidInputDevice`]: super::hid_device::HidInputDevice
+
+use std::cell::UnsafeCell;
+use std::mem::offset_of;
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{Receiver, Sender, TrySendError};
+use windows_sys::Win32::Foundation::{
+    GetLastError, ERROR_IO_PENDING, ERROR_OPERATION_ABORTED, HANDLE, INVALID_HANDLE_VALUE,
+};
+use windows_sys::Win32::Storage::FileSystem::ReadFile;
+use windows_sys::Win32::System::Threading::INFINITE;
+use windows_sys::Win32::System::IO::{
+    CancelIoEx, CreateIoCompletionPort, GetQueuedCompletionStatus, OVERLAPPED,
+};
+
+/// Reports buffered per device between polls.
+pub(crate) const REPORT_QUEUE_LEN: usize = 256;
+
+/// How long dropping a reader waits for its cancelled read to complete.
+const CANCEL_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A completed read, as seen by the device.
+pub(crate) enum Completion {
+    /// Report bytes (including the report ID byte) and their arrival time.
+    Report(Instant, Vec<u8>),
+    /// The read failed with this Win32 error; no further reports follow.
+    Failed(u32),
+}
+
+/// One device's pending read. Shared between the device and the port thread; every issued
+/// read holds one extra strong reference, released when its completion is processed.
+struct ReadSlot {
+    ov: UnsafeCell<OVERLAPPED>,
+    buf: UnsafeCell<Vec<u8>>,
+    handle: HANDLE,
+    closing: AtomicBool,
+    tx: Mutex<Option<Sender<Completion>>>,
+    /// Lets the port thread discard the oldest queued report when the queue is full.
+    rx: Receiver<Completion>,
+}
+
+// The OVERLAPPED and buffer are only touched by the kernel while a read is pending, and by
+// the port thread once it has completed; the device side never accesses them.
+unsafe impl Send for ReadSlot {}
+unsafe impl Sync for ReadSlot {}
+
+impl ReadSlot {
+    fn send(&self, c: Completion) -> bool {
+        let guard = self.tx.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(tx) = guard.as_ref() else {
+            return false;
+        };
+        match tx.try_send(c) {
+            Ok(()) => true,
+            Err(TrySendError::Full(c)) => {
+                let _ = self.rx.try_recv();
+                tx.try_send(c).is_ok()
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+
+    /// Stop reporting; lets the device know the last read has completed.
+    fn finish(&self) {
+        self.tx.lock().unwrap_or_else(|e| e.into_inner()).take();
+    }
+}
+
+/// Address of `slot`'s OVERLAPPED, computed without dereferencing (the slot may be gone when
+/// a stray completion carrying its key arrives).
+fn ov_of(slot: *const ReadSlot) -> *mut OVERLAPPED {
+    (slot as usize + offset_of!(ReadSlot, ov)) as *mut OVERLAPPED
+}
+
+/// Issue the next read on `slot`.
+fn issue(slot: &Arc<ReadSlot>) -> Result<(), u32> {
+    let raw = Arc::into_raw(slot.clone());
+    unsafe {
+        let ov = slot.ov.get();
+        std::ptr::write_bytes(ov, 0, 1);
+        let buf = &mut *slot.buf.get();
+        let ok = ReadFile(
+            slot.handle,
+            buf.as_mut_ptr(),
+            buf.len() as u32,
+            null_mut(),
+            ov,
+        );
+        if ok == 0 {
+            let code = GetLastError();
+            if code != ERROR_IO_PENDING {
+                drop(Arc::from_raw(raw));
+                return Err(code);
+            }
+        }
+    }
+    // Synchronous completions are queued to the port as well.
+    Ok(())
+}
+
+/// Handle a completed read of `slot` (`error` = 0 on success).
+fn complete(slot: Arc<ReadSlot>, n: u32, error: u32) {
+    if slot.closing.load(Ordering::Acquire) {
+        slot.finish();
+        return;
+    }
+    if error != 0 {
+        if error != ERROR_OPERATION_ABORTED {
+            slot.send(Completion::Failed(error));
+        }
+        slot.finish();
+        return;
+    }
+    let now = Instant::now();
+    let data = unsafe { (&*slot.buf.get())[..n as usize].to_vec() };
+    if !slot.send(Completion::Report(now, data)) {
+        slot.finish();
+        return;
+    }
+    if let Err(code) = issue(&slot) {
+        slot.send(Completion::Failed(code));
+        slot.finish();
+    }
+}
+
+struct Port(HANDLE);
+
+unsafe impl Send for Port {}
+unsafe impl Sync for Port {}
+
+/// The process-wide completion port, created (with its thread) on first use.
+fn port() -> Option<HANDLE> {
+    static PORT: OnceLock<Option<Port>> = OnceLock::new();
+    PORT.get_or_init(|| {
+        let h = unsafe { CreateIoCompletionPort(INVALID_HANDLE_VALUE, null_mut(), 0, 1) };
+        if h.is_null() {
+            return None;
+        }
+        let port = Port(h);
+        std::thread::Builder::new()
+            .name("stickup-hid-iocp".into())
+            .spawn(move || run(port))
+            .ok()?;
+        Some(Port(h))
+    })
+    .as_ref()
+    .map(|p| p.0)
+}
+
+fn run(port: Port) {
+    loop {
+        let mut n = 0u32;
+        let mut key = 0usize;
+        let mut ov: *mut OVERLAPPED = null_mut();
+        let ok = unsafe { GetQueuedCompletionStatus(port.0, &mut n, &mut key, &mut ov, INFINITE) };
+        if ov.is_null() {
+            if ok == 0 {
+                return; // port closed
+            }
+            continue;
+        }
+        let slot = key as *const ReadSlot;
+        if slot.is_null() || ov != ov_of(slot) {
+            continue; // hid.dll's own request on the handle
+        }
+        let error = if ok == 0 {
+            unsafe { GetLastError() }
+        } else {
+            0
+        };
+        // Releases the reference taken by `issue`.
+        let slot = unsafe { Arc::from_raw(slot) };
+        complete(slot, n, error);
+    }
+}
+
+/// Overlapped reads of one device handle (see module docs).
+pub(crate) struct OverlappedReader {
+    slot: Arc<ReadSlot>,
+    rx: Receiver<Completion>,
+}
+
+impl OverlappedReader {
+    /// Start reading reports of up to `report_len` bytes from `handle`.
+    ///
+    /// # Safety
+    /// `handle` must be opened with `FILE_FLAG_OVERLAPPED`, must not be associated with
+    /// another completion port, and must stay open until the reader is dropped.
+    pub(crate) unsafe fn start(handle: HANDLE, report_len: usize) -> Result<Self, u32> {
+        let port = port().ok_or_else(|| unsafe { GetLastError() })?;
+        let (tx, rx) = crossbeam_channel::bounded(REPORT_QUEUE_LEN);
+        let slot = Arc::new(ReadSlot {
+            ov: UnsafeCell::new(unsafe { std::mem::zeroed() }),
+            buf: UnsafeCell::new(vec![0u8; report_len.max(1)]),
+            handle,
+            closing: AtomicBool::new(false),
+            tx: Mutex::new(Some(tx)),
+            rx: rx.clone(),
+        });
+        let key = Arc::as_ptr(&slot) as usize;
+        if unsafe { CreateIoCompletionPort(handle, port, key, 0) }.is_null() {
+            return Err(unsafe { GetLastError() });
+        }
+        issue(&slot)?;
+        Ok(Self { slot, rx })
+    }
+
+    /// Next completed read, if any.
+    pub(crate) fn try_next(&self) -> Option<Completion> {
+        self.rx.try_recv().ok()
+    }
+}
+
+impl Drop for OverlappedReader {
+    fn drop(&mut self) {
+        self.slot.closing.store(true, Ordering::Release);
+        unsafe { CancelIoEx(self.slot.handle, self.slot.ov.get()) };
+        // The handle is closed right after this; wait until the kernel is done with the read.
+        let deadline = Instant::now() + CANCEL_TIMEOUT;
+        while self
+            .rx
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            .is_ok()
+        {}
+    }
+}
@@ -0,0 +1,254 @@
+//! Keyboard and mouse devices fed by Windows Raw Input.
+//!
+//! [`RawKeyboardDevice`] and [`RawMouseDevice`] wrap the packets parsed by
+//! [`raw_input`](super::raw_input) as regular [`Device`]s, so keys, mouse buttons, and mouse
+//! deltas flow through [`Manager::poll_events`](crate::manager::Manager::poll_events), show up
+//! in snapshots, and can be bound like joystick channels.
+//!
+//! Raw Input is push-based: the [`Manager`](crate::manager::Manager) creates one device per
+//! Raw Input handle on its first packet (from [`handle_wm_input`], the byte variant, or the
+//! managed pump) and queues converted events into it; `poll()` drains the queue.
+//!
+//! # Identity
+//! Device ids are the Raw Input interface path (`RIDI_DEVICENAME`), which is stable for a
+//! physical device across runs. Devices without a name fall back to `rawkbd:<handle>` /
+//! `rawmouse:<handle>`, which is only stable for the session.
+//!
+//! # Channels
+//! - Keyboard: emits [`KeyPressed`](InputKind::KeyPressed) / [`KeyReleased`](InputKind::KeyReleased).
+//!   Each key is described as a button channel (index [`key_index`], label [`key_label`]) when
+//!   first seen.
+//! - Mouse: relative axes ([`RelativeMoved`](InputKind::RelativeMoved)) `0=dx`, `1=dy` (raw
+//!   counts), `2=wheel`, `3=hwheel` (notches); buttons `0=lmb`, `1=rmb`, `2=mmb`, `3=x1`,
+//!   `4=x2`.
+//!
+//! [`handle_wm_input`]: crate::manager::Manager::handle_wm_input
+
+#![cfg(target_os = "windows")]
+
+use core::ffi::c_void;
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+
+use windows_sys::Win32::Foundation::HANDLE;
+
+use super::raw_input::{device_name, RawKeyboardPacket, RawMousePacket};
+use crate::device::Device;
+use crate::event::{ChannelDesc, ChannelKind, ChannelUnit, InputKind};
+use crate::keys::{key_index, key_label};
+use crate::metadata::DeviceMeta;
+
+/// Mouse axis names by index.
+const MOUSE_AXES: [&str; 4] = ["dx", "dy", "wheel", "hwheel"];
+
+/// Mouse buttons: `(down flag, up flag)` from RAWMOUSE `usButtonFlags` (RI_MOUSE_*), by index.
+const MOUSE_BUTTONS: [(u16, u16, &str); 5] = [
+    (0x0001, 0x0002, "lmb"),
+    (0x0004, 0x0008, "rmb"),
+    (0x0010, 0x0020, "mmb"),
+    (0x0040, 0x0080, "x1"),
+    (0x0100, 0x0200, "x2"),
+];
+
+/// Standard `WHEEL_DELTA` (one notch).
+const WHEEL_DELTA: f32 = 120.0;
+
+/// Stable id for a Raw Input handle (interface path when available).
+pub(crate) fn raw_device_id(hdevice: HANDLE, fallback_prefix: &str) -> String {
+    match device_name(hdevice) {
+        Some(s) => s,
+        None => format!("{fallback_prefix}:{:p}", hdevice as *const c_void),
+    }
+}
+
+/// Metadata for a Raw Input device with Generic Desktop `usage` (keyboard 0x06, mouse 0x02).
+fn raw_meta(id: &str, usage: u16) -> DeviceMeta {
+    DeviceMeta {
+        bus: Some("rawinput".into()),
+        path: Some(id.to_string()),
+        usage_page: Some(0x01),
+        usage: Some(usage),
+        ..DeviceMeta::default()
+    }
+}
+
+/// Producer side of a raw device: the manager pushes packets, the device drains events.
+#[derive(Clone, Default)]
+pub(crate) struct RawSink {
+    events: Arc<Mutex<Vec<InputKind>>>,
+    keys: Arc<Mutex<BTreeSet<u16>>>,
+}
+
+impl RawSink {
+    /// Queue a keyboard packet. Returns the key index if this key was never seen before.
+    pub(crate) fn push_keyboard(&self, k: &RawKeyboardPacket) -> Option<u16> {
+        let (scancode, extended) = (k.scancode, k.is_extended);
+        let ev = if k.is_break {
+            InputKind::KeyReleased { scancode, extended }
+        } else {
+            InputKind::KeyPressed { scancode, extended }
+        };
+        lock(&self.events).push(ev);
+        let idx = key_index(scancode, extended);
+        lock(&self.keys).insert(idx).then_some(idx)
+    }
+
+    /// Queue a mouse packet (relative motion, button transitions as edges).
+    pub(crate) fn push_mouse(&self, m: &RawMousePacket) {
+        let mut events = lock(&self.events);
+        let axes = [
+            m.dx as f32,
+            m.dy as f32,
+            m.wheel_delta as f32 / WHEEL_DELTA,
+            m.hwheel_delta as f32 / WHEEL_DELTA,
+        ];
+        for (axis, delta) in axes.into_iter().enumerate() {
+            if delta != 0.0 {
+                events.push(InputKind::RelativeMoved {
+                    axis: axis as u16,
+                    delta,
+                });
+            }
+        }
+        for (button, (down, up, _)) in MOUSE_BUTTONS.iter().enumerate() {
+            let button = button as u16;
+            if m.buttons_flags & down != 0 {
+                events.push(InputKind::ButtonPressed { button });
+            }
+            if m.buttons_flags & up != 0 {
+                events.push(InputKind::ButtonReleased { button });
+            }
+        }
+    }
+
+    fn drain(&self) -> Vec<InputKind> {
+        std::mem::take(&mut *lock(&self.events))
+    }
+}
+
+fn lock<T>(m: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Channel descriptor for a keyboard key.
+pub(crate) fn key_desc(key_idx: u16) -> ChannelDesc {
+    ChannelDesc {
+        kind: ChannelKind::Button,
+        idx: key_idx,
+        name: Some(key_label(key_idx)),
+        logical_min: 0,
+        logical_max: 1,
+        usage_page: None,
+        usage: None,
+        unit: Some(ChannelUnit::Boolean),
+        collection: None,
+        collection_name: None,
+    }
+}
+
+/// A Raw Input keyboard.
+pub struct RawKeyboardDevice {
+    id: String,
+    sink: RawSink,
+}
+
+impl RawKeyboardDevice {
+    /// Create the device for a Raw Input handle, returning the sink that feeds it.
+    pub(crate) fn new(hdevice: HANDLE) -> (Self, RawSink) {
+        let sink = RawSink::default();
+        let dev = Self {
+            id: raw_device_id(hdevice, "rawkbd"),
+            sink: sink.clone(),
+        };
+        (dev, sink)
+    }
+}
+
+impl Device for RawKeyboardDevice {
+    fn poll(&mut self) -> Vec<InputKind> {
+        self.sink.drain()
+    }
+
+    fn name(&self) -> &str {
+        "Keyboard"
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn metadata(&self) -> DeviceMeta {
+        raw_meta(&self.id, 0x06)
+    }
+
+    fn describe(&self) -> Vec<ChannelDesc> {
+        lock(&self.sink.keys).iter().map(|&k| key_desc(k)).collect()
+    }
+}
+
+/// A Raw Input mouse.
+pub struct RawMouseDevice {
+    id: String,
+    sink: RawSink,
+}
+
+impl RawMouseDevice {
+    /// Create the device for a Raw Input handle, returning the sink that feeds it.
+    pub(crate) fn new(hdevice: HANDLE) -> (Self, RawSink) {
+        let sink = RawSink::default();
+        let dev = Self {
+            id: raw_device_id(hdevice, "rawmouse"),
+            sink: sink.clone(),
+        };
+        (dev, sink)
+    }
+}
+
+impl Device for RawMouseDevice {
+    fn poll(&mut self) -> Vec<InputKind> {
+        self.sink.drain()
+    }
+
+    fn name(&self) -> &str {
+        "Mouse"
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn metadata(&self) -> DeviceMeta {
+        raw_meta(&self.id, 0x02)
+    }
+
+    fn describe(&self) -> Vec<ChannelDesc> {
+        let axes = MOUSE_AXES.iter().enumerate().map(|(i, name)| ChannelDesc {
+            kind: ChannelKind::Axis,
+            idx: i as u16,
+            name: Some(name.to_string()),
+            logical_min: -32768,
+            logical_max: 32767,
+            usage_page: None,
+            usage: None,
+            unit: Some(ChannelUnit::Counts),
+            collection: None,
+            collection_name: None,
+        });
+        let buttons = MOUSE_BUTTONS
+            .iter()
+            .enumerate()
+            .map(|(i, (_, _, name))| ChannelDesc {
+                kind: ChannelKind::Button,
+                idx: i as u16,
+                name: Some(name.to_string()),
+                logical_min: 0,
+                logical_max: 1,
+                usage_page: None,
+                usage: None,
+                unit: Some(ChannelUnit::Boolean),
+                collection: None,
+                collection_name: None,
+            });
+        axes.chain(buttons).collect()
+    }
+}
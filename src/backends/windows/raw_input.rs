@@ -17,7 +17,8 @@
 //! - Mouse deltas are reported in **raw OS units** (counts) as provided by Raw Input.
 //! - Wheel deltas are reported in **raw WHEEL_DELTA units** (typically ±120 per notch).
 //! - Keyboard identity is represented as `(scancode, extended)` and can be packed into a `u16`
-//!   via [`pack_key_index`]. This is intended for stable binding keys, not character mapping.
+//!   via [`key_index`](crate::keys::key_index). This is intended for stable binding keys, not
+//!   character mapping.
 
 #![cfg(target_os = "windows")]
 
@@ -225,19 +226,3 @@ pub(crate) fn device_name(hdev: HANDLE) -> Option<String> {
         Some(String::from_utf16_lossy(&wide))
     }
 }
-
-/// Pack a keyboard key identity into a stable `u16` index.
-///
-/// Layout:
-/// - low 15 bits = scancode
-/// - high bit    = extended flag
-///
-/// This is intended for **stable bindings** (e.g. "key_1E" vs "key_E01D"), not for mapping to text.
-#[inline]
-pub(crate) fn pack_key_index(scancode: u16, is_extended: bool) -> u16 {
-    let mut idx = scancode & 0x7FFF;
-    if is_extended {
-        idx |= 0x8000;
-    }
-    idx
-}
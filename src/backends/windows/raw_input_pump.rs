@@ -0,0 +1,260 @@
+//! Managed Raw Input message pump.
+//!
+//! [`Manager::handle_wm_input`](crate::manager::Manager::handle_wm_input) expects the host to
+//! own a window and a message loop. Console tools, services, and engines without an HWND
+//! can use this pump instead: it runs a hidden message-only window on a background thread,
+//! registers it for keyboard and/or mouse Raw Input (`RIDEV_INPUTSINK`, so input arrives
+//! regardless of focus), and forwards copied `RID_INPUT` payloads over a channel.
+//!
+//! Start it via [`Manager::start_raw_input`](crate::manager::Manager::start_raw_input); the
+//! manager drains the payloads in [`poll_events`](crate::manager::Manager::poll_events) and
+//! registers each keyboard/mouse as a managed device on first input.
+//!
+//! `RegisterRawInputDevices` is process-wide: each usage (keyboard, mouse) has a single
+//! target window per process. Starting the pump replaces any registration the host
+//! application (or a library it uses) made for the selected classes, so that window stops
+//! receiving `WM_INPUT` for them; stopping the pump does not restore it.
+
+#![cfg(target_os = "windows")]
+
+use core::ffi::c_void;
+use std::sync::mpsc::{self, Receiver, TryIter};
+use std::thread::JoinHandle;
+
+use windows_sys::Win32::Foundation::{GetLastError, HWND, LPARAM, LRESULT, WPARAM};
+use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows_sys::Win32::System::Threading::GetCurrentThreadId;
+use windows_sys::Win32::UI::Input::{
+    GetRawInputData, RegisterRawInputDevices, RAWINPUTDEVICE, RAWINPUTHEADER, RID_INPUT,
+};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, GetMessageW,
+    PostThreadMessageW, RegisterClassExW, HWND_MESSAGE, MSG, WM_INPUT, WM_QUIT, WNDCLASSEXW,
+};
+
+use crate::{Error, Result};
+
+// Local constants (avoid relying on module exports that vary by windows-sys version)
+const RIDEV_INPUTSINK: u32 = 0x0000_0100;
+const HID_USAGE_PAGE_GENERIC: u16 = 0x01;
+const HID_USAGE_GENERIC_MOUSE: u16 = 0x02;
+const HID_USAGE_GENERIC_KEYBOARD: u16 = 0x06;
+
+/// Which device classes the pump registers for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RawInputOptions {
+    /// Receive keyboard input.
+    pub keyboard: bool,
+    /// Receive mouse input.
+    pub mouse: bool,
+}
+
+impl Default for RawInputOptions {
+    fn default() -> Self {
+        Self {
+            keyboard: true,
+            mouse: true,
+        }
+    }
+}
+
+/// Background Raw Input pump. Stops its thread and window on drop.
+pub struct RawInputPump {
+    rx: Receiver<Vec<u8>>,
+    thread_id: u32,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl std::fmt::Debug for RawInputPump {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RawInputPump")
+            .field("thread_id", &self.thread_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RawInputPump {
+    /// Spawn the pump thread, create its window, and register for Raw Input.
+    ///
+    /// Fails with [`Error::Unsupported`] if `options` selects nothing, or [`Error::Other`] if
+    /// window creation or registration fails.
+    pub fn start(options: RawInputOptions) -> Result<Self> {
+        if !options.keyboard && !options.mouse {
+            return Err(Error::Unsupported(
+                "raw input pump without keyboard or mouse",
+            ));
+        }
+        let (tx, rx) = mpsc::channel::<Vec<u8>>();
+        let (ready_tx, ready_rx) = mpsc::channel::<Result<u32>>();
+        let thread = std::thread::Builder::new()
+            .name("stickup-raw-input".into())
+            .spawn(move || {
+                let hwnd = match unsafe { create_window(options) } {
+                    Ok(hwnd) => hwnd,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                };
+                let _ = ready_tx.send(Ok(unsafe { GetCurrentThreadId() }));
+                unsafe { pump(&tx) };
+                unsafe { DestroyWindow(hwnd) };
+            })
+            .map_err(|e| Error::Other(format!("spawn raw input thread: {e}")))?;
+
+        match ready_rx.recv() {
+            Ok(Ok(thread_id)) => Ok(Self {
+                rx,
+                thread_id,
+                thread: Some(thread),
+            }),
+            Ok(Err(e)) => {
+                let _ = thread.join();
+                Err(e)
+            }
+            Err(_) => {
+                let _ = thread.join();
+                Err(Error::Other(
+                    "raw input thread exited during startup".into(),
+                ))
+            }
+        }
+    }
+
+    /// Drain the `RID_INPUT` payloads received since the last call.
+    pub fn drain(&self) -> TryIter<'_, Vec<u8>> {
+        self.rx.try_iter()
+    }
+}
+
+impl Drop for RawInputPump {
+    fn drop(&mut self) {
+        unsafe { PostThreadMessageW(self.thread_id, WM_QUIT, 0, 0) };
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+unsafe extern "system" fn wnd_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    DefWindowProcW(hwnd, msg, wparam, lparam)
+}
+
+fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// Create the message-only window and register it as the Raw Input target.
+unsafe fn create_window(options: RawInputOptions) -> Result<HWND> {
+    let class_name = wide("StickUpRawInputPump");
+    let hinstance = GetModuleHandleW(core::ptr::null());
+
+    let mut wc: WNDCLASSEXW = core::mem::zeroed();
+    wc.cbSize = core::mem::size_of::<WNDCLASSEXW>() as u32;
+    wc.lpfnWndProc = Some(wnd_proc);
+    wc.hInstance = hinstance;
+    wc.lpszClassName = class_name.as_ptr();
+    // Fails harmlessly with ERROR_CLASS_ALREADY_EXISTS when a previous pump registered it.
+    RegisterClassExW(&wc);
+
+    let hwnd = CreateWindowExW(
+        0,
+        class_name.as_ptr(),
+        class_name.as_ptr(),
+        0,
+        0,
+        0,
+        0,
+        0,
+        HWND_MESSAGE,
+        core::ptr::null_mut(),
+        hinstance,
+        core::ptr::null(),
+    );
+    if hwnd.is_null() {
+        return Err(Error::Other(format!(
+            "CreateWindowExW failed (error {})",
+            GetLastError()
+        )));
+    }
+
+    let mut devices: Vec<RAWINPUTDEVICE> = Vec::new();
+    if options.keyboard {
+        devices.push(RAWINPUTDEVICE {
+            usUsagePage: HID_USAGE_PAGE_GENERIC,
+            usUsage: HID_USAGE_GENERIC_KEYBOARD,
+            dwFlags: RIDEV_INPUTSINK,
+            hwndTarget: hwnd,
+        });
+    }
+    if options.mouse {
+        devices.push(RAWINPUTDEVICE {
+            usUsagePage: HID_USAGE_PAGE_GENERIC,
+            usUsage: HID_USAGE_GENERIC_MOUSE,
+            dwFlags: RIDEV_INPUTSINK,
+            hwndTarget: hwnd,
+        });
+    }
+    let ok = RegisterRawInputDevices(
+        devices.as_ptr(),
+        devices.len() as u32,
+        core::mem::size_of::<RAWINPUTDEVICE>() as u32,
+    );
+    if ok == 0 {
+        let err = GetLastError();
+        DestroyWindow(hwnd);
+        return Err(Error::Other(format!(
+            "RegisterRawInputDevices failed (error {err})"
+        )));
+    }
+    Ok(hwnd)
+}
+
+/// Run the message loop until `WM_QUIT`, forwarding copied `WM_INPUT` payloads.
+unsafe fn pump(tx: &mpsc::Sender<Vec<u8>>) {
+    let mut msg: MSG = core::mem::zeroed();
+    while GetMessageW(&mut msg, core::ptr::null_mut(), 0, 0) > 0 {
+        if msg.message == WM_INPUT {
+            if let Some(bytes) = copy_raw_input(msg.lParam) {
+                // The receiver is gone only while the pump is being dropped.
+                let _ = tx.send(bytes);
+            }
+        }
+        // DefWindowProc performs the WM_INPUT cleanup.
+        DispatchMessageW(&msg);
+    }
+}
+
+/// Copy the `RID_INPUT` payload of a `WM_INPUT` lparam.
+unsafe fn copy_raw_input(lparam: LPARAM) -> Option<Vec<u8>> {
+    let header = core::mem::size_of::<RAWINPUTHEADER>() as u32;
+    let mut size: u32 = 0;
+    let r0 = GetRawInputData(
+        lparam as _,
+        RID_INPUT,
+        core::ptr::null_mut(),
+        &mut size,
+        header,
+    );
+    if r0 == u32::MAX || size == 0 {
+        return None;
+    }
+    let mut buf = vec![0u8; size as usize];
+    let r1 = GetRawInputData(
+        lparam as _,
+        RID_INPUT,
+        buf.as_mut_ptr() as *mut c_void,
+        &mut size,
+        header,
+    );
+    if r1 == u32::MAX {
+        return None;
+    }
+    buf.truncate(r1 as usize);
+    Some(buf)
+}
@@ -34,18 +34,28 @@
 //! - `hat = 0` uses the same 8-way convention as HID hats:
 //!   `-1` neutral, `0..7` directions (Up=0 clockwise).
 //!
+//! # Connection state
+//! Discovery only wraps connected slots. A wrapped slot keeps its device entry across
+//! unplug/replug; [`Device::is_connected`] reflects the last poll, and the
+//! [`Manager`](crate::manager::Manager) turns transitions into
+//! [`ManagerEvent`](crate::manager::ManagerEvent)s.
+//!
 //! # Debug logging
 //! When compiled with the `debug-log` feature, the first successful poll after a
 //! disconnect/reconnect logs a `[XINPUT/CONNECT]` line including the device id/fingerprint.
 //!
-//! # Limitations
-//! - XInput does not expose full HID descriptors, so [`Device::describe`] is currently a stub
-//!   in this module. If your UI depends on channel descriptions, fill it in with stable
-//!   `ChannelDesc` entries matching the axis/button/hat indices documented above.
-
-use crate::device::{Device, DeviceFingerprint};
-use crate::event::{ChannelDesc, ChannelKind, InputKind};
+//! # Capabilities
+//! XInput does not expose HID descriptors. Instead, `XInputGetCapabilities` reports the
+//! device subtype (gamepad, wheel, arcade stick, flight stick, ...) and which buttons/axes the
+//! device actually has. [`Device::describe`] lists only those channels (indices stay as
+//! documented above), and [`Device::name`] includes the subtype (`"XInput Wheel 0"`).
+//! If capabilities are unavailable (slot empty at construction), the full gamepad layout is
+//! described until the controller connects.
+
+use crate::device::{BatteryLevel, BatteryStatus, Device, DeviceFingerprint};
+use crate::event::{ChannelDesc, ChannelKind, ChannelUnit, InputKind};
 use crate::metadata::DeviceMeta;
+use crate::{Error, Result};
 
 use std::time::Instant;
 
@@ -57,6 +67,105 @@ use windows_sys::Win32::UI::Input::XboxController::*;
 /// Indices are documented in the module-level docs.
 const MAX_AXES: usize = 6;
 
+/// XInput buttons mapped to StickUp button indices (`(mask, index)`).
+///
+/// Indices are arbitrary but stable; names are in [`BUTTON_NAMES`].
+const BUTTON_MAP: [(u16, u16); 10] = [
+    (XINPUT_GAMEPAD_A, 0),
+    (XINPUT_GAMEPAD_B, 1),
+    (XINPUT_GAMEPAD_X, 2),
+    (XINPUT_GAMEPAD_Y, 3),
+    (XINPUT_GAMEPAD_LEFT_SHOULDER, 4),
+    (XINPUT_GAMEPAD_RIGHT_SHOULDER, 5),
+    (XINPUT_GAMEPAD_BACK, 6),
+    (XINPUT_GAMEPAD_START, 7),
+    (XINPUT_GAMEPAD_LEFT_THUMB, 8),
+    (XINPUT_GAMEPAD_RIGHT_THUMB, 9),
+];
+
+/// Button names by StickUp index (see [`BUTTON_MAP`]).
+const BUTTON_NAMES: [&str; 10] = [
+    "A", "B", "X", "Y", "LB", "RB", "Back", "Start", "LThumb", "RThumb",
+];
+
+/// All four DPad bits.
+const DPAD_MASK: u16 = XINPUT_GAMEPAD_DPAD_UP
+    | XINPUT_GAMEPAD_DPAD_DOWN
+    | XINPUT_GAMEPAD_DPAD_LEFT
+    | XINPUT_GAMEPAD_DPAD_RIGHT;
+
+/// Number of XInput user slots.
+pub const SLOT_COUNT: u32 = 4;
+
+/// Is a controller currently attached to XInput slot `index`?
+///
+/// Querying empty slots is comparatively slow in XInput; callers polling for new
+/// controllers should throttle this.
+pub fn slot_connected(index: u32) -> bool {
+    let mut state: XINPUT_STATE = unsafe { std::mem::zeroed() };
+    // NOTE: XInputGetState returns 0 on success.
+    unsafe { XInputGetState(index, &mut state) == 0 }
+}
+
+/// What a slot's controller supports, from `XInputGetCapabilities`.
+#[derive(Clone, Copy, Debug)]
+struct SlotCaps {
+    /// `XINPUT_DEVSUBTYPE_*` value.
+    subtype: u8,
+    /// Supported `wButtons` bits.
+    buttons: u16,
+    /// Supported axes, by StickUp axis index.
+    axes: [bool; MAX_AXES],
+}
+
+impl SlotCaps {
+    /// Query capabilities for slot `index` (`None` if the slot is empty).
+    fn query(index: u32) -> Option<Self> {
+        let mut caps: XINPUT_CAPABILITIES = unsafe { std::mem::zeroed() };
+        // NOTE: returns 0 on success (ERROR_DEVICE_NOT_CONNECTED otherwise).
+        if unsafe { XInputGetCapabilities(index, 0, &mut caps) } != 0 {
+            return None;
+        }
+        // In the capabilities struct, non-zero fields mark supported controls.
+        let gp = caps.Gamepad;
+        Some(Self {
+            subtype: caps.SubType as u8,
+            buttons: gp.wButtons,
+            axes: [
+                gp.sThumbLX != 0,
+                gp.sThumbLY != 0,
+                gp.sThumbRX != 0,
+                gp.sThumbRY != 0,
+                gp.bLeftTrigger != 0,
+                gp.bRightTrigger != 0,
+            ],
+        })
+    }
+
+    fn has_axis(caps: Option<Self>, i: usize) -> bool {
+        caps.map_or(true, |c| c.axes[i])
+    }
+
+    fn has_button(caps: Option<Self>, mask: u16) -> bool {
+        caps.map_or(true, |c| c.buttons & mask != 0)
+    }
+}
+
+/// Display name for an `XINPUT_DEVSUBTYPE_*` value.
+fn subtype_name(subtype: u8) -> &'static str {
+    match subtype {
+        0x01 => "Gamepad",
+        0x02 => "Wheel",
+        0x03 => "Arcade Stick",
+        0x04 => "Flight Stick",
+        0x05 => "Dance Pad",
+        0x06 | 0x07 | 0x0B => "Guitar",
+        0x08 => "Drum Kit",
+        0x13 => "Arcade Pad",
+        _ => "Controller",
+    }
+}
+
 /// XInput-backed device (slot 0–3).
 ///
 /// The stable device id returned by [`Device::id`] is derived from the provided
@@ -81,8 +190,10 @@ pub struct XInputDevice {
     last_hat: i8,
     /// Timestamp of last successful poll.
     last_poll: Instant,
-    /// Tracks whether we were previously connected (used for connect logging).
+    /// Whether the slot had a controller at the last poll.
     connected: bool,
+    /// Capabilities of the attached controller (`None` until one is seen).
+    caps: Option<SlotCaps>,
 }
 
 impl XInputDevice {
@@ -94,21 +205,34 @@ impl XInputDevice {
     /// - `vendor_id = 0x045e` (Microsoft, conventional)
     /// - `serial_number = Some("xinput:{slot}")`
     /// - `path = Some("xinput:{slot}")`
+    ///
+    /// The initial connection state and capabilities are queried immediately.
     pub fn new(index: u32, fingerprint: DeviceFingerprint, meta: DeviceMeta) -> Self {
-        let name = format!("XInput Controller {}", index);
-
-        Self {
+        let mut dev = Self {
             index,
             fingerprint_str: fingerprint.to_string(),
             fingerprint,
-            name,
+            name: format!("XInput Controller {}", index),
             meta,
             last_axes: [0.0; MAX_AXES],
             last_buttons: 0,
             last_hat: -1,
             last_poll: Instant::now(),
-            connected: false,
-        }
+            connected: slot_connected(index),
+            caps: None,
+        };
+        dev.refresh_caps();
+        dev
+    }
+
+    /// Re-query capabilities and update the subtype-based name.
+    fn refresh_caps(&mut self) {
+        let Some(caps) = SlotCaps::query(self.index) else {
+            return;
+        };
+        self.caps = Some(caps);
+        self.name = format!("XInput {} {}", subtype_name(caps.subtype), self.index);
+        self.meta.product_string = Some(self.name.clone());
     }
 
     #[inline]
@@ -186,10 +310,13 @@ impl Device for XInputDevice {
         let res = unsafe { XInputGetState(self.index, &mut state) };
 
         if res != 0 {
-            // Disconnected or empty slot.
+            // Disconnected or empty slot. The manager reports the transition via
+            // `is_connected`; forget the last state so a replug starts from neutral.
             if self.connected {
-                // You *could* emit "everything released" here if you want.
                 self.connected = false;
+                self.last_axes = [0.0; MAX_AXES];
+                self.last_buttons = 0;
+                self.last_hat = -1;
             }
             return events;
         }
@@ -199,6 +326,8 @@ impl Device for XInputDevice {
         self.last_poll = Instant::now();
 
         if !was_connected {
+            // A different controller may have taken the slot.
+            self.refresh_caps();
             #[cfg(feature = "debug-log")]
             eprintln!(
                 "[XINPUT/CONNECT] slot={} id={} fp={}",
@@ -222,6 +351,9 @@ impl Device for XInputDevice {
         ];
 
         for (i, &v) in new_axes.iter().enumerate() {
+            if !SlotCaps::has_axis(self.caps, i) {
+                continue;
+            }
             if (v - self.last_axes[i]).abs() > 0.001 {
                 self.last_axes[i] = v;
                 events.push(InputKind::AxisMoved {
@@ -235,27 +367,12 @@ impl Device for XInputDevice {
         let buttons: u16 = gp.wButtons;
         let changed = buttons ^ self.last_buttons;
 
-        // Map XInput buttons to stickup button indices.
-        // These indices are arbitrary, just keep them stable.
-        const BUTTON_MAP: &[(u16, u8)] = &[
-            (XINPUT_GAMEPAD_A, 0),
-            (XINPUT_GAMEPAD_B, 1),
-            (XINPUT_GAMEPAD_X, 2),
-            (XINPUT_GAMEPAD_Y, 3),
-            (XINPUT_GAMEPAD_LEFT_SHOULDER, 4),
-            (XINPUT_GAMEPAD_RIGHT_SHOULDER, 5),
-            (XINPUT_GAMEPAD_BACK, 6),
-            (XINPUT_GAMEPAD_START, 7),
-            (XINPUT_GAMEPAD_LEFT_THUMB, 8),
-            (XINPUT_GAMEPAD_RIGHT_THUMB, 9),
-        ];
-
-        for &(mask, idx) in BUTTON_MAP {
+        for &(mask, idx) in &BUTTON_MAP {
             if changed & mask != 0 {
                 if buttons & mask != 0 {
-                    events.push(InputKind::ButtonPressed { button: idx as u16 });
+                    events.push(InputKind::ButtonPressed { button: idx });
                 } else {
-                    events.push(InputKind::ButtonReleased { button: idx as u16 });
+                    events.push(InputKind::ButtonReleased { button: idx });
                 }
             }
         }
@@ -274,6 +391,12 @@ impl Device for XInputDevice {
         events
     }
 
+    /// The current pad state: the first poll reports everything away from neutral
+    /// (including released triggers at `-1`).
+    fn initial_state(&mut self) -> Vec<InputKind> {
+        self.poll()
+    }
+
     fn name(&self) -> &str {
         &self.name
     }
@@ -290,9 +413,46 @@ impl Device for XInputDevice {
         self.meta.clone()
     }
 
+    fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// The slot, which the controller's ring light shows; XInput cannot reassign it.
+    fn fixed_player_index(&self) -> Option<u8> {
+        Some(self.index as u8)
+    }
+
+    /// Query `XInputGetBatteryInformation` (bucketed level only).
+    fn battery(&self) -> Option<BatteryStatus> {
+        let mut info: XINPUT_BATTERY_INFORMATION = unsafe { std::mem::zeroed() };
+        // NOTE: returns 0 on success; device type 0 = BATTERY_DEVTYPE_GAMEPAD.
+        if unsafe { XInputGetBatteryInformation(self.index, 0, &mut info) } != 0 {
+            return None;
+        }
+        // BatteryType: 0 = disconnected, 1 = wired, 2 = alkaline, 3 = NiMH, 0xFF = unknown.
+        let wired = match info.BatteryType as u8 {
+            0x00 => return None,
+            0x01 => true,
+            _ => false,
+        };
+        let level = match info.BatteryLevel as u8 {
+            0x00 => BatteryLevel::Empty,
+            0x01 => BatteryLevel::Low,
+            0x02 => BatteryLevel::Medium,
+            _ => BatteryLevel::Full,
+        };
+        Some(BatteryStatus {
+            level,
+            percent: None,
+            charging: false,
+            wired,
+        })
+    }
+
     fn describe(&self) -> Vec<ChannelDesc> {
         // XInput does not expose HID descriptors, so we publish a stable,
-        // conventional channel map that matches `poll()`:
+        // conventional channel map that matches `poll()`, minus the controls the
+        // capabilities say are missing:
         // - 6 axes (0..5): LX, LY, RX, RY, LT, RT
         // - 10 buttons (0..9): A, B, X, Y, LB, RB, Back, Start, LThumb, RThumb
         // - 1 hat (0): DPad (-1|0..7)
@@ -304,6 +464,9 @@ impl Device for XInputDevice {
         // Axes
         const AXIS_NAMES: [&str; MAX_AXES] = ["LX", "LY", "RX", "RY", "LT", "RT"];
         for (i, &name) in AXIS_NAMES.iter().enumerate() {
+            if !SlotCaps::has_axis(self.caps, i) {
+                continue;
+            }
             out.push(ChannelDesc {
                 kind: ChannelKind::Axis,
                 idx: i as u16,
@@ -312,36 +475,67 @@ impl Device for XInputDevice {
                 logical_max: 1,
                 usage_page: None,
                 usage: None,
+                // LT / RT are triggers; sticks are unitless positions.
+                unit: (i >= 4).then_some(ChannelUnit::Percent),
+                collection: None,
+                collection_name: None,
             });
         }
 
-        // Buttons (indices must match BUTTON_MAP in `poll()`)
-        const BUTTON_NAMES: [&str; 10] = [
-            "A", "B", "X", "Y", "LB", "RB", "Back", "Start", "LThumb", "RThumb",
-        ];
-        for (i, &name) in BUTTON_NAMES.iter().enumerate() {
+        // Buttons (indices match BUTTON_MAP)
+        for &(mask, idx) in &BUTTON_MAP {
+            if !SlotCaps::has_button(self.caps, mask) {
+                continue;
+            }
             out.push(ChannelDesc {
                 kind: ChannelKind::Button,
-                idx: i as u16,
-                name: Some(name.to_string()),
+                idx,
+                name: Some(BUTTON_NAMES[idx as usize].to_string()),
                 logical_min: 0,
                 logical_max: 1,
                 usage_page: None,
                 usage: None,
+                unit: Some(ChannelUnit::Boolean),
+                collection: None,
+                collection_name: None,
             });
         }
 
         // DPad -> Hat(0)
-        out.push(ChannelDesc {
-            kind: ChannelKind::Hat,
-            idx: 0,
-            name: Some("DPad".to_string()),
-            logical_min: -1,
-            logical_max: 7,
-            usage_page: None,
-            usage: None,
-        });
+        if SlotCaps::has_button(self.caps, DPAD_MASK) {
+            out.push(ChannelDesc {
+                kind: ChannelKind::Hat,
+                idx: 0,
+                name: Some("DPad".to_string()),
+                logical_min: -1,
+                logical_max: 7,
+                usage_page: None,
+                usage: None,
+                unit: Some(ChannelUnit::HatDirection),
+                collection: None,
+                collection_name: None,
+            });
+        }
 
         out
     }
+
+    /// Drive the two XInput motors via `XInputSetState`.
+    ///
+    /// `low` maps to the left (low-frequency) motor, `high` to the right (high-frequency) one.
+    fn set_rumble(&mut self, low: f32, high: f32) -> Result<()> {
+        let vibration = XINPUT_VIBRATION {
+            wLeftMotorSpeed: (low.clamp(0.0, 1.0) * 65535.0).round() as u16,
+            wRightMotorSpeed: (high.clamp(0.0, 1.0) * 65535.0).round() as u16,
+        };
+        // NOTE: XInputSetState returns 0 on success (ERROR_DEVICE_NOT_CONNECTED otherwise).
+        let res = unsafe { XInputSetState(self.index, &vibration) };
+        if res != 0 {
+            return Err(Error::Other(format!(
+                "xinput slot {} rumble failed (code {res})",
+                self.index
+            )));
+        }
+        Ok(())
+    }
 }
@@ -15,10 +15,16 @@
 //!
 //! # Overview
 //! - [`DeviceState`]: snapshot of per-device inputs by **names** (`"X"`, `"Y"`, `"0"`, …).
-//! - [`AxisTransform`]/[`AxisCurve`]: shaping, deadzone, invert, gain, clamp.
-//! - [`BindingRule`]: declarative mapping (Axis1d / Button / Axis2d).
+//! - [`AxisTransform`]/[`AxisCurve`]: shaping, deadzone, invert, gain, clamp, and an optional
+//!   [expression](crate::expr).
+//! - [`BindingRule`]: declarative mapping (Axis1d / Button / Axis2d / Chord / Gesture / Dwell /
+//!   ButtonAxis / Interlock / HatActions / HatAxes).
 //! - [`BindingProfile`]: a named set of rules with `serde` serialization.
+//! - [`BindingLayer`]: modifier ("shift") layers that override rules while held.
+//! - [`ActionSet`]: named contexts ("flight", "menu") switched on and off at runtime.
 //! - [`BindingProfile::resolve`]: apply rules to device snapshots → [`BindingOutput`].
+//! - [`BindingProfile::load`]/[`BindingProfile::save`]: TOML/JSON files with schema
+//!   versioning and [`ProfileMigrator`] hooks.
 //!
 //! # Conventions
 //! - Axis values are assumed normalized to `[-1.0, 1.0]` **before** transforms.
@@ -63,8 +69,13 @@
 //!             control: ControlPath { control_id: "Trigger".into(), control_type: ControlType::Button },
 //!             action: "fire".into(),
 //!             axis_press_threshold: None,
+//!             hat_direction: None,
 //!         },
 //!     ],
+//!     layers: vec![],
+//!     action_sets: vec![],
+//!     required_devices: vec![],
+//!     channels: vec![],
 //! };
 //!
 //! let mut devices = HashMap::new();
@@ -82,7 +93,16 @@
 //! - **Missing inputs:** missing devices/controls resolve as inactive (`0.0`/`false`/neutral).
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::event::{DeviceId, DeviceIds};
+use crate::expr::Expr;
+use crate::gate::ChannelExpr;
+use crate::gestures::{GestureEvent, GestureTiming};
+use crate::manager::{DeviceMatcher, ManagedInfo};
+use crate::snapshot::DeviceStates;
+use crate::{Error, Result};
 
 /* =========================
    Core device state (runtime)
@@ -106,6 +126,15 @@ pub struct DeviceState {
     /// Conventionally labeled as `"hat0"`, `"hat1"`, etc. (the Windows HIDP parser follows this).
     #[serde(default)]
     pub hats: HashMap<String, i16>,
+    /// Buttons that went down since the previous poll (see [`just_pressed`](Self::just_pressed)).
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub pressed_this_frame: HashSet<String>,
+    /// Buttons that went up since the previous poll (see [`just_released`](Self::just_released)).
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub released_this_frame: HashSet<String>,
+    /// Relative motion summed over the last poll (see [`get_relative`](Self::get_relative)).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub relative: HashMap<String, f32>,
 }
 
 impl DeviceState {
@@ -128,6 +157,62 @@ impl DeviceState {
     pub fn get_hat(&self, name: &str) -> i16 {
         self.hats.get(name).copied().unwrap_or(-1)
     }
+
+    /// Relative motion of a named channel during the last poll, in device units (`0.0` if
+    /// none).
+    ///
+    /// Deltas are summed per poll, so several mouse reports in one frame add up rather than
+    /// the last one winning.
+    #[inline]
+    pub fn get_relative(&self, name: &str) -> f32 {
+        self.relative.get(name).copied().unwrap_or(0.0)
+    }
+
+    /// Did the named button go down during the last poll?
+    ///
+    /// Edges are kept from one `poll_events*` call to the next, so a tap shorter than a frame
+    /// reports both `just_pressed` and `just_released` while [`get_button`](Self::get_button)
+    /// is already `false`.
+    #[inline]
+    pub fn just_pressed(&self, name: &str) -> bool {
+        self.pressed_this_frame.contains(name)
+    }
+
+    /// Did the named button go up during the last poll?
+    #[inline]
+    pub fn just_released(&self, name: &str) -> bool {
+        self.released_this_frame.contains(name)
+    }
+
+    /// Set a button, recording a press/release edge if it changed.
+    pub(crate) fn set_button(&mut self, name: String, pressed: bool) {
+        let was = self.buttons.insert(name.clone(), pressed).unwrap_or(false);
+        match (was, pressed) {
+            (false, true) => self.pressed_this_frame.insert(name),
+            (true, false) => self.released_this_frame.insert(name),
+            _ => false,
+        };
+    }
+
+    /// Add relative motion to this frame's sum.
+    pub(crate) fn add_relative(&mut self, name: String, delta: f32) {
+        *self.relative.entry(name).or_insert(0.0) += delta;
+    }
+
+    /// Forget the edges and relative motion of the previous frame.
+    pub(crate) fn clear_edges(&mut self) {
+        self.pressed_this_frame.clear();
+        self.released_this_frame.clear();
+        self.relative.clear();
+    }
+
+    /// Any edges or relative motion recorded since the last
+    /// [`clear_edges`](Self::clear_edges)?
+    pub(crate) fn has_edges(&self) -> bool {
+        !self.pressed_this_frame.is_empty()
+            || !self.released_this_frame.is_empty()
+            || !self.relative.is_empty()
+    }
 }
 
 /* =========================
@@ -141,6 +226,16 @@ pub enum ControlType {
     Axis,
     /// Binary input channel.
     Button,
+    /// Hat (POV/D-pad): `-1` neutral, `0..7` directions (Up = 0, clockwise).
+    ///
+    /// In button rules, pair with `hat_direction` to bind one direction. In axis rules a hat
+    /// reads as `1.0` while pushed in any direction.
+    Hat,
+    /// Relative channel (mouse motion, wheel, dial): the motion summed over the last poll,
+    /// scaled by [`AxisTransform::sensitivity`].
+    ///
+    /// As a button it reads as pressed while it moved this poll.
+    Relative,
 }
 
 /// Identifies a concrete control on a device.
@@ -164,6 +259,12 @@ fn default_deadzone() -> f32 {
 fn default_gain() -> f32 {
     1.0
 }
+fn default_sensitivity() -> f32 {
+    1.0
+}
+fn is_default_sensitivity(v: &f32) -> bool {
+    *v == default_sensitivity()
+}
 fn default_min() -> f32 {
     -1.0
 }
@@ -215,11 +316,25 @@ pub struct AxisTransform {
     #[serde(default)]
     pub curve: AxisCurve,
 
+    /// Relative controls only: scale from device units (counts, notches) to the normalized
+    /// range, applied before the pipeline (`0.01` makes 100 mouse counts a full deflection).
+    #[serde(
+        default = "default_sensitivity",
+        skip_serializing_if = "is_default_sensitivity"
+    )]
+    pub sensitivity: f32,
+
     /// Clamp final result into `[min, max]` (defaults to `[-1, 1]`).
     #[serde(default = "default_min")]
     pub min: f32,
     #[serde(default = "default_max")]
     pub max: f32,
+
+    /// Optional final [expression](crate::expr) of the shaped value `x`, e.g.
+    /// `"clamp(x * 1.5, -1, 1)"`. Evaluated without history, so `smooth`/`slew` pass through;
+    /// use a [gate axis](crate::gate) for time-based filtering.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expr: Option<Expr>,
 }
 
 impl Default for AxisTransform {
@@ -229,8 +344,10 @@ impl Default for AxisTransform {
             deadzone: default_deadzone(),
             gain: default_gain(),
             curve: AxisCurve::default(),
+            sensitivity: default_sensitivity(),
             min: default_min(),
             max: default_max(),
+            expr: None,
         }
     }
 }
@@ -238,7 +355,7 @@ impl Default for AxisTransform {
 impl AxisTransform {
     /// Apply the transform pipeline to a normalized input `x ∈ [-1, 1]`.
     ///
-    /// Steps: deadzone w/ continuity → invert → curve → gain → clamp.
+    /// Steps: deadzone w/ continuity → invert → curve → gain → clamp → [`expr`](Self::expr).
     ///
     /// Note: if you already applied deadzone/curve/gain elsewhere, applying this again
     /// can cause early saturation and reduced travel.
@@ -246,7 +363,7 @@ impl AxisTransform {
     pub fn apply(&self, x: f32) -> f32 {
         // 1) deadzone with continuity remap
         let dz = self.deadzone.clamp(0.0, 0.95);
-        let mut v = {
+        let v = {
             let s = x.signum();
             let a = x.abs();
             if a <= dz {
@@ -256,7 +373,21 @@ impl AxisTransform {
                 s * ((a - dz) / (1.0 - dz))
             }
         };
+        self.shape(v)
+    }
+
+    /// Apply the transform pipeline to a relative delta (device units): scale by
+    /// [`sensitivity`](Self::sensitivity), then invert → curve → gain → clamp →
+    /// [`expr`](Self::expr).
+    ///
+    /// The deadzone is skipped: a device that did not move already reads `0.0`.
+    #[inline]
+    pub fn apply_relative(&self, delta: f32) -> f32 {
+        self.shape(delta * self.sensitivity)
+    }
 
+    /// Steps 2–6 of the pipeline (everything after the deadzone).
+    fn shape(&self, mut v: f32) -> f32 {
         // 2) invert
         if self.invert {
             v = -v;
@@ -277,7 +408,13 @@ impl AxisTransform {
         // 5) clamp
         let lo = self.min.min(self.max);
         let hi = self.min.max(self.max);
-        v.clamp(lo, hi)
+        v = v.clamp(lo, hi);
+
+        // 6) expression
+        match &self.expr {
+            Some(e) => e.eval_once(|name| (name == "x").then_some(v)),
+            None => v,
+        }
     }
 }
 
@@ -296,7 +433,11 @@ pub struct ControlPath2D {
 
 /// Declarative binding rules.
 ///
-/// Tagged enum (`kind: "axis1d" | "button" | "axis2d"`) to support clean `serde` IO.
+/// Tagged enum (`kind: "axis1d" | "button" | "axis2d" | "chord" | "gesture" | "dwell" |
+/// "button_axis" | "interlock" | "hat_actions" | "hat_axes"`) to support clean `serde` IO.
+///
+/// Hats are addressed with [`ControlType::Hat`]; a `Button` rule with `hat_direction`
+/// binds a single hat direction to an action.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub enum BindingRule {
@@ -323,6 +464,12 @@ pub enum BindingRule {
         /// Optional: threshold for axis→button synthesis (absolute), default `0.5`.
         #[serde(default)]
         axis_press_threshold: Option<f32>,
+        /// Hat controls only: direction (`0..7`, Up = 0, clockwise) that counts as pressed.
+        ///
+        /// Cardinal directions also match their adjacent diagonals (Up matches Up-Left and
+        /// Up-Right); diagonals match exactly. `None` matches any non-neutral position.
+        #[serde(default)]
+        hat_direction: Option<i16>,
         // Future: toggle/hold semantics can live here.
     },
     /// Map two controls into a 2D vector action with optional radial deadzone.
@@ -346,6 +493,268 @@ pub enum BindingRule {
         #[serde(default = "default_deadzone")]
         radial_deadzone_size: f32,
     },
+    /// Boolean action that is active while several controls are held together
+    /// (`Start+Back = menu`, two-button safety switches).
+    ///
+    /// Controls count as held like [`ModifierControl`]s (button down, axis past `0.5`, hat
+    /// pushed). [`BindingProfile::resolve`] has no notion of time and only checks that all are
+    /// held; [`BindingResolver`](crate::resolver::BindingResolver) additionally requires them
+    /// to be pressed within `window_ms` of each other.
+    Chord {
+        /// Device identifier.
+        device_id: String,
+        /// Controls that must all be held.
+        controls: Vec<ControlPath>,
+        /// Destination action name (key under [`BindingOutput::buttons`]).
+        action: String,
+        /// Maximum time between the first and last press (default `200` ms).
+        #[serde(default = "default_chord_window_ms")]
+        window_ms: u32,
+    },
+    /// Tap / hold / double-tap detection on one control, reported as [`GestureEvent`]s in
+    /// [`BindingOutput::events`].
+    ///
+    /// Gestures need time, so only [`BindingResolver`](crate::resolver::BindingResolver)
+    /// produces them; [`BindingProfile::resolve`] ignores these rules.
+    Gesture {
+        /// Device identifier.
+        device_id: String,
+        /// Source control (held like a [`ModifierControl`]).
+        control: ControlPath,
+        /// Hat controls only: direction that counts as pressed (see `Button`).
+        #[serde(default)]
+        hat_direction: Option<i16>,
+        /// Action name carried by the emitted events.
+        action: String,
+        /// Tap / hold / double-tap thresholds.
+        #[serde(default)]
+        timing: GestureTiming,
+    },
+    /// Boolean action that activates only after the control was held for `dwell_ms`
+    /// (accessibility dwell, guarded actions like ejection handles).
+    ///
+    /// While the control is held, [`BindingOutput::progress`] reports the dwell progress
+    /// (`0.0..=1.0`) for UI feedback. Only [`BindingResolver`](crate::resolver::BindingResolver)
+    /// tracks time; [`BindingProfile::resolve`] reports the action as inactive.
+    Dwell {
+        /// Device identifier.
+        device_id: String,
+        /// Source control (held like a [`ModifierControl`]).
+        control: ControlPath,
+        /// Hat controls only: direction that counts as pressed (see `Button`).
+        #[serde(default)]
+        hat_direction: Option<i16>,
+        /// Destination action name (key under [`BindingOutput::buttons`]).
+        action: String,
+        /// Hold time before the action fires (default `1000` ms).
+        #[serde(default = "default_dwell_ms")]
+        dwell_ms: u32,
+    },
+    /// Scalar action driven by a pair of buttons (keyboard throttle, trim buttons).
+    ///
+    /// See [`ButtonAxisMode`]: `absolute` jumps to fixed values, `incremental` ramps while a
+    /// button is held and keeps its value on release. Incremental axes need time, so
+    /// [`BindingProfile::resolve`] reports them as `0.0`; use
+    /// [`BindingResolver`](crate::resolver::BindingResolver).
+    ButtonAxis {
+        /// Device identifier.
+        device_id: String,
+        /// Control that pushes towards the negative end.
+        negative: ControlPath,
+        /// Control that pushes towards the positive end.
+        positive: ControlPath,
+        /// Destination action name (key under [`BindingOutput::axis`]).
+        action: String,
+        /// Absolute (default) or incremental behaviour.
+        #[serde(default)]
+        mode: ButtonAxisMode,
+    },
+    /// Two-hand safety interlock: a boolean action that engages only after all `controls`
+    /// were pressed within `window_ms` of each other and then held together for `hold_ms`.
+    ///
+    /// [`BindingResolver`](crate::resolver::BindingResolver) reports the outcome as
+    /// [`InterlockEvent`]s in [`BindingOutput::interlocks`] and the arming progress in
+    /// [`BindingOutput::progress`]. After a timeout or abort, every control must be released
+    /// before a new attempt. [`BindingProfile::resolve`] reports the action as inactive.
+    Interlock {
+        /// Device identifier.
+        device_id: String,
+        /// Controls that must be held concurrently (typically two, one per hand).
+        controls: Vec<ControlPath>,
+        /// Destination action name (key under [`BindingOutput::buttons`]).
+        action: String,
+        /// Maximum time from the first press until all controls are held (default `500` ms).
+        #[serde(default = "default_interlock_window_ms")]
+        window_ms: u32,
+        /// Time all controls must be held together before the action engages (default `1000` ms).
+        #[serde(default = "default_dwell_ms")]
+        hold_ms: u32,
+    },
+    /// Map an 8-way hat to named boolean actions (menu navigation, view snapping).
+    ///
+    /// With four `actions` they are Up, Right, Down, Left and diagonals press both neighbours;
+    /// with eight they follow the hat directions `0..7` (Up = 0, clockwise). Entries may be
+    /// empty strings to leave a direction unbound.
+    HatActions {
+        /// Device identifier.
+        device_id: String,
+        /// Source hat control.
+        control: ControlPath,
+        /// Destination action names (keys under [`BindingOutput::buttons`]).
+        actions: Vec<String>,
+    },
+    /// Map an 8-way hat to a synthetic X/Y pair (trim, view panning).
+    ///
+    /// X is `+1` for Right, Y is `-1` for Up (same convention as thumbsticks); neutral is
+    /// `[0, 0]`.
+    HatAxes {
+        /// Device identifier.
+        device_id: String,
+        /// Source hat control.
+        control: ControlPath,
+        /// Destination action name (key under [`BindingOutput::vec2`]).
+        action: String,
+        /// Scale diagonals to unit length instead of `[±1, ±1]`.
+        #[serde(default)]
+        normalize_diagonals: bool,
+    },
+}
+
+fn default_interlock_window_ms() -> u32 {
+    500
+}
+
+/// How a [`BindingRule::ButtonAxis`] turns button presses into a value.
+///
+/// Tagged enum (`type: "absolute" | "incremental"`). Holding both or neither button counts
+/// as neither.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ButtonAxisMode {
+    /// Jump to `negative_value` / `positive_value` while held, `rest_value` otherwise.
+    Absolute {
+        /// Value while the negative control is held (default `-1.0`).
+        #[serde(default = "default_min")]
+        negative_value: f32,
+        /// Value while the positive control is held (default `1.0`).
+        #[serde(default = "default_max")]
+        positive_value: f32,
+        /// Value while neither is held (default `0.0`).
+        #[serde(default)]
+        rest_value: f32,
+    },
+    /// Ramp by `rate` units per second while held, clamped to `min..=max`.
+    ///
+    /// The value starts at `0.0` (clamped into range) and stays where it is on release.
+    Incremental {
+        /// Units per second (default `1.0`).
+        #[serde(default = "default_gain")]
+        rate: f32,
+        /// Lower bound (default `-1.0`).
+        #[serde(default = "default_min")]
+        min: f32,
+        /// Upper bound (default `1.0`).
+        #[serde(default = "default_max")]
+        max: f32,
+    },
+}
+
+impl Default for ButtonAxisMode {
+    fn default() -> Self {
+        ButtonAxisMode::Absolute {
+            negative_value: -1.0,
+            positive_value: 1.0,
+            rest_value: 0.0,
+        }
+    }
+}
+
+fn default_dwell_ms() -> u32 {
+    1000
+}
+
+fn default_chord_window_ms() -> u32 {
+    200
+}
+
+/// Is `control` held: button down, axis past `0.5`, or hat matching `hat_direction`?
+#[inline]
+pub(crate) fn control_held(
+    st: &DeviceState,
+    control: &ControlPath,
+    hat_direction: Option<i16>,
+) -> bool {
+    match control.control_type {
+        ControlType::Button => st.get_button(&control.control_id),
+        ControlType::Axis => st.get_axis(&control.control_id).abs() >= 0.5,
+        ControlType::Hat => hat_matches(st.get_hat(&control.control_id), hat_direction),
+        ControlType::Relative => st.get_relative(&control.control_id) != 0.0,
+    }
+}
+
+/// `control` as a scalar shaped by `xform` (relative controls through
+/// [`AxisTransform::apply_relative`]).
+#[inline]
+fn shaped_scalar(st: &DeviceState, control: &ControlPath, xform: &AxisTransform) -> f32 {
+    let name = &control.control_id;
+    let raw = match control.control_type {
+        ControlType::Axis => st.get_axis(name),
+        ControlType::Button => {
+            if st.get_button(name) {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        ControlType::Hat => hat_scalar(st, name),
+        ControlType::Relative => return xform.apply_relative(st.get_relative(name)),
+    };
+    xform.apply(raw)
+}
+
+/// `1.0` while the hat is pushed in any direction, else `0.0`.
+#[inline]
+fn hat_scalar(st: &DeviceState, name: &str) -> f32 {
+    if st.get_hat(name) >= 0 {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Hat position as `[x, y]` (Right = `+x`, Up = `-y`), `[0, 0]` when neutral.
+#[inline]
+fn hat_vector(value: i16) -> [f32; 2] {
+    match value {
+        0 => [0.0, -1.0],
+        1 => [1.0, -1.0],
+        2 => [1.0, 0.0],
+        3 => [1.0, 1.0],
+        4 => [0.0, 1.0],
+        5 => [-1.0, 1.0],
+        6 => [-1.0, 0.0],
+        7 => [-1.0, -1.0],
+        _ => [0.0, 0.0],
+    }
+}
+
+/// Does hat `value` satisfy `direction`?
+///
+/// Cardinal directions (even) also accept their neighbouring diagonals; diagonals (odd)
+/// match exactly; `None` accepts any non-neutral value.
+#[inline]
+fn hat_matches(value: i16, direction: Option<i16>) -> bool {
+    if !(0..8).contains(&value) {
+        return false;
+    }
+    match direction {
+        None => true,
+        Some(d) if d % 2 == 0 => {
+            let d = d.rem_euclid(8);
+            value == d || value == (d + 1) % 8 || value == (d + 7) % 8
+        }
+        Some(d) => value == d.rem_euclid(8),
+    }
 }
 
 /* =========================
@@ -355,7 +764,7 @@ pub enum BindingRule {
 /// Serializable profile: a named collection of binding rules.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BindingProfile {
-    /// Schema version for forward/backward migrations.
+    /// Schema version for forward/backward migrations (see [`PROFILE_VERSION`]).
     #[serde(default)]
     pub version: u16,
     /// Human-readable profile name.
@@ -366,6 +775,136 @@ pub struct BindingProfile {
     /// Rules that define how inputs map to actions.
     #[serde(default)]
     pub bindings: Vec<BindingRule>,
+    /// Modifier ("shift") layers applied on top of `bindings` while their modifiers are held.
+    #[serde(default)]
+    pub layers: Vec<BindingLayer>,
+    /// Named contexts applied on top of `bindings` while activated by the application.
+    #[serde(default)]
+    pub action_sets: Vec<ActionSet>,
+    /// Hardware the profile needs (see
+    /// [`Manager::require_devices`](crate::manager::Manager::require_devices)).
+    #[serde(default)]
+    pub required_devices: Vec<RequiredDevice>,
+    /// Gate channels to install on devices (see
+    /// [`Manager::add_profile_channels`](crate::manager::Manager::add_profile_channels)).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub channels: Vec<ChannelExpr>,
+}
+
+/// A device a profile cannot work without ("stick", "throttle", "pedals").
+///
+/// Matches a specific `device_id` if given, otherwise any device satisfying `matcher`.
+/// Each connected device satisfies at most one requirement, checked in profile order.
+///
+/// ```toml
+/// [[required_devices]]
+/// name = "pedals"
+/// vid = 0x231d
+/// role = "joystick"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequiredDevice {
+    /// Label reported in readiness events.
+    pub name: String,
+    /// Exact device id, when the profile is tied to one device.
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// VID/PID/name/role criteria.
+    #[serde(flatten)]
+    pub matcher: DeviceMatcher,
+}
+
+impl RequiredDevice {
+    /// Does the device described by `info` satisfy this requirement?
+    pub fn matches(&self, info: &ManagedInfo) -> bool {
+        match &self.device_id {
+            Some(id) => *id == info.id && self.matcher.matches(info),
+            None => self.matcher.matches(info),
+        }
+    }
+}
+
+/// A named set of rules the application switches on per game state ("flight", "menu").
+///
+/// Active sets (see [`BindingProfile::resolve_with_sets`] and
+/// [`Manager::activate_action_set`](crate::manager::Manager::activate_action_set)) apply after
+/// the base rules and take over the controls they read, like a [`BindingLayer`]. Several sets
+/// may be active at once; they apply in profile order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionSet {
+    /// Set name used to activate it.
+    pub name: String,
+    /// Rules applied while the set is active.
+    #[serde(default)]
+    pub bindings: Vec<BindingRule>,
+}
+
+/// Where a rule returned by [`BindingProfile::effective_rules`] is declared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum RuleSource {
+    /// [`BindingProfile::bindings`].
+    Base,
+    /// [`BindingProfile::action_sets`] entry.
+    Set(usize),
+    /// [`BindingProfile::layers`] entry.
+    Layer(usize),
+}
+
+/// Does any of `rules` read a control `rule` reads?
+fn rules_shadow(rules: &[BindingRule], rule: &BindingRule) -> bool {
+    rules.iter().any(|r| {
+        r.device_id() == rule.device_id()
+            && rule.controls().iter().any(|c| r.controls().contains(c))
+    })
+}
+
+/// A control that must be held for a [`BindingLayer`] to be active.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModifierControl {
+    /// Device identifier.
+    pub device_id: String,
+    /// Modifier control: a button, an axis past `0.5`, or a hat (see `hat_direction`).
+    pub control: ControlPath,
+    /// Hat controls only: direction that counts as held (`None` = any).
+    #[serde(default)]
+    pub hat_direction: Option<i16>,
+}
+
+impl ModifierControl {
+    /// Is the modifier currently held?
+    pub fn is_held(&self, devices: &(impl DeviceStates + ?Sized)) -> bool {
+        devices
+            .device_state(&self.device_id)
+            .is_some_and(|st| self.held_in(st))
+    }
+
+    /// Is the modifier held in its device's state `st`?
+    fn held_in(&self, st: &DeviceState) -> bool {
+        control_held(st, &self.control, self.hat_direction)
+    }
+}
+
+/// A set of rules active while all `modifiers` are held (e.g. a HOTAS pinky shift).
+///
+/// While active, the layer's rules take over every control they read: base rules on those
+/// controls resolve as inactive, so a shifted button doesn't also fire its unshifted action.
+/// If several layers are active, the one with the most modifiers wins.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BindingLayer {
+    /// Layer name (for UIs).
+    pub name: String,
+    /// Controls that must all be held.
+    pub modifiers: Vec<ModifierControl>,
+    /// Rules applied while the layer is active.
+    #[serde(default)]
+    pub bindings: Vec<BindingRule>,
+}
+
+impl BindingLayer {
+    /// Does this layer take over a control `rule` reads (see [`BindingLayer`])?
+    pub fn shadows(&self, rule: &BindingRule) -> bool {
+        rules_shadow(&self.bindings, rule)
+    }
 }
 
 /// Normalized output produced by resolving a profile against device states.
@@ -380,6 +919,38 @@ pub struct BindingOutput {
     /// 2D axis actions (e.g., `"stick"`, `"dual_axis"`).
     #[serde(default)]
     pub vec2: HashMap<String, [f32; 2]>,
+    /// Dwell progress per action (`0.0..=1.0`) while its control is held.
+    #[serde(default)]
+    pub progress: HashMap<String, f32>,
+    /// Discrete action events completed this resolve (gestures), in rule order.
+    #[serde(default)]
+    pub events: Vec<GestureEvent>,
+    /// Interlock state changes this resolve, in rule order.
+    #[serde(default)]
+    pub interlocks: Vec<InterlockEvent>,
+}
+
+/// A state change of a [`BindingRule::Interlock`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterlockEvent {
+    /// Action name of the interlock rule.
+    pub action: String,
+    /// What happened.
+    pub kind: InterlockEventKind,
+}
+
+/// Interlock outcome reported in [`InterlockEvent`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterlockEventKind {
+    /// All controls were held for `hold_ms`; the action is now active.
+    Engaged,
+    /// A control was released after engaging; the action is inactive again.
+    Released,
+    /// Not all controls were pressed within `window_ms` of the first press.
+    TimedOut,
+    /// A control was released while arming (before `hold_ms` elapsed).
+    Aborted,
 }
 
 impl BindingProfile {
@@ -387,111 +958,598 @@ impl BindingProfile {
     ///
     /// Missing devices or controls are treated as inactive (`0.0`/`false`).
     ///
+    /// If a [`BindingLayer`] is active (all its modifiers held), its rules are applied after
+    /// the base rules; base rules reading a control the layer rebinds resolve as inactive.
+    ///
     /// This function is pure (no side effects): it only reads `devices` and produces output.
     #[inline]
-    pub fn resolve(&self, devices: &HashMap<String, DeviceState>) -> BindingOutput {
+    pub fn resolve(&self, devices: &(impl DeviceStates + ?Sized)) -> BindingOutput {
+        self.resolve_with_sets(devices, &[])
+    }
+
+    /// Like [`resolve`](Self::resolve), with the named [`ActionSet`]s active.
+    ///
+    /// Active sets apply after the base rules (in profile order) and before the active
+    /// layer; base rules reading a control a set rebinds resolve as inactive. Unknown names
+    /// are ignored.
+    pub fn resolve_with_sets(
+        &self,
+        devices: &(impl DeviceStates + ?Sized),
+        active_sets: &[String],
+    ) -> BindingOutput {
+        self.resolve_in(&ProfileStates::new(devices, None), active_sets)
+    }
+
+    /// [`resolve_with_sets`](Self::resolve_with_sets) reading states through `states`.
+    pub(crate) fn resolve_in<D: DeviceStates + ?Sized>(
+        &self,
+        states: &ProfileStates<'_, D>,
+        active_sets: &[String],
+    ) -> BindingOutput {
         let mut out = BindingOutput::default();
+        for (src, i, rule, shadowed) in self.effective_rules(states, active_sets) {
+            if shadowed {
+                rule.write_inactive(&mut out);
+            } else {
+                rule.apply(states.rule(src, i, rule), &mut out);
+            }
+        }
+        out
+    }
 
-        for rule in &self.bindings {
-            match rule {
-                BindingRule::Axis1d {
-                    device_id,
-                    control,
-                    action,
-                    xform,
-                } => {
-                    if let Some(st) = devices.get(device_id) {
-                        let raw = match control.control_type {
-                            ControlType::Axis => st.get_axis(&control.control_id),
-                            ControlType::Button => {
-                                if st.get_button(&control.control_id) {
-                                    1.0
-                                } else {
-                                    0.0
-                                }
-                            }
-                        };
-                        let v = xform.apply(raw);
-                        out.axis.insert(action.clone(), v);
-                    }
+    /// Rules in application order (base, active sets, active layer), each with its source,
+    /// index within that source, and whether a later source takes over its controls.
+    pub(crate) fn effective_rules<'a, D: DeviceStates + ?Sized>(
+        &'a self,
+        states: &ProfileStates<'_, D>,
+        active_sets: &[String],
+    ) -> Vec<(RuleSource, usize, &'a BindingRule, bool)> {
+        let layer = self.active_layer_in(states);
+        let sets: Vec<usize> = (0..self.action_sets.len())
+            .filter(|&i| active_sets.contains(&self.action_sets[i].name))
+            .collect();
+        let by_layer = |rule: &BindingRule| layer.is_some_and(|li| self.layers[li].shadows(rule));
+        let by_set = |rule: &BindingRule| {
+            sets.iter()
+                .any(|&si| rules_shadow(&self.action_sets[si].bindings, rule))
+        };
+
+        let mut rules = Vec::new();
+        for (i, rule) in self.bindings.iter().enumerate() {
+            rules.push((RuleSource::Base, i, rule, by_set(rule) || by_layer(rule)));
+        }
+        for &si in &sets {
+            for (i, rule) in self.action_sets[si].bindings.iter().enumerate() {
+                rules.push((RuleSource::Set(si), i, rule, by_layer(rule)));
+            }
+        }
+        if let Some(li) = layer {
+            for (i, rule) in self.layers[li].bindings.iter().enumerate() {
+                rules.push((RuleSource::Layer(li), i, rule, false));
+            }
+        }
+        rules
+    }
+
+    /// The layer whose modifiers are all held, preferring layers with more modifiers
+    /// (then declaration order). Layers without modifiers never activate.
+    pub fn active_layer(&self, devices: &(impl DeviceStates + ?Sized)) -> Option<&BindingLayer> {
+        let li = self.active_layer_in(&ProfileStates::new(devices, None))?;
+        Some(&self.layers[li])
+    }
+
+    /// Index of the [`active_layer`](Self::active_layer), reading states through `states`.
+    fn active_layer_in<D: DeviceStates + ?Sized>(
+        &self,
+        states: &ProfileStates<'_, D>,
+    ) -> Option<usize> {
+        let mut best: Option<usize> = None;
+        for (li, layer) in self.layers.iter().enumerate() {
+            let held = |(mi, m): (usize, &ModifierControl)| {
+                states.modifier(li, mi, m).is_some_and(|st| m.held_in(st))
+            };
+            if layer.modifiers.is_empty() || !layer.modifiers.iter().enumerate().all(held) {
+                continue;
+            }
+            match best {
+                Some(b) if self.layers[b].modifiers.len() >= layer.modifiers.len() => {}
+                _ => best = Some(li),
+            }
+        }
+        best
+    }
+}
+
+/// [`DeviceId`] of the device behind every rule and layer modifier of a profile, resolved
+/// once against an id table so evaluation looks states up by handle instead of hashing string
+/// ids per rule. Kept by [`BindingResolver`](crate::resolver::BindingResolver).
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DeviceHandles {
+    /// [`DeviceIds::version`] of the table the handles were resolved against.
+    table: Option<(u64, usize)>,
+    base: Vec<Option<DeviceId>>,
+    sets: Vec<Vec<Option<DeviceId>>>,
+    layers: Vec<Vec<Option<DeviceId>>>,
+    modifiers: Vec<Vec<Option<DeviceId>>>,
+}
+
+impl DeviceHandles {
+    /// Resolve `profile`'s device ids against `ids`, unless that table was resolved already.
+    ///
+    /// Ids missing from the table have no state in it either, so they resolve to `None`.
+    pub(crate) fn resolve(&mut self, profile: &BindingProfile, ids: &DeviceIds) {
+        let table = ids.version();
+        if self.table == Some(table) {
+            return;
+        }
+        let rules = |rules: &[BindingRule]| -> Vec<Option<DeviceId>> {
+            rules.iter().map(|r| ids.id_of(r.device_id())).collect()
+        };
+        self.base = rules(&profile.bindings);
+        self.sets = profile
+            .action_sets
+            .iter()
+            .map(|s| rules(&s.bindings))
+            .collect();
+        self.layers = profile.layers.iter().map(|l| rules(&l.bindings)).collect();
+        self.modifiers = profile
+            .layers
+            .iter()
+            .map(|l| {
+                l.modifiers
+                    .iter()
+                    .map(|m| ids.id_of(&m.device_id))
+                    .collect()
+            })
+            .collect();
+        self.table = Some(table);
+    }
+
+    fn rule(&self, src: RuleSource, i: usize) -> Option<DeviceId> {
+        let handles = match src {
+            RuleSource::Base => &self.base,
+            RuleSource::Set(si) => self.sets.get(si)?,
+            RuleSource::Layer(li) => self.layers.get(li)?,
+        };
+        handles.get(i).copied().flatten()
+    }
+
+    fn modifier(&self, layer: usize, i: usize) -> Option<DeviceId> {
+        self.modifiers.get(layer)?.get(i).copied().flatten()
+    }
+}
+
+/// Device states as one profile reads them: by resolved [`DeviceHandles`] when given (they
+/// must be resolved against `devices`' table), otherwise by string id.
+pub(crate) struct ProfileStates<'a, D: ?Sized> {
+    devices: &'a D,
+    handles: Option<&'a DeviceHandles>,
+}
+
+impl<'a, D: DeviceStates + ?Sized> ProfileStates<'a, D> {
+    pub(crate) fn new(devices: &'a D, handles: Option<&'a DeviceHandles>) -> Self {
+        Self { devices, handles }
+    }
+
+    /// State of the device `rule` (declared at `src`, index `i`) reads.
+    pub(crate) fn rule(
+        &self,
+        src: RuleSource,
+        i: usize,
+        rule: &BindingRule,
+    ) -> Option<&'a DeviceState> {
+        match self.handles {
+            Some(h) => self.devices.device_state_by_id(h.rule(src, i)?),
+            None => self.devices.device_state(rule.device_id()),
+        }
+    }
+
+    /// State of the device modifier `i` of layer `layer` reads.
+    fn modifier(&self, layer: usize, i: usize, m: &ModifierControl) -> Option<&'a DeviceState> {
+        match self.handles {
+            Some(h) => self.devices.device_state_by_id(h.modifier(layer, i)?),
+            None => self.devices.device_state(&m.device_id),
+        }
+    }
+}
+
+impl BindingRule {
+    /// Device the rule reads from.
+    pub fn device_id(&self) -> &str {
+        match self {
+            BindingRule::Axis1d { device_id, .. }
+            | BindingRule::Button { device_id, .. }
+            | BindingRule::Axis2d { device_id, .. }
+            | BindingRule::Chord { device_id, .. }
+            | BindingRule::Gesture { device_id, .. }
+            | BindingRule::Dwell { device_id, .. }
+            | BindingRule::ButtonAxis { device_id, .. }
+            | BindingRule::Interlock { device_id, .. }
+            | BindingRule::HatActions { device_id, .. }
+            | BindingRule::HatAxes { device_id, .. } => device_id,
+        }
+    }
+
+    /// Source controls of the rule.
+    pub fn controls(&self) -> Vec<&ControlPath> {
+        match self {
+            BindingRule::Axis1d { control, .. }
+            | BindingRule::Button { control, .. }
+            | BindingRule::Gesture { control, .. }
+            | BindingRule::Dwell { control, .. }
+            | BindingRule::HatActions { control, .. }
+            | BindingRule::HatAxes { control, .. } => vec![control],
+            BindingRule::Axis2d { control, .. } => vec![&control.x, &control.y],
+            BindingRule::ButtonAxis {
+                negative, positive, ..
+            } => vec![negative, positive],
+            BindingRule::Chord { controls, .. } | BindingRule::Interlock { controls, .. } => {
+                controls.iter().collect()
+            }
+        }
+    }
+
+    /// Write the rule's inactive value (`0.0` / `false` / `[0, 0]`) for its action.
+    fn write_inactive(&self, out: &mut BindingOutput) {
+        match self {
+            BindingRule::Axis1d { action, .. } | BindingRule::ButtonAxis { action, .. } => {
+                out.axis.insert(action.clone(), 0.0);
+            }
+            BindingRule::Button { action, .. }
+            | BindingRule::Chord { action, .. }
+            | BindingRule::Dwell { action, .. }
+            | BindingRule::Interlock { action, .. } => {
+                out.buttons.insert(action.clone(), false);
+            }
+            BindingRule::Axis2d { action, .. } | BindingRule::HatAxes { action, .. } => {
+                out.vec2.insert(action.clone(), [0.0, 0.0]);
+            }
+            BindingRule::HatActions { actions, .. } => {
+                for a in actions.iter().filter(|a| !a.is_empty()) {
+                    out.buttons.insert(a.clone(), false);
                 }
+            }
+            BindingRule::Gesture { .. } => {}
+        }
+    }
 
-                BindingRule::Button {
-                    device_id,
-                    control,
-                    action,
-                    axis_press_threshold,
-                } => {
-                    if let Some(st) = devices.get(device_id) {
-                        let pressed = match control.control_type {
-                            ControlType::Button => st.get_button(&control.control_id),
-                            ControlType::Axis => {
-                                let thr = axis_press_threshold.unwrap_or(0.5).abs().min(0.99);
-                                st.get_axis(&control.control_id).abs() >= thr
+    /// Evaluate the rule against its device's state (`None` = device missing) into `out`.
+    fn apply(&self, st: Option<&DeviceState>, out: &mut BindingOutput) {
+        match self {
+            BindingRule::Axis1d {
+                control,
+                action,
+                xform,
+                ..
+            } => {
+                if let Some(st) = st {
+                    let v = shaped_scalar(st, control, xform);
+                    out.axis.insert(action.clone(), v);
+                }
+            }
+
+            BindingRule::Button {
+                control,
+                action,
+                axis_press_threshold,
+                hat_direction,
+                ..
+            } => {
+                if let Some(st) = st {
+                    let pressed = match control.control_type {
+                        ControlType::Button => st.get_button(&control.control_id),
+                        ControlType::Axis => {
+                            let thr = axis_press_threshold.unwrap_or(0.5).abs().min(0.99);
+                            st.get_axis(&control.control_id).abs() >= thr
+                        }
+                        ControlType::Hat => {
+                            hat_matches(st.get_hat(&control.control_id), *hat_direction)
+                        }
+                        ControlType::Relative => st.get_relative(&control.control_id) != 0.0,
+                    };
+                    out.buttons.insert(action.clone(), pressed);
+                }
+            }
+
+            BindingRule::Axis2d {
+                control,
+                action,
+                xform_x,
+                xform_y,
+                radial_deadzone,
+                radial_deadzone_size,
+                ..
+            } => {
+                if let Some(st) = st {
+                    let mut x = shaped_scalar(st, &control.x, xform_x);
+                    let mut y = shaped_scalar(st, &control.y, xform_y);
+
+                    if *radial_deadzone {
+                        let dz = radial_deadzone_size.abs().min(0.95);
+                        let r = (x * x + y * y).sqrt();
+                        if r <= dz {
+                            x = 0.0;
+                            y = 0.0;
+                        } else {
+                            // map (dz..1) → (0..1) preserving direction
+                            let t = (r - dz) / (1.0 - dz);
+                            if r > 0.0 {
+                                let k = t / r;
+                                x *= k;
+                                y *= k;
                             }
-                        };
-                        out.buttons.insert(action.clone(), pressed);
+                        }
                     }
+
+                    out.vec2.insert(action.clone(), [x, y]);
                 }
+            }
 
-                BindingRule::Axis2d {
-                    device_id,
-                    control,
-                    action,
-                    xform_x,
-                    xform_y,
-                    radial_deadzone,
-                    radial_deadzone_size,
-                } => {
-                    if let Some(st) = devices.get(device_id) {
-                        let rx = match control.x.control_type {
-                            ControlType::Axis => st.get_axis(&control.x.control_id),
-                            ControlType::Button => {
-                                if st.get_button(&control.x.control_id) {
-                                    1.0
-                                } else {
-                                    0.0
-                                }
-                            }
-                        };
-                        let ry = match control.y.control_type {
-                            ControlType::Axis => st.get_axis(&control.y.control_id),
-                            ControlType::Button => {
-                                if st.get_button(&control.y.control_id) {
-                                    1.0
-                                } else {
-                                    0.0
-                                }
-                            }
-                        };
+            BindingRule::Chord {
+                controls, action, ..
+            } => {
+                if let Some(st) = st {
+                    let held =
+                        !controls.is_empty() && controls.iter().all(|c| control_held(st, c, None));
+                    out.buttons.insert(action.clone(), held);
+                }
+            }
 
-                        let mut x = xform_x.apply(rx);
-                        let mut y = xform_y.apply(ry);
-
-                        if *radial_deadzone {
-                            let dz = radial_deadzone_size.abs().min(0.95);
-                            let r = (x * x + y * y).sqrt();
-                            if r <= dz {
-                                x = 0.0;
-                                y = 0.0;
-                            } else {
-                                // map (dz..1) → (0..1) preserving direction
-                                let t = (r - dz) / (1.0 - dz);
-                                if r > 0.0 {
-                                    let k = t / r;
-                                    x *= k;
-                                    y *= k;
-                                }
-                            }
+            // Time-based; see `BindingResolver`.
+            BindingRule::Gesture { .. } => {}
+            BindingRule::Dwell { action, .. } | BindingRule::Interlock { action, .. } => {
+                out.buttons.insert(action.clone(), false);
+            }
+
+            BindingRule::ButtonAxis {
+                negative,
+                positive,
+                action,
+                mode,
+                ..
+            } => {
+                if let Some(st) = st {
+                    let dir = button_axis_direction(st, negative, positive);
+                    let v = match *mode {
+                        ButtonAxisMode::Absolute {
+                            negative_value,
+                            positive_value,
+                            rest_value,
+                        } => match dir {
+                            d if d < 0.0 => negative_value,
+                            d if d > 0.0 => positive_value,
+                            _ => rest_value,
+                        },
+                        ButtonAxisMode::Incremental { .. } => 0.0,
+                    };
+                    out.axis.insert(action.clone(), v);
+                }
+            }
+
+            BindingRule::HatActions {
+                control, actions, ..
+            } => {
+                if let Some(st) = st {
+                    let value = st.get_hat(&control.control_id);
+                    let eight_way = actions.len() >= 8;
+                    for (i, a) in actions
+                        .iter()
+                        .enumerate()
+                        .take(if eight_way { 8 } else { 4 })
+                    {
+                        if a.is_empty() {
+                            continue;
                         }
+                        let dir = if eight_way { i as i16 } else { i as i16 * 2 };
+                        let pressed = if eight_way {
+                            value == dir
+                        } else {
+                            hat_matches(value, Some(dir))
+                        };
+                        // Two directions may share an action name; any of them presses it.
+                        let slot = out.buttons.entry(a.clone()).or_insert(false);
+                        *slot |= pressed;
+                    }
+                }
+            }
 
-                        out.vec2.insert(action.clone(), [x, y]);
+            BindingRule::HatAxes {
+                control,
+                action,
+                normalize_diagonals,
+                ..
+            } => {
+                if let Some(st) = st {
+                    let [mut x, mut y] = hat_vector(st.get_hat(&control.control_id));
+                    if *normalize_diagonals && x != 0.0 && y != 0.0 {
+                        x *= core::f32::consts::FRAC_1_SQRT_2;
+                        y *= core::f32::consts::FRAC_1_SQRT_2;
                     }
+                    out.vec2.insert(action.clone(), [x, y]);
                 }
             }
         }
+    }
+}
 
-        out
+/// `-1.0` / `1.0` while only the negative / positive control is held, else `0.0`.
+#[inline]
+pub(crate) fn button_axis_direction(
+    st: &DeviceState,
+    negative: &ControlPath,
+    positive: &ControlPath,
+) -> f32 {
+    let neg = control_held(st, negative, None);
+    let pos = control_held(st, positive, None);
+    match (neg, pos) {
+        (true, false) => -1.0,
+        (false, true) => 1.0,
+        _ => 0.0,
+    }
+}
+
+/* =========================
+   Persistence
+========================= */
+
+/// Current [`BindingProfile`] schema version written by [`BindingProfile::save`].
+///
+/// Profiles without a version (`0`) predate versioning and use the same schema as `1`.
+pub const PROFILE_VERSION: u16 = 1;
+
+/// On-disk profile format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProfileFormat {
+    /// JSON (`.json`).
+    Json,
+    /// TOML (`.toml`).
+    Toml,
+}
+
+impl ProfileFormat {
+    /// Format implied by a file extension (`json` / `toml`, case-insensitive).
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from file contents (a JSON profile starts with `{`).
+    pub fn detect(text: &str) -> Self {
+        if text.trim_start().starts_with('{') {
+            Self::Json
+        } else {
+            Self::Toml
+        }
+    }
+}
+
+/// A migration step: edits a profile document from one schema version to the next.
+pub type MigrationStep = fn(&mut serde_json::Value) -> Result<()>;
+
+/// Upgrades older profile documents before they are deserialized.
+///
+/// Steps are registered per source version and run in order until the document reaches
+/// [`PROFILE_VERSION`]; each step only edits the document, the `version` field is bumped
+/// for it. Documents newer than [`PROFILE_VERSION`] are loaded as-is (unknown fields are
+/// ignored), so older builds can still read profiles written by newer ones.
+///
+/// ```
+/// use stickup::binding::{BindingProfile, ProfileMigrator};
+///
+/// // A hypothetical pre-release format that called the rule list "rules".
+/// let migrator = ProfileMigrator::new().step(0, |doc| {
+///     if let Some(rules) = doc.as_object_mut().and_then(|o| o.remove("rules")) {
+///         doc["bindings"] = rules;
+///     }
+///     Ok(())
+/// });
+/// let p = BindingProfile::from_str_with(r#"{"name": "old", "rules": []}"#, &migrator).unwrap();
+/// assert_eq!(p.version, 1);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ProfileMigrator {
+    steps: Vec<(u16, MigrationStep)>,
+}
+
+impl ProfileMigrator {
+    /// Migrator with no steps (versions `0` and `1` need none).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: register the step that upgrades `from_version` to `from_version + 1`.
+    pub fn step(mut self, from_version: u16, step: MigrationStep) -> Self {
+        self.steps.retain(|(v, _)| *v != from_version);
+        self.steps.push((from_version, step));
+        self
+    }
+
+    /// Upgrade `doc` in place to [`PROFILE_VERSION`].
+    pub fn migrate(&self, doc: &mut serde_json::Value) -> Result<()> {
+        let Some(obj) = doc.as_object() else {
+            return Err(Error::Other(
+                "invalid binding profile: expected a table/object".into(),
+            ));
+        };
+        let mut version = match obj.get("version") {
+            None => 0,
+            Some(v) => v
+                .as_u64()
+                .and_then(|v| u16::try_from(v).ok())
+                .ok_or_else(|| Error::Other(format!("invalid binding profile version: {v}")))?,
+        };
+        while version < PROFILE_VERSION {
+            if let Some((_, step)) = self.steps.iter().find(|(v, _)| *v == version) {
+                step(doc)?;
+            }
+            version += 1;
+            doc["version"] = version.into();
+        }
+        Ok(())
+    }
+}
+
+impl BindingProfile {
+    /// Parse a profile from text, detecting JSON vs TOML (see [`ProfileFormat::detect`]).
+    pub fn from_str_auto(text: &str) -> Result<Self> {
+        Self::from_str_with(text, &ProfileMigrator::new())
+    }
+
+    /// Like [`from_str_auto`](Self::from_str_auto), running `migrator` first.
+    pub fn from_str_with(text: &str, migrator: &ProfileMigrator) -> Result<Self> {
+        Self::parse(text, ProfileFormat::detect(text), migrator)
+    }
+
+    /// Parse text in a known format, migrating it to [`PROFILE_VERSION`].
+    pub fn parse(text: &str, format: ProfileFormat, migrator: &ProfileMigrator) -> Result<Self> {
+        let mut doc: serde_json::Value = match format {
+            ProfileFormat::Json => serde_json::from_str(text)
+                .map_err(|e| Error::Other(format!("invalid binding profile: {e}")))?,
+            ProfileFormat::Toml => toml::from_str(text)
+                .map_err(|e| Error::Other(format!("invalid binding profile: {e}")))?,
+        };
+        migrator.migrate(&mut doc)?;
+        serde_json::from_value(doc)
+            .map_err(|e| Error::Other(format!("invalid binding profile: {e}")))
+    }
+
+    /// Serialize in `format` (the version is written as at least [`PROFILE_VERSION`]).
+    pub fn to_string_as(&self, format: ProfileFormat) -> Result<String> {
+        let mut p = self.clone();
+        p.version = p.version.max(PROFILE_VERSION);
+        match format {
+            ProfileFormat::Json => serde_json::to_string_pretty(&p)
+                .map_err(|e| Error::Other(format!("serialize binding profile: {e}"))),
+            ProfileFormat::Toml => toml::to_string_pretty(&p)
+                .map_err(|e| Error::Other(format!("serialize binding profile: {e}"))),
+        }
+    }
+
+    /// Load a profile file. The format comes from the extension, else from the contents.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::load_with(path, &ProfileMigrator::new())
+    }
+
+    /// Like [`load`](Self::load), running `migrator` on older documents.
+    pub fn load_with(path: impl AsRef<Path>, migrator: &ProfileMigrator) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Other(format!("read {}: {e}", path.display())))?;
+        let format = ProfileFormat::from_path(path).unwrap_or_else(|| ProfileFormat::detect(&text));
+        Self::parse(&text, format, migrator)
+    }
+
+    /// Save the profile; the format comes from the extension (TOML if unknown).
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let format = ProfileFormat::from_path(path).unwrap_or(ProfileFormat::Toml);
+        let text = self.to_string_as(format)?;
+        std::fs::write(path, text)
+            .map_err(|e| Error::Other(format!("write {}: {e}", path.display())))
     }
 }
 
@@ -0,0 +1,833 @@
+//! Event bus: fan input events out to listeners and channel subscribers.
+//!
+//! An [`InputEventBus`] receives stamped events (usually from
+//! [`Manager::dispatch`](crate::manager::Manager::dispatch)) and delivers each one to
+//! - registered [`InputListener`]s, called synchronously on the publishing thread, and
+//! - channel subscribers created with [`InputEventBus::subscribe`], which receive events on a
+//!   [`Receiver`] and can live on any thread.
+//!
+//! Both are filtered with an [`EventFilter`]. Beyond the simple variants, a [`Filter`]
+//! builder selects devices and channel indices, e.g. the left stick and two face buttons of
+//! one pad:
+//!
+//! ```
+//! use stickup::bus::Filter;
+//! use stickup::event::InputKind;
+//!
+//! let f = Filter::device("xinput:0").axes(0..2).buttons([0, 1]);
+//! assert!(f.matches("xinput:0", &InputKind::ButtonPressed { button: 1 }));
+//! assert!(!f.matches("xinput:0", &InputKind::AxisMoved { axis: 4, value: 1.0 }));
+//! assert!(!f.matches("xinput:1", &InputKind::ButtonPressed { button: 1 }));
+//! ```
+//!
+//! Dispatch order is deterministic: listeners run in priority order (highest first, see
+//! [`add_listener_with_priority`](InputEventBus::add_listener_with_priority)), listeners of
+//! equal priority in registration order, and channel subscribers after all listeners in
+//! subscription order. Each event is fully delivered before the next. Listeners can
+//! *consume* an event by returning [`Propagation::Consumed`]: lower-priority listeners and
+//! channel subscribers then never see it. A menu overlay registered above gameplay listeners
+//! can swallow gamepad input while it is open.
+//!
+//! Listener work for large rigs can be spread over worker threads with
+//! [`InputEventBus::with_shards`]: each device is assigned to one shard, and a listener added
+//! with [`add_sharded_listener`](InputEventBus::add_sharded_listener) gets one instance per
+//! shard, which only ever runs on that shard's thread and sees the events of that shard's
+//! devices in order. Sharded listeners run after the synchronous listeners have passed an
+//! event and cannot stop delivery to subscribers.
+//!
+//! ```no_run
+//! use stickup::bus::{EventFilter, InputEventBus};
+//!
+//! # fn main() -> stickup::Result<()> {
+//! let mut mgr = stickup::Manager::discover()?;
+//! let mut bus = InputEventBus::new();
+//!
+//! let rx = bus.subscribe(EventFilter::ButtonsOnly);
+//! std::thread::spawn(move || {
+//!     for (device, ev) in rx {
+//!         println!("{device}: {:?}", ev.kind);
+//!     }
+//! });
+//!
+//! loop {
+//!     mgr.dispatch(&mut bus);
+//!     std::thread::sleep(std::time::Duration::from_millis(4));
+//! }
+//! # }
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread::JoinHandle;
+
+use crossbeam_channel::Sender;
+
+use crate::event::{InputEvent, InputKind};
+use crate::queue::{QueueReceiver, QueueStats, SharedQueue};
+
+pub use crossbeam_channel::Receiver;
+
+/// Which events a listener or subscriber receives.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum EventFilter {
+    /// Every event.
+    #[default]
+    All,
+    /// Events from one device id.
+    Device(String),
+    /// Events from devices whose id starts with the prefix (e.g. `"xinput:"`).
+    DevicePrefix(String),
+    /// Events from devices whose id matches a glob: `*` matches any run of characters, `?`
+    /// one character (e.g. `"231d:*"` for every VKB device).
+    DeviceGlob(String),
+    /// Axis moves only (absolute and relative).
+    AxisOnly,
+    /// Button presses/releases only.
+    ButtonsOnly,
+    /// Device and channel selection built with [`Filter`].
+    Filter(Box<Filter>),
+}
+
+impl From<Filter> for EventFilter {
+    fn from(f: Filter) -> Self {
+        Self::Filter(Box::new(f))
+    }
+}
+
+impl EventFilter {
+    /// Whether an event from `device_id` passes the filter.
+    pub fn matches(&self, device_id: &str, kind: &InputKind) -> bool {
+        match self {
+            Self::All => true,
+            Self::Device(id) => id == device_id,
+            Self::DevicePrefix(prefix) => device_id.starts_with(prefix.as_str()),
+            Self::DeviceGlob(pattern) => glob_match(pattern, device_id),
+            Self::AxisOnly => matches!(
+                kind,
+                InputKind::AxisMoved { .. } | InputKind::RelativeMoved { .. }
+            ),
+            Self::ButtonsOnly => matches!(
+                kind,
+                InputKind::ButtonPressed { .. } | InputKind::ButtonReleased { .. }
+            ),
+            Self::Filter(f) => f.matches(device_id, kind),
+        }
+    }
+}
+
+/// Device part of a [`Filter`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+enum DeviceMatch {
+    #[default]
+    Any,
+    Exact(String),
+    Prefix(String),
+    Glob(String),
+}
+
+impl DeviceMatch {
+    fn matches(&self, device_id: &str) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Exact(id) => id == device_id,
+            Self::Prefix(prefix) => device_id.starts_with(prefix.as_str()),
+            Self::Glob(pattern) => glob_match(pattern, device_id),
+        }
+    }
+}
+
+/// Set of channel indices, as a bitmap.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct IndexSet(Vec<u64>);
+
+impl IndexSet {
+    fn all() -> Self {
+        Self(vec![u64::MAX; 1 << 10])
+    }
+
+    fn insert(&mut self, idx: u16) {
+        let word = usize::from(idx / 64);
+        if self.0.len() <= word {
+            self.0.resize(word + 1, 0);
+        }
+        self.0[word] |= 1 << (idx % 64);
+    }
+
+    fn contains(&self, idx: u16) -> bool {
+        self.0
+            .get(usize::from(idx / 64))
+            .is_some_and(|w| w & (1 << (idx % 64)) != 0)
+    }
+}
+
+/// Composable event filter: a device selection plus, optionally, the channels to pass.
+///
+/// With no channel selection every event of the selected devices passes. Each of
+/// [`axes`](Self::axes), [`buttons`](Self::buttons), and [`hats`](Self::hats) adds channels
+/// (calls accumulate); once any is given only those channels pass, plus other event kinds
+/// (touch, motion, battery, markers) if [`other_events`](Self::other_events) is set.
+/// Keyboard keys count as buttons (by [key index](crate::keys::key_index)).
+///
+/// Indices are compiled into bitmaps, so matching costs a few comparisons per event. Convert
+/// with `.into()` (or pass directly: the bus accepts `impl Into<EventFilter>`).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Filter {
+    device: DeviceMatch,
+    axes: Option<IndexSet>,
+    buttons: Option<IndexSet>,
+    hats: Option<IndexSet>,
+    other: bool,
+}
+
+impl Filter {
+    /// Events from every device.
+    pub fn any_device() -> Self {
+        Self::default()
+    }
+
+    /// Events from one device id.
+    pub fn device(id: impl Into<String>) -> Self {
+        Self {
+            device: DeviceMatch::Exact(id.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Events from devices whose id starts with `prefix`.
+    pub fn device_prefix(prefix: impl Into<String>) -> Self {
+        Self {
+            device: DeviceMatch::Prefix(prefix.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Events from devices whose id matches a glob (see [`EventFilter::DeviceGlob`]).
+    pub fn device_glob(pattern: impl Into<String>) -> Self {
+        Self {
+            device: DeviceMatch::Glob(pattern.into()),
+            ..Self::default()
+        }
+    }
+
+    fn select(set: &mut Option<IndexSet>, indices: impl IntoIterator<Item = u16>) {
+        let set = set.get_or_insert_with(IndexSet::default);
+        for idx in indices {
+            set.insert(idx);
+        }
+    }
+
+    /// Pass these axes.
+    pub fn axes(mut self, indices: impl IntoIterator<Item = u16>) -> Self {
+        Self::select(&mut self.axes, indices);
+        self
+    }
+
+    /// Pass every axis.
+    pub fn all_axes(mut self) -> Self {
+        self.axes = Some(IndexSet::all());
+        self
+    }
+
+    /// Pass these buttons (and keyboard keys).
+    pub fn buttons(mut self, indices: impl IntoIterator<Item = u16>) -> Self {
+        Self::select(&mut self.buttons, indices);
+        self
+    }
+
+    /// Pass every button and key.
+    pub fn all_buttons(mut self) -> Self {
+        self.buttons = Some(IndexSet::all());
+        self
+    }
+
+    /// Pass these hats.
+    pub fn hats(mut self, indices: impl IntoIterator<Item = u16>) -> Self {
+        Self::select(&mut self.hats, indices);
+        self
+    }
+
+    /// Pass every hat.
+    pub fn all_hats(mut self) -> Self {
+        self.hats = Some(IndexSet::all());
+        self
+    }
+
+    /// Also pass touch, motion, battery, and marker events when channels are selected.
+    pub fn other_events(mut self, on: bool) -> Self {
+        self.other = on;
+        self
+    }
+
+    /// Whether an event from `device_id` passes.
+    pub fn matches(&self, device_id: &str, kind: &InputKind) -> bool {
+        if !self.device.matches(device_id) {
+            return false;
+        }
+        if self.axes.is_none() && self.buttons.is_none() && self.hats.is_none() {
+            return true;
+        }
+        let (set, idx) = match *kind {
+            InputKind::AxisMoved { axis, .. } | InputKind::RelativeMoved { axis, .. } => {
+                (&self.axes, axis)
+            }
+            InputKind::ButtonPressed { button } | InputKind::ButtonReleased { button } => {
+                (&self.buttons, button)
+            }
+            InputKind::KeyPressed { scancode, extended }
+            | InputKind::KeyReleased { scancode, extended } => {
+                (&self.buttons, crate::keys::key_index(scancode, extended))
+            }
+            InputKind::HatChanged { hat, .. } => (&self.hats, hat),
+            _ => return self.other,
+        };
+        set.as_ref().is_some_and(|s| s.contains(idx))
+    }
+}
+
+/// A listener's decision about an event it received.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Propagation {
+    /// Continue delivering to lower-priority listeners and subscribers.
+    #[default]
+    Pass,
+    /// Stop delivery here.
+    Consumed,
+}
+
+impl From<()> for Propagation {
+    fn from(_: ()) -> Self {
+        Self::Pass
+    }
+}
+
+impl From<bool> for Propagation {
+    /// `true` = consumed.
+    fn from(consumed: bool) -> Self {
+        if consumed {
+            Self::Consumed
+        } else {
+            Self::Pass
+        }
+    }
+}
+
+/// Whether `text` matches `pattern` (`*` = any run, `?` = any one character).
+fn glob_match(pattern: &str, text: &str) -> bool {
+    // Device ids are ASCII; comparing bytes avoids allocating per event.
+    let (p, t) = (pattern.as_bytes(), text.as_bytes());
+    let (mut pi, mut ti) = (0, 0);
+    // Position after the last `*` and the text position it is currently matched up to.
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == b'?' || (p[pi] != b'*' && p[pi] == t[ti])) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == b'*' {
+            pi += 1;
+            star = Some((pi, ti));
+        } else if let Some((sp, st)) = star {
+            // Let the last `*` absorb one more character and retry.
+            pi = sp;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == b'*')
+}
+
+/// Receives events synchronously from an [`InputEventBus`].
+///
+/// Implemented for closures `FnMut(&str, &InputEvent) -> R` where `R` is `()` (always
+/// pass), `bool` (`true` = consumed), or [`Propagation`].
+pub trait InputListener: Send {
+    /// Called once per delivered event, on the thread that publishes it.
+    fn on_input(&mut self, device_id: &str, event: &InputEvent) -> Propagation;
+}
+
+impl<F, R> InputListener for F
+where
+    F: FnMut(&str, &InputEvent) -> R + Send,
+    R: Into<Propagation>,
+{
+    fn on_input(&mut self, device_id: &str, event: &InputEvent) -> Propagation {
+        self(device_id, event).into()
+    }
+}
+
+/// Handle for removing a listener with [`InputEventBus::remove_listener`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ListenerId(u64);
+
+/// Ids of listeners whose guard was dropped, purged by the bus before its next dispatch.
+type DroppedListeners = Mutex<Vec<ListenerId>>;
+
+/// Keeps a listener registered; dropping it unregisters the listener.
+///
+/// Returned by [`InputEventBus::add_listener`], so registrations can follow the lifetime of
+/// the object that owns them (a UI widget, a game state). The listener is removed before
+/// the bus next delivers an event. Use [`forget`](Self::forget) to keep it registered for
+/// the bus's lifetime instead.
+#[must_use = "dropping the guard unregisters the listener"]
+#[derive(Debug)]
+pub struct ListenerGuard {
+    id: ListenerId,
+    dropped: Weak<DroppedListeners>,
+}
+
+impl ListenerGuard {
+    /// The listener's id.
+    pub fn id(&self) -> ListenerId {
+        self.id
+    }
+
+    /// Keep the listener registered; it can still be removed with
+    /// [`InputEventBus::remove_listener`].
+    pub fn forget(self) -> ListenerId {
+        let id = self.id;
+        std::mem::forget(self);
+        id
+    }
+}
+
+impl Drop for ListenerGuard {
+    fn drop(&mut self) {
+        // The bus may already be gone; then there is nothing to unregister from.
+        if let Some(dropped) = self.dropped.upgrade() {
+            dropped
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(self.id);
+        }
+    }
+}
+
+struct Registered {
+    id: ListenerId,
+    filter: EventFilter,
+    priority: i32,
+    listener: Box<dyn InputListener>,
+}
+
+enum Sink {
+    Channel(Sender<(Arc<str>, InputEvent)>),
+    /// Held together with the subscriber's [`QueueReceiver`]; the bus holds the last
+    /// reference once the receiver is dropped.
+    Bounded(Arc<SharedQueue>),
+}
+
+struct Subscriber {
+    filter: EventFilter,
+    sink: Sink,
+}
+
+impl Subscriber {
+    fn is_connected(&self) -> bool {
+        match &self.sink {
+            Sink::Channel(_) => true,
+            Sink::Bounded(q) => Arc::strong_count(q) > 1,
+        }
+    }
+}
+
+/// Work sent to a shard worker.
+enum ShardMsg {
+    Event(Arc<str>, InputEvent),
+    Add(Registered),
+    Remove(ListenerId),
+    /// Reply once every earlier message has been handled.
+    Flush(Sender<()>),
+}
+
+/// Worker threads running sharded listeners, one channel per shard.
+struct ShardPool {
+    senders: Vec<Sender<ShardMsg>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl ShardPool {
+    fn new(shards: usize) -> Self {
+        let (senders, workers) = (0..shards.max(1))
+            .map(|i| {
+                let (tx, rx) = crossbeam_channel::unbounded::<ShardMsg>();
+                let worker = std::thread::Builder::new()
+                    .name(format!("stickup-bus-{i}"))
+                    .spawn(move || run_shard(rx))
+                    .expect("spawn bus shard worker");
+                (tx, worker)
+            })
+            .unzip();
+        Self { senders, workers }
+    }
+
+    fn shard_of(&self, device_id: &str) -> usize {
+        let mut h = DefaultHasher::new();
+        device_id.hash(&mut h);
+        (h.finish() % self.senders.len() as u64) as usize
+    }
+
+    fn send(&self, shard: usize, msg: ShardMsg) {
+        // Workers only exit once their sender is gone, so this cannot fail while we hold it.
+        let _ = self.senders[shard].send(msg);
+    }
+
+    fn broadcast(&self, msg: impl Fn() -> ShardMsg) {
+        for shard in 0..self.senders.len() {
+            self.send(shard, msg());
+        }
+    }
+}
+
+impl Drop for ShardPool {
+    fn drop(&mut self) {
+        // Closing the channels lets each worker finish its queue and exit.
+        self.senders.clear();
+        for w in self.workers.drain(..) {
+            let _ = w.join();
+        }
+    }
+}
+
+/// Shard worker loop: runs the shard's listeners in registration order until the bus is
+/// dropped. A consumed event stops at its consumer.
+fn run_shard(rx: Receiver<ShardMsg>) {
+    let mut listeners: Vec<Registered> = Vec::new();
+    for msg in rx {
+        match msg {
+            ShardMsg::Event(id, ev) => {
+                for r in &mut listeners {
+                    if r.filter.matches(&id, &ev.kind)
+                        && r.listener.on_input(&id, &ev) == Propagation::Consumed
+                    {
+                        break;
+                    }
+                }
+            }
+            ShardMsg::Add(r) => listeners.push(r),
+            ShardMsg::Remove(id) => listeners.retain(|r| r.id != id),
+            ShardMsg::Flush(done) => {
+                let _ = done.send(());
+            }
+        }
+    }
+}
+
+/// Delivers events to listeners and channel subscribers (see module docs).
+#[derive(Default)]
+pub struct InputEventBus {
+    /// Dispatch order: priority descending, then registration order.
+    listeners: Vec<Registered>,
+    subscribers: Vec<Subscriber>,
+    next_id: u64,
+    dropped: Arc<DroppedListeners>,
+    /// Counters of bounded subscribers that have been dropped.
+    retired_stats: QueueStats,
+    /// Shard workers, if created with `with_shards`.
+    shards: Option<ShardPool>,
+    /// Listeners registered on every shard.
+    sharded: Vec<ListenerId>,
+}
+
+impl InputEventBus {
+    /// Empty bus.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Empty bus delivering to sharded listeners on `shards` worker threads (at least 1).
+    ///
+    /// The workers stop once the bus is dropped, after handling every event already
+    /// published.
+    pub fn with_shards(shards: usize) -> Self {
+        Self {
+            shards: Some(ShardPool::new(shards)),
+            ..Self::default()
+        }
+    }
+
+    /// Number of shard worker threads (`0` for a bus without shards).
+    pub fn shard_count(&self) -> usize {
+        self.shards.as_ref().map_or(0, |p| p.senders.len())
+    }
+
+    /// Shard handling events from `device_id`, if the bus has shards. Stable for the life of
+    /// the bus.
+    pub fn shard_of(&self, device_id: &str) -> Option<usize> {
+        self.shards.as_ref().map(|p| p.shard_of(device_id))
+    }
+
+    /// Register a listener on every shard: `make(shard)` builds the instance for each shard,
+    /// which is called on that shard's thread for the events of its devices that pass
+    /// `filter` (see module docs).
+    ///
+    /// Sharded listeners run in registration order within a shard, after the synchronous
+    /// listeners passed the event; consuming an event only hides it from later sharded
+    /// listeners. On a bus without shards, a single instance (`make(0)`) runs on the
+    /// publishing thread after every other listener.
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    /// use std::time::{Duration, Instant};
+    /// use stickup::bus::{EventFilter, InputEventBus};
+    /// use stickup::event::{DeviceId, InputEvent, InputKind};
+    ///
+    /// let mut bus = InputEventBus::with_shards(4);
+    /// let seen = Arc::new(AtomicUsize::new(0));
+    /// let counter = seen.clone();
+    /// let _guard = bus.add_sharded_listener(EventFilter::All, move |_shard| {
+    ///     let counter = counter.clone();
+    ///     move |_: &str, _: &InputEvent| {
+    ///         counter.fetch_add(1, Ordering::Relaxed);
+    ///     }
+    /// });
+    /// for n in 0..20 {
+    ///     let dev: Arc<str> = format!("pad{n}").into();
+    ///     let (at, timestamp, device) = (Instant::now(), Duration::ZERO, DeviceId::UNKNOWN);
+    ///     let kind = InputKind::ButtonPressed { button: 0 };
+    ///     bus.publish(&dev, &InputEvent { at, timestamp, seq: n, device, kind });
+    /// }
+    /// bus.flush_shards();
+    /// assert_eq!(seen.load(Ordering::Relaxed), 20);
+    /// ```
+    pub fn add_sharded_listener<L: InputListener + 'static>(
+        &mut self,
+        filter: impl Into<EventFilter>,
+        mut make: impl FnMut(usize) -> L,
+    ) -> ListenerGuard {
+        let filter = filter.into();
+        let Some(pool) = &self.shards else {
+            return self.add_listener_with_priority(filter, i32::MIN, make(0));
+        };
+        let id = ListenerId(self.next_id);
+        self.next_id += 1;
+        for shard in 0..pool.senders.len() {
+            let r = Registered {
+                id,
+                filter: filter.clone(),
+                priority: 0,
+                listener: Box::new(make(shard)),
+            };
+            pool.send(shard, ShardMsg::Add(r));
+        }
+        self.sharded.push(id);
+        ListenerGuard {
+            id,
+            dropped: Arc::downgrade(&self.dropped),
+        }
+    }
+
+    /// Block until every shard has handled the events published so far (no-op without
+    /// shards).
+    pub fn flush_shards(&self) {
+        let Some(pool) = &self.shards else {
+            return;
+        };
+        let (tx, rx) = crossbeam_channel::bounded(pool.senders.len());
+        pool.broadcast(|| ShardMsg::Flush(tx.clone()));
+        for _ in 0..pool.senders.len() {
+            let _ = rx.recv();
+        }
+    }
+
+    /// Register a listener for events passing `filter`, at priority `0`.
+    ///
+    /// The listener stays registered while the returned guard lives.
+    pub fn add_listener(
+        &mut self,
+        filter: impl Into<EventFilter>,
+        listener: impl InputListener + 'static,
+    ) -> ListenerGuard {
+        self.add_listener_with_priority(filter, 0, listener)
+    }
+
+    /// Register a listener that runs before every listener of lower `priority` (and after
+    /// listeners of equal priority registered earlier).
+    pub fn add_listener_with_priority(
+        &mut self,
+        filter: impl Into<EventFilter>,
+        priority: i32,
+        listener: impl InputListener + 'static,
+    ) -> ListenerGuard {
+        let id = ListenerId(self.next_id);
+        self.next_id += 1;
+        let pos = self.listeners.partition_point(|r| r.priority >= priority);
+        self.listeners.insert(
+            pos,
+            Registered {
+                id,
+                filter: filter.into(),
+                priority,
+                listener: Box::new(listener),
+            },
+        );
+        ListenerGuard {
+            id,
+            dropped: Arc::downgrade(&self.dropped),
+        }
+    }
+
+    /// Unregister a listener. Returns `false` if it was not registered.
+    pub fn remove_listener(&mut self, id: ListenerId) -> bool {
+        self.purge_dropped();
+        self.unregister(id)
+    }
+
+    fn unregister(&mut self, id: ListenerId) -> bool {
+        if let Some(pos) = self.sharded.iter().position(|s| *s == id) {
+            self.sharded.remove(pos);
+            if let Some(pool) = &self.shards {
+                pool.broadcast(|| ShardMsg::Remove(id));
+            }
+            return true;
+        }
+        let before = self.listeners.len();
+        self.listeners.retain(|r| r.id != id);
+        self.listeners.len() != before
+    }
+
+    /// Number of registered listeners.
+    pub fn listener_count(&self) -> usize {
+        let dropped = self.dropped.lock().unwrap_or_else(|e| e.into_inner());
+        self.listeners
+            .iter()
+            .map(|r| r.id)
+            .chain(self.sharded.iter().copied())
+            .filter(|id| !dropped.contains(id))
+            .count()
+    }
+
+    /// Remove listeners whose guards were dropped.
+    fn purge_dropped(&mut self) {
+        let dropped = std::mem::take(&mut *self.dropped.lock().unwrap_or_else(|e| e.into_inner()));
+        for id in dropped {
+            self.unregister(id);
+        }
+    }
+
+    /// Receive events passing `filter` on a channel.
+    ///
+    /// The channel is unbounded; drop the [`Receiver`] to unsubscribe (the bus forgets
+    /// disconnected subscribers on the next publish).
+    pub fn subscribe(
+        &mut self,
+        filter: impl Into<EventFilter>,
+    ) -> Receiver<(Arc<str>, InputEvent)> {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        self.subscribers.push(Subscriber {
+            filter: filter.into(),
+            sink: Sink::Channel(tx),
+        });
+        rx
+    }
+
+    /// Receive events passing `filter` on a bounded [`QueueReceiver`] holding at most
+    /// `capacity` events.
+    ///
+    /// A slow consumer never makes the bus block or grow: pending axis updates are coalesced
+    /// and the oldest events dropped once the queue is full, counted in
+    /// [`QueueReceiver::stats`] and [`queue_stats`](Self::queue_stats). Drop the receiver to
+    /// unsubscribe.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::time::{Duration, Instant};
+    /// use stickup::bus::{EventFilter, InputEventBus};
+    /// use stickup::event::{DeviceId, InputEvent, InputKind};
+    ///
+    /// let mut bus = InputEventBus::new();
+    /// let rx = bus.subscribe_bounded(EventFilter::All, 2);
+    /// let dev: Arc<str> = "pad".into();
+    /// let mut publish = |kind| {
+    ///     let (at, timestamp, device) = (Instant::now(), Duration::ZERO, DeviceId::UNKNOWN);
+    ///     let ev = InputEvent { at, timestamp, seq: 0, device, kind };
+    ///     bus.publish(&dev, &ev);
+    /// };
+    /// for i in 0..3 {
+    ///     publish(InputKind::AxisMoved { axis: 0, value: i as f32 / 2.0 });
+    /// }
+    /// publish(InputKind::ButtonPressed { button: 0 });
+    /// publish(InputKind::ButtonPressed { button: 1 });
+    ///
+    /// let stats = rx.stats();
+    /// assert_eq!((stats.events_coalesced, stats.events_dropped), (2, 1));
+    /// assert_eq!(rx.len(), 2);
+    /// ```
+    pub fn subscribe_bounded(
+        &mut self,
+        filter: impl Into<EventFilter>,
+        capacity: usize,
+    ) -> QueueReceiver {
+        let shared = SharedQueue::new(capacity);
+        self.subscribers.push(Subscriber {
+            filter: filter.into(),
+            sink: Sink::Bounded(shared.clone()),
+        });
+        QueueReceiver { shared }
+    }
+
+    /// Combined counters of every bounded subscriber, including dropped ones.
+    pub fn queue_stats(&self) -> QueueStats {
+        let mut total = self.retired_stats;
+        for s in &self.subscribers {
+            if let Sink::Bounded(q) = &s.sink {
+                total += q.stats();
+            }
+        }
+        total
+    }
+
+    /// Number of live channel subscribers.
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.len()
+    }
+
+    /// Deliver one event from `device_id`. Returns [`Propagation::Consumed`] if a listener
+    /// consumed it (subscribers were then skipped).
+    pub fn publish(&mut self, device_id: &Arc<str>, event: &InputEvent) -> Propagation {
+        self.purge_dropped();
+        for r in &mut self.listeners {
+            if r.filter.matches(device_id, &event.kind)
+                && r.listener.on_input(device_id, event) == Propagation::Consumed
+            {
+                return Propagation::Consumed;
+            }
+        }
+        if let (Some(pool), false) = (&self.shards, self.sharded.is_empty()) {
+            let shard = pool.shard_of(device_id);
+            pool.send(shard, ShardMsg::Event(device_id.clone(), event.clone()));
+        }
+        let retired = &mut self.retired_stats;
+        self.subscribers.retain(|s| {
+            if !s.is_connected() {
+                if let Sink::Bounded(q) = &s.sink {
+                    *retired += q.stats();
+                }
+                return false;
+            }
+            if !s.filter.matches(device_id, &event.kind) {
+                return true;
+            }
+            match &s.sink {
+                Sink::Channel(tx) => tx.send((device_id.clone(), event.clone())).is_ok(),
+                Sink::Bounded(q) => {
+                    q.push(device_id.clone(), event.clone());
+                    true
+                }
+            }
+        });
+        Propagation::Pass
+    }
+
+    /// Deliver a batch of events in order.
+    pub fn publish_all<'a>(
+        &mut self,
+        events: impl IntoIterator<Item = &'a (Arc<str>, InputEvent)>,
+    ) {
+        for (id, ev) in events {
+            self.publish(id, ev);
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! "Press any control to bind" capture.
+//!
+//! A [`BindingCapture`] watches the event stream for the next *significant* input across all
+//! devices and returns it as a [`CapturedControl`], ready to become a [`BindingRule`]:
+//! - a button or key going down,
+//! - an axis moving past a threshold away from where it rested when capture started
+//!   (so triggers resting at `-1.0` don't fire immediately),
+//! - a hat leaving neutral (the direction is kept for `hat_direction`).
+//!
+//! ```no_run
+//! use stickup::Manager;
+//!
+//! let mut mgr = Manager::discover().expect("discover devices");
+//! let mut capture = mgr.begin_capture();
+//! let captured = loop {
+//!     if let Some(c) = mgr.poll_capture(&mut capture) {
+//!         break c;
+//!     }
+//!     std::thread::sleep(std::time::Duration::from_millis(5));
+//! };
+//! let rule = captured.to_rule("fire");
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use crate::binding::{AxisTransform, BindingRule, ControlPath, ControlType};
+use crate::snapshot::Snapshot;
+
+/// Default absolute axis travel (normalized units) that counts as "moved".
+pub const DEFAULT_AXIS_THRESHOLD: f32 = 0.5;
+
+/// A control picked up by [`BindingCapture`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct CapturedControl {
+    /// Device the input came from.
+    pub device_id: String,
+    /// The control, addressed by its state key.
+    pub control: ControlPath,
+    /// For hats: the direction that was pushed (`0..7`).
+    pub hat_direction: Option<i16>,
+    /// For axes: the signed travel from the resting value when captured.
+    pub axis_delta: f32,
+}
+
+impl CapturedControl {
+    /// Build a binding rule for `action`: [`BindingRule::Axis1d`] for axes (inverted if the
+    /// axis was moved towards negative), [`BindingRule::Button`] for buttons, keys, and hat
+    /// directions.
+    pub fn to_rule(&self, action: impl Into<String>) -> BindingRule {
+        let action = action.into();
+        match self.control.control_type {
+            ControlType::Axis | ControlType::Relative => BindingRule::Axis1d {
+                device_id: self.device_id.clone(),
+                control: self.control.clone(),
+                action,
+                xform: AxisTransform {
+                    invert: self.axis_delta < 0.0,
+                    ..AxisTransform::default()
+                },
+            },
+            ControlType::Button | ControlType::Hat => BindingRule::Button {
+                device_id: self.device_id.clone(),
+                control: self.control.clone(),
+                action,
+                axis_press_threshold: None,
+                hat_direction: self.hat_direction,
+            },
+        }
+    }
+}
+
+/// Watches for the next significant input (see module docs).
+///
+/// Create with [`Manager::begin_capture`](crate::manager::Manager::begin_capture) so axis
+/// resting positions are known, then feed it with
+/// [`Manager::poll_capture`](crate::manager::Manager::poll_capture).
+#[derive(Clone, Debug)]
+pub struct BindingCapture {
+    axis_threshold: f32,
+    /// Accepted control types (all if empty).
+    types: HashSet<ControlType>,
+    /// Ignored devices (e.g. the mouse used to click "Bind").
+    ignored: HashSet<String>,
+    /// Resting axis values per device, taken when capture started.
+    rest: HashMap<String, HashMap<String, f32>>,
+}
+
+impl BindingCapture {
+    /// Start a capture with the given resting device states (axis values are baselines).
+    pub fn new(states: &Snapshot) -> Self {
+        Self {
+            axis_threshold: DEFAULT_AXIS_THRESHOLD,
+            types: HashSet::new(),
+            ignored: HashSet::new(),
+            rest: states
+                .iter()
+                .map(|(id, st)| (id.to_string(), st.axes.clone()))
+                .collect(),
+        }
+    }
+
+    /// Axis travel away from rest that counts as a deliberate move (default `0.5`).
+    pub fn axis_threshold(mut self, threshold: f32) -> Self {
+        self.axis_threshold = threshold.abs();
+        self
+    }
+
+    /// Only accept these control types (e.g. just axes for an "axis" action).
+    pub fn only(mut self, types: impl IntoIterator<Item = ControlType>) -> Self {
+        self.types = types.into_iter().collect();
+        self
+    }
+
+    /// Ignore input from a device.
+    pub fn ignore_device(mut self, device_id: impl Into<String>) -> Self {
+        self.ignored.insert(device_id.into());
+        self
+    }
+
+    fn accepts(&self, device_id: &str, ty: &ControlType) -> bool {
+        !self.ignored.contains(device_id) && (self.types.is_empty() || self.types.contains(ty))
+    }
+
+    /// Consider a button/key edge.
+    pub fn button(
+        &mut self,
+        device_id: &str,
+        control_id: &str,
+        pressed: bool,
+    ) -> Option<CapturedControl> {
+        if !pressed || !self.accepts(device_id, &ControlType::Button) {
+            return None;
+        }
+        Some(self.captured(device_id, control_id, ControlType::Button, None, 0.0))
+    }
+
+    /// Consider an axis sample.
+    pub fn axis(
+        &mut self,
+        device_id: &str,
+        control_id: &str,
+        value: f32,
+    ) -> Option<CapturedControl> {
+        if !self.accepts(device_id, &ControlType::Axis) {
+            return None;
+        }
+        // First sample of an axis unknown at start becomes its resting value.
+        let rest = *self
+            .rest
+            .entry(device_id.to_string())
+            .or_default()
+            .entry(control_id.to_string())
+            .or_insert(value);
+        let delta = value - rest;
+        if delta.abs() < self.axis_threshold {
+            return None;
+        }
+        Some(self.captured(device_id, control_id, ControlType::Axis, None, delta))
+    }
+
+    /// Consider a hat sample.
+    pub fn hat(
+        &mut self,
+        device_id: &str,
+        control_id: &str,
+        value: i16,
+    ) -> Option<CapturedControl> {
+        if !(0..8).contains(&value) || !self.accepts(device_id, &ControlType::Hat) {
+            return None;
+        }
+        Some(self.captured(device_id, control_id, ControlType::Hat, Some(value), 0.0))
+    }
+
+    fn captured(
+        &self,
+        device_id: &str,
+        control_id: &str,
+        control_type: ControlType,
+        hat_direction: Option<i16>,
+        axis_delta: f32,
+    ) -> CapturedControl {
+        CapturedControl {
+            device_id: device_id.to_string(),
+            control: ControlPath {
+                control_id: control_id.to_string(),
+                control_type,
+            },
+            hat_direction,
+            axis_delta,
+        }
+    }
+}
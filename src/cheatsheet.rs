@@ -0,0 +1,427 @@
+//! Printable binding cheat sheets.
+//!
+//! [`CheatSheet`] renders a [`BindingProfile`] as tables of *control → action*, grouped by
+//! device and then by section (base bindings, each layer with its modifiers, each action
+//! set), as Markdown or a standalone HTML page. Device names and channel details (the HID
+//! collection a control belongs to, controls the device doesn't have) come from the
+//! [`Manager`] or are supplied by hand:
+//!
+//! ```
+//! use stickup::cheatsheet::CheatSheet;
+//! use stickup::BindingProfile;
+//!
+//! let profile = BindingProfile::from_str_auto(r#"
+//! name = "Hornet"
+//! [[bindings]]
+//! kind = "button"
+//! device_id = "stick"
+//! control = { control_id = "Trigger", control_type = "Button" }
+//! action = "guns"
+//! "#).unwrap();
+//!
+//! let md = CheatSheet::new(&profile).device_name("stick", "Warthog Stick").to_markdown();
+//! assert!(md.contains("## Warthog Stick"));
+//! assert!(md.contains("| Trigger | guns |"));
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use crate::binding::{BindingProfile, BindingRule, ButtonAxisMode, ControlPath, ModifierControl};
+use crate::event::ChannelDesc;
+use crate::manager::Manager;
+
+/// One *control → action* line of a cheat sheet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SheetEntry {
+    /// Device the control belongs to.
+    pub device_id: String,
+    /// Section title (`Base`, `Layer: …`, `Action set: …`, `Derived channels`).
+    pub section: String,
+    /// Control(s), as the user would press them.
+    pub control: String,
+    /// Action name(s).
+    pub action: String,
+    /// How the control triggers the action (`chord`, `hold 1000 ms`, …); may be empty.
+    pub note: String,
+}
+
+/// Cheat sheet builder for a profile (see module docs).
+#[derive(Clone, Debug)]
+pub struct CheatSheet<'a> {
+    profile: &'a BindingProfile,
+    device_names: HashMap<String, String>,
+    channels: HashMap<String, Vec<ChannelDesc>>,
+}
+
+/// A section title and its rows.
+type Section = (String, Vec<SheetEntry>);
+
+const HAT_DIRECTIONS: [&str; 8] = [
+    "Up",
+    "Up-Right",
+    "Right",
+    "Down-Right",
+    "Down",
+    "Down-Left",
+    "Left",
+    "Up-Left",
+];
+
+impl<'a> CheatSheet<'a> {
+    /// Cheat sheet for `profile`, labelling devices by id.
+    pub fn new(profile: &'a BindingProfile) -> Self {
+        Self {
+            profile,
+            device_names: HashMap::new(),
+            channels: HashMap::new(),
+        }
+    }
+
+    /// Show `name` instead of the id for `device_id`.
+    pub fn device_name(mut self, device_id: impl Into<String>, name: impl Into<String>) -> Self {
+        self.device_names.insert(device_id.into(), name.into());
+        self
+    }
+
+    /// Use `channels` of `device_id` to annotate controls.
+    pub fn channels(mut self, device_id: impl Into<String>, channels: Vec<ChannelDesc>) -> Self {
+        self.channels.insert(device_id.into(), channels);
+        self
+    }
+
+    /// Take device names and channels from the devices `mgr` manages.
+    pub fn with_manager(mut self, mgr: &Manager) -> Self {
+        for d in mgr.devices() {
+            if !d.name.trim().is_empty() {
+                self.device_names.insert(d.id.clone(), d.name.clone());
+            }
+            if let Some(ch) = mgr.channels(&d.id) {
+                self.channels.insert(d.id.clone(), ch.to_vec());
+            }
+        }
+        self
+    }
+
+    /// Display name of a device.
+    fn name_of<'s>(&'s self, device_id: &'s str) -> &'s str {
+        self.device_names
+            .get(device_id)
+            .map_or(device_id, String::as_str)
+    }
+
+    /// Control label, annotated from the device's channels when known.
+    fn label(&self, device_id: &str, control: &ControlPath) -> String {
+        let id = &control.control_id;
+        let Some(channels) = self.channels.get(device_id) else {
+            return id.clone();
+        };
+        let is_gate = self
+            .profile
+            .channels
+            .iter()
+            .any(|g| g.device_id == device_id && g.name == *id);
+        match channels.iter().find(|c| c.state_name() == *id) {
+            Some(c) => match &c.collection_name {
+                Some(coll) => format!("{id} ({coll})"),
+                None => id.clone(),
+            },
+            None if is_gate => id.clone(),
+            None => format!("{id} (not on device)"),
+        }
+    }
+
+    fn modifier_label(&self, section_device: &str, m: &ModifierControl) -> String {
+        let mut s = self.label(&m.device_id, &m.control);
+        if let Some(dir) = m.hat_direction {
+            let _ = write!(s, " {}", hat_direction(dir));
+        }
+        if m.device_id != section_device {
+            s = format!("{} {s}", self.name_of(&m.device_id));
+        }
+        s
+    }
+
+    fn rule_entries(&self, section: &str, rule: &BindingRule, out: &mut Vec<SheetEntry>) {
+        let dev = rule.device_id();
+        let join = |controls: &[ControlPath]| {
+            controls
+                .iter()
+                .map(|c| self.label(dev, c))
+                .collect::<Vec<_>>()
+                .join(" + ")
+        };
+        let with_hat = |c: &ControlPath, dir: &Option<i16>| match dir {
+            Some(d) => format!("{} {}", self.label(dev, c), hat_direction(*d)),
+            None => self.label(dev, c),
+        };
+        let mut push = |control: String, action: &str, note: String| {
+            out.push(SheetEntry {
+                device_id: dev.to_string(),
+                section: section.to_string(),
+                control,
+                action: action.to_string(),
+                note,
+            })
+        };
+        match rule {
+            BindingRule::Axis1d {
+                control,
+                action,
+                xform,
+                ..
+            } => {
+                let note = if xform.invert {
+                    "axis, inverted"
+                } else {
+                    "axis"
+                };
+                push(self.label(dev, control), action, note.into());
+            }
+            BindingRule::Button {
+                control,
+                action,
+                hat_direction,
+                ..
+            } => push(with_hat(control, hat_direction), action, String::new()),
+            BindingRule::Axis2d {
+                control, action, ..
+            } => push(
+                format!(
+                    "{} / {}",
+                    self.label(dev, &control.x),
+                    self.label(dev, &control.y)
+                ),
+                action,
+                "2D".into(),
+            ),
+            BindingRule::Chord {
+                controls, action, ..
+            } => push(join(controls), action, "chord".into()),
+            BindingRule::Gesture {
+                control,
+                hat_direction,
+                action,
+                timing,
+                ..
+            } => push(
+                with_hat(control, hat_direction),
+                action,
+                format!("tap / hold {} ms / double tap", timing.hold_ms),
+            ),
+            BindingRule::Dwell {
+                control,
+                hat_direction,
+                action,
+                dwell_ms,
+                ..
+            } => push(
+                with_hat(control, hat_direction),
+                action,
+                format!("hold {dwell_ms} ms"),
+            ),
+            BindingRule::ButtonAxis {
+                negative,
+                positive,
+                action,
+                mode,
+                ..
+            } => {
+                let note = match mode {
+                    ButtonAxisMode::Absolute { .. } => "axis (− / +)",
+                    ButtonAxisMode::Incremental { .. } => "axis ramp (− / +)",
+                };
+                push(
+                    format!(
+                        "{} / {}",
+                        self.label(dev, negative),
+                        self.label(dev, positive)
+                    ),
+                    action,
+                    note.into(),
+                );
+            }
+            BindingRule::Interlock {
+                controls,
+                action,
+                hold_ms,
+                ..
+            } => push(
+                join(controls),
+                action,
+                format!("both hands, hold {hold_ms} ms"),
+            ),
+            BindingRule::HatActions {
+                control, actions, ..
+            } => {
+                let step = if actions.len() == 4 { 2 } else { 1 };
+                for (i, a) in actions.iter().enumerate().filter(|(_, a)| !a.is_empty()) {
+                    let dir = (i * step) as i16;
+                    push(with_hat(control, &Some(dir)), a, String::new());
+                }
+            }
+            BindingRule::HatAxes {
+                control, action, ..
+            } => push(self.label(dev, control), action, "2D".into()),
+        }
+    }
+
+    /// Every entry, grouped by device (in order of first use) and section.
+    pub fn entries(&self) -> Vec<SheetEntry> {
+        let p = self.profile;
+        let mut all = Vec::new();
+        for rule in &p.bindings {
+            self.rule_entries("Base", rule, &mut all);
+        }
+        for layer in &p.layers {
+            let mut section = format!("Layer: {}", layer.name);
+            if !layer.modifiers.is_empty() {
+                let dev = layer.bindings.first().map_or("", |r| r.device_id());
+                let mods: Vec<String> = layer
+                    .modifiers
+                    .iter()
+                    .map(|m| self.modifier_label(dev, m))
+                    .collect();
+                let _ = write!(section, " (hold {})", mods.join(" + "));
+            }
+            for rule in &layer.bindings {
+                self.rule_entries(&section, rule, &mut all);
+            }
+        }
+        for set in &p.action_sets {
+            let section = format!("Action set: {}", set.name);
+            for rule in &set.bindings {
+                self.rule_entries(&section, rule, &mut all);
+            }
+        }
+        for gate in &p.channels {
+            all.push(SheetEntry {
+                device_id: gate.device_id.clone(),
+                section: "Derived channels".into(),
+                control: gate.name.clone(),
+                action: gate.expr.source().to_string(),
+                note: format!("{:?}", gate.kind).to_lowercase(),
+            });
+        }
+
+        let mut devices: Vec<&str> = Vec::new();
+        for e in &all {
+            if !devices.contains(&e.device_id.as_str()) {
+                devices.push(&e.device_id);
+            }
+        }
+        let mut sorted = all.clone();
+        // Stable: keeps section and rule order within each device.
+        sorted.sort_by_key(|e| devices.iter().position(|d| *d == e.device_id));
+        sorted
+    }
+
+    /// Group entries as `(device_id, [(section, entries)])`.
+    fn grouped(&self) -> Vec<(String, Vec<Section>)> {
+        let mut groups: Vec<(String, Vec<Section>)> = Vec::new();
+        for e in self.entries() {
+            let new_device = match groups.last() {
+                Some((d, _)) => *d != e.device_id,
+                None => true,
+            };
+            if new_device {
+                groups.push((e.device_id.clone(), Vec::new()));
+            }
+            let sections = &mut groups.last_mut().expect("group pushed above").1;
+            match sections.iter_mut().find(|(s, _)| *s == e.section) {
+                Some((_, rows)) => rows.push(e),
+                None => sections.push((e.section.clone(), vec![e])),
+            }
+        }
+        groups
+    }
+
+    /// Render as Markdown: a `#` title, a `##` heading per device, a `###` heading and a
+    /// table per section.
+    pub fn to_markdown(&self) -> String {
+        let cell = |s: &str| s.replace('|', "\\|");
+        let mut out = format!("# {}\n", cell(&self.profile.name));
+        if let Some(desc) = &self.profile.description {
+            let _ = write!(out, "\n{desc}\n");
+        }
+        for (device, sections) in self.grouped() {
+            let _ = write!(out, "\n## {}\n", cell(self.name_of(&device)));
+            for (section, rows) in sections {
+                let _ = write!(
+                    out,
+                    "\n### {}\n\n| Control | Action | Notes |\n|---|---|---|\n",
+                    cell(&section)
+                );
+                for r in rows {
+                    let _ = writeln!(
+                        out,
+                        "| {} | {} | {} |",
+                        cell(&r.control),
+                        cell(&r.action),
+                        cell(&r.note)
+                    );
+                }
+            }
+        }
+        out
+    }
+
+    /// Render as a standalone HTML page with print-friendly styling.
+    pub fn to_html(&self) -> String {
+        let title = escape_html(&self.profile.name);
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+             <style>\nbody{{font-family:sans-serif;margin:2em}}\n\
+             table{{border-collapse:collapse;margin-bottom:1em;page-break-inside:avoid}}\n\
+             th,td{{border:1px solid #999;padding:.25em .6em;text-align:left}}\n\
+             th{{background:#eee}}\n</style>\n</head>\n<body>\n<h1>{title}</h1>\n"
+        );
+        if let Some(desc) = &self.profile.description {
+            let _ = writeln!(out, "<p>{}</p>", escape_html(desc));
+        }
+        for (device, sections) in self.grouped() {
+            let _ = writeln!(out, "<h2>{}</h2>", escape_html(self.name_of(&device)));
+            for (section, rows) in sections {
+                let _ = writeln!(
+                    out,
+                    "<h3>{}</h3>\n<table>\n<tr><th>Control</th><th>Action</th><th>Notes</th></tr>",
+                    escape_html(&section)
+                );
+                for r in rows {
+                    let _ = writeln!(
+                        out,
+                        "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                        escape_html(&r.control),
+                        escape_html(&r.action),
+                        escape_html(&r.note)
+                    );
+                }
+                out.push_str("</table>\n");
+            }
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+pub(crate) fn hat_direction(dir: i16) -> &'static str {
+    usize::try_from(dir)
+        .ok()
+        .and_then(|d| HAT_DIRECTIONS.get(d))
+        .copied()
+        .unwrap_or("centered")
+}
+
+pub(crate) fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,169 @@
+//! Cross-process device claims.
+//!
+//! Several StickUp-based programs can run side by side (a remapper daemon and a game using the
+//! crate directly). HID handles are opened shared, so nothing stops both from reading the same
+//! device — or from fighting over its rumble, force feedback, and lights. Claims are a tiny
+//! local protocol to negotiate that:
+//!
+//! - [`ClaimMode::Exclusive`]: the owner is the only process that should open the device.
+//!   Other managers drop it at discovery when
+//!   [`DiscoveryOptions::respect_claims`](crate::manager::DiscoveryOptions::respect_claims)
+//!   is set.
+//! - [`ClaimMode::Output`]: the owner drives outputs; others may keep reading the input stream.
+//!
+//! Output calls on a [`Manager`](crate::manager::Manager) fail with [`Error::Claimed`] while
+//! another process holds any claim on the device.
+//!
+//! On Windows a claim is a named kernel event in the session namespace
+//! (`Local\stickup.claim.<hash>.<mode>`). It lives exactly as long as the owning
+//! [`DeviceClaim`] (or process), so crashed owners never leave stale claims behind.
+//! On other platforms claims always succeed and no foreign claims are ever seen.
+//!
+//! ```no_run
+//! use stickup::claims::ClaimMode;
+//! use stickup::Manager;
+//!
+//! let mut mgr = Manager::discover().expect("discover devices");
+//! let id = mgr.devices()[0].id.clone();
+//! mgr.claim(&id, ClaimMode::Output).expect("someone else drives this device");
+//! ```
+
+use crate::{Error, Result};
+
+/// What a claim reserves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ClaimMode {
+    /// Sole access: other processes should not open the device at all.
+    Exclusive,
+    /// Output access (rumble, FFB, lights); others may still read input.
+    Output,
+}
+
+impl ClaimMode {
+    fn suffix(self) -> &'static str {
+        match self {
+            ClaimMode::Exclusive => "excl",
+            ClaimMode::Output => "out",
+        }
+    }
+}
+
+/// A held claim; released on drop.
+#[derive(Debug)]
+pub struct DeviceClaim {
+    device_id: String,
+    mode: ClaimMode,
+    #[cfg(target_os = "windows")]
+    handle: windows::Handle,
+}
+
+impl DeviceClaim {
+    /// Claim `device_id` for this process.
+    ///
+    /// Fails with [`Error::Claimed`] if any process (including this one) already holds a claim
+    /// on the device.
+    pub fn acquire(device_id: &str, mode: ClaimMode) -> Result<Self> {
+        if claimed_by_other(device_id).is_some() {
+            return Err(Error::Claimed(device_id.to_string()));
+        }
+        #[cfg(target_os = "windows")]
+        let handle = windows::create(&object_name(device_id, mode))
+            .ok_or_else(|| Error::Claimed(device_id.to_string()))?;
+        Ok(Self {
+            device_id: device_id.to_string(),
+            mode,
+            #[cfg(target_os = "windows")]
+            handle,
+        })
+    }
+
+    /// Claimed device id.
+    pub fn device_id(&self) -> &str {
+        &self.device_id
+    }
+
+    /// Claim mode.
+    pub fn mode(&self) -> ClaimMode {
+        self.mode
+    }
+}
+
+/// Mode of an existing claim on `device_id` held by any process, if one exists.
+///
+/// Claims held by this process are reported too; [`Manager`](crate::manager::Manager) filters
+/// out its own.
+#[cfg_attr(not(target_os = "windows"), allow(unused_variables))]
+pub fn claimed_by_other(device_id: &str) -> Option<ClaimMode> {
+    #[cfg(target_os = "windows")]
+    for mode in [ClaimMode::Exclusive, ClaimMode::Output] {
+        if windows::exists(&object_name(device_id, mode)) {
+            return Some(mode);
+        }
+    }
+    None
+}
+
+/// Kernel object name for a claim. Device ids contain path characters, so they are hashed.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn object_name(device_id: &str, mode: ClaimMode) -> String {
+    // FNV-1a (64-bit): stable across processes and builds, unlike `DefaultHasher`.
+    let hash = device_id.bytes().fold(0xcbf2_9ce4_8422_2325u64, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("Local\\stickup.claim.{hash:016x}.{}", mode.suffix())
+}
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+
+    use windows_sys::Win32::Foundation::{CloseHandle, GetLastError, ERROR_ALREADY_EXISTS, HANDLE};
+    use windows_sys::Win32::System::Threading::{CreateEventW, OpenEventW};
+
+    /// `SYNCHRONIZE` access right (enough to test for existence).
+    const SYNCHRONIZE: u32 = 0x0010_0000;
+
+    /// Owned event handle, closed on drop.
+    #[derive(Debug)]
+    pub(super) struct Handle(HANDLE);
+
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            unsafe { CloseHandle(self.0) };
+        }
+    }
+
+    fn wide(name: &str) -> Vec<u16> {
+        OsStr::new(name)
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect()
+    }
+
+    /// Create the named event; `None` if it already exists or cannot be created.
+    pub(super) fn create(name: &str) -> Option<Handle> {
+        let name = wide(name);
+        let h = unsafe { CreateEventW(std::ptr::null(), 1, 0, name.as_ptr()) };
+        if h.is_null() {
+            return None;
+        }
+        // Losing the race: another process created it between our check and now.
+        if unsafe { GetLastError() } == ERROR_ALREADY_EXISTS {
+            unsafe { CloseHandle(h) };
+            return None;
+        }
+        Some(Handle(h))
+    }
+
+    /// Does a named event exist (i.e. is someone holding the claim)?
+    pub(super) fn exists(name: &str) -> bool {
+        let name = wide(name);
+        let h = unsafe { OpenEventW(SYNCHRONIZE, 0, name.as_ptr()) };
+        if h.is_null() {
+            return false;
+        }
+        unsafe { CloseHandle(h) };
+        true
+    }
+}
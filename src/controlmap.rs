@@ -0,0 +1,515 @@
+//! SVG control maps: a profile's bindings drawn onto a picture of the device.
+//!
+//! A [`DeviceTemplate`] is an outline drawing plus the position of each physical control.
+//! [`ControlMap`] places a label with every bound action next to its control and connects the
+//! two with a leader line, producing a standalone SVG that prints or embeds anywhere.
+//!
+//! Templates for an Xbox-style gamepad ([`DeviceTemplate::gamepad`]) and a generic HID flight
+//! stick ([`DeviceTemplate::flight_stick`]) are built in; others load from TOML or JSON:
+//!
+//! ```toml
+//! name = "Pedals"
+//! width = 300
+//! height = 120
+//! artwork = '<rect x="20" y="30" width="260" height="60" rx="8"/>'
+//!
+//! [[controls]]
+//! controls = ["Rz", "Rudder"]
+//! x = 150
+//! y = 60
+//! ```
+//!
+//! `artwork` is SVG markup drawn in the template's own `width × height` coordinate space
+//! (stroke and fill default to an outline style); an optional `image` (path or URL) is drawn
+//! beneath it. Each anchor lists every channel name the control may appear under, so one
+//! template can serve several backends, and may give the control a canonical `name` shown
+//! above its actions. A [`TemplateRegistry`] picks templates for devices by VID/PID
+//! ([`ControlMap::with_registry`]).
+//!
+//! ```
+//! use stickup::controlmap::{ControlMap, DeviceTemplate};
+//! use stickup::BindingProfile;
+//!
+//! let profile = BindingProfile::from_str_auto(r#"
+//! name = "Racing"
+//! [[bindings]]
+//! kind = "button"
+//! device_id = "pad"
+//! control = { control_id = "A", control_type = "Button" }
+//! action = "shift_up"
+//! "#).unwrap();
+//!
+//! let svg = ControlMap::new(&profile)
+//!     .template("pad", DeviceTemplate::gamepad())
+//!     .render("pad")
+//!     .unwrap();
+//! assert!(svg.starts_with("<svg"));
+//! assert!(svg.contains("shift_up"));
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::binding::{BindingProfile, BindingRule, ControlPath, ProfileFormat};
+use crate::cheatsheet::{escape_html, hat_direction};
+use crate::manager::Manager;
+use crate::templates::TemplateRegistry;
+use crate::{Error, Result};
+
+/// Width of the label column on each side of the drawing.
+const LABEL_COLUMN: f32 = 220.0;
+/// Height of the title band above the drawing.
+const TITLE_BAND: f32 = 40.0;
+/// Line height of label text.
+const LINE: f32 = 15.0;
+/// Gap between the label column and the drawing.
+const GUTTER: f32 = 16.0;
+
+/// Where a physical control sits on a [`DeviceTemplate`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ControlAnchor {
+    /// Channel names this control may appear under (e.g. `["Hat", "hat0"]`).
+    pub controls: Vec<String>,
+    /// Canonical name of the physical control (`"Pinky switch"`), shown above its actions.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Position in template coordinates.
+    pub x: f32,
+    /// Position in template coordinates.
+    pub y: f32,
+}
+
+/// Outline drawing of a device with the positions of its controls (see module docs).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeviceTemplate {
+    /// Template name (shown when no device name is given).
+    pub name: String,
+    /// Drawing width.
+    pub width: f32,
+    /// Drawing height.
+    pub height: f32,
+    /// Picture of the device (path or URL), stretched to `width × height` beneath `artwork`.
+    #[serde(default)]
+    pub image: Option<String>,
+    /// SVG markup drawn beneath the labels.
+    #[serde(default)]
+    pub artwork: String,
+    /// Control positions.
+    #[serde(default)]
+    pub controls: Vec<ControlAnchor>,
+}
+
+fn anchor(controls: &[&str], x: f32, y: f32) -> ControlAnchor {
+    ControlAnchor {
+        controls: controls.iter().map(|c| c.to_string()).collect(),
+        name: None,
+        x,
+        y,
+    }
+}
+
+/// HID button `n` (1-based usage) under its descriptor name and its fallback state name.
+fn hid_button(n: u16, x: f32, y: f32) -> ControlAnchor {
+    ControlAnchor {
+        controls: vec![format!("UP_0009_U_{n:04x}"), format!("btn{}", n - 1)],
+        name: None,
+        x,
+        y,
+    }
+}
+
+impl DeviceTemplate {
+    /// Xbox-style gamepad, using the XInput backend's channel names.
+    pub fn gamepad() -> Self {
+        Self {
+            name: "Gamepad".into(),
+            width: 420.0,
+            height: 260.0,
+            image: None,
+            artwork: concat!(
+                r#"<path d="M90 60 Q210 35 330 60 Q400 80 405 180 Q410 250 350 240 "#,
+                r#"Q310 235 280 190 L140 190 Q110 235 70 240 Q10 250 15 180 Q20 80 90 60 Z"/>"#,
+                r#"<rect x="85" y="14" width="50" height="16" rx="6"/>"#,
+                r#"<rect x="285" y="14" width="50" height="16" rx="6"/>"#,
+                r#"<rect x="80" y="38" width="60" height="12" rx="6"/>"#,
+                r#"<rect x="280" y="38" width="60" height="12" rx="6"/>"#,
+                r#"<circle cx="120" cy="105" r="24"/>"#,
+                r#"<circle cx="260" cy="160" r="24"/>"#,
+                r#"<path d="M152 145 h16 v15 h15 v16 h-15 v15 h-16 v-15 h-15 v-16 h15 Z"/>"#,
+                r#"<circle cx="300" cy="80" r="11"/><circle cx="275" cy="105" r="11"/>"#,
+                r#"<circle cx="325" cy="105" r="11"/><circle cx="300" cy="130" r="11"/>"#,
+                r#"<circle cx="185" cy="105" r="7"/><circle cx="235" cy="105" r="7"/>"#,
+            )
+            .into(),
+            controls: vec![
+                anchor(&["LT"], 110.0, 22.0),
+                anchor(&["RT"], 310.0, 22.0),
+                anchor(&["LB"], 110.0, 44.0),
+                anchor(&["RB"], 310.0, 44.0),
+                anchor(&["LX", "LY", "LThumb"], 120.0, 105.0),
+                anchor(&["RX", "RY", "RThumb"], 260.0, 160.0),
+                anchor(&["DPad"], 160.0, 168.0),
+                anchor(&["Back"], 185.0, 105.0),
+                anchor(&["Start"], 235.0, 105.0),
+                anchor(&["Y"], 300.0, 80.0),
+                anchor(&["X"], 275.0, 105.0),
+                anchor(&["B"], 325.0, 105.0),
+                anchor(&["A"], 300.0, 130.0),
+            ],
+        }
+    }
+
+    /// Generic HID flight stick: X/Y and twist (`Rz`), a base slider, one hat, and six
+    /// buttons in usage order (trigger first).
+    pub fn flight_stick() -> Self {
+        Self {
+            name: "Flight stick".into(),
+            width: 300.0,
+            height: 440.0,
+            image: None,
+            artwork: concat!(
+                r#"<rect x="40" y="360" width="220" height="60" rx="10"/>"#,
+                r#"<path d="M125 360 L118 200 Q110 160 100 120 L200 120 Q190 160 182 200 "#,
+                r#"L175 360 Z"/>"#,
+                r#"<ellipse cx="150" cy="85" rx="62" ry="48"/>"#,
+                r#"<circle cx="150" cy="70" r="12"/>"#,
+                r#"<rect x="92" y="140" width="14" height="30" rx="5"/>"#,
+                r#"<circle cx="190" cy="70" r="8"/><circle cx="110" cy="70" r="8"/>"#,
+                r#"<circle cx="170" cy="105" r="7"/><circle cx="130" cy="105" r="7"/>"#,
+                r#"<rect x="195" y="135" width="10" height="22" rx="4"/>"#,
+                r#"<rect x="215" y="375" width="30" height="10" rx="3"/>"#,
+            )
+            .into(),
+            controls: vec![
+                anchor(&["Hat", "hat0"], 150.0, 70.0),
+                hid_button(1, 99.0, 155.0),
+                hid_button(2, 190.0, 70.0),
+                hid_button(3, 110.0, 70.0),
+                hid_button(4, 170.0, 105.0),
+                hid_button(5, 130.0, 105.0),
+                hid_button(6, 200.0, 146.0),
+                anchor(&["Rz"], 150.0, 240.0),
+                anchor(&["X", "Y"], 150.0, 340.0),
+                anchor(&["Slider", "Z", "Throttle"], 230.0, 380.0),
+            ],
+        }
+    }
+
+    /// Parse a template from JSON or TOML text (see [`ProfileFormat::detect`]).
+    pub fn from_str_auto(text: &str) -> Result<Self> {
+        Self::parse(text, ProfileFormat::detect(text))
+    }
+
+    /// Parse a template in a known format.
+    pub fn parse(text: &str, format: ProfileFormat) -> Result<Self> {
+        match format {
+            ProfileFormat::Json => serde_json::from_str(text)
+                .map_err(|e| Error::Other(format!("invalid device template: {e}"))),
+            ProfileFormat::Toml => toml::from_str(text)
+                .map_err(|e| Error::Other(format!("invalid device template: {e}"))),
+        }
+    }
+
+    /// Load a template file. The format comes from the extension, else from the contents.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Other(format!("read {}: {e}", path.display())))?;
+        let format = ProfileFormat::from_path(path).unwrap_or_else(|| ProfileFormat::detect(&text));
+        Self::parse(&text, format)
+    }
+
+    /// Anchor for channel `control`.
+    pub fn anchor(&self, control: &str) -> Option<&ControlAnchor> {
+        self.anchor_of(control).map(|i| &self.controls[i])
+    }
+
+    /// Index of the anchor for channel `control`.
+    fn anchor_of(&self, control: &str) -> Option<usize> {
+        self.controls
+            .iter()
+            .position(|a| a.controls.iter().any(|c| c == control))
+    }
+}
+
+/// Renders a profile's bindings onto [`DeviceTemplate`]s (see module docs).
+#[derive(Clone, Debug)]
+pub struct ControlMap<'a> {
+    profile: &'a BindingProfile,
+    templates: HashMap<String, DeviceTemplate>,
+    device_names: HashMap<String, String>,
+}
+
+impl<'a> ControlMap<'a> {
+    /// Control map for `profile`; add a template per device to draw.
+    pub fn new(profile: &'a BindingProfile) -> Self {
+        Self {
+            profile,
+            templates: HashMap::new(),
+            device_names: HashMap::new(),
+        }
+    }
+
+    /// Draw `device_id` on `template`.
+    pub fn template(mut self, device_id: impl Into<String>, template: DeviceTemplate) -> Self {
+        self.templates.insert(device_id.into(), template);
+        self
+    }
+
+    /// Use `registry`'s template and the device name for every device `mgr` manages that it
+    /// has a template for (see [`TemplateRegistry::for_device`]).
+    pub fn with_registry(mut self, mgr: &Manager, registry: &TemplateRegistry) -> Self {
+        for d in mgr.devices() {
+            let Some(t) = registry.for_device(&d.meta) else {
+                continue;
+            };
+            self.templates.insert(d.id.clone(), t.clone());
+            if !d.name.trim().is_empty() {
+                self.device_names.insert(d.id.clone(), d.name.clone());
+            }
+        }
+        self
+    }
+
+    /// Title the drawing of `device_id` with `name` instead of the template name.
+    pub fn device_name(mut self, device_id: impl Into<String>, name: impl Into<String>) -> Self {
+        self.device_names.insert(device_id.into(), name.into());
+        self
+    }
+
+    /// `(control_id, label)` for every binding of `device_id`, in profile order. Labels of
+    /// layer and action-set bindings are prefixed with `[name]`.
+    fn labels(&self, device_id: &str) -> Vec<(String, String)> {
+        let p = self.profile;
+        let sections = std::iter::once((None, &p.bindings))
+            .chain(p.layers.iter().map(|l| (Some(&l.name), &l.bindings)))
+            .chain(p.action_sets.iter().map(|s| (Some(&s.name), &s.bindings)));
+        let mut out = Vec::new();
+        for (section, rules) in sections {
+            let prefix = section.map_or(String::new(), |s| format!("[{s}] "));
+            for rule in rules.iter().filter(|r| r.device_id() == device_id) {
+                for (control, text) in rule_labels(rule) {
+                    out.push((control, format!("{prefix}{text}")));
+                }
+            }
+        }
+        out
+    }
+
+    /// Render the SVG for `device_id`, or `None` without a template for it.
+    ///
+    /// Bound controls the template has no anchor for are listed beneath the drawing.
+    pub fn render(&self, device_id: &str) -> Option<String> {
+        let t = self.templates.get(device_id)?;
+        let title = self.device_names.get(device_id).unwrap_or(&t.name);
+
+        let mut per_anchor: Vec<Vec<String>> = vec![Vec::new(); t.controls.len()];
+        let mut unplaced = Vec::new();
+        for (control, label) in self.labels(device_id) {
+            match t.anchor_of(&control) {
+                Some(i) => per_anchor[i].push(label),
+                None => unplaced.push(format!("{control}: {label}")),
+            }
+        }
+        for (lines, a) in per_anchor.iter_mut().zip(&t.controls) {
+            if let (false, Some(name)) = (lines.is_empty(), &a.name) {
+                lines.insert(0, name.clone());
+            }
+        }
+
+        // Stack labels per side in anchor order, pushing them down so they don't overlap.
+        let mut placed: Vec<(usize, bool, f32)> = Vec::new();
+        for left in [true, false] {
+            let mut side: Vec<usize> = (0..t.controls.len())
+                .filter(|&i| !per_anchor[i].is_empty() && (t.controls[i].x < t.width / 2.0) == left)
+                .collect();
+            side.sort_by(|&a, &b| t.controls[a].y.total_cmp(&t.controls[b].y));
+            let mut next = 0.0f32;
+            for i in side {
+                let lines = per_anchor[i].len() as f32;
+                let top = (t.controls[i].y - (lines - 1.0) * LINE / 2.0).max(next);
+                placed.push((i, left, top));
+                next = top + lines * LINE + 4.0;
+            }
+        }
+
+        let ox = LABEL_COLUMN + GUTTER;
+        let labels_bottom = placed
+            .iter()
+            .map(|&(i, _, top)| top + per_anchor[i].len() as f32 * LINE)
+            .fold(t.height, f32::max);
+        let width = t.width + 2.0 * ox;
+        let height = TITLE_BAND + labels_bottom + 20.0 + unplaced.len() as f32 * LINE;
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+             viewBox=\"0 0 {width} {height}\" font-family=\"sans-serif\" font-size=\"12\">\n\
+             <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n\
+             <text x=\"{}\" y=\"26\" font-size=\"18\" text-anchor=\"middle\">{}</text>\n",
+            width / 2.0,
+            escape_html(title),
+        );
+        if let Some(image) = &t.image {
+            let _ = writeln!(
+                svg,
+                "<image href=\"{}\" x=\"{ox}\" y=\"{TITLE_BAND}\" width=\"{}\" height=\"{}\" \
+                 preserveAspectRatio=\"none\"/>",
+                escape_html(image),
+                t.width,
+                t.height
+            );
+        }
+        let _ = writeln!(
+            svg,
+            "<g transform=\"translate({ox} {TITLE_BAND})\" fill=\"#f4f4f4\" stroke=\"#555\" \
+             stroke-width=\"2\">{}</g>",
+            t.artwork
+        );
+        for (i, a) in t.controls.iter().enumerate() {
+            let fill = if per_anchor[i].is_empty() {
+                "#bbb"
+            } else {
+                "#c22"
+            };
+            let _ = writeln!(
+                svg,
+                "<circle cx=\"{}\" cy=\"{}\" r=\"3.5\" fill=\"{fill}\"/>",
+                ox + a.x,
+                TITLE_BAND + a.y
+            );
+        }
+        for &(i, left, top) in &placed {
+            let a = &t.controls[i];
+            let lines = &per_anchor[i];
+            let (tx, anchor_attr) = if left {
+                (LABEL_COLUMN, "end")
+            } else {
+                (ox + t.width + GUTTER, "start")
+            };
+            let mid = TITLE_BAND + top + (lines.len() as f32 - 1.0) * LINE / 2.0;
+            let edge = if left { tx + 4.0 } else { tx - 4.0 };
+            let _ = writeln!(
+                svg,
+                "<polyline points=\"{},{} {},{} {edge},{mid}\" fill=\"none\" stroke=\"#c22\"/>",
+                ox + a.x,
+                TITLE_BAND + a.y,
+                if left { ox - 4.0 } else { ox + t.width + 4.0 },
+                mid,
+            );
+            let _ = write!(svg, "<text x=\"{tx}\" text-anchor=\"{anchor_attr}\">");
+            for (n, line) in lines.iter().enumerate() {
+                let weight = if n == 0 && a.name.is_some() {
+                    " font-weight=\"bold\""
+                } else {
+                    ""
+                };
+                let _ = write!(
+                    svg,
+                    "<tspan x=\"{tx}\" y=\"{}\"{weight}>{}</tspan>",
+                    TITLE_BAND + top + n as f32 * LINE + 4.0,
+                    escape_html(line)
+                );
+            }
+            svg.push_str("</text>\n");
+        }
+        for (n, line) in unplaced.iter().enumerate() {
+            let _ = writeln!(
+                svg,
+                "<text x=\"{ox}\" y=\"{}\" fill=\"#555\">{}</text>",
+                TITLE_BAND + labels_bottom + 20.0 + n as f32 * LINE,
+                escape_html(line)
+            );
+        }
+        svg.push_str("</svg>\n");
+        Some(svg)
+    }
+}
+
+/// `(control_id, text)` for each control a rule reads.
+fn rule_labels(rule: &BindingRule) -> Vec<(String, String)> {
+    let one = |c: &ControlPath, text: String| vec![(c.control_id.clone(), text)];
+    let with_hat = |action: &str, dir: &Option<i16>| match dir {
+        Some(d) => format!("{}: {action}", hat_direction(*d)),
+        None => action.to_string(),
+    };
+    match rule {
+        BindingRule::Axis1d {
+            control, action, ..
+        }
+        | BindingRule::HatAxes {
+            control, action, ..
+        } => one(control, action.clone()),
+        BindingRule::Button {
+            control,
+            action,
+            hat_direction,
+            ..
+        } => one(control, with_hat(action, hat_direction)),
+        BindingRule::Gesture {
+            control,
+            action,
+            hat_direction,
+            ..
+        } => one(
+            control,
+            format!("{} (tap/hold)", with_hat(action, hat_direction)),
+        ),
+        BindingRule::Dwell {
+            control,
+            action,
+            hat_direction,
+            dwell_ms,
+            ..
+        } => one(
+            control,
+            format!("{} (hold {dwell_ms} ms)", with_hat(action, hat_direction)),
+        ),
+        BindingRule::Axis2d {
+            control, action, ..
+        } => vec![
+            (control.x.control_id.clone(), format!("{action} X")),
+            (control.y.control_id.clone(), format!("{action} Y")),
+        ],
+        BindingRule::ButtonAxis {
+            negative,
+            positive,
+            action,
+            ..
+        } => vec![
+            (negative.control_id.clone(), format!("{action} −")),
+            (positive.control_id.clone(), format!("{action} +")),
+        ],
+        BindingRule::Chord {
+            controls, action, ..
+        } => controls
+            .iter()
+            .map(|c| (c.control_id.clone(), format!("{action} (chord)")))
+            .collect(),
+        BindingRule::Interlock {
+            controls, action, ..
+        } => controls
+            .iter()
+            .map(|c| (c.control_id.clone(), format!("{action} (interlock)")))
+            .collect(),
+        BindingRule::HatActions {
+            control, actions, ..
+        } => {
+            let step = if actions.len() == 4 { 2 } else { 1 };
+            actions
+                .iter()
+                .enumerate()
+                .filter(|(_, a)| !a.is_empty())
+                .map(|(i, a)| {
+                    let dir = (i * step) as i16;
+                    (
+                        control.control_id.clone(),
+                        format!("{}: {a}", hat_direction(dir)),
+                    )
+                })
+                .collect()
+        }
+    }
+}
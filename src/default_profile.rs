@@ -0,0 +1,185 @@
+//! Starter binding profiles for unknown hardware.
+//!
+//! [`BindingProfile::from_channels`] looks at a device's [`ChannelDesc`]s and builds a
+//! flight-style profile an application can use until the user binds things properly:
+//!
+//! | Channel (usage or name)                          | Rule                     | Action       |
+//! |--------------------------------------------------|--------------------------|--------------|
+//! | X / `LX`                                         | `Axis1d`                 | `roll`       |
+//! | Y / `LY`                                         | `Axis1d`                 | `pitch`      |
+//! | Rz / `RX`                                        | `Axis1d`                 | `yaw`        |
+//! | Throttle, Accelerator, Z, Slider (first of them) | `Axis1d`, no deadzone    | `throttle`   |
+//! | Brake                                            | `Axis1d`, no deadzone    | `brake`      |
+//! | other axes                                       | `Axis1d`                 | `axis_<name>`|
+//! | first hat                                        | `HatAxes`                | `view`       |
+//! | other hats                                       | `HatAxes`                | `hat_<n>`    |
+//! | buttons                                          | `Button`                 | `button_<n>` |
+//!
+//! Buttons and extra hats are numbered from 1 in descriptor order. Each role is assigned at
+//! most once; later candidates fall through to the generic names.
+
+use std::collections::HashSet;
+
+use crate::binding::{AxisTransform, BindingProfile, BindingRule, ControlPath, ControlType};
+use crate::event::{ChannelDesc, ChannelKind};
+
+/// Role of a well-known axis.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum AxisRole {
+    Roll,
+    Pitch,
+    Yaw,
+    Throttle,
+    Brake,
+}
+
+impl AxisRole {
+    fn action(self) -> &'static str {
+        match self {
+            AxisRole::Roll => "roll",
+            AxisRole::Pitch => "pitch",
+            AxisRole::Yaw => "yaw",
+            AxisRole::Throttle => "throttle",
+            AxisRole::Brake => "brake",
+        }
+    }
+
+    /// Throttle-like axes rest at one end; a centre deadzone would eat travel.
+    fn transform(self) -> AxisTransform {
+        match self {
+            AxisRole::Throttle | AxisRole::Brake => AxisTransform {
+                deadzone: 0.0,
+                ..AxisTransform::default()
+            },
+            _ => AxisTransform::default(),
+        }
+    }
+}
+
+/// Role suggested by the channel's HID usage, else by its name.
+fn axis_role(desc: &ChannelDesc) -> Option<AxisRole> {
+    match (desc.usage_page, desc.usage) {
+        (Some(0x01), Some(0x30)) => return Some(AxisRole::Roll),
+        (Some(0x01), Some(0x31)) => return Some(AxisRole::Pitch),
+        (Some(0x01), Some(0x35)) => return Some(AxisRole::Yaw),
+        (Some(0x01), Some(0x32 | 0x36)) | (Some(0x02), Some(0xBB | 0xB0)) => {
+            return Some(AxisRole::Throttle)
+        }
+        (Some(0x02), Some(0xB1)) => return Some(AxisRole::Brake),
+        _ => {}
+    }
+    match desc.name.as_deref()? {
+        "X" | "LX" => Some(AxisRole::Roll),
+        "Y" | "LY" => Some(AxisRole::Pitch),
+        "Rz" | "RX" => Some(AxisRole::Yaw),
+        "Throttle" | "Accelerator" | "Z" | "Slider" => Some(AxisRole::Throttle),
+        "Brake" => Some(AxisRole::Brake),
+        _ => None,
+    }
+}
+
+/// `"Hat 1 (Castle)"` → `"hat_1_castle"`.
+fn action_slug(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            out.push(c.to_ascii_lowercase());
+        } else if !out.is_empty() && !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+    while out.ends_with('_') {
+        out.pop();
+    }
+    out
+}
+
+/// `base`, or `base_2`, `base_3`, … if taken.
+fn unique(base: String, taken: &mut HashSet<String>) -> String {
+    let mut name = base.clone();
+    let mut n = 2;
+    while taken.contains(&name) {
+        name = format!("{base}_{n}");
+        n += 1;
+    }
+    taken.insert(name.clone());
+    name
+}
+
+impl BindingProfile {
+    /// Build a starter profile for `device_id` from its channels (see module docs).
+    ///
+    /// The profile is named after the device id; rename it before saving.
+    pub fn from_channels(device_id: &str, channels: &[ChannelDesc]) -> BindingProfile {
+        let mut bindings = Vec::new();
+        let mut roles = HashSet::new();
+        let mut taken = HashSet::new();
+        let (mut buttons, mut hats) = (0usize, 0usize);
+
+        for desc in channels {
+            let control_id = desc.state_name();
+            match desc.kind {
+                ChannelKind::Axis => {
+                    let role = axis_role(desc).filter(|r| roles.insert(*r));
+                    let (action, xform) = match role {
+                        Some(r) => (r.action().to_string(), r.transform()),
+                        None => (
+                            format!("axis_{}", action_slug(&control_id)),
+                            AxisTransform::default(),
+                        ),
+                    };
+                    bindings.push(BindingRule::Axis1d {
+                        device_id: device_id.to_string(),
+                        control: ControlPath {
+                            control_id,
+                            control_type: ControlType::Axis,
+                        },
+                        action: unique(action, &mut taken),
+                        xform,
+                    });
+                }
+                ChannelKind::Button => {
+                    buttons += 1;
+                    bindings.push(BindingRule::Button {
+                        device_id: device_id.to_string(),
+                        control: ControlPath {
+                            control_id,
+                            control_type: ControlType::Button,
+                        },
+                        action: unique(format!("button_{buttons}"), &mut taken),
+                        axis_press_threshold: None,
+                        hat_direction: None,
+                    });
+                }
+                ChannelKind::Hat => {
+                    hats += 1;
+                    let action = if hats == 1 {
+                        "view".to_string()
+                    } else {
+                        format!("hat_{hats}")
+                    };
+                    bindings.push(BindingRule::HatAxes {
+                        device_id: device_id.to_string(),
+                        control: ControlPath {
+                            control_id,
+                            control_type: ControlType::Hat,
+                        },
+                        action: unique(action, &mut taken),
+                        normalize_diagonals: false,
+                    });
+                }
+            }
+        }
+
+        BindingProfile {
+            version: crate::binding::PROFILE_VERSION,
+            name: device_id.to_string(),
+            description: Some("Generated default profile".into()),
+            bindings,
+            layers: Vec::new(),
+            action_sets: Vec::new(),
+            required_devices: Vec::new(),
+            channels: Vec::new(),
+        }
+    }
+}
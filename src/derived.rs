@@ -0,0 +1,329 @@
+//! Derived motion channels: per-axis velocity and acceleration.
+//!
+//! "Flick the stick", "slam the throttle": actions that depend on how fast a control moves
+//! rather than where it is. [`derive_motion`] wraps a device so each absolute axis gains up
+//! to two extra axes computed from report timestamps:
+//! - `"<axis> velocity"` — rate of change, normalized by
+//!   [`velocity_scale`](DerivedConfig::velocity_scale) (units per second at `1.0`);
+//! - `"<axis> accel"` — rate of change of velocity, normalized by
+//!   [`acceleration_scale`](DerivedConfig::acceleration_scale).
+//!
+//! Both are clamped to `[-1, 1]` and fall back to `0` once the source axis has been still for
+//! [`settle`](DerivedConfig::settle). They are ordinary axes: they appear in
+//! [`Device::describe`], snapshots, and bind capture, and bind like any other channel
+//! (`control_id = "X velocity"`). Derived indices follow the device's highest axis index, so
+//! existing bindings keep working.
+//!
+//! Enable them for every discovered device with
+//! [`DiscoveryOptions::derived_channels`](crate::manager::DiscoveryOptions::derived_channels):
+//!
+//! ```no_run
+//! use stickup::derived::DerivedConfig;
+//! use stickup::manager::DiscoveryOptions;
+//!
+//! # fn main() -> stickup::Result<()> {
+//! let opts = DiscoveryOptions::default().derived_channels(DerivedConfig::default());
+//! let mgr = stickup::Manager::discover_with(opts)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Relative axes (mouse motion, [`ChannelUnit::Counts`]) are already rates and get no derived
+//! channels.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::device::{BatteryStatus, Device, ReportIdDiagnostics};
+use crate::event::{ChannelDesc, ChannelKind, ChannelUnit, InputKind};
+use crate::ffb::{EffectHandle, EffectOp, FfbEffect};
+use crate::metadata::DeviceMeta;
+use crate::output::OutputCommand;
+use crate::Result;
+
+/// Samples closer together than this are merged into the next derivative step.
+const MIN_STEP: Duration = Duration::from_millis(1);
+
+/// Which derived channels to add and how to scale them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DerivedConfig {
+    /// Add a velocity channel per axis.
+    pub velocity: bool,
+    /// Add an acceleration channel per axis.
+    pub acceleration: bool,
+    /// Velocity (normalized units per second) reported as `1.0`.
+    pub velocity_scale: f32,
+    /// Acceleration (normalized units per second²) reported as `1.0`.
+    pub acceleration_scale: f32,
+    /// Exponential smoothing of both rates, `0.0` (none) to just below `1.0` (heavy).
+    pub smoothing: f32,
+    /// Derived channels drop to `0` after the source axis has been still this long.
+    pub settle: Duration,
+}
+
+impl Default for DerivedConfig {
+    fn default() -> Self {
+        Self {
+            velocity: true,
+            acceleration: false,
+            velocity_scale: 8.0,
+            acceleration_scale: 200.0,
+            smoothing: 0.3,
+            settle: Duration::from_millis(50),
+        }
+    }
+}
+
+impl DerivedConfig {
+    /// Enable/disable velocity channels.
+    pub fn velocity(mut self, on: bool) -> Self {
+        self.velocity = on;
+        self
+    }
+
+    /// Enable/disable acceleration channels.
+    pub fn acceleration(mut self, on: bool) -> Self {
+        self.acceleration = on;
+        self
+    }
+
+    /// Set the smoothing factor (clamped to `0.0..=0.99`).
+    pub fn smoothing(mut self, smoothing: f32) -> Self {
+        self.smoothing = smoothing.clamp(0.0, 0.99);
+        self
+    }
+}
+
+/// Derivative state of one source axis.
+#[derive(Clone, Copy, Debug)]
+struct Motion {
+    /// Index of the velocity channel, if enabled.
+    velocity_idx: Option<u16>,
+    /// Index of the acceleration channel, if enabled.
+    accel_idx: Option<u16>,
+    /// Last sample used as a derivative anchor.
+    last: Option<(f32, Instant)>,
+    velocity: f32,
+    accel: f32,
+    /// Last reported (normalized) values.
+    out_velocity: f32,
+    out_accel: f32,
+}
+
+/// A device with derived velocity/acceleration axes (see module docs).
+pub struct DerivedChannels {
+    inner: Box<dyn Device>,
+    config: DerivedConfig,
+    motions: HashMap<u16, Motion>,
+    descs: Vec<ChannelDesc>,
+}
+
+impl DerivedChannels {
+    fn sample(&mut self, axis: u16, value: f32, t: Instant, out: &mut Vec<InputKind>) {
+        let cfg = &self.config;
+        let Some(m) = self.motions.get_mut(&axis) else {
+            return;
+        };
+        let Some((last, last_t)) = m.last else {
+            m.last = Some((value, t));
+            return;
+        };
+        let dt = t.saturating_duration_since(last_t);
+        if dt < MIN_STEP {
+            return;
+        }
+        let dt = dt.as_secs_f32();
+        let keep = cfg.smoothing.clamp(0.0, 0.99);
+        let velocity = keep * m.velocity + (1.0 - keep) * (value - last) / dt;
+        let accel = keep * m.accel + (1.0 - keep) * (velocity - m.velocity) / dt;
+        m.velocity = velocity;
+        m.accel = accel;
+        m.last = Some((value, t));
+        emit(m, cfg, out);
+    }
+
+    /// Zero the rates of axes that have been still for the settle time.
+    fn settle(&mut self, now: Instant, out: &mut Vec<InputKind>) {
+        let cfg = &self.config;
+        for m in self.motions.values_mut() {
+            let Some((value, t)) = m.last else {
+                continue;
+            };
+            if now.saturating_duration_since(t) < cfg.settle {
+                continue;
+            }
+            if m.velocity != 0.0 || m.accel != 0.0 {
+                m.velocity = 0.0;
+                m.accel = 0.0;
+                // Re-anchor so the next movement is measured from now, not from the last sample.
+                m.last = Some((value, now));
+                emit(m, cfg, out);
+            }
+        }
+    }
+}
+
+/// Push changed normalized rates of `m`.
+fn emit(m: &mut Motion, cfg: &DerivedConfig, out: &mut Vec<InputKind>) {
+    let norm = |x: f32, scale: f32| {
+        if scale > 0.0 && x.is_finite() {
+            (x / scale).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        }
+    };
+    if let Some(idx) = m.velocity_idx {
+        let v = norm(m.velocity, cfg.velocity_scale);
+        if v != m.out_velocity {
+            m.out_velocity = v;
+            out.push(InputKind::AxisMoved {
+                axis: idx,
+                value: v,
+            });
+        }
+    }
+    if let Some(idx) = m.accel_idx {
+        let a = norm(m.accel, cfg.acceleration_scale);
+        if a != m.out_accel {
+            m.out_accel = a;
+            out.push(InputKind::AxisMoved {
+                axis: idx,
+                value: a,
+            });
+        }
+    }
+}
+
+impl Device for DerivedChannels {
+    fn poll(&mut self) -> Vec<InputKind> {
+        self.try_poll().unwrap_or_default()
+    }
+    fn try_poll(&mut self) -> Result<Vec<InputKind>> {
+        let mut events = self.inner.try_poll()?;
+        let now = Instant::now();
+        let t = self.inner.report_time().unwrap_or(now);
+        let mut derived = Vec::new();
+        for ev in &events {
+            if let InputKind::AxisMoved { axis, value } = *ev {
+                self.sample(axis, value, t, &mut derived);
+            }
+        }
+        self.settle(now, &mut derived);
+        events.append(&mut derived);
+        Ok(events)
+    }
+    /// The inner device's state; derived channels start at rest.
+    fn initial_state(&mut self) -> Vec<InputKind> {
+        self.inner.initial_state()
+    }
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+    fn id(&self) -> &str {
+        self.inner.id()
+    }
+    fn metadata(&self) -> DeviceMeta {
+        self.inner.metadata()
+    }
+    fn describe(&self) -> Vec<ChannelDesc> {
+        self.descs.clone()
+    }
+    fn report_id_diagnostics(&self) -> Option<ReportIdDiagnostics> {
+        self.inner.report_id_diagnostics()
+    }
+    fn battery(&self) -> Option<BatteryStatus> {
+        self.inner.battery()
+    }
+    fn fixed_player_index(&self) -> Option<u8> {
+        self.inner.fixed_player_index()
+    }
+    fn report_time(&self) -> Option<Instant> {
+        self.inner.report_time()
+    }
+    fn set_rumble(&mut self, low: f32, high: f32) -> Result<()> {
+        self.inner.set_rumble(low, high)
+    }
+    fn send_output(&mut self, cmd: &OutputCommand) -> Result<()> {
+        self.inner.send_output(cmd)
+    }
+    fn upload_effect(&mut self, effect: &FfbEffect) -> Result<EffectHandle> {
+        self.inner.upload_effect(effect)
+    }
+    fn play_effect(&mut self, handle: EffectHandle, op: EffectOp) -> Result<()> {
+        self.inner.play_effect(handle, op)
+    }
+    fn capture_reports(&mut self, path: Option<&Path>) -> Result<()> {
+        self.inner.capture_reports(path)
+    }
+}
+
+/// Add the derived channels `config` enables to every absolute axis of `device`.
+///
+/// Returns `device` unchanged if `config` enables none, or the device has no eligible axis.
+pub fn derive_motion(device: Box<dyn Device>, config: &DerivedConfig) -> Box<dyn Device> {
+    if !config.velocity && !config.acceleration {
+        return device;
+    }
+    let mut descs = device.describe();
+    let mut sources: Vec<ChannelDesc> = descs
+        .iter()
+        .filter(|d| d.kind == ChannelKind::Axis && d.unit != Some(ChannelUnit::Counts))
+        .cloned()
+        .collect();
+    if sources.is_empty() {
+        return device;
+    }
+    sources.sort_by_key(|d| d.idx);
+    let mut next = descs
+        .iter()
+        .filter(|d| d.kind == ChannelKind::Axis)
+        .map(|d| d.idx)
+        .max()
+        .map_or(0, |m| m.saturating_add(1));
+    let mut motions = HashMap::new();
+    for src in &sources {
+        let base = src.state_name();
+        let mut derived = |suffix: &str| {
+            let idx = next;
+            next = next.saturating_add(1);
+            descs.push(ChannelDesc {
+                idx,
+                name: Some(format!("{base} {suffix}")),
+                logical_min: -1,
+                logical_max: 1,
+                usage_page: None,
+                usage: None,
+                unit: None,
+                ..src.clone()
+            });
+            idx
+        };
+        let velocity_idx = config.velocity.then(|| derived("velocity"));
+        let accel_idx = config.acceleration.then(|| derived("accel"));
+        motions.insert(
+            src.idx,
+            Motion {
+                velocity_idx,
+                accel_idx,
+                last: None,
+                velocity: 0.0,
+                accel: 0.0,
+                out_velocity: 0.0,
+                out_accel: 0.0,
+            },
+        );
+    }
+    Box::new(DerivedChannels {
+        inner: device,
+        config: config.clone(),
+        motions,
+        descs,
+    })
+}
@@ -0,0 +1,877 @@
+//! Platform-independent HID report descriptor parsing.
+//!
+//! [`ReportDescriptor::parse`] walks raw report descriptor bytes (as returned by hidapi's
+//! `get_report_descriptor`, or stored in a [report capture](crate::report_capture)) and lays
+//! out every input field: report ID, bit offset, size, logical range, usages, and collection.
+//!
+//! [`DescriptorParser`] decodes input reports from that layout with the same policies as the
+//! Windows HIDP parser:
+//! - axes normalized to `[-1, 1]` (signed fields sign-extended when the logical minimum is
+//!   negative), with a ~2 LSB jitter filter;
+//! - hats (usage `0x39`) as slots: `-1` neutral, `0..7` clockwise from up, degrees converted
+//!   using 45° sectors;
+//! - 1-bit variable fields and array fields as buttons, emitted as press/release edges.
+//!
+//! Channel indices are assigned the same way as well: axes and buttons in descriptor order,
+//! hats ordered by (collection, report ID, usage).
+//!
+//! It needs no OS handles, so parser behavior can be verified on any platform from a
+//! descriptor and captured reports:
+//!
+//! ```no_run
+//! use stickup::descriptor::DescriptorParser;
+//! use stickup::report_capture::ReportCapture;
+//!
+//! # fn main() -> stickup::Result<()> {
+//! let capture = ReportCapture::load("throttle-capture.jsonl")?;
+//! let mut parser = DescriptorParser::from_capture(&capture)?;
+//! for events in capture.replay(&mut parser) {
+//!     println!("{events:?}");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Only input items are laid out; output and feature reports are skipped.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::device::{ParseCtx, ReportIdDiagnostics, ReportParser};
+use crate::event::{ChannelDesc, ChannelKind, ChannelUnit, InputKind};
+use crate::quirks::DeviceQuirks;
+use crate::report_capture::ReportCapture;
+use crate::{Error, Result};
+
+// Main item flags (Input/Output/Feature data bits).
+const FLAG_CONSTANT: u32 = 0x01;
+const FLAG_VARIABLE: u32 = 0x02;
+const FLAG_RELATIVE: u32 = 0x04;
+
+/// One input main item (non-constant) from the descriptor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InputField {
+    /// Report ID (`0` for unnumbered reports).
+    pub report_id: u8,
+    /// Bit offset in the report payload (after the report ID byte).
+    pub bit_offset: u32,
+    /// Bits per element (`Report Size`).
+    pub bit_size: u32,
+    /// Number of elements (`Report Count`).
+    pub report_count: u32,
+    /// `(usage page, usage)` pairs in declaration order, with usage ranges expanded.
+    ///
+    /// Variable fields use one usage per element (the last one repeats); array fields
+    /// report indices into this list.
+    pub usages: Vec<(u16, u16)>,
+    /// Logical minimum.
+    pub logical_min: i32,
+    /// Logical maximum.
+    pub logical_max: i32,
+    /// Raw main item data (`Data/Constant`, `Array/Variable`, `Absolute/Relative`, …).
+    pub flags: u32,
+    /// Index into [`ReportDescriptor::collections`] of the innermost enclosing collection.
+    pub collection: u16,
+}
+
+impl InputField {
+    /// Variable field (one value per element) rather than an array of usage indices.
+    pub fn is_variable(&self) -> bool {
+        self.flags & FLAG_VARIABLE != 0
+    }
+
+    /// Relative values (deltas) rather than absolute positions.
+    pub fn is_relative(&self) -> bool {
+        self.flags & FLAG_RELATIVE != 0
+    }
+
+    /// Whether the field's values are signed (negative logical minimum).
+    pub fn is_signed(&self) -> bool {
+        self.logical_min < 0
+    }
+
+    /// Usage of element `i` of a variable field.
+    pub fn usage(&self, i: usize) -> Option<(u16, u16)> {
+        self.usages.get(i).or_else(|| self.usages.last()).copied()
+    }
+}
+
+/// One collection from the descriptor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Collection {
+    /// Collection usage page.
+    pub usage_page: u16,
+    /// Collection usage.
+    pub usage: u16,
+    /// Enclosing collection, if nested.
+    pub parent: Option<u16>,
+}
+
+/// Input layout of a HID report descriptor (see module docs).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReportDescriptor {
+    /// Input fields in descriptor order (padding excluded).
+    pub fields: Vec<InputField>,
+    /// Collections in descriptor order; index `0` is the top-level collection, matching
+    /// HIDP link collection numbering.
+    pub collections: Vec<Collection>,
+    /// Input report IDs (`{0}` for devices with unnumbered reports).
+    pub report_ids: BTreeSet<u8>,
+    /// Input payload length in bits per report ID (padding included).
+    report_bits: BTreeMap<u8, u32>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Globals {
+    usage_page: u16,
+    logical_min: i32,
+    logical_max: i32,
+    logical_max_unsigned: u32,
+    report_size: u32,
+    report_count: u32,
+    report_id: u8,
+}
+
+#[derive(Default)]
+struct Locals {
+    /// `(explicit page from an extended usage, usage)`.
+    usages: Vec<(Option<u16>, u16)>,
+    usage_min: Option<(Option<u16>, u16)>,
+}
+
+/// Split local usage data into `(extended page, usage)`; 4-byte usages carry their page.
+fn local_usage(data: u32, size: usize) -> (Option<u16>, u16) {
+    if size == 4 {
+        (Some((data >> 16) as u16), data as u16)
+    } else {
+        (None, data as u16)
+    }
+}
+
+pub(crate) fn sign_extend(raw: u32, bits: u32) -> i32 {
+    if bits == 0 || bits >= 32 {
+        return raw as i32;
+    }
+    let shift = 32 - bits;
+    ((raw << shift) as i32) >> shift
+}
+
+impl ReportDescriptor {
+    /// Parse raw report descriptor bytes.
+    ///
+    /// Unknown items are ignored; truncated items and unbalanced collections or
+    /// `Push`/`Pop` are errors.
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let invalid = |at: usize, what: &str| {
+            Error::Other(format!("invalid report descriptor: offset {at}: {what}"))
+        };
+        let mut out = Self::default();
+        let mut globals = Globals::default();
+        let mut global_stack: Vec<Globals> = Vec::new();
+        let mut locals = Locals::default();
+        let mut collection_stack: Vec<u16> = Vec::new();
+
+        let mut i = 0;
+        while i < bytes.len() {
+            let prefix = bytes[i];
+            // Long items: 0xFE, data size, long tag, data.
+            if prefix == 0xFE {
+                let size = *bytes
+                    .get(i + 1)
+                    .ok_or_else(|| invalid(i, "truncated long item"))?;
+                i += 3 + size as usize;
+                continue;
+            }
+            let size = [0, 1, 2, 4][(prefix & 0x03) as usize];
+            let data_bytes = bytes
+                .get(i + 1..i + 1 + size)
+                .ok_or_else(|| invalid(i, "truncated item"))?;
+            let data = data_bytes
+                .iter()
+                .rev()
+                .fold(0u32, |acc, &b| (acc << 8) | b as u32);
+            let signed = sign_extend(data, size as u32 * 8);
+            let item_type = (prefix >> 2) & 0x03;
+            let tag = prefix >> 4;
+
+            match (item_type, tag) {
+                // ---- Main items ----
+                (0, 0x8) => {
+                    let bits = globals.report_size * globals.report_count;
+                    let rid = globals.report_id;
+                    let offset = out.report_bits.entry(rid).or_insert(0);
+                    if data & FLAG_CONSTANT == 0 && bits > 0 {
+                        let page = globals.usage_page;
+                        let logical_max = if globals.logical_min >= 0
+                            && globals.logical_max < globals.logical_min
+                        {
+                            // Common descriptor bug: an unsigned maximum written as a short
+                            // signed item (e.g. `25 FF` for 255).
+                            globals.logical_max_unsigned as i32
+                        } else {
+                            globals.logical_max
+                        };
+                        out.fields.push(InputField {
+                            report_id: rid,
+                            bit_offset: *offset,
+                            bit_size: globals.report_size,
+                            report_count: globals.report_count,
+                            usages: locals
+                                .usages
+                                .iter()
+                                .map(|&(p, u)| (p.unwrap_or(page), u))
+                                .collect(),
+                            logical_min: globals.logical_min,
+                            logical_max,
+                            flags: data,
+                            collection: collection_stack.last().copied().unwrap_or(0),
+                        });
+                    }
+                    *offset += bits;
+                    out.report_ids.insert(rid);
+                    locals = Locals::default();
+                }
+                (0, 0xA) => {
+                    let (page, usage) = locals
+                        .usages
+                        .first()
+                        .map_or((globals.usage_page, 0), |&(p, u)| {
+                            (p.unwrap_or(globals.usage_page), u)
+                        });
+                    let idx = out.collections.len() as u16;
+                    out.collections.push(Collection {
+                        usage_page: page,
+                        usage,
+                        parent: collection_stack.last().copied(),
+                    });
+                    collection_stack.push(idx);
+                    locals = Locals::default();
+                }
+                (0, 0xC) => {
+                    collection_stack
+                        .pop()
+                        .ok_or_else(|| invalid(i, "End Collection without Collection"))?;
+                    locals = Locals::default();
+                }
+                // Output, Feature: separate report spaces, not laid out here.
+                (0, _) => locals = Locals::default(),
+
+                // ---- Global items ----
+                (1, 0x0) => globals.usage_page = data as u16,
+                (1, 0x1) => globals.logical_min = signed,
+                (1, 0x2) => {
+                    globals.logical_max = signed;
+                    globals.logical_max_unsigned = data;
+                }
+                (1, 0x7) => globals.report_size = data,
+                (1, 0x8) => globals.report_id = data as u8,
+                (1, 0x9) => globals.report_count = data,
+                (1, 0xA) => global_stack.push(globals),
+                (1, 0xB) => {
+                    globals = global_stack
+                        .pop()
+                        .ok_or_else(|| invalid(i, "Pop without Push"))?;
+                }
+
+                // ---- Local items ----
+                (2, 0x0) => locals.usages.push(local_usage(data, size)),
+                (2, 0x1) => locals.usage_min = Some(local_usage(data, size)),
+                (2, 0x2) => {
+                    if let Some((page, min)) = locals.usage_min.take() {
+                        let (_, max) = local_usage(data, size);
+                        locals.usages.extend((min..=max).map(|u| (page, u)));
+                    }
+                }
+                _ => {}
+            }
+            i += 1 + size;
+        }
+
+        if !collection_stack.is_empty() {
+            return Err(invalid(bytes.len(), "unterminated collection"));
+        }
+        Ok(out)
+    }
+
+    /// Whether input reports are numbered (carry a report ID byte).
+    pub fn uses_report_ids(&self) -> bool {
+        self.report_ids.iter().any(|&rid| rid != 0)
+    }
+
+    /// Payload length in bytes (excluding the report ID byte) of input report `report_id`.
+    pub fn input_report_len(&self, report_id: u8) -> Option<usize> {
+        self.report_bits
+            .get(&report_id)
+            .map(|&bits| bits.div_ceil(8) as usize)
+    }
+}
+
+// --------------------- decoding ---------------------
+
+/// Read `bits` (≤ 32) little-endian bits at `offset`, or `None` if the payload is too short.
+fn read_bits(payload: &[u8], offset: u32, bits: u32) -> Option<u32> {
+    if bits == 0 || bits > 32 || (offset + bits).div_ceil(8) as usize > payload.len() {
+        return None;
+    }
+    let mut v = 0u32;
+    for i in 0..bits {
+        let bit = offset + i;
+        if (payload[(bit / 8) as usize] >> (bit % 8)) & 1 != 0 {
+            v |= 1 << i;
+        }
+    }
+    Some(v)
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ValueKind {
+    Axis(u16),
+    Hat { index: u16, degrees: bool },
+}
+
+#[derive(Clone, Debug)]
+struct ValueElement {
+    report_id: u8,
+    bit_offset: u32,
+    bit_size: u32,
+    logical_min: i32,
+    logical_max: i32,
+    usage_page: u16,
+    usage: u16,
+    collection: u16,
+    kind: ValueKind,
+    /// Sign-extend raw values (`logical_min < 0` unless overridden by a quirk).
+    signed: bool,
+    /// Reports deltas ([`InputKind::RelativeMoved`]) rather than positions.
+    relative: bool,
+}
+
+/// Jitter threshold: ~2 LSBs of the widest absolute logical range, in normalized units.
+fn axis_epsilon(values: &[ValueElement]) -> f32 {
+    let max_span = values
+        .iter()
+        .filter(|v| !v.relative)
+        .map(|v| v.logical_max.saturating_sub(v.logical_min))
+        .fold(1, i32::max);
+    2.0 * (2.0 / max_span as f32)
+}
+
+impl ValueElement {
+    fn read(&self, payload: &[u8]) -> Option<i32> {
+        let raw = read_bits(payload, self.bit_offset, self.bit_size)?;
+        Some(if self.signed {
+            sign_extend(raw, self.bit_size)
+        } else {
+            raw as i32
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+struct ButtonElement {
+    report_id: u8,
+    bit_offset: u32,
+    index: u16,
+}
+
+#[derive(Clone, Debug)]
+struct ArrayElement {
+    report_id: u8,
+    bit_offset: u32,
+    bit_size: u32,
+    count: u32,
+    logical_min: i32,
+    /// Button index per usage-list entry (`None` for usage `0`, "no event").
+    buttons: Vec<Option<u16>>,
+}
+
+/// [`ReportParser`] driven by a [`ReportDescriptor`] (see module docs).
+#[derive(Clone, Debug)]
+pub struct DescriptorParser {
+    descriptor: ReportDescriptor,
+    values: Vec<ValueElement>,
+    buttons: Vec<ButtonElement>,
+    arrays: Vec<ArrayElement>,
+    /// `(usage page, usage, collection)` by button index.
+    button_channels: Vec<(u16, u16, u16)>,
+    hat_names: Vec<String>,
+    collection_names: HashMap<u16, String>,
+    axis_epsilon: f32,
+    pressed: BTreeSet<u16>,
+    last_axis: HashMap<u16, f32>,
+    last_hat: HashMap<u16, i16>,
+    diagnostics: ReportIdDiagnostics,
+}
+
+impl DescriptorParser {
+    /// Parser for raw report descriptor bytes.
+    pub fn from_descriptor(bytes: &[u8]) -> Result<Self> {
+        Ok(Self::new(ReportDescriptor::parse(bytes)?))
+    }
+
+    /// Parser for the descriptor stored in a report capture.
+    pub fn from_capture(capture: &ReportCapture) -> Result<Self> {
+        let bytes = capture
+            .header
+            .descriptor_bytes()
+            .ok_or_else(|| Error::Other("report capture has no descriptor".into()))?;
+        Self::from_descriptor(&bytes)
+    }
+
+    /// Parser for an already parsed descriptor.
+    pub fn new(descriptor: ReportDescriptor) -> Self {
+        let mut values = Vec::new();
+        let mut buttons = Vec::new();
+        let mut arrays = Vec::new();
+        let mut button_channels = Vec::new();
+        let mut next_axis: u16 = 0;
+
+        for f in &descriptor.fields {
+            if !f.is_variable() {
+                let buttons = f
+                    .usages
+                    .iter()
+                    .map(|&(page, usage)| {
+                        (usage != 0).then(|| {
+                            button_channels.push((page, usage, f.collection));
+                            (button_channels.len() - 1) as u16
+                        })
+                    })
+                    .collect();
+                arrays.push(ArrayElement {
+                    report_id: f.report_id,
+                    bit_offset: f.bit_offset,
+                    bit_size: f.bit_size,
+                    count: f.report_count,
+                    logical_min: f.logical_min,
+                    buttons,
+                });
+                continue;
+            }
+            for i in 0..f.report_count {
+                let Some((page, usage)) = f.usage(i as usize) else {
+                    continue;
+                };
+                let bit_offset = f.bit_offset + i * f.bit_size;
+                if f.bit_size == 1 {
+                    buttons.push(ButtonElement {
+                        report_id: f.report_id,
+                        bit_offset,
+                        index: button_channels.len() as u16,
+                    });
+                    button_channels.push((page, usage, f.collection));
+                    continue;
+                }
+                let (is_hat, degrees) = classify_hat(page, usage, f.logical_min, f.logical_max);
+                let kind = if is_hat {
+                    // Indexed below, once all hats are known.
+                    ValueKind::Hat { index: 0, degrees }
+                } else {
+                    next_axis += 1;
+                    ValueKind::Axis(next_axis - 1)
+                };
+                values.push(ValueElement {
+                    report_id: f.report_id,
+                    bit_offset,
+                    bit_size: f.bit_size,
+                    logical_min: f.logical_min,
+                    logical_max: f.logical_max,
+                    usage_page: page,
+                    usage,
+                    collection: f.collection,
+                    kind,
+                    signed: f.logical_min < 0,
+                    relative: f.is_relative(),
+                });
+            }
+        }
+
+        // Hat indices: ordered by (collection, report ID, usage), as in the HIDP parser.
+        let mut hats: Vec<usize> = (0..values.len())
+            .filter(|&i| matches!(values[i].kind, ValueKind::Hat { .. }))
+            .collect();
+        hats.sort_by_key(|&i| {
+            let v = &values[i];
+            (v.collection, v.report_id, v.usage, i)
+        });
+        for (n, &i) in hats.iter().enumerate() {
+            if let ValueKind::Hat { index, .. } = &mut values[i].kind {
+                *index = n as u16;
+            }
+        }
+
+        let collection_names: HashMap<u16, String> = values
+            .iter()
+            .map(|v| v.collection)
+            .chain(button_channels.iter().map(|b| b.2))
+            .map(|lc| {
+                let known = descriptor
+                    .collections
+                    .get(lc as usize)
+                    .and_then(|c| collection_usage_name(c.usage_page, c.usage));
+                let name = known.map_or_else(|| format!("Collection {lc}"), str::to_string);
+                (lc, name)
+            })
+            .collect();
+
+        let hat_collections: BTreeSet<u16> = hats.iter().map(|&i| values[i].collection).collect();
+        let hat_names = hats
+            .iter()
+            .enumerate()
+            .map(
+                |(n, &i)| match collection_names.get(&values[i].collection) {
+                    Some(c) if hat_collections.len() > 1 => format!("Hat {} ({c})", n + 1),
+                    _ => format!("Hat {}", n + 1),
+                },
+            )
+            .collect();
+
+        let axis_epsilon = axis_epsilon(&values);
+
+        Self {
+            descriptor,
+            values,
+            buttons,
+            arrays,
+            button_channels,
+            hat_names,
+            collection_names,
+            axis_epsilon,
+            pressed: BTreeSet::new(),
+            last_axis: HashMap::new(),
+            last_hat: HashMap::new(),
+            diagnostics: ReportIdDiagnostics::default(),
+        }
+    }
+
+    /// Apply the logical range overrides of `quirks` (`logical_min`, `logical_max`, `signed`
+    /// on usage entries) to matching value fields.
+    ///
+    /// Only ranges change; names and kinds are left to the backend parsers.
+    pub fn with_range_overrides(mut self, quirks: &DeviceQuirks) -> Self {
+        for v in &mut self.values {
+            let Some(q) = quirks.usage(v.usage_page, v.usage) else {
+                continue;
+            };
+            (v.logical_min, v.logical_max, v.signed) =
+                q.logical_range(v.logical_min, v.logical_max);
+            if let ValueKind::Hat { degrees, .. } = &mut v.kind {
+                *degrees = classify_hat(v.usage_page, v.usage, v.logical_min, v.logical_max).1;
+            }
+        }
+        self.axis_epsilon = axis_epsilon(&self.values);
+        self
+    }
+
+    /// The descriptor layout this parser decodes.
+    pub fn descriptor(&self) -> &ReportDescriptor {
+        &self.descriptor
+    }
+
+    /// Forget the last decoded state, so the next report emits every channel again.
+    pub fn reset(&mut self) {
+        self.pressed.clear();
+        self.last_axis.clear();
+        self.last_hat.clear();
+    }
+}
+
+impl ReportParser for DescriptorParser {
+    fn input_report_len(&self) -> Option<usize> {
+        let max = self
+            .descriptor
+            .report_ids
+            .iter()
+            .filter_map(|&rid| self.descriptor.input_report_len(rid))
+            .max()?;
+        Some(1 + max)
+    }
+
+    fn describe(&self) -> Vec<ChannelDesc> {
+        let collection_name = |lc: u16| self.collection_names.get(&lc).cloned();
+        let mut out = Vec::new();
+        for v in &self.values {
+            if let ValueKind::Axis(idx) = v.kind {
+                out.push(ChannelDesc {
+                    kind: ChannelKind::Axis,
+                    idx,
+                    name: usage_name(v.usage_page, v.usage),
+                    logical_min: v.logical_min,
+                    logical_max: v.logical_max,
+                    usage_page: Some(v.usage_page),
+                    usage: Some(v.usage),
+                    unit: if v.relative {
+                        Some(ChannelUnit::Counts)
+                    } else {
+                        ChannelUnit::from_usage(v.usage_page, v.usage)
+                    },
+                    collection: Some(v.collection),
+                    collection_name: collection_name(v.collection),
+                });
+            }
+        }
+        let mut hats: Vec<(u16, &ValueElement)> = self
+            .values
+            .iter()
+            .filter_map(|v| match v.kind {
+                ValueKind::Hat { index, .. } => Some((index, v)),
+                ValueKind::Axis(_) => None,
+            })
+            .collect();
+        hats.sort_by_key(|&(index, _)| index);
+        for (idx, v) in hats {
+            out.push(ChannelDesc {
+                kind: ChannelKind::Hat,
+                idx,
+                name: self.hat_names.get(idx as usize).cloned(),
+                logical_min: 0,
+                logical_max: 7,
+                usage_page: Some(v.usage_page),
+                usage: Some(v.usage),
+                unit: Some(ChannelUnit::HatDirection),
+                collection: Some(v.collection),
+                collection_name: collection_name(v.collection),
+            });
+        }
+        for (idx, &(up, u, lc)) in self.button_channels.iter().enumerate() {
+            out.push(ChannelDesc {
+                kind: ChannelKind::Button,
+                idx: idx as u16,
+                name: usage_name(up, u),
+                logical_min: 0,
+                logical_max: 1,
+                usage_page: Some(up),
+                usage: Some(u),
+                unit: Some(ChannelUnit::Boolean),
+                collection: Some(lc),
+                collection_name: collection_name(lc),
+            });
+        }
+        out
+    }
+
+    fn parse(&mut self, ctx: &ParseCtx, payload: &[u8], out: &mut Vec<InputKind>) {
+        // Unnumbered reports with a non-zero leading byte: that byte is data (see the HIDP
+        // parser's `only_rid0` handling).
+        let tmp_body: Vec<u8>;
+        let (rid, body): (u8, &[u8]) = if !self.descriptor.uses_report_ids() && ctx.report_id != 0 {
+            self.diagnostics.rid0_reinterpreted += 1;
+            tmp_body = std::iter::once(ctx.report_id)
+                .chain(payload.iter().copied())
+                .collect();
+            (0, &tmp_body)
+        } else {
+            (ctx.report_id, payload)
+        };
+        if !self.descriptor.report_ids.contains(&rid) {
+            *self.diagnostics.unknown_report_ids.entry(rid).or_insert(0) += 1;
+            return;
+        }
+
+        // ----- BUTTONS -----
+        let mut state: BTreeMap<u16, bool> = BTreeMap::new();
+        for b in self.buttons.iter().filter(|b| b.report_id == rid) {
+            if let Some(raw) = read_bits(body, b.bit_offset, 1) {
+                state.insert(b.index, raw != 0);
+            }
+        }
+        for a in self.arrays.iter().filter(|a| a.report_id == rid) {
+            let slots: Option<Vec<u32>> = (0..a.count)
+                .map(|i| read_bits(body, a.bit_offset + i * a.bit_size, a.bit_size))
+                .collect();
+            let Some(slots) = slots else {
+                continue;
+            };
+            for &idx in a.buttons.iter().flatten() {
+                state.entry(idx).or_insert(false);
+            }
+            for raw in slots {
+                let i = raw as i64 - a.logical_min as i64;
+                if let Some(Some(idx)) = usize::try_from(i).ok().and_then(|i| a.buttons.get(i)) {
+                    state.insert(*idx, true);
+                }
+            }
+        }
+        for (&idx, _) in state.iter().filter(|(_, &down)| down) {
+            if self.pressed.insert(idx) {
+                out.push(InputKind::ButtonPressed { button: idx });
+            }
+        }
+        for (&idx, _) in state.iter().filter(|(_, &down)| !down) {
+            if self.pressed.remove(&idx) {
+                out.push(InputKind::ButtonReleased { button: idx });
+            }
+        }
+
+        // ----- VALUES (axes + hats) -----
+        for v in self.values.iter().filter(|v| v.report_id == rid) {
+            let Some(raw) = v.read(body) else {
+                continue;
+            };
+            match v.kind {
+                ValueKind::Axis(axis) if v.relative => {
+                    if raw != 0 {
+                        out.push(InputKind::RelativeMoved {
+                            axis,
+                            delta: raw as f32,
+                        });
+                    }
+                }
+                ValueKind::Axis(axis) => {
+                    let value = normalize_axis_value(raw, v.logical_min, v.logical_max);
+                    let last = self.last_axis.get(&axis).copied().unwrap_or(f32::NAN);
+                    if !last.is_finite() || (value - last).abs() > self.axis_epsilon {
+                        self.last_axis.insert(axis, value);
+                        out.push(InputKind::AxisMoved { axis, value });
+                    }
+                }
+                ValueKind::Hat { index, degrees } => {
+                    let slot = hat_value_to_slot(raw, v.logical_min, v.logical_max, degrees);
+                    if self.last_hat.insert(index, slot) != Some(slot) {
+                        out.push(InputKind::HatChanged {
+                            hat: index,
+                            value: slot,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    fn expects_report_id_prefix(&self) -> bool {
+        self.descriptor.uses_report_ids()
+    }
+
+    fn input_report_ids(&self) -> Vec<u8> {
+        self.descriptor.report_ids.iter().copied().collect()
+    }
+
+    fn report_id_diagnostics(&self) -> Option<ReportIdDiagnostics> {
+        Some(self.diagnostics.clone())
+    }
+}
+
+// --------------------- shared decoding helpers ---------------------
+
+/// Determine if a (usage_page, usage) is a Hat and whether it encodes degrees.
+///
+/// Returns `(is_hat, is_degrees)`. When `is_hat` is true:
+/// - `is_degrees == false` means logical slots (e.g., `0..7` or `1..8`).
+/// - `is_degrees == true` means an angular domain (e.g., `0..359`).
+pub(crate) fn classify_hat(
+    usage_page: u16,
+    usage: u16,
+    logical_min: i32,
+    logical_max: i32,
+) -> (bool, bool) {
+    // Generic Desktop page, usage 0x39 = Hat Switch
+    if usage_page == 0x01 && usage == 0x39 {
+        // Use full logical range, not just max:
+        // Treat as "slots" if the device describes exactly 8 positions (0..7 or 1..8).
+        let is_slots =
+            (logical_min == 0 && logical_max == 7) || (logical_min == 1 && logical_max == 8);
+        // Otherwise assume degrees (0..359, 0..100, etc.)
+        let is_degrees = !is_slots;
+        (true, is_degrees)
+    } else {
+        (false, false)
+    }
+}
+
+/// Normalize an integer axis value from `[lo..hi]` into `[-1.0, 1.0]` with clamping.
+pub(crate) fn normalize_axis_value(v: i32, lo: i32, hi: i32) -> f32 {
+    let lo = lo as f64;
+    let hi = hi as f64;
+    if (hi - lo).abs() < 1e-9 {
+        return 0.0;
+    }
+    let t = (v as f64 - lo) / (hi - lo); // 0..1
+    let n = t * 2.0 - 1.0; // -1..1
+    n.clamp(-1.0, 1.0) as f32
+}
+
+/// Convert a raw hat value into a standardized slot:
+/// - Returns `-1` for neutral.
+/// - Returns `0..7` for directions (Up=0, clockwise).
+/// - If `is_degrees`, maps degrees using 45° sectors; otherwise expects slots.
+pub(crate) fn hat_value_to_slot(raw: i32, lo: i32, hi: i32, is_degrees: bool) -> i16 {
+    // Unify common neutral encodings (outside logical range or special sentinels).
+    if raw < lo || raw > hi || matches!(raw, -1 | 8 | 15 | 255 | 0xFFFF) {
+        return -1;
+    }
+    if !is_degrees {
+        // slots already
+        if (0..=7).contains(&raw) {
+            return raw as i16;
+        }
+        // unknown → neutral
+        return -1;
+    }
+    // degrees → slot
+    let deg = raw as f32;
+    let mut slot = ((deg + 22.5) / 45.0).floor() as i32 % 8;
+    if slot < 0 {
+        slot += 8;
+    }
+    slot as i16
+}
+
+/// Provide friendly names for common Generic Desktop usages (X/Y/Z/Rx/Ry/Rz/etc.).
+pub(crate) fn usage_name(usage_page: u16, usage: u16) -> Option<String> {
+    // Generic Desktop
+    if usage_page == 0x01 {
+        let s = match usage {
+            0x30 => "X",
+            0x31 => "Y",
+            0x32 => "Z",
+            0x33 => "Rx",
+            0x34 => "Ry",
+            0x35 => "Rz",
+            0x36 => "Slider",
+            0x37 => "Dial",
+            0x38 => "Wheel",
+            0x39 => "Hat",
+            _ => return Some(format!("GD_{usage:#04x}")),
+        };
+        return Some(s.to_string());
+    }
+
+    // Simulation Controls
+    if usage_page == 0x02 {
+        let s = match usage {
+            0x00 => "SimAxis",
+            0xB0 => "Accelerator",
+            0xB1 => "Brake",
+            0xB2 => "Clutch",
+            0xBB => "Throttle",
+            _ => "Sim",
+        };
+        return Some(s.to_string());
+    }
+
+    // Vendor-defined
+    if (usage_page & 0xFF00) == 0xFF00 {
+        return Some("VendorAxis".into());
+    }
+
+    // Fallback
+    Some(format!("UP_{usage_page:04x}_U_{usage:04x}"))
+}
+
+/// Names for collection usages commonly found on sticks, pads, and HOTAS bases.
+pub(crate) fn collection_usage_name(usage_page: u16, usage: u16) -> Option<&'static str> {
+    match (usage_page, usage) {
+        (0x01, 0x01) => Some("Pointer"),
+        (0x01, 0x04) => Some("Joystick"),
+        (0x01, 0x05) => Some("Game Pad"),
+        (0x01, 0x08) => Some("Multi-axis Controller"),
+        (0x02, 0x01) => Some("Flight Simulation Device"),
+        (0x02, 0x04) => Some("Flight Stick"),
+        (0x02, 0x05) => Some("Cyclic Control"),
+        (0x02, 0x07) => Some("Flight Control Stick"),
+        (0x02, 0x20) => Some("Flight Yoke"),
+        (0x02, 0xBB) => Some("Throttle"),
+        (0x02, 0x02) => Some("Automobile Simulation Device"),
+        (0x02, 0xC8) => Some("Steering"),
+        _ => None,
+    }
+}
@@ -13,7 +13,11 @@
 //! StickUp reads devices. It does not create virtual devices.
 
 use crate::event::{ChannelDesc, InputKind};
-use crate::DeviceMeta;
+use crate::ffb::{EffectHandle, EffectOp, FfbEffect, FfbEncoder};
+use crate::output::OutputCommand;
+use crate::{DeviceMeta, Error, Result};
+use std::collections::BTreeMap;
+use std::path::Path;
 use std::time::Instant;
 
 /// A device identity fingerprint suitable for stable binding / persistence.
@@ -67,6 +71,77 @@ pub struct ParseCtx<'a> {
     pub fingerprint: &'a DeviceFingerprint,
 }
 
+/// Report-ID bookkeeping collected by a parser while decoding.
+///
+/// Coarse battery charge bucket.
+///
+/// Some backends (XInput) only report buckets; percentage-based backends map into them with
+/// [`BatteryLevel::from_percent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BatteryLevel {
+    Empty,
+    Low,
+    Medium,
+    Full,
+}
+
+impl BatteryLevel {
+    /// Bucket a percentage (`<5` empty, `<30` low, `<70` medium, otherwise full).
+    pub fn from_percent(percent: u8) -> Self {
+        match percent {
+            0..=4 => Self::Empty,
+            5..=29 => Self::Low,
+            30..=69 => Self::Medium,
+            _ => Self::Full,
+        }
+    }
+}
+
+/// Battery state reported by [`Device::battery`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatteryStatus {
+    /// Charge bucket.
+    pub level: BatteryLevel,
+    /// Charge in percent (`0..=100`), when the device reports more than a bucket.
+    pub percent: Option<u8>,
+    /// The battery is currently charging.
+    pub charging: bool,
+    /// The device is on external power (USB cable / wired controller).
+    pub wired: bool,
+}
+
+impl BatteryStatus {
+    /// `true` if a UI should warn: running on battery at [`BatteryLevel::Low`] or below.
+    pub fn is_low(&self) -> bool {
+        !self.wired && !self.charging && self.level <= BatteryLevel::Low
+    }
+}
+
+/// Parsers fall back to heuristics when a report does not line up with the descriptor
+/// (e.g. a single-report device whose first byte looks like an ID, or a button usage that
+/// only resolves through the report-ID-0 key). Those fallbacks keep devices working, but
+/// they also hide descriptor/stack mismatches. These counters make them visible so tooling
+/// can flag a device instead of silently relying on the fallback path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReportIdDiagnostics {
+    /// Report IDs seen at runtime that the descriptor does not declare, with hit counts.
+    pub unknown_report_ids: BTreeMap<u8, u64>,
+    /// Reports on a single-report (`only_rid0`) device whose leading byte was non-zero and
+    /// was therefore reinterpreted as payload data.
+    pub rid0_reinterpreted: u64,
+    /// Button usages that only resolved through the report-ID-0 fallback key.
+    pub fallback_lookups: u64,
+}
+
+impl ReportIdDiagnostics {
+    /// `true` when no mismatch or fallback has been observed.
+    pub fn is_clean(&self) -> bool {
+        self.unknown_report_ids.is_empty()
+            && self.rid0_reinterpreted == 0
+            && self.fallback_lookups == 0
+    }
+}
+
 /// Parser for raw HID input reports.
 ///
 /// Backends use this trait to decode OS-provided report bytes into StickUp events.
@@ -108,6 +183,55 @@ pub trait ReportParser: Send {
     fn expects_report_id_prefix(&self) -> bool {
         true
     }
+
+    /// Report-ID mismatch counters collected so far, if the parser tracks them.
+    fn report_id_diagnostics(&self) -> Option<ReportIdDiagnostics> {
+        None
+    }
+
+    /// Battery state, from the last parsed report or a feature-report query.
+    ///
+    /// Returns `None` if the device exposes no battery information.
+    fn battery(&self) -> Option<BatteryStatus> {
+        None
+    }
+
+    /// Encode a rumble output report for the given motor strengths (`0.0..=1.0`).
+    ///
+    /// Returns the full output report **including** the leading report ID byte, ready to be
+    /// written to the device, or `None` if the descriptor exposes no usable actuators.
+    fn encode_rumble(&self, _low: f32, _high: f32) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Encode an [`OutputCommand`] (lights, player LEDs, adaptive triggers) as an output report.
+    ///
+    /// Returns `None` if the device has no matching output. The returned buffer includes the
+    /// leading report ID byte.
+    fn encode_output(&self, _cmd: &OutputCommand) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Feature report IDs the backend should read once after opening the device.
+    ///
+    /// Some controllers switch report modes when a feature report is read (e.g. DualSense over
+    /// Bluetooth only sends its extended report after the calibration report was fetched).
+    fn feature_reports_on_open(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    /// Input report IDs the backend should fetch once after opening (`Get_Report` on the
+    /// control pipe) to prime the parser and the manager with the device's current state.
+    ///
+    /// Use `0` for unnumbered reports. The default fetches nothing.
+    fn input_report_ids(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    /// Force feedback encoder, if the descriptor exposes a usable PID layout.
+    fn ffb(&self) -> Option<&dyn FfbEncoder> {
+        None
+    }
 }
 
 /// A concrete input device managed by StickUp.
@@ -122,6 +246,33 @@ pub trait Device {
     /// Poll the device and return any input changes since the last poll.
     fn poll(&mut self) -> Vec<InputKind>;
 
+    /// Like [`poll`](Device::poll), but surfaces read failures.
+    ///
+    /// The default never fails. Backends that can lose their OS handle (unplug, driver reset)
+    /// override this and implement `poll` on top of it.
+    fn try_poll(&mut self) -> Result<Vec<InputKind>> {
+        Ok(self.poll())
+    }
+
+    /// Current state of the device right after it was opened, as changes from neutral.
+    ///
+    /// The [`Manager`](crate::manager::Manager) calls this once per opened device and seeds
+    /// its state with the result (no events are emitted), so controls resting off-center
+    /// (throttles, pedals, a held button) read correctly before they first move. Later polls
+    /// report changes relative to this state. The default reports nothing.
+    fn initial_state(&mut self) -> Vec<InputKind> {
+        Vec::new()
+    }
+
+    /// Whether the physical device is currently attached.
+    ///
+    /// Backends whose devices can come and go without the entry being removed (XInput slots)
+    /// override this; the [`Manager`](crate::manager::Manager) reports transitions as
+    /// [`ManagerEvent`](crate::manager::ManagerEvent)s. Defaults to `true`.
+    fn is_connected(&self) -> bool {
+        true
+    }
+
     /// Human-friendly device name for UI display.
     fn name(&self) -> &str;
 
@@ -137,4 +288,69 @@ pub trait Device {
     ///
     /// Indices must match indices used in [`InputKind`] emitted by [`poll`](Device::poll).
     fn describe(&self) -> Vec<ChannelDesc>;
+
+    /// Report-ID diagnostics for report-based devices (see [`ReportIdDiagnostics`]).
+    ///
+    /// Returns `None` for devices that do not decode HID reports (e.g. XInput).
+    fn report_id_diagnostics(&self) -> Option<ReportIdDiagnostics> {
+        None
+    }
+
+    /// Battery level and charging state, for wireless devices that report it.
+    ///
+    /// Returns `None` for wired-only devices and devices without battery reporting.
+    fn battery(&self) -> Option<BatteryStatus> {
+        None
+    }
+
+    /// Player index (0-based) fixed by the hardware/OS, e.g. the XInput slot shown on an
+    /// Xbox controller's ring light. `None` if the device has no fixed index.
+    fn fixed_player_index(&self) -> Option<u8> {
+        None
+    }
+
+    /// Capture time of the earliest report decoded by the last [`poll`](Device::poll) (the
+    /// [`ParseCtx::now`] passed to the parser). `None` if the device does not track it or the
+    /// last poll read nothing; callers then use their own clock.
+    fn report_time(&self) -> Option<Instant> {
+        None
+    }
+
+    /// Drive the device's rumble motors.
+    ///
+    /// - `low`: low-frequency (heavy, usually left) motor strength in `0.0..=1.0`
+    /// - `high`: high-frequency (light, usually right) motor strength in `0.0..=1.0`
+    ///
+    /// Values outside the range are clamped. `set_rumble(0.0, 0.0)` stops vibration.
+    /// Devices without actuators return [`Error::Unsupported`].
+    fn set_rumble(&mut self, _low: f32, _high: f32) -> Result<()> {
+        Err(Error::Unsupported("rumble"))
+    }
+
+    /// Send a non-rumble output command (lights, player LEDs, adaptive triggers).
+    ///
+    /// Devices without the requested output return [`Error::Unsupported`].
+    fn send_output(&mut self, _cmd: &OutputCommand) -> Result<()> {
+        Err(Error::Unsupported("output command"))
+    }
+
+    /// Upload a force feedback effect and return the device-assigned handle.
+    ///
+    /// Devices without HID PID support return [`Error::Unsupported`].
+    fn upload_effect(&mut self, _effect: &FfbEffect) -> Result<EffectHandle> {
+        Err(Error::Unsupported("force feedback"))
+    }
+
+    /// Start/stop a previously uploaded effect.
+    fn play_effect(&mut self, _handle: EffectHandle, _op: EffectOp) -> Result<()> {
+        Err(Error::Unsupported("force feedback"))
+    }
+
+    /// Start (`Some(path)`) or stop (`None`) writing raw input reports to a capture file
+    /// (see [`report_capture`](crate::report_capture)).
+    ///
+    /// Devices that do not read HID reports return [`Error::Unsupported`].
+    fn capture_reports(&mut self, _path: Option<&Path>) -> Result<()> {
+        Err(Error::Unsupported("raw report capture"))
+    }
 }
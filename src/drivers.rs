@@ -0,0 +1,310 @@
+//! Detection of third-party input drivers.
+//!
+//! StickUp itself never installs or talks to kernel drivers, but host apps that pair it with
+//! output/hiding drivers need to know whether those are usable before offering features:
+//! - **vJoy** — virtual joystick driver
+//! - **ViGEmBus** — virtual Xbox 360 / DualShock 4 bus
+//! - **HidHide** — hides physical devices from other applications
+//!
+//! [`driver_status`] answers "installed? version? running? accessible?" without elevation
+//! (service status query + driver file version), and [`DriverStatus::check`] turns a missing
+//! piece into a typed [`Error::Driver`] whose [`DriverProblem::guidance`] is a short,
+//! user-facing setup hint.
+//!
+//! ```no_run
+//! use stickup::drivers::{driver_status, Driver};
+//!
+//! let status = driver_status(Driver::HidHide);
+//! if let Err(stickup::Error::Driver { problem, .. }) = status.check() {
+//!     println!("HidHide unavailable: {}", problem.guidance(Driver::HidHide));
+//! }
+//! ```
+//!
+//! On non-Windows platforms every driver reports as not installed.
+
+use core::fmt;
+
+use crate::{Error, Result};
+
+/// Supported third-party drivers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Driver {
+    /// vJoy virtual joystick.
+    VJoy,
+    /// ViGEmBus virtual gamepad bus.
+    ViGEmBus,
+    /// HidHide device hiding filter.
+    HidHide,
+}
+
+impl Driver {
+    /// All known drivers.
+    pub const ALL: [Driver; 3] = [Driver::VJoy, Driver::ViGEmBus, Driver::HidHide];
+
+    /// Kernel service name (also the driver file stem).
+    pub fn service_name(self) -> &'static str {
+        match self {
+            Driver::VJoy => "vjoy",
+            Driver::ViGEmBus => "ViGEmBus",
+            Driver::HidHide => "HidHide",
+        }
+    }
+
+    /// Project page for downloads/installers.
+    pub fn homepage(self) -> &'static str {
+        match self {
+            Driver::VJoy => "https://github.com/jshafer817/vJoy",
+            Driver::ViGEmBus => "https://github.com/nefarius/ViGEmBus",
+            Driver::HidHide => "https://github.com/nefarius/HidHide",
+        }
+    }
+}
+
+impl fmt::Display for Driver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Driver::VJoy => "vJoy",
+            Driver::ViGEmBus => "ViGEmBus",
+            Driver::HidHide => "HidHide",
+        })
+    }
+}
+
+/// Why a driver is not usable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DriverProblem {
+    /// The driver service is not registered.
+    NotInstalled,
+    /// Installed, but the service is stopped (often: reboot pending after install).
+    NotRunning,
+    /// Running, but this process cannot open its control device.
+    Inaccessible,
+}
+
+impl DriverProblem {
+    /// Short setup hint suitable for showing to end users.
+    pub fn guidance(self, driver: Driver) -> String {
+        match self {
+            DriverProblem::NotInstalled => {
+                format!(
+                    "{driver} is not installed. Install it from {}.",
+                    driver.homepage()
+                )
+            }
+            DriverProblem::NotRunning => format!(
+                "{driver} is installed but not running. Restart Windows to finish the installation."
+            ),
+            DriverProblem::Inaccessible => format!(
+                "{driver} is running but cannot be opened by this application. Check that no other \
+                 tool holds it exclusively, or run the application with the required permissions."
+            ),
+        }
+    }
+}
+
+impl fmt::Display for DriverProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DriverProblem::NotInstalled => "not installed",
+            DriverProblem::NotRunning => "not running",
+            DriverProblem::Inaccessible => "not accessible",
+        })
+    }
+}
+
+/// Detected state of a driver.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DriverStatus {
+    pub driver: Driver,
+    /// Service is registered.
+    pub installed: bool,
+    /// Service is running.
+    pub running: bool,
+    /// Driver file version (`major.minor.build.revision`), if readable.
+    pub version: Option<String>,
+    /// The driver's control device can be opened by this process.
+    ///
+    /// Only HidHide exposes a fixed control device; for the others this mirrors `running`.
+    pub accessible: bool,
+}
+
+impl DriverStatus {
+    /// First problem preventing use of the driver, if any.
+    pub fn problem(&self) -> Option<DriverProblem> {
+        if !self.installed {
+            Some(DriverProblem::NotInstalled)
+        } else if !self.running {
+            Some(DriverProblem::NotRunning)
+        } else if !self.accessible {
+            Some(DriverProblem::Inaccessible)
+        } else {
+            None
+        }
+    }
+
+    /// `Ok` if the driver is usable, otherwise [`Error::Driver`].
+    pub fn check(&self) -> Result<()> {
+        match self.problem() {
+            None => Ok(()),
+            Some(problem) => Err(Error::Driver {
+                driver: self.driver,
+                problem,
+            }),
+        }
+    }
+}
+
+/// Detect a driver's state (no elevation required).
+pub fn driver_status(driver: Driver) -> DriverStatus {
+    #[cfg(target_os = "windows")]
+    {
+        windows::status(driver)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        DriverStatus {
+            driver,
+            installed: false,
+            running: false,
+            version: None,
+            accessible: false,
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use core::ffi::c_void;
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+
+    use windows_sys::Win32::Foundation::{
+        CloseHandle, GENERIC_READ, GENERIC_WRITE, INVALID_HANDLE_VALUE,
+    };
+    use windows_sys::Win32::Storage::FileSystem::{
+        CreateFileW, GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW,
+        FILE_ATTRIBUTE_NORMAL, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING, VS_FIXEDFILEINFO,
+    };
+    use windows_sys::Win32::System::Services::{
+        CloseServiceHandle, OpenSCManagerW, OpenServiceW, QueryServiceStatus, SERVICE_STATUS,
+    };
+
+    use super::{Driver, DriverStatus};
+
+    // Local constants (avoid relying on module exports that vary by windows-sys version)
+    const SC_MANAGER_CONNECT: u32 = 0x0001;
+    const SERVICE_QUERY_STATUS: u32 = 0x0004;
+    const SERVICE_RUNNING: u32 = 0x0000_0004;
+
+    /// HidHide control device.
+    const HIDHIDE_CONTROL_DEVICE: &str = r"\\.\HidHide";
+
+    fn wide(s: &str) -> Vec<u16> {
+        OsStr::new(s)
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect()
+    }
+
+    pub(super) fn status(driver: Driver) -> DriverStatus {
+        let service = query_service(driver.service_name());
+        let installed = service.is_some();
+        let running = service == Some(SERVICE_RUNNING);
+        let accessible = running
+            && match driver {
+                Driver::HidHide => can_open(HIDHIDE_CONTROL_DEVICE),
+                Driver::VJoy | Driver::ViGEmBus => true,
+            };
+        DriverStatus {
+            driver,
+            installed,
+            running,
+            version: installed
+                .then(|| file_version(driver.service_name()))
+                .flatten(),
+            accessible,
+        }
+    }
+
+    /// Current state (`SERVICE_*`) of a service, or `None` if it does not exist.
+    fn query_service(name: &str) -> Option<u32> {
+        unsafe {
+            let scm = OpenSCManagerW(std::ptr::null(), std::ptr::null(), SC_MANAGER_CONNECT);
+            if scm.is_null() {
+                return None;
+            }
+            let name = wide(name);
+            let svc = OpenServiceW(scm, name.as_ptr(), SERVICE_QUERY_STATUS);
+            let state = if svc.is_null() {
+                None
+            } else {
+                let mut st: SERVICE_STATUS = core::mem::zeroed();
+                let ok = QueryServiceStatus(svc, &mut st);
+                CloseServiceHandle(svc);
+                // The service exists even if its status cannot be read.
+                Some(if ok != 0 { st.dwCurrentState } else { 0 })
+            };
+            CloseServiceHandle(scm);
+            state
+        }
+    }
+
+    /// Version resource of `%SystemRoot%\System32\drivers\<stem>.sys`.
+    fn file_version(stem: &str) -> Option<String> {
+        let root = std::env::var("SystemRoot").unwrap_or_else(|_| r"C:\Windows".into());
+        let path = wide(&format!(r"{root}\System32\drivers\{stem}.sys"));
+        unsafe {
+            let mut ignored = 0u32;
+            let size = GetFileVersionInfoSizeW(path.as_ptr(), &mut ignored);
+            if size == 0 {
+                return None;
+            }
+            let mut buf = vec![0u8; size as usize];
+            if GetFileVersionInfoW(path.as_ptr(), 0, size, buf.as_mut_ptr() as *mut c_void) == 0 {
+                return None;
+            }
+            let root_block = wide("\\");
+            let mut info: *mut c_void = std::ptr::null_mut();
+            let mut len = 0u32;
+            if VerQueryValueW(
+                buf.as_ptr() as *const c_void,
+                root_block.as_ptr(),
+                &mut info,
+                &mut len,
+            ) == 0
+                || info.is_null()
+                || (len as usize) < core::mem::size_of::<VS_FIXEDFILEINFO>()
+            {
+                return None;
+            }
+            let fi = core::ptr::read_unaligned(info as *const VS_FIXEDFILEINFO);
+            Some(format!(
+                "{}.{}.{}.{}",
+                fi.dwFileVersionMS >> 16,
+                fi.dwFileVersionMS & 0xFFFF,
+                fi.dwFileVersionLS >> 16,
+                fi.dwFileVersionLS & 0xFFFF
+            ))
+        }
+    }
+
+    fn can_open(path: &str) -> bool {
+        let path = wide(path);
+        let h = unsafe {
+            CreateFileW(
+                path.as_ptr(),
+                GENERIC_READ | GENERIC_WRITE,
+                FILE_SHARE_READ | FILE_SHARE_WRITE,
+                std::ptr::null(),
+                OPEN_EXISTING,
+                FILE_ATTRIBUTE_NORMAL,
+                std::ptr::null_mut(),
+            )
+        };
+        if h == INVALID_HANDLE_VALUE {
+            return false;
+        }
+        unsafe { CloseHandle(h) };
+        true
+    }
+}
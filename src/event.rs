@@ -7,24 +7,28 @@
 //! - **HID / XInput axes:** by convention are normalized to `[-1.0, 1.0]`.
 //! - **Buttons:** boolean state expressed as press/release edges.
 //! - **Hats (POV/D-pad):** `-1` = neutral, `0..7` = 8-way directions (Up = 0, clockwise).
+//! - **Relative channels (mouse motion, wheels, dials):** [`InputKind::RelativeMoved`] carries
+//!   a delta in device units (counts, wheel notches), never a position.
 //!
 //! ### Important: units may vary by source
-//! `InputKind::AxisMoved` is used for multiple input sources:
-//! - For typical controller axes it is normalized (`[-1, 1]`).
-//! - For some injected sources (e.g. Windows Raw Input mouse deltas / wheel ticks),
-//!   the value may be **raw counts** or **tick units** rather than normalized.
-//!
-//! If you need strict normalization, apply it at the application layer (or introduce a
-//! dedicated mouse event type in a future release).
-//!
-//! This crate currently follows the “raw truth” approach for injected mouse input:
-//! it preserves the units reported by the OS. A later version may add dedicated mouse
-//! event variants for clearer semantics.
+//! `InputKind::AxisMoved` is normalized (`[-1, 1]`) for typical controller axes, but some
+//! injected sources may use raw units. Relative motion has its own variant so it is not forced
+//! into absolute axis semantics: the [`Manager`](crate::manager::Manager) sums the deltas of
+//! each poll into [`DeviceState::relative`](crate::binding::DeviceState::relative) instead of
+//! overwriting an axis value.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
 
 /// Per-device input change (delta).
 ///
 /// The `axis`/`button`/`hat` indices are device-local channel indices as described by [`ChannelDesc`].
-#[derive(Clone, Debug)]
+///
+/// Serializes internally tagged, e.g. `{"type":"axis_moved","axis":0,"value":0.5}`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum InputKind {
     /// A continuous channel changed.
     ///
@@ -32,31 +36,217 @@ pub enum InputKind {
     /// Some injected sources may use raw units (see module docs).
     AxisMoved { axis: u16, value: f32 },
 
+    /// A relative channel (mouse motion, wheel, HID dial) moved by `delta`.
+    ///
+    /// `axis` indexes the device's axis channels (described with
+    /// [`ChannelUnit::Counts`]); `delta` is in device units since the previous report.
+    RelativeMoved { axis: u16, delta: f32 },
+
     /// A button transitioned to pressed.
     ButtonPressed { button: u16 },
 
     /// A button transitioned to released.
     ButtonReleased { button: u16 },
 
+    /// A keyboard key went down.
+    ///
+    /// Identity is the hardware scancode plus the extended (`E0`) flag (see [`keys`](crate::keys)).
+    /// In descriptors and state the key is the button [`key_index`](crate::keys::key_index).
+    KeyPressed { scancode: u16, extended: bool },
+
+    /// A keyboard key went up.
+    KeyReleased { scancode: u16, extended: bool },
+
     /// A hat (POV/D-pad) changed.
     ///
     /// `value`: `-1` = neutral, `0..7` = directions (Up = 0, clockwise).
     HatChanged { hat: u16, value: i16 },
+
+    /// A touch point on a touch surface (e.g. a gamepad touchpad) changed.
+    ///
+    /// `x`/`y` are normalized to `[0.0, 1.0]` (origin top-left). When `active` is `false`
+    /// the finger was lifted and `x`/`y` hold its last position.
+    TouchMoved {
+        surface: u16,
+        finger: u8,
+        active: bool,
+        x: f32,
+        y: f32,
+    },
+
+    /// A motion sensor (IMU) produced a new sample.
+    ///
+    /// `gyro` is angular rate in degrees/second, `accel` is acceleration in g (both `[x, y, z]`).
+    MotionChanged {
+        sensor: u16,
+        gyro: [f32; 3],
+        accel: [f32; 3],
+    },
+
+    /// Battery level reported in-band by the device changed.
+    BatteryChanged { percent: u8, charging: bool },
+
+    /// Application-defined timeline marker (`"lap 2 start"`, `"bug here"`).
+    ///
+    /// Never produced by devices; added with
+    /// [`Manager::add_marker`](crate::manager::Manager::add_marker) and delivered in order
+    /// with device events under the [`MARKER_SOURCE`](crate::manager::MARKER_SOURCE) id.
+    Marker { label: Arc<str> },
 }
 
-/// Timestamped input event captured by the Manager.
+/// Compact handle for a device, assigned by the [`Manager`](crate::manager::Manager) when it
+/// first sees the device's string id ([`Device::id`](crate::device::Device::id)).
 ///
-/// This is a lightweight wrapper over [`InputKind`] with a monotonic timestamp.
+/// Handles are cheap to copy, hash, and compare, and stay the same for a given string id for
+/// the lifetime of the Manager, including across rescans and reconnects. They are **not**
+/// persistent: store the string id (profiles, config files) and map it with
+/// [`DeviceIds::id_of`] / [`Manager::id_of`](crate::manager::Manager::id_of).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DeviceId(u32);
+
+impl DeviceId {
+    /// Placeholder for events that did not come from a Manager (e.g. deserialized ones).
+    pub const UNKNOWN: DeviceId = DeviceId(u32::MAX);
+
+    /// Position in the owning [`DeviceIds`] table.
+    #[inline]
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl Default for DeviceId {
+    fn default() -> Self {
+        Self::UNKNOWN
+    }
+}
+
+impl std::fmt::Display for DeviceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Interning table mapping device string ids to [`DeviceId`]s and back.
+///
+/// Entries are never removed, so a handle stays valid after its device goes away.
 #[derive(Clone, Debug)]
+pub struct DeviceIds {
+    keys: Vec<Arc<str>>,
+    by_key: std::collections::HashMap<Arc<str>, DeviceId>,
+    /// Identifies the table this one was created as; clones keep it (they only ever grow
+    /// from the same entries).
+    lineage: u64,
+}
+
+impl Default for DeviceIds {
+    fn default() -> Self {
+        static NEXT_LINEAGE: AtomicU64 = AtomicU64::new(0);
+        Self {
+            keys: Vec::new(),
+            by_key: std::collections::HashMap::new(),
+            lineage: NEXT_LINEAGE.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
+
+impl DeviceIds {
+    /// Empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `(lineage, len)`: equal values mean handles resolved against one table are valid in
+    /// the other (same origin, same entries).
+    pub(crate) fn version(&self) -> (u64, usize) {
+        (self.lineage, self.keys.len())
+    }
+
+    /// Handle for `key`, assigning the next one if it is new.
+    pub fn intern(&mut self, key: &str) -> DeviceId {
+        if let Some(&id) = self.by_key.get(key) {
+            return id;
+        }
+        let id = DeviceId(self.keys.len() as u32);
+        let key: Arc<str> = Arc::from(key);
+        self.keys.push(key.clone());
+        self.by_key.insert(key, id);
+        id
+    }
+
+    /// Handle of `key`, if it was interned.
+    #[inline]
+    pub fn id_of(&self, key: &str) -> Option<DeviceId> {
+        self.by_key.get(key).copied()
+    }
+
+    /// String id behind `id`.
+    #[inline]
+    pub fn key_of(&self, id: DeviceId) -> Option<&str> {
+        self.keys.get(id.index()).map(|k| &**k)
+    }
+
+    /// String id behind `id`, shared (cloning it does not allocate).
+    #[inline]
+    pub fn shared_key(&self, id: DeviceId) -> Option<&Arc<str>> {
+        self.keys.get(id.index())
+    }
+
+    /// Number of interned ids.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// No ids interned yet?
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// All `(handle, string id)` pairs in assignment order.
+    pub fn iter(&self) -> impl Iterator<Item = (DeviceId, &str)> {
+        self.keys
+            .iter()
+            .enumerate()
+            .map(|(i, k)| (DeviceId(i as u32), &**k))
+    }
+}
+
+/// Timestamped input event captured by the Manager.
+///
+/// This is a lightweight wrapper over [`InputKind`] with a monotonic timestamp and a
+/// sequence number.
+///
+/// Serializes as `timestamp`, `seq` and the flattened [`InputKind`]; [`at`](Self::at) and
+/// [`device`](Self::device) are process-local and not serialized (deserialized events get the
+/// current time and [`DeviceId::UNKNOWN`]).
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InputEvent {
     /// Capture time (monotonic). Suitable for ordering / delta timing within a run.
+    ///
+    /// For report-based devices this is when the report was read
+    /// ([`Device::report_time`](crate::device::Device::report_time)), otherwise when the
+    /// Manager polled the device; `at.elapsed()` approximates input latency.
+    #[serde(skip, default = "std::time::Instant::now")]
     pub at: std::time::Instant,
+    /// [`at`](Self::at) relative to the Manager's creation (portable across logs).
+    pub timestamp: std::time::Duration,
+    /// Manager-wide sequence number, increasing by one per event. Breaks ties between
+    /// events sharing a timestamp.
+    pub seq: u64,
+    /// Handle of the device the event came from ([`DeviceId::UNKNOWN`] once deserialized;
+    /// handles are process-local).
+    #[serde(skip)]
+    pub device: DeviceId,
     /// The actual input change.
+    #[serde(flatten)]
     pub kind: InputKind,
 }
 
 /// Category of an input channel on a device.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ChannelKind {
     Axis,
     Button,
@@ -67,7 +257,7 @@ pub enum ChannelKind {
 ///
 /// Backends typically populate this from device descriptors (HIDP, XInput layout, etc.)
 /// so UIs and binding systems can present stable channel names and ranges.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ChannelDesc {
     /// Channel category.
     pub kind: ChannelKind,
@@ -83,4 +273,108 @@ pub struct ChannelDesc {
     pub usage_page: Option<u16>,
     /// Optional HID usage (when available).
     pub usage: Option<u16>,
+    /// What the value measures (when known), for labels and default transforms.
+    pub unit: Option<ChannelUnit>,
+    /// HID link collection the channel belongs to (when available).
+    ///
+    /// Channels sharing a collection form a physical/logical group on the device
+    /// (a grip, a throttle base, a trim cluster). `0` is the top-level collection.
+    pub collection: Option<u16>,
+    /// Display name for [`collection`](Self::collection), e.g. `"Throttle"` or `"Collection 3"`.
+    pub collection_name: Option<String>,
+}
+
+/// Physical meaning of a channel's value.
+///
+/// Values are still reported normalized (axes `[-1, 1]`, buttons on/off, hats `-1..=7`);
+/// the unit says what the range stands for, e.g. so a plot can be labelled "°" or a pedal
+/// can default to a `0..1` response curve. Filled by backends from usage knowledge and
+/// overridable per usage in [`quirks`](crate::quirks) (`unit = "degrees"`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelUnit {
+    /// On/off control.
+    Boolean,
+    /// 8-way direction (hat switch / D-pad).
+    HatDirection,
+    /// Travel of a one-sided control (trigger, pedal, throttle), `-1` = 0 %, `1` = 100 %.
+    Percent,
+    /// Rotation angle (steering wheel, rotary knob) across the logical range.
+    Degrees,
+    /// Angular rate (gyro, rotational velocity), degrees per second.
+    DegreesPerSecond,
+    /// Relative motion counts (mouse movement, wheel detents).
+    Counts,
+}
+
+impl ChannelUnit {
+    /// Default unit for a channel kind: buttons are [`Boolean`](Self::Boolean), hats
+    /// [`HatDirection`](Self::HatDirection), axes unknown.
+    pub fn for_kind(kind: ChannelKind) -> Option<Self> {
+        match kind {
+            ChannelKind::Axis => None,
+            ChannelKind::Button => Some(Self::Boolean),
+            ChannelKind::Hat => Some(Self::HatDirection),
+        }
+    }
+
+    /// Unit implied by a HID usage, if it has one.
+    pub fn from_usage(usage_page: u16, usage: u16) -> Option<Self> {
+        match (usage_page, usage) {
+            // Button page
+            (0x09, _) => Some(Self::Boolean),
+            // Generic Desktop: Hat Switch
+            (0x01, 0x39) => Some(Self::HatDirection),
+            // Generic Desktop: Vbrx / Vbry / Vbrz (body-relative angular velocity)
+            (0x01, 0x43..=0x45) => Some(Self::DegreesPerSecond),
+            // Simulation Controls: Throttle, Accelerator, Brake, Clutch
+            (0x02, 0xBB | 0xC4 | 0xC5 | 0xC6) => Some(Self::Percent),
+            // Simulation Controls: Steering, Rudder
+            (0x02, 0xC8 | 0xBA) => Some(Self::Degrees),
+            _ => None,
+        }
+    }
+}
+
+impl ChannelDesc {
+    /// Key of this channel in [`DeviceState`](crate::binding::DeviceState) and binding rules:
+    /// `name`, or `"axis{idx}"` / `"btn{idx}"` / `"hat{idx}"` when unnamed.
+    pub fn state_name(&self) -> String {
+        match (&self.name, self.kind) {
+            (Some(name), _) => name.clone(),
+            (None, ChannelKind::Axis) => format!("axis{}", self.idx),
+            (None, ChannelKind::Button) => format!("btn{}", self.idx),
+            (None, ChannelKind::Hat) => format!("hat{}", self.idx),
+        }
+    }
+}
+
+/// Channels of one device that share a link collection (see [`group_by_collection`]).
+#[derive(Clone, Debug)]
+pub struct ChannelGroup<'a> {
+    /// Link collection id (`None` for backends without collection info).
+    pub collection: Option<u16>,
+    /// Collection display name, if the backend provided one.
+    pub name: Option<&'a str>,
+    /// Channels in descriptor order.
+    pub channels: Vec<&'a ChannelDesc>,
+}
+
+/// Group channel descriptors by link collection, ordered by collection id.
+///
+/// Channels without collection info end up in a single leading group with `collection: None`.
+pub fn group_by_collection(descs: &[ChannelDesc]) -> Vec<ChannelGroup<'_>> {
+    let mut groups: Vec<ChannelGroup<'_>> = Vec::new();
+    for d in descs {
+        match groups.iter_mut().find(|g| g.collection == d.collection) {
+            Some(g) => g.channels.push(d),
+            None => groups.push(ChannelGroup {
+                collection: d.collection,
+                name: d.collection_name.as_deref(),
+                channels: vec![d],
+            }),
+        }
+    }
+    groups.sort_by_key(|g| g.collection);
+    groups
 }
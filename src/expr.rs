@@ -0,0 +1,588 @@
+//! Expression mini-language for derived channels and binding transforms.
+//!
+//! Profiles and gate channels use small expressions instead of compiled code:
+//!
+//! ```text
+//! clamp(x * 1.5, -1, 1)                      // binding transform: stronger response
+//! max(LT, RT) > 0.9 && !btn4                  // gate: either trigger floored, not shifted
+//! smooth("Throttle", 0.1) - "Throttle"        // derived axis: how far ahead of the filter
+//! ```
+//!
+//! Syntax:
+//! - numbers (`0.9`, `1e-3`), names (`X`, `axis3`, `btn5`; `buttonN` is accepted for `btnN`),
+//!   and names with spaces or symbols in double quotes (`"X velocity"`);
+//! - `+ - * /`, unary `-`, comparisons `< <= > >= == !=`, logic `&& || !`, parentheses;
+//! - functions:
+//!   - `min(a, b, ...)`, `max(a, b, ...)`, `abs(x)`, `sign(x)`, `sqrt(x)`, `pow(x, y)`;
+//!   - `clamp(x, lo, hi)`, `deadzone(x, d)` (zero inside `±d`, rescaled outside);
+//!   - `if(cond, a, b)`;
+//!   - `smooth(x, tau)`: exponential smoothing with time constant `tau` seconds;
+//!   - `slew(x, rate)`: follows `x` at most `rate` units per second.
+//!
+//! A `NaN` bound (a `clamp` limit or the `slew` rate, e.g. `pow(y, 0.5)` with negative `y`)
+//! is ignored: `clamp` keeps only the other limit and `slew` passes its input through.
+//!
+//! Comparisons and logic produce `1`/`0`; a value counts as true above `0.5`. What names
+//! refer to depends on the caller: device channels for [gates](crate::gate), the input `x`
+//! for [`AxisTransform::expr`](crate::binding::AxisTransform::expr). Unknown names read
+//! as `0`.
+//!
+//! `smooth` and `slew` keep state between evaluations in an [`ExprState`] and use the time
+//! since the previous evaluation. Evaluated without history
+//! ([`eval_once`](Expr::eval_once)) they pass their input through.
+//!
+//! ```
+//! use stickup::expr::Expr;
+//!
+//! let e = Expr::parse("clamp(x * 2, -1, 1)").unwrap();
+//! assert_eq!(e.eval_once(|name| (name == "x").then_some(0.3)), 0.6);
+//! ```
+
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Error, Result};
+
+/// Binary operators.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    And,
+    Or,
+}
+
+/// Built-in functions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Func {
+    Min,
+    Max,
+    Abs,
+    Sign,
+    Sqrt,
+    Pow,
+    Clamp,
+    Deadzone,
+    If,
+    Smooth,
+    Slew,
+}
+
+impl Func {
+    fn lookup(name: &str) -> Option<Self> {
+        Some(match name {
+            "min" => Self::Min,
+            "max" => Self::Max,
+            "abs" => Self::Abs,
+            "sign" => Self::Sign,
+            "sqrt" => Self::Sqrt,
+            "pow" => Self::Pow,
+            "clamp" => Self::Clamp,
+            "deadzone" => Self::Deadzone,
+            "if" => Self::If,
+            "smooth" => Self::Smooth,
+            "slew" => Self::Slew,
+            _ => return None,
+        })
+    }
+
+    /// Allowed argument counts (`max = None`: unbounded).
+    fn arity(self) -> (usize, Option<usize>) {
+        match self {
+            Self::Min | Self::Max => (1, None),
+            Self::Abs | Self::Sign | Self::Sqrt => (1, Some(1)),
+            Self::Pow | Self::Deadzone | Self::Smooth | Self::Slew => (2, Some(2)),
+            Self::Clamp | Self::If => (3, Some(3)),
+        }
+    }
+
+    fn is_stateful(self) -> bool {
+        matches!(self, Self::Smooth | Self::Slew)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Num(f32),
+    Var(String),
+    Neg(Box<Node>),
+    Not(Box<Node>),
+    Bin(Box<Node>, BinOp, Box<Node>),
+    /// Function call; `slot` indexes [`ExprState`] for stateful functions.
+    Call {
+        func: Func,
+        args: Vec<Node>,
+        slot: usize,
+    },
+}
+
+/// A parsed expression (see module docs). Serializes as its source text.
+#[derive(Clone, PartialEq)]
+pub struct Expr {
+    source: String,
+    root: Node,
+    slots: usize,
+}
+
+impl fmt::Debug for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Expr").field(&self.source).finish()
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl Serialize for Expr {
+    fn serialize<S: Serializer>(&self, s: S) -> std::result::Result<S::Ok, S::Error> {
+        s.serialize_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for Expr {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> std::result::Result<Self, D::Error> {
+        let source = String::deserialize(d)?;
+        Expr::parse(&source).map_err(serde::de::Error::custom)
+    }
+}
+
+/// History of an expression's stateful functions (`smooth`, `slew`) between evaluations.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExprState {
+    /// Previous output per stateful call (`NaN` = not evaluated yet).
+    slots: Vec<f32>,
+}
+
+impl ExprState {
+    /// Forget the history (the next evaluation starts fresh).
+    pub fn reset(&mut self) {
+        self.slots.fill(f32::NAN);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Num(f32),
+    Name(String),
+    /// Quoted name (never a function).
+    Quoted(String),
+    Op(&'static str),
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>> {
+    const OPS: [&str; 17] = [
+        "&&", "||", "<=", ">=", "==", "!=", "<", ">", "!", "(", ")", ",", "+", "-", "*", "/", "=",
+    ];
+    let err = |msg: String| Error::Other(format!("invalid expression {src:?}: {msg}"));
+    let mut out = Vec::new();
+    let mut rest = src.trim_start();
+    while let Some(c) = rest.chars().next() {
+        if c.is_ascii_digit() || c == '.' {
+            let mut len = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            // Exponent: 1e-3, 2E5.
+            if rest[len..].starts_with(['e', 'E']) {
+                let exp = &rest[len + 1..];
+                let sign = usize::from(exp.starts_with(['+', '-']));
+                let digits = exp[sign..]
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(exp.len() - sign);
+                if digits > 0 {
+                    len += 1 + sign + digits;
+                }
+            }
+            let n = rest[..len]
+                .parse()
+                .map_err(|_| err(format!("bad number {:?}", &rest[..len])))?;
+            out.push(Token::Num(n));
+            rest = &rest[len..];
+        } else if c == '"' {
+            let end = rest[1..]
+                .find('"')
+                .ok_or_else(|| err("unterminated quoted name".into()))?;
+            out.push(Token::Quoted(rest[1..1 + end].to_string()));
+            rest = &rest[end + 2..];
+        } else if c.is_alphanumeric() || c == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            out.push(Token::Name(rest[..len].to_string()));
+            rest = &rest[len..];
+        } else if let Some(op) = OPS.iter().find(|op| rest.starts_with(**op)) {
+            if *op == "=" {
+                return Err(err("`=` is not an operator (use `==`)".into()));
+            }
+            out.push(Token::Op(op));
+            rest = &rest[op.len()..];
+        } else {
+            return Err(err(format!("unexpected {c:?}")));
+        }
+        rest = rest.trim_start();
+    }
+    Ok(out)
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+    slots: usize,
+}
+
+impl Parser<'_> {
+    fn err(&self, msg: &str) -> Error {
+        Error::Other(format!("invalid expression {:?}: {msg}", self.src))
+    }
+
+    fn peek_op(&self) -> Option<&'static str> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) => Some(op),
+            _ => None,
+        }
+    }
+
+    fn eat(&mut self, op: &'static str) -> bool {
+        if self.peek_op() == Some(op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Left-associative binary level over `next`, with `ops` mapping tokens to operators.
+    fn binary(
+        &mut self,
+        ops: &[(&str, BinOp)],
+        next: fn(&mut Self) -> Result<Node>,
+    ) -> Result<Node> {
+        let mut lhs = next(self)?;
+        while let Some(&(_, op)) = self
+            .peek_op()
+            .and_then(|t| ops.iter().find(|(o, _)| *o == t))
+        {
+            self.pos += 1;
+            lhs = Node::Bin(Box::new(lhs), op, Box::new(next(self)?));
+        }
+        Ok(lhs)
+    }
+
+    fn or(&mut self) -> Result<Node> {
+        self.binary(&[("||", BinOp::Or)], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Node> {
+        self.binary(&[("&&", BinOp::And)], Self::cmp)
+    }
+
+    fn cmp(&mut self) -> Result<Node> {
+        let lhs = self.sum()?;
+        let op = match self.peek_op() {
+            Some("<") => BinOp::Lt,
+            Some("<=") => BinOp::Le,
+            Some(">") => BinOp::Gt,
+            Some(">=") => BinOp::Ge,
+            Some("==") => BinOp::Eq,
+            Some("!=") => BinOp::Ne,
+            _ => return Ok(lhs),
+        };
+        self.pos += 1;
+        Ok(Node::Bin(Box::new(lhs), op, Box::new(self.sum()?)))
+    }
+
+    fn sum(&mut self) -> Result<Node> {
+        self.binary(&[("+", BinOp::Add), ("-", BinOp::Sub)], Self::product)
+    }
+
+    fn product(&mut self) -> Result<Node> {
+        self.binary(&[("*", BinOp::Mul), ("/", BinOp::Div)], Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<Node> {
+        if self.eat("-") {
+            return Ok(match self.unary()? {
+                Node::Num(n) => Node::Num(-n),
+                e => Node::Neg(Box::new(e)),
+            });
+        }
+        if self.eat("!") {
+            return Ok(Node::Not(Box::new(self.unary()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Node> {
+        if self.eat("(") {
+            let inner = self.or()?;
+            if !self.eat(")") {
+                return Err(self.err("missing `)`"));
+            }
+            return Ok(inner);
+        }
+        let tok = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match tok {
+            Some(Token::Num(n)) => Ok(Node::Num(n)),
+            Some(Token::Quoted(name)) => Ok(Node::Var(name)),
+            Some(Token::Name(name)) if self.peek_op() == Some("(") => self.call(&name),
+            Some(Token::Name(name)) => Ok(Node::Var(normalize_name(name))),
+            Some(Token::Op(op)) => Err(self.err(&format!("unexpected `{op}`"))),
+            None => Err(self.err("unexpected end")),
+        }
+    }
+
+    fn call(&mut self, name: &str) -> Result<Node> {
+        let func =
+            Func::lookup(name).ok_or_else(|| self.err(&format!("unknown function {name}")))?;
+        self.pos += 1; // `(`
+        let mut args = Vec::new();
+        if !self.eat(")") {
+            loop {
+                args.push(self.or()?);
+                if self.eat(")") {
+                    break;
+                }
+                if !self.eat(",") {
+                    return Err(self.err(&format!("expected `,` or `)` in {name}()")));
+                }
+            }
+        }
+        let (min, max) = func.arity();
+        if args.len() < min || max.is_some_and(|m| args.len() > m) {
+            return Err(self.err(&format!("wrong number of arguments to {name}()")));
+        }
+        let slot = if func.is_stateful() {
+            self.slots += 1;
+            self.slots - 1
+        } else {
+            0
+        };
+        Ok(Node::Call { func, args, slot })
+    }
+}
+
+/// `buttonN` -> `btnN` (the state name of unnamed buttons).
+fn normalize_name(name: String) -> String {
+    match name.strip_prefix("button") {
+        Some(n) if !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()) => format!("btn{n}"),
+        _ => name,
+    }
+}
+
+fn truthy(x: f32) -> bool {
+    x > 0.5
+}
+
+fn bool_value(b: bool) -> f32 {
+    if b {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+impl Expr {
+    /// Parse an expression.
+    pub fn parse(src: &str) -> Result<Self> {
+        let mut p = Parser {
+            src,
+            tokens: tokenize(src)?,
+            pos: 0,
+            slots: 0,
+        };
+        let root = p.or()?;
+        if p.pos < p.tokens.len() {
+            return Err(p.err("trailing input"));
+        }
+        Ok(Self {
+            source: src.to_string(),
+            root,
+            slots: p.slots,
+        })
+    }
+
+    /// Source text.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Names referenced, in order of first appearance.
+    pub fn names(&self) -> Vec<&str> {
+        fn walk<'a>(n: &'a Node, out: &mut Vec<&'a str>) {
+            match n {
+                Node::Num(_) => {}
+                Node::Var(name) => {
+                    if !out.contains(&name.as_str()) {
+                        out.push(name);
+                    }
+                }
+                Node::Neg(e) | Node::Not(e) => walk(e, out),
+                Node::Bin(a, _, b) => {
+                    walk(a, out);
+                    walk(b, out);
+                }
+                Node::Call { args, .. } => args.iter().for_each(|a| walk(a, out)),
+            }
+        }
+        let mut out = Vec::new();
+        walk(&self.root, &mut out);
+        out
+    }
+
+    /// Fresh state for [`eval`](Self::eval).
+    pub fn new_state(&self) -> ExprState {
+        ExprState {
+            slots: vec![f32::NAN; self.slots],
+        }
+    }
+
+    /// Evaluate with `vars` resolving names, `state` carrying `smooth`/`slew` history, and
+    /// `dt` seconds since the previous evaluation.
+    pub fn eval(&self, vars: impl Fn(&str) -> Option<f32>, state: &mut ExprState, dt: f32) -> f32 {
+        if state.slots.len() != self.slots {
+            *state = self.new_state();
+        }
+        let mut ctx = Ctx {
+            vars: &vars,
+            slots: &mut state.slots,
+            dt: dt.max(0.0),
+        };
+        ctx.eval(&self.root)
+    }
+
+    /// Evaluate without history (`smooth`/`slew` pass their input through).
+    pub fn eval_once(&self, vars: impl Fn(&str) -> Option<f32>) -> f32 {
+        self.eval(vars, &mut self.new_state(), 0.0)
+    }
+
+    /// [`eval`](Self::eval) as a condition (true above `0.5`).
+    pub fn eval_bool(
+        &self,
+        vars: impl Fn(&str) -> Option<f32>,
+        state: &mut ExprState,
+        dt: f32,
+    ) -> bool {
+        truthy(self.eval(vars, state, dt))
+    }
+}
+
+struct Ctx<'a> {
+    vars: &'a dyn Fn(&str) -> Option<f32>,
+    slots: &'a mut [f32],
+    dt: f32,
+}
+
+impl Ctx<'_> {
+    fn eval(&mut self, n: &Node) -> f32 {
+        match n {
+            Node::Num(x) => *x,
+            Node::Var(name) => (self.vars)(name).unwrap_or(0.0),
+            Node::Neg(e) => -self.eval(e),
+            Node::Not(e) => bool_value(!truthy(self.eval(e))),
+            Node::Bin(a, BinOp::And, b) => bool_value(truthy(self.eval(a)) && truthy(self.eval(b))),
+            Node::Bin(a, BinOp::Or, b) => bool_value(truthy(self.eval(a)) || truthy(self.eval(b))),
+            Node::Bin(a, op, b) => {
+                let (a, b) = (self.eval(a), self.eval(b));
+                match op {
+                    BinOp::Add => a + b,
+                    BinOp::Sub => a - b,
+                    BinOp::Mul => a * b,
+                    BinOp::Div if b == 0.0 => 0.0,
+                    BinOp::Div => a / b,
+                    BinOp::Lt => bool_value(a < b),
+                    BinOp::Le => bool_value(a <= b),
+                    BinOp::Gt => bool_value(a > b),
+                    BinOp::Ge => bool_value(a >= b),
+                    BinOp::Eq => bool_value(a == b),
+                    BinOp::Ne => bool_value(a != b),
+                    BinOp::And | BinOp::Or => unreachable!("handled above"),
+                }
+            }
+            Node::Call { func, args, slot } => self.call(*func, args, *slot),
+        }
+    }
+
+    fn call(&mut self, func: Func, args: &[Node], slot: usize) -> f32 {
+        if func == Func::If {
+            return if truthy(self.eval(&args[0])) {
+                self.eval(&args[1])
+            } else {
+                self.eval(&args[2])
+            };
+        }
+        let mut v = [0.0f32; 3];
+        if matches!(func, Func::Min | Func::Max) {
+            let vals = args.iter().map(|a| self.eval(a));
+            return if func == Func::Min {
+                vals.fold(f32::INFINITY, f32::min)
+            } else {
+                vals.fold(f32::NEG_INFINITY, f32::max)
+            };
+        }
+        for (i, a) in args.iter().enumerate() {
+            v[i] = self.eval(a);
+        }
+        match func {
+            Func::Abs => v[0].abs(),
+            Func::Sign => {
+                if v[0] == 0.0 {
+                    0.0
+                } else {
+                    v[0].signum()
+                }
+            }
+            Func::Sqrt => v[0].max(0.0).sqrt(),
+            Func::Pow => v[0].powf(v[1]),
+            Func::Clamp => {
+                // `max`/`min` rather than `f32::clamp`, which panics on a NaN bound.
+                let (lo, hi) = if v[1] > v[2] {
+                    (v[2], v[1])
+                } else {
+                    (v[1], v[2])
+                };
+                v[0].max(lo).min(hi)
+            }
+            Func::Deadzone => {
+                let dz = v[1].abs().min(0.95);
+                if v[0].abs() <= dz {
+                    0.0
+                } else {
+                    v[0].signum() * (v[0].abs() - dz) / (1.0 - dz)
+                }
+            }
+            Func::Smooth | Func::Slew => {
+                let prev = self.slots[slot];
+                let out = if prev.is_nan() {
+                    v[0]
+                } else if func == Func::Smooth {
+                    let tau = v[1].max(0.0);
+                    let alpha = if tau == 0.0 {
+                        1.0
+                    } else {
+                        1.0 - (-self.dt / tau).exp()
+                    };
+                    prev + (v[0] - prev) * alpha
+                } else {
+                    let step = v[1].abs() * self.dt;
+                    prev + (v[0] - prev).max(-step).min(step)
+                };
+                self.slots[slot] = out;
+                out
+            }
+            Func::Min | Func::Max | Func::If => unreachable!("handled above"),
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! Fixed-offset decoding for simple gamepads.
+//!
+//! Most gamepads and sticks send a single input report whose layout never changes: a few
+//! axes, a hat, and a block of button bits. For those, looking every field up by usage on
+//! every report (as `HidP_GetUsageValue`/`HidP_GetUsages` do, several calls per report at
+//! 1 kHz) is avoidable. A [`FixedLayout`] records each channel's bit offset once and then
+//! decodes a report with plain shifts and masks, without allocating. The `parse` benchmark
+//! measures it against [`DescriptorParser`](crate::descriptor::DescriptorParser) only.
+//!
+//! Layouts come from a parsed descriptor ([`FixedLayout::from_descriptor`]) or, on Windows,
+//! from the HIDP caps of the device (the HIDP parser builds one automatically when a
+//! device qualifies). Decoding follows the same policies as the other parsers: axes
+//! normalized to `[-1, 1]` with a ~2 LSB jitter filter, hats as slots, buttons as edges.
+//!
+//! ```
+//! use stickup::descriptor::ReportDescriptor;
+//! use stickup::fastpath::FixedLayout;
+//!
+//! // X/Y (8 bit), a 4-bit hat with padding, 8 buttons.
+//! let desc = ReportDescriptor::parse(&[
+//!     0x05, 0x01, 0x09, 0x05, 0xA1, 0x01, 0x09, 0x30, 0x09, 0x31, 0x15, 0x00, 0x26, 0xFF,
+//!     0x00, 0x75, 0x08, 0x95, 0x02, 0x81, 0x02, 0x09, 0x39, 0x15, 0x00, 0x25, 0x07, 0x75,
+//!     0x04, 0x95, 0x01, 0x81, 0x42, 0x75, 0x04, 0x95, 0x01, 0x81, 0x03, 0x05, 0x09, 0x19,
+//!     0x01, 0x29, 0x08, 0x15, 0x00, 0x25, 0x01, 0x75, 0x01, 0x95, 0x08, 0x81, 0x02, 0xC0,
+//! ])?;
+//! let mut layout = FixedLayout::from_descriptor(&desc).expect("simple gamepad");
+//! let mut events = Vec::new();
+//! layout.decode(&[0xFF, 0x80, 0x02, 0x01], &mut events);
+//! assert_eq!(events.len(), 4); // button 0 pressed, X, Y, hat
+//! # Ok::<(), stickup::Error>(())
+//! ```
+
+use crate::descriptor::{
+    classify_hat, hat_value_to_slot, normalize_axis_value, sign_extend, ReportDescriptor,
+};
+use crate::event::InputKind;
+
+/// Axis or hat at a fixed position in the report.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedValue {
+    /// Axis or hat index reported in events.
+    pub index: u16,
+    /// Bit offset in the payload (after the report ID byte).
+    pub bit_offset: u32,
+    /// Bits per value (1..=32).
+    pub bit_size: u32,
+    pub logical_min: i32,
+    pub logical_max: i32,
+    /// Hat switch encoding; `None` for axes.
+    pub hat: Option<HatEncoding>,
+}
+
+/// How a hat value is encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HatEncoding {
+    /// Eight direction slots (`0..7` or `1..8`).
+    Slots,
+    /// An angle, converted to slots using 45° sectors.
+    Degrees,
+}
+
+/// Button bit at a fixed position in the report.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedButton {
+    /// Button index reported in events.
+    pub index: u16,
+    /// Bit offset in the payload (after the report ID byte).
+    pub bit_offset: u32,
+}
+
+/// Precomputed report layout and last decoded state (see module docs).
+#[derive(Clone, Debug)]
+pub struct FixedLayout {
+    report_id: u8,
+    /// Payload bytes needed to read every field.
+    min_len: usize,
+    values: Vec<FixedValue>,
+    buttons: Vec<FixedButton>,
+    axis_epsilon: f32,
+    /// Indexed by button index.
+    pressed: Vec<bool>,
+    /// Indexed by axis index (`NaN` = nothing reported yet).
+    last_axis: Vec<f32>,
+    /// Indexed by hat index (`i16::MIN` = nothing reported yet).
+    last_hat: Vec<i16>,
+}
+
+impl FixedLayout {
+    /// Layout of report `report_id` from explicit fields.
+    ///
+    /// `values` are decoded (and reported) in the given order, after the buttons.
+    pub fn new(report_id: u8, values: Vec<FixedValue>, buttons: Vec<FixedButton>) -> Self {
+        let bits = values
+            .iter()
+            .map(|v| v.bit_offset + v.bit_size)
+            .chain(buttons.iter().map(|b| b.bit_offset + 1))
+            .max()
+            .unwrap_or(0);
+        let max_span = values
+            .iter()
+            .map(|v| v.logical_max.saturating_sub(v.logical_min))
+            .fold(1, i32::max);
+        let slots = |hat: bool| {
+            values
+                .iter()
+                .filter(|v| v.hat.is_some() == hat)
+                .map(|v| usize::from(v.index) + 1)
+                .max()
+                .unwrap_or(0)
+        };
+        let buttons_len = buttons
+            .iter()
+            .map(|b| usize::from(b.index) + 1)
+            .max()
+            .unwrap_or(0);
+        Self {
+            report_id,
+            min_len: bits.div_ceil(8) as usize,
+            last_axis: vec![f32::NAN; slots(false)],
+            last_hat: vec![i16::MIN; slots(true)],
+            pressed: vec![false; buttons_len],
+            values,
+            buttons,
+            axis_epsilon: 2.0 * (2.0 / max_span as f32),
+        }
+    }
+
+    /// Layout for a descriptor with a single input report made only of absolute variable fields
+    /// of at most 32 bits; `None` for anything else (numbered multi-report devices, button
+    /// arrays, keyboards, relative fields).
+    ///
+    /// Channel indices match [`DescriptorParser`](crate::descriptor::DescriptorParser).
+    pub fn from_descriptor(desc: &ReportDescriptor) -> Option<Self> {
+        let [report_id] = desc.report_ids.iter().copied().collect::<Vec<_>>()[..] else {
+            return None;
+        };
+        let mut values = Vec::new();
+        let mut buttons = Vec::new();
+        // (collection, usage, position in `values`) per hat, numbered below.
+        let mut hats = Vec::new();
+        let mut next_axis = 0u16;
+        for f in &desc.fields {
+            if !f.is_variable() || f.is_relative() || f.bit_size > 32 {
+                return None;
+            }
+            for i in 0..f.report_count {
+                let (page, usage) = f.usage(i as usize)?;
+                let bit_offset = f.bit_offset + i * f.bit_size;
+                if f.bit_size == 1 {
+                    let index = buttons.len() as u16;
+                    buttons.push(FixedButton { index, bit_offset });
+                    continue;
+                }
+                let (is_hat, degrees) = classify_hat(page, usage, f.logical_min, f.logical_max);
+                let hat = is_hat.then_some(if degrees {
+                    HatEncoding::Degrees
+                } else {
+                    HatEncoding::Slots
+                });
+                let index = if is_hat {
+                    hats.push((f.collection, usage, values.len()));
+                    0
+                } else {
+                    next_axis += 1;
+                    next_axis - 1
+                };
+                values.push(FixedValue {
+                    index,
+                    bit_offset,
+                    bit_size: f.bit_size,
+                    logical_min: f.logical_min,
+                    logical_max: f.logical_max,
+                    hat,
+                });
+            }
+        }
+        hats.sort();
+        for (n, &(_, _, i)) in hats.iter().enumerate() {
+            values[i].index = n as u16;
+        }
+        Some(Self::new(report_id, values, buttons))
+    }
+
+    /// Report ID this layout decodes (`0` for unnumbered reports).
+    pub fn report_id(&self) -> u8 {
+        self.report_id
+    }
+
+    /// Decode one report payload (excluding the report ID byte), appending button edges,
+    /// then axis and hat changes in field order.
+    ///
+    /// Payloads shorter than the layout are ignored.
+    pub fn decode(&mut self, payload: &[u8], out: &mut Vec<InputKind>) {
+        if payload.len() < self.min_len {
+            return;
+        }
+        // Presses first, then releases, each in index order (as the other parsers do).
+        for b in &self.buttons {
+            let down = read_bits(payload, b.bit_offset, 1) != 0;
+            let was = &mut self.pressed[usize::from(b.index)];
+            if down && !*was {
+                *was = true;
+                out.push(InputKind::ButtonPressed { button: b.index });
+            }
+        }
+        for b in &self.buttons {
+            let down = read_bits(payload, b.bit_offset, 1) != 0;
+            let was = &mut self.pressed[usize::from(b.index)];
+            if !down && *was {
+                *was = false;
+                out.push(InputKind::ButtonReleased { button: b.index });
+            }
+        }
+        for v in &self.values {
+            let raw = read_bits(payload, v.bit_offset, v.bit_size);
+            let raw = if v.logical_min < 0 {
+                sign_extend(raw, v.bit_size)
+            } else {
+                raw as i32
+            };
+            match v.hat {
+                None => {
+                    let value = normalize_axis_value(raw, v.logical_min, v.logical_max);
+                    let last = &mut self.last_axis[usize::from(v.index)];
+                    if !last.is_finite() || (value - *last).abs() > self.axis_epsilon {
+                        *last = value;
+                        out.push(InputKind::AxisMoved {
+                            axis: v.index,
+                            value,
+                        });
+                    }
+                }
+                Some(enc) => {
+                    let degrees = enc == HatEncoding::Degrees;
+                    let slot = hat_value_to_slot(raw, v.logical_min, v.logical_max, degrees);
+                    let last = &mut self.last_hat[usize::from(v.index)];
+                    if *last != slot {
+                        *last = slot;
+                        out.push(InputKind::HatChanged {
+                            hat: v.index,
+                            value: slot,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Forget the last decoded state, so the next report emits every channel again.
+    pub fn reset(&mut self) {
+        self.pressed.fill(false);
+        self.last_axis.fill(f32::NAN);
+        self.last_hat.fill(i16::MIN);
+    }
+}
+
+/// Read `bits` (1..=32) little-endian bits at `offset`; the caller checks the length.
+#[inline]
+fn read_bits(payload: &[u8], offset: u32, bits: u32) -> u32 {
+    let start = (offset / 8) as usize;
+    let end = (start + 8).min(payload.len());
+    let mut window = [0u8; 8];
+    window[..end - start].copy_from_slice(&payload[start..end]);
+    // At most 7 + 32 bits: always inside the 64-bit window.
+    let v = u64::from_le_bytes(window) >> (offset % 8);
+    (v & ((1u64 << bits) - 1)) as u32
+}
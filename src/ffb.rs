@@ -0,0 +1,182 @@
+//! Force feedback (HID Physical Interface Device) effects.
+//!
+//! This module holds the platform-agnostic side of force feedback:
+//! - [`FfbEffect`]: a small builder describing one effect (constant force, spring, damper)
+//! - [`EffectHandle`] / [`EffectOp`]: identify and control uploaded effects
+//! - [`FfbEncoder`]: the contract parsers implement to turn effects into PID reports
+//!
+//! Uploading/playing goes through [`Device::upload_effect`](crate::device::Device::upload_effect)
+//! and [`Device::play_effect`](crate::device::Device::play_effect) (or the `Manager` wrappers).
+//! Devices without a PID output descriptor return [`Error::Unsupported`](crate::Error::Unsupported).
+//!
+//! ## Value conventions
+//! - Magnitudes, coefficients, and offsets are normalized to `[-1.0, 1.0]`.
+//! - Gain and deadband are normalized to `[0.0, 1.0]`.
+//! - Direction is in degrees (polar, `0` = up/forward, clockwise), as in the PID spec.
+//!
+//! # Example
+//! ```no_run
+//! use std::time::Duration;
+//! use stickup::ffb::{EffectOp, FfbEffect};
+//! use stickup::Manager;
+//!
+//! let mut mgr = Manager::discover().expect("discover devices");
+//! let spring = FfbEffect::spring(0.6).deadband(0.05);
+//! let handle = mgr.upload_effect("231d:0200:ABCD", &spring).expect("upload");
+//! mgr.play_effect("231d:0200:ABCD", handle, EffectOp::Start { loop_count: 1 })
+//!     .expect("play");
+//!
+//! let kick = FfbEffect::constant(-0.8).duration(Duration::from_millis(120));
+//! let _ = mgr.upload_effect("231d:0200:ABCD", &kick);
+//! ```
+
+use std::time::Duration;
+
+/// The effect-specific parameters of an [`FfbEffect`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum FfbEffectKind {
+    /// Constant force in the effect direction. `magnitude` in `[-1, 1]`.
+    ConstantForce { magnitude: f32 },
+    /// Position-dependent centering force.
+    ///
+    /// `coefficient` in `[-1, 1]`, `offset` (center point) in `[-1, 1]`,
+    /// `deadband` in `[0, 1]`.
+    Spring {
+        coefficient: f32,
+        offset: f32,
+        deadband: f32,
+    },
+    /// Velocity-dependent resistance. `coefficient` in `[-1, 1]`.
+    Damper { coefficient: f32 },
+}
+
+/// A force feedback effect description.
+///
+/// Build with [`FfbEffect::constant`], [`FfbEffect::spring`], or [`FfbEffect::damper`] and
+/// refine with the chained setters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FfbEffect {
+    /// Effect parameters.
+    pub kind: FfbEffectKind,
+    /// Playback duration; `None` plays until stopped.
+    pub duration: Option<Duration>,
+    /// Delay between start and the effect becoming active.
+    pub start_delay: Duration,
+    /// Overall effect gain in `[0, 1]`.
+    pub gain: f32,
+    /// Polar direction in degrees (`0` = up/forward, clockwise).
+    pub direction_deg: f32,
+}
+
+impl FfbEffect {
+    fn with_kind(kind: FfbEffectKind) -> Self {
+        Self {
+            kind,
+            duration: None,
+            start_delay: Duration::ZERO,
+            gain: 1.0,
+            direction_deg: 0.0,
+        }
+    }
+
+    /// Constant force with the given signed magnitude (`[-1, 1]`).
+    pub fn constant(magnitude: f32) -> Self {
+        Self::with_kind(FfbEffectKind::ConstantForce { magnitude })
+    }
+
+    /// Centering spring with the given coefficient (`[-1, 1]`).
+    pub fn spring(coefficient: f32) -> Self {
+        Self::with_kind(FfbEffectKind::Spring {
+            coefficient,
+            offset: 0.0,
+            deadband: 0.0,
+        })
+    }
+
+    /// Damper with the given coefficient (`[-1, 1]`).
+    pub fn damper(coefficient: f32) -> Self {
+        Self::with_kind(FfbEffectKind::Damper { coefficient })
+    }
+
+    /// Limit playback to `d` (default: play until stopped).
+    pub fn duration(mut self, d: Duration) -> Self {
+        self.duration = Some(d);
+        self
+    }
+
+    /// Delay the effect start by `d`.
+    pub fn start_delay(mut self, d: Duration) -> Self {
+        self.start_delay = d;
+        self
+    }
+
+    /// Set the overall gain (`[0, 1]`).
+    pub fn gain(mut self, gain: f32) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    /// Set the polar direction in degrees.
+    pub fn direction(mut self, degrees: f32) -> Self {
+        self.direction_deg = degrees;
+        self
+    }
+
+    /// Spring only: move the center point (`[-1, 1]`). Ignored for other kinds.
+    pub fn offset(mut self, offset: f32) -> Self {
+        if let FfbEffectKind::Spring { offset: o, .. } = &mut self.kind {
+            *o = offset;
+        }
+        self
+    }
+
+    /// Spring only: set the deadband around the center (`[0, 1]`). Ignored for other kinds.
+    pub fn deadband(mut self, deadband: f32) -> Self {
+        if let FfbEffectKind::Spring { deadband: d, .. } = &mut self.kind {
+            *d = deadband;
+        }
+        self
+    }
+}
+
+/// Device-assigned effect slot (the PID "Effect Block Index").
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EffectHandle(pub u8);
+
+/// Playback operation for an uploaded effect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EffectOp {
+    /// Start the effect, repeating `loop_count` times (`0` is treated as once).
+    Start { loop_count: u8 },
+    /// Start the effect and stop all others.
+    StartSolo { loop_count: u8 },
+    /// Stop the effect.
+    Stop,
+}
+
+/// Encodes effects into PID reports for a specific device descriptor.
+///
+/// Implemented by report parsers that discovered a PID output/feature layout. The device
+/// wrapper performs the I/O:
+/// 1. write the [`create_effect_report`](FfbEncoder::create_effect_report) as a feature report
+/// 2. read the feature report prepared by [`block_load_buffer`](FfbEncoder::block_load_buffer)
+///    and decode it with [`parse_block_load`](FfbEncoder::parse_block_load)
+/// 3. write every report from [`effect_reports`](FfbEncoder::effect_reports) as output reports
+///
+/// All returned buffers include the leading report ID byte.
+pub trait FfbEncoder {
+    /// Feature report requesting allocation of an effect block for `kind`.
+    fn create_effect_report(&self, kind: &FfbEffectKind) -> Option<Vec<u8>>;
+
+    /// Zeroed buffer (with report ID set) for reading the PID Block Load feature report.
+    fn block_load_buffer(&self) -> Option<Vec<u8>>;
+
+    /// Decode the PID Block Load report; returns the allocated block on success.
+    fn parse_block_load(&self, report: &[u8]) -> Option<EffectHandle>;
+
+    /// Output reports that configure `effect` in the allocated block.
+    fn effect_reports(&self, handle: EffectHandle, effect: &FfbEffect) -> Option<Vec<Vec<u8>>>;
+
+    /// Output report performing `op` on an uploaded effect.
+    fn operation_report(&self, handle: EffectHandle, op: EffectOp) -> Option<Vec<u8>>;
+}
@@ -0,0 +1,761 @@
+//! SDL GameControllerDB import.
+//!
+//! The community [`gamecontrollerdb.txt`](https://github.com/mdqinc/SDL_GameControllerDB)
+//! describes, per controller, which raw button/axis/hat drives which control of a standard
+//! gamepad. [`ControllerDb`] parses that format and [`map_gamepad`] wraps a device so it
+//! reports the standard layout instead of its raw channels:
+//!
+//! | Kind    | Index | Name (SDL name)                                   |
+//! |---------|-------|---------------------------------------------------|
+//! | axis    | 0..5  | `LX`, `LY`, `RX`, `RY` (sticks), `LT`, `RT` (triggers) |
+//! | button  | 0..   | see [`StdButton`] (`South` = `a`, `East` = `b`, …) |
+//! | hat     | 0     | `DPad` (from `dpup`/`dpdown`/`dpleft`/`dpright`)  |
+//!
+//! Axis values follow the crate's conventions: sticks in `[-1, 1]` (Up = `-1`), triggers
+//! `-1` released to `1` fully pressed (as for XInput). Only mapped controls are exposed;
+//! non-channel events (battery, motion, touch) pass through.
+//!
+//! Enable it for discovery with
+//! [`DiscoveryOptions::controller_db`](crate::manager::DiscoveryOptions::controller_db); HID
+//! devices whose VID/PID has a mapping for the current platform are wrapped.
+//!
+//! Raw indices (`b3`, `a1`, `h0.4`) are matched against the device's channel indices, which
+//! follow descriptor usage order like SDL's HID backend. Mappings written against another
+//! driver's ordering may need adjusting.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::Instant;
+
+use crate::device::{BatteryStatus, Device, ReportIdDiagnostics};
+use crate::event::{ChannelDesc, ChannelKind, ChannelUnit, InputKind};
+use crate::ffb::{EffectHandle, EffectOp, FfbEffect};
+use crate::metadata::DeviceMeta;
+use crate::output::OutputCommand;
+use crate::{Error, Result};
+
+/// Standard gamepad axes (index = output axis index).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StdAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl StdAxis {
+    const ALL: [StdAxis; 6] = [
+        StdAxis::LeftX,
+        StdAxis::LeftY,
+        StdAxis::RightX,
+        StdAxis::RightY,
+        StdAxis::LeftTrigger,
+        StdAxis::RightTrigger,
+    ];
+
+    fn from_sdl(name: &str) -> Option<Self> {
+        Some(match name {
+            "leftx" => StdAxis::LeftX,
+            "lefty" => StdAxis::LeftY,
+            "rightx" => StdAxis::RightX,
+            "righty" => StdAxis::RightY,
+            "lefttrigger" => StdAxis::LeftTrigger,
+            "righttrigger" => StdAxis::RightTrigger,
+            _ => return None,
+        })
+    }
+
+    /// Channel name in [`describe`](Device::describe).
+    pub fn name(self) -> &'static str {
+        match self {
+            StdAxis::LeftX => "LX",
+            StdAxis::LeftY => "LY",
+            StdAxis::RightX => "RX",
+            StdAxis::RightY => "RY",
+            StdAxis::LeftTrigger => "LT",
+            StdAxis::RightTrigger => "RT",
+        }
+    }
+
+    fn is_trigger(self) -> bool {
+        matches!(self, StdAxis::LeftTrigger | StdAxis::RightTrigger)
+    }
+
+    /// Value at rest.
+    fn rest(self) -> f32 {
+        if self.is_trigger() {
+            -1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Standard gamepad buttons (index = output button index).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StdButton {
+    South,
+    East,
+    West,
+    North,
+    Back,
+    Guide,
+    Start,
+    LeftStick,
+    RightStick,
+    LeftShoulder,
+    RightShoulder,
+    Misc1,
+    Paddle1,
+    Paddle2,
+    Paddle3,
+    Paddle4,
+    Touchpad,
+}
+
+impl StdButton {
+    const ALL: [StdButton; 17] = [
+        StdButton::South,
+        StdButton::East,
+        StdButton::West,
+        StdButton::North,
+        StdButton::Back,
+        StdButton::Guide,
+        StdButton::Start,
+        StdButton::LeftStick,
+        StdButton::RightStick,
+        StdButton::LeftShoulder,
+        StdButton::RightShoulder,
+        StdButton::Misc1,
+        StdButton::Paddle1,
+        StdButton::Paddle2,
+        StdButton::Paddle3,
+        StdButton::Paddle4,
+        StdButton::Touchpad,
+    ];
+
+    fn from_sdl(name: &str) -> Option<Self> {
+        Some(match name {
+            "a" => StdButton::South,
+            "b" => StdButton::East,
+            "x" => StdButton::West,
+            "y" => StdButton::North,
+            "back" => StdButton::Back,
+            "guide" => StdButton::Guide,
+            "start" => StdButton::Start,
+            "leftstick" => StdButton::LeftStick,
+            "rightstick" => StdButton::RightStick,
+            "leftshoulder" => StdButton::LeftShoulder,
+            "rightshoulder" => StdButton::RightShoulder,
+            "misc1" => StdButton::Misc1,
+            "paddle1" => StdButton::Paddle1,
+            "paddle2" => StdButton::Paddle2,
+            "paddle3" => StdButton::Paddle3,
+            "paddle4" => StdButton::Paddle4,
+            "touchpad" => StdButton::Touchpad,
+            _ => return None,
+        })
+    }
+
+    /// Channel name in [`describe`](Device::describe).
+    pub fn name(self) -> &'static str {
+        match self {
+            StdButton::South => "South",
+            StdButton::East => "East",
+            StdButton::West => "West",
+            StdButton::North => "North",
+            StdButton::Back => "Back",
+            StdButton::Guide => "Guide",
+            StdButton::Start => "Start",
+            StdButton::LeftStick => "LeftStick",
+            StdButton::RightStick => "RightStick",
+            StdButton::LeftShoulder => "LeftShoulder",
+            StdButton::RightShoulder => "RightShoulder",
+            StdButton::Misc1 => "Misc1",
+            StdButton::Paddle1 => "Paddle1",
+            StdButton::Paddle2 => "Paddle2",
+            StdButton::Paddle3 => "Paddle3",
+            StdButton::Paddle4 => "Paddle4",
+            StdButton::Touchpad => "Touchpad",
+        }
+    }
+}
+
+/// D-pad directions (combined into the `DPad` hat).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DPad {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+/// Which part of a raw axis is read (`+a0` / `-a0` / `a0`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AxisRange {
+    Full,
+    Positive,
+    Negative,
+}
+
+/// Raw input a mapping reads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// `bN`
+    Button(u16),
+    /// `aN`, `+aN`, `-aN`, optionally `~`-inverted.
+    Axis {
+        axis: u16,
+        range: AxisRange,
+        invert: bool,
+    },
+    /// `hN.M`: hat `N` pressed towards SDL mask `M` (1 up, 2 right, 4 down, 8 left).
+    Hat { hat: u16, mask: u8 },
+}
+
+/// Standard control a mapping drives.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    Button(StdButton),
+    DPad(DPad),
+    /// A standard axis, or one half of it (`+leftx` / `-leftx`).
+    Axis {
+        axis: StdAxis,
+        range: AxisRange,
+    },
+}
+
+/// One controller entry of the database.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ControllerMapping {
+    /// SDL joystick GUID (32 hex digits).
+    pub guid: String,
+    /// Controller name.
+    pub name: String,
+    /// USB vendor id encoded in the GUID, if present.
+    pub vid: Option<u16>,
+    /// USB product id encoded in the GUID, if present.
+    pub pid: Option<u16>,
+    /// `platform:` field (`"Windows"`, `"Linux"`, `"Mac OS X"`, …).
+    pub platform: Option<String>,
+    /// Parsed `target:source` pairs (unknown fields are skipped).
+    pub mappings: Vec<(Target, Source)>,
+}
+
+/// `platform:` value of the running OS.
+fn current_platform() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "Windows"
+    } else if cfg!(target_os = "macos") {
+        "Mac OS X"
+    } else if cfg!(target_os = "android") {
+        "Android"
+    } else if cfg!(target_os = "ios") {
+        "iOS"
+    } else {
+        "Linux"
+    }
+}
+
+/// VID/PID from a GUID (little-endian at bytes 4 and 8, zero padding at 6 and 10).
+fn guid_ids(guid: &str) -> Option<(u16, u16)> {
+    if guid.len() != 32 || !guid.is_ascii() {
+        return None;
+    }
+    let byte = |i: usize| u8::from_str_radix(&guid[i * 2..i * 2 + 2], 16).ok();
+    let word = |i: usize| Some(u16::from_le_bytes([byte(i)?, byte(i + 1)?]));
+    if word(6)? != 0 || word(10)? != 0 {
+        return None;
+    }
+    Some((word(4)?, word(8)?))
+}
+
+fn parse_source(s: &str) -> Option<Source> {
+    let (range, rest) = match s.as_bytes().first()? {
+        b'+' => (AxisRange::Positive, &s[1..]),
+        b'-' => (AxisRange::Negative, &s[1..]),
+        _ => (AxisRange::Full, s),
+    };
+    let (invert, rest) = match rest.strip_suffix('~') {
+        Some(r) => (true, r),
+        None => (false, rest),
+    };
+    let (kind, num) = rest.split_at(1);
+    match kind {
+        "b" => Some(Source::Button(num.parse().ok()?)),
+        "a" => Some(Source::Axis {
+            axis: num.parse().ok()?,
+            range,
+            invert,
+        }),
+        "h" => {
+            let (hat, mask) = num.split_once('.')?;
+            Some(Source::Hat {
+                hat: hat.parse().ok()?,
+                mask: mask.parse().ok()?,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn parse_target(s: &str) -> Option<Target> {
+    let (range, name) = match s.as_bytes().first()? {
+        b'+' => (AxisRange::Positive, &s[1..]),
+        b'-' => (AxisRange::Negative, &s[1..]),
+        _ => (AxisRange::Full, s),
+    };
+    if let Some(axis) = StdAxis::from_sdl(name) {
+        return Some(Target::Axis { axis, range });
+    }
+    if range != AxisRange::Full {
+        return None;
+    }
+    let dpad = match name {
+        "dpup" => Some(DPad::Up),
+        "dpright" => Some(DPad::Right),
+        "dpdown" => Some(DPad::Down),
+        "dpleft" => Some(DPad::Left),
+        _ => None,
+    };
+    dpad.map(Target::DPad)
+        .or_else(|| StdButton::from_sdl(name).map(Target::Button))
+}
+
+impl ControllerMapping {
+    /// Parse one database line (`None` for comments, blank or malformed lines).
+    pub fn parse_line(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let mut fields = line.split(',');
+        let guid = fields.next()?.trim().to_ascii_lowercase();
+        let name = fields.next()?.trim().to_string();
+        let (vid, pid) = guid_ids(&guid).unzip();
+        let mut platform = None;
+        let mut mappings = Vec::new();
+        for field in fields {
+            let Some((key, value)) = field.trim().split_once(':') else {
+                continue;
+            };
+            if key == "platform" {
+                platform = Some(value.to_string());
+                continue;
+            }
+            if let (Some(t), Some(s)) = (parse_target(key), parse_source(value)) {
+                mappings.push((t, s));
+            }
+        }
+        Some(Self {
+            guid,
+            name,
+            vid,
+            pid,
+            platform,
+            mappings,
+        })
+    }
+}
+
+/// A parsed `gamecontrollerdb.txt`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ControllerDb {
+    pub entries: Vec<ControllerMapping>,
+}
+
+impl ControllerDb {
+    /// Parse database text. Comment, blank and malformed lines are skipped.
+    pub fn parse(text: &str) -> Self {
+        Self {
+            entries: text
+                .lines()
+                .filter_map(ControllerMapping::parse_line)
+                .collect(),
+        }
+    }
+
+    /// Load a database file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Other(format!("read {}: {e}", path.display())))?;
+        Ok(Self::parse(&text))
+    }
+
+    /// Append another database (its entries take precedence).
+    pub fn merge(&mut self, other: ControllerDb) {
+        self.entries.extend(other.entries);
+    }
+
+    /// Mapping for a VID/PID on the running platform (entries without a platform match any
+    /// platform; later entries win, as in SDL).
+    pub fn find(&self, vid: u16, pid: u16) -> Option<&ControllerMapping> {
+        let platform = current_platform();
+        self.entries.iter().rev().find(|e| {
+            e.vid == Some(vid)
+                && e.pid == Some(pid)
+                && e.platform.as_deref().unwrap_or(platform) == platform
+        })
+    }
+}
+
+/// SDL hat mask for a hat value (`-1`, `0..7`).
+fn hat_mask(value: i16) -> u8 {
+    match value {
+        0 => 1,
+        1 => 1 | 2,
+        2 => 2,
+        3 => 2 | 4,
+        4 => 4,
+        5 => 4 | 8,
+        6 => 8,
+        7 => 8 | 1,
+        _ => 0,
+    }
+}
+
+/// Hat value for pressed d-pad directions (opposites cancel).
+fn dpad_value(up: bool, right: bool, down: bool, left: bool) -> i16 {
+    let y = up as i8 - down as i8;
+    let x = right as i8 - left as i8;
+    match (x, y) {
+        (0, 1) => 0,
+        (1, 1) => 1,
+        (1, 0) => 2,
+        (1, -1) => 3,
+        (0, -1) => 4,
+        (-1, -1) => 5,
+        (-1, 0) => 6,
+        (-1, 1) => 7,
+        _ => -1,
+    }
+}
+
+/// Raw channel state of the wrapped device.
+#[derive(Default)]
+struct RawState {
+    axes: HashMap<u16, f32>,
+    buttons: HashSet<u16>,
+    hats: HashMap<u16, i16>,
+}
+
+impl RawState {
+    /// Source value: `0..1` for buttons, hats and half axes; `-1..1` for full axes.
+    fn read(&self, src: Source) -> f32 {
+        match src {
+            Source::Button(b) => self.buttons.contains(&b) as u8 as f32,
+            Source::Hat { hat, mask } => {
+                let v = self.hats.get(&hat).copied().unwrap_or(-1);
+                (hat_mask(v) & mask != 0) as u8 as f32
+            }
+            Source::Axis {
+                axis,
+                range,
+                invert,
+            } => {
+                let mut v = self.axes.get(&axis).copied().unwrap_or(0.0);
+                if invert {
+                    v = -v;
+                }
+                match range {
+                    AxisRange::Full => v,
+                    AxisRange::Positive => v.max(0.0),
+                    AxisRange::Negative => (-v).max(0.0),
+                }
+            }
+        }
+    }
+
+    fn pressed(&self, src: Source) -> bool {
+        match src {
+            // Full-range axes (e.g. analog triggers resting at -1) count from the midpoint.
+            Source::Axis {
+                range: AxisRange::Full,
+                ..
+            } => self.read(src) > 0.0,
+            _ => self.read(src) > 0.5,
+        }
+    }
+}
+
+/// Standard-layout output state.
+#[derive(Clone, PartialEq)]
+struct StdState {
+    axes: Vec<f32>,
+    buttons: Vec<bool>,
+    dpad: i16,
+}
+
+/// A device remapped to the standard gamepad layout (see module docs).
+pub struct MappedGamepad {
+    inner: Box<dyn Device>,
+    mappings: Vec<(Target, Source)>,
+    axes: Vec<StdAxis>,
+    buttons: Vec<StdButton>,
+    has_dpad: bool,
+    raw: RawState,
+    last: StdState,
+}
+
+impl MappedGamepad {
+    fn compute(&self) -> StdState {
+        let mut axes: Vec<f32> = self.axes.iter().map(|a| a.rest()).collect();
+        let mut halves: Vec<Option<f32>> = vec![None; self.axes.len()];
+        let mut buttons = vec![false; self.buttons.len()];
+        let mut dirs = [false; 4];
+
+        for &(target, src) in &self.mappings {
+            match target {
+                Target::Button(b) => {
+                    if let Some(i) = self.buttons.iter().position(|x| *x == b) {
+                        buttons[i] |= self.raw.pressed(src);
+                    }
+                }
+                Target::DPad(d) => dirs[d as usize] |= self.raw.pressed(src),
+                Target::Axis { axis, range } => {
+                    let Some(i) = self.axes.iter().position(|x| *x == axis) else {
+                        continue;
+                    };
+                    let v = self.raw.read(src);
+                    let full_src = matches!(
+                        src,
+                        Source::Axis {
+                            range: AxisRange::Full,
+                            ..
+                        }
+                    );
+                    match range {
+                        AxisRange::Full => {
+                            axes[i] = if full_src {
+                                v
+                            } else if axis.is_trigger() {
+                                // 0..1 source → released (-1) .. pressed (1)
+                                v * 2.0 - 1.0
+                            } else {
+                                v
+                            };
+                        }
+                        AxisRange::Positive | AxisRange::Negative => {
+                            // Half outputs take 0..1; full sources are rescaled from -1..1.
+                            let mag = if full_src { (v + 1.0) * 0.5 } else { v };
+                            let sign = if range == AxisRange::Positive {
+                                1.0
+                            } else {
+                                -1.0
+                            };
+                            *halves[i].get_or_insert(0.0) += sign * mag;
+                        }
+                    }
+                }
+            }
+        }
+        for (i, h) in halves.into_iter().enumerate() {
+            if let Some(h) = h {
+                axes[i] = h;
+            }
+            axes[i] = axes[i].clamp(-1.0, 1.0);
+        }
+
+        StdState {
+            axes,
+            buttons,
+            dpad: dpad_value(dirs[0], dirs[1], dirs[2], dirs[3]),
+        }
+    }
+
+    /// Emit events for outputs that changed since the last computation.
+    fn diff(&mut self, out: &mut Vec<InputKind>) {
+        let next = self.compute();
+        for (i, (&new, &old)) in next.axes.iter().zip(&self.last.axes).enumerate() {
+            if new != old {
+                out.push(InputKind::AxisMoved {
+                    axis: i as u16,
+                    value: new,
+                });
+            }
+        }
+        for (i, (&new, &old)) in next.buttons.iter().zip(&self.last.buttons).enumerate() {
+            if new != old {
+                let button = i as u16;
+                out.push(if new {
+                    InputKind::ButtonPressed { button }
+                } else {
+                    InputKind::ButtonReleased { button }
+                });
+            }
+        }
+        if self.has_dpad && next.dpad != self.last.dpad {
+            out.push(InputKind::HatChanged {
+                hat: 0,
+                value: next.dpad,
+            });
+        }
+        self.last = next;
+    }
+
+    /// Fold raw device events into the raw state and return the mapped changes.
+    fn remap(&mut self, events: Vec<InputKind>) -> Vec<InputKind> {
+        let mut out = Vec::new();
+        let mut changed = false;
+        for ev in events {
+            match ev {
+                InputKind::AxisMoved { axis, value } => {
+                    self.raw.axes.insert(axis, value);
+                    changed = true;
+                }
+                InputKind::ButtonPressed { button } => {
+                    self.raw.buttons.insert(button);
+                    changed = true;
+                }
+                InputKind::ButtonReleased { button } => {
+                    self.raw.buttons.remove(&button);
+                    changed = true;
+                }
+                InputKind::HatChanged { hat, value } => {
+                    self.raw.hats.insert(hat, value);
+                    changed = true;
+                }
+                other => out.push(other),
+            }
+        }
+        if changed {
+            self.diff(&mut out);
+        }
+        out
+    }
+}
+
+impl Device for MappedGamepad {
+    fn poll(&mut self) -> Vec<InputKind> {
+        self.try_poll().unwrap_or_default()
+    }
+    fn try_poll(&mut self) -> Result<Vec<InputKind>> {
+        let events = self.inner.try_poll()?;
+        Ok(self.remap(events))
+    }
+    fn initial_state(&mut self) -> Vec<InputKind> {
+        let events = self.inner.initial_state();
+        self.remap(events)
+    }
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+    fn id(&self) -> &str {
+        self.inner.id()
+    }
+    fn metadata(&self) -> DeviceMeta {
+        self.inner.metadata()
+    }
+    fn describe(&self) -> Vec<ChannelDesc> {
+        let desc = |kind, idx: usize, name: &str, min, max| ChannelDesc {
+            kind,
+            idx: idx as u16,
+            name: Some(name.to_string()),
+            logical_min: min,
+            logical_max: max,
+            usage_page: None,
+            usage: None,
+            unit: ChannelUnit::for_kind(kind),
+            collection: None,
+            collection_name: None,
+        };
+        let mut out: Vec<ChannelDesc> = self
+            .axes
+            .iter()
+            .enumerate()
+            .map(|(i, a)| {
+                let mut d = desc(ChannelKind::Axis, i, a.name(), -1, 1);
+                if a.is_trigger() {
+                    d.unit = Some(ChannelUnit::Percent);
+                }
+                d
+            })
+            .collect();
+        out.extend(
+            self.buttons
+                .iter()
+                .enumerate()
+                .map(|(i, b)| desc(ChannelKind::Button, i, b.name(), 0, 1)),
+        );
+        if self.has_dpad {
+            out.push(desc(ChannelKind::Hat, 0, "DPad", -1, 7));
+        }
+        out
+    }
+    fn report_id_diagnostics(&self) -> Option<ReportIdDiagnostics> {
+        self.inner.report_id_diagnostics()
+    }
+    fn battery(&self) -> Option<BatteryStatus> {
+        self.inner.battery()
+    }
+    fn fixed_player_index(&self) -> Option<u8> {
+        self.inner.fixed_player_index()
+    }
+    fn report_time(&self) -> Option<Instant> {
+        self.inner.report_time()
+    }
+    fn set_rumble(&mut self, low: f32, high: f32) -> Result<()> {
+        self.inner.set_rumble(low, high)
+    }
+    fn send_output(&mut self, cmd: &OutputCommand) -> Result<()> {
+        self.inner.send_output(cmd)
+    }
+    fn upload_effect(&mut self, effect: &FfbEffect) -> Result<EffectHandle> {
+        self.inner.upload_effect(effect)
+    }
+    fn play_effect(&mut self, handle: EffectHandle, op: EffectOp) -> Result<()> {
+        self.inner.play_effect(handle, op)
+    }
+    fn capture_reports(&mut self, path: Option<&Path>) -> Result<()> {
+        self.inner.capture_reports(path)
+    }
+}
+
+/// Wrap `device` so it reports the standard layout described by `mapping`.
+///
+/// Axes and buttons are numbered in [`StdAxis`] / [`StdButton`] order, keeping only the
+/// controls the mapping provides.
+pub fn map_gamepad(device: Box<dyn Device>, mapping: &ControllerMapping) -> Box<dyn Device> {
+    let axes: Vec<StdAxis> = StdAxis::ALL
+        .into_iter()
+        .filter(|a| {
+            mapping
+                .mappings
+                .iter()
+                .any(|(t, _)| matches!(t, Target::Axis { axis, .. } if axis == a))
+        })
+        .collect();
+    let buttons: Vec<StdButton> = StdButton::ALL
+        .into_iter()
+        .filter(|b| {
+            mapping
+                .mappings
+                .iter()
+                .any(|(t, _)| *t == Target::Button(*b))
+        })
+        .collect();
+    let has_dpad = mapping
+        .mappings
+        .iter()
+        .any(|(t, _)| matches!(t, Target::DPad(_)));
+    let last = StdState {
+        axes: axes.iter().map(|a| a.rest()).collect(),
+        buttons: vec![false; buttons.len()],
+        dpad: -1,
+    };
+    Box::new(MappedGamepad {
+        inner: device,
+        mappings: mapping.mappings.clone(),
+        axes,
+        buttons,
+        has_dpad,
+        raw: RawState::default(),
+        last,
+    })
+}
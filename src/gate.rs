@@ -0,0 +1,153 @@
+//! Gate channels: synthetic buttons and axes computed from a device's channels.
+//!
+//! A gate is a named [expression](crate::expr) such as
+//!
+//! ```text
+//! axis3 > 0.9 && btn5
+//! "Throttle" < -0.95 || ("Rudder" > 0.5 && !Trigger)
+//! ```
+//!
+//! evaluated by the [`Manager`](crate::manager::Manager) on every poll against the device's
+//! current [`DeviceState`], and published as an ordinary channel on the same device:
+//! - a **button** gate ([`Manager::add_gate`](crate::manager::Manager::add_gate)) emits
+//!   `ButtonPressed` when the condition becomes true and `ButtonReleased` when it stops;
+//! - an **axis** gate ([`Manager::add_expr_axis`](crate::manager::Manager::add_expr_axis))
+//!   emits `AxisMoved` whenever the value changes, e.g. `max(LT, RT)` or
+//!   `smooth("Throttle", 0.2)`.
+//!
+//! Either way it shows up in snapshots and event streams and binds like any other control.
+//! It is a lightweight alternative to scripting for "detent reached", "both paddles held",
+//! and similar conditions.
+//!
+//! Names are channel [state names](crate::event::ChannelDesc::state_name) (`X`, `axis3`,
+//! `btn5`). Buttons read as `1`/`0`, axes as their normalized value, hats as their direction
+//! (`-1` = centered).
+//!
+//! Profiles can declare gates in their `channels` list (see [`ChannelExpr`]), installed with
+//! [`Manager::add_profile_channels`](crate::manager::Manager::add_profile_channels):
+//!
+//! ```toml
+//! [[channels]]
+//! device_id = "231d:0200"
+//! name = "afterburner"
+//! kind = "button"
+//! expr = '"Throttle" > 0.95'
+//! ```
+
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::binding::DeviceState;
+use crate::event::ChannelKind;
+use crate::expr::{Expr, ExprState};
+
+/// Index of a device's first gate of each kind; gates take consecutive indices from here so
+/// they never collide with hardware channels or keyboard keys.
+pub const GATE_INDEX_BASE: u16 = 0xF000;
+
+/// Output kind of a gate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GateKind {
+    /// Pressed while the expression is true (above `0.5`).
+    #[default]
+    Button,
+    /// The expression's value.
+    Axis,
+}
+
+impl GateKind {
+    /// Channel kind the gate is published as.
+    pub fn channel_kind(self) -> ChannelKind {
+        match self {
+            Self::Button => ChannelKind::Button,
+            Self::Axis => ChannelKind::Axis,
+        }
+    }
+}
+
+/// A gate declared in a profile file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChannelExpr {
+    /// Device the gate reads from and is published on.
+    pub device_id: String,
+    /// Channel name.
+    pub name: String,
+    /// Button (default) or axis.
+    #[serde(default)]
+    pub kind: GateKind,
+    /// The expression.
+    pub expr: Expr,
+}
+
+/// A gate installed on a device.
+#[derive(Clone, Debug)]
+pub struct Gate {
+    /// Channel name (its state name).
+    pub name: String,
+    /// Button or axis.
+    pub kind: GateKind,
+    /// Channel index on the device.
+    pub index: u16,
+    /// The expression.
+    pub expr: Expr,
+    /// Current value (`1`/`0` for buttons).
+    pub value: f32,
+    state: ExprState,
+    last_eval: Option<Instant>,
+}
+
+impl Gate {
+    /// New gate with neutral value.
+    pub fn new(name: impl Into<String>, kind: GateKind, index: u16, expr: Expr) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            index,
+            state: expr.new_state(),
+            expr,
+            value: 0.0,
+            last_eval: None,
+        }
+    }
+
+    /// Whether a button gate is pressed.
+    pub fn is_active(&self) -> bool {
+        self.value > 0.5
+    }
+
+    /// Re-evaluate against `st` at `now`; returns the new value if it changed.
+    pub fn update(&mut self, st: &DeviceState, now: Instant) -> Option<f32> {
+        let dt = self
+            .last_eval
+            .map_or(0.0, |t| now.saturating_duration_since(t).as_secs_f32());
+        self.last_eval = Some(now);
+        let vars = |name: &str| {
+            st.axes
+                .get(name)
+                .copied()
+                .or_else(|| st.buttons.get(name).map(|b| if *b { 1.0 } else { 0.0 }))
+                .or_else(|| st.hats.get(name).map(|h| *h as f32))
+        };
+        let raw = self.expr.eval(vars, &mut self.state, dt);
+        let value = match self.kind {
+            GateKind::Button if raw > 0.5 => 1.0,
+            GateKind::Button => 0.0,
+            GateKind::Axis if raw.is_finite() => raw,
+            GateKind::Axis => 0.0,
+        };
+        if value == self.value {
+            return None;
+        }
+        self.value = value;
+        Some(value)
+    }
+
+    /// Back to neutral with no history (device disconnected).
+    pub fn reset(&mut self) {
+        self.value = 0.0;
+        self.state.reset();
+        self.last_eval = None;
+    }
+}
@@ -0,0 +1,161 @@
+//! Tap / hold / double-tap detection.
+//!
+//! A [`GestureDetector`] turns the pressed/released history of one control into discrete
+//! [`Gesture`]s. Binding profiles use it through [`BindingRule::Gesture`], resolved by
+//! [`BindingResolver`](crate::resolver::BindingResolver), which reports the results in
+//! [`BindingOutput::events`](crate::binding::BindingOutput::events).
+//!
+//! Timing (see [`GestureTiming`]):
+//! - **Tap**: pressed and released within `tap_ms`. If double-tap detection is on, the tap is
+//!   reported only once `double_tap_ms` passed without a second tap.
+//! - **DoubleTap**: a second tap whose press follows the first tap's release within
+//!   `double_tap_ms`.
+//! - **Hold**: still pressed after `hold_ms`; reported once, with the time held so far.
+//!
+//! [`BindingRule::Gesture`]: crate::binding::BindingRule::Gesture
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// A detected gesture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Gesture {
+    /// Short press and release.
+    Tap,
+    /// Two taps in quick succession.
+    DoubleTap,
+    /// Press held past the hold threshold (how long it had been held when detected).
+    Hold(Duration),
+}
+
+/// A gesture detected for a bound action (see [`BindingOutput::events`](crate::binding::BindingOutput::events)).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GestureEvent {
+    /// Action name of the [`BindingRule::Gesture`] that produced the event.
+    pub action: String,
+    /// What was detected.
+    pub gesture: Gesture,
+}
+
+fn default_tap_ms() -> u32 {
+    200
+}
+fn default_hold_ms() -> u32 {
+    500
+}
+fn default_double_tap_ms() -> u32 {
+    250
+}
+
+/// Gesture timing thresholds in milliseconds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GestureTiming {
+    /// Longest press that still counts as a tap (default `200`).
+    #[serde(default = "default_tap_ms")]
+    pub tap_ms: u32,
+    /// Press duration that counts as a hold (default `500`).
+    #[serde(default = "default_hold_ms")]
+    pub hold_ms: u32,
+    /// Longest gap between a tap's release and the next press for a double tap
+    /// (default `250`; `0` disables double taps so taps report immediately).
+    #[serde(default = "default_double_tap_ms")]
+    pub double_tap_ms: u32,
+}
+
+impl Default for GestureTiming {
+    fn default() -> Self {
+        Self {
+            tap_ms: default_tap_ms(),
+            hold_ms: default_hold_ms(),
+            double_tap_ms: default_double_tap_ms(),
+        }
+    }
+}
+
+fn ms(v: u32) -> Duration {
+    Duration::from_millis(v as u64)
+}
+
+/// Gesture state machine for one control.
+#[derive(Clone, Debug, Default)]
+pub struct GestureDetector {
+    /// Current press start, if pressed.
+    down_since: Option<Instant>,
+    /// Hold already reported for the current press.
+    hold_sent: bool,
+    /// Release time of a tap that may still become a double tap.
+    pending_tap: Option<Instant>,
+    /// The current press started within the double-tap window of a pending tap.
+    second_press: bool,
+}
+
+impl GestureDetector {
+    /// New detector (released, no pending tap).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed the control's current state at `now`; returns a gesture completed by this sample.
+    pub fn update(
+        &mut self,
+        pressed: bool,
+        now: Instant,
+        timing: &GestureTiming,
+    ) -> Option<Gesture> {
+        match (self.down_since, pressed) {
+            // Press edge.
+            (None, true) => {
+                self.down_since = Some(now);
+                self.hold_sent = false;
+                self.second_press = self
+                    .pending_tap
+                    .is_some_and(|t| now.saturating_duration_since(t) <= ms(timing.double_tap_ms));
+                if self.second_press {
+                    self.pending_tap = None;
+                    None
+                } else {
+                    // A pending tap whose window ran out before this press.
+                    self.pending_tap.take().map(|_| Gesture::Tap)
+                }
+            }
+            // Held.
+            (Some(since), true) => {
+                let held = now.saturating_duration_since(since);
+                if !self.hold_sent && held >= ms(timing.hold_ms) {
+                    self.hold_sent = true;
+                    Some(Gesture::Hold(held))
+                } else {
+                    None
+                }
+            }
+            // Release edge.
+            (Some(since), false) => {
+                self.down_since = None;
+                let is_tap =
+                    !self.hold_sent && now.saturating_duration_since(since) <= ms(timing.tap_ms);
+                if !is_tap {
+                    self.second_press = false;
+                    return None;
+                }
+                if std::mem::take(&mut self.second_press) {
+                    Some(Gesture::DoubleTap)
+                } else if timing.double_tap_ms == 0 {
+                    Some(Gesture::Tap)
+                } else {
+                    self.pending_tap = Some(now);
+                    None
+                }
+            }
+            // Idle: flush a tap whose double-tap window expired.
+            (None, false) => match self.pending_tap {
+                Some(t) if now.saturating_duration_since(t) > ms(timing.double_tap_ms) => {
+                    self.pending_tap = None;
+                    Some(Gesture::Tap)
+                }
+                _ => None,
+            },
+        }
+    }
+}
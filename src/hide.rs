@@ -0,0 +1,137 @@
+//! Channel hiding.
+//!
+//! Many devices expose channels nobody binds: phantom axes that never move, unused vendor
+//! fields, duplicate report items. [`hide_channels`] wraps a device so those channels disappear
+//! from [`Device::describe`] and from polled events, and therefore from snapshots and bind
+//! capture as well.
+//!
+//! Hidden channels come from the device's [`DeviceQuirks`]:
+//! ```toml
+//! [[device]]
+//! vid = 0x044f
+//! pid = 0xb10a
+//! hide = ["Slider 2", "Dial"]       # by channel name
+//!
+//! [[device.usage]]                  # or by usage
+//! usage_page = 0xFF00
+//! usage = 0x21
+//! hidden = true
+//! ```
+//!
+//! Channel indices are left unchanged, so existing bindings keep working.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::Instant;
+
+use crate::device::{BatteryStatus, Device, ReportIdDiagnostics};
+use crate::event::{ChannelDesc, ChannelKind, InputKind};
+use crate::ffb::{EffectHandle, EffectOp, FfbEffect};
+use crate::metadata::DeviceMeta;
+use crate::output::OutputCommand;
+use crate::quirks::DeviceQuirks;
+use crate::Result;
+
+/// A device with some of its channels filtered out.
+pub struct HiddenChannels {
+    inner: Box<dyn Device>,
+    hidden: HashSet<(ChannelKind, u16)>,
+    descs: Vec<ChannelDesc>,
+}
+
+impl HiddenChannels {
+    fn visible(&self, ev: &InputKind) -> bool {
+        let key = match *ev {
+            InputKind::AxisMoved { axis, .. } | InputKind::RelativeMoved { axis, .. } => {
+                (ChannelKind::Axis, axis)
+            }
+            InputKind::ButtonPressed { button } | InputKind::ButtonReleased { button } => {
+                (ChannelKind::Button, button)
+            }
+            InputKind::KeyPressed { scancode, extended }
+            | InputKind::KeyReleased { scancode, extended } => (
+                ChannelKind::Button,
+                crate::keys::key_index(scancode, extended),
+            ),
+            InputKind::HatChanged { hat, .. } => (ChannelKind::Hat, hat),
+            _ => return true,
+        };
+        !self.hidden.contains(&key)
+    }
+}
+
+impl Device for HiddenChannels {
+    fn poll(&mut self) -> Vec<InputKind> {
+        self.try_poll().unwrap_or_default()
+    }
+    fn try_poll(&mut self) -> Result<Vec<InputKind>> {
+        let mut events = self.inner.try_poll()?;
+        events.retain(|ev| self.visible(ev));
+        Ok(events)
+    }
+    fn initial_state(&mut self) -> Vec<InputKind> {
+        let mut events = self.inner.initial_state();
+        events.retain(|ev| self.visible(ev));
+        events
+    }
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+    fn id(&self) -> &str {
+        self.inner.id()
+    }
+    fn metadata(&self) -> DeviceMeta {
+        self.inner.metadata()
+    }
+    fn describe(&self) -> Vec<ChannelDesc> {
+        self.descs.clone()
+    }
+    fn report_id_diagnostics(&self) -> Option<ReportIdDiagnostics> {
+        self.inner.report_id_diagnostics()
+    }
+    fn battery(&self) -> Option<BatteryStatus> {
+        self.inner.battery()
+    }
+    fn fixed_player_index(&self) -> Option<u8> {
+        self.inner.fixed_player_index()
+    }
+    fn report_time(&self) -> Option<Instant> {
+        self.inner.report_time()
+    }
+    fn set_rumble(&mut self, low: f32, high: f32) -> Result<()> {
+        self.inner.set_rumble(low, high)
+    }
+    fn send_output(&mut self, cmd: &OutputCommand) -> Result<()> {
+        self.inner.send_output(cmd)
+    }
+    fn upload_effect(&mut self, effect: &FfbEffect) -> Result<EffectHandle> {
+        self.inner.upload_effect(effect)
+    }
+    fn play_effect(&mut self, handle: EffectHandle, op: EffectOp) -> Result<()> {
+        self.inner.play_effect(handle, op)
+    }
+    fn capture_reports(&mut self, path: Option<&Path>) -> Result<()> {
+        self.inner.capture_reports(path)
+    }
+}
+
+/// Hide the channels `quirks` marks as hidden.
+///
+/// Returns `device` unchanged if none of its channels are hidden.
+pub fn hide_channels(device: Box<dyn Device>, quirks: &DeviceQuirks) -> Box<dyn Device> {
+    let (descs, hidden): (Vec<ChannelDesc>, Vec<ChannelDesc>) = device
+        .describe()
+        .into_iter()
+        .partition(|d| !quirks.is_hidden(d));
+    if hidden.is_empty() {
+        return device;
+    }
+    Box::new(HiddenChannels {
+        inner: device,
+        hidden: hidden.iter().map(|d| (d.kind, d.idx)).collect(),
+        descs,
+    })
+}
@@ -0,0 +1,104 @@
+//! Ring buffer of recent snapshots.
+//!
+//! Some input logic looks back in time: fighting-game input buffers ("was down-forward held
+//! in the last 8 frames?"), debounce analysis, or "what was the stick doing 100 ms ago".
+//! A [`SnapshotHistory`] keeps the last `capacity` [`Snapshot`]s with the time they were
+//! taken, newest last. Snapshots share unchanged device states, so a long history costs
+//! little more than the states that actually changed.
+//!
+//! The Manager records one entry per poll once enabled:
+//! ```no_run
+//! use std::time::{Duration, Instant};
+//! use stickup::Manager;
+//!
+//! let mut mgr = Manager::discover().expect("discover devices");
+//! mgr.enable_snapshot_history(120);
+//! mgr.poll_events();
+//! let history = mgr.snapshot_history().unwrap();
+//! if let Some(then) = history.at(Instant::now() - Duration::from_millis(100)) {
+//!     println!("{:?}", then.get("xinput:0").map(|s| s.get_axis("LX")));
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use crate::snapshot::Snapshot;
+
+/// Last `capacity` snapshots with timestamps (see module docs).
+#[derive(Clone, Debug)]
+pub struct SnapshotHistory {
+    capacity: usize,
+    entries: VecDeque<(Instant, Snapshot)>,
+}
+
+impl SnapshotHistory {
+    /// History keeping at most `capacity` snapshots (at least 1).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Maximum number of snapshots kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of snapshots kept.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no snapshot was recorded yet.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Record `snapshot` taken at `at`, dropping the oldest entry when full.
+    ///
+    /// Timestamps are expected to be non-decreasing.
+    pub fn push(&mut self, at: Instant, snapshot: Snapshot) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((at, snapshot));
+    }
+
+    /// Most recent snapshot.
+    pub fn latest(&self) -> Option<&Snapshot> {
+        self.entries.back().map(|(_, s)| s)
+    }
+
+    /// Snapshot `frames` entries before the latest (`0` = latest).
+    pub fn frames_ago(&self, frames: usize) -> Option<&Snapshot> {
+        let i = self.entries.len().checked_sub(frames + 1)?;
+        self.entries.get(i).map(|(_, s)| s)
+    }
+
+    /// State as of `at`: the newest snapshot taken at or before `at`.
+    ///
+    /// `None` if `at` predates every kept snapshot.
+    pub fn at(&self, at: Instant) -> Option<&Snapshot> {
+        let n = self.entries.partition_point(|(t, _)| *t <= at);
+        self.entries.get(n.checked_sub(1)?).map(|(_, s)| s)
+    }
+
+    /// Snapshots taken at or after `since`, oldest first.
+    pub fn since(&self, since: Instant) -> impl Iterator<Item = (Instant, &Snapshot)> {
+        let start = self.entries.partition_point(|(t, _)| *t < since);
+        self.entries.range(start..).map(|(t, s)| (*t, s))
+    }
+
+    /// All kept snapshots, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = (Instant, &Snapshot)> {
+        self.entries.iter().map(|(t, s)| (*t, s))
+    }
+
+    /// Forget every snapshot.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -0,0 +1,185 @@
+//! User idle detection (screensavers, kiosk attract modes).
+//!
+//! An [`IdleTracker`] watches input events and remembers when the user last did something:
+//! a button/key press or release, a hat change, touch input, or an axis moving by more than
+//! [`axis_threshold`](IdleTracker::axis_threshold) from where it last counted (so sensor
+//! jitter and slow drift do not keep the machine awake). Gyro/accelerometer, battery, and
+//! marker events never count.
+//!
+//! Whole device classes can be opted out with [`IdleTracker::ignore_role`] — e.g. mice on a
+//! kiosk that sits on a vibrating counter.
+//!
+//! The [`Manager`](crate::manager::Manager) owns one: query
+//! [`idle_time`](crate::manager::Manager::idle_time), or set
+//! [`set_idle_timeout`](crate::manager::Manager::set_idle_timeout) to receive
+//! [`UserIdle`](crate::manager::ManagerEvent::UserIdle) /
+//! [`UserActive`](crate::manager::ManagerEvent::UserActive) events.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use crate::event::InputKind;
+use crate::metadata::DeviceRole;
+
+/// Default axis travel (normalized units) that counts as activity.
+pub const DEFAULT_AXIS_THRESHOLD: f32 = 0.1;
+
+/// Idle state transition reported by [`IdleTracker`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdleChange {
+    /// Activity after an idle period.
+    Active,
+    /// No activity for the configured timeout.
+    Idle { idle_for: Duration },
+}
+
+/// Tracks the last user activity (see module docs).
+#[derive(Clone, Debug)]
+pub struct IdleTracker {
+    last_activity: Instant,
+    timeout: Option<Duration>,
+    idle: bool,
+    ignored: HashSet<DeviceRole>,
+    axis_threshold: f32,
+    /// Axis value at the last counted movement, per device and axis.
+    anchors: HashMap<String, HashMap<u16, f32>>,
+}
+
+impl Default for IdleTracker {
+    fn default() -> Self {
+        Self::new(Instant::now())
+    }
+}
+
+impl IdleTracker {
+    /// Tracker that considers `now` the last activity.
+    pub fn new(now: Instant) -> Self {
+        Self {
+            last_activity: now,
+            timeout: None,
+            idle: false,
+            ignored: HashSet::new(),
+            axis_threshold: DEFAULT_AXIS_THRESHOLD,
+            anchors: HashMap::new(),
+        }
+    }
+
+    /// Idle timeout for [`IdleChange::Idle`] (`None` = never report idle).
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Current idle timeout.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Axis travel that counts as activity.
+    pub fn axis_threshold(&self) -> f32 {
+        self.axis_threshold
+    }
+
+    /// Set the axis travel that counts as activity.
+    pub fn set_axis_threshold(&mut self, threshold: f32) {
+        self.axis_threshold = threshold.max(0.0);
+    }
+
+    /// Exclude (or re-include) a device class from activity.
+    pub fn ignore_role(&mut self, role: DeviceRole, ignored: bool) {
+        if ignored {
+            self.ignored.insert(role);
+        } else {
+            self.ignored.remove(&role);
+        }
+    }
+
+    /// Whether a device class is excluded.
+    pub fn is_ignored(&self, role: DeviceRole) -> bool {
+        self.ignored.contains(&role)
+    }
+
+    /// Feed one event from `device_id`. `role` is only called when some device class is
+    /// ignored. Returns [`IdleChange::Active`] when this event ends an idle period.
+    pub fn observe(
+        &mut self,
+        device_id: &str,
+        ev: &InputKind,
+        now: Instant,
+        role: impl FnOnce() -> Option<DeviceRole>,
+    ) -> Option<IdleChange> {
+        let active = match *ev {
+            InputKind::ButtonPressed { .. }
+            | InputKind::ButtonReleased { .. }
+            | InputKind::KeyPressed { .. }
+            | InputKind::KeyReleased { .. }
+            | InputKind::HatChanged { .. }
+            | InputKind::RelativeMoved { .. }
+            | InputKind::TouchMoved { .. } => true,
+            InputKind::AxisMoved { axis, value } => {
+                let axes = match self.anchors.get_mut(device_id) {
+                    Some(a) => a,
+                    None => self.anchors.entry(device_id.to_string()).or_default(),
+                };
+                match axes.get_mut(&axis) {
+                    Some(anchor) if (value - *anchor).abs() < self.axis_threshold => false,
+                    Some(anchor) => {
+                        *anchor = value;
+                        true
+                    }
+                    // First sample only sets the resting position.
+                    None => {
+                        axes.insert(axis, value);
+                        false
+                    }
+                }
+            }
+            InputKind::MotionChanged { .. }
+            | InputKind::BatteryChanged { .. }
+            | InputKind::Marker { .. } => false,
+        };
+        if !active {
+            return None;
+        }
+        if !self.ignored.is_empty() && role().is_some_and(|r| self.ignored.contains(&r)) {
+            return None;
+        }
+        self.mark_active(now)
+    }
+
+    /// Record activity from outside the event stream (touchscreen, network remote).
+    pub fn mark_active(&mut self, now: Instant) -> Option<IdleChange> {
+        self.last_activity = now;
+        std::mem::take(&mut self.idle).then_some(IdleChange::Active)
+    }
+
+    /// Report [`IdleChange::Idle`] once the timeout has passed without activity.
+    pub fn check(&mut self, now: Instant) -> Option<IdleChange> {
+        let timeout = self.timeout?;
+        let idle_for = self.idle_time(now);
+        if self.idle || idle_for < timeout {
+            return None;
+        }
+        self.idle = true;
+        Some(IdleChange::Idle { idle_for })
+    }
+
+    /// Time since the last activity.
+    pub fn idle_time(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.last_activity)
+    }
+
+    /// Instant of the last activity.
+    pub fn last_activity(&self) -> Instant {
+        self.last_activity
+    }
+
+    /// Whether [`IdleChange::Idle`] was reported and no activity followed yet.
+    pub fn is_idle(&self) -> bool {
+        self.idle
+    }
+
+    /// Forget per-device axis positions (e.g. after the device went away).
+    pub fn forget_device(&mut self, device_id: &str) {
+        self.anchors.remove(device_id);
+    }
+}
@@ -0,0 +1,136 @@
+//! Keyboard key identity and names.
+//!
+//! Keys are identified by their **hardware scancode** (PC scancode set 1, as delivered by
+//! Raw Input) plus the extended (`E0`) flag, so bindings don't depend on the keyboard layout.
+//! [`InputKind::KeyPressed`](crate::event::InputKind::KeyPressed) /
+//! [`KeyReleased`](crate::event::InputKind::KeyReleased) carry that pair.
+//!
+//! In [`ChannelDesc`](crate::event::ChannelDesc)s and [`DeviceState`](crate::binding::DeviceState)
+//! a key is a button channel whose index is [`key_index`] and whose label is [`key_label`]
+//! (`"key_001d"`). Labels stay stable for bindings; use [`key_name`] or [`label_display_name`]
+//! to show users `"Left Ctrl"` instead.
+//!
+//! Names describe the physical key on a US layout; they are not a text/character mapping.
+
+/// Pack a key identity into a button index (low 15 bits = scancode, high bit = extended).
+#[inline]
+pub fn key_index(scancode: u16, extended: bool) -> u16 {
+    let mut idx = scancode & 0x7FFF;
+    if extended {
+        idx |= 0x8000;
+    }
+    idx
+}
+
+/// Inverse of [`key_index`]: `(scancode, extended)`.
+#[inline]
+pub fn split_key_index(idx: u16) -> (u16, bool) {
+    (idx & 0x7FFF, idx & 0x8000 != 0)
+}
+
+/// Stable channel label for a key index (`"key_001d"`).
+pub fn key_label(idx: u16) -> String {
+    format!("key_{:04x}", idx)
+}
+
+/// Human-readable name of a key (`"Left Ctrl"`), if known.
+pub fn key_name(scancode: u16, extended: bool) -> Option<&'static str> {
+    const DIGITS: [&str; 10] = ["1", "2", "3", "4", "5", "6", "7", "8", "9", "0"];
+    const FKEYS: [&str; 10] = ["F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10"];
+    const F13_F23: [&str; 11] = [
+        "F13", "F14", "F15", "F16", "F17", "F18", "F19", "F20", "F21", "F22", "F23",
+    ];
+    const ROW_Q: [&str; 10] = ["Q", "W", "E", "R", "T", "Y", "U", "I", "O", "P"];
+    const ROW_A: [&str; 9] = ["A", "S", "D", "F", "G", "H", "J", "K", "L"];
+    const ROW_Z: [&str; 7] = ["Z", "X", "C", "V", "B", "N", "M"];
+
+    if extended {
+        return Some(match scancode {
+            0x10 => "Previous Track",
+            0x19 => "Next Track",
+            0x1C => "Numpad Enter",
+            0x1D => "Right Ctrl",
+            0x20 => "Mute",
+            0x22 => "Play/Pause",
+            0x24 => "Stop",
+            0x2E => "Volume Down",
+            0x30 => "Volume Up",
+            0x35 => "Numpad /",
+            0x37 => "Print Screen",
+            0x38 => "Right Alt",
+            0x47 => "Home",
+            0x48 => "Up",
+            0x49 => "Page Up",
+            0x4B => "Left",
+            0x4D => "Right",
+            0x4F => "End",
+            0x50 => "Down",
+            0x51 => "Page Down",
+            0x52 => "Insert",
+            0x53 => "Delete",
+            0x5B => "Left Win",
+            0x5C => "Right Win",
+            0x5D => "Menu",
+            _ => return None,
+        });
+    }
+    Some(match scancode {
+        0x01 => "Esc",
+        0x02..=0x0B => DIGITS[(scancode - 0x02) as usize],
+        0x0C => "-",
+        0x0D => "=",
+        0x0E => "Backspace",
+        0x0F => "Tab",
+        0x10..=0x19 => ROW_Q[(scancode - 0x10) as usize],
+        0x1A => "[",
+        0x1B => "]",
+        0x1C => "Enter",
+        0x1D => "Left Ctrl",
+        0x1E..=0x26 => ROW_A[(scancode - 0x1E) as usize],
+        0x27 => ";",
+        0x28 => "'",
+        0x29 => "`",
+        0x2A => "Left Shift",
+        0x2B => "\\",
+        0x2C..=0x32 => ROW_Z[(scancode - 0x2C) as usize],
+        0x33 => ",",
+        0x34 => ".",
+        0x35 => "/",
+        0x36 => "Right Shift",
+        0x37 => "Numpad *",
+        0x38 => "Left Alt",
+        0x39 => "Space",
+        0x3A => "Caps Lock",
+        0x3B..=0x44 => FKEYS[(scancode - 0x3B) as usize],
+        0x45 => "Num Lock",
+        0x46 => "Scroll Lock",
+        0x47 => "Numpad 7",
+        0x48 => "Numpad 8",
+        0x49 => "Numpad 9",
+        0x4A => "Numpad -",
+        0x4B => "Numpad 4",
+        0x4C => "Numpad 5",
+        0x4D => "Numpad 6",
+        0x4E => "Numpad +",
+        0x4F => "Numpad 1",
+        0x50 => "Numpad 2",
+        0x51 => "Numpad 3",
+        0x52 => "Numpad 0",
+        0x53 => "Numpad .",
+        0x56 => "ISO \\",
+        0x57 => "F11",
+        0x58 => "F12",
+        0x64..=0x6E => F13_F23[(scancode - 0x64) as usize],
+        0x76 => "F24",
+        _ => return None,
+    })
+}
+
+/// Human-readable name for a key channel label (`"key_001d"` → `"Left Ctrl"`).
+///
+/// Returns `None` for labels that aren't key labels or keys without a known name.
+pub fn label_display_name(label: &str) -> Option<&'static str> {
+    let hex = label.strip_prefix("key_")?;
+    let (scancode, extended) = split_key_index(u16::from_str_radix(hex, 16).ok()?);
+    key_name(scancode, extended)
+}
@@ -26,16 +26,58 @@
 //! ```
 //!
 //! # Modules
+//! - [`aim`] — gyro aiming (mouse-style deltas, virtual stick, ratcheting) and flick stick
+//! - [`arcade`] — arcade cabinet roles (coin/service/test/start), credits, coin lockout
+//! - [`default_profile`] — starter binding profiles generated from channel descriptors
+//! - [`derived`] — velocity/acceleration channels derived from axes
+//! - [`descriptor`] — platform-independent HID report descriptor parsing and decoding
 //! - [`device`] — core device trait implemented by backends
 //! - [`event`] — input events and channel descriptions
 //! - [`binding`] — device-agnostic mapping/transforms
+//! - [`bus`] — event bus with listeners and channel subscriptions
+//! - [`capture`] — "press any control to bind" capture
+//! - [`cheatsheet`] — printable Markdown/HTML binding cheat sheets
+//! - [`claims`] — cross-process device claims
+//! - [`controlmap`] — SVG control maps of bindings on device templates
+//! - [`drivers`] — detection of vJoy/ViGEmBus/HidHide
+//! - [`expr`] — expression mini-language for derived channels and transforms
+//! - [`fastpath`] — fixed-offset report decoding for simple single-report gamepads
+//! - [`ffb`] — force feedback effects (HID PID)
+//! - [`gamecontrollerdb`] — SDL GameControllerDB import (standard gamepad layout)
+//! - [`gate`] — synthetic buttons and axes computed from a device's channels
+//! - [`gestures`] — tap / hold / double-tap detection
+//! - [`output`] — output commands (light bar, player LEDs, adaptive triggers)
+//! - [`history`] — ring buffer of recent snapshots (input buffering, look-back queries)
+//! - [`idle`] — user idle detection (screensavers, attract modes)
+//! - [`keys`] — keyboard scancode identity and key names
 //! - [`metadata`] — device metadata struct
+//! - [`motion`] — gyro/accelerometer fusion into an orientation quaternion
+//! - `plugin` — sandboxed WASM transform plugins (feature `wasm-plugins`)
+//! - [`profiles`] — profile files with change detection (hot reload)
+//! - [`queue`] — bounded event queues with axis coalescing and drop statistics
+//! - [`quirks`] — per-device vendor usage names/semantics (TOML)
+//! - [`reconnect`] — identity continuity for devices replugged into another port
+//! - [`recorder`] — event recording (JSONL/binary) and replay devices
+//! - [`report_capture`] — raw HID report capture for bug reports
+//! - [`resolver`] — time-aware binding resolution (chords, gestures)
+//! - [`scenario`] — headless profile tests from scripted input scenarios
+//! - [`seat`] — multi-seat device partitioning (per-seat snapshots, bindings, events)
 //! - [`snapshot`] — per-frame view for bindings/UI (owned)
+//! - [`split`] — optional per-collection sub-device splitting
+//! - [`stats`] — per-channel statistics (range, histogram, reversals)
+//! - [`templates`] — device template registry (pictures, control positions, control names)
+//! - [`twist`] — 6-DOF twist output for teleoperation
+//! - [`validate`] — binding profile conflict detection and diagnostics
+//! - [`wear`] — wear diagnostics (actuation counts, rising bounce/noise)
 //! - [`backends`] — platform-specific implementations
 //! - [`Manager`] — high-level cross-device API
 //!
 //! # Feature flags
 //! - **`hid`** — enables the Windows HID/XInput backend (default in this build).
+//! - **`hot-reload`** — use a `notify` file watcher in [`profiles::ProfileManager`] instead of
+//!   interval polling.
+//! - **`wasm-plugins`** — load sandboxed WebAssembly transforms with `plugin::attach_plugin`
+//!   (adds a `wasmi` dependency).
 //! - **`virtual`** — reserved (no virtual-device backend is currently wired up).
 //!
 //! # Windows Raw Input
@@ -43,19 +85,64 @@
 //! - [`Manager::handle_wm_input`](crate::manager::Manager::handle_wm_input)
 //! - [`Manager::handle_raw_input_bytes`](crate::manager::Manager::handle_raw_input_bytes)
 //!
+//! Hosts without a message loop can call
+//! [`Manager::start_raw_input`](crate::manager::Manager::start_raw_input) instead, which runs a
+//! hidden message-only window on a background thread.
+//!
 //! ## Threading
 //! `Manager` owns live device handles and is intended to live on **one thread**.
 //! If multiple threads need to interact, route calls through a message-passing
 //! façade on a single “bridge” thread that owns the `Manager`. This avoids
 //! duplicate discoveries and honors backend thread-affinity.
 
+pub mod aim;
+pub mod arcade;
 pub mod backends;
 pub mod binding;
+pub mod bus;
+pub mod capture;
+pub mod cheatsheet;
+pub mod claims;
+pub mod controlmap;
+pub mod default_profile;
+pub mod derived;
+pub mod descriptor;
 pub mod device;
+pub mod drivers;
 pub mod event;
+pub mod expr;
+pub mod fastpath;
+pub mod ffb;
+pub mod gamecontrollerdb;
+pub mod gate;
+pub mod gestures;
+pub mod hide;
+pub mod history;
+pub mod idle;
+pub mod keys;
 pub mod manager;
 pub mod metadata;
+pub mod motion;
+pub mod output;
+#[cfg(feature = "wasm-plugins")]
+#[cfg_attr(docsrs, doc(cfg(feature = "wasm-plugins")))]
+pub mod plugin;
+pub mod profiles;
+pub mod queue;
+pub mod quirks;
+pub mod reconnect;
+pub mod recorder;
+pub mod report_capture;
+pub mod resolver;
+pub mod scenario;
+pub mod seat;
 pub mod snapshot;
+pub mod split;
+pub mod stats;
+pub mod templates;
+pub mod twist;
+pub mod validate;
+pub mod wear;
 
 /// Error and Result types for the crate.
 pub mod error {
@@ -63,15 +150,17 @@ pub mod error {
     //!
     //! Most public APIs that can fail return [`Result`](Result), notably:
     //! - [`Manager::discover`](crate::manager::Manager::discover)
+    //! - [`Device::try_poll`](crate::device::Device::try_poll)
     //!
-    //! Many operations are infallible after discovery (polling, snapshots), so this module
-    //! stays small by design.
+    //! Polling through the [`Manager`](crate::manager::Manager) stays infallible; per-device
+    //! read failures are collected and can be drained with
+    //! [`Manager::take_poll_errors`](crate::manager::Manager::take_poll_errors).
 
     /// Crate-wide error type.
     ///
-    /// StickUp keeps errors intentionally lightweight. Backend-specific details may be
-    /// mapped into [`Error::Other`] as a human-readable string.
-    #[derive(thiserror::Error, Debug)]
+    /// StickUp keeps errors intentionally lightweight: platform details are carried as plain
+    /// values (status codes, messages), never as platform error types.
+    #[derive(thiserror::Error, Debug, Clone)]
     pub enum Error {
         /// The requested backend is not available for this build/OS.
         ///
@@ -81,6 +170,63 @@ pub mod error {
         #[error("HID backend not available on this platform/build")]
         BackendUnavailable,
 
+        /// The device (or its backend) does not support the requested operation.
+        ///
+        /// Returned by optional output surfaces such as rumble when the device has no
+        /// matching actuator/output report.
+        #[error("{0} is not supported by this device")]
+        Unsupported(&'static str),
+
+        /// No managed device has the given id.
+        #[error("unknown device id: {0}")]
+        UnknownDevice(String),
+
+        /// Another process holds a claim on the device (see [`claims`](crate::claims)).
+        #[error("device {0} is claimed by another process")]
+        Claimed(String),
+
+        /// The player index is already assigned to another device.
+        #[error("player {player} is already assigned to {device}")]
+        PlayerTaken { player: u8, device: String },
+
+        /// No seat has the given name (see [`seat`](crate::seat)).
+        #[error("unknown seat: {0}")]
+        UnknownSeat(String),
+
+        /// A third-party driver is missing or unusable (see [`drivers`](crate::drivers)).
+        #[error("{driver} driver {problem}")]
+        Driver {
+            driver: crate::drivers::Driver,
+            problem: crate::drivers::DriverProblem,
+        },
+
+        /// A HID parsing call (`HidP_*` / `HidD_*`) failed.
+        ///
+        /// `status` is the raw `NTSTATUS` (HIDP calls) or `0` for `BOOLEAN` `HidD_*` calls.
+        #[error("{op} failed (status {status:#010x})")]
+        Hidp { op: &'static str, status: i32 },
+
+        /// The OS refused to open a HID device.
+        #[error("failed to open HID device {path}: {message}")]
+        HidOpen { path: String, message: String },
+
+        /// Reading from an open device failed (typically because it was unplugged).
+        #[error("read from {device} failed: {message}")]
+        Read { device: String, message: String },
+
+        /// Writing an output or feature report to an open device failed, or the device
+        /// rejected it.
+        #[error("write to {device} failed: {message}")]
+        Write { device: String, message: String },
+
+        /// Backend discovery could not run (e.g. the HID API failed to initialize).
+        #[error("device discovery failed: {0}")]
+        Discovery(String),
+
+        /// A blocking wait ran out of time (e.g. [`Manager::wait_for_device`](crate::manager::Manager::wait_for_device)).
+        #[error("timed out waiting for {0}")]
+        Timeout(String),
+
         /// Opaque backend error surfaced as a message.
         ///
         /// This is used when a backend wants to report a failure without exposing
@@ -105,12 +251,15 @@ pub use snapshot::Snapshot;
 // A tiny prelude for downstreams.
 pub mod prelude {
     pub use crate::binding::{
-        AxisCurve, AxisTransform, BindingOutput, BindingProfile, BindingRule, ControlPath,
-        ControlPath2D, ControlType, DeviceState,
+        ActionSet, AxisCurve, AxisTransform, BindingLayer, BindingOutput, BindingProfile,
+        BindingRule, ButtonAxisMode, ControlPath, ControlPath2D, ControlType, DeviceState,
+        InterlockEvent, InterlockEventKind, ModifierControl,
     };
     pub use crate::event::{ChannelDesc, ChannelKind, InputEvent, InputKind};
-    pub use crate::manager::{Manager, RescanReport};
+    pub use crate::gestures::{Gesture, GestureEvent, GestureTiming};
+    pub use crate::manager::{DiscoveryOptions, Manager, ManagerEvent, RescanReport};
     pub use crate::metadata::DeviceMeta;
+    pub use crate::resolver::BindingResolver;
     pub use crate::snapshot::Snapshot;
 }
 
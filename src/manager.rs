@@ -21,12 +21,11 @@
 //! message passing.
 //!
 //! ## Windows Raw Input (optional)
-//! On Windows, the host app may forward keyboard/mouse Raw Input (WM_INPUT) into `Manager`.
-//! Raw Input packets are parsed and queued as **injected events**, which are drained on the
-//! next call to [`poll_events`](Manager::poll_events).
-//!
-//! Note: the `*_shared` and `*_timed` polling helpers currently **do not** drain injected events.
-//! If you rely on WM_INPUT injection, use [`poll_events`](Manager::poll_events) (or add a drain step).
+//! On Windows, the host app may forward keyboard/mouse Raw Input (WM_INPUT) into `Manager`,
+//! or let the manager run its own pump ([`start_raw_input`](Manager::start_raw_input)).
+//! Each keyboard/mouse becomes a regular device on its first packet (see
+//! [`raw_devices`](crate::backends::windows::raw_devices)); its events are delivered by the
+//! next poll, like any other device's.
 //!
 //! ```no_run
 //! use stickup::Manager;
@@ -41,21 +40,58 @@
 //! }
 //! ```
 #[cfg(target_os = "windows")]
+use crate::backends::windows::raw_devices::{self, RawKeyboardDevice, RawMouseDevice};
+#[cfg(target_os = "windows")]
 use crate::backends::windows::raw_input;
-use crate::binding::DeviceState;
-use crate::device::Device;
-use crate::event::{ChannelDesc, ChannelKind, InputKind};
-use crate::metadata::DeviceMeta;
-use crate::Result;
 #[cfg(target_os = "windows")]
-use core::ffi::c_void;
+use crate::backends::windows::raw_input_pump::{RawInputOptions, RawInputPump};
+use crate::binding::{BindingOutput, BindingProfile, DeviceState, RequiredDevice};
+use crate::capture::{BindingCapture, CapturedControl};
+use crate::claims::{ClaimMode, DeviceClaim};
+use crate::derived::DerivedConfig;
+use crate::device::{BatteryStatus, Device, ReportIdDiagnostics};
+use crate::event::{
+    group_by_collection, ChannelDesc, ChannelGroup, ChannelKind, ChannelUnit, DeviceId, InputKind,
+};
+use crate::expr::Expr;
+use crate::ffb::{EffectHandle, EffectOp, FfbEffect};
+use crate::gamecontrollerdb::ControllerDb;
+use crate::gate::{Gate, GateKind, GATE_INDEX_BASE};
+use crate::history::SnapshotHistory;
+use crate::idle::{IdleChange, IdleTracker};
+use crate::metadata::{DeviceMeta, DeviceRole};
+use crate::output::OutputCommand;
+use crate::queue::{EventQueue, QueueStats};
+use crate::quirks::QuirkDb;
+use crate::resolver::BindingResolver;
+use crate::seat::Seat;
+use crate::snapshot::{Snapshot, SnapshotFilter};
+use crate::stats::StatsCollector;
+use crate::{Error, Result};
 use core::fmt;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+/// Device id under which [`Manager::add_marker`] markers are delivered.
+pub const MARKER_SOURCE: &str = "stickup:marker";
+
+/// How often empty XInput slots are checked for newly attached controllers.
+#[cfg(all(feature = "hid", target_os = "windows"))]
+const XINPUT_SLOT_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Raw hotplug transitions kept for [`Manager::take_hotplug_transitions`] (oldest dropped).
+const HOTPLUG_LOG_CAP: usize = 1024;
 
 type NameMap = HashMap<u16, String>;
 
+/// `(index, label)` pairs of `map` in index order.
+fn sorted_labels(map: &NameMap) -> Vec<(u16, String)> {
+    let mut v: Vec<(u16, String)> = map.iter().map(|(&i, n)| (i, n.clone())).collect();
+    v.sort();
+    v
+}
+
 #[derive(Default)]
 struct LabelMaps {
     axes: NameMap,
@@ -63,6 +99,20 @@ struct LabelMaps {
     hats: NameMap,
 }
 
+impl LabelMaps {
+    /// State key of a channel (label, or the default name if unlabeled).
+    fn label(&self, kind: ChannelKind, idx: u16) -> String {
+        let map = match kind {
+            ChannelKind::Axis => &self.axes,
+            ChannelKind::Button => &self.buttons,
+            ChannelKind::Hat => &self.hats,
+        };
+        map.get(&idx)
+            .cloned()
+            .unwrap_or_else(|| default_name(kind, idx))
+    }
+}
+
 fn default_name(kind: ChannelKind, idx: u16) -> String {
     match kind {
         ChannelKind::Axis => format!("axis{idx}"),
@@ -74,10 +124,7 @@ fn default_name(kind: ChannelKind, idx: u16) -> String {
 fn build_labels(descs: &[ChannelDesc]) -> LabelMaps {
     let mut lm = LabelMaps::default();
     for d in descs {
-        let name = d
-            .name
-            .clone()
-            .unwrap_or_else(|| default_name(d.kind.clone(), d.idx));
+        let name = d.state_name();
         match d.kind {
             ChannelKind::Axis => {
                 lm.axes.insert(d.idx, name);
@@ -93,12 +140,154 @@ fn build_labels(descs: &[ChannelDesc]) -> LabelMaps {
     lm
 }
 
+/// Device lifecycle notification, drained with [`Manager::take_manager_events`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ManagerEvent {
+    /// A device became available: added by a rescan or slot watch, or reattached
+    /// (e.g. an XInput pad plugged back into its slot).
+    DeviceConnected { id: String },
+    /// A device went away: removed by a rescan, or detached while its entry is kept.
+    ///
+    /// The device's [`DeviceState`] is reset to neutral.
+    DeviceDisconnected { id: String },
+    /// The device assigned to `player` went away.
+    ///
+    /// High priority: queued ahead of other pending events. The player keeps the device
+    /// assignment; if the same device returns, [`PlayerDeviceRestored`](Self::PlayerDeviceRestored)
+    /// follows. To hand the player another controller ("press A to reconnect"), use
+    /// [`Manager::reassign_player`].
+    PlayerDeviceLost { player: u8, id: String },
+    /// The device assigned to `player` came back.
+    PlayerDeviceRestored { player: u8, id: String },
+    /// A required device (see [`Manager::require_devices`]) is not present. Also reported
+    /// for requirements that are unmet when they are declared.
+    RequiredDeviceMissing { name: String },
+    /// A missing required device is present again, as device `id`.
+    RequiredDeviceRestored { name: String, id: String },
+    /// Readiness changed: `true` once every required device is present.
+    ReadinessChanged { ready: bool },
+    /// No user activity for the idle timeout (see [`Manager::set_idle_timeout`]).
+    UserIdle { idle_for: Duration },
+    /// User activity after [`UserIdle`](Self::UserIdle).
+    UserActive,
+    /// A poll ran past its time budget (see [`Manager::set_poll_budget`]); the last
+    /// `deferred` devices were left for the next poll.
+    PollBudgetExceeded { elapsed: Duration, deferred: usize },
+}
+
+/// One raw connect/disconnect observed by the Manager, before de-bouncing.
+///
+/// See [`Manager::set_hotplug_debounce`]; flaps that settle back within the debounce window
+/// produce no [`ManagerEvent`] but are still listed here.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HotplugTransition {
+    pub id: String,
+    /// `true` for an arrival/reattach, `false` for a removal/detach.
+    pub connected: bool,
+    pub at: Instant,
+}
+
+/// Criteria selecting devices, e.g. for [`Manager::wait_for_device`].
+///
+/// Unset criteria match anything; all set criteria must match.
+///
+/// ```
+/// use stickup::manager::DeviceMatcher;
+/// use stickup::metadata::DeviceRole;
+///
+/// let pedals = DeviceMatcher::new().vid(0x231d).name("rudder");
+/// let any_pad = DeviceMatcher::new().role(DeviceRole::Gamepad);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct DeviceMatcher {
+    pub vid: Option<u16>,
+    pub pid: Option<u16>,
+    /// Case-insensitive substring of the device name.
+    pub name: Option<String>,
+    pub role: Option<DeviceRole>,
+}
+
+impl DeviceMatcher {
+    /// Matcher accepting any device.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require this USB vendor id.
+    pub fn vid(mut self, vid: u16) -> Self {
+        self.vid = Some(vid);
+        self
+    }
+
+    /// Require this USB product id.
+    pub fn pid(mut self, pid: u16) -> Self {
+        self.pid = Some(pid);
+        self
+    }
+
+    /// Require the device name to contain `name` (case-insensitive).
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Require this [`DeviceRole`].
+    pub fn role(mut self, role: DeviceRole) -> Self {
+        self.role = Some(role);
+        self
+    }
+
+    /// Does `info` satisfy every set criterion?
+    pub fn matches(&self, info: &ManagedInfo) -> bool {
+        if self.vid.is_some() && self.vid != info.meta.vid {
+            return false;
+        }
+        if self.pid.is_some() && self.pid != info.meta.pid {
+            return false;
+        }
+        if self.role.is_some_and(|role| role != info.meta.role()) {
+            return false;
+        }
+        match &self.name {
+            Some(name) => info.name.to_lowercase().contains(&name.to_lowercase()),
+            None => true,
+        }
+    }
+}
+
+impl fmt::Display for DeviceMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(vid) = self.vid {
+            parts.push(format!("vid {vid:04x}"));
+        }
+        if let Some(pid) = self.pid {
+            parts.push(format!("pid {pid:04x}"));
+        }
+        if let Some(name) = &self.name {
+            parts.push(format!("name \"{name}\""));
+        }
+        if let Some(role) = self.role {
+            parts.push(format!("role {role:?}"));
+        }
+        if parts.is_empty() {
+            write!(f, "any device")
+        } else {
+            write!(f, "device with {}", parts.join(", "))
+        }
+    }
+}
+
 /// Minimal info about a managed device.
 ///
 /// Intended for UIs/tooling (device picker lists, rescan reporting, etc.).
 #[derive(Clone, Debug)]
 pub struct ManagedInfo {
     pub id: String,
+    /// Interned handle of `id` (see [`DeviceId`]).
+    pub handle: DeviceId,
     pub name: String,
     pub meta: DeviceMeta,
 }
@@ -122,81 +311,244 @@ impl fmt::Display for ManagedInfo {
 pub struct Manager {
     devices: Vec<Box<dyn Device>>,
     /// Stable `(axis/button/hat index -> label)` per device.
-    labels: HashMap<String, LabelMaps>,
-    /// Last-known per-device state.
-    states: HashMap<String, DeviceState>,
+    labels: HashMap<DeviceId, LabelMaps>,
+    /// Last-known per-device state; its id table interns every device the Manager has seen.
+    states: Snapshot,
+    /// Parallel to `devices`.
     infos: Vec<ManagedInfo>,
     /// Cached backend descriptors per device (from `Device::describe()`).
-    descs: HashMap<String, Vec<ChannelDesc>>,
-    /// Host-injected events (e.g., WM_INPUT keyboard/mouse) drained on next `poll_events()`.
+    descs: HashMap<DeviceId, Vec<ChannelDesc>>,
+    /// Injected events (timeline markers) drained on next `poll_events()`.
     injected: Vec<(String, InputKind)>,
+    /// Options used for discovery (re-applied on rescan).
+    options: DiscoveryOptions,
+    /// Optional subscription limiting what [`snapshot`](Manager::snapshot) copies.
+    snapshot_filter: Option<SnapshotFilter>,
+    /// Per-device read failures from the last polls (see `take_poll_errors`).
+    poll_errors: Vec<(String, Error)>,
+    /// Pending lifecycle notifications (see `take_manager_events`).
+    manager_events: Vec<ManagerEvent>,
+    /// Devices whose entry is kept but which reported `is_connected() == false`.
+    detached: HashSet<DeviceId>,
+    /// Devices whose last read failed, with the state they held before it was released.
+    read_failed: HashMap<DeviceId, DeviceState>,
+    /// Player index per device (see `assign_player`).
+    players: HashMap<DeviceId, u8>,
+    /// Seats in creation order (see `add_seat`).
+    seats: Vec<Seat>,
+    /// Cross-process claims held by this manager (see [`claims`](crate::claims)).
+    claims: HashMap<DeviceId, DeviceClaim>,
+    /// Active binding action sets (see `activate_action_set`).
+    action_sets: Vec<String>,
+    /// Presence must be stable this long before connection events fire (zero = immediately).
+    hotplug_debounce: Duration,
+    /// Unsettled presence per device: `(present, since, last reported presence)`.
+    pending_presence: HashMap<DeviceId, (bool, Instant, bool)>,
+    /// Raw transitions (see `take_hotplug_transitions`).
+    hotplug_log: Vec<HotplugTransition>,
+    /// Per-channel statistics, if enabled (see `enable_channel_stats`).
+    stats: Option<StatsCollector>,
+    /// Bounded event queue, if enabled (see `enable_event_queue`).
+    event_queue: Option<EventQueue>,
+    /// Recent snapshots, if enabled (see `enable_snapshot_history`).
+    history: Option<SnapshotHistory>,
+    /// Devices removed by a rescan, matched against serial-less newcomers (see `reconnect`).
+    departed: Vec<ManagedInfo>,
+    /// Gates per device (see `add_gate`).
+    gates: HashMap<DeviceId, Vec<Gate>>,
+    /// Last user activity and idle reporting (see `idle_time`).
+    idle: IdleTracker,
+    /// Declared required devices (see `require_devices`).
+    required: Vec<RequiredDevice>,
+    /// Device id satisfying each requirement (parallel to `required`).
+    required_by: Vec<Option<DeviceId>>,
+    /// Reference point for [`InputEvent::timestamp`](crate::event::InputEvent::timestamp).
+    epoch: Instant,
+    /// Next [`InputEvent::seq`](crate::event::InputEvent::seq).
+    next_seq: u64,
+    /// Time limit per poll (see `set_poll_budget`).
+    poll_budget: Option<Duration>,
+    /// Index of the first device the next poll visits.
+    poll_cursor: usize,
+    /// Rotate the starting device every poll (see `set_round_robin`).
+    round_robin: bool,
+    /// Managed Raw Input pump, if started (see `start_raw_input`).
+    #[cfg(target_os = "windows")]
+    raw_pump: Option<RawInputPump>,
+    /// Raw Input handle -> id and sink of the keyboard/mouse device it feeds.
+    #[cfg(target_os = "windows")]
+    raw_handles: HashMap<usize, (String, raw_devices::RawSink)>,
+    /// HID arrival/removal notifications, if enabled (see `watch_hotplug`).
+    #[cfg(all(feature = "hid", target_os = "windows"))]
+    hotplug: Option<crate::backends::windows::hotplug::HotplugWatcher>,
+    /// Last time empty XInput slots were checked for new controllers.
+    #[cfg(all(feature = "hid", target_os = "windows"))]
+    last_slot_check: Instant,
+}
+
+/// Options controlling how [`Manager::discover_with`] and [`Manager::rescan`] build the
+/// device list.
+#[derive(Clone, Debug, Default)]
+pub struct DiscoveryOptions {
+    /// Split devices whose channels span several HID link collections into one logical
+    /// device per collection (see [`split_by_collection`](crate::split::split_by_collection)).
+    pub split_collections: bool,
+    /// Per-device quirks (vendor usage names/semantics, hidden channels).
+    pub quirks: QuirkDb,
+    /// Skip devices another process has claimed exclusively (see [`claims`](crate::claims)).
+    pub respect_claims: bool,
+    /// SDL GameControllerDB mappings; matching HID gamepads report the standard layout
+    /// (see [`gamecontrollerdb`](crate::gamecontrollerdb)).
+    pub controller_db: Option<ControllerDb>,
+    /// Velocity/acceleration channels added to every device's axes
+    /// (see [`derived`](crate::derived)).
+    pub derived: Option<DerivedConfig>,
+}
+
+impl DiscoveryOptions {
+    /// Enable/disable per-collection sub-device splitting.
+    pub fn split_collections(mut self, on: bool) -> Self {
+        self.split_collections = on;
+        self
+    }
+
+    /// Use `quirks` for vendor usage naming/semantics.
+    pub fn quirks(mut self, quirks: QuirkDb) -> Self {
+        self.quirks = quirks;
+        self
+    }
+
+    /// Skip devices held under [`ClaimMode::Exclusive`] by another process.
+    pub fn respect_claims(mut self, on: bool) -> Self {
+        self.respect_claims = on;
+        self
+    }
+
+    /// Remap HID gamepads with a mapping in `db` to the standard gamepad layout.
+    ///
+    /// XInput and Raw Input devices are left as they are (they already use fixed layouts).
+    pub fn controller_db(mut self, db: ControllerDb) -> Self {
+        self.controller_db = Some(db);
+        self
+    }
+
+    /// Add derived velocity/acceleration axes to every device (see [`derived`](crate::derived)).
+    pub fn derived_channels(mut self, config: DerivedConfig) -> Self {
+        self.derived = Some(config);
+        self
+    }
+
+    /// Probe enabled backends with these options applied.
+    fn probe(&self) -> Result<Vec<Box<dyn Device>>> {
+        Ok(self.apply(crate::backends::try_probe_devices_with_quirks(
+            &self.quirks,
+        )?))
+    }
+
+    /// Post-process probed devices according to these options.
+    fn apply(&self, devices: Vec<Box<dyn Device>>) -> Vec<Box<dyn Device>> {
+        let devices = devices.into_iter().map(|dev| {
+            let meta = dev.metadata();
+            match (meta.vid, meta.pid) {
+                (Some(vid), Some(pid)) => match self.quirks.for_device(vid, pid) {
+                    Some(q) => crate::hide::hide_channels(dev, q),
+                    None => dev,
+                },
+                _ => dev,
+            }
+        });
+        let devices = devices.map(|dev| {
+            let Some(db) = &self.controller_db else {
+                return dev;
+            };
+            let meta = dev.metadata();
+            if matches!(meta.bus.as_deref(), Some("xinput" | "rawinput")) {
+                return dev;
+            }
+            match (meta.vid, meta.pid) {
+                (Some(vid), Some(pid)) => match db.find(vid, pid) {
+                    Some(mapping) => crate::gamecontrollerdb::map_gamepad(dev, mapping),
+                    None => dev,
+                },
+                _ => dev,
+            }
+        });
+        let devices = devices.map(|dev| match &self.derived {
+            Some(cfg) => crate::derived::derive_motion(dev, cfg),
+            None => dev,
+        });
+        let devices = devices.filter(|dev| {
+            !self.respect_claims
+                || crate::claims::claimed_by_other(dev.id()) != Some(ClaimMode::Exclusive)
+        });
+        if !self.split_collections {
+            return devices.collect();
+        }
+        devices
+            .flat_map(crate::split::split_by_collection)
+            .collect()
+    }
 }
 
 impl Manager {
     /// Discover devices using enabled backends.
     ///
     /// This is the typical entry point for applications. It probes enabled backends,
-    /// seeds neutral device state (so snapshots have stable keys immediately),
-    /// and caches channel descriptors for UI/binding use.
+    /// seeds device state (neutral, then each device's current values from
+    /// [`Device::initial_state`], so snapshots have stable keys and resting throttles read
+    /// correctly immediately), and caches channel descriptors for UI/binding use.
+    ///
+    /// Fails with [`Error::Discovery`] if an enabled backend cannot
+    /// be initialized. Individual devices that fail to open are skipped.
     pub fn discover() -> Result<Self> {
-        let devices: Vec<Box<dyn Device>> = crate::backends::probe_devices();
-        let mut labels: HashMap<String, LabelMaps> = HashMap::new();
-        let mut states: HashMap<String, DeviceState> = HashMap::new();
-        let mut infos: Vec<ManagedInfo> = Vec::new();
-        let mut descs: HashMap<String, Vec<ChannelDesc>> = HashMap::new();
-
-        for d in devices.iter() {
-            let id = d.id().to_string();
-            let name = d.name().to_string();
-            let meta = d.metadata();
-            let desc = d.describe();
-            let lm = build_labels(&desc);
-            labels.insert(id.clone(), lm);
-            // cache descriptors
-            descs.insert(id.clone(), desc.clone());
-            // seed neutral states so UI has values immediately
-            let mut st = DeviceState::default();
-            if let Some(lbl) = labels.get(&id) {
-                seed_neutral(&mut st, lbl, &desc);
-            }
-            states.insert(id.clone(), st);
-            infos.push(ManagedInfo { id, name, meta });
-        }
+        Self::discover_with(DiscoveryOptions::default())
+    }
 
-        Ok(Self {
-            devices,
-            labels,
-            states,
-            infos,
-            descs,
-            injected: Vec::new(),
-        })
+    /// Discover devices with explicit [`DiscoveryOptions`].
+    ///
+    /// The options are kept and re-applied by [`rescan`](Manager::rescan).
+    pub fn discover_with(options: DiscoveryOptions) -> Result<Self> {
+        let devices = options.probe()?;
+        let mut mgr = Self::from_devices(devices);
+        mgr.options = options;
+        Ok(mgr)
     }
 
     /// Construct from already created devices.
     ///
     /// This is mainly useful for tests, custom backend composition, or embedding StickUp
     /// into a host that manages device creation separately.
-    pub fn from_devices(devices: Vec<Box<dyn Device>>) -> Self {
-        let mut labels: HashMap<String, LabelMaps> = HashMap::new();
-        let mut states: HashMap<String, DeviceState> = HashMap::new();
+    pub fn from_devices(mut devices: Vec<Box<dyn Device>>) -> Self {
+        let mut labels: HashMap<DeviceId, LabelMaps> = HashMap::new();
+        let mut states = Snapshot::default();
         let mut infos: Vec<ManagedInfo> = Vec::new();
-        let mut descs: HashMap<String, Vec<ChannelDesc>> = HashMap::new();
-        for d in devices.iter() {
+        let mut descs: HashMap<DeviceId, Vec<ChannelDesc>> = HashMap::new();
+        for d in devices.iter_mut() {
             let id = d.id().to_string();
+            let handle = states.intern(&id);
             let name = d.name().to_string();
             let meta = d.metadata();
             let desc = d.describe();
             let lm = build_labels(&desc);
-            labels.insert(id.clone(), lm);
-            descs.insert(id.clone(), desc.clone());
             let mut st = DeviceState::default();
-            if let Some(lbl) = labels.get(&id) {
-                seed_neutral(&mut st, lbl, &desc);
-            }
-            states.insert(id.clone(), st);
-            infos.push(ManagedInfo { id, name, meta });
+            seed_neutral(&mut st, &lm, &desc);
+            prime_state(&mut st, &lm, &d.initial_state());
+            labels.insert(handle, lm);
+            descs.insert(handle, desc);
+            states.insert(handle, st);
+            infos.push(ManagedInfo {
+                id,
+                handle,
+                name,
+                meta,
+            });
         }
+        let detached = devices
+            .iter()
+            .zip(&infos)
+            .filter(|(d, _)| !d.is_connected())
+            .map(|(_, i)| i.handle)
+            .collect();
         Self {
             devices,
             labels,
@@ -204,6 +556,40 @@ impl Manager {
             infos,
             descs,
             injected: Vec::new(),
+            options: DiscoveryOptions::default(),
+            snapshot_filter: None,
+            poll_errors: Vec::new(),
+            manager_events: Vec::new(),
+            detached,
+            read_failed: HashMap::new(),
+            players: HashMap::new(),
+            seats: Vec::new(),
+            action_sets: Vec::new(),
+            hotplug_debounce: Duration::ZERO,
+            pending_presence: HashMap::new(),
+            hotplug_log: Vec::new(),
+            stats: None,
+            event_queue: None,
+            history: None,
+            departed: Vec::new(),
+            gates: HashMap::new(),
+            required: Vec::new(),
+            required_by: Vec::new(),
+            epoch: Instant::now(),
+            idle: IdleTracker::default(),
+            next_seq: 0,
+            poll_budget: None,
+            poll_cursor: 0,
+            round_robin: false,
+            claims: HashMap::new(),
+            #[cfg(target_os = "windows")]
+            raw_pump: None,
+            #[cfg(target_os = "windows")]
+            raw_handles: HashMap::new(),
+            #[cfg(all(feature = "hid", target_os = "windows"))]
+            hotplug: None,
+            #[cfg(all(feature = "hid", target_os = "windows"))]
+            last_slot_check: Instant::now(),
         }
     }
 
@@ -213,235 +599,1854 @@ impl Manager {
     /// at discovery/rescan time. They describe the *shape* of the device (axis/button/hat indices,
     /// optional names, logical ranges, usages when available).
     pub fn channels(&self, device_id: &str) -> Option<&[ChannelDesc]> {
-        self.descs.get(device_id).map(|v| v.as_slice())
+        self.id_of(device_id)
+            .and_then(|id| self.descs.get(&id))
+            .map(|v| v.as_slice())
     }
 
-    /// Poll all devices and yield `(device_id, event)` pairs.
+    /// Starter binding profile for a device, generated from its channels (see
+    /// [`default_profile`](crate::default_profile)).
+    pub fn default_profile(&self, device_id: &str) -> Option<BindingProfile> {
+        self.channels(device_id)
+            .map(|channels| BindingProfile::from_channels(device_id, channels))
+    }
+
+    /// Channel descriptors for a device grouped by HID link collection.
     ///
-    /// This updates internal per-device [`DeviceState`] and returns per-change deltas.
-    /// It also drains any host-injected events queued via the Windows Raw Input helpers.
-    pub fn poll_events(&mut self) -> Vec<(String, InputKind)> {
-        let mut out = Vec::new();
-        // 1) Poll normal devices.
-        for i in 0..self.devices.len() {
-            let (id, events) = {
-                let d = &mut self.devices[i];
-                (d.id().to_string(), d.poll())
-            };
-            for ev in events.into_iter() {
-                self.apply_event(&id, &ev);
-                out.push((id.clone(), ev));
-            }
-        }
+    /// Lets UIs show complex devices (HOTAS bases, grips) as named clusters instead of one
+    /// flat list. See [`group_by_collection`](crate::event::group_by_collection).
+    pub fn channel_groups(&self, device_id: &str) -> Option<Vec<ChannelGroup<'_>>> {
+        self.channels(device_id).map(group_by_collection)
+    }
 
-        // 2) Drain host-injected events (e.g., WM_INPUT keyboard).
-        let injected = std::mem::take(&mut self.injected);
-        if !injected.is_empty() {
-            //eprintln!("[stickup] draining injected: {} event(s)", injected.len());
-        }
-        for (id, ev) in injected {
-            self.apply_event(&id, &ev);
-            out.push((id, ev));
-        }
-        out
+    /// Report-ID mismatch diagnostics for a device, if its backend tracks them.
+    ///
+    /// Useful for device reports: a non-clean result means the device's reports did not line
+    /// up with its descriptor and decoding relied on a fallback path.
+    pub fn report_id_diagnostics(&self, device_id: &str) -> Option<ReportIdDiagnostics> {
+        self.devices
+            .iter()
+            .find(|d| d.id() == device_id)
+            .and_then(|d| d.report_id_diagnostics())
     }
 
-    /// Like [`poll_events`], but returns shared ids to avoid per-event `String` clone.
-    /// This is additive and does not change existing APIs.
+    /// Battery state of a device (see [`Device::battery`]).
     ///
-    /// Note: this currently polls *device backends only* and does **not** drain host-injected
-    /// (WM_INPUT) events. If you use Raw Input injection, prefer [`poll_events`](Manager::poll_events).
-    pub fn poll_events_shared(&mut self) -> Vec<(Arc<str>, InputKind)> {
-        let mut out = Vec::new();
-        for i in 0..self.devices.len() {
-            let (id_string, events) = {
-                let d = &mut self.devices[i];
-                (d.id().to_string(), d.poll())
-            };
-            // Create a shared id once per device for this batch
-            let id_shared: Arc<str> = Arc::from(id_string.as_str());
-            for ev in events.into_iter() {
-                self.apply_event(&id_string, &ev);
-                out.push((id_shared.clone(), ev));
-            }
-        }
-        out
+    /// Returns `None` for unknown ids and devices without battery reporting.
+    pub fn battery(&self, device_id: &str) -> Option<BatteryStatus> {
+        self.devices
+            .iter()
+            .find(|d| d.id() == device_id)
+            .and_then(|d| d.battery())
     }
 
-    /// Poll with timestamps.
+    /// Drive a device's rumble motors (see [`Device::set_rumble`]).
     ///
-    /// Produces the same device-poll deltas as [`poll_events`](Manager::poll_events),
-    /// but wraps each event with a capture timestamp.
+    /// Returns [`Error::UnknownDevice`] if no managed device has `device_id`,
+    /// [`Error::Claimed`] if another process holds a claim on it, or
+    /// [`Error::Unsupported`] if the device has no rumble output.
+    pub fn set_rumble(&mut self, device_id: &str, low: f32, high: f32) -> Result<()> {
+        self.output_device_mut(device_id)?.set_rumble(low, high)
+    }
+
+    /// Send an output command (light bar, player LEDs, adaptive triggers) to a device.
     ///
-    /// Note: this currently does **not** include drained injected (WM_INPUT) events.
-    pub fn poll_events_timed(&mut self) -> Vec<(String, crate::event::InputEvent)> {
-        let mut out = Vec::new();
+    /// See [`Device::send_output`].
+    pub fn send_output(&mut self, device_id: &str, cmd: &OutputCommand) -> Result<()> {
+        self.output_device_mut(device_id)?.send_output(cmd)
+    }
 
-        for i in 0..self.devices.len() {
-            let (id, events) = {
-                let d = &mut self.devices[i];
-                (d.id().to_string(), d.poll())
-            };
-            let now = Instant::now();
-            for ev in events.into_iter() {
-                self.apply_event(&id, &ev);
-                out.push((id.clone(), crate::event::InputEvent { at: now, kind: ev }));
-            }
-        }
+    /// Upload a force feedback effect to a device (see [`Device::upload_effect`]).
+    pub fn upload_effect(&mut self, device_id: &str, effect: &FfbEffect) -> Result<EffectHandle> {
+        self.output_device_mut(device_id)?.upload_effect(effect)
+    }
 
-        out
+    /// Start/stop an uploaded force feedback effect (see [`Device::play_effect`]).
+    pub fn play_effect(
+        &mut self,
+        device_id: &str,
+        handle: EffectHandle,
+        op: EffectOp,
+    ) -> Result<()> {
+        self.output_device_mut(device_id)?.play_effect(handle, op)
     }
 
-    /// Timestamped polling with shared ids (no per-event `String` clone).
+    /// Start (`Some(path)`) or stop (`None`) capturing a device's raw input reports to a file
+    /// for bug reports (see [`report_capture`](crate::report_capture)).
     ///
-    /// Note: this currently does **not** include drained injected (WM_INPUT) events.
-    pub fn poll_events_timed_shared(&mut self) -> Vec<(Arc<str>, crate::event::InputEvent)> {
-        let mut out = Vec::new();
-        for i in 0..self.devices.len() {
-            let (id_string, events) = {
-                let d = &mut self.devices[i];
-                (d.id().to_string(), d.poll())
-            };
-            let id_shared: Arc<str> = Arc::from(id_string.as_str());
-            let now = Instant::now();
-            for ev in events.into_iter() {
-                self.apply_event(&id_string, &ev);
-                out.push((
-                    id_shared.clone(),
-                    crate::event::InputEvent { at: now, kind: ev },
-                ));
-            }
+    /// Returns [`Error::UnknownDevice`] for unknown ids and [`Error::Unsupported`] for devices
+    /// that do not read HID reports (XInput, Raw Input). Capture stops on rescan.
+    pub fn capture_reports(
+        &mut self,
+        device_id: &str,
+        path: Option<impl AsRef<std::path::Path>>,
+    ) -> Result<()> {
+        let path = path.as_ref().map(|p| p.as_ref());
+        self.device_mut(device_id)?.capture_reports(path)
+    }
+
+    fn device_mut(&mut self, device_id: &str) -> Result<&mut Box<dyn Device>> {
+        self.devices
+            .iter_mut()
+            .find(|d| d.id() == device_id)
+            .ok_or_else(|| Error::UnknownDevice(device_id.to_string()))
+    }
+
+    /// [`device_mut`](Self::device_mut) for output calls: refuses devices claimed elsewhere.
+    fn output_device_mut(&mut self, device_id: &str) -> Result<&mut Box<dyn Device>> {
+        if self.claimed_elsewhere(device_id).is_some() {
+            return Err(Error::Claimed(device_id.to_string()));
         }
-        out
+        self.device_mut(device_id)
     }
 
-    fn apply_event(&mut self, id: &str, ev: &InputKind) {
-        let st = self.states.entry(id.to_string()).or_default();
-        let Some(lbl) = self.labels.get(id) else {
-            return;
-        };
-        match *ev {
-            InputKind::AxisMoved { axis, value } => {
-                let k = lbl
-                    .axes
-                    .get(&axis)
-                    .cloned()
-                    .unwrap_or_else(|| default_name(ChannelKind::Axis, axis));
-                st.axes.insert(k, value);
-            }
-            InputKind::ButtonPressed { button } => {
-                let k = lbl
-                    .buttons
-                    .get(&button)
-                    .cloned()
-                    .unwrap_or_else(|| default_name(ChannelKind::Button, button));
-                st.buttons.insert(k, true);
-            }
-            InputKind::ButtonReleased { button } => {
-                let k = lbl
-                    .buttons
-                    .get(&button)
-                    .cloned()
-                    .unwrap_or_else(|| default_name(ChannelKind::Button, button));
-                st.buttons.insert(k, false);
-            }
-            InputKind::HatChanged { hat, value } => {
-                let k = lbl
-                    .hats
-                    .get(&hat)
-                    .cloned()
-                    .unwrap_or_else(|| default_name(ChannelKind::Hat, hat));
-                st.hats.insert(k, value);
+    /// Handle of a managed device, or [`Error::UnknownDevice`].
+    fn managed_id(&self, device_id: &str) -> Result<DeviceId> {
+        self.infos
+            .iter()
+            .find(|i| i.id == device_id)
+            .map(|i| i.handle)
+            .ok_or_else(|| Error::UnknownDevice(device_id.to_string()))
+    }
+
+    /// Player index (0-based) assigned to a device, if any.
+    pub fn player_index(&self, device_id: &str) -> Option<u8> {
+        self.players.get(&self.id_of(device_id)?).copied()
+    }
+
+    /// Device assigned to player `index`, if any.
+    pub fn player_device(&self, index: u8) -> Option<&str> {
+        self.players
+            .iter()
+            .find(|(_, &p)| p == index)
+            .and_then(|(&id, _)| self.states.key_of(id))
+    }
+
+    /// Assign player `index` to a device and show it on the hardware where possible
+    /// (player LEDs and light bar color; see [`output::player_led_mask`](crate::output::player_led_mask)).
+    ///
+    /// A device holds at most one index; assigning a new one replaces the old. Fails with
+    /// [`Error::PlayerTaken`] if another device has `index` (use
+    /// [`steal_player`](Manager::steal_player) to take it over). Devices that can't display
+    /// an index still get the assignment.
+    pub fn assign_player(&mut self, device_id: &str, index: u8) -> Result<()> {
+        let id = self.managed_id(device_id)?;
+        if let Some(other) = self.player_device(index) {
+            if other != device_id {
+                return Err(Error::PlayerTaken {
+                    player: index,
+                    device: other.to_string(),
+                });
             }
+            return Ok(());
         }
+        self.players.insert(id, index);
+        self.show_player(device_id, Some(index));
+        Ok(())
     }
 
-    /// Rescan devices and report adds/removes. Preserves existing state where possible.
+    /// Assign player `index` to a device, releasing it from its current holder.
     ///
-    /// Devices are matched by `device_id` (backend-provided stable id). For devices that still
-    /// exist after rescan, prior [`DeviceState`] is preserved and re-seeded to ensure stable keys.
-    pub fn rescan(&mut self) -> RescanReport {
-        let old_ids: HashSet<_> = self.infos.iter().map(|i| i.id.clone()).collect();
-        let old_states = self.states.clone();
-
-        let new_devs = crate::backends::probe_devices();
-        let mut new_labels: HashMap<String, LabelMaps> = HashMap::new();
-        let mut new_states: HashMap<String, DeviceState> = HashMap::new();
-        let mut new_infos: Vec<ManagedInfo> = Vec::new();
-        let mut new_descs: HashMap<String, Vec<ChannelDesc>> = HashMap::new();
+    /// Returns the id of the device that lost the index, if any.
+    pub fn steal_player(&mut self, device_id: &str, index: u8) -> Result<Option<String>> {
+        self.device_mut(device_id)?;
+        let previous = self
+            .player_device(index)
+            .filter(|&other| other != device_id)
+            .map(str::to_string);
+        if let Some(other) = &previous {
+            self.release_player(other);
+        }
+        self.assign_player(device_id, index)?;
+        Ok(previous)
+    }
 
-        for d in new_devs.iter() {
-            let id = d.id().to_string();
-            let name = d.name().to_string();
-            let meta = d.metadata();
-            let desc = d.describe();
-            let lm = build_labels(&desc);
-            new_labels.insert(id.clone(), lm);
-            new_descs.insert(id.clone(), desc.clone());
-            let mut st = old_states.get(&id).cloned().unwrap_or_default();
-            if let Some(lbl) = new_labels.get(&id) {
-                seed_neutral(&mut st, lbl, &desc);
-            }
-            new_states.insert(id.clone(), st);
-            new_infos.push(ManagedInfo { id, name, meta });
+    /// Assign the device's fixed index (XInput slot) if free, otherwise the lowest free index.
+    ///
+    /// Returns the index (the current one if the device already has a player).
+    pub fn auto_assign_player(&mut self, device_id: &str) -> Result<u8> {
+        if let Some(index) = self.player_index(device_id) {
+            return Ok(index);
         }
+        let fixed = self.device_mut(device_id)?.fixed_player_index();
+        let index = fixed
+            .filter(|&i| self.player_device(i).is_none())
+            .or_else(|| (0..=u8::MAX).find(|&i| self.player_device(i).is_none()))
+            .ok_or(Error::Unsupported("more than 256 players"))?;
+        self.assign_player(device_id, index)?;
+        Ok(index)
+    }
 
-        let new_ids: HashSet<_> = new_infos.iter().map(|i| i.id.clone()).collect();
-        let removed: Vec<_> = old_ids.difference(&new_ids).cloned().collect();
-        let added_idset: HashSet<_> = new_ids.difference(&old_ids).cloned().collect();
-        let added: Vec<_> = new_infos
+    /// Players whose assigned device is currently disconnected or gone.
+    pub fn lost_players(&self) -> Vec<u8> {
+        let mut lost: Vec<u8> = self
+            .players
             .iter()
-            .filter(|i| added_idset.contains(&i.id))
-            .cloned()
+            .filter(|(&id, _)| !self.is_attached(id))
+            .map(|(_, &p)| p)
             .collect();
-
-        self.devices = new_devs;
-        self.labels = new_labels;
-        self.states = new_states;
-        self.infos = new_infos;
-        self.descs = new_descs;
-
-        RescanReport { added, removed }
+        lost.sort_unstable();
+        lost
     }
 
-    /// Get an immutable cloneable per-frame snapshot.
+    /// Move player `index` to another device (e.g. after
+    /// [`ManagerEvent::PlayerDeviceLost`], once the user pressed a button on a new controller).
     ///
-    /// This returns an **owned clone** of the last-known device state map.
-    /// It does **not** poll devices. Call [`poll_events`](Manager::poll_events) first
-    /// if you want to refresh state before snapshotting.
-    pub fn snapshot(&self) -> crate::snapshot::Snapshot {
-        // `Snapshot` is a tuple struct (see src/snapshot.rs)
-        crate::snapshot::Snapshot(self.states.clone())
+    /// The device's previous player index, if any, is released. Returns the id of the device
+    /// that held `index` before.
+    pub fn reassign_player(&mut self, index: u8, device_id: &str) -> Result<Option<String>> {
+        if self.player_index(device_id) != Some(index) {
+            self.release_player(device_id);
+        }
+        self.steal_player(device_id, index)
     }
 
-    /// Snapshot current managed devices (id, name, meta).
+    /// Release a device's player index (and turn off its player LEDs).
     ///
-    /// Useful for UI lists and device pickers.
-    pub fn devices(&self) -> &[ManagedInfo] {
-        &self.infos
+    /// Returns the released index, if the device had one.
+    pub fn release_player(&mut self, device_id: &str) -> Option<u8> {
+        let index = self.players.remove(&self.id_of(device_id)?)?;
+        self.show_player(device_id, None);
+        Some(index)
     }
 
-    // ==========================
-    // Windows: WM_INPUT injection
-    // ==========================
-    //
-    // The host app (AxisMirror, a game, etc.) owns the HWND/message loop and forwards
-    // WM_INPUT lparam here. StickUp parses RAWINPUT and queues normalized events.
-
-    /// Windows-only: parse a `WM_INPUT` lparam and enqueue any resulting events.
-    ///
-    /// The host is responsible for registering Raw Input devices on its HWND
-    /// (RegisterRawInputDevices). This just parses and normalizes, and queues the
-    /// resulting events to be drained on the next [`poll_events`](Manager::poll_events).
-    #[cfg(target_os = "windows")]
-    pub fn handle_wm_input(&mut self, lparam: isize) {
-        let Some(pkt) = raw_input::read_wm_input(lparam) else {
+    /// Best-effort: reflect a player index on the device's LEDs.
+    fn show_player(&mut self, device_id: &str, index: Option<u8>) {
+        let Ok(dev) = self.output_device_mut(device_id) else {
             return;
         };
-        self.handle_raw_input_packet(pkt);
+        // Devices without these outputs return `Unsupported`; that's expected here.
+        match index {
+            Some(i) => {
+                let (r, g, b) = crate::output::player_color(i);
+                let _ = dev.send_output(&OutputCommand::PlayerLeds(
+                    crate::output::player_led_mask(i),
+                ));
+                let _ = dev.send_output(&OutputCommand::Lightbar { r, g, b });
+            }
+            None => {
+                let _ = dev.send_output(&OutputCommand::PlayerLeds(0));
+            }
+        }
     }
-    /// Windows-only: parse a copied `RID_INPUT` payload (bytes returned by `GetRawInputData`)
-    /// and enqueue any resulting events.
+
+    /// Create a seat (see [`seat`](crate::seat)). No-op if it exists.
+    pub fn add_seat(&mut self, name: &str) {
+        if self.seat(name).is_none() {
+            self.seats.push(Seat::new(name));
+        }
+    }
+
+    /// Remove a seat, freeing its devices.
+    pub fn remove_seat(&mut self, name: &str) -> Option<Seat> {
+        let i = self.seats.iter().position(|s| s.name == name)?;
+        Some(self.seats.remove(i))
+    }
+
+    /// All seats, in creation order.
+    pub fn seats(&self) -> &[Seat] {
+        &self.seats
+    }
+
+    /// A seat by name.
+    pub fn seat(&self, name: &str) -> Option<&Seat> {
+        self.seats.iter().find(|s| s.name == name)
+    }
+
+    fn seat_mut(&mut self, name: &str) -> Result<&mut Seat> {
+        self.seats
+            .iter_mut()
+            .find(|s| s.name == name)
+            .ok_or_else(|| Error::UnknownSeat(name.to_string()))
+    }
+
+    /// Seat a device belongs to, if any.
+    pub fn seat_of(&self, device_id: &str) -> Option<&str> {
+        self.seats
+            .iter()
+            .find(|s| s.contains(device_id))
+            .map(|s| s.name.as_str())
+    }
+
+    /// Move a device into `seat`, removing it from any other seat.
+    pub fn assign_seat(&mut self, device_id: &str, seat: &str) -> Result<()> {
+        self.seat_device(device_id, seat, None)
+    }
+
+    /// Like [`assign_seat`](Manager::assign_seat), with a seat-local alias the seat's
+    /// profile can address the device by.
+    pub fn assign_seat_alias(&mut self, device_id: &str, seat: &str, alias: &str) -> Result<()> {
+        self.seat_device(device_id, seat, Some(alias.to_string()))
+    }
+
+    fn seat_device(&mut self, device_id: &str, seat: &str, alias: Option<String>) -> Result<()> {
+        self.device_mut(device_id)?;
+        self.seat_mut(seat)?;
+        self.release_seat(device_id);
+        self.seat_mut(seat)?
+            .devices
+            .insert(device_id.to_string(), alias);
+        Ok(())
+    }
+
+    /// Remove a device from its seat. Returns the seat it left.
+    pub fn release_seat(&mut self, device_id: &str) -> Option<String> {
+        let seat = self.seats.iter_mut().find(|s| s.contains(device_id))?;
+        seat.devices.remove(device_id);
+        Some(seat.name.clone())
+    }
+
+    /// Set the profile [`resolve_seat`](Manager::resolve_seat) uses for `seat`.
+    pub fn set_seat_profile(&mut self, seat: &str, profile: Option<BindingProfile>) -> Result<()> {
+        self.seat_mut(seat)?.profile = profile;
+        Ok(())
+    }
+
+    /// Snapshot of one seat's devices (also keyed by alias; see [`Seat::states`]).
+    pub fn seat_snapshot(&self, seat: &str) -> Option<crate::snapshot::Snapshot> {
+        let seat = self.seat(seat)?;
+        Some(Snapshot::from(seat.states(&self.states)))
+    }
+
+    /// Resolve a seat's profile against that seat's devices only, with the active action
+    /// sets. `None` if the seat does not exist or has no profile.
+    pub fn resolve_seat(&self, seat: &str) -> Option<BindingOutput> {
+        let seat = self.seat(seat)?;
+        let profile = seat.profile.as_ref()?;
+        Some(profile.resolve_with_sets(&seat.states(&self.states), &self.action_sets))
+    }
+
+    /// Add (or replace) a gate button on a device: a synthetic button named `name` that is
+    /// held while `expr` holds (see [`gate`](crate::gate)). Returns its button index.
+    ///
+    /// Fails with [`Error::UnknownDevice`] for unknown ids, and if `expr` does not parse,
+    /// references a channel the device does not have, or `name` is already a channel.
+    pub fn add_gate(&mut self, device_id: &str, name: &str, expr: &str) -> Result<u16> {
+        self.install_gate(device_id, name, GateKind::Button, Expr::parse(expr)?)
+    }
+
+    /// Add (or replace) a synthetic axis named `name` whose value is `expr`, e.g.
+    /// `max(LT, RT)` or `smooth(X, 0.1)` (see [`gate`](crate::gate)). Returns its axis index.
+    ///
+    /// Fails like [`add_gate`](Manager::add_gate).
+    pub fn add_expr_axis(&mut self, device_id: &str, name: &str, expr: &str) -> Result<u16> {
+        self.install_gate(device_id, name, GateKind::Axis, Expr::parse(expr)?)
+    }
+
+    /// Install the gates a profile declares (see [`ChannelExpr`](crate::gate::ChannelExpr)). Stops at the first failure.
+    pub fn add_profile_channels(&mut self, profile: &BindingProfile) -> Result<()> {
+        for c in &profile.channels {
+            self.install_gate(&c.device_id, &c.name, c.kind, c.expr.clone())?;
+        }
+        Ok(())
+    }
+
+    fn install_gate(
+        &mut self,
+        device_id: &str,
+        name: &str,
+        kind: GateKind,
+        expr: Expr,
+    ) -> Result<u16> {
+        let (id, st) = self
+            .id_of(device_id)
+            .and_then(|id| Some((id, self.states.get_by_id(id)?)))
+            .ok_or_else(|| Error::UnknownDevice(device_id.to_string()))?;
+        let known = |st: &DeviceState, c: &str| {
+            st.axes.contains_key(c) || st.buttons.contains_key(c) || st.hats.contains_key(c)
+        };
+        if let Some(c) = expr.names().into_iter().find(|c| !known(st, c)) {
+            return Err(Error::Other(format!(
+                "gate {name}: unknown channel {c:?} on {device_id}"
+            )));
+        }
+        self.remove_gate(device_id, name);
+        if self.states.get_by_id(id).is_some_and(|st| known(st, name)) {
+            return Err(Error::Other(format!(
+                "gate {name}: {device_id} already has a channel with that name"
+            )));
+        }
+        let gates = self.gates.entry(id).or_default();
+        let index = (GATE_INDEX_BASE..=u16::MAX)
+            .find(|i| gates.iter().all(|g| g.kind != kind || g.index != *i))
+            .ok_or_else(|| Error::Other(format!("too many gates on {device_id}")))?;
+        gates.push(Gate::new(name, kind, index, expr));
+        self.install_gates(id);
+        Ok(index)
+    }
+
+    /// Remove a gate. Returns `false` if the device has no gate `name`.
+    pub fn remove_gate(&mut self, device_id: &str, name: &str) -> bool {
+        let Some(id) = self.id_of(device_id) else {
+            return false;
+        };
+        let Some(gates) = self.gates.get_mut(&id) else {
+            return false;
+        };
+        let Some(i) = gates.iter().position(|g| g.name == name) else {
+            return false;
+        };
+        let gate = gates.remove(i);
+        if gates.is_empty() {
+            self.gates.remove(&id);
+        }
+        let kind = gate.kind.channel_kind();
+        if let Some(lbl) = self.labels.get_mut(&id) {
+            let labels = match gate.kind {
+                GateKind::Button => &mut lbl.buttons,
+                GateKind::Axis => &mut lbl.axes,
+            };
+            labels.remove(&gate.index);
+        }
+        if let Some(descs) = self.descs.get_mut(&id) {
+            descs.retain(|d| !(d.kind == kind && d.idx == gate.index));
+        }
+        if let Some(st) = self.states.get_mut(id) {
+            match gate.kind {
+                GateKind::Button => {
+                    st.buttons.remove(&gate.name);
+                }
+                GateKind::Axis => {
+                    st.axes.remove(&gate.name);
+                }
+            }
+        }
+        true
+    }
+
+    /// Gates of a device.
+    pub fn gates(&self, device_id: &str) -> &[Gate] {
+        self.id_of(device_id)
+            .and_then(|id| self.gates.get(&id))
+            .map_or(&[], |g| g.as_slice())
+    }
+
+    /// Add a device's gates to its labels, descriptors, and state (again, after a rescan
+    /// rebuilt them).
+    fn install_gates(&mut self, id: DeviceId) {
+        let Some(gates) = self.gates.get(&id) else {
+            return;
+        };
+        for g in gates {
+            let kind = g.kind.channel_kind();
+            if let Some(lbl) = self.labels.get_mut(&id) {
+                let labels = match g.kind {
+                    GateKind::Button => &mut lbl.buttons,
+                    GateKind::Axis => &mut lbl.axes,
+                };
+                labels.insert(g.index, g.name.clone());
+            }
+            if let Some(descs) = self.descs.get_mut(&id) {
+                if !descs.iter().any(|d| d.kind == kind && d.idx == g.index) {
+                    let (logical_min, logical_max) = match g.kind {
+                        GateKind::Button => (0, 1),
+                        GateKind::Axis => (-1, 1),
+                    };
+                    descs.push(ChannelDesc {
+                        kind,
+                        idx: g.index,
+                        name: Some(g.name.clone()),
+                        logical_min,
+                        logical_max,
+                        usage_page: None,
+                        usage: None,
+                        unit: ChannelUnit::for_kind(kind),
+                        collection: None,
+                        collection_name: None,
+                    });
+                }
+            }
+            if let Some(st) = self.states.get_mut(id) {
+                match g.kind {
+                    GateKind::Button => {
+                        st.buttons.insert(g.name.clone(), g.is_active());
+                    }
+                    GateKind::Axis => {
+                        st.axes.insert(g.name.clone(), g.value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Re-evaluate a device's gates; events for those that changed.
+    fn gate_events(&mut self, id: DeviceId) -> Vec<InputKind> {
+        let (Some(gates), Some(st)) = (self.gates.get_mut(&id), self.states.get_by_id(id)) else {
+            return Vec::new();
+        };
+        let now = Instant::now();
+        let mut out = Vec::new();
+        for g in gates {
+            let Some(value) = g.update(st, now) else {
+                continue;
+            };
+            out.push(match g.kind {
+                GateKind::Axis => InputKind::AxisMoved {
+                    axis: g.index,
+                    value,
+                },
+                GateKind::Button if g.is_active() => InputKind::ButtonPressed { button: g.index },
+                GateKind::Button => InputKind::ButtonReleased { button: g.index },
+            });
+        }
+        out
+    }
+
+    /// [`poll_events`](Manager::poll_events), split by seat. Events from devices without a
+    /// seat are dropped (their state is still updated).
+    pub fn poll_events_by_seat(&mut self) -> BTreeMap<String, Vec<(String, InputKind)>> {
+        let mut out: BTreeMap<String, Vec<(String, InputKind)>> = BTreeMap::new();
+        for (id, ev) in self.poll_events() {
+            if let Some(seat) = self.seat_of(&id) {
+                out.entry(seat.to_string()).or_default().push((id, ev));
+            }
+        }
+        out
+    }
+
+    /// Claim a managed device for this process (see [`claims`](crate::claims)).
+    ///
+    /// Re-claiming a device this manager already holds switches the mode. Returns
+    /// [`Error::UnknownDevice`] for unknown ids and [`Error::Claimed`] if another process
+    /// holds a claim on the device.
+    pub fn claim(&mut self, device_id: &str, mode: ClaimMode) -> Result<()> {
+        let id = self.managed_id(device_id)?;
+        if self.claims.get(&id).map(|c| c.mode()) == Some(mode) {
+            return Ok(());
+        }
+        // Drop our old claim first so it does not block the new one.
+        let previous = self.claims.remove(&id);
+        match DeviceClaim::acquire(device_id, mode) {
+            Ok(claim) => {
+                self.claims.insert(id, claim);
+                Ok(())
+            }
+            Err(e) => {
+                if let Some(previous) = previous {
+                    self.claims.insert(id, previous);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Release this manager's claim on a device (no-op if none is held).
+    pub fn release(&mut self, device_id: &str) {
+        if let Some(id) = self.id_of(device_id) {
+            self.claims.remove(&id);
+        }
+    }
+
+    /// Mode of the claim this manager holds on a device, if any.
+    pub fn claim_mode(&self, device_id: &str) -> Option<ClaimMode> {
+        self.claims.get(&self.id_of(device_id)?).map(|c| c.mode())
+    }
+
+    /// Mode of a claim another process holds on a device, if any.
+    pub fn claimed_elsewhere(&self, device_id: &str) -> Option<ClaimMode> {
+        if self
+            .id_of(device_id)
+            .is_some_and(|id| self.claims.contains_key(&id))
+        {
+            return None;
+        }
+        crate::claims::claimed_by_other(device_id)
+    }
+
+    /// Poll all devices and yield `(device_id, event)` pairs.
+    ///
+    /// This updates internal per-device [`DeviceState`] and returns per-change deltas.
+    /// It also drains injected timeline markers ([`add_marker`](Manager::add_marker)).
+    pub fn poll_events(&mut self) -> Vec<(String, InputKind)> {
+        self.before_poll();
+        let mut out = Vec::new();
+        // 1) Poll devices (including Raw Input keyboards/mice).
+        self.poll_round(|mgr, i| {
+            let (handle, events) = mgr.poll_device(i);
+            let events = mgr.apply_polled(handle, events);
+            let id = &mgr.infos[i].id;
+            out.extend(events.into_iter().map(|ev| (id.clone(), ev)));
+        });
+
+        // 2) Drain injected events (timeline markers).
+        let injected = std::mem::take(&mut self.injected);
+        if !injected.is_empty() {
+            //eprintln!("[stickup] draining injected: {} event(s)", injected.len());
+        }
+        for (id, ev) in injected {
+            let handle = self.states.intern(&id);
+            self.apply_event(handle, &ev);
+            out.push((id, ev));
+        }
+        out
+    }
+
+    /// Like [`poll_events`], but returns shared ids to avoid per-event `String` clone.
+    /// This is additive and does not change existing APIs.
+    ///
+    /// Note: this does **not** drain timeline markers ([`add_marker`](Manager::add_marker));
+    /// prefer [`poll_events`](Manager::poll_events) if you use them.
+    pub fn poll_events_shared(&mut self) -> Vec<(Arc<str>, InputKind)> {
+        self.before_poll();
+        let mut out = Vec::new();
+        self.poll_round(|mgr, i| {
+            let (handle, events) = mgr.poll_device(i);
+            let events = mgr.apply_polled(handle, events);
+            if events.is_empty() {
+                return;
+            }
+            let id = mgr.shared_key(handle);
+            out.extend(events.into_iter().map(|ev| (id.clone(), ev)));
+        });
+        out
+    }
+
+    /// Poll with timestamps.
+    ///
+    /// Produces the same device-poll deltas as [`poll_events`](Manager::poll_events),
+    /// but wraps each event with a capture timestamp and sequence number (see
+    /// [`InputEvent`](crate::event::InputEvent)).
+    ///
+    /// Note: this does **not** include timeline markers ([`add_marker`](Manager::add_marker)).
+    pub fn poll_events_timed(&mut self) -> Vec<(String, crate::event::InputEvent)> {
+        self.before_poll();
+        let mut out = Vec::new();
+
+        self.poll_round(|mgr, i| {
+            let (handle, events) = mgr.poll_device(i);
+            let at = mgr.devices[i].report_time().unwrap_or_else(Instant::now);
+            for ev in mgr.apply_polled(handle, events) {
+                let ev = mgr.stamp(at, handle, ev);
+                out.push((mgr.infos[i].id.clone(), ev));
+            }
+        });
+
+        out
+    }
+
+    /// Timestamped polling without string ids: each event's
+    /// [`device`](crate::event::InputEvent::device) says where it came from (map it back with
+    /// [`key_of`](Manager::key_of) when needed).
+    ///
+    /// Note: this does **not** include timeline markers ([`add_marker`](Manager::add_marker)).
+    pub fn poll_events_timed_ids(&mut self) -> Vec<crate::event::InputEvent> {
+        self.before_poll();
+        let mut out = Vec::new();
+        self.poll_round(|mgr, i| {
+            let (handle, events) = mgr.poll_device(i);
+            let at = mgr.devices[i].report_time().unwrap_or_else(Instant::now);
+            for ev in mgr.apply_polled(handle, events) {
+                out.push(mgr.stamp(at, handle, ev));
+            }
+        });
+        out
+    }
+
+    /// Poll all devices (as [`poll_events_timed_shared`](Manager::poll_events_timed_shared))
+    /// and publish the events to `bus`. Returns the number of events published.
+    pub fn dispatch(&mut self, bus: &mut crate::bus::InputEventBus) -> usize {
+        let events = self.poll_events_timed_shared();
+        bus.publish_all(&events);
+        events.len()
+    }
+
+    /// Buffer polled events in a bounded queue of `capacity` events (see
+    /// [`queue`](crate::queue)).
+    ///
+    /// Call [`pump`](Manager::pump) at the device rate and
+    /// [`drain_event_queue`](Manager::drain_event_queue) whenever the consumer is ready: if it
+    /// falls behind, axis updates are coalesced and the oldest events dropped instead of
+    /// accumulating. Replaces (and empties) any existing queue.
+    pub fn enable_event_queue(&mut self, capacity: usize) {
+        self.event_queue = Some(EventQueue::new(capacity));
+    }
+
+    /// Stop buffering and discard any queued events.
+    pub fn disable_event_queue(&mut self) {
+        self.event_queue = None;
+    }
+
+    /// Poll all devices (as [`poll_events_timed_shared`](Manager::poll_events_timed_shared))
+    /// into the event queue. Returns the number of events polled.
+    ///
+    /// Without a queue ([`enable_event_queue`](Manager::enable_event_queue)) the events are
+    /// applied to device state and discarded.
+    pub fn pump(&mut self) -> usize {
+        let events = self.poll_events_timed_shared();
+        let n = events.len();
+        if let Some(queue) = &mut self.event_queue {
+            for (id, ev) in events {
+                queue.push(id, ev);
+            }
+        }
+        n
+    }
+
+    /// Take every queued event, oldest first.
+    pub fn drain_event_queue(&mut self) -> Vec<(Arc<str>, crate::event::InputEvent)> {
+        self.event_queue
+            .as_mut()
+            .map(EventQueue::drain)
+            .unwrap_or_default()
+    }
+
+    /// Drop and coalesce counters of the event queue, if enabled.
+    pub fn queue_stats(&self) -> Option<QueueStats> {
+        self.event_queue.as_ref().map(EventQueue::stats)
+    }
+
+    /// Timestamped polling with shared ids (no per-event `String` clone).
+    ///
+    /// Note: this does **not** include timeline markers ([`add_marker`](Manager::add_marker)).
+    pub fn poll_events_timed_shared(&mut self) -> Vec<(Arc<str>, crate::event::InputEvent)> {
+        self.before_poll();
+        let mut out = Vec::new();
+        self.poll_round(|mgr, i| {
+            let (handle, events) = mgr.poll_device(i);
+            let at = mgr.devices[i].report_time().unwrap_or_else(Instant::now);
+            let events = mgr.apply_polled(handle, events);
+            if events.is_empty() {
+                return;
+            }
+            let id = mgr.shared_key(handle);
+            for ev in events {
+                let ev = mgr.stamp(at, handle, ev);
+                out.push((id.clone(), ev));
+            }
+        });
+        out
+    }
+
+    /// Limit how long one poll may spend reading devices (`None` = no limit).
+    ///
+    /// Once a poll has used up `budget`, the remaining devices are skipped and polled first
+    /// next time (then discovery order resumes, unless [round-robin](Manager::set_round_robin)
+    /// is on), so every device is read within a few ticks even when some are slow. Each
+    /// cut-short poll reports [`ManagerEvent::PollBudgetExceeded`]. At least one device is
+    /// polled per call, so a poll may overrun a budget smaller than one device's read time.
+    pub fn set_poll_budget(&mut self, budget: Option<Duration>) {
+        self.poll_budget = budget;
+    }
+
+    /// Time limit per poll, if set (see [`set_poll_budget`](Manager::set_poll_budget)).
+    pub fn poll_budget(&self) -> Option<Duration> {
+        self.poll_budget
+    }
+
+    /// Start each poll one device later than the previous one (default off: devices are
+    /// polled in discovery order).
+    ///
+    /// Events of a poll come out in polling order, so with rotation no device is always read
+    /// (and reported) last, and under a [poll budget](Manager::set_poll_budget) no device is
+    /// always the one left for the next tick.
+    pub fn set_round_robin(&mut self, enabled: bool) {
+        self.round_robin = enabled;
+    }
+
+    /// Call `visit` with the index of each device to poll this tick, starting with devices
+    /// deferred by the poll budget (or the next device in turn, see
+    /// [`set_round_robin`](Manager::set_round_robin)).
+    fn poll_round(&mut self, mut visit: impl FnMut(&mut Self, usize)) {
+        let n = self.devices.len();
+        if n == 0 {
+            return;
+        }
+        let start = Instant::now();
+        let first = self.poll_cursor % n;
+        // Without rotation, a round that reads the devices deferred by the previous one goes
+        // back to discovery order afterwards.
+        self.poll_cursor = if self.round_robin { first + 1 } else { 0 };
+        for k in 0..n {
+            let i = (first + k) % n;
+            if let Some(budget) = self.poll_budget {
+                let elapsed = start.elapsed();
+                if k > 0 && elapsed >= budget {
+                    self.poll_cursor = i;
+                    self.manager_events.push(ManagerEvent::PollBudgetExceeded {
+                        elapsed,
+                        deferred: n - k,
+                    });
+                    break;
+                }
+            }
+            visit(self, i);
+        }
+        if let Some(history) = &mut self.history {
+            history.push(Instant::now(), self.states.clone());
+        }
+    }
+
+    /// Shared string id of a managed device (interned when the device was added).
+    fn shared_key(&self, id: DeviceId) -> Arc<str> {
+        self.states
+            .ids()
+            .shared_key(id)
+            .cloned()
+            .expect("managed devices are interned")
+    }
+
+    /// Apply a device's polled events to its state, then append the gate transitions they
+    /// caused (see [`add_gate`](Manager::add_gate)).
+    fn apply_polled(&mut self, id: DeviceId, mut events: Vec<InputKind>) -> Vec<InputKind> {
+        for ev in &events {
+            self.apply_event(id, ev);
+        }
+        let gated = self.gate_events(id);
+        for ev in &gated {
+            self.apply_event(id, ev);
+        }
+        events.extend(gated);
+        events
+    }
+
+    /// Wrap `kind` captured at `at`, assigning the next sequence number.
+    fn stamp(
+        &mut self,
+        at: Instant,
+        device: DeviceId,
+        kind: InputKind,
+    ) -> crate::event::InputEvent {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        crate::event::InputEvent {
+            at,
+            timestamp: at.saturating_duration_since(self.epoch),
+            seq,
+            device,
+            kind,
+        }
+    }
+
+    /// Poll device `i` via [`Device::try_poll`], recording failures for
+    /// [`take_poll_errors`](Manager::take_poll_errors).
+    ///
+    /// Connection changes reported by [`Device::is_connected`] are turned into
+    /// [`ManagerEvent`]s here. When a device drops out (disconnect or read failure), the
+    /// returned events end with synthetic releases (see [`release_state`](Self::release_state)),
+    /// so bound actions don't stay held; other errors leave the state alone. The first
+    /// successful read after a failure starts by restoring what was held before it (see
+    /// [`restore_state`](Self::restore_state)).
+    fn poll_device(&mut self, i: usize) -> (DeviceId, Vec<InputKind>) {
+        let handle = self.infos[i].handle;
+        let d = &mut self.devices[i];
+        let result = d.try_poll();
+        let connected = d.is_connected();
+        let mut released = Vec::new();
+        if connected == self.detached.contains(&handle) {
+            let id = d.id().to_string();
+            if connected {
+                self.detached.remove(&handle);
+                self.device_connected(&id);
+            } else {
+                self.detached.insert(handle);
+                released = self.release_state(handle);
+                self.device_disconnected(&id);
+            }
+        }
+        match result {
+            Ok(mut events) => {
+                if let Some(held) = self.read_failed.remove(&handle) {
+                    let mut restored = self.restore_state(handle, &held);
+                    restored.append(&mut events);
+                    events = restored;
+                }
+                events.append(&mut released);
+                (handle, events)
+            }
+            Err(e) => {
+                // Only a failed read means the device's input is lost; other errors (a
+                // disabled plugin) leave its state as it is.
+                if matches!(e, Error::Read { .. }) {
+                    if !self.read_failed.contains_key(&handle) {
+                        let held = self.states.get_by_id(handle).cloned().unwrap_or_default();
+                        self.read_failed.insert(handle, held);
+                    }
+                    // Repeated failures find the state neutral already and add nothing.
+                    released.extend(self.release_state(handle));
+                }
+                self.poll_errors.push((self.infos[i].id.clone(), e));
+                (handle, released)
+            }
+        }
+    }
+
+    /// Reset a device's state to neutral and return the events leading there from its
+    /// current state: `ButtonReleased` for held buttons, axes back to `0.0`, hats centered.
+    ///
+    /// Only described channels get events. The released buttons keep their
+    /// [`just_released`](DeviceState::just_released) edge for this frame.
+    fn release_state(&mut self, id: DeviceId) -> Vec<InputKind> {
+        let mut out = Vec::new();
+        let mut released = Vec::new();
+        if let (Some(st), Some(lbl)) = (self.states.get_by_id(id), self.labels.get(&id)) {
+            for (button, name) in sorted_labels(&lbl.buttons) {
+                if st.get_button(&name) {
+                    out.push(InputKind::ButtonReleased { button });
+                    released.push(name);
+                }
+            }
+            for (axis, name) in sorted_labels(&lbl.axes) {
+                if st.axes.get(&name).is_some_and(|&v| v != 0.0) {
+                    out.push(InputKind::AxisMoved { axis, value: 0.0 });
+                }
+            }
+            for (hat, name) in sorted_labels(&lbl.hats) {
+                if st.hats.get(&name).is_some_and(|&v| v != -1) {
+                    out.push(InputKind::HatChanged { hat, value: -1 });
+                }
+            }
+        }
+        self.reset_state(id);
+        if !released.is_empty() {
+            self.states.entry(id).released_this_frame.extend(released);
+        }
+        out
+    }
+
+    /// Events leading from neutral back to `held`, the state a device had before a read
+    /// failure released it: the inverse of [`release_state`](Self::release_state).
+    ///
+    /// The device's parser still remembers that state, so its next reports only describe
+    /// changes from it; without this, a button held through the failure would stay released.
+    fn restore_state(&self, id: DeviceId, held: &DeviceState) -> Vec<InputKind> {
+        let mut out = Vec::new();
+        let Some(lbl) = self.labels.get(&id) else {
+            return out;
+        };
+        for (button, name) in sorted_labels(&lbl.buttons) {
+            if held.get_button(&name) {
+                out.push(InputKind::ButtonPressed { button });
+            }
+        }
+        for (axis, name) in sorted_labels(&lbl.axes) {
+            if let Some(&value) = held.axes.get(&name).filter(|&&v| v != 0.0) {
+                out.push(InputKind::AxisMoved { axis, value });
+            }
+        }
+        for (hat, name) in sorted_labels(&lbl.hats) {
+            if let Some(&value) = held.hats.get(&name).filter(|&&v| v != -1) {
+                out.push(InputKind::HatChanged { hat, value });
+            }
+        }
+        out
+    }
+
+    /// Reset a device's state to neutral (keys kept).
+    fn reset_state(&mut self, id: DeviceId) {
+        let mut st = DeviceState::default();
+        let desc = self.descs.get(&id);
+        if let (Some(lbl), Some(desc)) = (self.labels.get(&id), desc) {
+            seed_neutral(&mut st, lbl, desc);
+        }
+        self.states.insert(id, st);
+        for g in self.gates.get_mut(&id).into_iter().flatten() {
+            g.reset();
+        }
+    }
+
+    /// Drain device lifecycle notifications (connect/disconnect) since the last call.
+    pub fn take_manager_events(&mut self) -> Vec<ManagerEvent> {
+        std::mem::take(&mut self.manager_events)
+    }
+
+    /// Whether a managed device is currently attached (see [`Device::is_connected`]).
+    ///
+    /// Returns `false` for unknown ids.
+    pub fn is_connected(&self, device_id: &str) -> bool {
+        self.id_of(device_id).is_some_and(|id| self.is_attached(id))
+    }
+
+    /// [`is_connected`](Self::is_connected) by handle.
+    fn is_attached(&self, id: DeviceId) -> bool {
+        self.infos.iter().any(|i| i.handle == id) && !self.detached.contains(&id)
+    }
+
+    /// Start managing `dev` and report it as connected.
+    #[cfg_attr(not(all(feature = "hid", target_os = "windows")), allow(dead_code))]
+    fn add_device(&mut self, mut dev: Box<dyn Device>) {
+        let id = dev.id().to_string();
+        let handle = self.states.intern(&id);
+        let desc = dev.describe();
+        let lm = build_labels(&desc);
+        let mut st = DeviceState::default();
+        seed_neutral(&mut st, &lm, &desc);
+        prime_state(&mut st, &lm, &dev.initial_state());
+        self.labels.insert(handle, lm);
+        self.descs.insert(handle, desc);
+        self.states.insert(handle, st);
+        self.infos.push(ManagedInfo {
+            id: id.clone(),
+            handle,
+            name: dev.name().to_string(),
+            meta: dev.metadata(),
+        });
+        self.devices.push(dev);
+        self.install_gates(handle);
+        self.device_connected(&id);
+    }
+
+    /// Require a device to stay present/absent for `window` before
+    /// [`DeviceConnected`](ManagerEvent::DeviceConnected) /
+    /// [`DeviceDisconnected`](ManagerEvent::DeviceDisconnected) (and the player events) fire.
+    ///
+    /// Some hubs make devices flap in and out rapidly; with a window set, a device that comes
+    /// back before it elapses produces no events at all. Device state and the device list
+    /// still change immediately; only the notifications are held back. Raw transitions remain
+    /// visible via [`take_hotplug_transitions`](Manager::take_hotplug_transitions).
+    /// `Duration::ZERO` (the default) reports every transition immediately.
+    pub fn set_hotplug_debounce(&mut self, window: Duration) {
+        self.hotplug_debounce = window;
+        if window.is_zero() {
+            self.flush_presence(true);
+        }
+    }
+
+    /// Current hotplug debounce window.
+    pub fn hotplug_debounce(&self) -> Duration {
+        self.hotplug_debounce
+    }
+
+    /// Drain raw (not de-bounced) connect/disconnect transitions since the last call.
+    ///
+    /// At most the latest 1024 transitions are kept.
+    pub fn take_hotplug_transitions(&mut self) -> Vec<HotplugTransition> {
+        std::mem::take(&mut self.hotplug_log)
+    }
+
+    /// Record a raw presence change and report it now or once it has settled.
+    fn presence_changed(&mut self, id: &str, present: bool) {
+        let now = Instant::now();
+        if self.hotplug_log.len() >= HOTPLUG_LOG_CAP {
+            self.hotplug_log.remove(0);
+        }
+        self.hotplug_log.push(HotplugTransition {
+            id: id.to_string(),
+            connected: present,
+            at: now,
+        });
+        if self.hotplug_debounce.is_zero() {
+            self.report_presence(id, present);
+            return;
+        }
+        // A transition means the previous (reported) state was the opposite one.
+        let handle = self.states.intern(id);
+        let reported = self
+            .pending_presence
+            .get(&handle)
+            .map(|&(_, _, reported)| reported)
+            .unwrap_or(!present);
+        self.pending_presence
+            .insert(handle, (present, now, reported));
+    }
+
+    /// Report presence changes that have been stable for the debounce window (or all of them
+    /// with `all`); flaps that ended where they started are dropped.
+    fn flush_presence(&mut self, all: bool) {
+        if self.pending_presence.is_empty() {
+            return;
+        }
+        let window = self.hotplug_debounce;
+        let mut due: Vec<(DeviceId, bool, bool)> = Vec::new();
+        self.pending_presence
+            .retain(|&id, &mut (present, since, reported)| {
+                if all || since.elapsed() >= window {
+                    due.push((id, present, reported));
+                    false
+                } else {
+                    true
+                }
+            });
+        for (id, present, reported) in due {
+            if present != reported {
+                let key = self.states.key_of(id).unwrap_or_default().to_string();
+                self.report_presence(&key, present);
+            }
+        }
+    }
+
+    fn report_presence(&mut self, id: &str, present: bool) {
+        if present {
+            self.report_connected(id);
+        } else {
+            self.report_disconnected(id);
+        }
+        self.update_readiness();
+    }
+
+    /// Declare the devices the application cannot run without (replacing earlier ones).
+    ///
+    /// The Manager tracks which connected device satisfies each requirement and reports
+    /// changes as [`RequiredDeviceMissing`](ManagerEvent::RequiredDeviceMissing) /
+    /// [`RequiredDeviceRestored`](ManagerEvent::RequiredDeviceRestored) and
+    /// [`ReadinessChanged`](ManagerEvent::ReadinessChanged) events, following the (de-bounced)
+    /// connection events. Requirements unmet right now are reported immediately.
+    pub fn require_devices(&mut self, required: impl IntoIterator<Item = RequiredDevice>) {
+        let was_ready = self.is_ready();
+        self.required = required.into_iter().collect();
+        self.required_by = vec![None; self.required.len()];
+        self.assign_required();
+        for (req, by) in self.required.iter().zip(&self.required_by) {
+            if by.is_none() {
+                self.manager_events
+                    .push(ManagerEvent::RequiredDeviceMissing {
+                        name: req.name.clone(),
+                    });
+            }
+        }
+        if self.is_ready() != was_ready {
+            self.manager_events.push(ManagerEvent::ReadinessChanged {
+                ready: self.is_ready(),
+            });
+        }
+    }
+
+    /// Declare the [`required_devices`](BindingProfile::required_devices) of `profile`.
+    pub fn require_profile_devices(&mut self, profile: &BindingProfile) {
+        self.require_devices(profile.required_devices.iter().cloned());
+    }
+
+    /// Is every required device present? `true` when nothing is required.
+    pub fn is_ready(&self) -> bool {
+        self.required_by.iter().all(Option::is_some)
+    }
+
+    /// Names of required devices that are currently missing.
+    pub fn missing_devices(&self) -> Vec<&str> {
+        self.required
+            .iter()
+            .zip(&self.required_by)
+            .filter(|(_, by)| by.is_none())
+            .map(|(req, _)| req.name.as_str())
+            .collect()
+    }
+
+    /// Device id currently satisfying the requirement `name`.
+    pub fn required_device(&self, name: &str) -> Option<&str> {
+        self.required
+            .iter()
+            .position(|r| r.name == name)
+            .and_then(|i| self.required_by[i])
+            .and_then(|id| self.states.key_of(id))
+    }
+
+    /// Match connected devices to requirements, in order, one device per requirement.
+    fn assign_required(&mut self) {
+        let mut used: HashSet<DeviceId> = HashSet::new();
+        let mut assigned = Vec::with_capacity(self.required.len());
+        for req in &self.required {
+            let found = self
+                .infos
+                .iter()
+                .filter(|i| !self.detached.contains(&i.handle) && !used.contains(&i.handle))
+                .find(|i| req.matches(i));
+            if let Some(info) = found {
+                used.insert(info.handle);
+            }
+            assigned.push(found.map(|i| i.handle));
+        }
+        self.required_by = assigned;
+    }
+
+    /// Re-match requirements after a presence change and queue readiness events.
+    fn update_readiness(&mut self) {
+        if self.required.is_empty() {
+            return;
+        }
+        let was_ready = self.is_ready();
+        let before = self.required_by.clone();
+        self.assign_required();
+        for (i, req) in self.required.iter().enumerate() {
+            match (before[i], self.required_by[i]) {
+                (Some(_), None) => self
+                    .manager_events
+                    .push(ManagerEvent::RequiredDeviceMissing {
+                        name: req.name.clone(),
+                    }),
+                (None, Some(id)) => {
+                    self.manager_events
+                        .push(ManagerEvent::RequiredDeviceRestored {
+                            name: req.name.clone(),
+                            id: self.states.key_of(id).unwrap_or_default().to_string(),
+                        })
+                }
+                _ => {}
+            }
+        }
+        if self.is_ready() != was_ready {
+            self.manager_events.push(ManagerEvent::ReadinessChanged {
+                ready: self.is_ready(),
+            });
+        }
+    }
+
+    fn device_connected(&mut self, id: &str) {
+        self.presence_changed(id, true);
+    }
+
+    fn device_disconnected(&mut self, id: &str) {
+        self.presence_changed(id, false);
+    }
+
+    /// Queue connection events (and restore the device's player, if it has one).
+    fn report_connected(&mut self, id: &str) {
+        self.manager_events
+            .push(ManagerEvent::DeviceConnected { id: id.to_string() });
+        if let Some(player) = self.player_index(id) {
+            self.show_player(id, Some(player));
+            self.manager_events
+                .push(ManagerEvent::PlayerDeviceRestored {
+                    player,
+                    id: id.to_string(),
+                });
+        }
+    }
+
+    /// Queue disconnection events; player loss jumps the queue.
+    fn report_disconnected(&mut self, id: &str) {
+        self.manager_events
+            .push(ManagerEvent::DeviceDisconnected { id: id.to_string() });
+        if let Some(player) = self.player_index(id) {
+            self.manager_events.insert(
+                0,
+                ManagerEvent::PlayerDeviceLost {
+                    player,
+                    id: id.to_string(),
+                },
+            );
+        }
+    }
+
+    /// Housekeeping shared by all `poll_events*` variants.
+    fn before_poll(&mut self) {
+        // A new frame: edges and relative motion from the previous poll no longer apply.
+        self.states.clear_edges();
+        self.watch_slots();
+        self.rescan_on_hotplug();
+        self.drain_raw_pump();
+        self.flush_presence(false);
+        if let Some(change) = self.idle.check(Instant::now()) {
+            self.report_idle(change);
+        }
+    }
+
+    /// Rescan devices automatically when HID devices arrive or leave.
+    ///
+    /// Uses Configuration Manager notifications, which need no window or message loop and
+    /// therefore also work from a Windows service. Added/removed devices are reported as
+    /// [`ManagerEvent`]s from the next poll on. Calling this again is a no-op.
+    #[cfg(all(feature = "hid", target_os = "windows"))]
+    pub fn watch_hotplug(&mut self) -> Result<()> {
+        if self.hotplug.is_none() {
+            self.hotplug = Some(crate::backends::windows::hotplug::HotplugWatcher::start()?);
+        }
+        Ok(())
+    }
+
+    /// Stop automatic hotplug rescans.
+    #[cfg(all(feature = "hid", target_os = "windows"))]
+    pub fn unwatch_hotplug(&mut self) {
+        self.hotplug = None;
+    }
+
+    #[cfg(all(feature = "hid", target_os = "windows"))]
+    fn rescan_on_hotplug(&mut self) {
+        if self.hotplug.as_ref().is_some_and(|h| h.take_changed()) {
+            self.rescan();
+        }
+    }
+
+    #[cfg(not(all(feature = "hid", target_os = "windows")))]
+    fn rescan_on_hotplug(&mut self) {}
+
+    /// Pick up controllers plugged into previously empty XInput slots.
+    ///
+    /// Empty-slot queries are slow in XInput, so this runs at most once per
+    /// `XINPUT_SLOT_CHECK_INTERVAL`.
+    #[cfg(all(feature = "hid", target_os = "windows"))]
+    fn watch_slots(&mut self) {
+        use crate::backends::windows::hid_discovery::{xinput_device, xinput_fingerprint};
+        use crate::backends::windows::xinput_devices::{slot_connected, SLOT_COUNT};
+
+        if self.last_slot_check.elapsed() < XINPUT_SLOT_CHECK_INTERVAL {
+            return;
+        }
+        self.last_slot_check = Instant::now();
+        for index in 0..SLOT_COUNT {
+            let id = xinput_fingerprint(index).to_string();
+            if self.infos.iter().any(|i| i.id == id) || !slot_connected(index) {
+                continue;
+            }
+            for dev in self.options.apply(vec![Box::new(xinput_device(index))]) {
+                self.add_device(dev);
+            }
+        }
+    }
+
+    #[cfg(not(all(feature = "hid", target_os = "windows")))]
+    fn watch_slots(&mut self) {}
+
+    /// Insert a named marker into the event stream.
+    ///
+    /// The marker is delivered by the next [`poll_events`](Manager::poll_events) as
+    /// `(MARKER_SOURCE, InputKind::Marker { label })`, after that poll's device events, so
+    /// consumers logging or recording the stream can navigate by it.
+    pub fn add_marker(&mut self, label: impl Into<Arc<str>>) {
+        self.injected.push((
+            MARKER_SOURCE.to_string(),
+            InputKind::Marker {
+                label: label.into(),
+            },
+        ));
+    }
+
+    /// Start collecting per-channel statistics from polled events (see [`stats`](crate::stats)).
+    ///
+    /// Replaces any collector already running. Statistics cost a few operations per event;
+    /// leave them off in production loops that do not need them.
+    pub fn enable_channel_stats(&mut self, collector: StatsCollector) {
+        self.stats = Some(collector);
+    }
+
+    /// Stop collecting statistics, returning what was collected.
+    pub fn disable_channel_stats(&mut self) -> Option<StatsCollector> {
+        self.stats.take()
+    }
+
+    /// Statistics collected so far, if enabled.
+    pub fn channel_stats(&self) -> Option<&StatsCollector> {
+        self.stats.as_ref()
+    }
+
+    /// Mutable access to the collector (e.g. to [`reset`](StatsCollector::reset) it).
+    pub fn channel_stats_mut(&mut self) -> Option<&mut StatsCollector> {
+        self.stats.as_mut()
+    }
+
+    /// Time since the last user activity on any (non-ignored) device (see [`idle`](crate::idle)).
+    pub fn idle_time(&self) -> Duration {
+        self.idle.idle_time(Instant::now())
+    }
+
+    /// Whether [`ManagerEvent::UserIdle`] was reported and no activity followed yet.
+    pub fn is_user_idle(&self) -> bool {
+        self.idle.is_idle()
+    }
+
+    /// Report [`ManagerEvent::UserIdle`] after `timeout` without activity, and
+    /// [`ManagerEvent::UserActive`] on the next activity (`None` = no idle events).
+    pub fn set_idle_timeout(&mut self, timeout: Option<Duration>) {
+        self.idle.set_timeout(timeout);
+    }
+
+    /// Exclude (or re-include) a device class from idle detection.
+    pub fn set_idle_ignored(&mut self, role: DeviceRole, ignored: bool) {
+        self.idle.ignore_role(role, ignored);
+    }
+
+    /// Record user activity from outside StickUp (touchscreen, remote control).
+    pub fn mark_user_active(&mut self) {
+        if let Some(change) = self.idle.mark_active(Instant::now()) {
+            self.report_idle(change);
+        }
+    }
+
+    /// The idle tracker, for tuning (see [`IdleTracker::set_axis_threshold`]).
+    pub fn idle_tracker_mut(&mut self) -> &mut IdleTracker {
+        &mut self.idle
+    }
+
+    fn report_idle(&mut self, change: IdleChange) {
+        self.manager_events.push(match change {
+            IdleChange::Active => ManagerEvent::UserActive,
+            IdleChange::Idle { idle_for } => ManagerEvent::UserIdle { idle_for },
+        });
+    }
+
+    /// Drain read failures collected by the polling methods since the last call.
+    ///
+    /// Polling never aborts on a failing device; each failure is recorded here as
+    /// `(device_id, error)`: typically [`Error::Read`] after an unplug, or the trap that
+    /// disabled a device's WASM plugin.
+    pub fn take_poll_errors(&mut self) -> Vec<(String, Error)> {
+        std::mem::take(&mut self.poll_errors)
+    }
+
+    fn apply_event(&mut self, id: DeviceId, ev: &InputKind) {
+        if let InputKind::Marker { .. } = ev {
+            return;
+        }
+        let key = self.states.key_of(id).unwrap_or_default();
+        if let Some(stats) = &mut self.stats {
+            stats.observe(key, ev);
+        }
+        let infos = &self.infos;
+        let role = || infos.iter().find(|i| i.handle == id).map(|i| i.meta.role());
+        let idle = self.idle.observe(key, ev, Instant::now(), role);
+        if let Some(change) = idle {
+            self.report_idle(change);
+        }
+        let st = self.states.entry(id);
+        let Some(lbl) = self.labels.get(&id) else {
+            return;
+        };
+        match *ev {
+            InputKind::AxisMoved { axis, value } => {
+                let k = lbl
+                    .axes
+                    .get(&axis)
+                    .cloned()
+                    .unwrap_or_else(|| default_name(ChannelKind::Axis, axis));
+                st.axes.insert(k, value);
+            }
+            InputKind::RelativeMoved { axis, delta } => {
+                let k = lbl
+                    .axes
+                    .get(&axis)
+                    .cloned()
+                    .unwrap_or_else(|| default_name(ChannelKind::Axis, axis));
+                st.add_relative(k, delta);
+            }
+            InputKind::ButtonPressed { button } => {
+                let k = lbl
+                    .buttons
+                    .get(&button)
+                    .cloned()
+                    .unwrap_or_else(|| default_name(ChannelKind::Button, button));
+                st.set_button(k, true);
+            }
+            InputKind::ButtonReleased { button } => {
+                let k = lbl
+                    .buttons
+                    .get(&button)
+                    .cloned()
+                    .unwrap_or_else(|| default_name(ChannelKind::Button, button));
+                st.set_button(k, false);
+            }
+            InputKind::KeyPressed { scancode, extended }
+            | InputKind::KeyReleased { scancode, extended } => {
+                let button = crate::keys::key_index(scancode, extended);
+                let k = lbl
+                    .buttons
+                    .get(&button)
+                    .cloned()
+                    .unwrap_or_else(|| crate::keys::key_label(button));
+                st.set_button(k, matches!(ev, InputKind::KeyPressed { .. }));
+            }
+            InputKind::HatChanged { hat, value } => {
+                let k = lbl
+                    .hats
+                    .get(&hat)
+                    .cloned()
+                    .unwrap_or_else(|| default_name(ChannelKind::Hat, hat));
+                st.hats.insert(k, value);
+            }
+            // Touch, motion, and battery samples are not mirrored into `DeviceState`;
+            // consume them from the event stream.
+            InputKind::TouchMoved { .. }
+            | InputKind::MotionChanged { .. }
+            | InputKind::BatteryChanged { .. }
+            | InputKind::Marker { .. } => {}
+        }
+    }
+
+    /// Rescan devices and report adds/removes. Preserves existing state where possible.
+    ///
+    /// Devices are matched by `device_id` (backend-provided stable id). For devices that still
+    /// exist after rescan, prior [`DeviceState`] is preserved and re-seeded to ensure stable keys.
+    ///
+    /// A device with a never-seen id that replaces a departed one (same model and interface,
+    /// e.g. a serial-less stick moved to another USB port) takes over the departed device's
+    /// id, handle, and bindings; see [`reconnect`](crate::reconnect).
+    ///
+    /// If backend discovery fails, the device list is left untouched and an empty report is
+    /// returned.
+    pub fn rescan(&mut self) -> RescanReport {
+        let old_ids: HashSet<_> = self.infos.iter().map(|i| i.id.clone()).collect();
+
+        let Ok(mut new_devs) = self.options.probe() else {
+            return RescanReport::default();
+        };
+        new_devs.extend(self.take_host_devices());
+        let mut new_devs = self.reattach_departed(new_devs);
+        let mut new_labels: HashMap<DeviceId, LabelMaps> = HashMap::new();
+        let mut new_states = self.states.emptied();
+        let mut new_infos: Vec<ManagedInfo> = Vec::new();
+        let mut new_descs: HashMap<DeviceId, Vec<ChannelDesc>> = HashMap::new();
+
+        for d in new_devs.iter_mut() {
+            let id = d.id().to_string();
+            let handle = new_states.intern(&id);
+            let name = d.name().to_string();
+            let meta = d.metadata();
+            let desc = d.describe();
+            let lm = build_labels(&desc);
+            let mut st = self.states.get_by_id(handle).cloned().unwrap_or_default();
+            seed_neutral(&mut st, &lm, &desc);
+            prime_state(&mut st, &lm, &d.initial_state());
+            new_labels.insert(handle, lm);
+            new_descs.insert(handle, desc);
+            new_states.insert(handle, st);
+            new_infos.push(ManagedInfo {
+                id,
+                handle,
+                name,
+                meta,
+            });
+        }
+
+        let new_ids: HashSet<_> = new_infos.iter().map(|i| i.id.clone()).collect();
+        let removed: Vec<_> = old_ids.difference(&new_ids).cloned().collect();
+        let added_idset: HashSet<_> = new_ids.difference(&old_ids).cloned().collect();
+        let added: Vec<_> = new_infos
+            .iter()
+            .filter(|i| added_idset.contains(&i.id))
+            .cloned()
+            .collect();
+
+        self.departed.retain(|d| !new_ids.contains(&d.id));
+        self.departed.extend(
+            self.infos
+                .iter()
+                .filter(|i| removed.contains(&i.id))
+                .cloned(),
+        );
+
+        self.detached = new_devs
+            .iter()
+            .zip(&new_infos)
+            .filter(|(d, _)| !d.is_connected())
+            .map(|(_, i)| i.handle)
+            .collect();
+        // Every device was just primed from its current reports.
+        self.read_failed.clear();
+        self.devices = new_devs;
+        self.labels = new_labels;
+        self.states = new_states;
+        self.infos = new_infos;
+        self.descs = new_descs;
+        let gated: Vec<DeviceId> = self.gates.keys().copied().collect();
+        for id in gated {
+            self.install_gates(id);
+        }
+
+        for info in &added {
+            self.device_connected(&info.id);
+        }
+        for id in &removed {
+            self.device_disconnected(id);
+        }
+
+        RescanReport { added, removed }
+    }
+
+    /// Present newly probed devices that replace departed ones under the departed ids.
+    ///
+    /// Devices whose id was seen before keep it; candidates are departed devices plus managed
+    /// devices missing from this probe (a quick replug between two rescans).
+    fn reattach_departed(&self, devs: Vec<Box<dyn Device>>) -> Vec<Box<dyn Device>> {
+        let probed: HashSet<String> = devs.iter().map(|d| d.id().to_string()).collect();
+        let mut candidates: Vec<ManagedInfo> = self
+            .departed
+            .iter()
+            .chain(&self.infos)
+            .filter(|i| !probed.contains(&i.id))
+            .cloned()
+            .collect();
+        devs.into_iter()
+            .map(|d| {
+                if self.states.id_of(d.id()).is_some() {
+                    return d;
+                }
+                let Some(old) = crate::reconnect::match_departed(&d.metadata(), &candidates) else {
+                    return d;
+                };
+                let id = old.id.clone();
+                candidates.retain(|c| c.id != id);
+                crate::reconnect::reattach(d, id)
+            })
+            .collect()
+    }
+
+    /// Devices removed by a rescan and not back yet, oldest first.
+    ///
+    /// A newly found device that replaces one of these takes over its id (see
+    /// [`rescan`](Manager::rescan)).
+    pub fn departed_devices(&self) -> &[ManagedInfo] {
+        &self.departed
+    }
+
+    /// Forget departed devices, so the next new device is never taken for one of them.
+    pub fn forget_departed_devices(&mut self) {
+        self.departed.clear();
+    }
+
+    /// First managed device matching `matcher`.
+    pub fn find_device(&self, matcher: &DeviceMatcher) -> Option<&ManagedInfo> {
+        self.infos
+            .iter()
+            .find(|i| matcher.matches(i) && !self.detached.contains(&i.handle))
+    }
+
+    /// Block until a connected device matching `matcher` is managed, rescanning periodically.
+    ///
+    /// Returns the device's info immediately if one is already present, or
+    /// [`Error::Timeout`] once `timeout` has elapsed. Meant for launcher scripts and tests
+    /// that must not proceed without required hardware; devices found along the way are
+    /// reported as [`ManagerEvent`]s as usual. Input arriving while waiting is not delivered.
+    pub fn wait_for_device(
+        &mut self,
+        matcher: &DeviceMatcher,
+        timeout: Duration,
+    ) -> Result<ManagedInfo> {
+        const RESCAN_INTERVAL: Duration = Duration::from_millis(500);
+        const SLEEP: Duration = Duration::from_millis(25);
+
+        let deadline = Instant::now() + timeout;
+        let mut last_rescan = Instant::now();
+        loop {
+            if let Some(info) = self.find_device(matcher) {
+                return Ok(info.clone());
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(Error::Timeout(matcher.to_string()));
+            }
+            if now.duration_since(last_rescan) >= RESCAN_INTERVAL {
+                self.rescan();
+                last_rescan = now;
+            } else {
+                // Picks up reattached XInput slots and hotplug rescans.
+                self.poll_events();
+            }
+            std::thread::sleep(SLEEP.min(deadline - now));
+        }
+    }
+
+    /// Start a binding capture, using current device state as the resting position of axes.
+    pub fn begin_capture(&self) -> BindingCapture {
+        BindingCapture::new(&self.states)
+    }
+
+    /// Poll devices and feed the events to `capture`.
+    ///
+    /// Returns the first significant input of this poll, if any. Events are consumed (state is
+    /// still updated), so don't call [`poll_events`](Manager::poll_events) separately while
+    /// capturing.
+    pub fn poll_capture(&mut self, capture: &mut BindingCapture) -> Option<CapturedControl> {
+        let events = self.poll_events();
+        events
+            .into_iter()
+            .find_map(|(id, ev)| self.feed_capture(capture, &id, &ev))
+    }
+
+    fn feed_capture(
+        &self,
+        capture: &mut BindingCapture,
+        id: &str,
+        ev: &InputKind,
+    ) -> Option<CapturedControl> {
+        let lbl = self.labels.get(&self.id_of(id)?)?;
+        match *ev {
+            InputKind::AxisMoved { axis, value } => {
+                capture.axis(id, &lbl.label(ChannelKind::Axis, axis), value)
+            }
+            InputKind::ButtonPressed { button } => {
+                capture.button(id, &lbl.label(ChannelKind::Button, button), true)
+            }
+            InputKind::KeyPressed { scancode, extended } => {
+                let button = crate::keys::key_index(scancode, extended);
+                capture.button(id, &lbl.label(ChannelKind::Button, button), true)
+            }
+            InputKind::HatChanged { hat, value } => {
+                capture.hat(id, &lbl.label(ChannelKind::Hat, hat), value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get an immutable cloneable per-frame snapshot.
+    ///
+    /// This returns an **owned clone** of the last-known device state map, which
+    /// [`poll_events`](Manager::poll_events) keeps up to date incrementally. It does **not**
+    /// poll devices, and device states are shared copy-on-write, so the clone is cheap (see
+    /// [`state`](Manager::state) to borrow instead).
+    ///
+    /// If a [`SnapshotFilter`] is set (see [`set_snapshot_filter`](Manager::set_snapshot_filter)),
+    /// only the subscribed devices/channels are copied.
+    pub fn snapshot(&self) -> crate::snapshot::Snapshot {
+        match &self.snapshot_filter {
+            Some(filter) => self.snapshot_with(filter),
+            None => self.states.clone(),
+        }
+    }
+
+    /// Borrow the last-known state of every device without copying anything.
+    ///
+    /// Reads like [`snapshot`](Manager::snapshot) but ignores the snapshot filter.
+    #[inline]
+    pub fn state(&self) -> &crate::snapshot::Snapshot {
+        &self.states
+    }
+
+    /// Snapshot only the devices/channels selected by `filter`.
+    pub fn snapshot_with(&self, filter: &SnapshotFilter) -> crate::snapshot::Snapshot {
+        filter.apply(&self.states)
+    }
+
+    /// Keep the last `capacity` snapshots, one per poll (see [`history`](crate::history)).
+    ///
+    /// Entries hold every device regardless of the snapshot filter. Replaces (and empties)
+    /// any existing history.
+    pub fn enable_snapshot_history(&mut self, capacity: usize) {
+        self.history = Some(SnapshotHistory::new(capacity));
+    }
+
+    /// Stop recording and discard the snapshot history.
+    pub fn disable_snapshot_history(&mut self) {
+        self.history = None;
+    }
+
+    /// Snapshots recorded by recent polls, if enabled.
+    pub fn snapshot_history(&self) -> Option<&SnapshotHistory> {
+        self.history.as_ref()
+    }
+
+    /// Subscribe [`snapshot`](Manager::snapshot) to a subset of devices/channels
+    /// (`None` = everything).
+    ///
+    /// State tracking is unaffected; only snapshot construction is narrowed.
+    pub fn set_snapshot_filter(&mut self, filter: Option<SnapshotFilter>) {
+        self.snapshot_filter = filter;
+    }
+
+    /// Activate a binding [`ActionSet`](crate::binding::ActionSet) by name (no-op if active).
+    ///
+    /// Active sets are used by [`resolve`](Manager::resolve) and
+    /// [`resolve_with`](Manager::resolve_with); several can be active at once.
+    pub fn activate_action_set(&mut self, name: &str) {
+        if !self.action_sets.iter().any(|s| s == name) {
+            self.action_sets.push(name.to_string());
+        }
+    }
+
+    /// Deactivate an action set by name.
+    pub fn deactivate_action_set(&mut self, name: &str) {
+        self.action_sets.retain(|s| s != name);
+    }
+
+    /// Replace the active action sets (e.g. on a game-state change).
+    pub fn set_action_sets<I, S>(&mut self, names: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.action_sets = names.into_iter().map(Into::into).collect();
+    }
+
+    /// Names of the active action sets, in activation order.
+    pub fn active_action_sets(&self) -> &[String] {
+        &self.action_sets
+    }
+
+    /// Resolve `profile` against the last polled state with the active action sets
+    /// (see [`BindingProfile::resolve_with_sets`]).
+    pub fn resolve(&self, profile: &BindingProfile) -> BindingOutput {
+        profile.resolve_with_sets(&self.states, &self.action_sets)
+    }
+
+    /// Time-aware variant of [`resolve`](Manager::resolve): syncs the active action sets into
+    /// `resolver` and resolves at `now`.
+    pub fn resolve_with(&self, resolver: &mut BindingResolver, now: Instant) -> BindingOutput {
+        resolver.set_action_sets(&self.action_sets);
+        resolver.resolve(&self.states, now)
+    }
+
+    /// Snapshot current managed devices (id, name, meta).
+    ///
+    /// Useful for UI lists and device pickers.
+    pub fn devices(&self) -> &[ManagedInfo] {
+        &self.infos
+    }
+
+    /// Interned handle of `device_id` (see [`DeviceId`]). Also known for devices that have
+    /// gone away since.
+    pub fn id_of(&self, device_id: &str) -> Option<DeviceId> {
+        self.states.id_of(device_id)
+    }
+
+    /// String id behind a device handle.
+    pub fn key_of(&self, id: DeviceId) -> Option<&str> {
+        self.states.key_of(id)
+    }
+
+    // ==========================
+    // Windows: WM_INPUT injection
+    // ==========================
+    //
+    // The host app (AxisMirror, a game, etc.) owns the HWND/message loop and forwards
+    // WM_INPUT lparam here. StickUp parses RAWINPUT and routes it to the matching
+    // RawKeyboardDevice/RawMouseDevice.
+
+    /// Windows-only: parse a `WM_INPUT` lparam and enqueue any resulting events.
+    ///
+    /// The host is responsible for registering Raw Input devices on its HWND
+    /// (RegisterRawInputDevices). This just parses and normalizes, and queues the
+    /// resulting events on the source keyboard/mouse device (created on first input) to be
+    /// delivered by the next poll.
+    #[cfg(target_os = "windows")]
+    pub fn handle_wm_input(&mut self, lparam: isize) {
+        let Some(pkt) = raw_input::read_wm_input(lparam) else {
+            return;
+        };
+        self.handle_raw_input_packet(pkt);
+    }
+    /// Windows-only: start a managed Raw Input pump so the host needs no window or
+    /// message loop.
+    ///
+    /// A background thread owns a hidden message-only window registered for the selected
+    /// device classes; its packets are drained by [`poll_events`](Manager::poll_events), and
+    /// each keyboard/mouse shows up in [`devices`](Manager::devices) on first input.
+    /// Restarting replaces the running pump.
+    ///
+    /// Raw Input registration is process-wide: this replaces any keyboard/mouse registration
+    /// the host application already made, and its window stops receiving those `WM_INPUT`
+    /// messages (dropping the pump does not restore them). Don't combine this with
+    /// [`handle_wm_input`](Manager::handle_wm_input) on a host window.
+    ///
+    /// Fails with [`Error::Unsupported`] in session 0 (Windows services): Raw Input only reaches
+    /// processes on an interactive desktop.
+    #[cfg(target_os = "windows")]
+    pub fn start_raw_input(&mut self, options: RawInputOptions) -> Result<()> {
+        if crate::backends::windows::hotplug::in_service_session() {
+            return Err(Error::Unsupported("raw input in a session 0 service"));
+        }
+        self.raw_pump = None;
+        self.raw_pump = Some(RawInputPump::start(options)?);
+        Ok(())
+    }
+
+    /// Windows-only: stop the managed Raw Input pump (no-op if none is running).
+    #[cfg(target_os = "windows")]
+    pub fn stop_raw_input(&mut self) {
+        self.raw_pump = None;
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn drain_raw_pump(&mut self) {}
+
+    #[cfg(target_os = "windows")]
+    fn drain_raw_pump(&mut self) {
+        let Some(pump) = self.raw_pump.take() else {
+            return;
+        };
+        for bytes in pump.drain() {
+            self.handle_raw_input_bytes(&bytes);
+        }
+        self.raw_pump = Some(pump);
+    }
+
+    /// Windows-only: parse a copied `RID_INPUT` payload (bytes returned by `GetRawInputData`)
+    /// and enqueue any resulting events.
     ///
     /// This is the safe "deferred processing" path: the host must copy the WM_INPUT payload
     /// during the window proc, then it can hand the bytes to StickUp later (outside the proc).
@@ -457,288 +2462,131 @@ impl Manager {
     fn handle_raw_input_packet(&mut self, pkt: raw_input::RawInputPacket) {
         match pkt {
             raw_input::RawInputPacket::Keyboard(k) => {
-                //eprintln!(
-                //    "[stickup] rawkbd packet: scancode=0x{:02x} ext={} break={} hdev={:p}",
-                //    k.scancode, k.is_extended, k.is_break, k.hdevice as *const c_void
-                //);
-                let key_idx = raw_input::pack_key_index(k.scancode, k.is_extended);
-
-                let dev_id = match raw_input::device_name(k.hdevice) {
-                    Some(s) => s,
-                    None => format!("rawkbd:{:p}", k.hdevice as *const c_void),
-                };
-
-                self.ensure_keyboard_key_registered(&dev_id, key_idx);
-
-                let ev = if k.is_break {
-                    InputKind::ButtonReleased { button: key_idx }
-                } else {
-                    InputKind::ButtonPressed { button: key_idx }
-                };
-                //eprintln!("[stickup] inject => dev_id={} ev={:?}", dev_id, ev);
-                self.injected.push((dev_id, ev));
+                let (dev_id, sink) = self.raw_sink(k.hdevice, |h| {
+                    let (dev, sink) = RawKeyboardDevice::new(h);
+                    (Box::new(dev), sink)
+                });
+                if let Some(key_idx) = sink.push_keyboard(&k) {
+                    self.register_key(&dev_id, key_idx);
+                }
             }
-
             raw_input::RawInputPacket::Mouse(m) => {
-                // Button flag bits from RAWMOUSE usButtonFlags (RI_MOUSE_*).
-                const RI_MOUSE_LEFT_BUTTON_DOWN: u16 = 0x0001;
-                const RI_MOUSE_LEFT_BUTTON_UP: u16 = 0x0002;
-                const RI_MOUSE_RIGHT_BUTTON_DOWN: u16 = 0x0004;
-                const RI_MOUSE_RIGHT_BUTTON_UP: u16 = 0x0008;
-                const RI_MOUSE_MIDDLE_BUTTON_DOWN: u16 = 0x0010;
-                const RI_MOUSE_MIDDLE_BUTTON_UP: u16 = 0x0020;
-                const RI_MOUSE_BUTTON_4_DOWN: u16 = 0x0040;
-                const RI_MOUSE_BUTTON_4_UP: u16 = 0x0080;
-                const RI_MOUSE_BUTTON_5_DOWN: u16 = 0x0100;
-                const RI_MOUSE_BUTTON_5_UP: u16 = 0x0200;
-
-                // Use the device interface path when available for "one device per physical mouse".
-                let dev_id = match raw_input::device_name(m.hdevice) {
-                    Some(s) => s,
-                    None => format!("rawmouse:{:p}", m.hdevice as *const c_void),
-                };
-
-                self.ensure_mouse_registered(&dev_id);
-
-                // Axes: 0=dx, 1=dy, 2=wheel, 3=hwheel (raw counts / wheel ticks).
-                if m.dx != 0 {
-                    self.injected.push((
-                        dev_id.clone(),
-                        InputKind::AxisMoved {
-                            axis: 0,
-                            value: m.dx as f32,
-                        },
-                    ));
-                }
-                if m.dy != 0 {
-                    self.injected.push((
-                        dev_id.clone(),
-                        InputKind::AxisMoved {
-                            axis: 1,
-                            value: m.dy as f32,
-                        },
-                    ));
-                }
-                if m.wheel_delta != 0 {
-                    // Standard WHEEL_DELTA is 120; exposing "ticks" is usually nicer.
-                    self.injected.push((
-                        dev_id.clone(),
-                        InputKind::AxisMoved {
-                            axis: 2,
-                            value: (m.wheel_delta as f32) / 120.0,
-                        },
-                    ));
-                }
-                if m.hwheel_delta != 0 {
-                    self.injected.push((
-                        dev_id.clone(),
-                        InputKind::AxisMoved {
-                            axis: 3,
-                            value: (m.hwheel_delta as f32) / 120.0,
-                        },
-                    ));
-                }
-
-                // Buttons: 0=L, 1=R, 2=M, 3=X1, 4=X2
-                let f = m.buttons_flags;
-                if (f & RI_MOUSE_LEFT_BUTTON_DOWN) != 0 {
-                    self.injected
-                        .push((dev_id.clone(), InputKind::ButtonPressed { button: 0 }));
-                }
-                if (f & RI_MOUSE_LEFT_BUTTON_UP) != 0 {
-                    self.injected
-                        .push((dev_id.clone(), InputKind::ButtonReleased { button: 0 }));
-                }
-                if (f & RI_MOUSE_RIGHT_BUTTON_DOWN) != 0 {
-                    self.injected
-                        .push((dev_id.clone(), InputKind::ButtonPressed { button: 1 }));
-                }
-                if (f & RI_MOUSE_RIGHT_BUTTON_UP) != 0 {
-                    self.injected
-                        .push((dev_id.clone(), InputKind::ButtonReleased { button: 1 }));
-                }
-                if (f & RI_MOUSE_MIDDLE_BUTTON_DOWN) != 0 {
-                    self.injected
-                        .push((dev_id.clone(), InputKind::ButtonPressed { button: 2 }));
-                }
-                if (f & RI_MOUSE_MIDDLE_BUTTON_UP) != 0 {
-                    self.injected
-                        .push((dev_id.clone(), InputKind::ButtonReleased { button: 2 }));
-                }
-                if (f & RI_MOUSE_BUTTON_4_DOWN) != 0 {
-                    self.injected
-                        .push((dev_id.clone(), InputKind::ButtonPressed { button: 3 }));
-                }
-                if (f & RI_MOUSE_BUTTON_4_UP) != 0 {
-                    self.injected
-                        .push((dev_id.clone(), InputKind::ButtonReleased { button: 3 }));
-                }
-                if (f & RI_MOUSE_BUTTON_5_DOWN) != 0 {
-                    self.injected
-                        .push((dev_id.clone(), InputKind::ButtonPressed { button: 4 }));
-                }
-                if (f & RI_MOUSE_BUTTON_5_UP) != 0 {
-                    self.injected
-                        .push((dev_id.clone(), InputKind::ButtonReleased { button: 4 }));
-                }
+                let (_, sink) = self.raw_sink(m.hdevice, |h| {
+                    let (dev, sink) = RawMouseDevice::new(h);
+                    (Box::new(dev), sink)
+                });
+                sink.push_mouse(&m);
             }
         }
     }
+
+    /// Id and sink of the raw device for `hdevice`, creating and managing the device on
+    /// first use.
     #[cfg(target_os = "windows")]
-    fn ensure_keyboard_key_registered(&mut self, dev_id: &str, key_idx: u16) {
-        // Ensure device entry exists.
-        if !self.labels.contains_key(dev_id) {
-            // Create empty label maps.
-            self.labels.insert(dev_id.to_string(), LabelMaps::default());
-            // Create empty descriptor list.
-            self.descs.insert(dev_id.to_string(), Vec::new());
-            // Seed empty state.
-            self.states
-                .insert(dev_id.to_string(), DeviceState::default());
-            // Add to device list for UIs.
-            let mut meta = DeviceMeta::default();
-            meta.bus = Some("rawinput".into());
-            meta.path = Some(dev_id.to_string());
-            self.infos.push(ManagedInfo {
-                id: dev_id.to_string(),
-                name: "Keyboard".into(),
-                meta,
-            });
+    fn raw_sink(
+        &mut self,
+        hdevice: windows_sys::Win32::Foundation::HANDLE,
+        make: impl FnOnce(
+            windows_sys::Win32::Foundation::HANDLE,
+        ) -> (Box<dyn Device>, raw_devices::RawSink),
+    ) -> (String, raw_devices::RawSink) {
+        if let Some(entry) = self.raw_handles.get(&(hdevice as usize)) {
+            return entry.clone();
         }
+        let (dev, sink) = make(hdevice);
+        let entry = (dev.id().to_string(), sink);
+        self.add_device(dev);
+        self.raw_handles.insert(hdevice as usize, entry.clone());
+        entry
+    }
 
-        // Ensure this specific key has a label + descriptor (so DeviceState updates work).
-        let key_name = format!("key_{:04x}", key_idx);
-
-        if let Some(lbl) = self.labels.get_mut(dev_id) {
+    /// Add a newly seen keyboard key to the device's labels, descriptors, and state.
+    #[cfg(target_os = "windows")]
+    fn register_key(&mut self, dev_id: &str, key_idx: u16) {
+        let Some(handle) = self.id_of(dev_id) else {
+            return;
+        };
+        let key_name = crate::keys::key_label(key_idx);
+        if let Some(lbl) = self.labels.get_mut(&handle) {
             lbl.buttons
                 .entry(key_idx)
                 .or_insert_with(|| key_name.clone());
         }
-
-        if let Some(descs) = self.descs.get_mut(dev_id) {
-            let exists = descs
-                .iter()
-                .any(|d| d.kind == ChannelKind::Button && d.idx == key_idx);
-            if !exists {
-                descs.push(ChannelDesc {
-                    kind: ChannelKind::Button,
-                    idx: key_idx,
-                    name: Some(key_name),
-                    logical_min: 0,
-                    logical_max: 1,
-                    usage_page: None,
-                    usage: None,
-                });
-            }
+        if let Some(descs) = self.descs.get_mut(&handle) {
+            descs.push(raw_devices::key_desc(key_idx));
         }
-
-        // Ensure state has an entry for this control so consumers see a stable key set.
-        if let Some(st) = self.states.get_mut(dev_id) {
-            st.buttons
-                .entry(format!("key_{:04x}", key_idx))
-                .or_insert(false);
+        if let Some(st) = self.states.get_mut(handle) {
+            st.buttons.entry(key_name).or_insert(false);
         }
     }
 
+    /// Devices fed by the host rather than discovered (kept across rescans).
     #[cfg(target_os = "windows")]
-    fn ensure_mouse_registered(&mut self, dev_id: &str) {
-        if !self.labels.contains_key(dev_id) {
-            self.labels.insert(dev_id.to_string(), LabelMaps::default());
-            self.descs.insert(dev_id.to_string(), Vec::new());
-            self.states
-                .insert(dev_id.to_string(), DeviceState::default());
-
-            let mut meta = DeviceMeta::default();
-            meta.bus = Some("rawinput".into());
-            meta.path = Some(dev_id.to_string());
-            self.infos.push(ManagedInfo {
-                id: dev_id.to_string(),
-                name: "Mouse".into(),
-                meta,
-            });
-        }
-
-        // Give the mouse a stable “shape” for bindings/UIs immediately.
-        // Axes: 0=dx, 1=dy, 2=wheel, 3=hwheel
-        // Buttons: 0=L, 1=R, 2=M, 3=X1, 4=X2
-        let axes = [
-            (0u16, "dx"),
-            (1u16, "dy"),
-            (2u16, "wheel"),
-            (3u16, "hwheel"),
-        ];
-        let buttons = [
-            (0u16, "lmb"),
-            (1u16, "rmb"),
-            (2u16, "mmb"),
-            (3u16, "x1"),
-            (4u16, "x2"),
-        ];
-
-        if let Some(lbl) = self.labels.get_mut(dev_id) {
-            for (idx, name) in axes {
-                lbl.axes.entry(idx).or_insert_with(|| name.to_string());
-            }
-            for (idx, name) in buttons {
-                lbl.buttons.entry(idx).or_insert_with(|| name.to_string());
-            }
-        }
-
-        if let Some(descs) = self.descs.get_mut(dev_id) {
-            for (idx, name) in axes {
-                let exists = descs
-                    .iter()
-                    .any(|d| d.kind == ChannelKind::Axis && d.idx == idx);
-                if !exists {
-                    descs.push(ChannelDesc {
-                        kind: ChannelKind::Axis,
-                        idx,
-                        name: Some(name.to_string()),
-                        logical_min: -32768,
-                        logical_max: 32767,
-                        usage_page: None,
-                        usage: None,
-                    });
-                }
-            }
-            for (idx, name) in buttons {
-                let exists = descs
-                    .iter()
-                    .any(|d| d.kind == ChannelKind::Button && d.idx == idx);
-                if !exists {
-                    descs.push(ChannelDesc {
-                        kind: ChannelKind::Button,
-                        idx,
-                        name: Some(name.to_string()),
-                        logical_min: 0,
-                        logical_max: 1,
-                        usage_page: None,
-                        usage: None,
-                    });
-                }
-            }
-        }
+    fn take_host_devices(&mut self) -> Vec<Box<dyn Device>> {
+        let ids: HashSet<String> = self
+            .devices
+            .iter()
+            .filter(|d| d.metadata().bus.as_deref() == Some("rawinput"))
+            .map(|d| d.id().to_string())
+            .collect();
+        let (host, rest) = std::mem::take(&mut self.devices)
+            .into_iter()
+            .partition(|d| ids.contains(d.id()));
+        self.devices = rest;
+        host
+    }
 
-        if let Some(st) = self.states.get_mut(dev_id) {
-            // Seed axes/buttons so snapshot consumers see stable keys.
-            for (_, name) in axes {
-                st.axes.entry(name.to_string()).or_insert(0.0);
-            }
-            for (_, name) in buttons {
-                st.buttons.entry(name.to_string()).or_insert(false);
-            }
-        }
+    #[cfg(not(target_os = "windows"))]
+    fn take_host_devices(&mut self) -> Vec<Box<dyn Device>> {
+        Vec::new()
     }
 }
 
 /// Added/removed devices after a rescan.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct RescanReport {
     pub added: Vec<ManagedInfo>,
     pub removed: Vec<String>,
 }
 
 // ------ helpers ------
+/// Overwrite `state` with a freshly opened device's [`initial_state`](Device::initial_state).
+///
+/// Values are set directly: no press/release edges are recorded and no events are emitted.
+fn prime_state(state: &mut DeviceState, labels: &LabelMaps, events: &[InputKind]) {
+    for ev in events {
+        match *ev {
+            InputKind::AxisMoved { axis, value } => {
+                let k = labels
+                    .axes
+                    .get(&axis)
+                    .cloned()
+                    .unwrap_or_else(|| default_name(ChannelKind::Axis, axis));
+                state.axes.insert(k, value);
+            }
+            InputKind::ButtonPressed { button } | InputKind::ButtonReleased { button } => {
+                let k = labels
+                    .buttons
+                    .get(&button)
+                    .cloned()
+                    .unwrap_or_else(|| default_name(ChannelKind::Button, button));
+                state
+                    .buttons
+                    .insert(k, matches!(ev, InputKind::ButtonPressed { .. }));
+            }
+            InputKind::HatChanged { hat, value } => {
+                let k = labels
+                    .hats
+                    .get(&hat)
+                    .cloned()
+                    .unwrap_or_else(|| default_name(ChannelKind::Hat, hat));
+                state.hats.insert(k, value);
+            }
+            _ => {}
+        }
+    }
+}
+
 fn seed_neutral(state: &mut DeviceState, labels: &LabelMaps, descs: &[ChannelDesc]) {
     for d in descs {
         let label = match d.kind {
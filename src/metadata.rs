@@ -70,6 +70,43 @@ pub struct DeviceMeta {
 
     /// Windows-only: container identifier (DEVPKEY_Device_ContainerId), if known.
     ///
-    /// Identifies a logical container that may group related interfaces.
+    /// Identifies a logical container that may group related interfaces. Formatted as a
+    /// lowercase braced GUID; `None` for virtual devices without a physical container.
     pub container_id: Option<String>,
 }
+
+/// Broad device category, derived from the HID top-level usage (see [`DeviceMeta::role`]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceRole {
+    /// Joystick, HOTAS part, pedals (Generic Desktop Joystick).
+    Joystick,
+    /// Gamepad (Generic Desktop Gamepad, XInput).
+    Gamepad,
+    /// Multi-axis controller (6-DOF mice, button boxes reporting as such).
+    MultiAxis,
+    Keyboard,
+    Mouse,
+    /// Simulation Controls page devices (wheels, flight controls).
+    Simulation,
+    /// Anything else or unknown.
+    Other,
+}
+
+impl DeviceMeta {
+    /// Category of this device from its bus and top-level usage.
+    pub fn role(&self) -> DeviceRole {
+        if self.bus.as_deref() == Some("xinput") {
+            return DeviceRole::Gamepad;
+        }
+        match (self.usage_page, self.usage) {
+            (Some(0x01), Some(0x02)) => DeviceRole::Mouse,
+            (Some(0x01), Some(0x04)) => DeviceRole::Joystick,
+            (Some(0x01), Some(0x05)) => DeviceRole::Gamepad,
+            (Some(0x01), Some(0x06)) => DeviceRole::Keyboard,
+            (Some(0x01), Some(0x08)) => DeviceRole::MultiAxis,
+            (Some(0x02), _) => DeviceRole::Simulation,
+            _ => DeviceRole::Other,
+        }
+    }
+}
@@ -0,0 +1,352 @@
+//! Motion sensor fusion: gyro + accelerometer → orientation.
+//!
+//! [`MotionFusion`] consumes [`InputKind::MotionChanged`] samples (gyro in °/s, accel in g)
+//! and tracks the controller's orientation as a [`Quat`]:
+//! - [`FusionAlgorithm::Complementary`] (Mahony-style proportional correction) is cheap and
+//!   predictable; [`FusionAlgorithm::Madgwick`] converges faster from bad starts.
+//! - The accelerometer corrects pitch/roll drift. Yaw has no absolute reference (no
+//!   magnetometer), so it is kept usable by **gyro bias calibration** while the controller
+//!   rests and by [`recenter`](MotionFusion::recenter).
+//!
+//! Samples don't carry their own timestamps; pass the time since the previous sample
+//! (e.g. from [`Manager::poll_events_timed`](crate::manager::Manager::poll_events_timed)):
+//!
+//! ```no_run
+//! use stickup::motion::{FusionAlgorithm, MotionFusion};
+//! use stickup::{InputKind, Manager};
+//!
+//! let mut mgr = Manager::discover().expect("discover devices");
+//! let mut fusion = MotionFusion::new(FusionAlgorithm::default());
+//! let mut last = None;
+//! loop {
+//!     for (_id, ev) in mgr.poll_events_timed() {
+//!         if let InputKind::MotionChanged { .. } = ev.kind {
+//!             let dt = last.map_or(0.0, |t| ev.at.duration_since(t).as_secs_f32());
+//!             last = Some(ev.at);
+//!             fusion.feed(&ev.kind, dt);
+//!         }
+//!     }
+//!     let _q = fusion.orientation();
+//! }
+//! ```
+
+use crate::event::InputKind;
+
+/// Unit quaternion `w + xi + yj + zk` (rotation from sensor frame to world frame).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quat {
+    pub w: f32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Default for Quat {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Quat {
+    /// No rotation.
+    pub const IDENTITY: Quat = Quat {
+        w: 1.0,
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    };
+
+    /// Rotation of `angle` radians around the unit vector `axis`.
+    pub fn from_axis_angle(axis: [f32; 3], angle: f32) -> Quat {
+        let (s, c) = (angle * 0.5).sin_cos();
+        Quat {
+            w: c,
+            x: axis[0] * s,
+            y: axis[1] * s,
+            z: axis[2] * s,
+        }
+    }
+
+    /// Inverse of a unit quaternion.
+    pub fn conjugate(self) -> Quat {
+        Quat {
+            w: self.w,
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+
+    /// Scaled to unit length (identity if degenerate).
+    pub fn normalized(self) -> Quat {
+        let n = (self.w * self.w + self.x * self.x + self.y * self.y + self.z * self.z).sqrt();
+        if n <= f32::EPSILON || !n.is_finite() {
+            return Quat::IDENTITY;
+        }
+        Quat {
+            w: self.w / n,
+            x: self.x / n,
+            y: self.y / n,
+            z: self.z / n,
+        }
+    }
+
+    /// Rotate vector `v`.
+    pub fn rotate(self, v: [f32; 3]) -> [f32; 3] {
+        let p = Quat {
+            w: 0.0,
+            x: v[0],
+            y: v[1],
+            z: v[2],
+        };
+        let r = self * p * self.conjugate();
+        [r.x, r.y, r.z]
+    }
+}
+
+impl core::ops::Mul for Quat {
+    type Output = Quat;
+
+    /// Hamilton product `self * rhs` (apply `rhs`, then `self`).
+    fn mul(self, rhs: Quat) -> Quat {
+        Quat {
+            w: self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+            x: self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            y: self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            z: self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+        }
+    }
+}
+
+/// Fusion filter choice.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FusionAlgorithm {
+    /// Gyro integration with a proportional pull towards the accelerometer's gravity
+    /// (`gain` in 1/s; default `1.0`).
+    Complementary { gain: f32 },
+    /// Madgwick gradient-descent filter (`beta` in rad/s; default `0.1`).
+    Madgwick { beta: f32 },
+}
+
+impl Default for FusionAlgorithm {
+    fn default() -> Self {
+        FusionAlgorithm::Complementary { gain: 1.0 }
+    }
+}
+
+/// Gyro bias calibration while resting (see [`MotionFusion::auto_calibrate`]).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RestDetection {
+    /// Maximum angular rate (°/s, after bias removal) that counts as resting.
+    pub max_gyro_dps: f32,
+    /// Maximum deviation of `|accel|` from 1 g that counts as resting.
+    pub max_accel_dev_g: f32,
+    /// Rest time before samples start refining the bias (seconds).
+    pub settle_secs: f32,
+    /// Time constant of the bias average (seconds).
+    pub smoothing_secs: f32,
+}
+
+impl Default for RestDetection {
+    fn default() -> Self {
+        Self {
+            max_gyro_dps: 4.0,
+            max_accel_dev_g: 0.05,
+            settle_secs: 1.0,
+            smoothing_secs: 2.0,
+        }
+    }
+}
+
+/// Orientation tracker for one IMU (see module docs).
+#[derive(Clone, Debug)]
+pub struct MotionFusion {
+    algorithm: FusionAlgorithm,
+    /// Sensor → world orientation.
+    q: Quat,
+    /// Orientation treated as "forward" (see `recenter`).
+    reference: Quat,
+    /// Estimated gyro bias (°/s).
+    bias: [f32; 3],
+    rest: Option<RestDetection>,
+    resting_for: f32,
+}
+
+impl MotionFusion {
+    /// Fusion at identity orientation with rest calibration enabled.
+    pub fn new(algorithm: FusionAlgorithm) -> Self {
+        Self {
+            algorithm,
+            q: Quat::IDENTITY,
+            reference: Quat::IDENTITY,
+            bias: [0.0; 3],
+            rest: Some(RestDetection::default()),
+            resting_for: 0.0,
+        }
+    }
+
+    /// Builder: configure (or with `None`, disable) gyro bias calibration at rest.
+    pub fn auto_calibrate(mut self, rest: Option<RestDetection>) -> Self {
+        self.rest = rest;
+        self
+    }
+
+    /// Orientation relative to the last [`recenter`](Self::recenter).
+    pub fn orientation(&self) -> Quat {
+        (self.reference.conjugate() * self.q).normalized()
+    }
+
+    /// Absolute orientation (world `+z` is "up" as measured by the resting accelerometer).
+    pub fn raw_orientation(&self) -> Quat {
+        self.q
+    }
+
+    /// Current gyro bias estimate (°/s).
+    pub fn gyro_bias(&self) -> [f32; 3] {
+        self.bias
+    }
+
+    /// Set the gyro bias (e.g. from a stored calibration).
+    pub fn set_gyro_bias(&mut self, bias: [f32; 3]) {
+        self.bias = bias;
+    }
+
+    /// Treat the current orientation as "forward" from now on.
+    pub fn recenter(&mut self) {
+        self.reference = self.q;
+    }
+
+    /// Forget all state (orientation, reference, bias).
+    pub fn reset(&mut self) {
+        *self = Self::new(self.algorithm).auto_calibrate(self.rest);
+    }
+
+    /// Feed a [`InputKind::MotionChanged`] sample taken `dt` seconds after the previous one.
+    /// Other events are ignored.
+    pub fn feed(&mut self, event: &InputKind, dt: f32) {
+        if let InputKind::MotionChanged { gyro, accel, .. } = event {
+            self.update(*gyro, *accel, dt);
+        }
+    }
+
+    /// Advance by one sample: `gyro` in °/s, `accel` in g, `dt` in seconds.
+    ///
+    /// Returns the relative [`orientation`](Self::orientation).
+    pub fn update(&mut self, gyro: [f32; 3], accel: [f32; 3], dt: f32) -> Quat {
+        if !(dt > 0.0 && dt.is_finite()) {
+            return self.orientation();
+        }
+        let g = [
+            gyro[0] - self.bias[0],
+            gyro[1] - self.bias[1],
+            gyro[2] - self.bias[2],
+        ];
+        self.calibrate(gyro, g, accel, dt);
+
+        let rad = core::f32::consts::PI / 180.0;
+        let g = [g[0] * rad, g[1] * rad, g[2] * rad];
+        self.q = match self.algorithm {
+            FusionAlgorithm::Complementary { gain } => complementary(self.q, g, accel, gain, dt),
+            FusionAlgorithm::Madgwick { beta } => madgwick(self.q, g, accel, beta, dt),
+        };
+        self.orientation()
+    }
+
+    /// Refine the gyro bias while the controller rests.
+    fn calibrate(&mut self, raw: [f32; 3], unbiased: [f32; 3], accel: [f32; 3], dt: f32) {
+        let Some(rest) = self.rest else {
+            return;
+        };
+        let rate = (unbiased[0].powi(2) + unbiased[1].powi(2) + unbiased[2].powi(2)).sqrt();
+        let a = (accel[0].powi(2) + accel[1].powi(2) + accel[2].powi(2)).sqrt();
+        if rate > rest.max_gyro_dps || (a - 1.0).abs() > rest.max_accel_dev_g {
+            self.resting_for = 0.0;
+            return;
+        }
+        self.resting_for += dt;
+        if self.resting_for < rest.settle_secs {
+            return;
+        }
+        let k = (dt / rest.smoothing_secs.max(dt)).min(1.0);
+        for (b, r) in self.bias.iter_mut().zip(raw) {
+            *b += (r - *b) * k;
+        }
+    }
+}
+
+/// Normalized accelerometer vector, or `None` in free fall / on bad data.
+fn unit(a: [f32; 3]) -> Option<[f32; 3]> {
+    let n = (a[0] * a[0] + a[1] * a[1] + a[2] * a[2]).sqrt();
+    (n > 1e-6 && n.is_finite()).then(|| [a[0] / n, a[1] / n, a[2] / n])
+}
+
+/// Integrate body rates `g` (rad/s) into `q`.
+fn integrate(q: Quat, g: [f32; 3], dt: f32) -> Quat {
+    let dq = q * Quat {
+        w: 0.0,
+        x: g[0],
+        y: g[1],
+        z: g[2],
+    };
+    Quat {
+        w: q.w + 0.5 * dq.w * dt,
+        x: q.x + 0.5 * dq.x * dt,
+        y: q.y + 0.5 * dq.y * dt,
+        z: q.z + 0.5 * dq.z * dt,
+    }
+    .normalized()
+}
+
+/// Mahony-style proportional correction towards measured gravity.
+fn complementary(q: Quat, mut g: [f32; 3], accel: [f32; 3], gain: f32, dt: f32) -> Quat {
+    if let Some([ax, ay, az]) = unit(accel) {
+        // Gravity ("up" as measured by a resting accelerometer) predicted in the sensor frame.
+        let vx = 2.0 * (q.x * q.z - q.w * q.y);
+        let vy = 2.0 * (q.w * q.x + q.y * q.z);
+        let vz = q.w * q.w - q.x * q.x - q.y * q.y + q.z * q.z;
+        // Error = measured × predicted.
+        let e = [ay * vz - az * vy, az * vx - ax * vz, ax * vy - ay * vx];
+        for (gi, ei) in g.iter_mut().zip(e) {
+            *gi += gain * ei;
+        }
+    }
+    integrate(q, g, dt)
+}
+
+/// Madgwick IMU update (gyro + accel).
+fn madgwick(q: Quat, g: [f32; 3], accel: [f32; 3], beta: f32, dt: f32) -> Quat {
+    let (q0, q1, q2, q3) = (q.w, q.x, q.y, q.z);
+    let mut d0 = 0.5 * (-q1 * g[0] - q2 * g[1] - q3 * g[2]);
+    let mut d1 = 0.5 * (q0 * g[0] + q2 * g[2] - q3 * g[1]);
+    let mut d2 = 0.5 * (q0 * g[1] - q1 * g[2] + q3 * g[0]);
+    let mut d3 = 0.5 * (q0 * g[2] + q1 * g[1] - q2 * g[0]);
+
+    if let Some([ax, ay, az]) = unit(accel) {
+        let (q0q0, q1q1, q2q2, q3q3) = (q0 * q0, q1 * q1, q2 * q2, q3 * q3);
+        let s0 = 4.0 * q0 * q2q2 + 2.0 * q2 * ax + 4.0 * q0 * q1q1 - 2.0 * q1 * ay;
+        let s1 = 4.0 * q1 * q3q3 - 2.0 * q3 * ax + 4.0 * q0q0 * q1 - 2.0 * q0 * ay - 4.0 * q1
+            + 8.0 * q1 * q1q1
+            + 8.0 * q1 * q2q2
+            + 4.0 * q1 * az;
+        let s2 = 4.0 * q0q0 * q2 + 2.0 * q0 * ax + 4.0 * q2 * q3q3 - 2.0 * q3 * ay - 4.0 * q2
+            + 8.0 * q2 * q1q1
+            + 8.0 * q2 * q2q2
+            + 4.0 * q2 * az;
+        let s3 = 4.0 * q1q1 * q3 - 2.0 * q1 * ax + 4.0 * q2q2 * q3 - 2.0 * q2 * ay;
+        let n = (s0 * s0 + s1 * s1 + s2 * s2 + s3 * s3).sqrt();
+        if n > 1e-9 {
+            d0 -= beta * s0 / n;
+            d1 -= beta * s1 / n;
+            d2 -= beta * s2 / n;
+            d3 -= beta * s3 / n;
+        }
+    }
+
+    Quat {
+        w: q0 + d0 * dt,
+        x: q1 + d1 * dt,
+        y: q2 + d2 * dt,
+        z: q3 + d3 * dt,
+    }
+    .normalized()
+}
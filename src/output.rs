@@ -0,0 +1,94 @@
+//! Device output commands (lights, player indicators, adaptive triggers).
+//!
+//! Rumble and force feedback have dedicated APIs ([`Device::set_rumble`](crate::device::Device::set_rumble),
+//! [`ffb`](crate::ffb)). Everything else a controller can display or actuate goes through
+//! [`OutputCommand`] and [`Device::send_output`](crate::device::Device::send_output).
+//!
+//! Backends accept the commands their hardware supports and return
+//! [`Error::Unsupported`](crate::Error::Unsupported) for the rest.
+//!
+//! # Example
+//! ```no_run
+//! use stickup::output::{OutputCommand, Trigger, TriggerEffect};
+//! use stickup::Manager;
+//!
+//! let mut mgr = Manager::discover().expect("discover devices");
+//! let id = "054c:0ce6:ABCDEF";
+//! mgr.send_output(id, &OutputCommand::Lightbar { r: 0, g: 64, b: 255 }).ok();
+//! mgr.send_output(
+//!     id,
+//!     &OutputCommand::TriggerEffect {
+//!         trigger: Trigger::Right,
+//!         effect: TriggerEffect::Resistance { start: 0.3, force: 0.8 },
+//!     },
+//! )
+//! .ok();
+//! ```
+
+/// Which adaptive trigger a [`TriggerEffect`] applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Trigger {
+    Left,
+    Right,
+}
+
+/// Adaptive trigger effect.
+///
+/// Positions are normalized along the trigger travel (`0.0` = rest, `1.0` = fully pressed);
+/// strengths are normalized to `[0, 1]`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TriggerEffect {
+    /// No effect (free trigger).
+    Off,
+    /// Continuous resistance from `start` to the end of travel.
+    Resistance { start: f32, force: f32 },
+    /// Resistance only between `start` and `end` (a "wall" or click point).
+    Section { start: f32, end: f32, force: f32 },
+    /// Vibration from `start` onwards.
+    Vibration {
+        start: f32,
+        amplitude: f32,
+        frequency_hz: u8,
+    },
+}
+
+/// Conventional player indicator pattern for player `index` (0-based) on a 5-LED bar
+/// (DualSense style: P1 = center LED, P2 = two inner LEDs, ...).
+pub fn player_led_mask(index: u8) -> u8 {
+    match index {
+        0 => 0b00100,
+        1 => 0b01010,
+        2 => 0b10101,
+        3 => 0b11011,
+        _ => 0b11111,
+    }
+}
+
+/// Conventional light bar color for player `index` (0-based): blue, red, green, pink,
+/// then white.
+pub fn player_color(index: u8) -> (u8, u8, u8) {
+    match index {
+        0 => (0, 0, 64),
+        1 => (64, 0, 0),
+        2 => (0, 64, 0),
+        3 => (32, 0, 32),
+        _ => (32, 32, 32),
+    }
+}
+
+/// A single output request for a device.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OutputCommand {
+    /// Set the light bar / LED color.
+    Lightbar { r: u8, g: u8, b: u8 },
+    /// Set player indicator LEDs as a bit mask (bit 0 = leftmost LED).
+    PlayerLeds(u8),
+    /// Configure an adaptive trigger.
+    TriggerEffect {
+        trigger: Trigger,
+        effect: TriggerEffect,
+    },
+    /// Write an output report verbatim (including the leading report ID byte), for outputs
+    /// without a dedicated command: arcade I/O boards, LED controllers, lockout coils.
+    RawReport(Vec<u8>),
+}
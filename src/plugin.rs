@@ -0,0 +1,450 @@
+//! Sandboxed WASM transform plugins (feature **`wasm-plugins`**).
+//!
+//! A plugin is a WebAssembly module run once per poll of the device it is attached to: it
+//! reads a fixed list of the device's channels and computes a fixed list of extra axes.
+//! Modules run in an interpreter ([wasmi](https://docs.rs/wasmi)) with no host imports (no
+//! files, clock, or network), a fuel budget per tick, and a memory cap, so a community-made
+//! curve or filter cannot hang or escape the host.
+//!
+//! # Module ABI
+//!
+//! The module must export:
+//! - `memory` — its linear memory;
+//! - `stickup_io() -> i32` — address of an `f32` buffer with room for
+//!   `inputs.len() + outputs.len()` values;
+//! - `stickup_tick(dt: f32)` — compute outputs. Before the call the host writes input `i`
+//!   (normalized value, `1`/`0` for buttons, direction or `-1` for hats) to slot `i`; after
+//!   it, output `j` is read from slot `inputs.len() + j`. `dt` is seconds since the previous
+//!   tick (`0` on the first).
+//!
+//! and may export `stickup_reset()`, called when the device reconnects. A module that
+//! imports anything is rejected.
+//!
+//! ```text
+//! (module
+//!   (memory (export "memory") 1)
+//!   (func (export "stickup_io") (result i32) i32.const 0)
+//!   ;; output 0 = input 0 squared, sign kept
+//!   (func (export "stickup_tick") (param f32)
+//!     i32.const 4
+//!     i32.const 0 f32.load
+//!     i32.const 0 f32.load f32.abs
+//!     f32.mul
+//!     f32.store))
+//! ```
+//!
+//! Attach a plugin with [`attach_plugin`]; outputs become axes named after
+//! [`PluginConfig::outputs`] at indices after the device's highest axis, so they bind like
+//! any other channel. A plugin that traps or runs out of fuel is disabled and its outputs
+//! return to `0`; the device keeps working. The next poll of the device fails with the trap
+//! (collected by [`Manager::take_poll_errors`](crate::manager::Manager::take_poll_errors))
+//! instead of reading input; later polls read normally.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use wasmi::{
+    Config, Engine, Instance, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder,
+    TypedFunc,
+};
+
+use crate::device::{BatteryStatus, Device, ReportIdDiagnostics};
+use crate::event::{ChannelDesc, ChannelKind, InputKind};
+use crate::ffb::{EffectHandle, EffectOp, FfbEffect};
+use crate::metadata::DeviceMeta;
+use crate::output::OutputCommand;
+use crate::{Error, Result};
+
+/// Channels a plugin reads and writes, and its resource limits.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginConfig {
+    /// Input channel [state names](ChannelDesc::state_name), in slot order.
+    pub inputs: Vec<String>,
+    /// Output axis names, in slot order.
+    pub outputs: Vec<String>,
+    /// Instruction budget of one tick (wasmi fuel).
+    pub fuel_per_tick: u64,
+    /// Largest linear memory the module may grow to, in bytes.
+    pub max_memory: usize,
+}
+
+impl Default for PluginConfig {
+    fn default() -> Self {
+        Self {
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            fuel_per_tick: 100_000,
+            max_memory: 1 << 20,
+        }
+    }
+}
+
+impl PluginConfig {
+    /// Config reading `inputs` and producing `outputs`, with default limits.
+    pub fn new<S: Into<String>>(
+        inputs: impl IntoIterator<Item = S>,
+        outputs: impl IntoIterator<Item = S>,
+    ) -> Self {
+        Self {
+            inputs: inputs.into_iter().map(Into::into).collect(),
+            outputs: outputs.into_iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the per-tick fuel budget.
+    pub fn fuel_per_tick(mut self, fuel: u64) -> Self {
+        self.fuel_per_tick = fuel;
+        self
+    }
+
+    /// Set the memory cap in bytes.
+    pub fn max_memory(mut self, bytes: usize) -> Self {
+        self.max_memory = bytes;
+        self
+    }
+}
+
+fn wasm_err(context: &str, e: impl std::fmt::Display) -> Error {
+    Error::Other(format!("wasm plugin: {context}: {e}"))
+}
+
+/// A loaded, instantiated plugin (see module docs).
+pub struct WasmTransform {
+    config: PluginConfig,
+    store: Store<StoreLimits>,
+    memory: Memory,
+    io: u32,
+    tick: TypedFunc<f32, ()>,
+    reset: Option<TypedFunc<(), ()>>,
+    outputs: Vec<f32>,
+}
+
+impl WasmTransform {
+    /// Compile and instantiate a module (binary, or text format).
+    pub fn new(wasm: impl AsRef<[u8]>, config: PluginConfig) -> Result<Self> {
+        let mut engine_config = Config::default();
+        engine_config.consume_fuel(true);
+        let engine = Engine::new(&engine_config);
+        let module = Module::new(&engine, wasm).map_err(|e| wasm_err("invalid module", e))?;
+        if let Some(import) = module.imports().next() {
+            return Err(Error::Other(format!(
+                "wasm plugin: imports are not allowed ({}::{})",
+                import.module(),
+                import.name()
+            )));
+        }
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(config.max_memory)
+            .instances(1)
+            .build();
+        let mut store = Store::new(&engine, limits);
+        store.limiter(|limits| limits);
+        store
+            .set_fuel(config.fuel_per_tick)
+            .map_err(|e| wasm_err("fuel", e))?;
+        let instance: Instance = Linker::new(&engine)
+            .instantiate_and_start(&mut store, &module)
+            .map_err(|e| wasm_err("instantiate", e))?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| Error::Other("wasm plugin: no exported `memory`".into()))?;
+        let io_fn = instance
+            .get_typed_func::<(), i32>(&store, "stickup_io")
+            .map_err(|e| wasm_err("stickup_io", e))?;
+        let tick = instance
+            .get_typed_func::<f32, ()>(&store, "stickup_tick")
+            .map_err(|e| wasm_err("stickup_tick", e))?;
+        let reset = instance
+            .get_typed_func::<(), ()>(&store, "stickup_reset")
+            .ok();
+        store
+            .set_fuel(config.fuel_per_tick)
+            .map_err(|e| wasm_err("fuel", e))?;
+        let io = io_fn
+            .call(&mut store, ())
+            .map_err(|e| wasm_err("stickup_io", e))? as u32;
+        let outputs = vec![0.0; config.outputs.len()];
+        Ok(Self {
+            config,
+            store,
+            memory,
+            io,
+            tick,
+            reset,
+            outputs,
+        })
+    }
+
+    /// Load a module from a `.wasm` (or `.wat`) file.
+    pub fn from_file(path: impl AsRef<Path>, config: PluginConfig) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .map_err(|e| Error::Other(format!("wasm plugin {}: {e}", path.display())))?;
+        Self::new(bytes, config)
+    }
+
+    /// The plugin's channel lists and limits.
+    pub fn config(&self) -> &PluginConfig {
+        &self.config
+    }
+
+    /// Output values of the last tick.
+    pub fn outputs(&self) -> &[f32] {
+        &self.outputs
+    }
+
+    /// Run one tick. `vars` looks up input channels by state name (missing ones read `0`).
+    pub fn tick(&mut self, vars: impl Fn(&str) -> Option<f32>, dt: f32) -> Result<&[f32]> {
+        let n_in = self.config.inputs.len();
+        let mut buf = Vec::with_capacity((n_in + self.outputs.len()) * 4);
+        for name in &self.config.inputs {
+            buf.extend_from_slice(&vars(name).unwrap_or(0.0).to_le_bytes());
+        }
+        self.memory
+            .write(&mut self.store, self.io as usize, &buf)
+            .map_err(|e| wasm_err("write inputs", e))?;
+        self.store
+            .set_fuel(self.config.fuel_per_tick)
+            .map_err(|e| wasm_err("fuel", e))?;
+        self.tick
+            .call(&mut self.store, dt)
+            .map_err(|e| wasm_err("stickup_tick", e))?;
+        buf.resize(self.outputs.len() * 4, 0);
+        self.memory
+            .read(&self.store, self.io as usize + n_in * 4, &mut buf)
+            .map_err(|e| wasm_err("read outputs", e))?;
+        for (out, b) in self.outputs.iter_mut().zip(buf.chunks_exact(4)) {
+            let v = f32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+            *out = if v.is_finite() {
+                v.clamp(-1.0, 1.0)
+            } else {
+                0.0
+            };
+        }
+        Ok(&self.outputs)
+    }
+
+    /// Call the module's `stickup_reset` (if exported) and zero the outputs.
+    pub fn reset(&mut self) -> Result<()> {
+        self.outputs.iter_mut().for_each(|v| *v = 0.0);
+        let Some(reset) = self.reset else {
+            return Ok(());
+        };
+        self.store
+            .set_fuel(self.config.fuel_per_tick)
+            .map_err(|e| wasm_err("fuel", e))?;
+        reset
+            .call(&mut self.store, ())
+            .map_err(|e| wasm_err("stickup_reset", e))
+    }
+}
+
+/// A device with plugin output axes (see module docs).
+pub struct PluginDevice {
+    inner: Box<dyn Device>,
+    plugin: Option<WasmTransform>,
+    descs: Vec<ChannelDesc>,
+    /// State name per `(kind, index)` of the inner device's channels.
+    names: HashMap<(ChannelKind, u16), String>,
+    /// Current input values by state name.
+    values: HashMap<String, f32>,
+    /// Index of the first output axis.
+    first_output: u16,
+    /// Last reported output values.
+    reported: Vec<f32>,
+    last_tick: Option<Instant>,
+    was_connected: bool,
+    /// Why the plugin was disabled, until the next `try_poll` reports it.
+    fault: Option<Error>,
+}
+
+impl PluginDevice {
+    fn record(&mut self, ev: &InputKind) {
+        let (key, value) = match *ev {
+            InputKind::AxisMoved { axis, value } => ((ChannelKind::Axis, axis), value),
+            InputKind::ButtonPressed { button } => ((ChannelKind::Button, button), 1.0),
+            InputKind::ButtonReleased { button } => ((ChannelKind::Button, button), 0.0),
+            InputKind::HatChanged { hat, value } => ((ChannelKind::Hat, hat), f32::from(value)),
+            _ => return,
+        };
+        if let Some(name) = self.names.get(&key) {
+            self.values.insert(name.clone(), value);
+        }
+    }
+
+    fn run(&mut self, now: Instant, out: &mut Vec<InputKind>) {
+        let Some(plugin) = &mut self.plugin else {
+            return;
+        };
+        let dt = self
+            .last_tick
+            .map_or(0.0, |t| now.saturating_duration_since(t).as_secs_f32());
+        self.last_tick = Some(now);
+        let values = &self.values;
+        let outputs = match plugin.tick(|name| values.get(name).copied(), dt) {
+            Ok(outputs) => outputs.to_vec(),
+            Err(e) => {
+                #[cfg(feature = "debug-log")]
+                eprintln!("[stickup] {}: plugin disabled: {e}", self.inner.id());
+                self.plugin = None;
+                self.fault = Some(e);
+                vec![0.0; self.reported.len()]
+            }
+        };
+        for (j, (v, last)) in outputs.iter().zip(&mut self.reported).enumerate() {
+            if v != last {
+                *last = *v;
+                out.push(InputKind::AxisMoved {
+                    axis: self.first_output + j as u16,
+                    value: *v,
+                });
+            }
+        }
+    }
+}
+
+impl Device for PluginDevice {
+    fn poll(&mut self) -> Vec<InputKind> {
+        self.try_poll().unwrap_or_default()
+    }
+    /// Poll the inner device and run the plugin.
+    ///
+    /// After the plugin was disabled, the next call returns the error that disabled it
+    /// without polling the inner device (its reports wait for the following call).
+    fn try_poll(&mut self) -> Result<Vec<InputKind>> {
+        if let Some(e) = self.fault.take() {
+            return Err(e);
+        }
+        let mut events = self.inner.try_poll()?;
+        let connected = self.inner.is_connected();
+        if connected && !self.was_connected {
+            self.values.clear();
+            self.last_tick = None;
+            if let Some(p) = &mut self.plugin {
+                if let Err(e) = p.reset() {
+                    self.plugin = None;
+                    self.fault = Some(e);
+                }
+            }
+        }
+        self.was_connected = connected;
+        for ev in &events {
+            self.record(ev);
+        }
+        let now = self.inner.report_time().unwrap_or_else(Instant::now);
+        let mut outputs = Vec::new();
+        self.run(now, &mut outputs);
+        events.append(&mut outputs);
+        Ok(events)
+    }
+    /// The inner device's state, also recorded as the plugin's starting inputs.
+    fn initial_state(&mut self) -> Vec<InputKind> {
+        let events = self.inner.initial_state();
+        for ev in &events {
+            self.record(ev);
+        }
+        events
+    }
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+    fn id(&self) -> &str {
+        self.inner.id()
+    }
+    fn metadata(&self) -> DeviceMeta {
+        self.inner.metadata()
+    }
+    fn describe(&self) -> Vec<ChannelDesc> {
+        self.descs.clone()
+    }
+    fn report_id_diagnostics(&self) -> Option<ReportIdDiagnostics> {
+        self.inner.report_id_diagnostics()
+    }
+    fn battery(&self) -> Option<BatteryStatus> {
+        self.inner.battery()
+    }
+    fn fixed_player_index(&self) -> Option<u8> {
+        self.inner.fixed_player_index()
+    }
+    fn report_time(&self) -> Option<Instant> {
+        self.inner.report_time()
+    }
+    fn set_rumble(&mut self, low: f32, high: f32) -> Result<()> {
+        self.inner.set_rumble(low, high)
+    }
+    fn send_output(&mut self, cmd: &OutputCommand) -> Result<()> {
+        self.inner.send_output(cmd)
+    }
+    fn upload_effect(&mut self, effect: &FfbEffect) -> Result<EffectHandle> {
+        self.inner.upload_effect(effect)
+    }
+    fn play_effect(&mut self, handle: EffectHandle, op: EffectOp) -> Result<()> {
+        self.inner.play_effect(handle, op)
+    }
+    fn capture_reports(&mut self, path: Option<&Path>) -> Result<()> {
+        self.inner.capture_reports(path)
+    }
+}
+
+/// Run `plugin` on every poll of `device`, publishing its outputs as extra axes.
+///
+/// Fails if an input names no channel of `device`. Wrap devices before handing them to
+/// [`Manager::from_devices`](crate::manager::Manager::from_devices).
+pub fn attach_plugin(device: Box<dyn Device>, plugin: WasmTransform) -> Result<Box<dyn Device>> {
+    let mut descs = device.describe();
+    let names: HashMap<(ChannelKind, u16), String> = descs
+        .iter()
+        .map(|d| ((d.kind, d.idx), d.state_name()))
+        .collect();
+    if let Some(missing) = plugin
+        .config()
+        .inputs
+        .iter()
+        .find(|i| !names.values().any(|n| n == *i))
+    {
+        return Err(Error::Other(format!(
+            "wasm plugin: device {} has no channel {missing:?}",
+            device.id()
+        )));
+    }
+    let first_output = descs
+        .iter()
+        .filter(|d| d.kind == ChannelKind::Axis)
+        .map(|d| d.idx)
+        .max()
+        .map_or(0, |m| m.saturating_add(1));
+    for (j, name) in plugin.config().outputs.iter().enumerate() {
+        descs.push(ChannelDesc {
+            kind: ChannelKind::Axis,
+            idx: first_output + j as u16,
+            name: Some(name.clone()),
+            logical_min: -1,
+            logical_max: 1,
+            usage_page: None,
+            usage: None,
+            unit: None,
+            collection: None,
+            collection_name: None,
+        });
+    }
+    let reported = vec![0.0; plugin.config().outputs.len()];
+    Ok(Box::new(PluginDevice {
+        was_connected: device.is_connected(),
+        inner: device,
+        plugin: Some(plugin),
+        descs,
+        names,
+        values: HashMap::new(),
+        first_output,
+        reported,
+        last_tick: None,
+        fault: None,
+    }))
+}
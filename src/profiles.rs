@@ -0,0 +1,232 @@
+//! Binding profiles loaded from disk, reloaded when their files change.
+//!
+//! [`ProfileManager`] owns a set of profile files. Call [`poll`](ProfileManager::poll) once per
+//! frame/tick: files whose modification time changed are reloaded and reported as
+//! [`ProfileEvent::ProfileReloaded`], so curves and deadzones can be tuned in an editor while
+//! the application runs.
+//!
+//! ```no_run
+//! use stickup::profiles::{ProfileEvent, ProfileManager};
+//!
+//! let mut profiles = ProfileManager::new();
+//! profiles.load("flight.toml").expect("load profile");
+//! loop {
+//!     for ev in profiles.poll() {
+//!         if let ProfileEvent::ProfileReloadFailed { path, error } = ev {
+//!             eprintln!("{}: {error}", path.display());
+//!         }
+//!     }
+//!     let _profile = profiles.profile("flight.toml").unwrap();
+//!     // ... resolve with it
+//! }
+//! ```
+//!
+//! Change detection:
+//! - By default, `poll` compares modification times, at most once per
+//!   [`check_interval`](ProfileManager::check_interval) (250 ms).
+//! - With the **`hot-reload`** feature, a `notify` file watcher on each profile's directory
+//!   triggers the check instead, so idle polls don't touch the file system.
+//!
+//! A profile that fails to parse keeps its previous version and is reported as
+//! [`ProfileEvent::ProfileReloadFailed`].
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+#[cfg(feature = "hot-reload")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "hot-reload")]
+use std::sync::Arc;
+
+use crate::binding::{BindingProfile, ProfileMigrator};
+use crate::{Error, Result};
+
+/// Outcome of a reload triggered by a file change.
+#[derive(Debug, Clone)]
+pub enum ProfileEvent {
+    /// The file changed and the new profile is active.
+    ProfileReloaded { path: PathBuf },
+    /// The file changed but could not be loaded; the previous profile stays active.
+    ProfileReloadFailed { path: PathBuf, error: Error },
+}
+
+#[derive(Debug)]
+struct WatchedProfile {
+    path: PathBuf,
+    profile: BindingProfile,
+    migrator: ProfileMigrator,
+    modified: Option<SystemTime>,
+}
+
+/// Loaded profile files with change detection (see module docs).
+pub struct ProfileManager {
+    entries: Vec<WatchedProfile>,
+    interval: Duration,
+    last_check: Option<Instant>,
+    #[cfg(feature = "hot-reload")]
+    changed: Arc<AtomicBool>,
+    #[cfg(feature = "hot-reload")]
+    watcher: Option<notify::RecommendedWatcher>,
+}
+
+impl Default for ProfileManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProfileManager {
+    /// No profiles loaded.
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            interval: Duration::from_millis(250),
+            last_check: None,
+            #[cfg(feature = "hot-reload")]
+            changed: Arc::new(AtomicBool::new(false)),
+            #[cfg(feature = "hot-reload")]
+            watcher: None,
+        }
+    }
+
+    /// Builder: minimum time between modification-time checks (default 250 ms).
+    ///
+    /// With the `hot-reload` feature this only applies after the watcher reported a change.
+    pub fn check_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Load `path` (see [`BindingProfile::load`]) and watch it for changes.
+    ///
+    /// Loading a path that is already managed reloads it.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<&BindingProfile> {
+        self.load_with(path, ProfileMigrator::new())
+    }
+
+    /// Like [`load`](Self::load), running `migrator` on this and every later reload.
+    pub fn load_with(
+        &mut self,
+        path: impl AsRef<Path>,
+        migrator: ProfileMigrator,
+    ) -> Result<&BindingProfile> {
+        let path = path.as_ref().to_path_buf();
+        let modified = modified_time(&path);
+        let profile = BindingProfile::load_with(&path, &migrator)?;
+        self.watch(&path);
+        let entry = WatchedProfile {
+            path,
+            profile,
+            migrator,
+            modified,
+        };
+        let i = match self.entries.iter().position(|e| e.path == entry.path) {
+            Some(i) => {
+                self.entries[i] = entry;
+                i
+            }
+            None => {
+                self.entries.push(entry);
+                self.entries.len() - 1
+            }
+        };
+        Ok(&self.entries[i].profile)
+    }
+
+    /// Stop managing `path`.
+    pub fn unload(&mut self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        self.entries.retain(|e| e.path != path);
+    }
+
+    /// The current profile loaded from `path` (as passed to [`load`](Self::load)).
+    pub fn profile(&self, path: impl AsRef<Path>) -> Option<&BindingProfile> {
+        let path = path.as_ref();
+        self.entries
+            .iter()
+            .find(|e| e.path == path)
+            .map(|e| &e.profile)
+    }
+
+    /// Paths of the managed profiles.
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.entries.iter().map(|e| e.path.as_path())
+    }
+
+    /// Reload profiles whose files changed since the last check.
+    pub fn poll(&mut self) -> Vec<ProfileEvent> {
+        #[cfg(feature = "hot-reload")]
+        if self.watcher.is_some() && !self.changed.load(Ordering::Acquire) {
+            return Vec::new();
+        }
+
+        let now = Instant::now();
+        if let Some(last) = self.last_check {
+            if now.saturating_duration_since(last) < self.interval {
+                return Vec::new();
+            }
+        }
+        self.last_check = Some(now);
+        #[cfg(feature = "hot-reload")]
+        self.changed.store(false, Ordering::Release);
+
+        let mut events = Vec::new();
+        for e in &mut self.entries {
+            let modified = modified_time(&e.path);
+            if modified.is_none() || modified == e.modified {
+                // Missing (mid-save) or unchanged.
+                continue;
+            }
+            e.modified = modified;
+            match BindingProfile::load_with(&e.path, &e.migrator) {
+                Ok(p) => {
+                    e.profile = p;
+                    events.push(ProfileEvent::ProfileReloaded {
+                        path: e.path.clone(),
+                    });
+                }
+                Err(error) => events.push(ProfileEvent::ProfileReloadFailed {
+                    path: e.path.clone(),
+                    error,
+                }),
+            }
+        }
+        events
+    }
+
+    /// Start watching the directory containing `path` (editors often replace the file).
+    #[cfg(feature = "hot-reload")]
+    fn watch(&mut self, path: &Path) {
+        use notify::{RecursiveMode, Watcher};
+
+        if self.watcher.is_none() {
+            let changed = Arc::clone(&self.changed);
+            self.watcher =
+                notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+                    if res.is_ok() {
+                        changed.store(true, Ordering::Release);
+                    }
+                })
+                .ok();
+        }
+        let dir = match path.parent() {
+            Some(d) if !d.as_os_str().is_empty() => d,
+            _ => Path::new("."),
+        };
+        let watching = self
+            .watcher
+            .as_mut()
+            .is_some_and(|w| w.watch(dir, RecursiveMode::NonRecursive).is_ok());
+        if !watching {
+            // Fall back to interval polling.
+            self.watcher = None;
+        }
+    }
+
+    #[cfg(not(feature = "hot-reload"))]
+    fn watch(&mut self, _path: &Path) {}
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
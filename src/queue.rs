@@ -0,0 +1,234 @@
+//! Bounded event queues for slow consumers.
+//!
+//! An unbounded queue between a fast producer (device polling at 1 kHz) and a slow consumer
+//! (a UI thread that stalls, a network sink) grows without limit. An [`EventQueue`] holds at
+//! most `capacity` events instead:
+//! - an axis update for a `(device, axis)` that still has an undelivered update replaces
+//!   that update's value in place (*coalesced*) — only the latest position matters;
+//! - when the queue is full, the oldest event is dropped to make room (*dropped*).
+//!
+//! Both are counted in [`QueueStats`], so applications can tell when they fall behind.
+//!
+//! Bounded queues are used by [`Manager::enable_event_queue`](crate::manager::Manager::enable_event_queue)
+//! and [`InputEventBus::subscribe_bounded`](crate::bus::InputEventBus::subscribe_bounded).
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::event::{InputEvent, InputKind};
+
+/// Counters of a bounded queue.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QueueStats {
+    /// Events accepted.
+    pub events_queued: u64,
+    /// Events discarded because the queue was full.
+    pub events_dropped: u64,
+    /// Axis updates merged into a pending update of the same axis.
+    pub events_coalesced: u64,
+    /// Largest number of events pending at once.
+    pub high_water: usize,
+}
+
+impl std::ops::AddAssign for QueueStats {
+    fn add_assign(&mut self, o: Self) {
+        self.events_queued += o.events_queued;
+        self.events_dropped += o.events_dropped;
+        self.events_coalesced += o.events_coalesced;
+        self.high_water = self.high_water.max(o.high_water);
+    }
+}
+
+/// Bounded, axis-coalescing, drop-oldest event queue (see module docs).
+#[derive(Clone, Debug)]
+pub struct EventQueue {
+    capacity: usize,
+    events: VecDeque<(Arc<str>, InputEvent)>,
+    /// Events popped so far; `events[i]` has absolute position `popped + i`.
+    popped: u64,
+    /// Absolute position of the pending update per `(device, axis)`.
+    pending_axes: HashMap<(Arc<str>, u16), u64>,
+    stats: QueueStats,
+}
+
+impl EventQueue {
+    /// Queue holding at most `capacity` events (at least 1).
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            events: VecDeque::new(),
+            popped: 0,
+            pending_axes: HashMap::new(),
+            stats: QueueStats::default(),
+        }
+    }
+
+    /// Maximum number of pending events.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of pending events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Whether no events are pending.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Counters since creation (or the last [`reset_stats`](Self::reset_stats)).
+    pub fn stats(&self) -> QueueStats {
+        self.stats
+    }
+
+    /// Zero the counters.
+    pub fn reset_stats(&mut self) {
+        self.stats = QueueStats::default();
+    }
+
+    /// Enqueue one event.
+    pub fn push(&mut self, device_id: Arc<str>, event: InputEvent) {
+        self.stats.events_queued += 1;
+        if let InputKind::AxisMoved { axis, .. } = event.kind {
+            let key = (device_id, axis);
+            if let Some(&pos) = self.pending_axes.get(&key) {
+                let i = (pos - self.popped) as usize;
+                let slot = &mut self.events[i].1;
+                // Keep the queue position and sequence number, take the newer reading.
+                slot.kind = event.kind;
+                slot.at = event.at;
+                slot.timestamp = event.timestamp;
+                self.stats.events_coalesced += 1;
+                return;
+            }
+            let device_id = key.0.clone();
+            if self.events.len() == self.capacity {
+                self.drop_oldest();
+            }
+            self.pending_axes
+                .insert(key, self.popped + self.events.len() as u64);
+            self.events.push_back((device_id, event));
+        } else {
+            if self.events.len() == self.capacity {
+                self.drop_oldest();
+            }
+            self.events.push_back((device_id, event));
+        }
+        self.stats.high_water = self.stats.high_water.max(self.events.len());
+    }
+
+    fn drop_oldest(&mut self) {
+        if self.pop().is_some() {
+            self.stats.events_dropped += 1;
+        }
+    }
+
+    /// Dequeue the oldest event.
+    pub fn pop(&mut self) -> Option<(Arc<str>, InputEvent)> {
+        let (id, ev) = self.events.pop_front()?;
+        if let InputKind::AxisMoved { axis, .. } = ev.kind {
+            let key = (id.clone(), axis);
+            if self.pending_axes.get(&key) == Some(&self.popped) {
+                self.pending_axes.remove(&key);
+            }
+        }
+        self.popped += 1;
+        Some((id, ev))
+    }
+
+    /// Dequeue every pending event, oldest first.
+    pub fn drain(&mut self) -> Vec<(Arc<str>, InputEvent)> {
+        self.pending_axes.clear();
+        self.popped += self.events.len() as u64;
+        self.events.drain(..).collect()
+    }
+}
+
+/// State shared between the bus and a [`QueueReceiver`].
+#[derive(Debug)]
+pub(crate) struct SharedQueue {
+    queue: Mutex<EventQueue>,
+    ready: Condvar,
+}
+
+impl SharedQueue {
+    pub(crate) fn new(capacity: usize) -> Arc<Self> {
+        Arc::new(Self {
+            queue: Mutex::new(EventQueue::new(capacity)),
+            ready: Condvar::new(),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, EventQueue> {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn push(&self, device_id: Arc<str>, event: InputEvent) {
+        self.lock().push(device_id, event);
+        self.ready.notify_one();
+    }
+
+    pub(crate) fn stats(&self) -> QueueStats {
+        self.lock().stats()
+    }
+}
+
+/// Receiving end of a bounded bus subscription
+/// ([`InputEventBus::subscribe_bounded`](crate::bus::InputEventBus::subscribe_bounded)).
+///
+/// Drop it to unsubscribe.
+#[derive(Debug)]
+pub struct QueueReceiver {
+    pub(crate) shared: Arc<SharedQueue>,
+}
+
+impl QueueReceiver {
+    /// Next event, if one is pending.
+    pub fn try_recv(&self) -> Option<(Arc<str>, InputEvent)> {
+        self.shared.lock().pop()
+    }
+
+    /// Next event, waiting up to `timeout` for one.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<(Arc<str>, InputEvent)> {
+        let deadline = Instant::now() + timeout;
+        let mut q = self.shared.lock();
+        loop {
+            if let Some(ev) = q.pop() {
+                return Some(ev);
+            }
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return None;
+            }
+            q = self
+                .shared
+                .ready
+                .wait_timeout(q, left)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+    }
+
+    /// Every pending event, oldest first.
+    pub fn drain(&self) -> Vec<(Arc<str>, InputEvent)> {
+        self.shared.lock().drain()
+    }
+
+    /// Number of pending events.
+    pub fn len(&self) -> usize {
+        self.shared.lock().len()
+    }
+
+    /// Whether no events are pending.
+    pub fn is_empty(&self) -> bool {
+        self.shared.lock().is_empty()
+    }
+
+    /// Drop and coalesce counters of this subscription.
+    pub fn stats(&self) -> QueueStats {
+        self.shared.stats()
+    }
+}
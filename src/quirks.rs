@@ -0,0 +1,231 @@
+//! Per-device quirk/naming definitions.
+//!
+//! Vendor-defined usage pages (`0xFF00..=0xFFFF`) carry no standard meaning, so the HIDP parser
+//! can only label them generically (`"VendorAxis"`). A [`QuirkDb`] lets users and integrators
+//! describe those usages for specific VID/PIDs without code changes:
+//! - give a usage a display name (`"Trim wheel"`, `"Mode switch"`)
+//! - decide its semantics: a continuous axis, an on/off button, or a packed button matrix
+//! - state its physical unit ([`ChannelUnit`])
+//!
+//! Quirks are plain TOML:
+//! ```toml
+//! [[device]]
+//! vid = 0x3344
+//! pid = 0x8194
+//! name = "Throttle base"            # optional display name for the device
+//!
+//! [[device.usage]]
+//! usage_page = 0xFF00
+//! usage = 0x01
+//! name = "Trim wheel"
+//! kind = "axis"                     # "axis" (default) | "button" | "button_matrix"
+//! unit = "degrees"                  # optional, see ChannelUnit
+//!
+//! [[device.usage]]
+//! usage_page = 0xFF00
+//! usage = 0x02
+//! name = "Mode switch"
+//! kind = "button"                   # non-zero value = pressed
+//!
+//! [[device.usage]]                  # packed button matrix (button boxes, panels)
+//! usage_page = 0xFF00
+//! usage = 0x10
+//! name = "Panel"
+//! kind = "button_matrix"
+//! matrix = { bits = 32, first_bit = 0, cols = 8 }   # → "Panel R1C1" .. "Panel R4C8"
+//!
+//! [[device.usage]]                  # misdeclared range (any usage page)
+//! usage_page = 0x01
+//! usage = 0x32                      # Z: declares -32768..32767, sends 0..65535
+//! logical_min = 0
+//! logical_max = 65535
+//! signed = false                    # read raw bits as unsigned
+//! ```
+//!
+//! Range overrides are for devices whose descriptor lies about `Logical Minimum`/`Maximum`:
+//! an unsigned axis declared signed reads its center as the minimum and pegs over half its
+//! travel. Parsers already repair the common case of an unsigned maximum written as a short
+//! signed item (`Logical Maximum` below `Logical Minimum`); anything else needs a quirk.
+//!
+//! Channels can also be hidden per device with `hide = ["name", ..]` or `hidden = true` on a
+//! usage (see [`hide`](crate::hide)).
+//!
+//! Pass the database to discovery via
+//! [`DiscoveryOptions::quirks`](crate::manager::DiscoveryOptions::quirks).
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::event::{ChannelDesc, ChannelUnit};
+use crate::{Error, Result};
+
+/// How a quirk-annotated usage should be exposed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageKind {
+    /// Continuous value, normalized like any other axis.
+    #[default]
+    Axis,
+    /// On/off control: a non-zero value is reported as pressed.
+    Button,
+    /// Packed bitfield expanded into one button per bit (see [`MatrixLayout`]).
+    ButtonMatrix,
+}
+
+/// Bit layout of a packed button matrix.
+///
+/// Bits are read LSB-first from the usage's raw value bytes (little-endian), starting at
+/// `first_bit`. Each bit becomes its own button channel.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MatrixLayout {
+    /// Number of buttons (bits) to expand.
+    pub bits: u16,
+    /// First bit of the matrix within the raw value.
+    #[serde(default)]
+    pub first_bit: u16,
+    /// Optional column count, used for `R<row>C<col>` names.
+    #[serde(default)]
+    pub cols: Option<u16>,
+    /// Optional explicit names, one per bit (missing entries fall back to generated names).
+    #[serde(default)]
+    pub names: Vec<String>,
+    /// Set if a cleared bit means "pressed".
+    #[serde(default)]
+    pub active_low: bool,
+}
+
+impl MatrixLayout {
+    /// State of button `i` in `raw` (bytes of the usage value, little-endian).
+    pub fn pressed(&self, raw: &[u8], i: u16) -> bool {
+        let bit = (self.first_bit + i) as usize;
+        let set = raw
+            .get(bit / 8)
+            .map(|b| b & (1 << (bit % 8)) != 0)
+            .unwrap_or(false);
+        set != self.active_low
+    }
+
+    /// Display name for button `i`, using `base` as the prefix for generated names.
+    pub fn button_name(&self, base: &str, i: u16) -> String {
+        if let Some(n) = self.names.get(i as usize) {
+            return n.clone();
+        }
+        match self.cols {
+            Some(cols) if cols > 0 => format!("{base} R{}C{}", i / cols + 1, i % cols + 1),
+            _ => format!("{base} {}", i + 1),
+        }
+    }
+}
+
+/// Annotation for one `(usage_page, usage)` on a device.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UsageQuirk {
+    pub usage_page: u16,
+    pub usage: u16,
+    /// Display name used for the channel.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Channel semantics (default: axis).
+    #[serde(default)]
+    pub kind: UsageKind,
+    /// Physical unit reported in [`ChannelDesc::unit`] (overrides the usage default).
+    #[serde(default)]
+    pub unit: Option<ChannelUnit>,
+    /// Bit layout, required when `kind = "button_matrix"`.
+    #[serde(default)]
+    pub matrix: Option<MatrixLayout>,
+    /// Hide channels with this usage (see [`hide`](crate::hide)).
+    #[serde(default)]
+    pub hidden: bool,
+    /// Logical minimum replacing the descriptor's.
+    #[serde(default)]
+    pub logical_min: Option<i32>,
+    /// Logical maximum replacing the descriptor's.
+    #[serde(default)]
+    pub logical_max: Option<i32>,
+    /// Sign-extend raw values (`true`) or read them as unsigned (`false`); by default values
+    /// are signed when the (overridden) logical minimum is negative.
+    #[serde(default)]
+    pub signed: Option<bool>,
+}
+
+impl UsageQuirk {
+    /// `(logical_min, logical_max, signed)` for a field declared with the given range, after
+    /// this quirk's overrides.
+    pub fn logical_range(&self, logical_min: i32, logical_max: i32) -> (i32, i32, bool) {
+        let min = self.logical_min.unwrap_or(logical_min);
+        let max = self.logical_max.unwrap_or(logical_max);
+        (min, max, self.signed.unwrap_or(min < 0))
+    }
+}
+
+/// All quirks for one VID/PID.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeviceQuirks {
+    pub vid: u16,
+    pub pid: u16,
+    /// Optional display name overriding the product string.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Usage annotations.
+    #[serde(default, rename = "usage")]
+    pub usages: Vec<UsageQuirk>,
+    /// Channel names to hide (see [`hide`](crate::hide)).
+    #[serde(default)]
+    pub hide: Vec<String>,
+}
+
+impl DeviceQuirks {
+    /// Look up the annotation for a usage, if any.
+    pub fn usage(&self, usage_page: u16, usage: u16) -> Option<&UsageQuirk> {
+        self.usages
+            .iter()
+            .find(|u| u.usage_page == usage_page && u.usage == usage)
+    }
+
+    /// Should this channel be hidden?
+    pub fn is_hidden(&self, desc: &ChannelDesc) -> bool {
+        desc.name
+            .as_ref()
+            .is_some_and(|name| self.hide.contains(name))
+            || match (desc.usage_page, desc.usage) {
+                (Some(page), Some(usage)) => self.usage(page, usage).is_some_and(|u| u.hidden),
+                _ => false,
+            }
+    }
+}
+
+/// A collection of [`DeviceQuirks`], typically loaded from a TOML file.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuirkDb {
+    #[serde(default, rename = "device")]
+    pub devices: Vec<DeviceQuirks>,
+}
+
+impl QuirkDb {
+    /// Parse a quirk database from TOML text.
+    pub fn from_toml_str(s: &str) -> Result<Self> {
+        toml::from_str(s).map_err(|e| Error::Other(format!("invalid quirks file: {e}")))
+    }
+
+    /// Load a quirk database from a TOML file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Other(format!("read {}: {e}", path.display())))?;
+        Self::from_toml_str(&text)
+    }
+
+    /// Merge another database into this one (entries from `other` take precedence).
+    pub fn merge(&mut self, other: QuirkDb) {
+        let mut devices = other.devices;
+        devices.append(&mut self.devices);
+        self.devices = devices;
+    }
+
+    /// Quirks for a VID/PID, if any.
+    pub fn for_device(&self, vid: u16, pid: u16) -> Option<&DeviceQuirks> {
+        self.devices.iter().find(|d| d.vid == vid && d.pid == pid)
+    }
+}
@@ -0,0 +1,162 @@
+//! Identity continuity across unplug/replug.
+//!
+//! Device ids come from fingerprints (see
+//! [`DeviceFingerprint::to_string`](crate::device::DeviceFingerprint::to_string)). Devices
+//! with a serial number get the same id on every plug, but serial-less devices fall back to
+//! their OS path, which changes when the device moves to another USB port. Without help,
+//! such a device comes back as a stranger: a new [`DeviceId`](crate::event::DeviceId), no
+//! player slot, no bindings.
+//!
+//! The [`Manager`](crate::manager::Manager) remembers devices that left
+//! ([`departed_devices`](crate::manager::Manager::departed_devices)). When a device with an
+//! unknown id appears, [`match_departed`] looks for the device it replaces:
+//! - vendor/product id, HID usage page/usage, and interface number must agree
+//! - a matching serial number wins; differing serials never match
+//! - among serial-less candidates, the one with the same path wins, otherwise the match is
+//!   only made when a single candidate is left
+//!
+//! A match is [`reattach`]ed: wrapped so it reports the departed device's id, which keeps
+//! its handle, state keys, player assignment, and bindings.
+//!
+//! ```
+//! use stickup::event::DeviceIds;
+//! use stickup::manager::ManagedInfo;
+//! use stickup::metadata::DeviceMeta;
+//! use stickup::reconnect::match_departed;
+//!
+//! let meta = |path: &str| DeviceMeta {
+//!     vid: Some(0x044f),
+//!     pid: Some(0x0404),
+//!     path: Some(path.into()),
+//!     ..Default::default()
+//! };
+//! let departed = [ManagedInfo {
+//!     id: "044f:0404@port1".into(),
+//!     handle: DeviceIds::new().intern("044f:0404@port1"),
+//!     name: "Throttle".into(),
+//!     meta: meta("port1"),
+//! }];
+//! // Same model, new port: the only candidate.
+//! let old = match_departed(&meta("port2"), &departed).unwrap();
+//! assert_eq!(old.id, "044f:0404@port1");
+//! ```
+
+use std::path::Path;
+use std::time::Instant;
+
+use crate::device::{BatteryStatus, Device, ReportIdDiagnostics};
+use crate::event::{ChannelDesc, InputKind};
+use crate::ffb::{EffectHandle, EffectOp, FfbEffect};
+use crate::manager::ManagedInfo;
+use crate::metadata::DeviceMeta;
+use crate::output::OutputCommand;
+use crate::Result;
+
+/// The departed device a newly seen device (described by `meta`) replaces, if any (see
+/// module docs).
+pub fn match_departed<'a>(
+    meta: &DeviceMeta,
+    departed: impl IntoIterator<Item = &'a ManagedInfo>,
+) -> Option<&'a ManagedInfo> {
+    let serial = |m: &DeviceMeta| m.serial_number.clone().filter(|s| !s.is_empty());
+    let new_serial = serial(meta);
+    let candidates: Vec<&ManagedInfo> = departed
+        .into_iter()
+        .filter(|old| {
+            let m = &old.meta;
+            meta.vid.is_some()
+                && (m.vid, m.pid) == (meta.vid, meta.pid)
+                && (m.usage_page, m.usage) == (meta.usage_page, meta.usage)
+                && m.interface_number == meta.interface_number
+                && serial(m) == new_serial
+        })
+        .collect();
+    if new_serial.is_some() {
+        return candidates.first().copied();
+    }
+    if let Some(same_path) = candidates
+        .iter()
+        .find(|old| meta.path.is_some() && old.meta.path == meta.path)
+    {
+        return Some(same_path);
+    }
+    match candidates[..] {
+        [only] => Some(only),
+        _ => None,
+    }
+}
+
+/// A device presented under the id of the device it replaces.
+pub struct Reattached {
+    inner: Box<dyn Device>,
+    id: String,
+}
+
+impl Reattached {
+    /// The id the wrapped device reports on its own.
+    pub fn original_id(&self) -> &str {
+        self.inner.id()
+    }
+}
+
+impl Device for Reattached {
+    fn poll(&mut self) -> Vec<InputKind> {
+        self.inner.poll()
+    }
+    fn try_poll(&mut self) -> Result<Vec<InputKind>> {
+        self.inner.try_poll()
+    }
+    fn initial_state(&mut self) -> Vec<InputKind> {
+        self.inner.initial_state()
+    }
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+    fn id(&self) -> &str {
+        &self.id
+    }
+    fn metadata(&self) -> DeviceMeta {
+        self.inner.metadata()
+    }
+    fn describe(&self) -> Vec<ChannelDesc> {
+        self.inner.describe()
+    }
+    fn report_id_diagnostics(&self) -> Option<ReportIdDiagnostics> {
+        self.inner.report_id_diagnostics()
+    }
+    fn battery(&self) -> Option<BatteryStatus> {
+        self.inner.battery()
+    }
+    fn fixed_player_index(&self) -> Option<u8> {
+        self.inner.fixed_player_index()
+    }
+    fn report_time(&self) -> Option<Instant> {
+        self.inner.report_time()
+    }
+    fn set_rumble(&mut self, low: f32, high: f32) -> Result<()> {
+        self.inner.set_rumble(low, high)
+    }
+    fn send_output(&mut self, cmd: &OutputCommand) -> Result<()> {
+        self.inner.send_output(cmd)
+    }
+    fn upload_effect(&mut self, effect: &FfbEffect) -> Result<EffectHandle> {
+        self.inner.upload_effect(effect)
+    }
+    fn play_effect(&mut self, handle: EffectHandle, op: EffectOp) -> Result<()> {
+        self.inner.play_effect(handle, op)
+    }
+    fn capture_reports(&mut self, path: Option<&Path>) -> Result<()> {
+        self.inner.capture_reports(path)
+    }
+}
+
+/// Present `device` under `id` (the departed device's id).
+pub fn reattach(device: Box<dyn Device>, id: impl Into<String>) -> Box<dyn Device> {
+    Box::new(Reattached {
+        inner: device,
+        id: id.into(),
+    })
+}
@@ -0,0 +1,639 @@
+//! Event recording and replay.
+//!
+//! A [`Recorder`] writes timestamped [`InputEvent`]s, plus a description of each device
+//! (name, metadata, channels), to a file. [`Recording`] reads the file back and
+//! [`ReplayDevice`] plays one device's events through the [`Device`] trait with their original
+//! timing, so a user's session can be reproduced against parsers, bindings, or a
+//! [`Manager`] without the hardware:
+//!
+//! ```no_run
+//! use stickup::recorder::{Recorder, Recording};
+//! use stickup::Manager;
+//!
+//! # fn main() -> stickup::Result<()> {
+//! let mut mgr = Manager::discover()?;
+//! let mut rec = Recorder::create("session.jsonl")?;
+//! rec.add_manager_devices(&mgr)?;
+//! for _ in 0..1000 {
+//!     for (id, ev) in mgr.poll_events_timed() {
+//!         rec.record(&id, &ev)?;
+//!     }
+//! }
+//! rec.finish()?;
+//!
+//! // Later: replay into a manager.
+//! let replay = Recording::load("session.jsonl")?.replay_devices();
+//! let mut mgr = Manager::from_devices(replay);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Two formats are supported ([`RecordFormat`]):
+//! - **JSONL** — one JSON object per line (`{"record":"device",..}` / `{"record":"event",..}`),
+//!   easy to read, diff and edit;
+//! - **binary** — compact little-endian records, for long sessions and high-rate devices.
+//!
+//! [`Recording::parse`] detects the format from the content.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::device::Device;
+use crate::event::{ChannelDesc, DeviceId, InputEvent, InputKind};
+use crate::manager::{Manager, MARKER_SOURCE};
+use crate::metadata::DeviceMeta;
+use crate::{Error, Result};
+
+/// Magic bytes starting a binary recording (format version 1).
+const BINARY_MAGIC: &[u8; 8] = b"STKREC\0\x01";
+
+const TAG_DEVICE: u8 = 0;
+const TAG_ID: u8 = 1;
+const TAG_EVENT: u8 = 2;
+
+/// Recording file format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// One JSON record per line.
+    Jsonl,
+    /// Compact binary records.
+    Binary,
+}
+
+impl RecordFormat {
+    /// Guess from a file extension: `.jsonl` / `.json` → JSONL, anything else binary.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("jsonl") || ext.eq_ignore_ascii_case("json") => {
+                RecordFormat::Jsonl
+            }
+            _ => RecordFormat::Binary,
+        }
+    }
+}
+
+/// Description of a recorded device.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedDevice {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub meta: DeviceMeta,
+    #[serde(default)]
+    pub channels: Vec<ChannelDesc>,
+}
+
+/// One recorded event and the device it came from.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    pub device: String,
+    #[serde(flatten)]
+    pub event: InputEvent,
+}
+
+/// JSONL line.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum Record {
+    Device(RecordedDevice),
+    Event(RecordedEvent),
+}
+
+/// Writes devices and events to a recording (see module docs).
+pub struct Recorder<W: Write> {
+    out: W,
+    format: RecordFormat,
+    /// Binary format: device id → interned index.
+    ids: HashMap<String, u16>,
+    events: u64,
+}
+
+impl Recorder<BufWriter<File>> {
+    /// Create (truncate) `path`, choosing the format from its extension
+    /// (see [`RecordFormat::from_path`]).
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        Self::create_as(path, RecordFormat::from_path(path))
+    }
+
+    /// Create (truncate) `path` in `format`.
+    pub fn create_as(path: impl AsRef<Path>, format: RecordFormat) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .map_err(|e| Error::Other(format!("create {}: {e}", path.display())))?;
+        Recorder::new(BufWriter::new(file), format)
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    /// Record into `out` (writes the format header immediately).
+    pub fn new(mut out: W, format: RecordFormat) -> Result<Self> {
+        if format == RecordFormat::Binary {
+            out.write_all(BINARY_MAGIC).map_err(write_err)?;
+        }
+        Ok(Self {
+            out,
+            format,
+            ids: HashMap::new(),
+            events: 0,
+        })
+    }
+
+    /// Number of events recorded so far.
+    pub fn event_count(&self) -> u64 {
+        self.events
+    }
+
+    /// Record a device description (used by [`ReplayDevice::describe`] and friends).
+    pub fn add_device(&mut self, device: &RecordedDevice) -> Result<()> {
+        match self.format {
+            RecordFormat::Jsonl => self.write_line(&Record::Device(device.clone())),
+            RecordFormat::Binary => {
+                let json = serde_json::to_vec(device)
+                    .map_err(|e| Error::Other(format!("encode device: {e}")))?;
+                let mut buf = vec![TAG_DEVICE];
+                buf.extend_from_slice(&(json.len() as u32).to_le_bytes());
+                buf.extend_from_slice(&json);
+                self.out.write_all(&buf).map_err(write_err)
+            }
+        }
+    }
+
+    /// Record the description of every device `mgr` currently manages.
+    pub fn add_manager_devices(&mut self, mgr: &Manager) -> Result<()> {
+        for info in mgr.devices() {
+            self.add_device(&RecordedDevice {
+                id: info.id.clone(),
+                name: info.name.clone(),
+                meta: info.meta.clone(),
+                channels: mgr.channels(&info.id).unwrap_or_default().to_vec(),
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Record one event from `device_id`.
+    pub fn record(&mut self, device_id: &str, event: &InputEvent) -> Result<()> {
+        self.events += 1;
+        match self.format {
+            RecordFormat::Jsonl => self.write_line(&Record::Event(RecordedEvent {
+                device: device_id.to_string(),
+                event: event.clone(),
+            })),
+            RecordFormat::Binary => {
+                let mut buf = Vec::with_capacity(48);
+                let idx = match self.ids.get(device_id) {
+                    Some(&idx) => idx,
+                    None => {
+                        let idx = self.ids.len() as u16;
+                        self.ids.insert(device_id.to_string(), idx);
+                        buf.push(TAG_ID);
+                        buf.extend_from_slice(&idx.to_le_bytes());
+                        put_str(&mut buf, device_id);
+                        idx
+                    }
+                };
+                buf.push(TAG_EVENT);
+                buf.extend_from_slice(&idx.to_le_bytes());
+                buf.extend_from_slice(&(event.timestamp.as_micros() as u64).to_le_bytes());
+                buf.extend_from_slice(&event.seq.to_le_bytes());
+                encode_kind(&mut buf, &event.kind);
+                self.out.write_all(&buf).map_err(write_err)
+            }
+        }
+    }
+
+    /// Record a batch as returned by
+    /// [`Manager::poll_events_timed`](crate::manager::Manager::poll_events_timed).
+    pub fn record_all(&mut self, events: &[(String, InputEvent)]) -> Result<()> {
+        for (id, ev) in events {
+            self.record(id, ev)?;
+        }
+        Ok(())
+    }
+
+    /// Flush buffered output.
+    pub fn flush(&mut self) -> Result<()> {
+        self.out.flush().map_err(write_err)
+    }
+
+    /// Flush and return the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.flush()?;
+        Ok(self.out)
+    }
+
+    fn write_line(&mut self, record: &Record) -> Result<()> {
+        let mut line =
+            serde_json::to_vec(record).map_err(|e| Error::Other(format!("encode record: {e}")))?;
+        line.push(b'\n');
+        self.out.write_all(&line).map_err(write_err)
+    }
+}
+
+fn write_err(e: std::io::Error) -> Error {
+    Error::Other(format!("write recording: {e}"))
+}
+
+fn invalid(what: &str) -> Error {
+    Error::Other(format!("invalid recording: {what}"))
+}
+
+fn put_str(buf: &mut Vec<u8>, s: &str) {
+    let bytes = &s.as_bytes()[..s.len().min(u16::MAX as usize)];
+    buf.extend_from_slice(&(bytes.len() as u16).to_le_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+fn encode_kind(buf: &mut Vec<u8>, kind: &InputKind) {
+    let f32s = |buf: &mut Vec<u8>, v: &[f32]| {
+        for x in v {
+            buf.extend_from_slice(&x.to_le_bytes());
+        }
+    };
+    match kind {
+        InputKind::AxisMoved { axis, value } => {
+            buf.push(0);
+            buf.extend_from_slice(&axis.to_le_bytes());
+            f32s(buf, &[*value]);
+        }
+        InputKind::ButtonPressed { button } => {
+            buf.push(1);
+            buf.extend_from_slice(&button.to_le_bytes());
+        }
+        InputKind::ButtonReleased { button } => {
+            buf.push(2);
+            buf.extend_from_slice(&button.to_le_bytes());
+        }
+        InputKind::KeyPressed { scancode, extended } => {
+            buf.push(3);
+            buf.extend_from_slice(&scancode.to_le_bytes());
+            buf.push(*extended as u8);
+        }
+        InputKind::KeyReleased { scancode, extended } => {
+            buf.push(4);
+            buf.extend_from_slice(&scancode.to_le_bytes());
+            buf.push(*extended as u8);
+        }
+        InputKind::HatChanged { hat, value } => {
+            buf.push(5);
+            buf.extend_from_slice(&hat.to_le_bytes());
+            buf.extend_from_slice(&value.to_le_bytes());
+        }
+        InputKind::TouchMoved {
+            surface,
+            finger,
+            active,
+            x,
+            y,
+        } => {
+            buf.push(6);
+            buf.extend_from_slice(&surface.to_le_bytes());
+            buf.push(*finger);
+            buf.push(*active as u8);
+            f32s(buf, &[*x, *y]);
+        }
+        InputKind::MotionChanged {
+            sensor,
+            gyro,
+            accel,
+        } => {
+            buf.push(7);
+            buf.extend_from_slice(&sensor.to_le_bytes());
+            f32s(buf, gyro);
+            f32s(buf, accel);
+        }
+        InputKind::BatteryChanged { percent, charging } => {
+            buf.push(8);
+            buf.push(*percent);
+            buf.push(*charging as u8);
+        }
+        InputKind::Marker { label } => {
+            buf.push(9);
+            put_str(buf, label);
+        }
+        InputKind::RelativeMoved { axis, delta } => {
+            buf.push(10);
+            buf.extend_from_slice(&axis.to_le_bytes());
+            f32s(buf, &[*delta]);
+        }
+    }
+}
+
+/// Cursor over binary recording bytes.
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.data.len() < n {
+            return Err(invalid("truncated record"));
+        }
+        let (head, tail) = self.data.split_at(n);
+        self.data = tail;
+        Ok(head)
+    }
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+    fn i16(&mut self) -> Result<i16> {
+        Ok(i16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+    fn f32(&mut self) -> Result<f32> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+    fn f32x3(&mut self) -> Result<[f32; 3]> {
+        Ok([self.f32()?, self.f32()?, self.f32()?])
+    }
+    fn bool(&mut self) -> Result<bool> {
+        Ok(self.u8()? != 0)
+    }
+    fn str(&mut self) -> Result<String> {
+        let len = self.u16()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| invalid("non-UTF-8 string"))
+    }
+
+    fn kind(&mut self) -> Result<InputKind> {
+        Ok(match self.u8()? {
+            0 => InputKind::AxisMoved {
+                axis: self.u16()?,
+                value: self.f32()?,
+            },
+            1 => InputKind::ButtonPressed {
+                button: self.u16()?,
+            },
+            2 => InputKind::ButtonReleased {
+                button: self.u16()?,
+            },
+            3 => InputKind::KeyPressed {
+                scancode: self.u16()?,
+                extended: self.bool()?,
+            },
+            4 => InputKind::KeyReleased {
+                scancode: self.u16()?,
+                extended: self.bool()?,
+            },
+            5 => InputKind::HatChanged {
+                hat: self.u16()?,
+                value: self.i16()?,
+            },
+            6 => InputKind::TouchMoved {
+                surface: self.u16()?,
+                finger: self.u8()?,
+                active: self.bool()?,
+                x: self.f32()?,
+                y: self.f32()?,
+            },
+            7 => InputKind::MotionChanged {
+                sensor: self.u16()?,
+                gyro: self.f32x3()?,
+                accel: self.f32x3()?,
+            },
+            8 => InputKind::BatteryChanged {
+                percent: self.u8()?,
+                charging: self.bool()?,
+            },
+            9 => InputKind::Marker {
+                label: Arc::from(self.str()?),
+            },
+            10 => InputKind::RelativeMoved {
+                axis: self.u16()?,
+                delta: self.f32()?,
+            },
+            t => return Err(invalid(&format!("unknown event type {t}"))),
+        })
+    }
+}
+
+/// A recording read back from a file (see module docs).
+#[derive(Clone, Debug, Default)]
+pub struct Recording {
+    /// Recorded device descriptions, in recording order.
+    pub devices: Vec<RecordedDevice>,
+    /// Recorded events, in recording order.
+    pub events: Vec<RecordedEvent>,
+}
+
+impl Recording {
+    /// Read a recording file (either format).
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)
+            .map_err(|e| Error::Other(format!("read {}: {e}", path.display())))?;
+        Self::parse(&data)
+    }
+
+    /// Parse recording bytes, detecting the format from the binary header.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        match data.strip_prefix(BINARY_MAGIC.as_slice()) {
+            Some(rest) => Self::parse_binary(rest),
+            None => Self::parse_jsonl(data),
+        }
+    }
+
+    fn parse_jsonl(data: &[u8]) -> Result<Self> {
+        let text = std::str::from_utf8(data).map_err(|_| invalid("not UTF-8 JSONL"))?;
+        let mut out = Self::default();
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: Record =
+                serde_json::from_str(line).map_err(|e| invalid(&format!("line {}: {e}", i + 1)))?;
+            match record {
+                Record::Device(d) => out.devices.push(d),
+                Record::Event(e) => out.events.push(e),
+            }
+        }
+        Ok(out)
+    }
+
+    fn parse_binary(data: &[u8]) -> Result<Self> {
+        let mut r = Reader { data };
+        let mut out = Self::default();
+        let mut ids: HashMap<u16, String> = HashMap::new();
+        while !r.data.is_empty() {
+            match r.u8()? {
+                TAG_DEVICE => {
+                    let len = r.u32()? as usize;
+                    let device = serde_json::from_slice(r.take(len)?)
+                        .map_err(|e| invalid(&format!("device record: {e}")))?;
+                    out.devices.push(device);
+                }
+                TAG_ID => {
+                    let idx = r.u16()?;
+                    ids.insert(idx, r.str()?);
+                }
+                TAG_EVENT => {
+                    let idx = r.u16()?;
+                    let device = ids
+                        .get(&idx)
+                        .cloned()
+                        .ok_or_else(|| invalid("event for undeclared device"))?;
+                    let timestamp = Duration::from_micros(r.u64()?);
+                    let seq = r.u64()?;
+                    let kind = r.kind()?;
+                    out.events.push(RecordedEvent {
+                        device,
+                        event: InputEvent {
+                            at: Instant::now(),
+                            timestamp,
+                            seq,
+                            device: DeviceId::UNKNOWN,
+                            kind,
+                        },
+                    });
+                }
+                t => return Err(invalid(&format!("unknown record tag {t}"))),
+            }
+        }
+        Ok(out)
+    }
+
+    /// One [`ReplayDevice`] per recorded device (plus devices that only appear in events).
+    ///
+    /// All devices share the recording's time base: event times are relative to the first
+    /// event in the file. Markers are not replayed; use the events directly for those.
+    pub fn replay_devices(&self) -> Vec<Box<dyn Device>> {
+        let start = self
+            .events
+            .iter()
+            .map(|e| e.event.timestamp)
+            .min()
+            .unwrap_or_default();
+        let mut devices: Vec<RecordedDevice> = self.devices.clone();
+        for e in &self.events {
+            if e.device != MARKER_SOURCE && !devices.iter().any(|d| d.id == e.device) {
+                devices.push(RecordedDevice {
+                    id: e.device.clone(),
+                    name: e.device.clone(),
+                    meta: DeviceMeta::default(),
+                    channels: Vec::new(),
+                });
+            }
+        }
+        devices
+            .into_iter()
+            .map(|d| {
+                let events = self
+                    .events
+                    .iter()
+                    .filter(|e| e.device == d.id)
+                    .map(|e| {
+                        (
+                            e.event.timestamp.saturating_sub(start),
+                            e.event.kind.clone(),
+                        )
+                    })
+                    .collect();
+                Box::new(ReplayDevice::new(d, events)) as Box<dyn Device>
+            })
+            .collect()
+    }
+}
+
+/// A [`Device`] that plays back recorded events with their original timing.
+///
+/// The clock starts at the first [`poll`](Device::poll); each poll returns the events whose
+/// time has come. [`describe`](Device::describe), [`name`](Device::name),
+/// [`id`](Device::id) and [`metadata`](Device::metadata) come from the recorded device.
+pub struct ReplayDevice {
+    info: RecordedDevice,
+    /// `(time since start, event)`, sorted by time.
+    events: Vec<(Duration, InputKind)>,
+    next: usize,
+    started: Option<Instant>,
+    speed: f32,
+}
+
+impl ReplayDevice {
+    /// Replay `events` (`(time since start, event)`) as `info`.
+    pub fn new(info: RecordedDevice, mut events: Vec<(Duration, InputKind)>) -> Self {
+        events.sort_by_key(|(t, _)| *t);
+        Self {
+            info,
+            events,
+            next: 0,
+            started: None,
+            speed: 1.0,
+        }
+    }
+
+    /// Builder: playback speed multiplier (`2.0` = twice as fast). Non-positive values are
+    /// treated as `1.0`.
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = if speed > 0.0 && speed.is_finite() {
+            speed
+        } else {
+            1.0
+        };
+        self
+    }
+
+    /// Have all events been delivered?
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.events.len()
+    }
+
+    /// Rewind to the start; the clock restarts at the next poll.
+    pub fn restart(&mut self) {
+        self.next = 0;
+        self.started = None;
+    }
+
+    /// Deliver every remaining event regardless of timing.
+    pub fn drain_all(&mut self) -> Vec<InputKind> {
+        let out = self.events[self.next..]
+            .iter()
+            .map(|(_, k)| k.clone())
+            .collect();
+        self.next = self.events.len();
+        out
+    }
+}
+
+impl Device for ReplayDevice {
+    fn poll(&mut self) -> Vec<InputKind> {
+        let started = *self.started.get_or_insert_with(Instant::now);
+        let now = started.elapsed().mul_f32(self.speed);
+        let end = self.next
+            + self.events[self.next..]
+                .iter()
+                .take_while(|(t, _)| *t <= now)
+                .count();
+        let out = self.events[self.next..end]
+            .iter()
+            .map(|(_, k)| k.clone())
+            .collect();
+        self.next = end;
+        out
+    }
+    fn name(&self) -> &str {
+        &self.info.name
+    }
+    fn id(&self) -> &str {
+        &self.info.id
+    }
+    fn metadata(&self) -> DeviceMeta {
+        self.info.meta.clone()
+    }
+    fn describe(&self) -> Vec<ChannelDesc> {
+        self.info.channels.clone()
+    }
+}
@@ -0,0 +1,208 @@
+//! Raw HID report capture for bug reports.
+//!
+//! Parser bugs on exotic hardware (HOTAS bases, button boxes, wheels) are hard to reproduce
+//! without the device. With capture enabled, a HID device writes every input report it reads
+//! (report ID, bytes, time since capture start) to a file, preceded by its report descriptor
+//! and metadata. Users attach the file to an issue; maintainers load it with
+//! [`ReportCapture::load`] and feed the exact byte stream to a parser in a test via
+//! [`ReportCapture::replay`].
+//!
+//! ```no_run
+//! # fn main() -> stickup::Result<()> {
+//! let mut mgr = stickup::Manager::discover()?;
+//! let id = mgr.devices()[0].id.clone();
+//! mgr.capture_reports(&id, Some("throttle-capture.jsonl"))?;
+//! // ... reproduce the problem while polling ...
+//! mgr.capture_reports(&id, None::<&str>)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The file is JSONL: a `header` line, then one `report` line per input report, with byte
+//! strings hex-encoded.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::device::{DeviceFingerprint, ParseCtx, ReportParser};
+use crate::event::InputKind;
+use crate::metadata::DeviceMeta;
+use crate::{Error, Result};
+
+/// First line of a capture file.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CaptureHeader {
+    /// Device metadata at capture time.
+    pub meta: DeviceMeta,
+    /// HID report descriptor (hex), if the platform could provide it.
+    #[serde(default)]
+    pub descriptor: Option<String>,
+    /// Whether reads carry a leading report ID byte
+    /// ([`ReportParser::expects_report_id_prefix`]).
+    pub report_id_prefix: bool,
+}
+
+impl CaptureHeader {
+    /// Decoded report descriptor bytes.
+    pub fn descriptor_bytes(&self) -> Option<Vec<u8>> {
+        self.descriptor.as_deref().and_then(from_hex)
+    }
+}
+
+/// One captured input report.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CapturedReport {
+    /// Time since capture start.
+    pub at: Duration,
+    /// Report ID (`0` for devices without numbered reports).
+    pub report_id: u8,
+    /// Payload bytes after the report ID.
+    pub payload: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum Line {
+    Header(CaptureHeader),
+    Report {
+        t_us: u64,
+        report_id: u8,
+        data: String,
+    },
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    s.as_bytes()
+        .chunks(2)
+        .map(|pair| match pair {
+            [hi, lo] => Some((hex_digit(*hi)? << 4) | hex_digit(*lo)?),
+            _ => None,
+        })
+        .collect()
+}
+
+fn hex_digit(c: u8) -> Option<u8> {
+    (c as char).to_digit(16).map(|d| d as u8)
+}
+
+fn write_err(e: impl std::fmt::Display) -> Error {
+    Error::Other(format!("write report capture: {e}"))
+}
+
+/// Writes a capture file; used by backends while capture is enabled.
+pub struct ReportCaptureWriter {
+    out: BufWriter<File>,
+    start: Instant,
+}
+
+impl ReportCaptureWriter {
+    /// Create (truncate) `path` and write the header.
+    pub fn create(path: &Path, header: &CaptureHeader) -> Result<Self> {
+        let file = File::create(path)
+            .map_err(|e| Error::Other(format!("create {}: {e}", path.display())))?;
+        let mut w = Self {
+            out: BufWriter::new(file),
+            start: Instant::now(),
+        };
+        w.write_line(&Line::Header(header.clone()))?;
+        w.flush()?;
+        Ok(w)
+    }
+
+    /// Append one report read at `now`.
+    pub fn write_report(&mut self, now: Instant, report_id: u8, payload: &[u8]) -> Result<()> {
+        self.write_line(&Line::Report {
+            t_us: now.saturating_duration_since(self.start).as_micros() as u64,
+            report_id,
+            data: to_hex(payload),
+        })
+    }
+
+    /// Flush buffered lines to disk.
+    pub fn flush(&mut self) -> Result<()> {
+        self.out.flush().map_err(write_err)
+    }
+
+    fn write_line(&mut self, line: &Line) -> Result<()> {
+        serde_json::to_writer(&mut self.out, line).map_err(write_err)?;
+        self.out.write_all(b"\n").map_err(write_err)
+    }
+}
+
+/// A capture file read back (see module docs).
+#[derive(Clone, Debug, Default)]
+pub struct ReportCapture {
+    pub header: CaptureHeader,
+    pub reports: Vec<CapturedReport>,
+}
+
+impl ReportCapture {
+    /// Read a capture file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Other(format!("read {}: {e}", path.display())))?;
+        Self::parse(&text)
+    }
+
+    /// Parse capture file text.
+    pub fn parse(text: &str) -> Result<Self> {
+        let invalid = |i: usize, what: String| {
+            Error::Other(format!("invalid report capture: line {}: {what}", i + 1))
+        };
+        let mut out = Self::default();
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(line).map_err(|e| invalid(i, e.to_string()))? {
+                Line::Header(h) => out.header = h,
+                Line::Report {
+                    t_us,
+                    report_id,
+                    data,
+                } => out.reports.push(CapturedReport {
+                    at: Duration::from_micros(t_us),
+                    report_id,
+                    payload: from_hex(&data).ok_or_else(|| invalid(i, "bad hex".into()))?,
+                }),
+            }
+        }
+        Ok(out)
+    }
+
+    /// Feed every captured report to `parser` (as the device backend would) and collect the
+    /// resulting events, one `Vec` per report.
+    pub fn replay(&self, parser: &mut dyn ReportParser) -> Vec<Vec<InputKind>> {
+        let meta = &self.header.meta;
+        let fingerprint = DeviceFingerprint {
+            vendor_id: meta.vid.unwrap_or(0),
+            product_id: meta.pid.unwrap_or(0),
+            serial_number: meta.serial_number.clone(),
+            path: meta.path.clone(),
+        };
+        let start = Instant::now();
+        self.reports
+            .iter()
+            .map(|r| {
+                let ctx = ParseCtx {
+                    report_id: r.report_id,
+                    now: start + r.at,
+                    meta,
+                    fingerprint: &fingerprint,
+                };
+                let mut events = Vec::new();
+                parser.parse(&ctx, &r.payload, &mut events);
+                events
+            })
+            .collect()
+    }
+}
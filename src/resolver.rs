@@ -0,0 +1,471 @@
+//! Stateful binding resolution.
+//!
+//! [`BindingProfile::resolve`] is a pure function of the current device states. Some rules
+//! need time as well: a [`BindingRule::Chord`] should only fire if its controls were pressed
+//! *together* (within `window_ms`), not when one was held for ages and the other tapped.
+//!
+//! [`BindingResolver`] owns a profile plus the press timestamps those rules need. For users
+//! who cannot hold several controls at once, chords can also be entered one control at a
+//! time ([`set_sequential_chords`](BindingResolver::set_sequential_chords)).
+//! [`BindingRule::Gesture`] rules turn press/release timing into tap, hold, and double-tap
+//! events (see [`gestures`](crate::gestures)), reported in [`BindingOutput::events`], and
+//! [`BindingRule::Dwell`] rules fire after a hold time with progress in
+//! [`BindingOutput::progress`]. Incremental [`BindingRule::ButtonAxis`] rules ramp by the
+//! time since the previous call. [`BindingRule::Interlock`] rules report engage / release /
+//! timeout / abort in [`BindingOutput::interlocks`]. Call
+//! [`resolve`](BindingResolver::resolve) once per frame/tick with a monotonic `now`:
+//!
+//! ```no_run
+//! use std::time::Instant;
+//! use stickup::resolver::BindingResolver;
+//! use stickup::{BindingProfile, Manager};
+//!
+//! let mut mgr = Manager::discover().expect("discover devices");
+//! let profile: BindingProfile = serde_json::from_str("{\"name\":\"demo\"}").unwrap();
+//! let mut resolver = BindingResolver::new(profile);
+//! loop {
+//!     mgr.poll_events();
+//!     let out = resolver.resolve(&mgr.snapshot(), Instant::now());
+//!     // ... use out.buttons / out.axis / out.vec2 / out.events
+//! }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use crate::binding::{
+    button_axis_direction, control_held, BindingOutput, BindingProfile, BindingRule,
+    ButtonAxisMode, ControlPath, DeviceHandles, InterlockEvent, InterlockEventKind, ProfileStates,
+    RuleSource,
+};
+use crate::gestures::{GestureDetector, GestureEvent};
+use crate::snapshot::DeviceStates;
+
+/// Identifies a rule: `(where it is declared, rule index)`.
+type RuleKey = (RuleSource, usize);
+
+/// Progress of one interlock rule (absent = idle).
+#[derive(Clone, Copy, Debug)]
+enum InterlockState {
+    /// Some controls pressed at the given time; waiting for the rest.
+    Waiting(Instant),
+    /// All controls held since the given time.
+    Arming(Instant),
+    /// Action active.
+    Engaged,
+    /// Timed out or aborted; waiting for every control to be released.
+    Locked,
+}
+
+/// Time-aware resolver for a [`BindingProfile`] (see module docs).
+#[derive(Clone, Debug)]
+pub struct BindingResolver {
+    profile: BindingProfile,
+    /// Device handles of the profile's rules, resolved against the id table of the states
+    /// being resolved.
+    handles: DeviceHandles,
+    /// When each `(device_id, control_id)` was first seen held (cleared on release).
+    pressed_at: HashMap<(String, String), Instant>,
+    /// Chord rules currently active.
+    active_chords: HashSet<RuleKey>,
+    /// Sequential ("sticky") chord entry timeout, if enabled.
+    sequential: Option<Duration>,
+    /// Latched controls for sequential chord entry: `None` while held since the press,
+    /// then the release time. Consumed latches are removed and return on the next press.
+    latched: HashMap<(String, String), Option<Instant>>,
+    /// Gesture state per gesture rule in effect.
+    gestures: HashMap<RuleKey, GestureDetector>,
+    /// Hold start per dwell rule whose control is held.
+    dwell_since: HashMap<RuleKey, Instant>,
+    /// Current value per incremental button-axis rule.
+    ramps: HashMap<RuleKey, f32>,
+    /// State per interlock rule that is not idle.
+    interlocks: HashMap<RuleKey, InterlockState>,
+    /// Time of the previous [`resolve`](Self::resolve) call.
+    last_resolve: Option<Instant>,
+    /// Active [`ActionSet`](crate::binding::ActionSet) names.
+    action_sets: Vec<String>,
+}
+
+impl BindingResolver {
+    /// Resolver for `profile` with no input history.
+    pub fn new(profile: BindingProfile) -> Self {
+        Self {
+            profile,
+            handles: DeviceHandles::default(),
+            pressed_at: HashMap::new(),
+            active_chords: HashSet::new(),
+            sequential: None,
+            latched: HashMap::new(),
+            gestures: HashMap::new(),
+            dwell_since: HashMap::new(),
+            ramps: HashMap::new(),
+            interlocks: HashMap::new(),
+            last_resolve: None,
+            action_sets: Vec::new(),
+        }
+    }
+
+    /// Accessibility: allow chords to be entered one control at a time.
+    ///
+    /// With `Some(timeout)`, a pressed control stays "latched" for `timeout` after release.
+    /// A chord fires once every control is held or latched and at least one is held
+    /// (press Start, release, press Back). It stays active while any of its controls is held;
+    /// firing consumes the latches. `None` (default) requires holding all controls together.
+    pub fn set_sequential_chords(&mut self, timeout: Option<Duration>) {
+        self.sequential = timeout;
+        self.latched.clear();
+    }
+
+    /// Activate exactly the named [`ActionSet`](crate::binding::ActionSet)s (see
+    /// [`BindingProfile::resolve_with_sets`]).
+    pub fn set_action_sets(&mut self, names: &[String]) {
+        self.action_sets = names.to_vec();
+    }
+
+    /// Names of the active action sets.
+    pub fn action_sets(&self) -> &[String] {
+        &self.action_sets
+    }
+
+    /// The resolved profile.
+    pub fn profile(&self) -> &BindingProfile {
+        &self.profile
+    }
+
+    /// Replace the profile (input history is cleared, settings and active sets are kept).
+    pub fn set_profile(&mut self, profile: BindingProfile) {
+        let sequential = self.sequential;
+        let action_sets = std::mem::take(&mut self.action_sets);
+        *self = Self::new(profile);
+        self.sequential = sequential;
+        self.action_sets = action_sets;
+    }
+
+    /// Resolve actions at time `now` (see [`BindingProfile::resolve_with_sets`]).
+    ///
+    /// With a [`Snapshot`](crate::snapshot::Snapshot), the profile's device ids are mapped to
+    /// [`DeviceId`](crate::event::DeviceId)s once (again only when the Manager learns new
+    /// devices), and rules read their device's state by handle.
+    pub fn resolve(
+        &mut self,
+        devices: &(impl DeviceStates + ?Sized),
+        now: Instant,
+    ) -> BindingOutput {
+        let mut handles = std::mem::take(&mut self.handles);
+        if let Some(ids) = devices.device_ids() {
+            handles.resolve(&self.profile, ids);
+        }
+        let states = ProfileStates::new(devices, devices.device_ids().map(|_| &handles));
+        let out = self.resolve_states(&states, now);
+        self.handles = handles;
+        out
+    }
+
+    fn resolve_states<D: DeviceStates + ?Sized>(
+        &mut self,
+        states: &ProfileStates<'_, D>,
+        now: Instant,
+    ) -> BindingOutput {
+        let mut out = self.profile.resolve_in(states, &self.action_sets);
+        self.track_presses(states, now);
+
+        let rules: Vec<(RuleKey, &BindingRule)> = self
+            .profile
+            .effective_rules(states, &self.action_sets)
+            .into_iter()
+            .filter(|(_, _, _, shadowed)| !shadowed)
+            .map(|(src, i, r, _)| ((src, i), r))
+            .collect();
+
+        let mut still_active = HashSet::new();
+        let mut consumed: Vec<(String, String)> = Vec::new();
+        let mut gestures = HashMap::new();
+        let mut dwell_since = HashMap::new();
+        let mut interlocks = HashMap::new();
+        let dt = self
+            .last_resolve
+            .map_or(0.0, |t| now.saturating_duration_since(t).as_secs_f32());
+        for (key, rule) in rules {
+            let st = states.rule(key.0, key.1, rule);
+            if let BindingRule::Interlock {
+                controls,
+                action,
+                window_ms,
+                hold_ms,
+                ..
+            } = rule
+            {
+                let held = st.map_or(0, |st| {
+                    controls
+                        .iter()
+                        .filter(|c| control_held(st, c, None))
+                        .count()
+                });
+                let all = !controls.is_empty() && held == controls.len();
+                let any = held > 0;
+                let window = Duration::from_millis(*window_ms as u64);
+                let hold = Duration::from_millis(*hold_ms as u64);
+                let mut emit = |kind| {
+                    out.interlocks.push(InterlockEvent {
+                        action: action.clone(),
+                        kind,
+                    })
+                };
+                let next = match self.interlocks.remove(&key) {
+                    None if all => Some(InterlockState::Arming(now)),
+                    None if any => Some(InterlockState::Waiting(now)),
+                    None => None,
+                    Some(InterlockState::Waiting(t)) => {
+                        if now.saturating_duration_since(t) > window {
+                            emit(InterlockEventKind::TimedOut);
+                            Some(InterlockState::Locked)
+                        } else if all {
+                            Some(InterlockState::Arming(now))
+                        } else if any {
+                            Some(InterlockState::Waiting(t))
+                        } else {
+                            None
+                        }
+                    }
+                    Some(InterlockState::Arming(t)) => {
+                        if !all {
+                            emit(InterlockEventKind::Aborted);
+                            any.then_some(InterlockState::Locked)
+                        } else if now.saturating_duration_since(t) >= hold {
+                            emit(InterlockEventKind::Engaged);
+                            Some(InterlockState::Engaged)
+                        } else {
+                            Some(InterlockState::Arming(t))
+                        }
+                    }
+                    Some(InterlockState::Engaged) => {
+                        if all {
+                            Some(InterlockState::Engaged)
+                        } else {
+                            emit(InterlockEventKind::Released);
+                            any.then_some(InterlockState::Locked)
+                        }
+                    }
+                    Some(InterlockState::Locked) => any.then_some(InterlockState::Locked),
+                };
+                match next {
+                    Some(InterlockState::Arming(t)) => {
+                        let p = if hold.is_zero() {
+                            1.0
+                        } else {
+                            now.saturating_duration_since(t).as_secs_f32() / hold.as_secs_f32()
+                        };
+                        out.progress.insert(action.clone(), p.min(1.0));
+                    }
+                    Some(InterlockState::Engaged) => {
+                        out.progress.insert(action.clone(), 1.0);
+                    }
+                    _ => {}
+                }
+                out.buttons.insert(
+                    action.clone(),
+                    matches!(next, Some(InterlockState::Engaged)),
+                );
+                if let Some(state) = next {
+                    interlocks.insert(key, state);
+                }
+                continue;
+            }
+            if let BindingRule::ButtonAxis {
+                negative,
+                positive,
+                action,
+                mode: ButtonAxisMode::Incremental { rate, min, max },
+                ..
+            } = rule
+            {
+                let dir = st.map_or(0.0, |st| button_axis_direction(st, negative, positive));
+                let (lo, hi) = (min.min(*max), min.max(*max));
+                let v = self.ramps.get(&key).copied().unwrap_or(0.0);
+                let v = (v + dir * rate * dt).clamp(lo, hi);
+                self.ramps.insert(key, v);
+                out.axis.insert(action.clone(), v);
+                continue;
+            }
+            if let BindingRule::Dwell {
+                control,
+                hat_direction,
+                action,
+                dwell_ms,
+                ..
+            } = rule
+            {
+                let held = st.is_some_and(|st| control_held(st, control, *hat_direction));
+                if held {
+                    let since = self.dwell_since.get(&key).copied().unwrap_or(now);
+                    let need = Duration::from_millis(*dwell_ms as u64);
+                    let elapsed = now.saturating_duration_since(since);
+                    let progress = if need.is_zero() {
+                        1.0
+                    } else {
+                        (elapsed.as_secs_f32() / need.as_secs_f32()).min(1.0)
+                    };
+                    out.progress.insert(action.clone(), progress);
+                    out.buttons.insert(action.clone(), elapsed >= need);
+                    dwell_since.insert(key, since);
+                } else {
+                    out.buttons.insert(action.clone(), false);
+                }
+                continue;
+            }
+            if let BindingRule::Gesture {
+                control,
+                hat_direction,
+                action,
+                timing,
+                ..
+            } = rule
+            {
+                let pressed = st.is_some_and(|st| control_held(st, control, *hat_direction));
+                let mut detector = self.gestures.remove(&key).unwrap_or_default();
+                if let Some(gesture) = detector.update(pressed, now, timing) {
+                    out.events.push(GestureEvent {
+                        action: action.clone(),
+                        gesture,
+                    });
+                }
+                gestures.insert(key, detector);
+                continue;
+            }
+            let BindingRule::Chord {
+                device_id,
+                controls,
+                action,
+                window_ms,
+            } = rule
+            else {
+                continue;
+            };
+            let Some(st) = st else {
+                continue;
+            };
+            if controls.is_empty() {
+                out.buttons.insert(action.clone(), false);
+                continue;
+            }
+            if let Some(timeout) = self.sequential {
+                let key_of = |c: &ControlPath| (device_id.clone(), c.control_id.clone());
+                let any_held = controls.iter().any(|c| control_held(st, c, None));
+                let active = any_held
+                    && (self.active_chords.contains(&key)
+                        || controls.iter().all(|c| {
+                            control_held(st, c, None)
+                                || self.latched.get(&key_of(c)).is_some_and(|released| {
+                                    released
+                                        .is_none_or(|t| now.saturating_duration_since(t) <= timeout)
+                                })
+                        }));
+                if active {
+                    if !self.active_chords.contains(&key) {
+                        consumed.extend(controls.iter().map(key_of));
+                    }
+                    still_active.insert(key);
+                }
+                out.buttons.insert(action.clone(), active);
+                continue;
+            }
+            let held = controls.iter().all(|c| control_held(st, c, None));
+            let active = held
+                && (self.active_chords.contains(&key) || {
+                    let times: Vec<Instant> = controls
+                        .iter()
+                        .filter_map(|c| {
+                            self.pressed_at
+                                .get(&(device_id.clone(), c.control_id.clone()))
+                                .copied()
+                        })
+                        .collect();
+                    let spread = match (times.iter().min(), times.iter().max()) {
+                        (Some(first), Some(last)) => last.saturating_duration_since(*first),
+                        _ => Duration::ZERO,
+                    };
+                    spread <= Duration::from_millis(*window_ms as u64)
+                });
+            if active {
+                still_active.insert(key);
+            }
+            out.buttons.insert(action.clone(), active);
+        }
+        self.active_chords = still_active;
+        // Gesture rules that dropped out (layer change) start over next time.
+        self.gestures = gestures;
+        self.dwell_since = dwell_since;
+        self.interlocks = interlocks;
+        self.last_resolve = Some(now);
+        for k in consumed {
+            self.latched.remove(&k);
+        }
+        if let Some(timeout) = self.sequential {
+            self.latched.retain(|_, released| {
+                released.is_none_or(|t| now.saturating_duration_since(t) <= timeout)
+            });
+        }
+        out
+    }
+
+    /// Record first-held times for every control a chord reads, and press/release edges for
+    /// sequential chord latches.
+    fn track_presses<D: DeviceStates + ?Sized>(
+        &mut self,
+        states: &ProfileStates<'_, D>,
+        now: Instant,
+    ) {
+        let profile = &self.profile;
+        let rules = sourced(RuleSource::Base, &profile.bindings)
+            .chain(
+                profile
+                    .layers
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(li, l)| sourced(RuleSource::Layer(li), &l.bindings)),
+            )
+            .chain(
+                profile
+                    .action_sets
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(si, s)| sourced(RuleSource::Set(si), &s.bindings)),
+            );
+        for (src, i, rule) in rules {
+            let BindingRule::Chord {
+                device_id,
+                controls,
+                ..
+            } = rule
+            else {
+                continue;
+            };
+            let st = states.rule(src, i, rule);
+            for c in controls {
+                let key = (device_id.clone(), c.control_id.clone());
+                if st.is_some_and(|st| control_held(st, c, None)) {
+                    if !self.pressed_at.contains_key(&key) {
+                        if self.sequential.is_some() {
+                            self.latched.insert(key.clone(), None);
+                        }
+                        self.pressed_at.insert(key, now);
+                    }
+                } else if self.pressed_at.remove(&key).is_some() {
+                    // The latch times out from the release, however long the press lasted.
+                    if let Some(released @ None) = self.latched.get_mut(&key) {
+                        *released = Some(now);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// `rules` with their source and index (see [`RuleKey`]).
+fn sourced(
+    src: RuleSource,
+    rules: &[BindingRule],
+) -> impl Iterator<Item = (RuleSource, usize, &BindingRule)> {
+    rules.iter().enumerate().map(move |(i, r)| (src, i, r))
+}
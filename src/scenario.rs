@@ -0,0 +1,476 @@
+//! Headless profile tests: scripted input scenarios with expected actions.
+//!
+//! A [`Scenario`] is a timeline of control changes on simulated devices plus the actions a
+//! profile should produce along the way. [`Scenario::run`] replays it through a
+//! [`BindingResolver`] (and the profile's gate [`channels`](BindingProfile::channels)) on a
+//! virtual clock — no hardware, no sleeping — and reports every expectation that did not
+//! hold. Use it to check layers, chords, gestures, and interlocks before relying on a
+//! profile, e.g. from a `#[test]`:
+//!
+//! ```
+//! use stickup::scenario::Scenario;
+//! use stickup::BindingProfile;
+//!
+//! let profile = BindingProfile::from_str_auto(r#"
+//! name = "demo"
+//! [[bindings]]
+//! kind = "gesture"
+//! device_id = "stick"
+//! control = { control_id = "Trigger", control_type = "Button" }
+//! action = "gear"
+//! [[bindings]]
+//! kind = "axis1d"
+//! device_id = "throttle"
+//! control = { control_id = "Z", control_type = "Axis" }
+//! action = "thrust"
+//! xform = { deadzone = 0.0 }
+//! "#).unwrap();
+//!
+//! let scenario = Scenario::from_str_auto(r#"
+//! name = "double tap raises gear"
+//! [[inputs]]
+//! at_ms = 0
+//! device_id = "stick"
+//! press = "Trigger"
+//! [[inputs]]
+//! at_ms = 80
+//! device_id = "stick"
+//! release = "Trigger"
+//! [[inputs]]
+//! at_ms = 150
+//! device_id = "stick"
+//! press = "Trigger"
+//! [[inputs]]
+//! at_ms = 220
+//! device_id = "stick"
+//! release = "Trigger"
+//! [[inputs]]
+//! at_ms = 300
+//! device_id = "throttle"
+//! axis = { control = "Z", value = 0.5 }
+//!
+//! [[expect]]
+//! at_ms = 220
+//! event = { action = "gear", kind = "double_tap" }
+//! [[expect]]
+//! at_ms = 300
+//! axis = { action = "thrust", value = 0.5 }
+//! "#).unwrap();
+//!
+//! scenario.run(&profile).assert_passed();
+//! ```
+//!
+//! Inputs are `press`/`release` (button name), `axis = { control, value }`, or
+//! `hat = { control, value }`, using the same control ids as the profile. Expectations are
+//! checked at `at_ms`: `button = { action, pressed }` and `axis = { action, value,
+//! tolerance }` against the resolved state, `event = { action, kind, within_ms }` against
+//! gesture and interlock events emitted from `at_ms` to `at_ms + within_ms`. The resolver
+//! runs every [`tick_ms`](Scenario::tick_ms) and at every input and expectation time.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::binding::{
+    BindingOutput, BindingProfile, DeviceState, InterlockEventKind, ProfileFormat,
+};
+use crate::gate::{Gate, GateKind};
+use crate::gestures::Gesture;
+use crate::resolver::BindingResolver;
+use crate::{Error, Result};
+
+fn default_tick_ms() -> u32 {
+    10
+}
+
+fn default_tolerance() -> f32 {
+    0.01
+}
+
+/// A scripted input timeline with expected actions (see module docs).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Scenario {
+    /// Name shown in reports.
+    #[serde(default)]
+    pub name: String,
+    /// Resolve interval in milliseconds (default `10`).
+    #[serde(default = "default_tick_ms")]
+    pub tick_ms: u32,
+    /// Action sets active for the whole run.
+    #[serde(default)]
+    pub action_sets: Vec<String>,
+    /// Control changes, in any order.
+    #[serde(default)]
+    pub inputs: Vec<InputStep>,
+    /// Expected actions.
+    #[serde(default)]
+    pub expect: Vec<Expectation>,
+}
+
+/// One control change at a point in time.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InputStep {
+    /// Time since the start of the scenario.
+    pub at_ms: u32,
+    /// Simulated device.
+    pub device_id: String,
+    /// What changes.
+    #[serde(flatten)]
+    pub change: InputChange,
+}
+
+/// A control change of an [`InputStep`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputChange {
+    /// Press the named button.
+    Press(String),
+    /// Release the named button.
+    Release(String),
+    /// Move the named axis.
+    Axis {
+        /// Control id.
+        control: String,
+        /// Normalized value.
+        value: f32,
+    },
+    /// Set the named hat (`-1` neutral, `0..7` directions).
+    Hat {
+        /// Control id.
+        control: String,
+        /// Direction.
+        value: i16,
+    },
+}
+
+/// An expected action at a point in time.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Expectation {
+    /// Time since the start of the scenario.
+    pub at_ms: u32,
+    /// What must hold.
+    #[serde(flatten)]
+    pub check: Check,
+}
+
+/// Condition of an [`Expectation`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+    /// Button action state.
+    Button {
+        /// Action name.
+        action: String,
+        /// Expected state.
+        pressed: bool,
+    },
+    /// Axis action value.
+    Axis {
+        /// Action name.
+        action: String,
+        /// Expected value.
+        value: f32,
+        /// Largest accepted difference (default `0.01`).
+        #[serde(default = "default_tolerance")]
+        tolerance: f32,
+    },
+    /// A gesture or interlock event for `action`.
+    Event {
+        /// Action name.
+        action: String,
+        /// Event kind (`tap`, `double_tap`, `hold`, `engaged`, `released`, `timed_out`,
+        /// `aborted`); any kind if omitted.
+        #[serde(default)]
+        kind: Option<String>,
+        /// Accept events up to this long after `at_ms`.
+        #[serde(default)]
+        within_ms: u32,
+    },
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Button { action, pressed } => write!(f, "button {action:?} pressed={pressed}"),
+            Self::Axis {
+                action,
+                value,
+                tolerance,
+            } => write!(f, "axis {action:?} = {value} ± {tolerance}"),
+            Self::Event {
+                action,
+                kind,
+                within_ms,
+            } => {
+                write!(f, "event {action:?}")?;
+                if let Some(kind) = kind {
+                    write!(f, " {kind}")?;
+                }
+                if *within_ms > 0 {
+                    write!(f, " within {within_ms} ms")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// An expectation that did not hold.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScenarioFailure {
+    /// Time of the expectation.
+    pub at_ms: u32,
+    /// What was expected.
+    pub expected: String,
+    /// What the profile produced.
+    pub actual: String,
+}
+
+/// Outcome of [`Scenario::run`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScenarioReport {
+    /// Scenario name.
+    pub name: String,
+    /// Number of expectations checked.
+    pub checked: usize,
+    /// Expectations that did not hold, in time order.
+    pub failures: Vec<ScenarioFailure>,
+}
+
+impl ScenarioReport {
+    /// Whether every expectation held.
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Panic with the report unless every expectation held (for `#[test]`s).
+    #[track_caller]
+    pub fn assert_passed(&self) {
+        if !self.passed() {
+            panic!("{self}");
+        }
+    }
+}
+
+impl fmt::Display for ScenarioReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = if self.name.is_empty() {
+            "scenario"
+        } else {
+            &self.name
+        };
+        writeln!(
+            f,
+            "{name}: {}/{} expectations passed",
+            self.checked - self.failures.len(),
+            self.checked
+        )?;
+        for fail in &self.failures {
+            writeln!(
+                f,
+                "  at {} ms: expected {}, got {}",
+                fail.at_ms, fail.expected, fail.actual
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Name of a gesture as used in [`Check::Event::kind`].
+fn gesture_kind(g: &Gesture) -> &'static str {
+    match g {
+        Gesture::Tap => "tap",
+        Gesture::DoubleTap => "double_tap",
+        Gesture::Hold(_) => "hold",
+    }
+}
+
+/// Name of an interlock event as used in [`Check::Event::kind`].
+fn interlock_kind(k: InterlockEventKind) -> &'static str {
+    match k {
+        InterlockEventKind::Engaged => "engaged",
+        InterlockEventKind::Released => "released",
+        InterlockEventKind::TimedOut => "timed_out",
+        InterlockEventKind::Aborted => "aborted",
+    }
+}
+
+/// `(action, kind)` of every event in `out`.
+fn events(out: &BindingOutput) -> impl Iterator<Item = (&str, &'static str)> {
+    out.events
+        .iter()
+        .map(|e| (e.action.as_str(), gesture_kind(&e.gesture)))
+        .chain(
+            out.interlocks
+                .iter()
+                .map(|e| (e.action.as_str(), interlock_kind(e.kind))),
+        )
+}
+
+impl Scenario {
+    /// Parse a scenario from JSON or TOML text (see [`ProfileFormat::detect`]).
+    pub fn from_str_auto(text: &str) -> Result<Self> {
+        Self::parse(text, ProfileFormat::detect(text))
+    }
+
+    /// Parse a scenario in a known format.
+    pub fn parse(text: &str, format: ProfileFormat) -> Result<Self> {
+        match format {
+            ProfileFormat::Json => serde_json::from_str(text)
+                .map_err(|e| Error::Other(format!("invalid scenario: {e}"))),
+            ProfileFormat::Toml => {
+                toml::from_str(text).map_err(|e| Error::Other(format!("invalid scenario: {e}")))
+            }
+        }
+    }
+
+    /// Load a scenario file. The format comes from the extension, else from the contents.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Other(format!("read {}: {e}", path.display())))?;
+        let format = ProfileFormat::from_path(path).unwrap_or_else(|| ProfileFormat::detect(&text));
+        Self::parse(&text, format)
+    }
+
+    /// Replay the scenario against `profile` and check every expectation.
+    pub fn run(&self, profile: &BindingProfile) -> ScenarioReport {
+        let mut times: BTreeSet<u32> = self
+            .inputs
+            .iter()
+            .map(|i| i.at_ms)
+            .chain(self.expect.iter().map(|e| e.at_ms))
+            .collect();
+        let end = self
+            .expect
+            .iter()
+            .map(|e| match e.check {
+                Check::Event { within_ms, .. } => e.at_ms.saturating_add(within_ms),
+                _ => e.at_ms,
+            })
+            .chain(times.iter().copied())
+            .max()
+            .unwrap_or(0);
+        let tick = self.tick_ms.max(1);
+        times.extend((0..=end / tick).map(|i| i * tick));
+        times.insert(end);
+
+        let mut inputs: Vec<&InputStep> = self.inputs.iter().collect();
+        inputs.sort_by_key(|i| i.at_ms);
+        let mut inputs = inputs.into_iter().peekable();
+
+        let mut gates: HashMap<&str, Vec<Gate>> = HashMap::new();
+        for ch in &profile.channels {
+            gates
+                .entry(ch.device_id.as_str())
+                .or_default()
+                .push(Gate::new(&ch.name, ch.kind, 0, ch.expr.clone()));
+        }
+
+        let mut resolver = BindingResolver::new(profile.clone());
+        resolver.set_action_sets(&self.action_sets);
+        let mut devices: HashMap<String, DeviceState> = HashMap::new();
+        let start = Instant::now();
+        let mut timeline: Vec<(u32, BindingOutput)> = Vec::with_capacity(times.len());
+        for t in times {
+            while let Some(step) = inputs.next_if(|i| i.at_ms <= t) {
+                let st = devices.entry(step.device_id.clone()).or_default();
+                match &step.change {
+                    InputChange::Press(b) => {
+                        st.buttons.insert(b.clone(), true);
+                    }
+                    InputChange::Release(b) => {
+                        st.buttons.insert(b.clone(), false);
+                    }
+                    InputChange::Axis { control, value } => {
+                        st.axes.insert(control.clone(), *value);
+                    }
+                    InputChange::Hat { control, value } => {
+                        st.hats.insert(control.clone(), *value);
+                    }
+                }
+            }
+            let now = start + Duration::from_millis(t as u64);
+            for (id, device_gates) in &mut gates {
+                let st = devices.entry(id.to_string()).or_default();
+                for g in device_gates {
+                    if g.update(st, now).is_none() {
+                        continue;
+                    }
+                    if g.kind == GateKind::Button {
+                        st.buttons.insert(g.name.clone(), g.is_active());
+                    } else {
+                        st.axes.insert(g.name.clone(), g.value);
+                    }
+                }
+            }
+            timeline.push((t, resolver.resolve(&devices, now)));
+        }
+
+        let mut expect: Vec<&Expectation> = self.expect.iter().collect();
+        expect.sort_by_key(|e| e.at_ms);
+        let mut report = ScenarioReport {
+            name: self.name.clone(),
+            checked: expect.len(),
+            failures: Vec::new(),
+        };
+        for e in expect {
+            let at = timeline.partition_point(|(t, _)| *t < e.at_ms);
+            let out = &timeline[at].1;
+            let actual = match &e.check {
+                Check::Button { action, pressed } => {
+                    let got = out.buttons.get(action).copied().unwrap_or(false);
+                    (got != *pressed).then(|| format!("pressed={got}"))
+                }
+                Check::Axis {
+                    action,
+                    value,
+                    tolerance,
+                } => {
+                    let got = out.axis.get(action).copied().unwrap_or(0.0);
+                    ((got - value).abs() > *tolerance).then(|| format!("{got}"))
+                }
+                Check::Event {
+                    action,
+                    kind,
+                    within_ms,
+                } => {
+                    let until = e.at_ms.saturating_add(*within_ms);
+                    let seen: Vec<(&str, u32)> = timeline[at..]
+                        .iter()
+                        .take_while(|(t, _)| *t <= until)
+                        .flat_map(|(t, out)| {
+                            events(out)
+                                .filter(|(a, _)| a == action)
+                                .map(move |(_, k)| (k, *t))
+                        })
+                        .collect();
+                    let hit = seen.iter().any(|(k, _)| match kind {
+                        Some(want) => k == want,
+                        None => true,
+                    });
+                    (!hit).then(|| {
+                        if seen.is_empty() {
+                            "no event".to_string()
+                        } else {
+                            let seen: Vec<String> =
+                                seen.iter().map(|(k, t)| format!("{k} at {t} ms")).collect();
+                            seen.join(", ")
+                        }
+                    })
+                }
+            };
+            if let Some(actual) = actual {
+                report.failures.push(ScenarioFailure {
+                    at_ms: e.at_ms,
+                    expected: e.check.to_string(),
+                    actual,
+                });
+            }
+        }
+        report
+    }
+}
@@ -0,0 +1,79 @@
+//! Multi-seat partitioning: devices split among local seats.
+//!
+//! A *seat* is one station sharing the machine — a split-screen player, or one terminal of a
+//! multi-station kiosk. Each managed device belongs to at most one seat; the
+//! [`Manager`](crate::manager::Manager) enforces that partition in its per-seat views:
+//! - [`seat_snapshot`](crate::manager::Manager::seat_snapshot) contains only the seat's
+//!   devices;
+//! - [`resolve_seat`](crate::manager::Manager::resolve_seat) resolves the seat's own
+//!   [`BindingProfile`] against those devices only, so one seat's bindings never read another
+//!   seat's controller;
+//! - [`poll_events_by_seat`](crate::manager::Manager::poll_events_by_seat) splits the event
+//!   stream per seat.
+//!
+//! Devices can join a seat under a seat-local *alias* (e.g. `"pad"`), so every seat can share
+//! one profile written against the alias instead of concrete device ids.
+//!
+//! ```no_run
+//! # fn main() -> stickup::Result<()> {
+//! let profile = stickup::binding::BindingProfile::load("pad.toml")?;
+//! let mut mgr = stickup::Manager::discover()?;
+//! let pads: Vec<String> = mgr.devices().iter().map(|d| d.id.clone()).collect();
+//! for (i, id) in pads.iter().take(2).enumerate() {
+//!     let seat = format!("p{}", i + 1);
+//!     mgr.add_seat(&seat);
+//!     mgr.assign_seat_alias(id, &seat, "pad")?;
+//!     mgr.set_seat_profile(&seat, Some(profile.clone()))?;
+//! }
+//! mgr.poll_events();
+//! let p1 = mgr.resolve_seat("p1");
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::binding::{BindingProfile, DeviceState};
+use crate::snapshot::Snapshot;
+
+/// One seat and its devices.
+#[derive(Clone, Debug, Default)]
+pub struct Seat {
+    /// Seat name (unique per manager).
+    pub name: String,
+    /// Member device ids, each with its optional seat-local alias.
+    pub devices: BTreeMap<String, Option<String>>,
+    /// Bindings resolved by [`Manager::resolve_seat`](crate::manager::Manager::resolve_seat).
+    pub profile: Option<BindingProfile>,
+}
+
+impl Seat {
+    /// Empty seat.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Whether `device_id` belongs to this seat.
+    pub fn contains(&self, device_id: &str) -> bool {
+        self.devices.contains_key(device_id)
+    }
+
+    /// The seat's view of `states`: member devices under their id, and again under their
+    /// alias when they have one.
+    pub fn states(&self, states: &Snapshot) -> HashMap<String, DeviceState> {
+        let mut out = HashMap::new();
+        for (id, alias) in &self.devices {
+            let Some(st) = states.get(id) else {
+                continue;
+            };
+            if let Some(alias) = alias {
+                out.insert(alias.clone(), st.clone());
+            }
+            out.insert(id.clone(), st.clone());
+        }
+        out
+    }
+}
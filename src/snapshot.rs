@@ -6,10 +6,17 @@
 //! for fan-out to multiple consumers.
 //!
 //! # Semantics
-//! - Keys are `device_id` strings (same format as [`Device::id`](crate::device::Device::id)).
+//! - Keys are [`DeviceId`] handles; [`get`](Snapshot::get) and [`iter`](Snapshot::iter) map
+//!   them back to `device_id` strings (same format as [`Device::id`](crate::device::Device::id)).
 //! - Values are [`DeviceState`](crate::binding::DeviceState) maps of axis/button/hat values.
 //! - A snapshot is **immutable**. To refresh, call `poll_events()` and then request a new snapshot.
 //! - `Snapshot` does **not** poll devices; it reflects the manager’s last-known state.
+//! - The manager updates its state incrementally as events arrive. Device states are shared
+//!   copy-on-write, so taking a snapshot copies no channel maps, and a device's state is
+//!   only duplicated when the manager next changes it while an older snapshot still holds it.
+//! - Button edges ([`DeviceState::just_pressed`](crate::binding::DeviceState::just_pressed) /
+//!   [`just_released`](crate::binding::DeviceState::just_released)) cover the most recent
+//!   poll and are cleared at the start of the next one.
 //!
 //! # Examples
 //! Iterate devices and read values:
@@ -30,32 +37,287 @@
 //! ```ignore
 //! let map = snap.clone().into_inner(); // HashMap<String, DeviceState>
 //! ```
+//!
+//! # Subscriptions
+//! Large setups rarely read every channel every frame. A [`SnapshotFilter`] limits which
+//! devices (and optionally which channels) a snapshot copies:
+//! ```no_run
+//! use stickup::snapshot::SnapshotFilter;
+//! use stickup::Manager;
+//!
+//! let mut mgr = Manager::discover().expect("discover devices");
+//! mgr.set_snapshot_filter(Some(
+//!     SnapshotFilter::new()
+//!         .device("044f:b10a:ABC")                   // every channel
+//!         .channels("044f:0404:XYZ", ["X", "Y", "Trigger"]),
+//! ));
+//! let snap = mgr.snapshot(); // only the subscribed state is cloned
+//! ```
 
 use crate::binding::DeviceState;
-use std::collections::HashMap;
+use crate::event::{DeviceId, DeviceIds};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
-/// Owned snapshot of current device states (`device_id → DeviceState`).
+/// Owned snapshot of current device states, keyed by [`DeviceId`].
 ///
-/// Cloning is inexpensive for typical setups and useful for per-tick fan-out.
+/// Cloning is cheap and useful for per-tick fan-out: the id table and every device state
+/// are reference-counted, so a clone only copies the handle map. Look devices up by handle
+/// ([`get_by_id`](Self::get_by_id)) or by string id ([`get`](Self::get)).
 #[derive(Clone, Debug, Default)]
-pub struct Snapshot(pub HashMap<String, DeviceState>);
+pub struct Snapshot {
+    states: HashMap<DeviceId, Arc<DeviceState>>,
+    ids: Arc<DeviceIds>,
+}
 
 impl Snapshot {
+    /// Empty snapshot sharing this one's id table.
+    pub(crate) fn emptied(&self) -> Self {
+        Self {
+            states: HashMap::new(),
+            ids: self.ids.clone(),
+        }
+    }
+
+    /// Handle for `device_id`, assigning one if new. Snapshots cloned earlier keep their
+    /// (older) table.
+    pub(crate) fn intern(&mut self, device_id: &str) -> DeviceId {
+        match self.ids.id_of(device_id) {
+            Some(id) => id,
+            None => Arc::make_mut(&mut self.ids).intern(device_id),
+        }
+    }
+
+    /// Mutable state of `id`, copied first if a clone of this snapshot still shares it.
+    pub(crate) fn get_mut(&mut self, id: DeviceId) -> Option<&mut DeviceState> {
+        self.states.get_mut(&id).map(Arc::make_mut)
+    }
+
+    pub(crate) fn insert(&mut self, id: DeviceId, state: DeviceState) {
+        self.states.insert(id, Arc::new(state));
+    }
+
+    pub(crate) fn entry(&mut self, id: DeviceId) -> &mut DeviceState {
+        Arc::make_mut(self.states.entry(id).or_default())
+    }
+
+    /// Clear the press/release edges of every device, copying only states that have any.
+    pub(crate) fn clear_edges(&mut self) {
+        for st in self.states.values_mut() {
+            if st.has_edges() {
+                Arc::make_mut(st).clear_edges();
+            }
+        }
+    }
+
     /// Get the state for a specific `device_id`.
     #[inline]
     pub fn get(&self, device_id: &str) -> Option<&DeviceState> {
-        self.0.get(device_id)
+        self.states.get(&self.ids.id_of(device_id)?).map(|st| &**st)
+    }
+
+    /// Get the state of the device with handle `id`.
+    #[inline]
+    pub fn get_by_id(&self, id: DeviceId) -> Option<&DeviceState> {
+        self.states.get(&id).map(|st| &**st)
+    }
+
+    /// Handle of `device_id`.
+    #[inline]
+    pub fn id_of(&self, device_id: &str) -> Option<DeviceId> {
+        self.ids.id_of(device_id)
+    }
+
+    /// String id behind `id`.
+    #[inline]
+    pub fn key_of(&self, id: DeviceId) -> Option<&str> {
+        self.ids.key_of(id)
+    }
+
+    /// The id table this snapshot resolves handles with.
+    #[inline]
+    pub fn ids(&self) -> &DeviceIds {
+        &self.ids
     }
 
     /// Iterate `(device_id, state)` pairs.
     #[inline]
-    pub fn iter(&self) -> impl Iterator<Item = (&String, &DeviceState)> {
-        self.0.iter()
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &DeviceState)> {
+        self.states
+            .iter()
+            .filter_map(|(id, st)| Some((self.ids.key_of(*id)?, &**st)))
+    }
+
+    /// Iterate `(handle, state)` pairs.
+    #[inline]
+    pub fn iter_ids(&self) -> impl Iterator<Item = (DeviceId, &DeviceState)> {
+        self.states.iter().map(|(id, st)| (*id, &**st))
     }
 
-    /// Consume the snapshot and return the inner map.
+    /// Number of devices in the snapshot.
     #[inline]
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    /// No devices?
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// Consume the snapshot and return the states keyed by string id.
     pub fn into_inner(self) -> HashMap<String, DeviceState> {
-        self.0
+        let ids = self.ids;
+        self.states
+            .into_iter()
+            .filter_map(|(id, st)| {
+                let st = Arc::try_unwrap(st).unwrap_or_else(|st| (*st).clone());
+                Some((ids.key_of(id)?.to_string(), st))
+            })
+            .collect()
     }
 }
+
+impl From<HashMap<String, DeviceState>> for Snapshot {
+    fn from(map: HashMap<String, DeviceState>) -> Self {
+        let mut ids = DeviceIds::new();
+        let states = map
+            .into_iter()
+            .map(|(key, st)| (ids.intern(&key), Arc::new(st)))
+            .collect();
+        Self {
+            states,
+            ids: Arc::new(ids),
+        }
+    }
+}
+
+/// Per-device state looked up by string id; what binding resolution reads.
+///
+/// Implemented for [`Snapshot`] and for plain `HashMap<String, DeviceState>` maps, so
+/// profiles resolve against either without converting.
+pub trait DeviceStates {
+    /// State of `device_id`, if present.
+    fn device_state(&self, device_id: &str) -> Option<&DeviceState>;
+
+    /// Id table the states are keyed by, if any.
+    ///
+    /// Resolvers that keep a profile loaded (see
+    /// [`BindingResolver`](crate::resolver::BindingResolver)) map its device ids through this
+    /// table once and then read states with [`device_state_by_id`](Self::device_state_by_id).
+    fn device_ids(&self) -> Option<&DeviceIds> {
+        None
+    }
+
+    /// State of the device with handle `id` in [`device_ids`](Self::device_ids), if present.
+    fn device_state_by_id(&self, _id: DeviceId) -> Option<&DeviceState> {
+        None
+    }
+}
+
+impl DeviceStates for Snapshot {
+    #[inline]
+    fn device_state(&self, device_id: &str) -> Option<&DeviceState> {
+        self.get(device_id)
+    }
+
+    #[inline]
+    fn device_ids(&self) -> Option<&DeviceIds> {
+        Some(&self.ids)
+    }
+
+    #[inline]
+    fn device_state_by_id(&self, id: DeviceId) -> Option<&DeviceState> {
+        self.get_by_id(id)
+    }
+}
+
+impl DeviceStates for HashMap<String, DeviceState> {
+    #[inline]
+    fn device_state(&self, device_id: &str) -> Option<&DeviceState> {
+        self.get(device_id)
+    }
+}
+
+/// Subset of devices/channels to include in a [`Snapshot`].
+///
+/// Devices not mentioned are left out. Channel names are matched across axes, buttons, and
+/// hats.
+#[derive(Clone, Debug, Default)]
+pub struct SnapshotFilter {
+    /// `device_id → channel names` (`None` = all channels).
+    devices: HashMap<String, Option<HashSet<String>>>,
+}
+
+impl SnapshotFilter {
+    /// Empty filter (matches nothing until devices are added).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Include every channel of `device_id`.
+    pub fn device(mut self, device_id: impl Into<String>) -> Self {
+        self.devices.insert(device_id.into(), None);
+        self
+    }
+
+    /// Include the named channels of `device_id` (adds to earlier calls for the same device).
+    pub fn channels<I, S>(mut self, device_id: impl Into<String>, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let entry = self
+            .devices
+            .entry(device_id.into())
+            .or_insert_with(|| Some(HashSet::new()));
+        if let Some(set) = entry {
+            set.extend(names.into_iter().map(Into::into));
+        }
+        self
+    }
+
+    /// Is `device_id` included at all?
+    #[inline]
+    pub fn includes_device(&self, device_id: &str) -> bool {
+        self.devices.contains_key(device_id)
+    }
+
+    /// Build the filtered snapshot from the full one, cloning only what is subscribed.
+    pub fn apply(&self, snapshot: &Snapshot) -> Snapshot {
+        let mut out = snapshot.emptied();
+        for (key, channels) in &self.devices {
+            let Some(id) = snapshot.id_of(key) else {
+                continue;
+            };
+            let Some(st) = snapshot.states.get(&id) else {
+                continue;
+            };
+            let st = match channels {
+                None => st.clone(),
+                Some(names) => Arc::new(DeviceState {
+                    axes: pick(&st.axes, names),
+                    buttons: pick(&st.buttons, names),
+                    hats: pick(&st.hats, names),
+                    pressed_this_frame: pick_set(&st.pressed_this_frame, names),
+                    released_this_frame: pick_set(&st.released_this_frame, names),
+                    relative: pick(&st.relative, names),
+                }),
+            };
+            out.states.insert(id, st);
+        }
+        out
+    }
+}
+
+fn pick<V: Copy>(map: &HashMap<String, V>, names: &HashSet<String>) -> HashMap<String, V> {
+    names
+        .iter()
+        .filter_map(|n| map.get(n).map(|v| (n.clone(), *v)))
+        .collect()
+}
+
+fn pick_set(set: &HashSet<String>, names: &HashSet<String>) -> HashSet<String> {
+    set.intersection(names).cloned().collect()
+}
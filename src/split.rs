@@ -0,0 +1,234 @@
+//! Per-collection sub-device splitting.
+//!
+//! Complex HID devices (HOTAS bases with a grip attached, button boxes with several clusters)
+//! often expose everything through one HID interface, grouped only by link collection. Some
+//! users prefer binding each group as an independent device ("grip" vs "base").
+//!
+//! [`split_by_collection`] wraps such a device into one logical [`Device`] per link collection
+//! (see [`ChannelDesc::collection`]). The views share the physical device:
+//! - polling any view reads the device once and routes events to the owning view
+//! - channel indices are renumbered densely per view (`0..n` per kind)
+//! - ids are `"<parent id>#<collection>"`; names get the collection name appended
+//! - output (rumble, FFB, lights) from any view goes to the physical device
+//!
+//! Enable it for discovery with
+//! [`DiscoveryOptions::split_collections`](crate::manager::DiscoveryOptions::split_collections).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Instant;
+
+use crate::device::{BatteryStatus, Device, ReportIdDiagnostics};
+use crate::event::{ChannelDesc, ChannelKind, InputKind};
+use crate::ffb::{EffectHandle, EffectOp, FfbEffect};
+use crate::metadata::DeviceMeta;
+use crate::output::OutputCommand;
+use crate::Result;
+
+/// `(view, local index)` for one parent channel.
+type Route = (usize, u16);
+
+/// The physical device plus per-view pending events.
+struct Shared {
+    inner: Box<dyn Device>,
+    queues: Vec<Vec<InputKind>>,
+    /// Per-view initial state, read from the device by the first view that asks.
+    initial: Option<Vec<Vec<InputKind>>>,
+    axes: HashMap<u16, Route>,
+    buttons: HashMap<u16, Route>,
+    hats: HashMap<u16, Route>,
+}
+
+impl Shared {
+    /// Poll the physical device and distribute events to view queues.
+    fn pump(&mut self) -> Result<()> {
+        let events = self.inner.try_poll()?;
+        let mut queues = std::mem::take(&mut self.queues);
+        self.route(events, &mut queues);
+        self.queues = queues;
+        Ok(())
+    }
+
+    /// Initial state of `view`, reading the device's once for all views.
+    fn initial_state(&mut self, view: usize) -> Vec<InputKind> {
+        if self.initial.is_none() {
+            let events = self.inner.initial_state();
+            let mut initial = vec![Vec::new(); self.queues.len()];
+            self.route(events, &mut initial);
+            self.initial = Some(initial);
+        }
+        self.initial
+            .as_mut()
+            .map(|initial| std::mem::take(&mut initial[view]))
+            .unwrap_or_default()
+    }
+
+    /// Renumber parent events and append them to the owning view's queue.
+    fn route(&self, events: Vec<InputKind>, queues: &mut [Vec<InputKind>]) {
+        for ev in events {
+            let (view, ev) = match ev {
+                InputKind::AxisMoved { axis, value } => match self.axes.get(&axis) {
+                    Some(&(v, axis)) => (v, InputKind::AxisMoved { axis, value }),
+                    None => continue,
+                },
+                InputKind::RelativeMoved { axis, delta } => match self.axes.get(&axis) {
+                    Some(&(v, axis)) => (v, InputKind::RelativeMoved { axis, delta }),
+                    None => continue,
+                },
+                InputKind::ButtonPressed { button } => match self.buttons.get(&button) {
+                    Some(&(v, button)) => (v, InputKind::ButtonPressed { button }),
+                    None => continue,
+                },
+                InputKind::ButtonReleased { button } => match self.buttons.get(&button) {
+                    Some(&(v, button)) => (v, InputKind::ButtonReleased { button }),
+                    None => continue,
+                },
+                InputKind::HatChanged { hat, value } => match self.hats.get(&hat) {
+                    Some(&(v, hat)) => (v, InputKind::HatChanged { hat, value }),
+                    None => continue,
+                },
+                // Events without a channel index belong to the first view.
+                other => (0, other),
+            };
+            queues[view].push(ev);
+        }
+    }
+}
+
+/// One link collection of a shared physical device, exposed as its own [`Device`].
+pub struct CollectionView {
+    shared: Rc<RefCell<Shared>>,
+    view: usize,
+    id: String,
+    name: String,
+    meta: DeviceMeta,
+    descs: Vec<ChannelDesc>,
+}
+
+impl Device for CollectionView {
+    fn poll(&mut self) -> Vec<InputKind> {
+        self.try_poll().unwrap_or_default()
+    }
+    fn try_poll(&mut self) -> Result<Vec<InputKind>> {
+        let mut shared = self.shared.borrow_mut();
+        shared.pump()?;
+        Ok(std::mem::take(&mut shared.queues[self.view]))
+    }
+    fn initial_state(&mut self) -> Vec<InputKind> {
+        self.shared.borrow_mut().initial_state(self.view)
+    }
+    fn is_connected(&self) -> bool {
+        self.shared.borrow().inner.is_connected()
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn id(&self) -> &str {
+        &self.id
+    }
+    fn metadata(&self) -> DeviceMeta {
+        self.meta.clone()
+    }
+    fn describe(&self) -> Vec<ChannelDesc> {
+        self.descs.clone()
+    }
+    fn report_id_diagnostics(&self) -> Option<ReportIdDiagnostics> {
+        self.shared.borrow().inner.report_id_diagnostics()
+    }
+    fn battery(&self) -> Option<BatteryStatus> {
+        self.shared.borrow().inner.battery()
+    }
+    fn fixed_player_index(&self) -> Option<u8> {
+        self.shared.borrow().inner.fixed_player_index()
+    }
+    fn report_time(&self) -> Option<Instant> {
+        self.shared.borrow().inner.report_time()
+    }
+    fn set_rumble(&mut self, low: f32, high: f32) -> Result<()> {
+        self.shared.borrow_mut().inner.set_rumble(low, high)
+    }
+    fn send_output(&mut self, cmd: &OutputCommand) -> Result<()> {
+        self.shared.borrow_mut().inner.send_output(cmd)
+    }
+    fn upload_effect(&mut self, effect: &FfbEffect) -> Result<EffectHandle> {
+        self.shared.borrow_mut().inner.upload_effect(effect)
+    }
+    fn play_effect(&mut self, handle: EffectHandle, op: EffectOp) -> Result<()> {
+        self.shared.borrow_mut().inner.play_effect(handle, op)
+    }
+    fn capture_reports(&mut self, path: Option<&Path>) -> Result<()> {
+        self.shared.borrow_mut().inner.capture_reports(path)
+    }
+}
+
+/// Split `device` into one [`CollectionView`] per link collection.
+///
+/// Devices whose channels all live in one collection (or carry no collection info) are
+/// returned unchanged as a single entry.
+pub fn split_by_collection(device: Box<dyn Device>) -> Vec<Box<dyn Device>> {
+    let descs = device.describe();
+    let mut collections: Vec<u16> = descs.iter().filter_map(|d| d.collection).collect();
+    collections.sort_unstable();
+    collections.dedup();
+    if collections.len() < 2 {
+        return vec![device];
+    }
+
+    let mut axes = HashMap::new();
+    let mut buttons = HashMap::new();
+    let mut hats = HashMap::new();
+    let mut view_descs: Vec<Vec<ChannelDesc>> = vec![Vec::new(); collections.len()];
+
+    for d in &descs {
+        // Channels without collection info stay with the first view.
+        let view = d
+            .collection
+            .and_then(|c| collections.iter().position(|&x| x == c))
+            .unwrap_or(0);
+        let (routes, kind) = match d.kind {
+            ChannelKind::Axis => (&mut axes, ChannelKind::Axis),
+            ChannelKind::Button => (&mut buttons, ChannelKind::Button),
+            ChannelKind::Hat => (&mut hats, ChannelKind::Hat),
+        };
+        let local = view_descs[view].iter().filter(|x| x.kind == kind).count() as u16;
+        routes.insert(d.idx, (view, local));
+        view_descs[view].push(ChannelDesc {
+            idx: local,
+            ..d.clone()
+        });
+    }
+
+    let parent_id = device.id().to_string();
+    let parent_name = device.name().to_string();
+    let meta = device.metadata();
+    let shared = Rc::new(RefCell::new(Shared {
+        inner: device,
+        queues: vec![Vec::new(); collections.len()],
+        initial: None,
+        axes,
+        buttons,
+        hats,
+    }));
+
+    collections
+        .iter()
+        .zip(view_descs)
+        .enumerate()
+        .map(|(view, (&collection, descs))| {
+            let label = descs
+                .iter()
+                .find_map(|d| d.collection_name.clone())
+                .unwrap_or_else(|| format!("Collection {collection}"));
+            Box::new(CollectionView {
+                shared: Rc::clone(&shared),
+                view,
+                id: format!("{parent_id}#{collection}"),
+                name: format!("{parent_name} ({label})"),
+                meta: meta.clone(),
+                descs,
+            }) as Box<dyn Device>
+        })
+        .collect()
+}
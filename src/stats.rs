@@ -0,0 +1,219 @@
+//! Per-channel statistics (range seen, histogram, reversals).
+//!
+//! A [`StatsCollector`] watches the event stream and keeps, per device channel:
+//! - axes: minimum/maximum value seen, a histogram over `[-1, 1]`, and the number of
+//!   direction reversals (with a small hysteresis so sensor noise does not count);
+//! - buttons: press count;
+//! - hats: change count.
+//!
+//! Useful for hardware reviews ("does the throttle ever reach 100 %?"), bug reports, and
+//! judging calibration quality (a noisy pot shows many reversals while at rest; a worn one
+//! leaves gaps in the histogram). Enable it on a [`Manager`](crate::manager::Manager) with
+//! [`enable_channel_stats`](crate::manager::Manager::enable_channel_stats), or feed events
+//! yourself with [`StatsCollector::observe`].
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::event::InputKind;
+
+/// Default histogram bin count.
+pub const DEFAULT_BINS: usize = 32;
+
+/// Default movement (in normalized units) that must be exceeded before a direction change
+/// counts as a reversal.
+pub const DEFAULT_REVERSAL_HYSTERESIS: f32 = 0.02;
+
+/// Statistics for one axis.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AxisStats {
+    /// Smallest value seen.
+    pub min: f32,
+    /// Largest value seen.
+    pub max: f32,
+    /// Number of samples.
+    pub samples: u64,
+    /// Sample counts per bin; bin `i` covers `[-1 + i·w, -1 + (i+1)·w)` with `w = 2 / bins`.
+    /// Values outside `[-1, 1]` are counted in the outermost bins.
+    pub histogram: Vec<u64>,
+    /// Direction changes by more than the hysteresis.
+    pub reversals: u64,
+    /// Last value seen.
+    pub last: f32,
+    /// Extreme of the current movement, and its direction (`1` up, `-1` down, `0` unknown).
+    turn: f32,
+    direction: i8,
+}
+
+impl AxisStats {
+    fn new(bins: usize, value: f32) -> Self {
+        Self {
+            min: value,
+            max: value,
+            samples: 0,
+            histogram: vec![0; bins.max(1)],
+            reversals: 0,
+            last: value,
+            turn: value,
+            direction: 0,
+        }
+    }
+
+    fn observe(&mut self, value: f32, hysteresis: f32) {
+        if !value.is_finite() {
+            return;
+        }
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.samples += 1;
+        let bins = self.histogram.len();
+        let bin = (((value + 1.0) * 0.5) * bins as f32).floor();
+        self.histogram[(bin.max(0.0) as usize).min(bins - 1)] += 1;
+
+        // Track the running extreme in the current direction; a move back by more than the
+        // hysteresis from that extreme is a reversal.
+        match self.direction {
+            0 => {
+                if (value - self.turn).abs() > hysteresis {
+                    self.direction = if value > self.turn { 1 } else { -1 };
+                    self.turn = value;
+                }
+            }
+            d => {
+                let d = d as f32;
+                if (value - self.turn) * d >= 0.0 {
+                    self.turn = value;
+                } else if (self.turn - value) * d > hysteresis {
+                    self.reversals += 1;
+                    self.direction = -self.direction;
+                    self.turn = value;
+                }
+            }
+        }
+        self.last = value;
+    }
+
+    /// Observed range `max - min`.
+    pub fn span(&self) -> f32 {
+        self.max - self.min
+    }
+
+    /// Fraction of histogram bins between the lowest and highest occupied bin that never
+    /// received a sample (`0.0` = smooth coverage). Gaps often point at worn or dirty
+    /// potentiometers.
+    pub fn gap_ratio(&self) -> f32 {
+        let first = self.histogram.iter().position(|&c| c > 0);
+        let last = self.histogram.iter().rposition(|&c| c > 0);
+        match (first, last) {
+            (Some(a), Some(b)) if b > a => {
+                let empty = self.histogram[a..=b].iter().filter(|&&c| c == 0).count();
+                empty as f32 / (b - a + 1) as f32
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+/// Statistics for one device.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DeviceStats {
+    /// Per axis index.
+    pub axes: BTreeMap<u16, AxisStats>,
+    /// Press count per button index.
+    pub button_presses: BTreeMap<u16, u64>,
+    /// Change count per hat index.
+    pub hat_changes: BTreeMap<u16, u64>,
+}
+
+/// Collects [`DeviceStats`] from input events (see module docs).
+#[derive(Clone, Debug)]
+pub struct StatsCollector {
+    bins: usize,
+    hysteresis: f32,
+    devices: HashMap<String, DeviceStats>,
+}
+
+impl Default for StatsCollector {
+    fn default() -> Self {
+        Self::new(DEFAULT_BINS)
+    }
+}
+
+impl StatsCollector {
+    /// Collector with `bins` histogram bins per axis.
+    pub fn new(bins: usize) -> Self {
+        Self {
+            bins: bins.max(1),
+            hysteresis: DEFAULT_REVERSAL_HYSTERESIS,
+            devices: HashMap::new(),
+        }
+    }
+
+    /// Builder: reversal hysteresis in normalized units.
+    pub fn reversal_hysteresis(mut self, hysteresis: f32) -> Self {
+        self.hysteresis = hysteresis.max(0.0);
+        self
+    }
+
+    /// Feed one event from `device_id`.
+    pub fn observe(&mut self, device_id: &str, ev: &InputKind) {
+        let relevant = matches!(
+            ev,
+            InputKind::AxisMoved { .. }
+                | InputKind::ButtonPressed { .. }
+                | InputKind::HatChanged { .. }
+        );
+        if !relevant {
+            return;
+        }
+        let dev = match self.devices.get_mut(device_id) {
+            Some(d) => d,
+            None => self.devices.entry(device_id.to_string()).or_default(),
+        };
+        match *ev {
+            InputKind::AxisMoved { axis, value } => dev
+                .axes
+                .entry(axis)
+                .or_insert_with(|| AxisStats::new(self.bins, value))
+                .observe(value, self.hysteresis),
+            InputKind::ButtonPressed { button } => {
+                *dev.button_presses.entry(button).or_default() += 1;
+            }
+            InputKind::HatChanged { hat, .. } => {
+                *dev.hat_changes.entry(hat).or_default() += 1;
+            }
+            _ => {}
+        }
+    }
+
+    /// Feed a batch of `(device_id, event)` pairs.
+    pub fn observe_all<'a>(&mut self, events: impl IntoIterator<Item = &'a (String, InputKind)>) {
+        for (id, ev) in events {
+            self.observe(id, ev);
+        }
+    }
+
+    /// Statistics for one device.
+    pub fn device(&self, device_id: &str) -> Option<&DeviceStats> {
+        self.devices.get(device_id)
+    }
+
+    /// Statistics for one axis.
+    pub fn axis(&self, device_id: &str, axis: u16) -> Option<&AxisStats> {
+        self.devices.get(device_id)?.axes.get(&axis)
+    }
+
+    /// All devices seen so far.
+    pub fn devices(&self) -> impl Iterator<Item = (&str, &DeviceStats)> {
+        self.devices.iter().map(|(id, s)| (id.as_str(), s))
+    }
+
+    /// Forget everything collected.
+    pub fn reset(&mut self) {
+        self.devices.clear();
+    }
+
+    /// Forget one device.
+    pub fn reset_device(&mut self, device_id: &str) {
+        self.devices.remove(device_id);
+    }
+}
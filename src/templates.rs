@@ -0,0 +1,140 @@
+//! Device template registry.
+//!
+//! A [`TemplateRegistry`] maps known VID/PIDs to [`DeviceTemplate`]s: a picture of the
+//! device, where each physical control sits, and the canonical name of each control. The
+//! [SVG generator](crate::controlmap) draws bindings with it; UIs can use it to show a
+//! device's layout or to label channels with the names printed on the hardware.
+//!
+//! Registries are plain TOML and can be loaded (and merged) at runtime:
+//! ```toml
+//! [[template]]
+//! name = "Warthog stick"
+//! width = 300
+//! height = 440
+//! image = "warthog_stick.png"
+//!
+//! [[template.controls]]
+//! controls = ["UP_0009_U_0001", "btn0"]
+//! name = "Trigger (first stage)"
+//! x = 96
+//! y = 150
+//!
+//! [[device]]
+//! vid = 0x044F
+//! pid = 0x0402
+//! template = "Warthog stick"
+//!
+//! [[device]]                        # no `pid`: every product of the vendor
+//! vid = 0x231D
+//! template = "Flight stick"
+//! ```
+//!
+//! [`TemplateRegistry::builtin`] holds the built-in `"Gamepad"` and `"Flight stick"`
+//! templates, which [`for_device`](TemplateRegistry::for_device) also falls back to for
+//! unlisted gamepads and joysticks.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::controlmap::DeviceTemplate;
+use crate::metadata::{DeviceMeta, DeviceRole};
+use crate::{Error, Result};
+
+/// Name of the built-in gamepad template ([`DeviceTemplate::gamepad`]).
+pub const GAMEPAD_TEMPLATE: &str = "Gamepad";
+/// Name of the built-in flight stick template ([`DeviceTemplate::flight_stick`]).
+pub const FLIGHT_STICK_TEMPLATE: &str = "Flight stick";
+
+/// Assigns a template to a VID (and optionally PID).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateEntry {
+    pub vid: u16,
+    /// Product id; `None` matches every product of the vendor.
+    #[serde(default)]
+    pub pid: Option<u16>,
+    /// Name of the template ([`DeviceTemplate::name`]).
+    pub template: String,
+}
+
+/// Templates and the devices they belong to (see module docs).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TemplateRegistry {
+    #[serde(default, rename = "template")]
+    pub templates: Vec<DeviceTemplate>,
+    #[serde(default, rename = "device")]
+    pub devices: Vec<TemplateEntry>,
+}
+
+impl TemplateRegistry {
+    /// Registry with the built-in templates and no device entries.
+    pub fn builtin() -> Self {
+        Self {
+            templates: vec![DeviceTemplate::gamepad(), DeviceTemplate::flight_stick()],
+            devices: Vec::new(),
+        }
+    }
+
+    /// Parse a registry from TOML text.
+    pub fn from_toml_str(s: &str) -> Result<Self> {
+        toml::from_str(s).map_err(|e| Error::Other(format!("invalid template registry: {e}")))
+    }
+
+    /// Load a registry from a TOML file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Other(format!("read {}: {e}", path.display())))?;
+        Self::from_toml_str(&text)
+    }
+
+    /// Merge another registry into this one (templates and entries from `other` take
+    /// precedence).
+    pub fn merge(&mut self, other: TemplateRegistry) {
+        let mut templates = other.templates;
+        templates.append(&mut self.templates);
+        self.templates = templates;
+        let mut devices = other.devices;
+        devices.append(&mut self.devices);
+        self.devices = devices;
+    }
+
+    /// Template by name.
+    pub fn template(&self, name: &str) -> Option<&DeviceTemplate> {
+        self.templates.iter().find(|t| t.name == name)
+    }
+
+    /// Template for a VID/PID: an exact entry, else a vendor-wide one.
+    pub fn for_ids(&self, vid: u16, pid: u16) -> Option<&DeviceTemplate> {
+        let exact = self
+            .devices
+            .iter()
+            .find(|e| e.vid == vid && e.pid == Some(pid));
+        let vendor = || {
+            self.devices
+                .iter()
+                .find(|e| e.vid == vid && e.pid.is_none())
+        };
+        self.template(&exact.or_else(vendor)?.template)
+    }
+
+    /// Template for a managed device: by VID/PID, else the built-in template for its
+    /// [`role`](DeviceMeta::role) (gamepads, joysticks) if this registry has it.
+    pub fn for_device(&self, meta: &DeviceMeta) -> Option<&DeviceTemplate> {
+        if let (Some(vid), Some(pid)) = (meta.vid, meta.pid) {
+            if let Some(t) = self.for_ids(vid, pid) {
+                return Some(t);
+            }
+        }
+        match meta.role() {
+            DeviceRole::Gamepad => self.template(GAMEPAD_TEMPLATE),
+            DeviceRole::Joystick => self.template(FLIGHT_STICK_TEMPLATE),
+            _ => None,
+        }
+    }
+
+    /// Canonical name of channel `channel` on a device, if its template names it.
+    pub fn canonical_name(&self, meta: &DeviceMeta, channel: &str) -> Option<&str> {
+        self.for_device(meta)?.anchor(channel)?.name.as_deref()
+    }
+}
@@ -0,0 +1,131 @@
+//! 6-DOF "twist" output for teleoperation.
+//!
+//! Robotics stacks usually command a base or arm with a velocity twist: linear `x/y/z` plus
+//! angular `x/y/z` (ROS `geometry_msgs/Twist`). [`TwistMapping`] builds one from scalar
+//! actions of a resolved [`BindingOutput`], with per-axis deadband and scaling:
+//!
+//! ```
+//! use stickup::binding::BindingOutput;
+//! use stickup::twist::{TwistAxis, TwistMapping};
+//!
+//! let mapping = TwistMapping {
+//!     linear: [Some(TwistAxis::new("drive").scale(0.5)), None, None],
+//!     angular: [None, None, Some(TwistAxis::new("turn").deadband(0.1))],
+//! };
+//! let mut out = BindingOutput::default();
+//! out.axis.insert("drive".into(), 1.0);
+//! let twist = mapping.twist(&out);
+//! assert_eq!(twist.linear, [0.5, 0.0, 0.0]);
+//! assert_eq!(twist.angular, [0.0, 0.0, 0.0]);
+//! ```
+//!
+//! Actions missing from the output count as `0.0`. Scales carry physical units (m/s, rad/s);
+//! with the default scale of `1.0` components stay in `[-1, 1]`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::binding::BindingOutput;
+
+/// Linear and angular velocity, each `[x, y, z]`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Twist {
+    /// Linear components.
+    pub linear: [f32; 3],
+    /// Angular components.
+    pub angular: [f32; 3],
+}
+
+impl Twist {
+    /// All components zero (stop).
+    pub fn is_zero(&self) -> bool {
+        self.linear
+            .iter()
+            .chain(self.angular.iter())
+            .all(|v| *v == 0.0)
+    }
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+/// Source of one twist component: a scalar action plus deadband and scale.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TwistAxis {
+    /// Action name (key under [`BindingOutput::axis`]).
+    pub action: String,
+    /// Values with `|x| <= deadband` become `0`; others are rescaled to keep continuity.
+    #[serde(default)]
+    pub deadband: f32,
+    /// Output at full deflection (negative to invert; default `1.0`).
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+}
+
+impl TwistAxis {
+    /// Read `action` with no deadband and unit scale.
+    pub fn new(action: impl Into<String>) -> Self {
+        Self {
+            action: action.into(),
+            deadband: 0.0,
+            scale: default_scale(),
+        }
+    }
+
+    /// Builder: set the deadband (`0.0..0.95`).
+    pub fn deadband(mut self, deadband: f32) -> Self {
+        self.deadband = deadband;
+        self
+    }
+
+    /// Builder: set the full-scale output.
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Component value for a normalized action value `x`.
+    pub fn apply(&self, x: f32) -> f32 {
+        let db = self.deadband.clamp(0.0, 0.95);
+        let x = x.clamp(-1.0, 1.0);
+        let v = if x.abs() <= db {
+            0.0
+        } else {
+            x.signum() * (x.abs() - db) / (1.0 - db)
+        };
+        v * self.scale
+    }
+}
+
+/// Which actions feed which twist components (`None` = always `0`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TwistMapping {
+    /// Sources for linear `x/y/z`.
+    #[serde(default)]
+    pub linear: [Option<TwistAxis>; 3],
+    /// Sources for angular `x/y/z`.
+    #[serde(default)]
+    pub angular: [Option<TwistAxis>; 3],
+}
+
+impl TwistMapping {
+    /// Build a twist from resolved actions.
+    pub fn twist(&self, out: &BindingOutput) -> Twist {
+        let read = |axis: &Option<TwistAxis>| match axis {
+            Some(a) => a.apply(out.axis.get(&a.action).copied().unwrap_or(0.0)),
+            None => 0.0,
+        };
+        Twist {
+            linear: [
+                read(&self.linear[0]),
+                read(&self.linear[1]),
+                read(&self.linear[2]),
+            ],
+            angular: [
+                read(&self.angular[0]),
+                read(&self.angular[1]),
+                read(&self.angular[2]),
+            ],
+        }
+    }
+}
@@ -0,0 +1,531 @@
+//! Static checks for binding profiles.
+//!
+//! [`BindingProfile::validate`] and [`BindingProfile::validate_devices`] look for mistakes a
+//! profile editor should point out before the profile is used:
+//! - two rules in the same scope writing the same action,
+//! - rules or layer modifiers reading channels a device doesn't expose (or of another kind),
+//! - chords whose controls overlap so that one fires together with the other,
+//! - layers and layered bindings that can never take effect.
+//!
+//! Each finding is a [`ProfileIssue`] with a [`Severity`] and a `Display` message for UIs.
+//!
+//! [`BindingProfile::lint`] runs the same checks, treats the profile's gate
+//! [`channels`](BindingProfile::channels) as controls of their devices, and adds style
+//! warnings such as deadzones that swallow most of an axis. The `lint_profile` example is a
+//! command-line front end:
+//!
+//! ```text
+//! cargo run --example lint_profile -- my_profile.toml
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::binding::{AxisTransform, BindingProfile, BindingRule, ControlPath, ControlType};
+use crate::event::{ChannelDesc, ChannelKind};
+use crate::gate::GATE_INDEX_BASE;
+
+/// Deadzones of at least this fraction of travel are reported by [`BindingProfile::lint`].
+pub const LINT_DEADZONE_LIMIT: f32 = 0.5;
+
+/// How serious a [`ProfileIssue`] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Probably unintended, but the profile works.
+    Warning,
+    /// Part of the profile can never work as written.
+    Error,
+}
+
+/// Where a rule is declared.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RuleScope {
+    /// [`BindingProfile::bindings`].
+    Base,
+    /// A [`BindingLayer`](crate::binding::BindingLayer), by name.
+    Layer(String),
+    /// An [`ActionSet`](crate::binding::ActionSet), by name.
+    ActionSet(String),
+}
+
+/// A rule: its scope plus its index within that scope's `bindings`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RuleRef {
+    pub scope: RuleScope,
+    pub index: usize,
+}
+
+impl fmt::Display for RuleRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.scope {
+            RuleScope::Base => write!(f, "binding #{}", self.index),
+            RuleScope::Layer(name) => write!(f, "layer '{name}' binding #{}", self.index),
+            RuleScope::ActionSet(name) => write!(f, "action set '{name}' binding #{}", self.index),
+        }
+    }
+}
+
+/// Why a layer can never become active.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UnreachableReason {
+    /// The layer has no modifiers.
+    NoModifiers,
+    /// An earlier layer has the same modifiers and wins ties.
+    SameModifiersAs(String),
+}
+
+/// A problem found by [`BindingProfile::validate`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProfileIssue {
+    /// Several rules in one scope write the same action.
+    DuplicateAction { action: String, rules: Vec<RuleRef> },
+    /// A rule reads a control the device doesn't expose.
+    UnknownControl {
+        rule: RuleRef,
+        device_id: String,
+        control_id: String,
+    },
+    /// A rule reads a control as a different kind than the device exposes.
+    ControlKindMismatch {
+        rule: RuleRef,
+        device_id: String,
+        control_id: String,
+        expected: ControlType,
+        found: ChannelKind,
+    },
+    /// A layer modifier reads a control the device doesn't expose.
+    UnknownModifier {
+        layer: String,
+        device_id: String,
+        control_id: String,
+    },
+    /// Two chords on the same device share all of `first`'s controls, so holding `second`
+    /// also fires `first` (identical sets fire together).
+    OverlappingChords { first: RuleRef, second: RuleRef },
+    /// A layer can never become active; none of its bindings take effect.
+    UnreachableLayer {
+        layer: String,
+        reason: UnreachableReason,
+    },
+    /// A layered binding reads one of its layer's own modifiers, which is always held while
+    /// the layer is active.
+    BindingReadsModifier { rule: RuleRef, control_id: String },
+    /// A deadzone is negative or at least [`LINT_DEADZONE_LIMIT`] (lint only).
+    ExtremeDeadzone { rule: RuleRef, deadzone: f32 },
+}
+
+impl ProfileIssue {
+    /// Severity of this issue.
+    pub fn severity(&self) -> Severity {
+        match self {
+            ProfileIssue::UnknownControl { .. }
+            | ProfileIssue::ControlKindMismatch { .. }
+            | ProfileIssue::UnknownModifier { .. }
+            | ProfileIssue::UnreachableLayer { .. } => Severity::Error,
+            ProfileIssue::DuplicateAction { .. }
+            | ProfileIssue::OverlappingChords { .. }
+            | ProfileIssue::BindingReadsModifier { .. }
+            | ProfileIssue::ExtremeDeadzone { .. } => Severity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for ProfileIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileIssue::DuplicateAction { action, rules } => {
+                write!(f, "action '{action}' is written by ")?;
+                for (i, r) in rules.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{r}")?;
+                }
+                Ok(())
+            }
+            ProfileIssue::UnknownControl {
+                rule,
+                device_id,
+                control_id,
+            } => write!(
+                f,
+                "{rule}: device '{device_id}' has no control '{control_id}'"
+            ),
+            ProfileIssue::ControlKindMismatch {
+                rule,
+                device_id,
+                control_id,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{rule}: '{control_id}' on '{device_id}' is {found:?}, not {expected:?}"
+            ),
+            ProfileIssue::UnknownModifier {
+                layer,
+                device_id,
+                control_id,
+            } => write!(
+                f,
+                "layer '{layer}': device '{device_id}' has no modifier control '{control_id}'"
+            ),
+            ProfileIssue::OverlappingChords { first, second } => {
+                write!(f, "{second} also triggers the chord in {first}")
+            }
+            ProfileIssue::UnreachableLayer { layer, reason } => match reason {
+                UnreachableReason::NoModifiers => {
+                    write!(f, "layer '{layer}' has no modifiers and never activates")
+                }
+                UnreachableReason::SameModifiersAs(other) => write!(
+                    f,
+                    "layer '{layer}' has the same modifiers as '{other}' and never activates"
+                ),
+            },
+            ProfileIssue::BindingReadsModifier { rule, control_id } => write!(
+                f,
+                "{rule} reads '{control_id}', which is held whenever the layer is active"
+            ),
+            ProfileIssue::ExtremeDeadzone { rule, deadzone } if *deadzone < 0.0 => {
+                write!(f, "{rule}: deadzone {deadzone} is negative")
+            }
+            ProfileIssue::ExtremeDeadzone { rule, deadzone } => write!(
+                f,
+                "{rule}: deadzone {deadzone} ignores most of the axis travel"
+            ),
+        }
+    }
+}
+
+/// Output map an action name lands in (duplicates only conflict within one map).
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum OutputMap {
+    Axis,
+    Button,
+    Vec2,
+}
+
+/// Actions a rule writes, with the output map each lands in.
+fn rule_outputs(rule: &BindingRule) -> Vec<(OutputMap, &str)> {
+    match rule {
+        BindingRule::Axis1d { action, .. } | BindingRule::ButtonAxis { action, .. } => {
+            vec![(OutputMap::Axis, action)]
+        }
+        BindingRule::Button { action, .. }
+        | BindingRule::Chord { action, .. }
+        | BindingRule::Dwell { action, .. }
+        | BindingRule::Interlock { action, .. } => vec![(OutputMap::Button, action)],
+        BindingRule::Axis2d { action, .. } | BindingRule::HatAxes { action, .. } => {
+            vec![(OutputMap::Vec2, action)]
+        }
+        // Several directions may share a name on purpose; count each name once.
+        BindingRule::HatActions { actions, .. } => {
+            let mut names: Vec<&str> = actions
+                .iter()
+                .map(String::as_str)
+                .filter(|a| !a.is_empty())
+                .collect();
+            names.sort_unstable();
+            names.dedup();
+            names.into_iter().map(|a| (OutputMap::Button, a)).collect()
+        }
+        // Events carry the gesture kind; several gesture rules may share an action.
+        BindingRule::Gesture { .. } => Vec::new(),
+    }
+}
+
+fn kind_matches(expected: &ControlType, found: ChannelKind) -> bool {
+    matches!(
+        (expected, found),
+        (ControlType::Axis, ChannelKind::Axis)
+            | (ControlType::Button, ChannelKind::Button)
+            | (ControlType::Hat, ChannelKind::Hat)
+    )
+}
+
+/// Check one control path against a device's channels.
+fn check_control(
+    rule: &RuleRef,
+    device_id: &str,
+    control: &ControlPath,
+    channels: &[ChannelDesc],
+    issues: &mut Vec<ProfileIssue>,
+) {
+    let found: Vec<ChannelKind> = channels
+        .iter()
+        .filter(|c| c.state_name() == control.control_id)
+        .map(|c| c.kind)
+        .collect();
+    if found.is_empty() {
+        issues.push(ProfileIssue::UnknownControl {
+            rule: rule.clone(),
+            device_id: device_id.to_string(),
+            control_id: control.control_id.clone(),
+        });
+    } else if !found
+        .iter()
+        .any(|k| kind_matches(&control.control_type, *k))
+    {
+        issues.push(ProfileIssue::ControlKindMismatch {
+            rule: rule.clone(),
+            device_id: device_id.to_string(),
+            control_id: control.control_id.clone(),
+            expected: control.control_type.clone(),
+            found: found[0],
+        });
+    }
+}
+
+impl BindingProfile {
+    /// Validate the profile assuming every device it references exposes `channels`
+    /// (the usual case for single-device profiles).
+    pub fn validate(&self, channels: &[ChannelDesc]) -> Vec<ProfileIssue> {
+        self.check(&|_| Some(channels))
+    }
+
+    /// Validate the profile against per-device channel lists (e.g. from
+    /// [`Manager::channels`](crate::manager::Manager::channels)).
+    ///
+    /// Devices missing from `devices` are skipped by the channel checks; the structural
+    /// checks (duplicates, chords, layers) always run.
+    pub fn validate_devices(
+        &self,
+        devices: &HashMap<String, Vec<ChannelDesc>>,
+    ) -> Vec<ProfileIssue> {
+        self.check(&|id| devices.get(id).map(Vec::as_slice))
+    }
+
+    /// Validate against per-device channel lists like
+    /// [`validate_devices`](Self::validate_devices), plus lint checks:
+    /// - the profile's gate [`channels`](BindingProfile::channels) count as controls of their
+    ///   devices, so rules bound to them are not reported as unknown;
+    /// - deadzones that are negative or at least [`LINT_DEADZONE_LIMIT`].
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use stickup::validate::ProfileIssue;
+    /// use stickup::BindingProfile;
+    ///
+    /// let profile = BindingProfile::from_str_auto(r#"
+    /// name = "demo"
+    /// [[bindings]]
+    /// kind = "axis1d"
+    /// device_id = "stick"
+    /// control = { control_id = "X", control_type = "Axis" }
+    /// action = "roll"
+    /// xform = { deadzone = 0.8 }
+    /// "#).unwrap();
+    /// let issues = profile.lint(&HashMap::new());
+    /// assert!(matches!(issues[..], [ProfileIssue::ExtremeDeadzone { .. }]));
+    /// ```
+    pub fn lint(&self, devices: &HashMap<String, Vec<ChannelDesc>>) -> Vec<ProfileIssue> {
+        let mut devices = devices.clone();
+        for (i, gate) in self.channels.iter().enumerate() {
+            if let Some(channels) = devices.get_mut(&gate.device_id) {
+                channels.push(ChannelDesc {
+                    kind: gate.kind.channel_kind(),
+                    idx: GATE_INDEX_BASE.saturating_add(i as u16),
+                    name: Some(gate.name.clone()),
+                    logical_min: if gate.kind.channel_kind() == ChannelKind::Axis {
+                        -1
+                    } else {
+                        0
+                    },
+                    logical_max: 1,
+                    usage_page: None,
+                    usage: None,
+                    unit: None,
+                    collection: None,
+                    collection_name: None,
+                });
+            }
+        }
+        let mut issues = self.validate_devices(&devices);
+
+        for (scope, rules) in &self.scopes() {
+            for (index, rule) in rules.iter().enumerate() {
+                let xforms: Vec<&AxisTransform> = match rule {
+                    BindingRule::Axis1d { xform, .. } => vec![xform],
+                    BindingRule::Axis2d {
+                        xform_x, xform_y, ..
+                    } => vec![xform_x, xform_y],
+                    _ => Vec::new(),
+                };
+                let mut deadzones: Vec<f32> = xforms.iter().map(|x| x.deadzone).collect();
+                if let BindingRule::Axis2d {
+                    radial_deadzone: true,
+                    radial_deadzone_size,
+                    ..
+                } = rule
+                {
+                    deadzones.push(*radial_deadzone_size);
+                }
+                if let Some(&deadzone) = deadzones
+                    .iter()
+                    .find(|d| **d < 0.0 || **d >= LINT_DEADZONE_LIMIT)
+                {
+                    issues.push(ProfileIssue::ExtremeDeadzone {
+                        rule: RuleRef {
+                            scope: scope.clone(),
+                            index,
+                        },
+                        deadzone,
+                    });
+                }
+            }
+        }
+        issues
+    }
+
+    /// Every rule list with its scope: base, then layers, then action sets.
+    fn scopes(&self) -> Vec<(RuleScope, &[BindingRule])> {
+        let mut scopes: Vec<(RuleScope, &[BindingRule])> = vec![(RuleScope::Base, &self.bindings)];
+        scopes.extend(
+            self.layers
+                .iter()
+                .map(|l| (RuleScope::Layer(l.name.clone()), l.bindings.as_slice())),
+        );
+        scopes.extend(
+            self.action_sets
+                .iter()
+                .map(|s| (RuleScope::ActionSet(s.name.clone()), s.bindings.as_slice())),
+        );
+        scopes
+    }
+
+    fn check<'a>(
+        &self,
+        channels_of: &dyn Fn(&str) -> Option<&'a [ChannelDesc]>,
+    ) -> Vec<ProfileIssue> {
+        let mut issues = Vec::new();
+
+        for (scope, rules) in &self.scopes() {
+            let at = |index| RuleRef {
+                scope: scope.clone(),
+                index,
+            };
+
+            // Duplicate actions within the scope (first-seen order).
+            let mut writers: Vec<((OutputMap, &str), Vec<RuleRef>)> = Vec::new();
+            for (i, rule) in rules.iter().enumerate() {
+                for key in rule_outputs(rule) {
+                    match writers.iter_mut().find(|(k, _)| *k == key) {
+                        Some((_, refs)) => refs.push(at(i)),
+                        None => writers.push((key, vec![at(i)])),
+                    }
+                }
+            }
+            for ((_, action), rules) in writers {
+                if rules.len() > 1 {
+                    issues.push(ProfileIssue::DuplicateAction {
+                        action: action.to_string(),
+                        rules,
+                    });
+                }
+            }
+
+            // Controls the devices don't expose.
+            for (i, rule) in rules.iter().enumerate() {
+                if let Some(channels) = channels_of(rule.device_id()) {
+                    for control in rule.controls() {
+                        check_control(&at(i), rule.device_id(), control, channels, &mut issues);
+                    }
+                }
+            }
+
+            // Chords whose controls are contained in another chord's.
+            let chords: Vec<(usize, &str, &Vec<ControlPath>)> = rules
+                .iter()
+                .enumerate()
+                .filter_map(|(i, r)| match r {
+                    BindingRule::Chord {
+                        device_id,
+                        controls,
+                        ..
+                    } if !controls.is_empty() => Some((i, device_id.as_str(), controls)),
+                    _ => None,
+                })
+                .collect();
+            for (a, &(ia, dev_a, ctl_a)) in chords.iter().enumerate() {
+                for &(ib, dev_b, ctl_b) in &chords[a + 1..] {
+                    if dev_a != dev_b {
+                        continue;
+                    }
+                    let a_in_b = ctl_a.iter().all(|c| ctl_b.contains(c));
+                    let b_in_a = ctl_b.iter().all(|c| ctl_a.contains(c));
+                    if a_in_b {
+                        issues.push(ProfileIssue::OverlappingChords {
+                            first: at(ia),
+                            second: at(ib),
+                        });
+                    } else if b_in_a {
+                        issues.push(ProfileIssue::OverlappingChords {
+                            first: at(ib),
+                            second: at(ia),
+                        });
+                    }
+                }
+            }
+        }
+
+        // Layers.
+        for (li, layer) in self.layers.iter().enumerate() {
+            for m in &layer.modifiers {
+                let Some(channels) = channels_of(&m.device_id) else {
+                    continue;
+                };
+                if !channels
+                    .iter()
+                    .any(|c| c.state_name() == m.control.control_id)
+                {
+                    issues.push(ProfileIssue::UnknownModifier {
+                        layer: layer.name.clone(),
+                        device_id: m.device_id.clone(),
+                        control_id: m.control.control_id.clone(),
+                    });
+                }
+            }
+
+            let unreachable = if layer.modifiers.is_empty() {
+                Some(UnreachableReason::NoModifiers)
+            } else {
+                self.layers[..li]
+                    .iter()
+                    .find(|earlier| {
+                        earlier.modifiers.len() == layer.modifiers.len()
+                            && layer
+                                .modifiers
+                                .iter()
+                                .all(|m| earlier.modifiers.contains(m))
+                    })
+                    .map(|earlier| UnreachableReason::SameModifiersAs(earlier.name.clone()))
+            };
+            if let Some(reason) = unreachable {
+                issues.push(ProfileIssue::UnreachableLayer {
+                    layer: layer.name.clone(),
+                    reason,
+                });
+                continue;
+            }
+
+            for (i, rule) in layer.bindings.iter().enumerate() {
+                for control in rule.controls() {
+                    let is_modifier = layer
+                        .modifiers
+                        .iter()
+                        .any(|m| m.device_id == rule.device_id() && m.control == *control);
+                    if is_modifier {
+                        issues.push(ProfileIssue::BindingReadsModifier {
+                            rule: RuleRef {
+                                scope: RuleScope::Layer(layer.name.clone()),
+                                index: i,
+                            },
+                            control_id: control.control_id.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        issues
+    }
+}
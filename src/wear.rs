@@ -0,0 +1,330 @@
+//! Wear diagnostics: actuation counts and rising bounce/noise per control.
+//!
+//! Where [`stats`](crate::stats) describes a session, a [`WearMonitor`] tracks controls over
+//! their lifetime and looks for the signatures of failing hardware:
+//! - **buttons**: total actuations, and *bounces* — a press arriving within
+//!   [`bounce_window`](WearConfig::bounce_window) of the previous release (worn or dirty
+//!   switches chatter);
+//! - **axes**: *jitter* — small direction flips (below
+//!   [`noise_amplitude`](WearConfig::noise_amplitude)) that a healthy pot or sensor at rest
+//!   does not produce.
+//!
+//! Rates are measured over fixed windows of actuations / samples. When a window closes with
+//! a rate above the configured threshold that has also grown against the control's baseline
+//! (its first window), a [`WearWarning`] is queued — "this switch is dying".
+//!
+//! The monitor is serializable, so sim pits and arcade cabinets can persist it between
+//! sessions and keep counting:
+//!
+//! ```no_run
+//! use stickup::wear::WearMonitor;
+//!
+//! # fn main() -> stickup::Result<()> {
+//! let mut mgr = stickup::Manager::discover()?;
+//! let mut wear = WearMonitor::default();
+//! loop {
+//!     for (id, ev) in mgr.poll_events_timed() {
+//!         wear.observe(&id, &ev);
+//!     }
+//!     for w in wear.take_warnings() {
+//!         eprintln!("{w}");
+//!     }
+//! #   break;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::event::{InputEvent, InputKind};
+
+/// Most window rates kept per control.
+const HISTORY_CAP: usize = 64;
+
+/// Thresholds for [`WearMonitor`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WearConfig {
+    /// A press this soon after the previous release counts as a bounce.
+    pub bounce_window: Duration,
+    /// Actuations per button measurement window.
+    pub button_window: u32,
+    /// Bounce rate (bounces / actuations) that triggers a warning.
+    pub bounce_warn: f32,
+    /// Direction flips smaller than this (normalized units) count as jitter.
+    pub noise_amplitude: f32,
+    /// Samples per axis measurement window.
+    pub axis_window: u32,
+    /// Jitter rate (flips / samples) that triggers a warning.
+    pub noise_warn: f32,
+    /// How much a rate must have grown over the baseline window to warn (`2.0` = doubled).
+    pub growth: f32,
+}
+
+impl Default for WearConfig {
+    fn default() -> Self {
+        Self {
+            bounce_window: Duration::from_millis(15),
+            button_window: 200,
+            bounce_warn: 0.05,
+            noise_amplitude: 0.02,
+            axis_window: 1000,
+            noise_warn: 0.2,
+            growth: 2.0,
+        }
+    }
+}
+
+/// Lifetime wear data for one button.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ButtonWear {
+    /// Total presses.
+    pub actuations: u64,
+    /// Total bounces.
+    pub bounces: u64,
+    /// Bounce rate of each completed window, oldest first (the first is the baseline).
+    pub history: Vec<f32>,
+    window_actuations: u32,
+    window_bounces: u32,
+    #[serde(skip)]
+    last_release: Option<Duration>,
+}
+
+/// Lifetime wear data for one axis.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AxisWear {
+    /// Total samples.
+    pub samples: u64,
+    /// Total jitter flips.
+    pub jitter: u64,
+    /// Jitter rate of each completed window, oldest first (the first is the baseline).
+    pub history: Vec<f32>,
+    window_samples: u32,
+    window_jitter: u32,
+    #[serde(skip)]
+    last: Option<(f32, f32)>,
+}
+
+/// Rate of the latest completed window and the baseline (first) window.
+fn trend(history: &[f32]) -> Option<(f32, f32)> {
+    Some((*history.last()?, *history.first()?))
+}
+
+impl ButtonWear {
+    /// `(latest, baseline)` bounce rates, once a window has completed.
+    pub fn trend(&self) -> Option<(f32, f32)> {
+        trend(&self.history)
+    }
+}
+
+impl AxisWear {
+    /// `(latest, baseline)` jitter rates, once a window has completed.
+    pub fn trend(&self) -> Option<(f32, f32)> {
+        trend(&self.history)
+    }
+}
+
+/// Wear data for one device.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceWear {
+    pub buttons: BTreeMap<u16, ButtonWear>,
+    pub axes: BTreeMap<u16, AxisWear>,
+}
+
+/// A control whose bounce/noise rate crossed its threshold and grew against its baseline.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WearWarning {
+    /// A switch is chattering.
+    ButtonBounce {
+        device: String,
+        button: u16,
+        rate: f32,
+        baseline: f32,
+    },
+    /// An axis is noisy.
+    AxisNoise {
+        device: String,
+        axis: u16,
+        rate: f32,
+        baseline: f32,
+    },
+}
+
+impl fmt::Display for WearWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ButtonBounce {
+                device,
+                button,
+                rate,
+                baseline,
+            } => write!(
+                f,
+                "{device}: button {button} bounces on {:.1}% of presses (was {:.1}%)",
+                rate * 100.0,
+                baseline * 100.0
+            ),
+            Self::AxisNoise {
+                device,
+                axis,
+                rate,
+                baseline,
+            } => write!(
+                f,
+                "{device}: axis {axis} jitters on {:.1}% of samples (was {:.1}%)",
+                rate * 100.0,
+                baseline * 100.0
+            ),
+        }
+    }
+}
+
+/// Tracks wear per control (see module docs).
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct WearMonitor {
+    #[serde(default)]
+    pub config: WearConfig,
+    #[serde(default)]
+    devices: HashMap<String, DeviceWear>,
+    #[serde(skip)]
+    warnings: Vec<WearWarning>,
+}
+
+/// Close a window with `rate`; returns the baseline if the rate warrants a warning.
+fn close_window(history: &mut Vec<f32>, rate: f32, warn: f32, growth: f32) -> Option<f32> {
+    let baseline = history.first().copied();
+    if history.len() == HISTORY_CAP {
+        // Keep the baseline; drop the oldest of the rest.
+        history.remove(1);
+    }
+    history.push(rate);
+    match baseline {
+        _ if rate < warn => None,
+        Some(b) if rate < b * growth => None,
+        Some(b) => Some(b),
+        None => Some(rate),
+    }
+}
+
+impl WearMonitor {
+    /// Monitor with custom thresholds.
+    pub fn new(config: WearConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Feed one timestamped event from `device_id`.
+    pub fn observe(&mut self, device_id: &str, ev: &InputEvent) {
+        self.observe_kind(device_id, &ev.kind, ev.timestamp);
+    }
+
+    /// Feed one event that happened at `timestamp` (any monotonic time base).
+    pub fn observe_kind(&mut self, device_id: &str, kind: &InputKind, timestamp: Duration) {
+        let relevant = matches!(
+            kind,
+            InputKind::AxisMoved { .. }
+                | InputKind::ButtonPressed { .. }
+                | InputKind::ButtonReleased { .. }
+        );
+        if !relevant {
+            return;
+        }
+        let cfg = &self.config;
+        let dev = match self.devices.get_mut(device_id) {
+            Some(d) => d,
+            None => self.devices.entry(device_id.to_string()).or_default(),
+        };
+        match *kind {
+            InputKind::ButtonPressed { button } => {
+                let w = dev.buttons.entry(button).or_default();
+                w.actuations += 1;
+                w.window_actuations += 1;
+                let bounced = w
+                    .last_release
+                    .is_some_and(|r| timestamp.saturating_sub(r) < cfg.bounce_window);
+                if bounced {
+                    w.bounces += 1;
+                    w.window_bounces += 1;
+                }
+                if w.window_actuations >= cfg.button_window.max(1) {
+                    let rate = w.window_bounces as f32 / w.window_actuations as f32;
+                    (w.window_actuations, w.window_bounces) = (0, 0);
+                    if let Some(baseline) =
+                        close_window(&mut w.history, rate, cfg.bounce_warn, cfg.growth)
+                    {
+                        self.warnings.push(WearWarning::ButtonBounce {
+                            device: device_id.to_string(),
+                            button,
+                            rate,
+                            baseline,
+                        });
+                    }
+                }
+            }
+            InputKind::ButtonReleased { button } => {
+                dev.buttons.entry(button).or_default().last_release = Some(timestamp);
+            }
+            InputKind::AxisMoved { axis, value } => {
+                if !value.is_finite() {
+                    return;
+                }
+                let w = dev.axes.entry(axis).or_default();
+                w.samples += 1;
+                w.window_samples += 1;
+                if let Some((last, last_delta)) = w.last {
+                    let delta = value - last;
+                    let flipped = delta * last_delta < 0.0;
+                    if flipped && delta.abs() < cfg.noise_amplitude {
+                        w.jitter += 1;
+                        w.window_jitter += 1;
+                    }
+                    w.last = Some((value, delta));
+                } else {
+                    w.last = Some((value, 0.0));
+                }
+                if w.window_samples >= cfg.axis_window.max(1) {
+                    let rate = w.window_jitter as f32 / w.window_samples as f32;
+                    (w.window_samples, w.window_jitter) = (0, 0);
+                    if let Some(baseline) =
+                        close_window(&mut w.history, rate, cfg.noise_warn, cfg.growth)
+                    {
+                        self.warnings.push(WearWarning::AxisNoise {
+                            device: device_id.to_string(),
+                            axis,
+                            rate,
+                            baseline,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Warnings raised since the last call.
+    pub fn take_warnings(&mut self) -> Vec<WearWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Wear data for one device.
+    pub fn device(&self, device_id: &str) -> Option<&DeviceWear> {
+        self.devices.get(device_id)
+    }
+
+    /// All devices seen so far.
+    pub fn devices(&self) -> impl Iterator<Item = (&str, &DeviceWear)> {
+        self.devices.iter().map(|(id, w)| (id.as_str(), w))
+    }
+
+    /// Forget one device (e.g. after replacing a switch).
+    pub fn reset_device(&mut self, device_id: &str) {
+        self.devices.remove(device_id);
+    }
+}
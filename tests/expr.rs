@@ -0,0 +1,24 @@
+//! Expression evaluation edge cases that the doc examples don't cover.
+
+use stickup::expr::Expr;
+
+#[test]
+fn nan_bounds_are_ignored() {
+    let clamp = Expr::parse("clamp(2, pow(-1, 0.5), 1)").unwrap();
+    assert_eq!(clamp.eval_once(|_| None), 1.0);
+    let clamp = Expr::parse("clamp(-2, -1, pow(-1, 0.5))").unwrap();
+    assert_eq!(clamp.eval_once(|_| None), -1.0);
+
+    // The first evaluation only seeds the slew state; the second applies the NaN rate.
+    let slew = Expr::parse("slew(x, pow(y, 0.5))").unwrap();
+    let mut state = slew.new_state();
+    let vars = |x: f32| {
+        move |name: &str| match name {
+            "x" => Some(x),
+            "y" => Some(-1.0),
+            _ => None,
+        }
+    };
+    assert_eq!(slew.eval(vars(0.0), &mut state, 0.01), 0.0);
+    assert_eq!(slew.eval(vars(1.0), &mut state, 0.01), 1.0);
+}
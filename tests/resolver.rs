@@ -0,0 +1,53 @@
+//! Time-dependent [`BindingResolver`] behavior, driven with synthetic states and instants.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use stickup::resolver::BindingResolver;
+use stickup::{BindingProfile, BindingRule, ControlPath, ControlType, DeviceState};
+
+fn button(id: &str) -> ControlPath {
+    ControlPath {
+        control_id: id.into(),
+        control_type: ControlType::Button,
+    }
+}
+
+fn held(buttons: &[&str]) -> HashMap<String, DeviceState> {
+    let mut st = DeviceState::default();
+    for b in buttons {
+        st.buttons.insert((*b).into(), true);
+    }
+    HashMap::from([("pad".to_string(), st)])
+}
+
+#[test]
+fn sequential_latch_times_out_from_release() {
+    let mut profile: BindingProfile = serde_json::from_str(r#"{"name":"latch"}"#).unwrap();
+    profile.bindings.push(BindingRule::Chord {
+        device_id: "pad".into(),
+        controls: vec![button("start"), button("back")],
+        action: "menu".into(),
+        window_ms: 200,
+    });
+    let mut resolver = BindingResolver::new(profile);
+    let timeout = Duration::from_secs(1);
+    resolver.set_sequential_chords(Some(timeout));
+
+    // Hold the modifier for three timeouts, release it, then press the chord key.
+    let t0 = Instant::now();
+    for step in 0..=3 {
+        let out = resolver.resolve(&held(&["start"]), t0 + timeout * step);
+        assert_eq!(out.buttons.get("menu"), Some(&false));
+    }
+    let released = t0 + timeout * 3 + Duration::from_millis(100);
+    resolver.resolve(&held(&[]), released);
+    let out = resolver.resolve(&held(&["back"]), released + timeout / 2);
+    assert_eq!(out.buttons.get("menu"), Some(&true));
+
+    // Latches still expire `timeout` after release.
+    resolver.resolve(&held(&["start"]), released + timeout * 2);
+    resolver.resolve(&held(&[]), released + timeout * 3);
+    let out = resolver.resolve(&held(&["back"]), released + timeout * 5);
+    assert_eq!(out.buttons.get("menu"), Some(&false));
+}